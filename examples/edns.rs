@@ -1,8 +1,8 @@
 use std::net::{IpAddr, Ipv4Addr};
+use std::sync::Arc;
 use std::time::Duration;
 use tokio::runtime::Runtime;
 use dns_rust::client::Client;
-use dns_rust::client::client_connection::ClientConnection;
 use dns_rust::client::udp_connection::ClientUDPConnection;
 use dns_rust::domain_name::DomainName;
 use dns_rust::message::rclass::Rclass;
@@ -17,7 +17,7 @@ fn main() {
 
     let addr = IpAddr::V4(Ipv4Addr::new(8, 8, 8, 8));
     let conn = ClientUDPConnection::new_default(addr, Duration::from_secs(10));
-    let mut client = Client::new(conn);
+    let mut client = Client::new(Arc::new(conn), Duration::from_secs(10));
 
     // message
     let mut dns_query_message =
@@ -1,5 +1,4 @@
 use tokio::runtime::Runtime;
-use dns_rust::client::client_connection::ClientConnection;
 use dns_rust::domain_name::DomainName;
 use dns_rust::message::rclass::Rclass;
 use dns_rust::message::rrtype::Rrtype;
@@ -1,15 +1,20 @@
 pub mod config;
 pub mod lookup;
 pub mod lookup_response;
+pub mod recursive_lookup;
 pub mod resolver_error;
+pub mod root_hints;
 pub mod server_info;
 pub mod slist;
+pub mod state_block;
 
 use self::lookup_response::LookupResponse;
 use crate::async_resolver::resolver_error::ResolverError;
 use crate::async_resolver::{config::ResolverConfig, lookup::LookupStrategy};
+use crate::async_resolver::recursive_lookup::RecursiveLookup;
 use crate::client::client_connection::ConnectionProtocol;
 use crate::client::client_error::ClientError;
+use crate::dnssec::dnssec_fetch::{bogus_response, ChainValidator, SecurityStatus};
 use crate::domain_name::DomainName;
 use crate::message::rclass::Rclass;
 use crate::message::rcode::Rcode;
@@ -215,6 +220,13 @@ impl AsyncResolver {
     /// This lookup is done asynchronously using the `tokio` runtime. It calls the
     /// asynchronous method `run()` of the `LookupStrategy` struct. This method
     /// is used to perform the DNS lookup and return the response of the query.
+    /// If the recursive mode is enabled in the configuration, the `RecursiveLookup`
+    /// struct is used instead, which follows the referrals starting from the root
+    /// hints.
+    ///
+    /// If DNSSEC validation is enabled, the response is validated with a
+    /// `ChainValidator` and its `SecurityStatus` is set. A response that fails
+    /// validation is replaced by a SERVFAIL with an Extended DNS Error code.
     ///
     /// If the response has an error, the method returns the corresponding `ResolverError`
     /// to the Client.
@@ -236,13 +248,58 @@ impl AsyncResolver {
         domain_name: DomainName,
         rrtype: Rrtype,
         rclass: Rclass,
+    ) -> Result<LookupResponse, ResolverError> {
+        let lookup_response = self.lookup_unvalidated(domain_name, rrtype, rclass).await;
+        if !self.config.get_dnssec_validation() {
+            return lookup_response;
+        }
+
+        let response = lookup_response?.to_dns_msg();
+        let mut validator = ChainValidator::new(self.clone(), self.config.get_trust_anchors());
+        let security_status = validator.validate(&response).await;
+
+        let mut response = match security_status {
+            SecurityStatus::Bogus(code) => bogus_response(&response, code),
+            _ => response,
+        };
+        let mut header = response.get_header();
+        header.set_ad(security_status == SecurityStatus::Secure);
+        response.set_header(header);
+
+        // RFC 8198: validated denials and wildcards answer later queries
+        if security_status == SecurityStatus::Secure && self.config.is_cache_enabled() {
+            if let Ok(mut cache) = self.cache.lock() {
+                cache.add_validated(&response);
+            }
+        }
+
+        let bytes = response.to_bytes();
+        let mut lookup_response = LookupResponse::new(response, bytes);
+        lookup_response.set_security_status(Some(security_status));
+        Ok(lookup_response)
+    }
+
+    /// Performs the lookup of `inner_lookup` without validating the response.
+    ///
+    /// When the resolver validates the answers, the query asks for the
+    /// DNSSEC records with the DO bit and sets the CD bit, so that a
+    /// validating recursive server returns the data even if it does not
+    /// validate.
+    pub(crate) async fn lookup_unvalidated(
+        &self,
+        domain_name: DomainName,
+        rrtype: Rrtype,
+        rclass: Rclass,
     ) -> Result<LookupResponse, ResolverError> {
         let mut query = message::create_recursive_query(domain_name.clone(), rrtype, rclass);
 
         let config = self.config.clone();
 
-        if config.get_edns0() {
-            config.add_edns0_to_message(&mut query);
+        config.add_edns0_to_message(&mut query);
+        if config.get_dnssec_validation() {
+            let mut header = query.get_header();
+            header.set_cd(true);
+            query.set_header(header);
         }
 
         // Cache lookup
@@ -272,10 +329,14 @@ impl AsyncResolver {
              */
         }
 
-        let mut lookup_strategy = LookupStrategy::new(query, self.config.clone());
-
         // TODO: add general timeout
-        let lookup_response = lookup_strategy.run().await;
+        let lookup_response = if self.config.get_recursive_mode() {
+            let mut recursive_lookup = RecursiveLookup::new(query, self.config.clone(), self.cache.clone());
+            recursive_lookup.run().await
+        } else {
+            let mut lookup_strategy = LookupStrategy::new(query, self.config.clone());
+            lookup_strategy.run().await
+        };
 
         if let Ok(ref r) = lookup_response {
             self.store_data_cache(r.to_dns_msg().clone());
@@ -300,8 +361,36 @@ impl AsyncResolver {
     /// the host.  For example, a request for the host name
     /// corresponding to IP address 1.2.3.4 looks for PTR RRs for
     /// domain name "4.3.2.1.IN-ADDR.ARPA".
-    pub async fn reverse_query() {
-        unimplemented!()
+    ///
+    /// IPv6 addresses are translated in the same way, using one label for
+    /// each nibble under "IP6.ARPA" as described in RFC 3596.
+    ///
+    /// # Examples
+    /// ```
+    /// let resolver = AsyncResolver::new(ResolverConfig::default());
+    /// let ip_address: IpAddr = "8.8.8.8".parse().unwrap();
+    /// let host_names = resolver.reverse_lookup(ip_address).await.unwrap();
+    /// ```
+    pub async fn reverse_lookup(
+        &self,
+        ip_address: IpAddr,
+    ) -> Result<Vec<DomainName>, ClientError> {
+        let domain_name = DomainName::from_ip(ip_address);
+
+        let response = self
+            .inner_lookup(domain_name, Rrtype::PTR, Rclass::IN)
+            .await;
+
+        self.check_error_from_msg(response).map(|lookup_response| {
+            lookup_response
+                .to_vec_of_rr()
+                .into_iter()
+                .filter_map(|rr| match rr.get_rdata() {
+                    Rdata::PTR(ptr) => Some(ptr.get_ptrdname()),
+                    _ => None,
+                })
+                .collect()
+        })
     }
 
     /// Stores the data of the response in the cache.
@@ -530,7 +619,7 @@ mod async_resolver_test {
     use crate::async_resolver::config::ResolverConfig;
     use crate::async_resolver::resolver_error::ResolverError;
     use crate::async_resolver::server_info::ServerInfo;
-    use crate::client::client_connection::ClientConnection;
+    use crate::client::client_connection::ConnectionProtocol;
     use crate::client::client_error::ClientError;
     use crate::client::tcp_connection::ClientTCPConnection;
     use crate::client::udp_connection::ClientUDPConnection;
@@ -538,11 +627,14 @@ mod async_resolver_test {
     use crate::message::rclass::Rclass;
     use crate::message::rcode::Rcode;
     use crate::message::rdata::a_rdata::ARdata;
+    use crate::message::rdata::ptr_rdata::PtrRdata;
     use crate::message::rdata::Rdata;
     use crate::message::resource_record::ResourceRecord;
     use crate::message::rrtype::Rrtype;
     use crate::message::DnsMessage;
-    use std::net::{IpAddr, Ipv4Addr};
+    use crate::testing::mock_connection::MockConnection;
+    use crate::testing::MockResponse;
+    use std::net::{IpAddr, Ipv4Addr, SocketAddr};
     use std::time::Duration;
     use std::vec;
     use tokio::io;
@@ -882,19 +974,50 @@ mod async_resolver_test {
         };
     }
 
-    // async fn reverse_query() {
-    //     let resolver = AsyncResolver::new(ResolverConfig::default());
-    //     let ip_address = "192.168.0.1";
-    //     let domain_name = resolver.reverse_query(ip_address).await;
+    #[tokio::test]
+    async fn reverse_lookup() {
+        let ip_address: IpAddr = "192.0.2.1".parse().unwrap();
+        let reverse_name = DomainName::from_ip(ip_address);
+        let mut ptr_rdata = PtrRdata::new();
+        ptr_rdata.set_ptrdname(DomainName::new_from_str("host.example.com"));
+        let mut resource_record = ResourceRecord::new(Rdata::PTR(ptr_rdata));
+        resource_record.set_name(reverse_name.clone());
+        resource_record.set_type_code(Rrtype::PTR);
+        resource_record.set_ttl(3600);
+
+        let server_addr = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 53));
+        let conn = MockConnection::new_default(server_addr, Duration::from_secs(1));
+        conn.add_response(reverse_name, Rrtype::PTR, MockResponse::answer(vec![resource_record]));
+        let mut config = ResolverConfig::default();
+        config.remove_servers();
+        config.add_server_with_transport(SocketAddr::new(server_addr, 53), ConnectionProtocol::UDP, Arc::new(conn.clone()));
+        config.set_protocol(ConnectionProtocol::UDP);
+        config.set_use_0x20(false);
+        let resolver = AsyncResolver::new(config);
+
+        let host_names = resolver.reverse_lookup(ip_address).await.unwrap();
+
+        assert_eq!(host_names, vec![DomainName::new_from_str("host.example.com")]);
+        assert_eq!(conn.get_query_count(), 1);
+    }
 
-    //     // Realiza aserciones para verificar que domain_name contiene un nombre de dominio válido.
-    //     assert!(!domain_name.is_empty(), "El nombre de dominio no debe estar vacío");
+    #[tokio::test]
+    async fn reverse_lookup_from_cache() {
+        let resolver = AsyncResolver::new(ResolverConfig::default());
+        let ip_address: IpAddr = "2001:db8::1".parse().unwrap();
+        let reverse_name = DomainName::from_ip(ip_address);
+
+        let mut ptr_rdata = PtrRdata::new();
+        ptr_rdata.set_ptrdname(DomainName::new_from_str("host.example.com"));
+        let mut resource_record = ResourceRecord::new(Rdata::PTR(ptr_rdata));
+        resource_record.set_name(reverse_name.clone());
+        resource_record.set_ttl(3600);
+        resolver.cache.lock().unwrap().add_answer(reverse_name, resource_record, Some(Rrtype::PTR), Rclass::IN, None);
 
-    //     // Debe verificar que devuelve el nombre de dominio correspondiente a la dirección IP dada.
-    //     // Dependiendo de tu implementación, puedes comparar el resultado con un valor esperado.
-    //     // Por ejemplo, si esperas que la dirección IP "192.168.0.1" se traduzca a "ejemplo.com":
-    //     assert_eq!(domain_name, "ejemplo.com", "El nombre de dominio debe ser 'ejemplo.com'");
-    // }
+        let host_names = resolver.reverse_lookup(ip_address).await.unwrap();
+
+        assert_eq!(host_names, vec![DomainName::new_from_str("host.example.com")]);
+    }
 
     #[tokio::test]
     async fn timeout() {
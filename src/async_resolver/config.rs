@@ -1,14 +1,19 @@
-use crate::client::{udp_connection::ClientUDPConnection, tcp_connection::ClientTCPConnection,client_connection::ClientConnection };
 use crate::client::client_connection::ConnectionProtocol;
+use crate::client::dns_transport::DnsTransport;
 use crate::message::rcode::Rcode;
 use crate::edns::opt_option::option_code::OptionCode;
 use crate::message::DnsMessage;
 use crate::tsig::tsig_algorithm::TsigAlgorithm;
 use std::cmp::max;
-use std::{env, io, net::{IpAddr, SocketAddr, Ipv4Addr}, time::Duration};
+use std::{env, io, net::{IpAddr, SocketAddr}, time::Duration};
 use std::fs::File;
+use std::sync::Arc;
 use std::io::BufRead;
 use super::server_info::ServerInfo;
+use super::root_hints::root_hints;
+use crate::domain_name::DomainName;
+use crate::dnssec::trust_anchor::root_trust_anchor;
+use crate::message::resource_record::ResourceRecord;
 
 #[cfg(target_os = "windows")]
 use ipconfig;
@@ -35,8 +40,10 @@ const RECOMMENDED_MAX_PAYLOAD: usize = 4000;
 pub struct  ResolverConfig {
     /// Vector of tuples with the UDP and TCP connections to a Name Server.
     name_servers: Vec<ServerInfo>,
-    /// Socket address of the resolver.
-    bind_addr: SocketAddr,
+    /// Local address the queries to the name servers are sent from, which
+    /// selects the interface of the outgoing queries. The system chooses
+    /// it when `None`.
+    bind_addr: Option<SocketAddr>,
     /// Maximum quantity of queries for each sent query.
     ///
     /// If this number is surpassed, the resolver is expected to panic in
@@ -50,6 +57,28 @@ pub struct  ResolverConfig {
     ///
     /// This is whether the resolver uses recursive queries or not.
     recursive_available: bool,
+    /// Whether the resolver performs the resolution itself.
+    ///
+    /// When enabled, the resolver ignores `name_servers` and follows the
+    /// referrals starting from `root_hints`, instead of asking a recursive
+    /// server to do the work.
+    recursive_mode: bool,
+    /// Name servers used to start a recursive resolution.
+    ///
+    /// Each element is the name of a root server and one of its addresses.
+    root_hints: Vec<(DomainName, IpAddr)>,
+    /// Whether the resolver validates the answers with DNSSEC.
+    ///
+    /// When enabled, queries are sent with the DO and CD bits set, and
+    /// answers that fail validation are replaced by a SERVFAIL.
+    dnssec_validation: bool,
+    /// DS and DNSKEY records from which the chains of trust start.
+    trust_anchors: Vec<ResourceRecord>,
+    /// Whether the query names are sent with random case.
+    ///
+    /// Responses must then echo the name with the same case, which makes
+    /// them harder to spoof (draft-vixie-dnsext-dns0x20).
+    use_0x20: bool,
     /// Transport protocol for queries.
     ///
     /// This is the transport protocol used by the resolver to send queries
@@ -95,6 +124,10 @@ pub struct  ResolverConfig {
 impl ResolverConfig {
     /// Creates a ResolverConfig with the given address, protocol and timeout.
     ///
+    /// `resolver_addr` is the address of the resolver itself and does not
+    /// select where the queries are sent from: the system chooses the local
+    /// address unless one is given with `set_bind_addr`.
+    ///
     /// # Examples
     ///
     /// ```
@@ -107,19 +140,24 @@ impl ResolverConfig {
     /// let protocol = ConnectionProtocol::UDP;
     /// let timeout = Duration::from_secs(TIMEOUT);
     /// let resolver_config = ResolverConfig::new(addr, protocol, timeout);
-    /// assert_eq!(resolver_config.get_addr(), SocketAddr::new(addr, 53));
+    /// assert_eq!(resolver_config.get_bind_addr(), None);
     /// ```
     pub fn new(
-        resolver_addr: IpAddr,
+        _resolver_addr: IpAddr,
         protocol: ConnectionProtocol,
         timeout: Duration,
     ) -> Self {
         let resolver_config: ResolverConfig = ResolverConfig {
             name_servers: Vec::new(),
-            bind_addr: SocketAddr::new(resolver_addr, 53),
+            bind_addr: None,
             retransmission_loop_attempts: 3,
             cache_enabled: true,
             recursive_available: false,
+            recursive_mode: false,
+            root_hints: root_hints(),
+            dnssec_validation: false,
+            trust_anchors: root_trust_anchor(),
+            use_0x20: false,
             protocol,
             timeout,
             max_retry_interval_seconds: 10,
@@ -162,10 +200,15 @@ impl ResolverConfig {
 
         let resolver_config: ResolverConfig = ResolverConfig {
             name_servers: servers_info,
-            bind_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 5333),
+            bind_addr: None,
             retransmission_loop_attempts,
             cache_enabled: true,
             recursive_available: false,
+            recursive_mode: false,
+            root_hints: root_hints(),
+            dnssec_validation: false,
+            trust_anchors: root_trust_anchor(),
+            use_0x20: false,
             protocol: ConnectionProtocol::UDP,
             timeout,
             max_retry_interval_seconds,
@@ -187,8 +230,8 @@ impl ResolverConfig {
 
     /// Adds a new Name Server to the list of Name Servers.
     ///
-    /// This corresponds to a tuple of UDP and TCP connections to a Name Server
-    /// of the type `(ClientUDPConnection, ClientTCPConnection)`.
+    /// The server is queried with the connections of the crate, built with
+    /// the timeout and buffer size of the configuration.
     ///
     /// # Examples
     ///
@@ -204,10 +247,15 @@ impl ResolverConfig {
     /// assert_eq!(resolver_config.get_name_servers().len(), 2);
     /// ```
     pub fn add_servers(&mut self, addr: IpAddr) {
-        let conn_udp:ClientUDPConnection = ClientUDPConnection::new(addr, self.timeout, self.bufsize as usize);
-        let conn_tcp:ClientTCPConnection = ClientTCPConnection::new(addr, self.timeout, self.bufsize as usize);
+        let server_info = ServerInfo::new_from_addr(addr, self.timeout, self.bufsize as usize);
+        self.name_servers.push(server_info);
+    }
 
-        let server_info = ServerInfo::new_with_ip(addr, conn_udp, conn_tcp);
+    /// Adds a Name Server at `addr` that is queried with `transport` when
+    /// `protocol` is used, instead of the connections of the crate.
+    pub fn add_server_with_transport(&mut self, addr: SocketAddr, protocol: ConnectionProtocol, transport: Arc<dyn DnsTransport>) {
+        let mut server_info = ServerInfo::new_from_socket_addr(addr, self.timeout);
+        server_info.set_transport(protocol, transport);
         self.name_servers.push(server_info);
     }
 
@@ -268,12 +316,13 @@ impl ResolverConfig {
     /// resolver_config.add_edns0_to_message(&message);
     /// ```
     pub fn add_edns0_to_message(&self, message: &mut DnsMessage) {
-        if self.edns0 {
+        // DNSSEC records are only sent to requestors that set the DO bit
+        if self.edns0 || self.dnssec_validation {
             message.add_edns0(
                 Some(self.get_max_payload()),
                 Rcode::NOERROR,
                 self.get_edns0_version(),
-                self.get_edns0_do(),
+                self.get_edns0_do() || self.dnssec_validation,
                 Some(self.get_edns0_options()));
         }
     }
@@ -406,8 +455,8 @@ impl ResolverConfig {
         self.name_servers.clone()
     }
 
-    /// Returns the socket address of the resolver.
-    pub fn get_addr(&self) -> SocketAddr {
+    /// Returns the local address the queries are sent from.
+    pub fn get_bind_addr(&self) -> Option<SocketAddr> {
         self.bind_addr
     }
 
@@ -427,6 +476,31 @@ impl ResolverConfig {
         self.recursive_available
     }
 
+    /// Returns whether the resolver performs the resolution itself.
+    pub fn get_recursive_mode(&self) -> bool {
+        self.recursive_mode
+    }
+
+    /// Returns the name servers used to start a recursive resolution.
+    pub fn get_root_hints(&self) -> Vec<(DomainName, IpAddr)> {
+        self.root_hints.clone()
+    }
+
+    /// Returns whether the resolver validates the answers with DNSSEC.
+    pub fn get_dnssec_validation(&self) -> bool {
+        self.dnssec_validation
+    }
+
+    /// Returns the trust anchors used to validate the answers.
+    pub fn get_trust_anchors(&self) -> Vec<ResourceRecord> {
+        self.trust_anchors.clone()
+    }
+
+    /// Returns whether the query names are sent with random case.
+    pub fn get_use_0x20(&self) -> bool {
+        self.use_0x20
+    }
+
     /// Returns the transport protocol for queries.
     pub fn get_protocol(&self) -> ConnectionProtocol {
         self.protocol
@@ -499,9 +573,10 @@ impl ResolverConfig{
         self.name_servers.push(server_info);
     }
 
-    /// Sets the socket address of the resolver.
-    pub fn set_ddr(&mut self,addr:SocketAddr) {
-        self.bind_addr = addr;
+    /// Sets the local address the queries are sent from. With `None` the
+    /// system chooses it.
+    pub fn set_bind_addr(&mut self, bind_addr: Option<SocketAddr>) {
+        self.bind_addr = bind_addr;
     }
 
     /// Sets the quantity of retries before the resolver panic in a
@@ -520,6 +595,31 @@ impl ResolverConfig{
         self.recursive_available = recursive_available;
     }
 
+    /// Sets whether the resolver performs the resolution itself.
+    pub fn set_recursive_mode(&mut self, recursive_mode: bool) {
+        self.recursive_mode = recursive_mode;
+    }
+
+    /// Sets the name servers used to start a recursive resolution.
+    pub fn set_root_hints(&mut self, root_hints: Vec<(DomainName, IpAddr)>) {
+        self.root_hints = root_hints;
+    }
+
+    /// Sets whether the resolver validates the answers with DNSSEC.
+    pub fn set_dnssec_validation(&mut self, dnssec_validation: bool) {
+        self.dnssec_validation = dnssec_validation;
+    }
+
+    /// Sets the trust anchors used to validate the answers.
+    pub fn set_trust_anchors(&mut self, trust_anchors: Vec<ResourceRecord>) {
+        self.trust_anchors = trust_anchors;
+    }
+
+    /// Sets whether the query names are sent with random case.
+    pub fn set_use_0x20(&mut self, use_0x20: bool) {
+        self.use_0x20 = use_0x20;
+    }
+
     /// Sets the transport protocol for queries.
     pub fn set_protocol(&mut self, protocol:ConnectionProtocol) {
         self.protocol = protocol;
@@ -584,11 +684,15 @@ impl ResolverConfig{
 mod tests_resolver_config {
     use crate::async_resolver::server_info;
     //TODO: FK test config and documentation
-    use crate::client::client_connection::ClientConnection;
     use crate::client::tcp_connection::ClientTCPConnection;
     use crate::client::udp_connection::ClientUDPConnection;
     use crate::client::client_connection::ConnectionProtocol;
+    use crate::client::dns_transport::DnsTransport;
+    use std::sync::Arc;
     use crate::async_resolver::config::ResolverConfig;
+    use crate::dnssec::trust_anchor::root_trust_anchor;
+    use crate::message::DnsMessage;
+    use crate::message::rrtype::Rrtype;
     use std::net::{IpAddr,Ipv4Addr, SocketAddr};
     use std::time::Duration;
     static TIMEOUT: u64 = 10;
@@ -600,7 +704,8 @@ mod tests_resolver_config {
         let timeout = Duration::from_secs(TIMEOUT);
         let resolver_config = ResolverConfig::new(addr, protocol, timeout);
 
-        assert_eq!(resolver_config.get_addr(), SocketAddr::new(addr, 53));
+        assert_eq!(resolver_config.get_bind_addr(), None);
+        assert_eq!(resolver_config.get_protocol(), protocol);
     }
 
     #[test]
@@ -611,6 +716,22 @@ mod tests_resolver_config {
         assert_eq!(resolver_config.get_name_servers().len(), 9);
     }
 
+    #[test]
+    fn add_server_with_transport() {
+        let mut resolver_config = ResolverConfig::default();
+        resolver_config.remove_servers();
+        let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(192, 168, 0, 1)), 5353);
+        let transport: Arc<dyn DnsTransport> = Arc::new(ClientTCPConnection::new_default(addr.ip(), Duration::from_secs(2)));
+        resolver_config.add_server_with_transport(addr, ConnectionProtocol::UDP, transport.clone());
+
+        let name_servers = resolver_config.get_name_servers();
+        let server_transport = name_servers[0].get_transport(ConnectionProtocol::UDP).unwrap();
+
+        assert_eq!(name_servers.len(), 1);
+        assert_eq!(name_servers[0].get_socket_addr(), addr);
+        assert!(Arc::ptr_eq(&server_transport, &transport));
+    }
+
     #[test]
     fn get_and_set_name_servers() {
         let mut resolver_config = ResolverConfig::default();
@@ -634,15 +755,15 @@ mod tests_resolver_config {
     }
 
     #[test]
-    fn get_and_set_addr() {
+    fn get_and_set_bind_addr() {
         let mut resolver_config = ResolverConfig::default();
 
-        assert_eq!(resolver_config.get_addr(), SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 5333));
+        assert_eq!(resolver_config.get_bind_addr(), None);
 
-        let addr = IpAddr::V4(Ipv4Addr::new(192, 168, 0, 1));
-        resolver_config.set_ddr(SocketAddr::new(addr, 10));
+        let bind_addr: SocketAddr = "[2001:db8::1]:0".parse().unwrap();
+        resolver_config.set_bind_addr(Some(bind_addr));
 
-        assert_eq!(resolver_config.get_addr(), SocketAddr::new(addr, 10));
+        assert_eq!(resolver_config.get_bind_addr(), Some(bind_addr));
     }
 
     #[test]
@@ -667,6 +788,44 @@ mod tests_resolver_config {
         assert_eq!(resolver_config.get_recursive_available(), true);
     }
 
+    #[test]
+    fn get_and_set_dnssec_validation() {
+        let mut resolver_config = ResolverConfig::default();
+
+        assert_eq!(resolver_config.get_dnssec_validation(), false);
+        assert_eq!(resolver_config.get_trust_anchors(), root_trust_anchor());
+
+        resolver_config.set_dnssec_validation(true);
+        resolver_config.set_trust_anchors(Vec::new());
+
+        assert_eq!(resolver_config.get_dnssec_validation(), true);
+        assert!(resolver_config.get_trust_anchors().is_empty());
+    }
+
+    #[test]
+    fn get_and_set_use_0x20() {
+        let mut resolver_config = ResolverConfig::default();
+
+        assert_eq!(resolver_config.get_use_0x20(), false);
+
+        resolver_config.set_use_0x20(true);
+
+        assert_eq!(resolver_config.get_use_0x20(), true);
+    }
+
+    #[test]
+    fn add_edns0_to_message_with_dnssec_validation() {
+        let mut resolver_config = ResolverConfig::default();
+        resolver_config.set_dnssec_validation(true);
+        let mut message = DnsMessage::new();
+
+        resolver_config.add_edns0_to_message(&mut message);
+
+        let opt_rr = message.get_additional().pop().unwrap();
+        assert_eq!(opt_rr.get_rtype(), Rrtype::OPT);
+        assert_eq!(opt_rr.get_ttl() & 0x8000, 0x8000);
+    }
+
     #[test]
     fn get_and_set_protocol() {
         let mut resolver_config = ResolverConfig::default();
@@ -676,6 +835,10 @@ mod tests_resolver_config {
         resolver_config.set_protocol(ConnectionProtocol::TCP);
 
         assert_eq!(resolver_config.get_protocol(), ConnectionProtocol::TCP);
+
+        resolver_config.set_protocol(ConnectionProtocol::from("HTTPS"));
+
+        assert_eq!(resolver_config.get_protocol(), ConnectionProtocol::HTTPS);
     }
 
     #[test]
@@ -1,13 +1,13 @@
 use crate::client::client_error::ClientError;
 use crate::message::rcode::Rcode;
 use crate::message::DnsMessage;
-use crate::client::client_connection::ClientConnection;
 use super::lookup_response::LookupResponse;
 use super::resolver_error::ResolverError;
 use super::server_info::ServerInfo;
 use std::sync::{Mutex,Arc};
 use std::time::Instant;
 use crate::client::client_connection::ConnectionProtocol;
+use crate::client::response_match::{apply_0x20, check_response, restore_qname};
 use crate::async_resolver::config::ResolverConfig;
 
 /// Struct that represents the execution of a lookup.
@@ -191,21 +191,32 @@ impl LookupStrategy {
     ) -> Result<LookupResponse, ResolverError>  {
         let response_arc=  self.response_msg.clone();
         let protocol = self.config.get_protocol();
+        let use_0x20 = self.config.get_use_0x20();
+        let mut query = self.query.clone();
+        if use_0x20 {
+            apply_0x20(&mut query);
+        }
+        let mut server_info = server_info.clone();
+        if let Some(bind_addr) = self.config.get_bind_addr() {
+            server_info.set_bind_addr(Some(bind_addr));
+        }
         let mut dns_msg_result: Result<(DnsMessage, Vec<u8>), ResolverError>;
         {
             // Guard reference to modify the response
             let mut response_guard = response_arc.lock().unwrap(); // TODO: add error handling
             let send_future = send_query_by_protocol(
                 timeout_duration,
-                &self.query,
+                &query,
                 protocol,
-                server_info
+                &server_info,
+                use_0x20
             );
             dns_msg_result = tokio::time::timeout(timeout_duration, send_future)
                 .await
                 .unwrap_or_else(
                     |_| {Err(ResolverError::Message("Execute Strategy Timeout Error".into()))}
-                );
+                )
+                .map(|response| self.restore_question(response, use_0x20));
             *response_guard = dns_msg_result.clone();
             //*response_guard = dns_msg_result.clone();
         }
@@ -221,16 +232,18 @@ impl LookupStrategy {
             let tcp_protocol = ConnectionProtocol::TCP;
             let send_future = send_query_by_protocol(
                 timeout_duration,
-                &self.query,
+                &query,
                 tcp_protocol,
-                server_info
+                &server_info,
+                use_0x20
             );
             tokio::time::sleep(timeout_duration).await;
             dns_msg_result = tokio::time::timeout(timeout_duration, send_future)
                 .await
                 .unwrap_or_else(
                     |_| {Err(ResolverError::Message("Execute Strategy Timeout Error".into()))}
-                ); 
+                )
+                .map(|response| self.restore_question(response, use_0x20));
             let mut response_guard = response_arc.lock().unwrap();
             *response_guard = dns_msg_result.clone();
         }
@@ -241,40 +254,36 @@ impl LookupStrategy {
             }
         )
     }
+
+    /// Returns the response with the question as it was in the query of
+    /// the lookup, undoing the mixed case of `apply_0x20`.
+    fn restore_question(&self, response: (DnsMessage, Vec<u8>), use_0x20: bool) -> (DnsMessage, Vec<u8>) {
+        let (mut dns_msg, mut bytes) = response;
+        if use_0x20 {
+            restore_qname(&self.query, &mut dns_msg, &mut bytes);
+        }
+        (dns_msg, bytes)
+    }
 }
 
 ///  Sends a DNS query to a resolver using the specified connection protocol.
 /// 
 ///  This function takes a DNS query, a result containing a DNS message,
-///  and connection information. Depending on the specified protocol (UDP or TCP),
-///  it sends the query using the corresponding connection and updates the result
-///  with the parsed response.
+///  and connection information. The query is sent with the transport of
+///  the server for the protocol, and the result is updated with the parsed
+///  response.
 async fn send_query_by_protocol(
     timeout: tokio::time::Duration,
     query: &DnsMessage,
     protocol: ConnectionProtocol,
     server_info:  &ServerInfo,
+    use_0x20: bool,
 ) ->  Result<(DnsMessage, Vec<u8>), ResolverError> {
-    let query_id = query.get_query_id();
-    let dns_query = query.clone();
-    let dns_msg_result;
-    match protocol{ 
-        ConnectionProtocol::UDP => {
-            let mut udp_connection = server_info.get_udp_connection().clone();
-            udp_connection.set_timeout(timeout);
-            let response_result = udp_connection.send(dns_query).await;
-            dns_msg_result = parse_response(response_result, query_id);
-            dns_msg_result
-        }
-        ConnectionProtocol::TCP => {
-            let mut tcp_connection = server_info.get_tcp_connection().clone();
-            tcp_connection.set_timeout(timeout);
-            let response_result = tcp_connection.send(dns_query).await;
-            dns_msg_result = parse_response(response_result, query_id);
-            dns_msg_result
-        }
-        _ =>  Err(ResolverError::Message("Invalid Protocol".into())), // TODO: specific add error handling
-    }
+    let transport = server_info
+        .get_transport(protocol)
+        .ok_or(ResolverError::Message("Invalid Protocol"))?;
+    let response_result = transport.send_query(query, timeout, use_0x20).await;
+    parse_response(response_result, query, use_0x20)
 }
 
 /// Parse the received response datagram to a `DnsMessage`.
@@ -296,7 +305,9 @@ async fn send_query_by_protocol(
 ///      excessively long TTL, say greater than 1 week, either discard
 ///      the whole response, or limit all TTLs in the response to 1
 ///      week.
-fn parse_response(response_result: Result<Vec<u8>, ClientError>, query_id:u16) -> Result<(DnsMessage, Vec<u8>), ResolverError> {
+///
+/// The response must also answer `query`, see `check_response`.
+fn parse_response(response_result: Result<Vec<u8>, ClientError>, query: &DnsMessage, use_0x20: bool) -> Result<(DnsMessage, Vec<u8>), ResolverError> {
     let response_msg = response_result.map_err(Into::<ResolverError>::into)?;
 
     let dns_msg = DnsMessage::from_bytes(&response_msg).
@@ -309,15 +320,10 @@ fn parse_response(response_result: Result<Vec<u8>, ClientError>, query_id:u16) -
         .format_check()
         .map_err(|e| ResolverError::Parse(format!("Error formated Header: {}", e)))?;
 
-    // Check ID
-    if dns_msg.get_query_id() != query_id {
-        return  Err(ResolverError::Parse("Error expected ID from query".to_string()))
-    }
+    // Check ID, QR, opcode and question
+    check_response(query, &dns_msg, use_0x20).map_err(|e| ResolverError::Parse(e.to_string()))?;
 
-    if header.get_qr() {
-        return Ok((dns_msg, response_msg));
-    }
-    Err(ResolverError::Parse("Message is a query. A response was expected.".to_string()))
+    Ok((dns_msg, response_msg))
 }
 
 #[cfg(test)]
@@ -332,11 +338,90 @@ mod async_resolver_test {
     use crate::message::resource_record::ResourceRecord;
     use crate::message::rrtype::Rrtype;
     use crate::{ domain_name::DomainName, dns_cache::DnsCache};
-    use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+    use std::net::{IpAddr, Ipv4Addr};
     use std::str::FromStr;
     use std::time::Duration;
     use std::num::NonZeroUsize;
     use super::*;
+
+    /// Transport that answers every query with an A record, without network,
+    /// and records the timeout it was given.
+    #[derive(Debug, Default)]
+    struct AnsweringTransport {
+        timeout: std::sync::Mutex<Option<Duration>>,
+    }
+
+    #[async_trait::async_trait]
+    impl crate::client::dns_transport::DnsTransport for AnsweringTransport {
+        async fn send_query(&self, dns_query: &DnsMessage, timeout: Duration, _use_0x20: bool) -> Result<Vec<u8>, ClientError> {
+            *self.timeout.lock().unwrap() = Some(timeout);
+            let mut response = dns_query.clone();
+            let mut header = response.get_header();
+            header.set_qr(true);
+            header.set_ancount(1);
+            response.set_header(header);
+            let mut rr = ResourceRecord::new(Rdata::A(ARdata::new_from_addr(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1)))));
+            rr.set_name(dns_query.get_question().get_qname());
+            response.set_answer(vec![rr]);
+            Ok(response.to_bytes())
+        }
+
+        fn get_ip(&self) -> IpAddr {
+            IpAddr::V4(Ipv4Addr::new(192, 0, 2, 53))
+        }
+    }
+
+    #[tokio::test]
+    async fn transmit_query_to_server_with_custom_transport() {
+        let domain_name = DomainName::new_from_str("example.com");
+        let timeout = Duration::from_secs(2);
+        let mut server_info = server_info::ServerInfo::new_from_addr_with_default_size(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 53)), Duration::from_secs(10));
+        let transport = Arc::new(AnsweringTransport::default());
+        server_info.set_transport(ConnectionProtocol::UDP, transport.clone());
+
+        let lookup_strategy = LookupStrategy::new(
+            message::create_recursive_query(domain_name, Rrtype::A, Rclass::IN),
+            ResolverConfig::default(),
+        );
+        let response = lookup_strategy.transmit_query_to_server(&server_info, timeout).await.unwrap();
+        let answer = response.to_dns_msg().get_answer();
+
+        assert_eq!(*transport.timeout.lock().unwrap(), Some(timeout));
+        assert_eq!(answer.len(), 1);
+        assert_eq!(answer[0].get_rdata(), Rdata::A(ARdata::new_from_addr(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1)))));
+    }
+
+    #[tokio::test]
+    async fn transmit_query_to_server_restores_0x20_case() {
+        let domain_name = DomainName::new_from_str("www.example.com");
+        let timeout = Duration::from_secs(2);
+        let mut server_info = server_info::ServerInfo::new_from_addr_with_default_size(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 53)), timeout);
+        server_info.set_transport(ConnectionProtocol::UDP, Arc::new(AnsweringTransport::default()));
+        let mut config = ResolverConfig::default();
+        config.set_use_0x20(true);
+
+        let lookup_strategy = LookupStrategy::new(
+            message::create_recursive_query(domain_name.clone(), Rrtype::A, Rclass::IN),
+            config,
+        );
+        let response = lookup_strategy.transmit_query_to_server(&server_info, timeout).await.unwrap();
+        let qname = response.to_dns_msg().get_question().get_qname();
+
+        assert!(qname.eq_case_sensitive(&domain_name));
+        assert!(response.to_dns_msg().get_answer()[0].get_name().eq_case_sensitive(&domain_name));
+        assert!(DnsMessage::from_bytes(&response.to_bytes()).unwrap().get_question().get_qname().eq_case_sensitive(&domain_name));
+    }
+
+    fn test_query(query_id: u16) -> DnsMessage {
+        DnsMessage::new_query_message(
+            DomainName::new_from_str("test.com"),
+            Rrtype::TXT,
+            Rclass::IN,
+            0,
+            false,
+            query_id,
+        )
+    }
    
     #[test]
     fn lookup() {
@@ -362,7 +447,7 @@ mod async_resolver_test {
         );
 
         assert_eq!(lookup_future.query.get_question().get_qname(), DomainName::new_from_string("example.com".to_string()));
-        assert_eq!(lookup_future.config.get_addr(),SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 5333));
+        assert_eq!(lookup_future.config.get_bind_addr(), None);
     }
      
     #[tokio::test]
@@ -559,9 +644,9 @@ mod async_resolver_test {
             101, 115, 116, 3, 99, 111, 109, 0, 0, 16, 0, 1, 3, 100, 99, 99, 2, 99, 108, 0, 0, 16, 0,
             1, 0, 0, 0b00010110, 0b00001010, 0, 6, 5, 104, 101, 108, 108, 111,
         ];
-        let query_id = 0b00100100;
+        let query = test_query(0b00100100);
         let response_result: Result<Vec<u8>, ClientError> = Ok(bytes.to_vec());
-        let response_dns_msg = parse_response(response_result,query_id);
+        let response_dns_msg = parse_response(response_result, &query, false);
         println!("[###############] {:?}",response_dns_msg);
         assert!(response_dns_msg.is_ok());
         if let Ok((dns_msg,_)) = response_dns_msg {
@@ -581,9 +666,9 @@ mod async_resolver_test {
             101, 115, 116, 3, 99, 111, 109, 0, 0, 16, 0, 1, 3, 100, 99, 99, 2, 99, 108, 0, 0, 16, 0,
             1, 0, 0, 0b00010110, 0b00001010, 0, 6, 5, 104, 101, 108, 108, 111,
         ];
-        let query_id = 0b10100101;
+        let query = test_query(0b10100101);
         let response_result: Result<Vec<u8>, ClientError> = Ok(bytes.to_vec());
-        let response_dns_msg = parse_response(response_result,query_id);
+        let response_dns_msg = parse_response(response_result, &query, false);
         let err_msg = "Message is a query. A response was expected.".to_string();
         if let Err(ResolverError::Parse(err)) = response_dns_msg {
             assert_eq!(err, err_msg)
@@ -600,9 +685,9 @@ mod async_resolver_test {
             101, 115, 116, 3, 99, 111, 109, 0, 0, 16, 0, 1, 3, 100, 99, 99, 2, 99, 45, 0, 0, 16, 0,
             1, 0, 0, 0b00010110, 0b00001010, 0, 6, 5, 104, 101, 108, 108, 111,
         ];
-        let query_id = 0b10100101;
+        let query = test_query(0b10100101);
         let response_result: Result<Vec<u8>, ClientError> = Ok(bytes.to_vec());
-        let response_dns_msg = parse_response(response_result,query_id);
+        let response_dns_msg = parse_response(response_result, &query, false);
         let err_msg = "The name server was unable to interpret the query.".to_string();
         if let Err(ResolverError::Parse(err)) = response_dns_msg {
             assert_eq!(err, err_msg)
@@ -615,13 +700,13 @@ mod async_resolver_test {
     fn parse_error_domain_name() {
         let bytes: [u8; 50] = [
             //test passes with this one
-            0b10100101, 0b10010101, 0b11111111, 0b11111111, 0, 1, 0b00000000, 1, 0, 0, 0, 0, 4, 116,
+            0b10100101, 0b10010101, 0b11111111, 0b11111111, 0, 1, 0b00000000, 1, 0, 0, 0, 0, 0b10000100, 116,
             101, 115, 64, 3, 99, 111, 109, 0, 0, 16, 0, 1, 3, 100, 99, 99, 2, 99, 108, 0, 0, 16, 0,
             1, 0, 0, 0b00010110, 0b00001010, 0, 6, 5, 104, 101, 108, 108, 111,
         ];
-        let query_id = 0b10100101;
+        let query = test_query(0b10100101);
         let response_result: Result<Vec<u8>, ClientError> = Ok(bytes.to_vec());
-        let response_dns_msg = parse_response(response_result,query_id);
+        let response_dns_msg = parse_response(response_result, &query, false);
         let err_msg = "The name server was unable to interpret the query.".to_string();
 
         if let Err(ResolverError::Parse(err)) = response_dns_msg {
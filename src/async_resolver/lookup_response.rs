@@ -1,3 +1,4 @@
+use crate::dnssec::dnssec_fetch::SecurityStatus;
 use crate::message::{resource_record::ResourceRecord, DnsMessage};
 use std::fmt;
 
@@ -12,13 +13,26 @@ use std::fmt;
 pub struct LookupResponse {
     // The DNS message response.
     dns_msg_response: DnsMessage,
-    bytes: Vec<u8>
+    bytes: Vec<u8>,
+    // The DNSSEC status of the response, `None` if it was not validated.
+    security_status: Option<SecurityStatus>,
 }
 
 impl LookupResponse {
     /// Create a new `LookupResponse` instance.
     pub fn new(dns_msg_response: DnsMessage, bytes: Vec<u8>) -> LookupResponse {
-        LookupResponse { dns_msg_response, bytes: bytes }
+        LookupResponse { dns_msg_response, bytes, security_status: None }
+    }
+
+    /// Returns the DNSSEC status of the response, or `None` if the resolver
+    /// does not validate the answers.
+    pub fn get_security_status(&self) -> Option<SecurityStatus> {
+        self.security_status
+    }
+
+    /// Sets the DNSSEC status of the response.
+    pub fn set_security_status(&mut self, security_status: Option<SecurityStatus>) {
+        self.security_status = security_status;
     }
 
     pub fn get_bytes(&self) -> Vec<u8> {
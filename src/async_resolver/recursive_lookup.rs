@@ -0,0 +1,810 @@
+use crate::async_resolver::config::ResolverConfig;
+use crate::client::client_connection::ConnectionProtocol;
+use crate::domain_name::DomainName;
+use crate::message::rclass::Rclass;
+use crate::message::rcode::Rcode;
+use crate::message::rdata::Rdata;
+use crate::message::resource_record::ResourceRecord;
+use crate::message::rrtype::Rrtype;
+use crate::message::DnsMessage;
+use crate::resolver_cache::ResolverCache;
+use super::lookup::LookupStrategy;
+use super::lookup_response::LookupResponse;
+use super::resolver_error::ResolverError;
+use super::server_info::ServerInfo;
+use super::slist::Slist;
+use super::state_block::StateBlock;
+use rand::{thread_rng, Rng};
+use std::future::Future;
+use std::net::IpAddr;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Maximum number of CNAME records followed in a single resolution.
+const MAX_CNAME_CHAIN: usize = 16;
+
+/// Maximum nesting of the resolutions started to find the address of a
+/// name server without glue.
+const MAX_SUBQUERY_DEPTH: u8 = 4;
+
+/// Struct that represents the execution of a recursive lookup.
+///
+/// Unlike `LookupStrategy`, which asks a recursive name server to do the
+/// work, this struct performs the resolution itself. It starts from the
+/// closest delegation found in the cache, or from the root hints, and
+/// follows the referrals until a server answers with authority.
+///
+/// All the work done on behalf of the request, including the resolution
+/// of name servers without glue, is counted in a single `StateBlock`.
+///
+/// [RFC 1034]: https://datatracker.ietf.org/doc/html/rfc1034#section-5.3.3
+///
+/// 5.3.3. Algorithm
+///
+/// The top level algorithm has four steps:
+///
+///    1. See if the answer is in local information, and if so return
+///       it to the client.
+///
+///    2. Find the best servers to ask.
+///
+///    3. Send them queries until one returns a response.
+///
+///    4. Analyze the response, either:
+///
+///       a. if the response answers the question or contains a name
+///       error, cache the data as well as returning it back to the client.
+///
+///       b. if the response contains a better delegation to other
+///       servers, cache the delegation information, and go to step 2.
+///
+///       c. if the response shows a CNAME and that is not the answer
+///       itself, cache the CNAME, change the SNAME to the canonical name
+///       in the CNAME RR and go to step 1.
+///
+///       d. if the response shows a servers failure or other bizarre
+///       contents, delete the server from the SLIST and go back to step 3.
+pub struct RecursiveLookup {
+    /// Query received from the client.
+    query: DnsMessage,
+    /// Resolver configuration.
+    config: ResolverConfig,
+    /// Cache of the resolver, where the delegations are stored.
+    cache: Arc<Mutex<ResolverCache>>,
+    /// State of the request.
+    state_block: StateBlock,
+}
+
+/// Classification of a response received while following referrals.
+#[derive(Clone, Debug, PartialEq)]
+enum ResponseKind {
+    /// The response contains the records asked for.
+    Answer,
+    /// The response is a name error or has no data for the name.
+    Negative,
+    /// The response is an alias chain ending at a name it has no data for.
+    Cname(DomainName, Vec<ResourceRecord>),
+    /// The response delegates a zone closer to the name to other servers.
+    Referral(DomainName, Vec<ResourceRecord>),
+    /// The response is an error or does not get us closer to the answer.
+    Lame,
+}
+
+impl RecursiveLookup {
+
+    /// Creates a new `RecursiveLookup` for the given query.
+    pub fn new(
+        query: DnsMessage,
+        config: ResolverConfig,
+        cache: Arc<Mutex<ResolverCache>>,
+    ) -> Self {
+        let state_block = StateBlock::new(config.get_global_retransmission_limit() as u32);
+        Self {
+            query,
+            config,
+            cache,
+            state_block,
+        }
+    }
+
+    /// Executes the recursive lookup asynchronously.
+    ///
+    /// The response is built from the response of the last server asked,
+    /// with the CNAME records followed on the way at the start of the
+    /// answer section.
+    pub async fn run(
+        &mut self,
+    ) -> Result<LookupResponse, ResolverError> {
+        let question = self.query.get_question();
+        let mut response = self.resolve(
+            question.get_qname(),
+            question.get_rrtype(),
+            question.get_rclass(),
+            0,
+        ).await?;
+
+        let mut header = response.get_header();
+        header.set_id(self.query.get_query_id());
+        header.set_rd(self.query.get_header().get_rd());
+        header.set_aa(false);
+        header.set_ra(true);
+        response.set_header(header);
+        response.set_question(question);
+        response.update_header_counters();
+
+        let bytes = response.to_bytes();
+        Ok(LookupResponse::new(response, bytes))
+    }
+
+    /// Resolves `sname` following referrals from the best servers known.
+    ///
+    /// Returns the response of the server that answered, with the question
+    /// set to `sname` and the CNAME records followed prepended to its answer.
+    fn resolve<'a>(
+        &'a mut self,
+        sname: DomainName,
+        qtype: Rrtype,
+        qclass: Rclass,
+        depth: u8,
+    ) -> Pin<Box<dyn Future<Output = Result<DnsMessage, ResolverError>> + 'a>> {
+        Box::pin(async move {
+            if depth > MAX_SUBQUERY_DEPTH {
+                return Err(ResolverError::Message("Maximum resolution depth exceeded"));
+            }
+            let original_sname = sname.clone();
+            let mut sname = sname;
+            let mut cname_chain: Vec<ResourceRecord> = Vec::new();
+
+            'names: loop {
+                if cname_chain.len() > MAX_CNAME_CHAIN {
+                    return Err(ResolverError::Message("CNAME chain too long"));
+                }
+
+                // Step 1: see if the answer is in local information.
+                if let Some(answer) = self.get_cached_rrs(&sname, qtype, qclass) {
+                    let mut response = DnsMessage::new_query_message(
+                        sname.clone(), qtype, qclass, 0, false, 0,
+                    );
+                    response.set_answer(answer);
+                    match classify_response(&response, &sname, qtype, &DomainName::new_from_str(".")) {
+                        ResponseKind::Answer => {
+                            return Ok(with_cname_chain(response, cname_chain, &original_sname));
+                        }
+                        ResponseKind::Cname(target, cnames) => {
+                            cname_chain.extend(cnames);
+                            sname = target;
+                            continue 'names;
+                        }
+                        _ => {}
+                    }
+                }
+
+                // Step 2: find the best servers to ask. The DS RRset lives
+                // on the parent side of a zone cut (RFC 4035 section 4.2).
+                let search_name = match qtype {
+                    Rrtype::DS => sname.parent().unwrap_or_else(|| sname.clone()),
+                    _ => sname.clone(),
+                };
+                let (mut zone, mut slist) = self.find_best_servers(&search_name, qclass);
+
+                loop {
+                    // Step 3: send them queries until one returns a response.
+                    let (response, ip_addr) = self.query_slist(&mut slist, &sname, qtype, qclass).await?;
+
+                    // Step 4: analyze the response.
+                    match classify_response(&response, &sname, qtype, &zone) {
+                        ResponseKind::Answer | ResponseKind::Negative => {
+                            self.cache_response(&response);
+                            return Ok(with_cname_chain(response, cname_chain, &original_sname));
+                        }
+                        ResponseKind::Cname(target, cnames) => {
+                            self.cache_response(&response);
+                            cname_chain.extend(cnames);
+                            sname = target;
+                            continue 'names;
+                        }
+                        ResponseKind::Referral(child_zone, ns_rrs) => {
+                            let glue = get_glue(&response, &ns_rrs, &zone);
+                            self.cache_delegation(&ns_rrs, &glue);
+                            slist = self.slist_from_referral(&child_zone, &ns_rrs, &glue, qclass, depth).await?;
+                            zone = child_zone;
+                        }
+                        ResponseKind::Lame => {
+                            delete_address(&mut slist, ip_addr);
+                        }
+                    }
+                }
+            }
+        })
+    }
+
+    /// Sends the query to the servers of the SLIST until one of them returns
+    /// a response.
+    ///
+    /// Returns the response and the address of the server that sent it.
+    /// Servers that fail or return an error are deleted from the SLIST. Each
+    /// query sent decrements the request global counter of the request.
+    async fn query_slist(
+        &mut self,
+        slist: &mut Slist,
+        sname: &DomainName,
+        qtype: Rrtype,
+        qclass: Rclass,
+    ) -> Result<(DnsMessage, IpAddr), ResolverError> {
+        let timeout = Duration::from_secs(self.config.get_min_retry_interval_seconds().max(1));
+
+        while slist.len() > 0 {
+            let ip_addr = slist.get_first().get_ip_address();
+            self.state_block.decrement_request_global_counter()?;
+
+            let mut result = send_query(self.config.clone(), ip_addr, sname, qtype, qclass, timeout).await;
+            let truncated = matches!(&result, Ok(response) if response.get_header().get_tc());
+            if truncated {
+                self.state_block.decrement_request_global_counter()?;
+                let mut config = self.config.clone();
+                config.set_protocol(ConnectionProtocol::TCP);
+                result = send_query(config, ip_addr, sname, qtype, qclass, timeout).await;
+            }
+
+            match result {
+                Ok(response) if is_usable(&response, sname, qtype) => return Ok((response, ip_addr)),
+                _ => delete_address(slist, ip_addr),
+            }
+        }
+        Err(ResolverError::Message("No name server could answer the query"))
+    }
+
+    /// Builds the SLIST for a zone from a referral.
+    ///
+    /// The addresses are taken from the glue records of the referral. If
+    /// there is no glue, the addresses of the name servers are resolved
+    /// using the same `StateBlock`, so this work is also limited by the
+    /// request global counter.
+    async fn slist_from_referral(
+        &mut self,
+        zone: &DomainName,
+        ns_rrs: &[ResourceRecord],
+        glue: &[ResourceRecord],
+        qclass: Rclass,
+        depth: u8,
+    ) -> Result<Slist, ResolverError> {
+        let mut slist = Slist::new();
+        slist.set_zone_name_equivalent(zone.label_count() as i32);
+
+        for rr in glue {
+            if let Some(ip_addr) = get_address(rr) {
+                slist.insert(rr.get_name(), ip_addr, 0);
+            }
+        }
+        if slist.len() > 0 {
+            return Ok(slist);
+        }
+
+        for ns_name in get_ns_names(ns_rrs) {
+            // A name server inside the zone can only be reached through glue.
+            if ns_name.is_subdomain_of(zone) {
+                continue;
+            }
+            let result = self.resolve(ns_name.clone(), Rrtype::A, qclass, depth + 1).await;
+            let response = match result {
+                Ok(response) => response,
+                Err(ResolverError::RetriesLimitExceeded) => return Err(ResolverError::RetriesLimitExceeded),
+                Err(_) => continue,
+            };
+            for rr in response.get_answer() {
+                if let Some(ip_addr) = get_address(&rr) {
+                    slist.insert(ns_name.clone(), ip_addr, 0);
+                }
+            }
+            if slist.len() > 0 {
+                return Ok(slist);
+            }
+        }
+        Err(ResolverError::Message("No address found for the name servers of the zone"))
+    }
+
+    /// Finds the best servers to ask for `sname`.
+    ///
+    /// Looks in the cache for the delegation of the closest ancestor of
+    /// `sname` whose name servers have known addresses. If there is none,
+    /// the root hints of the configuration are used.
+    fn find_best_servers(&self, sname: &DomainName, qclass: Rclass) -> (DomainName, Slist) {
+        if self.config.is_cache_enabled() {
+            let mut cache = self.cache.lock().unwrap();
+            cache.timeout();
+
+            let mut current = Some(sname.clone());
+            while let Some(zone) = current {
+                if let Some(ns_rrs) = cache.get_authority(zone.clone(), Rrtype::NS, qclass) {
+                    let mut slist = Slist::new();
+                    slist.set_zone_name_equivalent(zone.label_count() as i32);
+                    for ns_name in get_ns_names(&ns_rrs) {
+                        for rrtype in [Rrtype::A, Rrtype::AAAA] {
+                            let rrs = cache.get_additional(ns_name.clone(), rrtype, qclass)
+                                .or_else(|| cache.get_answer(ns_name.clone(), rrtype, qclass))
+                                .unwrap_or_default();
+                            for rr in rrs.iter().filter(|rr| rr.get_name() == ns_name) {
+                                if let Some(ip_addr) = get_address(rr) {
+                                    slist.insert(ns_name.clone(), ip_addr, 0);
+                                }
+                            }
+                        }
+                    }
+                    if slist.len() > 0 {
+                        return (zone, slist);
+                    }
+                }
+                current = zone.parent();
+            }
+        }
+
+        let mut slist = Slist::new();
+        slist.set_zone_name_equivalent(0);
+        for (name, ip_addr) in self.config.get_root_hints() {
+            slist.insert(name, ip_addr, 0);
+        }
+        (DomainName::new_from_str("."), slist)
+    }
+
+    /// Looks for records of the given type owned by `name` in the answer cache.
+    fn get_cached_rrs(&self, name: &DomainName, rrtype: Rrtype, rclass: Rclass) -> Option<Vec<ResourceRecord>> {
+        if !self.config.is_cache_enabled() {
+            return None;
+        }
+        let mut cache = self.cache.lock().unwrap();
+        cache.timeout();
+        cache.get_answer(name.clone(), rrtype, rclass)
+    }
+
+    /// Stores the delegation of a referral in the authority and additional caches.
+    ///
+    /// The NS records are stored in the authority cache under the name of
+    /// the zone, and their glue records in the additional cache under the
+    /// name of each server.
+    fn cache_delegation(&self, ns_rrs: &[ResourceRecord], glue: &[ResourceRecord]) {
+        if !self.config.is_cache_enabled() {
+            return;
+        }
+        let mut cache = self.cache.lock().unwrap();
+        for rr in ns_rrs {
+            cache.add_authority(rr.get_name(), rr.clone(), Some(Rrtype::NS), rr.get_rclass(), None);
+        }
+        for rr in glue {
+            cache.add_additional(rr.get_name(), rr.clone(), Some(rr.get_rtype()), rr.get_rclass(), None);
+        }
+    }
+
+    /// Stores a final response in the cache, unless it is truncated.
+    fn cache_response(&self, response: &DnsMessage) {
+        if !self.config.is_cache_enabled() || response.get_header().get_tc() {
+            return;
+        }
+        let mut cache = self.cache.lock().unwrap();
+        cache.add(response.clone());
+    }
+}
+
+// Getters
+impl RecursiveLookup {
+    /// Returns the state of the request.
+    pub fn get_state_block(&self) -> &StateBlock {
+        &self.state_block
+    }
+}
+
+/// Sends a single non recursive query to the server at `ip_addr`, using the
+/// transport of `config`.
+async fn send_query(
+    config: ResolverConfig,
+    ip_addr: IpAddr,
+    sname: &DomainName,
+    qtype: Rrtype,
+    qclass: Rclass,
+    timeout: Duration,
+) -> Result<DnsMessage, ResolverError> {
+    let query_id: u16 = thread_rng().gen();
+    let mut query = DnsMessage::new_query_message(sname.clone(), qtype, qclass, 0, false, query_id);
+    config.add_edns0_to_message(&mut query);
+
+    let server_info = ServerInfo::new_from_addr_with_default_size(ip_addr, timeout);
+    let lookup_strategy = LookupStrategy::new(query, config);
+    lookup_strategy
+        .transmit_query_to_server(&server_info, timeout)
+        .await
+        .map(|lookup_response| lookup_response.to_dns_msg())
+}
+
+/// Classifies a response to a query for `sname` sent to a server of `zone`.
+fn classify_response(
+    response: &DnsMessage,
+    sname: &DomainName,
+    qtype: Rrtype,
+    zone: &DomainName,
+) -> ResponseKind {
+    let header = response.get_header();
+    match header.get_rcode() {
+        Rcode::NOERROR => {}
+        Rcode::NXDOMAIN => return ResponseKind::Negative,
+        _ => return ResponseKind::Lame,
+    }
+
+    let answer = response.get_answer();
+    if !answer.is_empty() {
+        let mut current = sname.clone();
+        let mut cnames = Vec::new();
+        while cnames.len() <= MAX_CNAME_CHAIN {
+            let matches_question = |rr: &ResourceRecord| {
+                rr.get_name() == current && (qtype == Rrtype::ANY || rr.get_rtype() == qtype)
+            };
+            if answer.iter().any(matches_question) {
+                return ResponseKind::Answer;
+            }
+            let cname = answer
+                .iter()
+                .find(|rr| rr.get_name() == current && rr.get_rtype() == Rrtype::CNAME);
+            match cname.map(|rr| (rr, rr.get_rdata())) {
+                Some((rr, Rdata::CNAME(cname_rdata))) => {
+                    cnames.push(rr.clone());
+                    current = cname_rdata.get_cname();
+                }
+                _ => break,
+            }
+        }
+        if !cnames.is_empty() {
+            return ResponseKind::Cname(current, cnames);
+        }
+        return ResponseKind::Lame;
+    }
+
+    let ns_rrs: Vec<ResourceRecord> = response
+        .get_authority()
+        .into_iter()
+        .filter(|rr| rr.get_rtype() == Rrtype::NS)
+        .collect();
+    if ns_rrs.is_empty() || header.get_aa() {
+        return ResponseKind::Negative;
+    }
+
+    let child_zone = ns_rrs[0].get_name();
+    let is_closer = sname.is_subdomain_of(&child_zone)
+        && child_zone.is_subdomain_of(zone)
+        && child_zone.label_count() > zone.label_count();
+    if !is_closer {
+        return ResponseKind::Lame;
+    }
+    let ns_rrs = ns_rrs.into_iter().filter(|rr| rr.get_name() == child_zone).collect();
+    ResponseKind::Referral(child_zone, ns_rrs)
+}
+
+/// Returns `true` if the response can be analyzed, that is, it is not an
+/// error of the server and it is a response to the question sent.
+fn is_usable(response: &DnsMessage, sname: &DomainName, qtype: Rrtype) -> bool {
+    let question = response.get_question();
+    let rcode = response.get_header().get_rcode();
+    let is_error = !matches!(rcode, Rcode::NOERROR | Rcode::NXDOMAIN);
+    !response.get_header().get_tc() && !is_error && question.get_qname() == *sname && question.get_rrtype() == qtype
+}
+
+/// Returns the glue records of a referral sent by a server of `zone`.
+///
+/// Only address records of the delegated name servers are accepted, and
+/// only when the server is authoritative for their names, so a server
+/// cannot inject addresses for names outside of its zone.
+fn get_glue(response: &DnsMessage, ns_rrs: &[ResourceRecord], zone: &DomainName) -> Vec<ResourceRecord> {
+    let ns_names = get_ns_names(ns_rrs);
+    response
+        .get_additional()
+        .into_iter()
+        .filter(|rr| matches!(rr.get_rtype(), Rrtype::A | Rrtype::AAAA))
+        .filter(|rr| ns_names.contains(&rr.get_name()) && rr.get_name().is_subdomain_of(zone))
+        .collect()
+}
+
+/// Returns the names of the name servers of a set of NS records.
+fn get_ns_names(ns_rrs: &[ResourceRecord]) -> Vec<DomainName> {
+    ns_rrs
+        .iter()
+        .filter_map(|rr| match rr.get_rdata() {
+            Rdata::NS(ns_rdata) => Some(ns_rdata.get_nsdname()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Returns the address of an A or AAAA record.
+fn get_address(rr: &ResourceRecord) -> Option<IpAddr> {
+    match rr.get_rdata() {
+        Rdata::A(a_rdata) => Some(a_rdata.get_address()),
+        Rdata::AAAA(aaaa_rdata) => Some(aaaa_rdata.get_address()),
+        _ => None,
+    }
+}
+
+/// Deletes the element with the given address from the SLIST.
+fn delete_address(slist: &mut Slist, ip_addr: IpAddr) {
+    let ns_list = slist
+        .get_ns_list()
+        .into_iter()
+        .filter(|element| element.get_ip_address() != ip_addr)
+        .collect();
+    slist.set_ns_list(ns_list);
+}
+
+/// Prepends the CNAME records followed to the answer of a response, and sets
+/// its question to the name originally asked for.
+fn with_cname_chain(
+    mut response: DnsMessage,
+    mut cname_chain: Vec<ResourceRecord>,
+    original_sname: &DomainName,
+) -> DnsMessage {
+    let mut question = response.get_question();
+    question.set_qname(original_sname.clone());
+    response.set_question(question);
+
+    cname_chain.extend(response.get_answer());
+    response.set_answer(cname_chain);
+
+    let additional = response
+        .get_additional()
+        .into_iter()
+        .filter(|rr| !matches!(rr.get_rtype(), Rrtype::OPT | Rrtype::TSIG))
+        .collect();
+    response.set_additional(additional);
+    response.update_header_counters();
+    response
+}
+
+#[cfg(test)]
+mod recursive_lookup_test {
+    use super::*;
+    use crate::message::rdata::a_rdata::ARdata;
+    use crate::message::rdata::cname_rdata::CnameRdata;
+    use crate::message::rdata::ns_rdata::NsRdata;
+    use crate::message::rdata::soa_rdata::SoaRdata;
+
+    fn rr(name: &str, rdata: Rdata) -> ResourceRecord {
+        let mut rr = ResourceRecord::new(rdata);
+        rr.set_name(DomainName::new_from_str(name));
+        rr.set_rclass(Rclass::IN);
+        rr.set_ttl(3600);
+        rr
+    }
+
+    fn ns_rr(name: &str, nsdname: &str) -> ResourceRecord {
+        let mut ns_rdata = NsRdata::new();
+        ns_rdata.set_nsdname(DomainName::new_from_str(nsdname));
+        rr(name, Rdata::NS(ns_rdata))
+    }
+
+    fn a_rr(name: &str, address: &str) -> ResourceRecord {
+        let address: IpAddr = address.parse().unwrap();
+        rr(name, Rdata::A(ARdata::new_from_addr(address)))
+    }
+
+    fn cname_rr(name: &str, cname: &str) -> ResourceRecord {
+        let mut cname_rdata = CnameRdata::new();
+        cname_rdata.set_cname(DomainName::new_from_str(cname));
+        rr(name, Rdata::CNAME(cname_rdata))
+    }
+
+    fn response(qname: &str, qtype: Rrtype, rcode: Rcode, aa: bool) -> DnsMessage {
+        let mut response = DnsMessage::new_query_message(
+            DomainName::new_from_str(qname), qtype, Rclass::IN, 0, false, 1,
+        );
+        let mut header = response.get_header();
+        header.set_qr(true);
+        header.set_aa(aa);
+        header.set_rcode(rcode);
+        response.set_header(header);
+        response
+    }
+
+    fn recursive_lookup(config: ResolverConfig) -> RecursiveLookup {
+        let query = DnsMessage::new_query_message(
+            DomainName::new_from_str("www.example.com"), Rrtype::A, Rclass::IN, 0, true, 1,
+        );
+        RecursiveLookup::new(query, config, Arc::new(Mutex::new(ResolverCache::new(None))))
+    }
+
+    #[test]
+    fn classify_answer() {
+        let sname = DomainName::new_from_str("www.example.com");
+        let mut msg = response("www.example.com", Rrtype::A, Rcode::NOERROR, true);
+        msg.set_answer(vec![a_rr("www.example.com", "192.0.2.1")]);
+
+        let kind = classify_response(&msg, &sname, Rrtype::A, &DomainName::new_from_str("example.com"));
+
+        assert_eq!(kind, ResponseKind::Answer);
+    }
+
+    #[test]
+    fn classify_cname_in_same_response() {
+        let sname = DomainName::new_from_str("www.example.com");
+        let mut msg = response("www.example.com", Rrtype::A, Rcode::NOERROR, true);
+        msg.set_answer(vec![
+            cname_rr("www.example.com", "web.example.com"),
+            a_rr("web.example.com", "192.0.2.1"),
+        ]);
+
+        let kind = classify_response(&msg, &sname, Rrtype::A, &DomainName::new_from_str("example.com"));
+
+        assert_eq!(kind, ResponseKind::Answer);
+    }
+
+    #[test]
+    fn classify_cname_to_other_zone() {
+        let sname = DomainName::new_from_str("www.example.com");
+        let cname = cname_rr("www.example.com", "www.example.net");
+        let mut msg = response("www.example.com", Rrtype::A, Rcode::NOERROR, true);
+        msg.set_answer(vec![cname.clone()]);
+
+        let kind = classify_response(&msg, &sname, Rrtype::A, &DomainName::new_from_str("example.com"));
+
+        assert_eq!(kind, ResponseKind::Cname(DomainName::new_from_str("www.example.net"), vec![cname]));
+    }
+
+    #[test]
+    fn classify_referral() {
+        let sname = DomainName::new_from_str("www.example.com");
+        let ns = ns_rr("example.com", "ns1.example.com");
+        let mut msg = response("www.example.com", Rrtype::A, Rcode::NOERROR, false);
+        msg.set_authority(vec![ns.clone()]);
+
+        let kind = classify_response(&msg, &sname, Rrtype::A, &DomainName::new_from_str("com"));
+
+        assert_eq!(kind, ResponseKind::Referral(DomainName::new_from_str("example.com"), vec![ns]));
+    }
+
+    #[test]
+    fn classify_upward_referral_is_lame() {
+        let sname = DomainName::new_from_str("www.example.com");
+        let mut msg = response("www.example.com", Rrtype::A, Rcode::NOERROR, false);
+        msg.set_authority(vec![ns_rr("com", "a.gtld-servers.net")]);
+
+        let kind = classify_response(&msg, &sname, Rrtype::A, &DomainName::new_from_str("example.com"));
+
+        assert_eq!(kind, ResponseKind::Lame);
+    }
+
+    #[test]
+    fn classify_negative_and_errors() {
+        let sname = DomainName::new_from_str("www.example.com");
+        let zone = DomainName::new_from_str("example.com");
+
+        let nxdomain = response("www.example.com", Rrtype::A, Rcode::NXDOMAIN, true);
+        assert_eq!(classify_response(&nxdomain, &sname, Rrtype::A, &zone), ResponseKind::Negative);
+
+        let mut nodata = response("www.example.com", Rrtype::A, Rcode::NOERROR, true);
+        nodata.set_authority(vec![rr("example.com", Rdata::SOA(SoaRdata::new()))]);
+        assert_eq!(classify_response(&nodata, &sname, Rrtype::A, &zone), ResponseKind::Negative);
+
+        let servfail = response("www.example.com", Rrtype::A, Rcode::SERVFAIL, false);
+        assert_eq!(classify_response(&servfail, &sname, Rrtype::A, &zone), ResponseKind::Lame);
+    }
+
+    #[test]
+    fn glue_outside_of_zone_is_ignored() {
+        let ns_rrs = vec![
+            ns_rr("example.com", "ns1.example.com"),
+            ns_rr("example.com", "ns.example.net"),
+        ];
+        let mut msg = response("www.example.com", Rrtype::A, Rcode::NOERROR, false);
+        msg.set_authority(ns_rrs.clone());
+        msg.set_additional(vec![
+            a_rr("ns1.example.com", "192.0.2.53"),
+            a_rr("ns.example.net", "198.51.100.53"),
+            a_rr("www.example.com", "203.0.113.1"),
+        ]);
+
+        let glue = get_glue(&msg, &ns_rrs, &DomainName::new_from_str("com"));
+
+        assert_eq!(glue, vec![a_rr("ns1.example.com", "192.0.2.53")]);
+    }
+
+    #[test]
+    fn find_best_servers_from_root_hints() {
+        let mut config = ResolverConfig::default();
+        let root = (DomainName::new_from_str("a.root-servers.net"), "198.41.0.4".parse().unwrap());
+        config.set_root_hints(vec![root]);
+        let lookup = recursive_lookup(config);
+
+        let (zone, slist) = lookup.find_best_servers(&DomainName::new_from_str("www.example.com"), Rclass::IN);
+
+        assert!(zone.is_root());
+        assert_eq!(slist.len(), 1);
+        assert_eq!(slist.get_zone_name_equivalent(), 0);
+        assert_eq!(slist.get_first().get_ip_address(), "198.41.0.4".parse::<IpAddr>().unwrap());
+    }
+
+    #[test]
+    fn find_best_servers_from_cached_delegation() {
+        let lookup = recursive_lookup(ResolverConfig::default());
+        let ns_rrs = vec![ns_rr("example.com", "ns1.example.com"), ns_rr("example.com", "ns2.example.com")];
+        let glue = vec![a_rr("ns1.example.com", "192.0.2.53")];
+
+        lookup.cache_delegation(&ns_rrs, &glue);
+        let (zone, slist) = lookup.find_best_servers(&DomainName::new_from_str("www.example.com"), Rclass::IN);
+
+        assert_eq!(zone, DomainName::new_from_str("example.com"));
+        assert_eq!(slist.len(), 1);
+        assert_eq!(slist.get_zone_name_equivalent(), 2);
+        assert_eq!(slist.get_first().get_domain_name(), DomainName::new_from_str("ns1.example.com"));
+
+        let mut cache = lookup.cache.lock().unwrap();
+        let cached_ns = cache.get_authority(DomainName::new_from_str("example.com"), Rrtype::NS, Rclass::IN);
+        assert_eq!(cached_ns.unwrap().len(), 2);
+        let cached_glue = cache.get_additional(DomainName::new_from_str("ns1.example.com"), Rrtype::A, Rclass::IN);
+        assert_eq!(cached_glue.unwrap(), glue);
+    }
+
+    #[test]
+    fn delegation_without_addresses_falls_back_to_parent() {
+        let lookup = recursive_lookup(ResolverConfig::default());
+        lookup.cache_delegation(&[ns_rr("com", "a.gtld-servers.net")], &[a_rr("a.gtld-servers.net", "192.5.6.30")]);
+        lookup.cache_delegation(&[ns_rr("example.com", "ns.example.net")], &[]);
+
+        let (zone, slist) = lookup.find_best_servers(&DomainName::new_from_str("www.example.com"), Rclass::IN);
+
+        assert_eq!(zone, DomainName::new_from_str("com"));
+        assert_eq!(slist.get_first().get_ip_address(), "192.5.6.30".parse::<IpAddr>().unwrap());
+    }
+
+    #[tokio::test]
+    async fn slist_from_referral_uses_glue() {
+        let mut lookup = recursive_lookup(ResolverConfig::default());
+        let zone = DomainName::new_from_str("example.com");
+        let ns_rrs = vec![ns_rr("example.com", "ns1.example.com")];
+        let glue = vec![a_rr("ns1.example.com", "192.0.2.53")];
+
+        let slist = lookup.slist_from_referral(&zone, &ns_rrs, &glue, Rclass::IN, 0).await.unwrap();
+
+        assert_eq!(slist.len(), 1);
+        assert_eq!(slist.get_zone_name_equivalent(), 2);
+        assert_eq!(lookup.get_state_block().get_request_global_counter(), 30);
+    }
+
+    #[tokio::test]
+    async fn slist_from_referral_without_reachable_servers() {
+        let mut lookup = recursive_lookup(ResolverConfig::default());
+        let zone = DomainName::new_from_str("example.com");
+        let ns_rrs = vec![ns_rr("example.com", "ns1.example.com")];
+
+        let result = lookup.slist_from_referral(&zone, &ns_rrs, &[], Rclass::IN, 0).await;
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn request_global_counter_is_enforced() {
+        let mut config = ResolverConfig::default();
+        config.set_global_retransmission_limit(0);
+        let mut lookup = recursive_lookup(config);
+
+        let result = lookup.run().await;
+
+        assert!(matches!(result, Err(ResolverError::RetriesLimitExceeded)));
+    }
+
+    #[tokio::test]
+    async fn answer_from_cache_follows_cname() {
+        let lookup_config = ResolverConfig::default();
+        let mut lookup = recursive_lookup(lookup_config);
+        {
+            let mut cache = lookup.cache.lock().unwrap();
+            let cname = cname_rr("www.example.com", "www.example.net");
+            cache.add_answer(DomainName::new_from_str("www.example.com"), cname, Some(Rrtype::A), Rclass::IN, None);
+            let a = a_rr("www.example.net", "192.0.2.1");
+            cache.add_answer(DomainName::new_from_str("www.example.net"), a, Some(Rrtype::A), Rclass::IN, None);
+        }
+
+        let response = lookup.run().await.unwrap().to_dns_msg();
+
+        assert_eq!(response.get_question().get_qname(), DomainName::new_from_str("www.example.com"));
+        assert_eq!(response.get_answer().len(), 2);
+        assert_eq!(response.get_answer()[0].get_rtype(), Rrtype::CNAME);
+        assert_eq!(response.get_answer()[1].get_name(), DomainName::new_from_str("www.example.net"));
+        assert!(response.get_header().get_ra());
+        assert_eq!(lookup.get_state_block().get_request_global_counter(), 30);
+    }
+}
@@ -65,6 +65,7 @@ impl From<ClientError> for ResolverError {
             ClientError::Refused(err) => ResolverError::Parse(err.to_string()),
             ClientError::ResponseError(err) => ResolverError::Parse(err.to_string()),
             ClientError::TemporaryError(err) => ResolverError::Parse(err.to_string()),
+            ClientError::Doq(code) => ResolverError::Io(std::io::Error::other(format!("DoQ error: {}", code))),
         }
     }
 }
@@ -0,0 +1,63 @@
+use crate::domain_name::DomainName;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// Root name servers with their IPv4 and IPv6 addresses.
+///
+/// Taken from the `named.root` file published by IANA
+/// (https://www.internic.net/domain/named.root).
+const ROOT_SERVERS: [(&str, [u8; 4], [u16; 8]); 13] = [
+    ("a.root-servers.net", [198, 41, 0, 4], [0x2001, 0x503, 0xba3e, 0, 0, 0, 0x2, 0x30]),
+    ("b.root-servers.net", [170, 247, 170, 2], [0x2801, 0x1b8, 0x10, 0, 0, 0, 0, 0xb]),
+    ("c.root-servers.net", [192, 33, 4, 12], [0x2001, 0x500, 0x2, 0, 0, 0, 0, 0xc]),
+    ("d.root-servers.net", [199, 7, 91, 13], [0x2001, 0x500, 0x2d, 0, 0, 0, 0, 0xd]),
+    ("e.root-servers.net", [192, 203, 230, 10], [0x2001, 0x500, 0xa8, 0, 0, 0, 0, 0xe]),
+    ("f.root-servers.net", [192, 5, 5, 241], [0x2001, 0x500, 0x2f, 0, 0, 0, 0, 0xf]),
+    ("g.root-servers.net", [192, 112, 36, 4], [0x2001, 0x500, 0x12, 0, 0, 0, 0, 0xd0d]),
+    ("h.root-servers.net", [198, 97, 190, 53], [0x2001, 0x500, 0x1, 0, 0, 0, 0, 0x53]),
+    ("i.root-servers.net", [192, 36, 148, 17], [0x2001, 0x7fe, 0, 0, 0, 0, 0, 0x53]),
+    ("j.root-servers.net", [192, 58, 128, 30], [0x2001, 0x503, 0xc27, 0, 0, 0, 0x2, 0x30]),
+    ("k.root-servers.net", [193, 0, 14, 129], [0x2001, 0x7fd, 0, 0, 0, 0, 0, 0x1]),
+    ("l.root-servers.net", [199, 7, 83, 42], [0x2001, 0x500, 0x9f, 0, 0, 0, 0, 0x42]),
+    ("m.root-servers.net", [202, 12, 27, 33], [0x2001, 0xdc3, 0, 0, 0, 0, 0, 0x35]),
+];
+
+/// Returns the root hints used to start a recursive resolution.
+///
+/// [RFC 1034]: https://datatracker.ietf.org/doc/html/rfc1034#section-5.3.2
+///
+/// If there are no cached name servers closer to the name being resolved,
+/// the resolver starts from the servers of the root zone. The IPv4
+/// addresses are listed before the IPv6 ones, so hosts without IPv6
+/// connectivity do not waste their first attempts.
+///
+/// # Example
+/// ```
+/// let hints = root_hints();
+/// assert_eq!(hints.len(), 26);
+/// ```
+pub fn root_hints() -> Vec<(DomainName, IpAddr)> {
+    let ipv4 = ROOT_SERVERS.iter().map(|(name, ipv4, _)| {
+        (DomainName::new_from_str(name), IpAddr::V4(Ipv4Addr::from(*ipv4)))
+    });
+    let ipv6 = ROOT_SERVERS.iter().map(|(name, _, ipv6)| {
+        (DomainName::new_from_str(name), IpAddr::V6(Ipv6Addr::from(*ipv6)))
+    });
+
+    ipv4.chain(ipv6).collect()
+}
+
+#[cfg(test)]
+mod root_hints_test {
+    use super::*;
+
+    #[test]
+    fn root_hints_ipv4_first() {
+        let hints = root_hints();
+
+        assert_eq!(hints.len(), 26);
+        assert!(hints[..13].iter().all(|(_, ip)| ip.is_ipv4()));
+        assert!(hints[13..].iter().all(|(_, ip)| ip.is_ipv6()));
+        assert_eq!(hints[0], (DomainName::new_from_str("a.root-servers.net"), "198.41.0.4".parse().unwrap()));
+        assert_eq!(hints[25], (DomainName::new_from_str("m.root-servers.net"), "2001:dc3::35".parse().unwrap()));
+    }
+}
@@ -1,12 +1,24 @@
-use crate::client::client_connection::ClientConnection;
+use crate::client::client_connection::{ConnectionProtocol, DNS_PORT};
+use crate::client::dns_transport::DnsTransport;
+use crate::client::https_connection::{ClientHTTPSConnection, HTTPS_PORT};
+use crate::client::quic_connection::{ClientQUICConnection, DOQ_PORT};
+use crate::client::tls_config::TlsConfig;
+use crate::client::tls_connection::{ClientTLSConnection, DOT_PORT};
 use crate::client::tcp_connection::ClientTCPConnection;
 use crate::client::udp_connection::ClientUDPConnection;
-use std::net::IpAddr;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use tokio::time::Duration;
 
+/// Payload size of the servers created without one.
+const DEFAULT_PAYLOAD_SIZE: usize = 512;
 
 ///This struscture is used to represent the information of a server.
-
-#[derive(Clone, Debug, PartialEq, Eq)]
+///
+/// The queries are sent with the transport set for their protocol with
+/// `set_transport`, or else with a connection of the crate built from the
+/// settings of the server, see `get_transport`.
+#[derive(Clone, Debug)]
 pub struct ServerInfo {
     //The IP address of the server.
     ip_addr: IpAddr,
@@ -18,40 +30,65 @@ pub struct ServerInfo {
     key: String,
     // The algorithm of the server.
     algorithm: String,
-    //UDP connection
-    udp_connection: ClientUDPConnection,
-    //TCP connection
-    tcp_connection: ClientTCPConnection,
+    //Timeout of the connections built for the server.
+    timeout: Duration,
+    //Payload size of the UDP and TCP connections built for the server.
+    payload_size: usize,
+    //TLS settings for DNS over TLS, HTTPS and QUIC
+    tls_config: TlsConfig,
+    //Local address the queries are sent from, chosen by the system if None.
+    bind_addr: Option<SocketAddr>,
+    //Transports set for the server, by protocol.
+    transports: Vec<(ConnectionProtocol, Arc<dyn DnsTransport>)>,
+}
+
+impl PartialEq for ServerInfo {
+    /// Transports are equal only if they are the same object.
+    fn eq(&self, other: &Self) -> bool {
+        self.ip_addr == other.ip_addr
+            && self.port == other.port
+            && self.tsig == other.tsig
+            && self.key == other.key
+            && self.algorithm == other.algorithm
+            && self.timeout == other.timeout
+            && self.payload_size == other.payload_size
+            && self.tls_config == other.tls_config
+            && self.bind_addr == other.bind_addr
+            && self.transports.len() == other.transports.len()
+            && self.transports.iter().zip(other.transports.iter()).all(
+                |((protocol, transport), (other_protocol, other_transport))| {
+                    protocol == other_protocol && Arc::ptr_eq(transport, other_transport)
+                })
+    }
 }
 
+impl Eq for ServerInfo {}
+
 impl ServerInfo {
     /// Create a new `ServerInfo` instance.
+    ///
+    /// The UDP and TCP connections are set to use `port` and become the
+    /// transports of the server for their protocols, used as they are.
     pub fn new(ip_addr: IpAddr, port: u16, key: String, algorithm: String, 
-        udp_connection: ClientUDPConnection, tcp_connection: ClientTCPConnection) -> ServerInfo {
-        ServerInfo {
-            ip_addr,
-            port,
-            tsig: false,
-            key,
-            algorithm,
-            udp_connection,
-            tcp_connection,
-        }
-    }
-
+        mut udp_connection: ClientUDPConnection, mut tcp_connection: ClientTCPConnection) -> ServerInfo {
+        udp_connection.set_port(port);
+        tcp_connection.set_port(port);
+        let mut server_info = ServerInfo::new_from_addr_with_default_size(ip_addr, udp_connection.get_timeout());
+        server_info.port = port;
+        server_info.key = key;
+        server_info.algorithm = algorithm;
+        server_info.set_transport(ConnectionProtocol::UDP, Arc::new(udp_connection));
+        server_info.set_transport(ConnectionProtocol::TCP, Arc::new(tcp_connection));
+        server_info
+    }
+
+    /// Create a new `ServerInfo` on the standard DNS port, whose UDP and
+    /// TCP transports are the given connections, used as they are.
     pub fn new_with_ip(ip_addr: IpAddr, udp_connection: ClientUDPConnection, tcp_connection: ClientTCPConnection) -> ServerInfo {
-        let port = 53;
-        let key = String::from("");
-        let algorithm = String::from("");
-        ServerInfo {
-            ip_addr,
-            port,
-            tsig: false,
-            key,
-            algorithm,
-            udp_connection,
-            tcp_connection,
-        }
+        let mut server_info = ServerInfo::new_from_addr_with_default_size(ip_addr, udp_connection.get_timeout());
+        server_info.set_transport(ConnectionProtocol::UDP, Arc::new(udp_connection));
+        server_info.set_transport(ConnectionProtocol::TCP, Arc::new(tcp_connection));
+        server_info
     }
 
 
@@ -59,33 +96,29 @@ impl ServerInfo {
         let port = 53;
         let key = String::from("");
         let algorithm = String::from("");
-        let udp_connection = ClientUDPConnection::new(ip_addr, timeout, buf_size);
-        let tcp_connection = ClientTCPConnection::new(ip_addr, timeout, buf_size);
         ServerInfo {
             ip_addr,
             port,
             tsig: false,
             key,
             algorithm,
-            udp_connection,
-            tcp_connection,
+            timeout,
+            payload_size: buf_size,
+            tls_config: TlsConfig::default(),
+            bind_addr: None,
+            transports: Vec::new(),
         }
     }
     pub fn new_from_addr_with_default_size(ip_addr: IpAddr, timeout: tokio::time::Duration) -> ServerInfo {
-        let port = 53;
-        let key = String::from("");
-        let algorithm = String::from("");
-        let udp_connection = ClientUDPConnection::new_default(ip_addr, timeout);
-        let tcp_connection = ClientTCPConnection::new_default(ip_addr, timeout);
-        ServerInfo {
-            ip_addr,
-            port,
-            tsig: false,
-            key,
-            algorithm,
-            udp_connection,
-            tcp_connection,
-        }
+        ServerInfo::new_from_addr(ip_addr, timeout, DEFAULT_PAYLOAD_SIZE)
+    }
+
+    /// Creates a `ServerInfo` for a server listening on `addr`, which may
+    /// use any port.
+    pub fn new_from_socket_addr(addr: SocketAddr, timeout: tokio::time::Duration) -> ServerInfo {
+        let mut server_info = ServerInfo::new_from_addr_with_default_size(addr.ip(), timeout);
+        server_info.set_port(addr.port());
+        server_info
     }
 
     /// Function to enable tsig.
@@ -116,9 +149,45 @@ impl ServerInfo {
 
     /// Implements set the port of the server.
     /// param port: u16
+    ///
+    /// The connections built for the server use the new port, see
+    /// `get_socket_addr_for`.
     pub fn set_port(&mut self, port: u16) {
         self.port = port;
     }
+
+    /// Get the address and port of the server.
+    pub fn get_socket_addr(&self) -> SocketAddr {
+        SocketAddr::new(self.ip_addr, self.port)
+    }
+
+    /// Get the address the queries with `protocol` are sent to.
+    ///
+    /// A server on the standard DNS port is expected to offer the
+    /// encrypted protocols on their own standard ports, 853 for DNS over
+    /// TLS and QUIC and 443 for DNS over HTTPS. A server set to any other
+    /// port is reached on that port with every protocol.
+    pub fn get_socket_addr_for(&self, protocol: ConnectionProtocol) -> SocketAddr {
+        let port = match protocol {
+            _ if self.port != DNS_PORT => self.port,
+            ConnectionProtocol::TLS => DOT_PORT,
+            ConnectionProtocol::QUIC => DOQ_PORT,
+            ConnectionProtocol::HTTPS => HTTPS_PORT,
+            _ => self.port,
+        };
+        SocketAddr::new(self.ip_addr, port)
+    }
+
+    /// Get the local address the queries are sent from.
+    pub fn get_bind_addr(&self) -> Option<SocketAddr> {
+        self.bind_addr
+    }
+
+    /// Set the local address the queries are sent from, for every
+    /// connection built for the server. With `None` the system chooses it.
+    pub fn set_bind_addr(&mut self, bind_addr: Option<SocketAddr>) {
+        self.bind_addr = bind_addr;
+    }
     
     /// Get the tsig of the server.
     pub fn get_tsig(&self) -> bool {
@@ -147,36 +216,107 @@ impl ServerInfo {
         self.algorithm = algorithm;
     }
 
-    /// Get the UDP connection of the server.
-    /// return the UDP connection
-    pub fn get_udp_connection(&self) -> &ClientUDPConnection {
-        &self.udp_connection
+    /// Get the timeout of the connections built for the server.
+    pub fn get_timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Set the timeout of the connections built for the server.
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    /// Get the payload size of the UDP and TCP connections built for the
+    /// server.
+    pub fn get_payload_size(&self) -> usize {
+        self.payload_size
+    }
+
+    /// Set the payload size of the UDP and TCP connections built for the
+    /// server.
+    pub fn set_payload_size(&mut self, payload_size: usize) {
+        self.payload_size = payload_size;
     }
 
-    /// Set the UDP connection of the server.
-    /// param udp_connection: ClientUDPConnection
-    pub fn set_udp_connection(&mut self, udp_connection: ClientUDPConnection) {
-        self.udp_connection = udp_connection;
+    /// Get the TLS settings used for DNS over TLS, HTTPS and QUIC.
+    /// return the TLS settings
+    pub fn get_tls_config(&self) -> &TlsConfig {
+        &self.tls_config
     }
 
-    /// Get the TCP connection of the server.
-    /// return the TCP connection
-    pub fn get_tcp_connection(&self) -> &ClientTCPConnection {
-        &self.tcp_connection
+    /// Set the TLS settings used for DNS over TLS, HTTPS and QUIC.
+    /// param tls_config: TlsConfig
+    pub fn set_tls_config(&mut self, tls_config: TlsConfig) {
+        self.tls_config = tls_config;
     }
 
-    /// Set the TCP connection of the server.
-    /// param tcp_connection: ClientTCPConnection
-    pub fn set_tcp_connection(&mut self, tcp_connection: ClientTCPConnection) {
-        self.tcp_connection = tcp_connection;
+    /// Get the transport used to send queries with `protocol`.
+    ///
+    /// This is the transport set for the protocol with `set_transport`, or
+    /// else the connection of the crate for it, built from the address,
+    /// bind address, timeout, payload size and TLS settings of the server.
+    /// Returns `None` if there is no connection for the protocol.
+    pub fn get_transport(&self, protocol: ConnectionProtocol) -> Option<Arc<dyn DnsTransport>> {
+        if let Some((_, transport)) = self.transports.iter().find(|(transport_protocol, _)| *transport_protocol == protocol) {
+            return Some(transport.clone());
+        }
+
+        let socket_addr = self.get_socket_addr_for(protocol);
+        let transport: Arc<dyn DnsTransport> = match protocol {
+            ConnectionProtocol::UDP => {
+                let mut udp_connection = ClientUDPConnection::new(self.ip_addr, self.timeout, self.payload_size);
+                udp_connection.set_socket_addr(socket_addr);
+                udp_connection.set_bind_addr(self.bind_addr);
+                Arc::new(udp_connection)
+            }
+            ConnectionProtocol::TCP => {
+                let mut tcp_connection = ClientTCPConnection::new(self.ip_addr, self.timeout, self.payload_size);
+                tcp_connection.set_socket_addr(socket_addr);
+                tcp_connection.set_bind_addr(self.bind_addr);
+                Arc::new(tcp_connection)
+            }
+            ConnectionProtocol::TLS => {
+                let mut tls_connection = ClientTLSConnection::new_default(self.ip_addr, self.timeout);
+                tls_connection.set_socket_addr(socket_addr);
+                tls_connection.set_bind_addr(self.bind_addr);
+                tls_connection.set_tls_config(self.tls_config.clone());
+                Arc::new(tls_connection)
+            }
+            ConnectionProtocol::HTTPS => {
+                let mut https_connection = ClientHTTPSConnection::new_default(self.ip_addr, self.timeout);
+                https_connection.set_socket_addr(socket_addr);
+                https_connection.set_bind_addr(self.bind_addr);
+                https_connection.set_tls_config(self.tls_config.clone());
+                Arc::new(https_connection)
+            }
+            ConnectionProtocol::QUIC => {
+                let mut quic_connection = ClientQUICConnection::new_default(self.ip_addr, self.timeout);
+                quic_connection.set_socket_addr(socket_addr);
+                quic_connection.set_bind_addr(self.bind_addr);
+                quic_connection.set_tls_config(self.tls_config.clone());
+                Arc::new(quic_connection)
+            }
+            ConnectionProtocol::UNKNOWN => return None,
+        };
+        Some(transport)
+    }
+
+    /// Set the transport used to send queries with `protocol`, in place of
+    /// the connection the server would build for it.
+    ///
+    /// The transport is used as it is: the port, bind address and TLS
+    /// settings of the server do not apply to it. It can be one of the
+    /// connections of the crate, for example a `ClientHTTPSConnection` with
+    /// another path, or a transport like a connection pool or a mock.
+    pub fn set_transport(&mut self, protocol: ConnectionProtocol, transport: Arc<dyn DnsTransport>) {
+        self.transports.retain(|(transport_protocol, _)| *transport_protocol != protocol);
+        self.transports.push((protocol, transport));
     }
 
 }
 
 #[cfg(test)]
 mod server_info_tests {
-    use crate::client::client_connection::ClientConnection;
-
     use super::*;
     use std::{net::{IpAddr, Ipv4Addr}, time::Duration};
 
@@ -194,12 +334,58 @@ mod server_info_tests {
         assert_eq!(server_info.get_port(), 53);
         assert_eq!(server_info.get_key(), "key");
         assert_eq!(server_info.get_algorithm(), "algorithm");
-        assert_eq!(server_info.get_udp_connection().get_server_addr(), IpAddr::V4(Ipv4Addr::new(192, 168, 0, 1)));
-        assert_eq!(server_info.get_udp_connection().get_timeout(), Duration::from_secs(100));
-        assert_eq!(server_info.get_tcp_connection().get_server_addr(), IpAddr::V4(Ipv4Addr::new(192, 168, 0, 1)));
-        assert_eq!(server_info.get_tcp_connection().get_timeout(), Duration::from_secs(100));
+        assert_eq!(server_info.get_timeout(), Duration::from_secs(100));
+        assert_eq!(server_info.get_transport(ConnectionProtocol::UDP).unwrap().get_ip(), IpAddr::V4(Ipv4Addr::new(192, 168, 0, 1)));
+        assert_eq!(server_info.get_transport(ConnectionProtocol::TCP).unwrap().get_ip(), IpAddr::V4(Ipv4Addr::new(192, 168, 0, 1)));
     }
     
+    #[test]
+    fn new_from_socket_addr() {
+        let addr: SocketAddr = "[2001:db8::53]:5353".parse().unwrap();
+        let bind_addr: SocketAddr = "[2001:db8::1]:0".parse().unwrap();
+        let mut server_info = ServerInfo::new_from_socket_addr(addr, Duration::from_secs(2));
+
+        assert_eq!(server_info.get_socket_addr(), addr);
+        assert_eq!(server_info.get_socket_addr_for(ConnectionProtocol::UDP), addr);
+        assert_eq!(server_info.get_socket_addr_for(ConnectionProtocol::TCP), addr);
+        assert_eq!(server_info.get_socket_addr_for(ConnectionProtocol::HTTPS), addr);
+        assert_eq!(server_info.get_socket_addr_for(ConnectionProtocol::TLS), addr);
+        assert_eq!(server_info.get_socket_addr_for(ConnectionProtocol::QUIC), addr);
+        assert_eq!(server_info.get_bind_addr(), None);
+
+        server_info.set_bind_addr(Some(bind_addr));
+
+        assert_eq!(server_info.get_bind_addr(), Some(bind_addr));
+    }
+
+    #[test]
+    fn socket_addr_for_standard_port() {
+        let ip_addr = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 53));
+        let server_info = ServerInfo::new_from_addr_with_default_size(ip_addr, Duration::from_secs(2));
+
+        assert_eq!(server_info.get_socket_addr_for(ConnectionProtocol::UDP), SocketAddr::new(ip_addr, 53));
+        assert_eq!(server_info.get_socket_addr_for(ConnectionProtocol::TLS), SocketAddr::new(ip_addr, 853));
+        assert_eq!(server_info.get_socket_addr_for(ConnectionProtocol::QUIC), SocketAddr::new(ip_addr, 853));
+        assert_eq!(server_info.get_socket_addr_for(ConnectionProtocol::HTTPS), SocketAddr::new(ip_addr, 443));
+    }
+
+    #[test]
+    fn set_transport() {
+        let ip_addr = IpAddr::V4(Ipv4Addr::new(192, 168, 0, 1));
+        let timeout = Duration::from_secs(2);
+        let mut server_info = ServerInfo::new_from_addr_with_default_size(ip_addr, timeout);
+        let transport: Arc<dyn DnsTransport> = Arc::new(ClientTCPConnection::new_default(ip_addr, timeout));
+
+        assert!(server_info.get_transport(ConnectionProtocol::UDP).is_some());
+        assert!(server_info.get_transport(ConnectionProtocol::UNKNOWN).is_none());
+
+        server_info.set_transport(ConnectionProtocol::UDP, transport.clone());
+
+        assert!(Arc::ptr_eq(&server_info.get_transport(ConnectionProtocol::UDP).unwrap(), &transport));
+        assert_ne!(server_info, ServerInfo::new_from_addr_with_default_size(ip_addr, timeout));
+        assert_eq!(server_info, server_info.clone());
+    }
+
     #[test]
     fn get_ip_addr() {
         let ip_addr = IpAddr::V4(Ipv4Addr::new(192, 168, 0, 1));
@@ -253,33 +439,19 @@ mod server_info_tests {
     }
 
     #[test]
-    fn get_udp_connection() {
+    fn get_transport_of_new() {
         let ip_addr = IpAddr::V4(Ipv4Addr::new(192, 168, 0, 1));
         let port = 53;
         let key = String::from("key");
         let algorithm = String::from("algorithm");
         let udp_connection = ClientUDPConnection::new_default(ip_addr, Duration::from_secs(100));
-        let tcp_connection = ClientTCPConnection::new_default(ip_addr, Duration::from_secs(100));
-
-        let server_info = ServerInfo::new(ip_addr, port, key, algorithm, udp_connection, tcp_connection);
-
-        assert_eq!(server_info.get_udp_connection().get_server_addr(), IpAddr::V4(Ipv4Addr::new(192, 168, 0, 1)));
-        assert_eq!(server_info.get_udp_connection().get_timeout(), Duration::from_secs(100));
-    }
-
-    #[test]
-    fn get_tcp_connection() {
-        let ip_addr = IpAddr::V4(Ipv4Addr::new(192, 168, 0, 1));
-        let port = 53;
-        let key = String::from("key"); 
-        let algorithm = String::from("algorithm");
-        let udp_connection = ClientUDPConnection::new_default(ip_addr, Duration::from_secs(100));
-        let tcp_connection = ClientTCPConnection::new_default(ip_addr, Duration::from_secs(100));
+        let tcp_connection = ClientTCPConnection::new_default(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), Duration::from_secs(100));
 
         let server_info = ServerInfo::new(ip_addr, port, key, algorithm, udp_connection, tcp_connection);
 
-        assert_eq!(server_info.get_tcp_connection().get_server_addr(), IpAddr::V4(Ipv4Addr::new(192, 168, 0, 1)));
-        assert_eq!(server_info.get_tcp_connection().get_timeout(), Duration::from_secs(100));
+        assert_eq!(server_info.get_transport(ConnectionProtocol::UDP).unwrap().get_ip(), ip_addr);
+        assert_eq!(server_info.get_transport(ConnectionProtocol::TCP).unwrap().get_ip(), IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)));
+        assert_eq!(server_info.get_transport(ConnectionProtocol::TLS).unwrap().get_ip(), ip_addr);
     }
 
     #[test]
@@ -343,53 +515,46 @@ mod server_info_tests {
     }
 
     #[test]
-    fn set_udp_connection() {
+    fn new_from_addr_constructor() {
         let ip_addr = IpAddr::V4(Ipv4Addr::new(192, 168, 0, 1));
-        let port = 53;
-        let key = String::from("key");
-        let algorithm = String::from("algorithm");
-        let udp_connection = ClientUDPConnection::new_default(ip_addr, Duration::from_secs(100));
-        let tcp_connection = ClientTCPConnection::new_default(ip_addr, Duration::from_secs(100));
-        let mut server_info = ServerInfo::new(ip_addr, port, key, algorithm, udp_connection, tcp_connection);
-
-        let new_udp_connection = ClientUDPConnection::new_default(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), Duration::from_secs(200));
-
-        server_info.set_udp_connection(new_udp_connection);
-
-        assert_eq!(server_info.get_udp_connection().get_server_addr(), IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)));
+        let server_info = ServerInfo::new_from_addr_with_default_size(ip_addr, Duration::from_secs(100));
 
+        assert_eq!(server_info.get_ip_addr(), IpAddr::V4(Ipv4Addr::new(192, 168, 0, 1)));
+        assert_eq!(server_info.get_port(), 53);
+        assert_eq!(server_info.get_key(), "");
+        assert_eq!(server_info.get_algorithm(), "");
+        assert_eq!(server_info.get_timeout(), Duration::from_secs(100));
+        assert_eq!(server_info.get_payload_size(), 512);
+        for protocol in [ConnectionProtocol::UDP, ConnectionProtocol::TCP, ConnectionProtocol::HTTPS] {
+            assert_eq!(server_info.get_transport(protocol).unwrap().get_ip(), IpAddr::V4(Ipv4Addr::new(192, 168, 0, 1)));
+        }
     }
 
     #[test]
-    fn set_tcp_connection() {
+    fn set_https_transport() {
         let ip_addr = IpAddr::V4(Ipv4Addr::new(192, 168, 0, 1));
-        let port = 53;
-        let key = String::from("key");
-        let algorithm = String::from("algorithm");
-        let udp_connection = ClientUDPConnection::new_default(ip_addr, Duration::from_secs(100));
-        let tcp_connection = ClientTCPConnection::new_default(ip_addr, Duration::from_secs(100));
-        let mut server_info = ServerInfo::new(ip_addr, port, key, algorithm, udp_connection, tcp_connection);
-
-        let new_tcp_connection = ClientTCPConnection::new_default(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), Duration::from_secs(200));
+        let mut server_info = ServerInfo::new_from_addr_with_default_size(ip_addr, Duration::from_secs(100));
 
-        server_info.set_tcp_connection(new_tcp_connection);
+        let mut https_connection = ClientHTTPSConnection::new_default(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), Duration::from_secs(200));
+        https_connection.set_path("/resolve".to_string());
+        server_info.set_transport(ConnectionProtocol::HTTPS, Arc::new(https_connection));
 
-        assert_eq!(server_info.get_tcp_connection().get_server_addr(), IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)));
+        assert_eq!(server_info.get_transport(ConnectionProtocol::HTTPS).unwrap().get_ip(), IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)));
+        assert_eq!(server_info.get_transport(ConnectionProtocol::TLS).unwrap().get_ip(), ip_addr);
     }
 
     #[test]
-    fn new_from_addr_constructor() {
+    fn set_tls_config() {
         let ip_addr = IpAddr::V4(Ipv4Addr::new(192, 168, 0, 1));
-        let server_info = ServerInfo::new_from_addr_with_default_size(ip_addr, Duration::from_secs(100));
+        let mut server_info = ServerInfo::new_from_addr_with_default_size(ip_addr, Duration::from_secs(100));
 
-        assert_eq!(server_info.get_ip_addr(), IpAddr::V4(Ipv4Addr::new(192, 168, 0, 1)));
-        assert_eq!(server_info.get_port(), 53);
-        assert_eq!(server_info.get_key(), "");
-        assert_eq!(server_info.get_algorithm(), "");
-        assert_eq!(server_info.get_udp_connection().get_server_addr(), IpAddr::V4(Ipv4Addr::new(192, 168, 0, 1)));
-        assert_eq!(server_info.get_udp_connection().get_timeout(), Duration::from_secs(100));
-        assert_eq!(server_info.get_tcp_connection().get_server_addr(), IpAddr::V4(Ipv4Addr::new(192, 168, 0, 1)));
-        assert_eq!(server_info.get_tcp_connection().get_timeout(), Duration::from_secs(100));
+        assert_eq!(server_info.get_tls_config(), &TlsConfig::default());
+
+        let mut tls_config = TlsConfig::new();
+        tls_config.set_auth_name(Some("dns.example.com".to_string()));
+        server_info.set_tls_config(tls_config);
+
+        assert_eq!(server_info.get_tls_config().get_auth_name(), Some("dns.example.com"));
     }
 }
 
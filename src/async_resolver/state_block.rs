@@ -1,4 +1,5 @@
 use tokio::time::Instant;
+use super::resolver_error::ResolverError;
 
 /// This struct represent the state of information of a pending request.
 /// 
@@ -32,11 +33,11 @@ impl StateBlock {
     /// Creates a new StateBlock for a request.
     /// 
     /// # Arguments
-    /// * `timestamp` - A Instant that represents the time the request began.
+    /// * `request_global_limit` - Initial value of the request global counter.
     /// 
     /// # Example
     /// ```
-    /// let state_block = StateBlock::new(Instant::now());
+    /// let state_block = StateBlock::new(30);
     /// ```
     pub fn new(request_global_limit: u32) -> StateBlock {
         StateBlock {
@@ -53,22 +54,42 @@ impl StateBlock {
     /// 
     /// # Example
     /// ```
-    /// let mut state_block = StateBlock::new(Instant::now());
-    /// state_block.decrement_request_global_counter();
+    /// let mut state_block = StateBlock::new(1);
+    /// assert!(state_block.decrement_request_global_counter().is_ok());
+    /// assert!(state_block.decrement_request_global_counter().is_err());
     /// ```
-    pub fn decrement_request_global_counter(&mut self) {
+    pub fn decrement_request_global_counter(&mut self) -> Result<(), ResolverError> {
+        if self.request_global_counter == 0 {
+            return Err(ResolverError::RetriesLimitExceeded);
+        }
         self.request_global_counter -= 1;
-
-        // TODO: Implement the logic to terminate the request if the counter reaches zero.
+        Ok(())
     }
 
     /// Returns a reference to the `timestamp` of the request.
     pub fn get_timestamp(&self) -> &Instant {
-        return &self.timestamp;
+        &self.timestamp
     }
 
     /// Returns a the `request_global_counter` of the request.
     pub fn get_request_global_counter(&self) -> u32 {
-        return self.request_global_counter;
+        self.request_global_counter
+    }
+}
+#[cfg(test)]
+mod state_block_test {
+    use super::*;
+
+    #[test]
+    fn decrement_request_global_counter() {
+        let mut state_block = StateBlock::new(2);
+
+        assert!(state_block.decrement_request_global_counter().is_ok());
+        assert!(state_block.decrement_request_global_counter().is_ok());
+        assert_eq!(state_block.get_request_global_counter(), 0);
+
+        let result = state_block.decrement_request_global_counter();
+        assert!(matches!(result, Err(ResolverError::RetriesLimitExceeded)));
+        assert_eq!(state_block.get_request_global_counter(), 0);
     }
-}
\ No newline at end of file
+}
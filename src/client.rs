@@ -1,54 +1,67 @@
 pub mod client_connection;
+pub mod dns_transport;
 pub mod client_security;
 pub mod tcp_connection;
+pub mod tls_config;
 pub mod tls_connection;
+pub mod pool_connection;
+pub mod https_connection;
+pub mod quic_connection;
 pub mod udp_connection;
 pub mod client_error;
+pub mod response_match;
 
 use crate::message::rrtype::Rrtype;
-use crate::client::client_connection::ClientConnection;
+use crate::client::dns_transport::DnsTransport;
 use crate::message::DnsMessage;
 use crate::domain_name::DomainName;
 
 
 
 use rand::{thread_rng, Rng};
+use std::sync::Arc;
+use tokio::time::Duration;
 
 use self::client_error::ClientError;
+use self::response_match::{apply_0x20, check_response, restore_qname};
 /*
 TODO: caso para recibir truncados (no lo hace ahora)
  */
 
 
 /// Struct that represents a Client dns
-pub struct Client<T>
-    where
-        T: ClientConnection,
-{
-    /// Conection
-    conn: T ,
+pub struct Client {
+    /// Transport the queries are sent with
+    transport: Arc<dyn DnsTransport>,
+    /// Time to wait for each response
+    timeout: Duration,
     /// query dns
     dns_query: DnsMessage,
+    /// Whether the query name is sent in mixed case (draft-vixie-dnsext-dns0x20)
+    use_0x20: bool,
 }
 
-impl <T: ClientConnection> Client<T> {
+impl Client {
 
-    /// Creates a new Client with default values
+    /// Creates a new Client that sends its queries with `transport`,
+    /// waiting `timeout` for each response.
     /// # Example
     /// ```text
     /// let server_addr:IpAddr = IpAddr::V4(Ipv4Addr::new(1, 1, 1, 1));
     /// let timeout: Duration = Duration::from_secs(2);
-    /// let conn_tcp:ClientTCPConnection = ClientConnection::new_default(server_addr,timeout);
-    /// let mut client = Client::new(conn_tcp);
-    /// assert_eq!(client.get_conn().get_server_addr(), server_addr);
+    /// let conn_tcp = ClientTCPConnection::new_default(server_addr,timeout);
+    /// let mut client = Client::new(Arc::new(conn_tcp), timeout);
+    /// assert_eq!(client.get_transport().get_ip(), server_addr);
     /// assert_eq!(client.dns_query.get_question().get_qname().get_name(), String::from(""));
     /// ```
-    pub fn new(conn: T) -> Self {
+    pub fn new(transport: Arc<dyn DnsTransport>, timeout: Duration) -> Self {
 
         
         let client = Client {
-            conn: conn,
+            transport,
+            timeout,
             dns_query:  DnsMessage::new(),
+            use_0x20: false,
         };
 
         client
@@ -59,8 +72,8 @@ impl <T: ClientConnection> Client<T> {
     /// ```text
     /// let server_addr:IpAddr = IpAddr::V4(Ipv4Addr::new(1, 1, 1, 1));
     /// let timeout: Duration = Duration::from_secs(2);
-    /// let conn_tcp:ClientTCPConnection = ClientConnection::new_default(server_addr,timeout);
-    /// let mut client = Client::new(conn_tcp);
+    /// let conn_tcp = ClientTCPConnection::new_default(server_addr,timeout);
+    /// let mut client = Client::new(Arc::new(conn_tcp), timeout);
     /// let dns_query = client.create_dns_query("www.test.com", "A", "IN");
     /// assert_eq!(dns_query.get_qname().get_name(), String::from("www.test.com"));
     /// assert_eq!(dns_query.get_rrtype(), Rtype::A);
@@ -97,49 +110,33 @@ impl <T: ClientConnection> Client<T> {
     /// ```text
     /// let server_addr:IpAddr = IpAddr::V4(Ipv4Addr::new(1, 1, 1, 1));
     /// let timeout: Duration = Duration::from_secs(2);
-    /// let conn_tcp:ClientTCPConnection = ClientConnection::new_default(server_addr,timeout);
-    /// let mut client = Client::new(conn_tcp);
+    /// let conn_tcp = ClientTCPConnection::new_default(server_addr,timeout);
+    /// let mut client = Client::new(Arc::new(conn_tcp), timeout);
     /// let dns_query = client.create_dns_query("www.test.com", "A", "IN");
     /// let dns_response = client.send_query();
-    /// assert_eq!(client.get_conn().get_server_addr(), server_addr);
+    /// assert_eq!(client.get_transport().get_ip(), server_addr);
     /// assert_eq!(dns_response.get_question().get_rrtype(), Rtype::A);
     /// assert_eq!(dns_response.get_question().get_qname().get_name(), String::from("www.test.com"));
     /// ```
     pub async fn send_query(&self) -> Result<DnsMessage, ClientError> {
 
-        let client_query = self.get_dns_query();
-        let conn: &T = &self.get_conn();
-
-        let dns_response: DnsMessage = match conn.send(client_query).await {
-            Ok(response_message) => {
-                match DnsMessage::from_bytes(&response_message) {
-                    Ok(dns_message) => {
-                        // TODO: Verify functionality of this fragment
-                        /*let additional = dns_message.get_additional();
-                        let lenght = additional.len();
-                        let a_r = additional.get(lenght - 1);
-                        match a_r {
-                            Some(a_r) => {
-                                let rdata = a_r.get_rdata();
-                                match rdata {
-                                    Rdata::A(val) => {
-                                        let ipv = val.get_address();
-                                        if ip_addr != ipv{
-                                            return Err(ClientError::Message("The ip address of the server is not the same as the one in the connection."))?;
-                                        }
-                                    },
-                                    _ => {},
-                                }
-                            },
-                            None => {},
-                        }*/
-                        dns_message},
-                    Err(_) => return Err(ClientError::FormatError("The name server was unable to interpret the query."))?,
-                }
-            },
-            Err(client_error) => return  Err(client_error),
+        let mut client_query = self.get_dns_query();
+        if self.use_0x20 {
+            apply_0x20(&mut client_query);
+        }
+        let mut response_message = self.transport.send_query(&client_query, self.timeout, self.use_0x20).await?;
+        let mut dns_response = match DnsMessage::from_bytes(&response_message) {
+            Ok(dns_message) => dns_message,
+            Err(_) => return Err(ClientError::FormatError("The name server was unable to interpret the query."))?,
         };
 
+        // The transports already discard what does not answer the query,
+        // this also covers connections implemented outside of the crate
+        check_response(&client_query, &dns_response, self.use_0x20).map_err(ClientError::FormatError)?;
+        if self.use_0x20 {
+            restore_qname(&self.get_dns_query(), &mut dns_response, &mut response_message);
+        }
+
         Ok(dns_response)
     }
 
@@ -148,11 +145,11 @@ impl <T: ClientConnection> Client<T> {
     /// ```text
     /// let server_addr:IpAddr = IpAddr::V4(Ipv4Addr::new(1, 1, 1, 1));
     /// let timeout: Duration = Duration::from_secs(2);
-    /// let conn_tcp:ClientTCPConnection = ClientConnection::new_default(server_addr,timeout);
-    /// let mut client = Client::new(conn_tcp);
+    /// let conn_tcp = ClientTCPConnection::new_default(server_addr,timeout);
+    /// let mut client = Client::new(Arc::new(conn_tcp), timeout);
     /// let dns_query = client.create_dns_query("www.test.com", "A", "IN");
     /// let dns_response = client.query();
-    /// assert_eq!(client.get_conn().get_server_addr(), server_addr);
+    /// assert_eq!(client.get_transport().get_ip(), server_addr);
     /// assert_eq!(dns_response.get_question().get_rrtype(), Rtype::A);
     /// assert_eq!(dns_response.get_question().get_qname().get_name(), String::from("www.test.com"));
     pub async fn query(&mut self, domain_name: DomainName, rrtype: &str, rclass: &str) -> Result<DnsMessage, ClientError> {
@@ -165,31 +162,47 @@ impl <T: ClientConnection> Client<T> {
 
 }
 
-#[allow(dead_code)]
 //Getters
-impl <T: ClientConnection> Client<T> {
+impl Client {
+
+    pub fn get_transport(&self) -> &Arc<dyn DnsTransport> {
+        &self.transport
+    }
 
-    fn get_conn(&self) -> &T {
-        &self.conn
+    pub fn get_timeout(&self) -> Duration {
+        self.timeout
     }
 
     pub fn get_dns_query(&self)-> DnsMessage {
         return self.dns_query.clone();
     }
+
+    pub fn get_use_0x20(&self) -> bool {
+        self.use_0x20
+    }
 }
 
-#[allow(dead_code)]
 //Setters
-impl <T: ClientConnection> Client<T>{
+impl Client {
+
+    pub fn set_transport(&mut self, transport: Arc<dyn DnsTransport>) {
+        self.transport = transport;
+    }
 
-    fn set_conn(&mut self,conn :T) {
-        self.conn = conn;
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
     }
 
     pub fn set_dns_query(&mut self,dns_query: DnsMessage) {
         self.dns_query = dns_query;
     }
 
+    /// Sets whether the case of the query name is randomized, so that a
+    /// response is only accepted if it echoes the same name, letter by letter.
+    pub fn set_use_0x20(&mut self, use_0x20: bool) {
+        self.use_0x20 = use_0x20;
+    }
+
 }
 
 #[cfg(test)]
@@ -200,7 +213,8 @@ mod client_test {
     use crate::message::rdata::Rdata;
     use crate::domain_name::DomainName;
     use crate::message::rdata::a_rdata::ARdata;
-    use super::{Client, tcp_connection::ClientTCPConnection, client_connection::ClientConnection, udp_connection::ClientUDPConnection};
+    use super::{Client, tcp_connection::ClientTCPConnection, udp_connection::ClientUDPConnection};
+    use std::sync::Arc;
 
     #[tokio::test]
     async fn udp_client_query() {
@@ -208,8 +222,8 @@ mod client_test {
         let server_addr: IpAddr = IpAddr::V4(Ipv4Addr::new(8, 8, 8, 8));
         let timeout: Duration = Duration::from_secs(2);
 
-        let conn_udp:ClientUDPConnection = ClientUDPConnection::new_default(server_addr, timeout);
-        let mut udp_client = Client::new(conn_udp); //se crea un cliente vacio?
+        let conn_udp = ClientUDPConnection::new_default(server_addr, timeout);
+        let mut udp_client = Client::new(Arc::new(conn_udp), timeout); //se crea un cliente vacio?
 
         let mut domain_name = DomainName::new();
 
@@ -250,8 +264,8 @@ mod client_test {
         let server_addr: IpAddr = IpAddr::V4(Ipv4Addr::new(8, 8, 8, 8));
         let timeout: Duration = Duration::from_secs(2);
 
-        let conn_udp:ClientUDPConnection = ClientUDPConnection::new_default(server_addr, timeout);
-        let mut udp_client = Client::new(conn_udp);
+        let conn_udp = ClientUDPConnection::new_default(server_addr, timeout);
+        let mut udp_client = Client::new(Arc::new(conn_udp), timeout);
 
         let mut domain_name = DomainName::new();
         domain_name.set_name(String::from("example.com"));
@@ -278,8 +292,8 @@ mod client_test {
         let server_addr: IpAddr = IpAddr::V4(Ipv4Addr::new(8, 8, 8, 8));
         let timeout: Duration = Duration::from_secs(2);
 
-        let conn_udp:ClientUDPConnection = ClientUDPConnection::new_default(server_addr, timeout);
-        let mut udp_client = Client::new(conn_udp);
+        let conn_udp = ClientUDPConnection::new_default(server_addr, timeout);
+        let mut udp_client = Client::new(Arc::new(conn_udp), timeout);
 
         let mut domain_name = DomainName::new();
         domain_name.set_name(String::from("example.com"));
@@ -306,8 +320,8 @@ mod client_test {
         let server_addr: IpAddr = IpAddr::V4(Ipv4Addr::new(8, 8, 8, 8));
         let timeout: Duration = Duration::from_secs(2);
 
-        let conn_udp:ClientUDPConnection = ClientUDPConnection::new_default(server_addr, timeout);
-        let mut udp_client = Client::new(conn_udp);
+        let conn_udp = ClientUDPConnection::new_default(server_addr, timeout);
+        let mut udp_client = Client::new(Arc::new(conn_udp), timeout);
 
         let mut domain_name = DomainName::new();
         domain_name.set_name(String::from("example.com"));
@@ -334,8 +348,8 @@ mod client_test {
         let server_addr: IpAddr = IpAddr::V4(Ipv4Addr::new(8, 8, 8, 8));
         let timeout: Duration = Duration::from_secs(2);
 
-        let conn_udp:ClientUDPConnection = ClientUDPConnection::new_default(server_addr, timeout);
-        let mut udp_client = Client::new(conn_udp);
+        let conn_udp = ClientUDPConnection::new_default(server_addr, timeout);
+        let mut udp_client = Client::new(Arc::new(conn_udp), timeout);
 
         let mut domain_name = DomainName::new();
         domain_name.set_name(String::from("example.com"));
@@ -362,8 +376,8 @@ mod client_test {
         let server_addr: IpAddr = IpAddr::V4(Ipv4Addr::new(8, 8, 8, 8));
         let timeout: Duration = Duration::from_secs(2);
 
-        let conn_udp:ClientUDPConnection = ClientUDPConnection::new_default(server_addr, timeout);
-        let mut udp_client = Client::new(conn_udp);
+        let conn_udp = ClientUDPConnection::new_default(server_addr, timeout);
+        let mut udp_client = Client::new(Arc::new(conn_udp), timeout);
 
         let mut domain_name = DomainName::new();
         domain_name.set_name(String::from("example.com"));
@@ -390,8 +404,8 @@ mod client_test {
         let server_addr: IpAddr = IpAddr::V4(Ipv4Addr::new(8, 8, 8, 8));
         let timeout: Duration = Duration::from_secs(2);
 
-        let conn_udp:ClientUDPConnection = ClientUDPConnection::new_default(server_addr, timeout);
-        let mut udp_client = Client::new(conn_udp);
+        let conn_udp = ClientUDPConnection::new_default(server_addr, timeout);
+        let mut udp_client = Client::new(Arc::new(conn_udp), timeout);
 
         let mut domain_name = DomainName::new();
         domain_name.set_name(String::from("example.com"));
@@ -418,8 +432,8 @@ mod client_test {
         let server_addr: IpAddr = IpAddr::V4(Ipv4Addr::new(8, 8, 8, 8));
         let timeout: Duration = Duration::from_secs(2);
 
-        let conn_udp:ClientUDPConnection = ClientUDPConnection::new_default(server_addr, timeout);
-        let mut udp_client = Client::new(conn_udp);
+        let conn_udp = ClientUDPConnection::new_default(server_addr, timeout);
+        let mut udp_client = Client::new(Arc::new(conn_udp), timeout);
 
         let mut domain_name = DomainName::new();
 
@@ -446,8 +460,8 @@ mod client_test {
         let server_addr: IpAddr = IpAddr::V4(Ipv4Addr::new(8, 8, 8, 8));
         let timeout: Duration = Duration::from_secs(2);
 
-        let conn_udp:ClientUDPConnection = ClientUDPConnection::new_default(server_addr, timeout);
-        let mut udp_client = Client::new(conn_udp);
+        let conn_udp = ClientUDPConnection::new_default(server_addr, timeout);
+        let mut udp_client = Client::new(Arc::new(conn_udp), timeout);
 
         let mut domain_name = DomainName::new();
 
@@ -474,8 +488,8 @@ mod client_test {
         let server_addr: IpAddr = IpAddr::V4(Ipv4Addr::new(8, 8, 8, 8));
         let timeout: Duration = Duration::from_secs(2);
 
-        let conn_udp:ClientUDPConnection = ClientUDPConnection::new_default(server_addr, timeout);
-        let mut udp_client = Client::new(conn_udp);
+        let conn_udp = ClientUDPConnection::new_default(server_addr, timeout);
+        let mut udp_client = Client::new(Arc::new(conn_udp), timeout);
 
         let mut domain_name = DomainName::new();
 
@@ -506,10 +520,10 @@ mod client_test {
         let server_addr:IpAddr = IpAddr::V4(Ipv4Addr::new(1, 1, 1, 1));
         let timeout: Duration = Duration::from_secs(2);
 
-        let conn_tcp:ClientTCPConnection = ClientConnection::new_default(server_addr,timeout);
+        let conn_tcp = ClientTCPConnection::new_default(server_addr,timeout);
 
         //create client
-        let mut tcp_client = Client::new(conn_tcp);
+        let mut tcp_client = Client::new(Arc::new(conn_tcp), timeout);
 
         //create query
         let mut domain_name = DomainName::new();
@@ -539,9 +553,10 @@ mod client_test {
         let server_addr:IpAddr = IpAddr::V4(Ipv4Addr::new(1, 1, 1, 1));
         let timeout: Duration = Duration::from_secs(2);
 
-        let conn_tcp:ClientTCPConnection = ClientConnection::new_default(server_addr,timeout);
-        let new_client = Client::new(conn_tcp);
-        assert_eq!(new_client.get_conn().get_server_addr(), server_addr);
+        let conn_tcp = ClientTCPConnection::new_default(server_addr,timeout);
+        let new_client = Client::new(Arc::new(conn_tcp), timeout);
+        assert_eq!(new_client.get_transport().get_ip(), server_addr);
+        assert_eq!(new_client.get_timeout(), timeout);
         assert_eq!(new_client.get_dns_query().get_question().get_qname().get_name(), String::from(""));
     }
     
@@ -551,8 +566,8 @@ mod client_test {
         let server_addr:IpAddr = IpAddr::V4(Ipv4Addr::new(1, 1, 1, 1));
         let timeout: Duration = Duration::from_secs(2);
 
-        let conn_udp:ClientUDPConnection = ClientConnection::new_default(server_addr,timeout);
-        let mut new_client = Client::new(conn_udp);
+        let conn_udp = ClientUDPConnection::new_default(server_addr,timeout);
+        let mut new_client = Client::new(Arc::new(conn_udp), timeout);
         let mut domain_name = DomainName::new();
         domain_name.set_name(String::from("www.test.com"));
         let dns_query = new_client.create_dns_query(domain_name, "A", "IN");
@@ -568,8 +583,8 @@ mod client_test {
         let server_addr:IpAddr = IpAddr::V4(Ipv4Addr::new(1, 1, 1, 1));
         let timeout: Duration = Duration::from_secs(2);
 
-        let conn_tcp:ClientTCPConnection = ClientConnection::new_default(server_addr,timeout);
-        let mut new_client = Client::new(conn_tcp);
+        let conn_tcp = ClientTCPConnection::new_default(server_addr,timeout);
+        let mut new_client = Client::new(Arc::new(conn_tcp), timeout);
         let mut domain_name = DomainName::new();
         domain_name.set_name(String::from("www.test.com"));
         let dns_query = new_client.create_dns_query(domain_name, "A", "IN");
@@ -584,8 +599,8 @@ mod client_test {
         let server_addr:IpAddr = IpAddr::V4(Ipv4Addr::new(171, 18, 0, 1));
         let timeout: Duration = Duration::from_secs(2);
 
-        let conn_tcp:ClientTCPConnection = ClientConnection::new_default(server_addr,timeout);
-        let mut new_client = Client::new(conn_tcp);
+        let conn_tcp = ClientTCPConnection::new_default(server_addr,timeout);
+        let mut new_client = Client::new(Arc::new(conn_tcp), timeout);
         let mut domain_name = DomainName::new();
         domain_name.set_name(String::from("www.u-cursos.cl"));
         new_client.create_dns_query(domain_name, "A", "IN");
@@ -599,8 +614,8 @@ mod client_test {
         let server_addr:IpAddr = IpAddr::V4(Ipv4Addr::new(171, 18, 0, 1));
         let timeout: Duration = Duration::from_secs(2);
 
-        let conn_udp:ClientUDPConnection = ClientConnection::new_default(server_addr,timeout);
-        let mut new_client = Client::new(conn_udp);
+        let conn_udp = ClientUDPConnection::new_default(server_addr,timeout);
+        let mut new_client = Client::new(Arc::new(conn_udp), timeout);
         let mut domain_name = DomainName::new();
         domain_name.set_name(String::from("www.u-cursos.cl"));
         new_client.create_dns_query(domain_name, "A", "IN");
@@ -614,13 +629,16 @@ mod client_test {
         let server_addr:IpAddr = IpAddr::V4(Ipv4Addr::new(8, 8, 8, 8));
         let timeout: Duration = Duration::from_secs(2);
 
-        let conn_tcp:ClientTCPConnection = ClientConnection::new_default(server_addr,timeout);
-        let mut new_client = Client::new(conn_tcp);
+        let conn_tcp = ClientTCPConnection::new_default(server_addr,timeout);
+        let mut new_client = Client::new(Arc::new(conn_tcp), timeout);
         let mut domain_name = DomainName::new();
         domain_name.set_name(String::from("?www.u-cursos.cl"));
         let domain_name_copy =domain_name.clone();
         new_client.create_dns_query(domain_name, "A", "IN");
-        let _response = new_client.query(domain_name_copy, "A", "IN").await.unwrap_err();
+        let response = new_client.query(domain_name_copy, "A", "IN").await.unwrap();
+
+        // Any octet is allowed in a label, so the server answers that the name does not exist
+        assert!(response.get_answer().is_empty());
 
         
     }
@@ -631,13 +649,16 @@ mod client_test {
         let server_addr:IpAddr = IpAddr::V4(Ipv4Addr::new(8, 8, 8, 8));
         let timeout: Duration = Duration::from_secs(2);
 
-        let conn_udp:ClientUDPConnection = ClientConnection::new_default(server_addr,timeout);
-        let mut new_client = Client::new(conn_udp);
+        let conn_udp = ClientUDPConnection::new_default(server_addr,timeout);
+        let mut new_client = Client::new(Arc::new(conn_udp), timeout);
         let mut domain_name = DomainName::new();
         domain_name.set_name(String::from("?www.u-cursos.cl"));
         let domain_name_copy =domain_name.clone();
         new_client.create_dns_query(domain_name, "A", "IN");
-        let _response = new_client.query(domain_name_copy, "A", "IN").await.unwrap_err();
+        let response = new_client.query(domain_name_copy, "A", "IN").await.unwrap();
+
+        // Any octet is allowed in a label, so the server answers that the name does not exist
+        assert!(response.get_answer().is_empty());
         
 
         
@@ -649,8 +670,8 @@ mod client_test {
         let server_addr:IpAddr = IpAddr::V4(Ipv4Addr::new(8, 8, 8, 8));
         let timeout: Duration = Duration::from_secs(2);
 
-        let conn_tcp:ClientTCPConnection = ClientConnection::new_default(server_addr,timeout);
-        let mut new_client = Client::new(conn_tcp);
+        let conn_tcp = ClientTCPConnection::new_default(server_addr,timeout);
+        let mut new_client = Client::new(Arc::new(conn_tcp), timeout);
         let mut domain_name = DomainName::new();
         domain_name.set_name(String::from("nonexisten.comt-domain"));
         let response = new_client.query(domain_name, "A", "IN").await.unwrap();
@@ -664,11 +685,13 @@ mod client_test {
         let server_addr:IpAddr = IpAddr::V4(Ipv4Addr::new(8, 8, 8, 8));
         let timeout: Duration = Duration::from_secs(2);
 
-        let conn_tcp:ClientTCPConnection = ClientConnection::new_default(server_addr,timeout);
-        let mut new_client = Client::new(conn_tcp);
+        let conn_tcp = ClientTCPConnection::new_default(server_addr,timeout);
+        let mut new_client = Client::new(Arc::new(conn_tcp), timeout);
         let mut domain_name = DomainName::new();
         domain_name.set_name(String::from("2www.u-cursos.cl"));
-        let _response = new_client.query(domain_name, "A", "IN").await.unwrap_err();
+        let response = new_client.query(domain_name, "A", "IN").await.unwrap();
+
+        assert!(response.get_answer().is_empty());
 
         
     }
@@ -679,11 +702,13 @@ mod client_test {
         let server_addr:IpAddr = IpAddr::V4(Ipv4Addr::new(8, 8, 8, 8));
         let timeout: Duration = Duration::from_secs(2);
 
-        let conn_udp:ClientUDPConnection = ClientConnection::new_default(server_addr,timeout);
-        let mut new_client = Client::new(conn_udp);
+        let conn_udp = ClientUDPConnection::new_default(server_addr,timeout);
+        let mut new_client = Client::new(Arc::new(conn_udp), timeout);
         let mut domain_name = DomainName::new();
         domain_name.set_name(String::from("2www.u-cursos.cl"));
-        let _response = new_client.query(domain_name, "A", "IN").await.unwrap_err();
+        let response = new_client.query(domain_name, "A", "IN").await.unwrap();
+
+        assert!(response.get_answer().is_empty());
 
       
     }
@@ -1,31 +1,46 @@
-use crate::message::DnsMessage;
-use std::net::IpAddr;
-use tokio::time::Duration;
-use super::client_error::ClientError;
-
-
-use async_trait::async_trait;
-
-
-#[async_trait]
-pub trait ClientConnection: Copy {//: 'static + Sized + Send + Sync + Unpin 
-
-    //Creates a ClientConecction 
-    fn new(server_addr:IpAddr,
-        timeout:Duration, payload_size: usize) -> Self;
-
-    fn new_default(server_addr:IpAddr, timeout:Duration) -> Self;
+use std::io::Result as IoResult;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use tokio::net::{TcpSocket, TcpStream};
+
+/// Default port of DNS over UDP and TCP.
+///
+/// [RFC 1035](https://datatracker.ietf.org/doc/html/rfc1035#section-4.2)
+pub const DNS_PORT: u16 = 53;
+
+/// Returns the local address of a socket that sends queries to `server_addr`.
+///
+/// This is `bind_addr` if it is given, otherwise the unspecified address of
+/// the same family as the server, so that IPv6 servers can be reached, with
+/// a port chosen by the system.
+pub fn local_addr_for(server_addr: SocketAddr, bind_addr: Option<SocketAddr>) -> SocketAddr {
+    bind_addr.unwrap_or_else(|| match server_addr {
+        SocketAddr::V4(_) => SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 0),
+        SocketAddr::V6(_) => SocketAddr::new(IpAddr::V6(Ipv6Addr::UNSPECIFIED), 0),
+    })
+}
 
-    //Sends query 
-    async fn send(self, dns_query: DnsMessage) -> Result<Vec<u8>, ClientError>;
-    // async fn send(self, dns_query: DnsMessage) -> Result<(Vec<u8>, IpAddr), ClientError>;
-    fn get_ip(&self) -> IpAddr;
+/// Opens a TCP stream to `server_addr`, from `bind_addr` if it is given.
+pub async fn connect_tcp(server_addr: SocketAddr, bind_addr: Option<SocketAddr>) -> IoResult<TcpStream> {
+    let bind_addr = match bind_addr {
+        Some(bind_addr) => bind_addr,
+        None => return TcpStream::connect(server_addr).await,
+    };
+
+    let socket = match bind_addr {
+        SocketAddr::V4(_) => TcpSocket::new_v4()?,
+        SocketAddr::V6(_) => TcpSocket::new_v6()?,
+    };
+    socket.bind(bind_addr)?;
+    socket.connect(server_addr).await
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ConnectionProtocol {
     UDP,
     TCP,
+    TLS,
+    HTTPS,
+    QUIC,
     UNKNOWN,
 }
 
@@ -36,6 +51,9 @@ impl From<&str> for ConnectionProtocol {
         match conn {
             "UDP" => ConnectionProtocol::UDP,
             "TCP" => ConnectionProtocol::TCP,
+            "TLS" => ConnectionProtocol::TLS,
+            "HTTPS" => ConnectionProtocol::HTTPS,
+            "QUIC" => ConnectionProtocol::QUIC,
             _ => ConnectionProtocol::UNKNOWN,
         }
     } 
@@ -1,5 +1,6 @@
 use std::fmt;
 use std::fmt::Debug;
+use crate::client::quic_connection::DoqErrorCode;
 
 #[derive(thiserror::Error)]
 #[non_exhaustive] 
@@ -123,6 +124,15 @@ pub enum ClientError {
     /// difficult.
     #[error("Temporary Error: {0}")]
     TemporaryError(&'static str),
+
+    /// An error signalled by a DNS over QUIC peer.
+    ///
+    /// [RFC 9250]: https://datatracker.ietf.org/doc/html/rfc9250#section-4.3
+    ///
+    /// The error code is carried when closing the connection, resetting a
+    /// stream or stopping a stream.
+    #[error("DoQ Error: {0}")]
+    Doq(DoqErrorCode),
 }
 
 impl Debug for ClientError {
@@ -138,6 +148,7 @@ impl Debug for ClientError {
             Refused(err) => write!(f, "Refused: {}", err),
             ResponseError(err) => write!(f, "Response with error code {}", err),
             TemporaryError(err) => write!(f, "Temporary Error: {}", err),
+            Doq(err) => write!(f, "DoQ Error: {}", err),
         }
     }
 }
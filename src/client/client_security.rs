@@ -4,11 +4,11 @@ use tokio::time::Duration;
 use super::client_error::ClientError;
 
 use async_trait::async_trait;
-use crate::client::client_connection::ClientConnection;
+use crate::client::dns_transport::DnsTransport;
 use crate::client::tls_connection::ClientTLSConnection;
 
 #[async_trait]
-pub trait ClientSecurity: ClientConnection + Copy {//: 'static + Sized + Send + Sync + Unpin
+pub trait ClientSecurity: DnsTransport + Clone {//: 'static + Sized + Send + Sync + Unpin
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
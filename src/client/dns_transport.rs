@@ -0,0 +1,85 @@
+use crate::message::DnsMessage;
+use super::client_error::ClientError;
+
+use async_trait::async_trait;
+use std::fmt::Debug;
+use std::net::IpAddr;
+use tokio::time::Duration;
+
+/// Transport that sends a query to a name server and returns its response.
+///
+/// It has no constructor and sends with `&self`, so it can be used as
+/// `Arc<dyn DnsTransport>`. Besides the connections of the crate, transports
+/// with settings of their own (connection pools, HTTP clients or mocks) can
+/// be given to a `Client` or to a `ServerInfo` for any upstream.
+#[async_trait]
+pub trait DnsTransport: Debug + Send + Sync {
+    /// Sends the query and returns the response in wire format, waiting at
+    /// most `timeout` for it.
+    ///
+    /// Implementations should only return a response that answers the
+    /// query, see `response_match::check_response`. With `use_0x20` the
+    /// question name was sent in mixed case, and a response that does not
+    /// echo it exactly does not answer the query.
+    async fn send_query(&self, dns_query: &DnsMessage, timeout: Duration, use_0x20: bool) -> Result<Vec<u8>, ClientError>;
+
+    /// Returns the address of the name server.
+    fn get_ip(&self) -> IpAddr;
+}
+
+#[cfg(test)]
+mod dns_transport_test {
+    use super::*;
+    use crate::client::tcp_connection::ClientTCPConnection;
+    use crate::client::udp_connection::ClientUDPConnection;
+    use crate::domain_name::DomainName;
+    use crate::message::rclass::Rclass;
+    use crate::message::rrtype::Rrtype;
+    use std::net::Ipv4Addr;
+    use std::sync::Arc;
+    use tokio::net::UdpSocket;
+
+    #[tokio::test]
+    async fn connections_as_trait_objects() {
+        let server = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let mut conn = ClientUDPConnection::new_default(IpAddr::V4(Ipv4Addr::LOCALHOST), Duration::from_secs(2));
+        conn.set_socket_addr(server.local_addr().unwrap());
+
+        let server_task = tokio::spawn(async move {
+            let mut buf = vec![0u8; 512];
+            let (n, client_addr) = server.recv_from(&mut buf).await.unwrap();
+            let mut response = DnsMessage::from_bytes(&buf[..n]).unwrap();
+            let mut header = response.get_header();
+            header.set_qr(true);
+            response.set_header(header);
+            server.send_to(&response.to_bytes(), client_addr).await.unwrap();
+        });
+
+        let transports: Vec<Arc<dyn DnsTransport>> = vec![
+            Arc::new(conn),
+            Arc::new(ClientTCPConnection::new_default(IpAddr::V4(Ipv4Addr::LOCALHOST), Duration::from_secs(2))),
+        ];
+        let query = DnsMessage::new_query_message(DomainName::new_from_str("example.com"), Rrtype::A, Rclass::IN, 0, false, 3);
+        let response = transports[0].send_query(&query, Duration::from_secs(2), false).await.unwrap();
+
+        assert_eq!(DnsMessage::from_bytes(&response).unwrap().get_query_id(), 3);
+        assert_eq!(transports[1].get_ip(), IpAddr::V4(Ipv4Addr::LOCALHOST));
+        server_task.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn send_query_waits_the_given_timeout() {
+        // The server never answers
+        let server = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let mut conn = ClientUDPConnection::new_default(IpAddr::V4(Ipv4Addr::LOCALHOST), Duration::from_secs(30));
+        conn.set_socket_addr(server.local_addr().unwrap());
+        let transport: Arc<dyn DnsTransport> = Arc::new(conn);
+        let query = DnsMessage::new_query_message(DomainName::new_from_str("example.com"), Rrtype::A, Rclass::IN, 0, false, 3);
+
+        let start = tokio::time::Instant::now();
+        let response = transport.send_query(&query, Duration::from_millis(100), false).await;
+
+        assert!(response.is_err());
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+}
@@ -0,0 +1,563 @@
+use crate::message::DnsMessage;
+use super::client_error::ClientError;
+use super::response_match::match_response;
+use super::client_security::ClientSecurity;
+use async_trait::async_trait;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use bytes::Bytes;
+use http::{Method, Request, StatusCode};
+use std::io::Error as IoError;
+use std::io::ErrorKind;
+use std::net::IpAddr;
+use std::net::SocketAddr;
+use std::str::FromStr;
+use tokio::time::Duration;
+use tokio::time::timeout;
+use tokio_rustls::TlsConnector;
+use crate::client::client_connection::connect_tcp;
+use crate::client::dns_transport::DnsTransport;
+use crate::client::tls_config::TlsConfig;
+
+/// Media type of a DNS message in wire format.
+///
+/// [RFC 8484](https://datatracker.ietf.org/doc/html/rfc8484#section-6)
+pub const DNS_MESSAGE_MEDIA_TYPE: &str = "application/dns-message";
+
+/// Default URI path of the DoH endpoint, the one used by the well known
+/// public resolvers.
+pub const DEFAULT_PATH: &str = "/dns-query";
+
+/// Default port of HTTPS.
+pub const HTTPS_PORT: u16 = 443;
+
+/// ALPN token of HTTP/2.
+///
+/// [RFC 8484](https://datatracker.ietf.org/doc/html/rfc8484#section-5.2)
+///
+/// HTTP/2 is the minimum RECOMMENDED version of HTTP for use with DoH.
+pub const H2_ALPN: &[u8] = b"h2";
+
+/// HTTP method used to send the query.
+///
+/// [RFC 8484](https://datatracker.ietf.org/doc/html/rfc8484#section-4.1)
+///
+/// When the HTTP method is GET, the single variable "dns" is defined as the
+/// content of the DNS request, encoded with base64url. When using the POST
+/// method, the DNS query is included as the message body of the HTTP request,
+/// and the Content-Type request header field indicates the media type of the
+/// message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HttpsMethod {
+    GET,
+    POST,
+}
+
+impl FromStr for HttpsMethod {
+    type Err = &'static str;
+
+    /// Parses the name of the method, in any case.
+    fn from_str(method: &str) -> Result<Self, Self::Err> {
+        match method.to_ascii_uppercase().as_str() {
+            "GET" => Ok(HttpsMethod::GET),
+            "POST" => Ok(HttpsMethod::POST),
+            _ => Err("Error: the HTTPS method must be GET or POST."),
+        }
+    }
+}
+
+/// DNS over HTTPS (DoH) connection, as specified in RFC 8484.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClientHTTPSConnection {
+    /// Client address
+    server_addr: IpAddr,
+    /// TCP port of the DoH server
+    port: u16,
+    /// Local address of the stream, chosen by the system if `None`
+    bind_addr: Option<SocketAddr>,
+    /// Read time timeout
+    timeout: tokio::time::Duration,
+    /// Whether the query name was sent with 0x20 mixed case
+    use_0x20: bool,
+    /// HTTP method used for the query
+    method: HttpsMethod,
+    /// URI path of the DoH endpoint
+    path: String,
+    /// TLS settings: trust anchors, pins, authentication name and client certificate
+    tls_config: TlsConfig,
+}
+
+impl ClientHTTPSConnection {
+    pub fn new_default(server_addr:IpAddr, timeout: Duration) -> Self {
+        ClientHTTPSConnection {
+            server_addr,
+            port: HTTPS_PORT,
+            bind_addr: None,
+            timeout,
+            use_0x20: false,
+            method: HttpsMethod::POST,
+            path: DEFAULT_PATH.to_string(),
+            tls_config: TlsConfig::default(),
+        }
+    }
+
+    /// Sends the query over HTTP/2 and receives the response.
+    ///
+    /// [RFC 8484](https://datatracker.ietf.org/doc/html/rfc8484#section-4.1)
+    ///
+    /// In order to maximize HTTP cache friendliness, DoH clients using media
+    /// formats that include the ID field from the DNS message header, such
+    /// as "application/dns-message", SHOULD use a DNS ID of 0 in every DNS
+    /// request.
+    ///
+    /// The query is sent with ID 0 and the original ID is put back in the
+    /// response, so callers can keep matching responses with their queries.
+    pub async fn send(self, dns_query: DnsMessage) -> Result<Vec<u8>, ClientError> {
+        let query_id = dns_query.get_query_id();
+        let mut doh_query = dns_query.clone();
+        doh_query.set_query_id(0);
+
+        let mut response = timeout(self.timeout, self.exchange(doh_query.to_bytes()))
+            .await
+            .map_err(|_| ClientError::Io(IoError::new(ErrorKind::TimedOut, "HTTPS request timed out")))??;
+
+        if response.len() < 2 {
+            return Err(ClientError::Io(IoError::new(
+                ErrorKind::InvalidData,
+                "HTTPS response is too short to be a DNS message",
+            )));
+        }
+
+        // Each request carries a single query, so a response that does not
+        // answer it can not be discarded to wait for another one.
+        if match_response(&doh_query, &response, self.use_0x20).is_none() {
+            return Err(ClientError::FormatError("The response does not answer the query."));
+        }
+        response[0..2].copy_from_slice(&query_id.to_be_bytes());
+        Ok(response)
+    }
+}
+
+#[async_trait]
+impl DnsTransport for ClientHTTPSConnection {
+    async fn send_query(&self, dns_query: &DnsMessage, timeout: Duration, use_0x20: bool) -> Result<Vec<u8>, ClientError> {
+        let mut conn = self.clone();
+        conn.set_timeout(timeout);
+        conn.set_use_0x20(use_0x20);
+        conn.send(dns_query.clone()).await
+    }
+
+    fn get_ip(&self) -> IpAddr {
+        self.server_addr
+    }
+}
+
+#[async_trait]
+impl ClientSecurity for ClientHTTPSConnection {
+
+
+}
+
+impl ClientHTTPSConnection {
+    /// Builds the HTTP request for the wire format query.
+    ///
+    /// [RFC 8484](https://datatracker.ietf.org/doc/html/rfc8484#section-4.1.1)
+    ///
+    /// The authority of the URI is the authentication domain name of the
+    /// server if it has one, its IP address otherwise.
+    pub fn build_request(&self, query: &[u8]) -> Result<Request<()>, ClientError> {
+        let authority = match self.tls_config.get_auth_name() {
+            Some(auth_name) => format!("{}:{}", auth_name, self.port),
+            None => self.get_socket_addr().to_string(),
+        };
+        let builder = match self.method {
+            HttpsMethod::GET => Request::builder()
+                .method(Method::GET)
+                .uri(format!("https://{}{}?dns={}", authority, self.path, URL_SAFE_NO_PAD.encode(query))),
+            HttpsMethod::POST => Request::builder()
+                .method(Method::POST)
+                .uri(format!("https://{}{}", authority, self.path))
+                .header(http::header::CONTENT_TYPE, DNS_MESSAGE_MEDIA_TYPE)
+                .header(http::header::CONTENT_LENGTH, query.len()),
+        };
+        builder
+            .header(http::header::ACCEPT, DNS_MESSAGE_MEDIA_TYPE)
+            .body(())
+            .map_err(|e| ClientError::Io(IoError::new(ErrorKind::InvalidInput, format!("Invalid HTTPS request: {}", e))))
+    }
+
+    /// Opens the TLS connection, negotiates HTTP/2 and sends
+    /// the query, returning the body of the response.
+    async fn exchange(&self, query: Vec<u8>) -> Result<Vec<u8>, ClientError> {
+        let config = self.tls_config.to_client_config_with_alpn(&[H2_ALPN])?;
+        let server_name = self.tls_config.server_name(self.get_server_addr())?;
+        let connector = TlsConnector::from(config);
+
+        // Connect to the DNS server over TCP
+        let stream = connect_tcp(self.get_socket_addr(), self.bind_addr).await.map_err(ClientError::from)?;
+
+        let tls_stream = connector.connect(server_name, stream).await.map_err(|e| {
+            ClientError::Io(IoError::other(format!("TLS connection error: {}", e)))
+        })?;
+
+        let (send_request, connection) = h2::client::handshake(tls_stream).await.map_err(|e| {
+            ClientError::Io(IoError::other(format!("HTTP/2 handshake error: {}", e)))
+        })?;
+        tokio::spawn(async move {
+            let _ = connection.await;
+        });
+
+        let h2_error = |e: h2::Error| {
+            ClientError::Io(IoError::other(format!("HTTP/2 error: {}", e)))
+        };
+
+        let request = self.build_request(&query)?;
+        let mut send_request = send_request.ready().await.map_err(h2_error)?;
+        let response_future = match self.method {
+            HttpsMethod::GET => send_request.send_request(request, true).map_err(h2_error)?.0,
+            HttpsMethod::POST => {
+                let (response_future, mut send_stream) = send_request.send_request(request, false).map_err(h2_error)?;
+                send_stream.send_data(Bytes::from(query), true).map_err(h2_error)?;
+                response_future
+            }
+        };
+
+        let response = response_future.await.map_err(h2_error)?;
+        if response.status() != StatusCode::OK {
+            return Err(ClientError::Io(IoError::other(
+                format!("HTTPS server answered with status {}", response.status()),
+            )));
+        }
+
+        // RFC 8484 section 4.2: the media type of the body is indicated
+        // in the Content-Type of the response. Anything else, such as the
+        // HTML page of a captive portal, is not a DNS message.
+        let media_type = response
+            .headers()
+            .get(http::header::CONTENT_TYPE)
+            .and_then(|content_type| content_type.to_str().ok())
+            .and_then(|content_type| content_type.split(';').next());
+        if !media_type.is_some_and(|media_type| media_type.trim().eq_ignore_ascii_case(DNS_MESSAGE_MEDIA_TYPE)) {
+            return Err(ClientError::Io(IoError::new(
+                ErrorKind::InvalidData,
+                "HTTPS response is not an application/dns-message",
+            )));
+        }
+
+        let mut body = response.into_body();
+        let mut bytes = Vec::new();
+        while let Some(chunk) = body.data().await {
+            let chunk = chunk.map_err(h2_error)?;
+            let _ = body.flow_control().release_capacity(chunk.len());
+            bytes.extend_from_slice(&chunk);
+            if bytes.len() > u16::MAX as usize {
+                return Err(ClientError::Io(IoError::new(
+                    ErrorKind::InvalidData,
+                    "HTTPS response is larger than a DNS message",
+                )));
+            }
+        }
+        Ok(bytes)
+    }
+}
+
+//Getters
+impl ClientHTTPSConnection {
+
+    pub fn get_server_addr(&self)-> IpAddr {
+        self.server_addr
+    }
+
+    pub fn get_port(&self)-> u16 {
+        self.port
+    }
+
+    /// Returns the address and port of the DoH server.
+    pub fn get_socket_addr(&self)-> SocketAddr {
+        SocketAddr::new(self.server_addr, self.port)
+    }
+
+    pub fn get_bind_addr(&self)-> Option<SocketAddr> {
+        self.bind_addr
+    }
+
+    pub fn get_timeout(&self)-> Duration {
+        self.timeout
+    }
+
+    pub fn get_use_0x20(&self)-> bool {
+        self.use_0x20
+    }
+
+    pub fn get_method(&self)-> HttpsMethod {
+        self.method
+    }
+
+    pub fn get_path(&self)-> &str {
+        &self.path
+    }
+
+    pub fn get_tls_config(&self)-> &TlsConfig {
+        &self.tls_config
+    }
+}
+
+//Setters
+impl ClientHTTPSConnection {
+
+    pub fn set_server_addr(&mut self,addr :IpAddr) {
+        self.server_addr = addr;
+    }
+
+    pub fn set_port(&mut self,port: u16) {
+        self.port = port;
+    }
+
+    /// Sets the address and port of the DoH server.
+    pub fn set_socket_addr(&mut self, addr: SocketAddr) {
+        self.server_addr = addr.ip();
+        self.port = addr.port();
+    }
+
+    /// Sets the local address of the stream. With `None` the system
+    /// chooses it.
+    pub fn set_bind_addr(&mut self, bind_addr: Option<SocketAddr>) {
+        self.bind_addr = bind_addr;
+    }
+
+    pub fn set_timeout(&mut self,timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    /// Sets whether the query name was sent in 0x20 mixed case, so that a
+    /// response echoing another case is rejected.
+    pub fn set_use_0x20(&mut self, use_0x20: bool) {
+        self.use_0x20 = use_0x20;
+    }
+
+    pub fn set_method(&mut self,method: HttpsMethod) {
+        self.method = method;
+    }
+
+    pub fn set_path(&mut self,path: String) {
+        self.path = path;
+    }
+
+    pub fn set_tls_config(&mut self,tls_config: TlsConfig) {
+        self.tls_config = tls_config;
+    }
+}
+
+#[cfg(test)]
+mod https_connection_test{
+    use super::*;
+    use std::net::{IpAddr,Ipv4Addr,Ipv6Addr};
+    use std::sync::Arc;
+    use crate::domain_name::DomainName;
+    use crate::message::rrtype::Rrtype;
+    use crate::message::rclass::Rclass;
+    use crate::client::tls_config::tls_config_test::{test_server_config, CERT_PEM};
+
+    fn query(id: u16) -> DnsMessage {
+        DnsMessage::new_query_message(DomainName::new_from_str("example.com"), Rrtype::A, Rclass::IN, 0, true, id)
+    }
+
+    /// Answers one DoH query on a local port with `CERT_PEM`, echoing the
+    /// question, and returns the SNI and the request received, or `None`
+    /// if the TLS handshake fails.
+    async fn local_doh_server() -> (u16, tokio::task::JoinHandle<Option<(Option<String>, Request<()>)>>) {
+        local_doh_server_with_content_type(DNS_MESSAGE_MEDIA_TYPE).await
+    }
+
+    /// Like `local_doh_server`, sending the response with `content_type`.
+    async fn local_doh_server_with_content_type(
+        content_type: &'static str,
+    ) -> (u16, tokio::task::JoinHandle<Option<(Option<String>, Request<()>)>>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let mut server_config = test_server_config();
+        server_config.alpn_protocols = vec![H2_ALPN.to_vec()];
+        let acceptor = tokio_rustls::TlsAcceptor::from(Arc::new(server_config));
+
+        let handle = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let tls_stream = acceptor.accept(stream).await.ok()?;
+            let sni = tls_stream.get_ref().1.server_name().map(str::to_string);
+            let mut connection = h2::server::handshake(tls_stream).await.unwrap();
+
+            let (request, mut respond) = connection.accept().await.unwrap().unwrap();
+            let (parts, mut body) = request.into_parts();
+            let mut query = Vec::new();
+            while let Some(chunk) = body.data().await {
+                query.extend_from_slice(&chunk.unwrap());
+            }
+
+            let mut response = DnsMessage::from_bytes(&query).unwrap();
+            assert_eq!(response.get_query_id(), 0);
+            let mut header = response.get_header();
+            header.set_qr(true);
+            response.set_header(header);
+            let http_response = http::Response::builder()
+                .status(StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, content_type)
+                .body(())
+                .unwrap();
+            let mut send_stream = respond.send_response(http_response, false).unwrap();
+            send_stream.send_data(Bytes::from(response.to_bytes()), true).unwrap();
+            // Keep the connection going until the client closes it
+            let _ = std::future::poll_fn(|cx| connection.poll_closed(cx)).await;
+
+            Some((sni, Request::from_parts(parts, ())))
+        });
+        (port, handle)
+    }
+
+    fn local_connection(port: u16, tls_config: TlsConfig) -> ClientHTTPSConnection {
+        let mut conn = ClientHTTPSConnection::new_default(IpAddr::V4(Ipv4Addr::LOCALHOST), Duration::from_secs(5));
+        conn.set_port(port);
+        conn.set_tls_config(tls_config);
+        conn
+    }
+
+    #[test]
+    fn create_https() {
+        let ip_addr = IpAddr::V4(Ipv4Addr::new(192, 168, 0, 1));
+        let timeout = Duration::from_secs(100);
+
+        let _conn_new = ClientHTTPSConnection::new_default(ip_addr, timeout);
+
+        assert_eq!(_conn_new.get_server_addr(), IpAddr::V4(Ipv4Addr::new(192, 168, 0, 1)));
+        assert_eq!(_conn_new.get_timeout(),  Duration::from_secs(100));
+        assert_eq!(_conn_new.get_method(), HttpsMethod::POST);
+        assert_eq!(_conn_new.get_path(), "/dns-query");
+    }
+
+    #[test]
+    fn get_ip_v6(){
+        let ip_address = IpAddr::V6(Ipv6Addr::new(0xc0, 0xa8, 0, 1, 0, 0, 0, 0));
+        let timeout = Duration::from_secs(100);
+        let connection = ClientHTTPSConnection::new_default(ip_address, timeout);
+        assert_eq!(connection.get_ip(), IpAddr::V6(Ipv6Addr::new(0xc0, 0xa8, 0, 1, 0, 0, 0, 0)));
+    }
+
+    #[test]
+    fn set_method_and_path(){
+        let ip_addr = IpAddr::V4(Ipv4Addr::new(192, 168, 0, 1));
+        let timeout = Duration::from_secs(100);
+        let mut _conn_new = ClientHTTPSConnection::new_default(ip_addr, timeout);
+
+        _conn_new.set_method("get".parse().unwrap());
+        _conn_new.set_path("/resolve".to_string());
+        _conn_new.set_timeout(Duration::from_secs(200));
+
+        assert_eq!(_conn_new.get_method(), HttpsMethod::GET);
+        assert_eq!(_conn_new.get_path(), "/resolve");
+        assert_eq!(_conn_new.get_timeout(),  Duration::from_secs(200));
+    }
+
+    #[test]
+    fn parse_method() {
+        assert_eq!("GET".parse::<HttpsMethod>(), Ok(HttpsMethod::GET));
+        assert_eq!("Post".parse::<HttpsMethod>(), Ok(HttpsMethod::POST));
+        assert!("PUT".parse::<HttpsMethod>().is_err());
+        assert!("".parse::<HttpsMethod>().is_err());
+    }
+
+    #[test]
+    fn build_get_request() {
+        // RFC 8484 section 4.1.1, first example
+        let query = hex::decode("0000010000010000000000000377777707657861\
+            6d706c6503636f6d0000010001").unwrap();
+        let mut conn = ClientHTTPSConnection::new_default(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1)), Duration::from_secs(2));
+        conn.set_method(HttpsMethod::GET);
+
+        let request = conn.build_request(&query).unwrap();
+
+        assert_eq!(request.method(), Method::GET);
+        assert_eq!(request.uri().to_string(),
+            "https://192.0.2.1:443/dns-query?dns=AAABAAABAAAAAAAAA3d3dwdleGFtcGxlA2NvbQAAAQAB");
+        assert_eq!(request.headers()[http::header::ACCEPT], DNS_MESSAGE_MEDIA_TYPE);
+    }
+
+    #[test]
+    fn build_post_request() {
+        let ip_addr = IpAddr::V6(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1));
+        let conn = ClientHTTPSConnection::new_default(ip_addr, Duration::from_secs(2));
+
+        let request = conn.build_request(&[0u8; 33]).unwrap();
+
+        assert_eq!(request.method(), Method::POST);
+        assert_eq!(request.uri().to_string(), "https://[2001:db8::1]:443/dns-query");
+        assert_eq!(request.headers()[http::header::CONTENT_TYPE], DNS_MESSAGE_MEDIA_TYPE);
+        assert_eq!(request.headers()[http::header::CONTENT_LENGTH], "33");
+    }
+
+    #[test]
+    fn build_request_to_other_port() {
+        let mut conn = ClientHTTPSConnection::new_default(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1)), Duration::from_secs(2));
+        conn.set_socket_addr("[2001:db8::1]:8443".parse().unwrap());
+
+        let request = conn.build_request(&[0u8; 33]).unwrap();
+
+        assert_eq!(conn.get_port(), 8443);
+        assert_eq!(request.uri().to_string(), "https://[2001:db8::1]:8443/dns-query");
+    }
+
+    #[test]
+    fn build_request_with_auth_name() {
+        let mut conn = ClientHTTPSConnection::new_default(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1)), Duration::from_secs(2));
+        let mut tls_config = TlsConfig::new();
+        tls_config.set_auth_name(Some("dns.example.com".to_string()));
+        conn.set_tls_config(tls_config);
+
+        let request = conn.build_request(&[0u8; 33]).unwrap();
+
+        assert_eq!(request.uri().to_string(), "https://dns.example.com:443/dns-query");
+    }
+
+    #[tokio::test]
+    async fn send_with_private_ca_and_auth_name() {
+        let (port, server) = local_doh_server().await;
+        let mut tls_config = TlsConfig::new();
+        tls_config.set_use_native_roots(false);
+        tls_config.add_root_certs_from_pem(CERT_PEM.as_bytes()).unwrap();
+        tls_config.set_auth_name(Some("localhost".to_string()));
+
+        let response = local_connection(port, tls_config).send(query(4321)).await.unwrap();
+        let response = DnsMessage::from_bytes(&response).unwrap();
+        let (sni, request) = server.await.unwrap().unwrap();
+
+        assert_eq!(response.get_query_id(), 4321);
+        assert!(response.get_header().get_qr());
+        assert_eq!(sni.as_deref(), Some("localhost"));
+        assert_eq!(request.method(), Method::POST);
+        assert_eq!(request.uri().to_string(), format!("https://localhost:{}/dns-query", port));
+    }
+
+    #[tokio::test]
+    async fn send_untrusted_certificate() {
+        let (port, server) = local_doh_server().await;
+        let mut tls_config = TlsConfig::new();
+        tls_config.set_use_native_roots(false);
+
+        let response = local_connection(port, tls_config).send(query(1)).await;
+
+        assert!(matches!(response, Err(ClientError::Io(_))));
+        assert!(server.await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn send_response_of_other_media_type() {
+        let (port, server) = local_doh_server_with_content_type("text/html; charset=utf-8").await;
+        let mut tls_config = TlsConfig::new();
+        tls_config.set_use_native_roots(false);
+        tls_config.add_root_certs_from_pem(CERT_PEM.as_bytes()).unwrap();
+        tls_config.set_auth_name(Some("localhost".to_string()));
+
+        let response = local_connection(port, tls_config).send(query(7)).await;
+
+        assert!(matches!(response, Err(ClientError::Io(ref e)) if e.kind() == ErrorKind::InvalidData));
+        assert!(server.await.unwrap().is_some());
+    }
+
+}
@@ -0,0 +1,770 @@
+use crate::client::client_connection::{DNS_PORT, connect_tcp};
+use crate::client::dns_transport::DnsTransport;
+use crate::client::tls_config::TlsConfig;
+use crate::client::tls_connection::DOT_PORT;
+use crate::edns::opt_option::option_code::OptionCode;
+use crate::edns::opt_option::option_data::OptionData;
+use crate::edns::opt_option::OptOption;
+use crate::message::rdata::Rdata;
+use crate::message::resource_record::ToBytes;
+use crate::message::DnsMessage;
+use super::client_error::ClientError;
+use super::response_match::check_response;
+use async_trait::async_trait;
+use rand::{thread_rng, Rng};
+use std::collections::HashMap;
+use std::io::Error as IoError;
+use std::io::ErrorKind;
+use std::net::IpAddr;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::Duration;
+use tokio::time::timeout;
+use tokio_rustls::TlsConnector;
+
+/// Time an open session is kept without outstanding queries.
+pub const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Stream transport of a `ClientPoolConnection`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PoolTransport {
+    /// DNS over TCP, port 53.
+    TCP,
+    /// DNS over TLS, port 853.
+    TLS(TlsConfig),
+}
+
+/// Connection that keeps a TCP or TLS session open and pipelines the
+/// queries over it.
+///
+/// [RFC 7766](https://datatracker.ietf.org/doc/html/rfc7766#section-6.2.1)
+///
+/// In order to achieve performance on par with UDP, DNS clients SHOULD
+/// pipeline their queries. When a DNS client sends multiple queries to a
+/// server, it SHOULD NOT wait for an outstanding reply before sending the
+/// next query. Clients MUST take care to use unique message IDs for
+/// pipelined queries, and since responses may come out of order, they match
+/// each response to its query using the message ID and the question.
+///
+/// Clones share the same session, so a pool is created once per server and
+/// cloned for every query.
+#[derive(Clone, Debug)]
+pub struct ClientPoolConnection {
+    /// Server address
+    server_addr: IpAddr,
+    /// Server port
+    port: u16,
+    /// Local address of the sessions, chosen by the system if `None`
+    bind_addr: Option<SocketAddr>,
+    /// Time to wait for each response
+    timeout: Duration,
+    /// Whether the query name is sent with 0x20 mixed case
+    use_0x20: bool,
+    /// Time an idle session is kept open
+    idle_timeout: Duration,
+    /// TCP or TLS
+    transport: PoolTransport,
+    /// Session shared by the clones of this connection
+    session: Arc<tokio::sync::Mutex<Option<Arc<Session>>>>,
+}
+
+#[async_trait]
+impl DnsTransport for ClientPoolConnection {
+    /// Sends the query over the open session, opening one if there is none.
+    async fn send_query(&self, dns_query: &DnsMessage, timeout: Duration, use_0x20: bool) -> Result<Vec<u8>, ClientError> {
+        tokio::time::timeout(timeout, self.exchange(dns_query.clone(), use_0x20))
+            .await
+            .map_err(|_| ClientError::Io(IoError::new(ErrorKind::TimedOut, "Error: timeout")))?
+    }
+
+    fn get_ip(&self) -> IpAddr {
+        self.server_addr
+    }
+}
+
+impl ClientPoolConnection {
+    /// Creates a TCP pool
+    pub fn new_default(server_addr: IpAddr, timeout: Duration) -> Self {
+        ClientPoolConnection::new_with_transport(server_addr, timeout, PoolTransport::TCP)
+    }
+
+    /// Sends the query with the timeout and 0x20 setting of the pool.
+    pub async fn send(self, dns_query: DnsMessage) -> Result<Vec<u8>, ClientError> {
+        self.send_query(&dns_query, self.timeout, self.use_0x20).await
+    }
+
+    /// Creates a pool for the given transport, on port 53 for TCP and 853
+    /// for TLS.
+    pub fn new_with_transport(server_addr: IpAddr, timeout: Duration, transport: PoolTransport) -> Self {
+        let port = match transport {
+            PoolTransport::TCP => DNS_PORT,
+            PoolTransport::TLS(_) => DOT_PORT,
+        };
+        ClientPoolConnection {
+            server_addr,
+            port,
+            bind_addr: None,
+            timeout,
+            use_0x20: false,
+            idle_timeout: DEFAULT_IDLE_TIMEOUT,
+            transport,
+            session: Arc::new(tokio::sync::Mutex::new(None)),
+        }
+    }
+
+    /// Returns whether there is a session open with the server.
+    pub async fn is_connected(&self) -> bool {
+        match self.session.lock().await.as_ref() {
+            Some(session) => !session.is_closed(),
+            None => false,
+        }
+    }
+
+    /// Closes the open session, failing its outstanding queries.
+    pub async fn close(&self) {
+        if let Some(session) = self.session.lock().await.take() {
+            session.close();
+        }
+    }
+
+    async fn exchange(&self, mut dns_query: DnsMessage, use_0x20: bool) -> Result<Vec<u8>, ClientError> {
+        let session = self.get_session().await?;
+
+        let query_id = dns_query.get_query_id();
+        let (responder, response) = oneshot::channel();
+        let _pending = session.register(&mut dns_query, use_0x20, responder)?;
+        add_tcp_keepalive(&mut dns_query);
+
+        let bytes = dns_query.to_bytes();
+        let full_msg = [&(bytes.len() as u16).to_be_bytes(), bytes.as_slice()].concat();
+        session.write(full_msg)?;
+
+        let mut response = response
+            .await
+            .map_err(|_| ClientError::Io(IoError::new(ErrorKind::ConnectionAborted, "connection closed before the response")))?;
+        response[0..2].copy_from_slice(&query_id.to_be_bytes());
+        Ok(response)
+    }
+
+    /// Returns the open session, connecting to the server if it was closed.
+    async fn get_session(&self) -> Result<Arc<Session>, ClientError> {
+        let mut guard = self.session.lock().await;
+        if let Some(session) = guard.as_ref() {
+            if !session.is_closed() {
+                return Ok(session.clone());
+            }
+        }
+
+        let stream = connect_tcp(self.get_socket_addr(), self.bind_addr).await?;
+        let session = match &self.transport {
+            PoolTransport::TCP => Session::start(stream, self.idle_timeout),
+            PoolTransport::TLS(tls_config) => {
+                let connector = TlsConnector::from(tls_config.to_client_config()?);
+                let server_name = tls_config.server_name(self.server_addr)?;
+                let tls_stream = connector.connect(server_name, stream).await.map_err(|e| {
+                    ClientError::Io(IoError::other(format!("TLS connection error: {}", e)))
+                })?;
+                Session::start(tls_stream, self.idle_timeout)
+            }
+        };
+        *guard = Some(session.clone());
+        Ok(session)
+    }
+}
+
+//Getters
+impl ClientPoolConnection {
+
+    pub fn get_server_addr(&self) -> IpAddr {
+        self.server_addr
+    }
+
+    pub fn get_port(&self) -> u16 {
+        self.port
+    }
+
+    /// Returns the address and port of the server.
+    pub fn get_socket_addr(&self) -> SocketAddr {
+        SocketAddr::new(self.server_addr, self.port)
+    }
+
+    pub fn get_bind_addr(&self) -> Option<SocketAddr> {
+        self.bind_addr
+    }
+
+    pub fn get_timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    pub fn get_use_0x20(&self) -> bool {
+        self.use_0x20
+    }
+
+    pub fn get_idle_timeout(&self) -> Duration {
+        self.idle_timeout
+    }
+
+    pub fn get_transport(&self) -> &PoolTransport {
+        &self.transport
+    }
+}
+
+//Setters
+impl ClientPoolConnection {
+
+    pub fn set_port(&mut self, port: u16) {
+        self.port = port;
+    }
+
+    /// Sets the address and port of the server for the sessions opened
+    /// from now on.
+    pub fn set_socket_addr(&mut self, addr: SocketAddr) {
+        self.server_addr = addr.ip();
+        self.port = addr.port();
+    }
+
+    /// Sets the local address of the sessions opened from now on. With
+    /// `None` the system chooses it.
+    pub fn set_bind_addr(&mut self, bind_addr: Option<SocketAddr>) {
+        self.bind_addr = bind_addr;
+    }
+
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    /// Sets whether `send` sends the query name in 0x20 mixed case, so
+    /// that responses not echoing it are left for the timeout.
+    pub fn set_use_0x20(&mut self, use_0x20: bool) {
+        self.use_0x20 = use_0x20;
+    }
+
+    /// Sets the idle timeout of the sessions opened from now on.
+    pub fn set_idle_timeout(&mut self, idle_timeout: Duration) {
+        self.idle_timeout = idle_timeout;
+    }
+}
+
+/// Adds an empty edns-tcp-keepalive option to queries carrying EDNS.
+///
+/// [RFC 7828](https://datatracker.ietf.org/doc/html/rfc7828#section-3.2.1)
+///
+/// DNS clients MAY include the edns-tcp-keepalive option in the first
+/// query sent to a server using TCP transport to signal their desire to
+/// keep the connection open when idle. The TIMEOUT field MUST be omitted.
+fn add_tcp_keepalive(dns_query: &mut DnsMessage) {
+    let mut additional = dns_query.get_additional();
+    for rr in additional.iter_mut() {
+        if let Rdata::OPT(mut opt_rdata) = rr.get_rdata() {
+            let mut options = opt_rdata.get_option();
+            if options.iter().all(|option| option.get_option_code() != OptionCode::TCPKEEPALIVE) {
+                options.push(OptOption::new(OptionCode::TCPKEEPALIVE));
+                opt_rdata.set_option(options);
+                let rdata = Rdata::OPT(opt_rdata);
+                rr.set_rdlength(rdata.to_bytes().len() as u16);
+                rr.set_rdata(rdata);
+            }
+        }
+    }
+    dns_query.set_additional(additional);
+}
+
+/// Returns the TIMEOUT of the edns-tcp-keepalive option of a response.
+fn tcp_keepalive_timeout(response: &DnsMessage) -> Option<u16> {
+    response.get_additional().into_iter().find_map(|rr| match rr.get_rdata() {
+        Rdata::OPT(opt_rdata) => opt_rdata.get_option().into_iter().find_map(|option| {
+            match option.get_opt_data() {
+                OptionData::TcpKeepalive(timeout) => timeout,
+                _ => None,
+            }
+        }),
+        _ => None,
+    })
+}
+
+/// Query waiting for its response.
+#[derive(Debug)]
+struct PendingQuery {
+    /// Query as it was written on the stream
+    query: DnsMessage,
+    /// The question name of the query is in 0x20 mixed case
+    use_0x20: bool,
+    responder: oneshot::Sender<Vec<u8>>,
+}
+
+/// Open TCP or TLS stream with its outstanding queries.
+#[derive(Debug)]
+struct Session {
+    /// Messages to be written on the stream
+    writer: Mutex<Option<mpsc::UnboundedSender<Vec<u8>>>>,
+    /// Outstanding queries by the message ID sent on the wire
+    pending: Mutex<HashMap<u16, PendingQuery>>,
+    /// The stream was closed or failed
+    closed: AtomicBool,
+    /// The server asked to close the connection once it is idle
+    closing: AtomicBool,
+    /// Idle timeout in milliseconds
+    idle_timeout: AtomicU64,
+}
+
+/// Registration of a pending query, removed when the query finishes or
+/// is cancelled by the timeout.
+struct Registration<'a> {
+    session: &'a Session,
+    wire_id: u16,
+}
+
+impl Drop for Registration<'_> {
+    fn drop(&mut self) {
+        self.session.pending.lock().unwrap().remove(&self.wire_id);
+    }
+}
+
+impl Session {
+    /// Starts the reader and writer tasks of the stream.
+    fn start<S>(stream: S, idle_timeout: Duration) -> Arc<Session>
+    where
+        S: AsyncRead + AsyncWrite + Send + Unpin + 'static,
+    {
+        let (mut read_half, mut write_half) = tokio::io::split(stream);
+        let (writer, mut messages) = mpsc::unbounded_channel::<Vec<u8>>();
+        let session = Arc::new(Session {
+            writer: Mutex::new(Some(writer)),
+            pending: Mutex::new(HashMap::new()),
+            closed: AtomicBool::new(false),
+            closing: AtomicBool::new(false),
+            idle_timeout: AtomicU64::new(idle_timeout.as_millis() as u64),
+        });
+
+        let write_session = session.clone();
+        tokio::spawn(async move {
+            while let Some(message) = messages.recv().await {
+                if write_half.write_all(&message).await.is_err() {
+                    break;
+                }
+            }
+            let _ = write_half.shutdown().await;
+            write_session.close();
+        });
+
+        let read_session = session.clone();
+        tokio::spawn(async move {
+            let mut received: Vec<u8> = Vec::new();
+            let mut chunk = vec![0u8; 4096];
+            loop {
+                while let Some(response) = next_frame(&mut received) {
+                    read_session.dispatch(response);
+                }
+                if read_session.closing.load(Ordering::Relaxed) && read_session.is_idle() {
+                    break;
+                }
+
+                // `read` is cancel safe, so a timeout that fires in the
+                // middle of a message loses no bytes. The bytes are kept in
+                // `received` until the whole message has arrived.
+                let idle_timeout = Duration::from_millis(read_session.idle_timeout.load(Ordering::Relaxed));
+                match timeout(idle_timeout, read_half.read(&mut chunk)).await {
+                    Ok(Ok(0)) | Ok(Err(_)) => break,
+                    Ok(Ok(n)) => received.extend_from_slice(&chunk[..n]),
+                    // Only idle sessions are closed
+                    Err(_) if read_session.is_idle() => break,
+                    Err(_) => {}
+                }
+            }
+            read_session.close();
+        });
+
+        session
+    }
+
+    /// Registers a query, replacing its message ID with one not used by the
+    /// other outstanding queries.
+    fn register(&self, query: &mut DnsMessage, use_0x20: bool, responder: oneshot::Sender<Vec<u8>>) -> Result<Registration<'_>, ClientError> {
+        let mut pending = self.pending.lock().unwrap();
+        if pending.len() > u16::MAX as usize {
+            return Err(ClientError::Message("too many outstanding queries"));
+        }
+        let mut wire_id = query.get_query_id();
+        while pending.contains_key(&wire_id) {
+            wire_id = thread_rng().gen();
+        }
+        query.set_query_id(wire_id);
+        pending.insert(wire_id, PendingQuery { query: query.clone(), use_0x20, responder });
+        Ok(Registration { session: self, wire_id })
+    }
+
+    fn write(&self, message: Vec<u8>) -> Result<(), ClientError> {
+        let sent = match self.writer.lock().unwrap().as_ref() {
+            Some(writer) if !self.is_closed() => writer.send(message).is_ok(),
+            _ => false,
+        };
+        if sent {
+            Ok(())
+        } else {
+            Err(ClientError::Io(IoError::new(ErrorKind::NotConnected, "connection closed")))
+        }
+    }
+
+    /// Delivers a response to the query it answers, dropping it if there
+    /// is none.
+    fn dispatch(&self, response: Vec<u8>) {
+        let message = match DnsMessage::from_bytes(&response) {
+            Ok(message) => message,
+            Err(_) => return,
+        };
+
+        // RFC 7828 section 3.3.2: the client uses the TIMEOUT of the
+        // response as the idle timeout, and a TIMEOUT of 0 asks it to
+        // close the connection as soon as there are no pending queries.
+        match tcp_keepalive_timeout(&message) {
+            Some(0) => self.closing.store(true, Ordering::Relaxed),
+            Some(keepalive) => {
+                let keepalive = keepalive as u64 * 100;
+                self.idle_timeout.fetch_min(keepalive, Ordering::Relaxed);
+            }
+            None => {}
+        }
+
+        let mut pending = self.pending.lock().unwrap();
+        let wire_id = message.get_query_id();
+        let matches = pending
+            .get(&wire_id)
+            .map(|query| check_response(&query.query, &message, query.use_0x20).is_ok())
+            .unwrap_or(false);
+        if matches {
+            if let Some(query) = pending.remove(&wire_id) {
+                let _ = query.responder.send(response);
+            }
+        }
+    }
+
+    fn is_idle(&self) -> bool {
+        self.pending.lock().unwrap().is_empty()
+    }
+
+    fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Relaxed) || self.closing.load(Ordering::Relaxed)
+    }
+
+    /// Stops accepting queries, drops the outstanding ones and ends the
+    /// writer task, which closes the stream.
+    fn close(&self) {
+        self.closed.store(true, Ordering::Relaxed);
+        self.writer.lock().unwrap().take();
+        self.pending.lock().unwrap().clear();
+    }
+}
+
+/// Takes the first message of `received`, if it has arrived completely.
+/// Each message is preceded by its length in two bytes.
+fn next_frame(received: &mut Vec<u8>) -> Option<Vec<u8>> {
+    if received.len() < 2 {
+        return None;
+    }
+    let size = u16::from_be_bytes([received[0], received[1]]) as usize;
+    if received.len() < 2 + size {
+        return None;
+    }
+    let frame = received[2..2 + size].to_vec();
+    received.drain(..2 + size);
+    Some(frame)
+}
+
+#[cfg(test)]
+mod pool_connection_test {
+    use super::*;
+    use crate::client::tls_config::tls_config_test::{test_server_config, CERT_PEM};
+    use crate::domain_name::DomainName;
+    use crate::message::rclass::Rclass;
+    use crate::message::rcode::Rcode;
+    use crate::message::rrtype::Rrtype;
+    use std::net::Ipv4Addr;
+    use tokio::net::TcpListener;
+
+    fn query(name: &str, id: u16) -> DnsMessage {
+        let mut domain_name = DomainName::new();
+        domain_name.set_name(name.to_string());
+        DnsMessage::new_query_message(domain_name, Rrtype::A, Rclass::IN, 0, true, id)
+    }
+
+    fn answer(query: &[u8], keepalive: Option<u16>) -> Vec<u8> {
+        let mut response = DnsMessage::from_bytes(query).unwrap();
+        let mut header = response.get_header();
+        header.set_qr(true);
+        response.set_header(header);
+        response.set_additional(Vec::new());
+        response.update_header_counters();
+        if let Some(keepalive) = keepalive {
+            response.add_edns0(None, Rcode::NOERROR, 0, false, Some(vec![OptionCode::TCPKEEPALIVE]));
+            let mut additional = response.get_additional();
+            if let Rdata::OPT(mut opt_rdata) = additional[0].get_rdata() {
+                let mut option = OptOption::new(OptionCode::TCPKEEPALIVE);
+                option.set_option_len(2);
+                option.set_opt_data(OptionData::TcpKeepalive(Some(keepalive)));
+                opt_rdata.set_option(vec![option]);
+                additional[0].set_rdlength(6);
+                additional[0].set_rdata(Rdata::OPT(opt_rdata));
+            }
+            response.set_additional(additional);
+        }
+        response.to_bytes()
+    }
+
+    async fn read_message<S: AsyncRead + Unpin>(stream: &mut S) -> Option<Vec<u8>> {
+        let mut length = [0u8; 2];
+        stream.read_exact(&mut length).await.ok()?;
+        let mut msg = vec![0u8; u16::from_be_bytes(length) as usize];
+        stream.read_exact(&mut msg).await.ok()?;
+        Some(msg)
+    }
+
+    async fn write_message<S: AsyncWrite + Unpin>(stream: &mut S, msg: &[u8]) {
+        stream.write_all(&(msg.len() as u16).to_be_bytes()).await.unwrap();
+        stream.write_all(msg).await.unwrap();
+    }
+
+    async fn local_pool() -> (ClientPoolConnection, TcpListener) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mut pool = ClientPoolConnection::new_default(IpAddr::V4(Ipv4Addr::LOCALHOST), Duration::from_secs(5));
+        pool.set_port(listener.local_addr().unwrap().port());
+        (pool, listener)
+    }
+
+    #[test]
+    fn create_pool() {
+        let ip_addr = IpAddr::V4(Ipv4Addr::new(192, 168, 0, 1));
+        let mut pool = ClientPoolConnection::new_default(ip_addr, Duration::from_secs(2));
+
+        assert_eq!(pool.get_ip(), ip_addr);
+        assert_eq!(pool.get_port(), 53);
+        assert_eq!(pool.get_transport(), &PoolTransport::TCP);
+        assert_eq!(pool.get_idle_timeout(), DEFAULT_IDLE_TIMEOUT);
+
+        pool.set_idle_timeout(Duration::from_secs(1));
+        assert_eq!(pool.get_idle_timeout(), Duration::from_secs(1));
+
+        let tls_pool = ClientPoolConnection::new_with_transport(ip_addr, Duration::from_secs(2), PoolTransport::TLS(TlsConfig::new()));
+        assert_eq!(tls_pool.get_port(), 853);
+    }
+
+    #[test]
+    fn keepalive_added_only_to_edns_queries() {
+        let mut plain = query("example.com", 1);
+        add_tcp_keepalive(&mut plain);
+        assert!(plain.get_additional().is_empty());
+
+        let mut edns = query("example.com", 1);
+        edns.add_edns0(Some(1232), Rcode::NOERROR, 0, false, None);
+        add_tcp_keepalive(&mut edns);
+        add_tcp_keepalive(&mut edns);
+        let rebuilt = DnsMessage::from_bytes(&edns.to_bytes()).unwrap();
+        match rebuilt.get_additional()[0].get_rdata() {
+            Rdata::OPT(opt_rdata) => {
+                assert_eq!(opt_rdata.get_option().len(), 1);
+                assert_eq!(opt_rdata.get_option()[0].get_opt_data(), OptionData::TcpKeepalive(None));
+            }
+            _ => panic!("expected OPT record"),
+        }
+    }
+
+    #[tokio::test]
+    async fn pipelined_queries_answered_out_of_order() {
+        let (pool, listener) = local_pool().await;
+
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut queries = Vec::new();
+            for _ in 0..3 {
+                queries.push(read_message(&mut stream).await.unwrap());
+            }
+            for query in queries.iter().rev() {
+                write_message(&mut stream, &answer(query, None)).await;
+            }
+            // A second connection would never be accepted
+            read_message(&mut stream).await;
+        });
+
+        let names = ["a.example", "b.example", "c.example"];
+        let (first, second, third) = tokio::join!(
+            pool.clone().send(query(names[0], 100)),
+            pool.clone().send(query(names[1], 101)),
+            pool.clone().send(query(names[2], 102))
+        );
+
+        for (i, response) in vec![first, second, third].into_iter().enumerate() {
+            let response = DnsMessage::from_bytes(&response.unwrap()).unwrap();
+            assert_eq!(response.get_query_id(), 100 + i as u16);
+            assert_eq!(response.get_question().get_qname().get_name(), names[i]);
+        }
+        assert!(pool.is_connected().await);
+        pool.close().await;
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn duplicate_ids_are_made_unique() {
+        let (pool, listener) = local_pool().await;
+
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let first = read_message(&mut stream).await.unwrap();
+            let second = read_message(&mut stream).await.unwrap();
+            assert_ne!(first[0..2], second[0..2]);
+            write_message(&mut stream, &answer(&second, None)).await;
+            write_message(&mut stream, &answer(&first, None)).await;
+            read_message(&mut stream).await;
+        });
+
+        let (first, second) = tokio::join!(
+            pool.clone().send(query("a.example", 7)),
+            pool.clone().send(query("b.example", 7))
+        );
+
+        let first = DnsMessage::from_bytes(&first.unwrap()).unwrap();
+        let second = DnsMessage::from_bytes(&second.unwrap()).unwrap();
+        assert_eq!(first.get_query_id(), 7);
+        assert_eq!(first.get_question().get_qname().get_name(), "a.example");
+        assert_eq!(second.get_query_id(), 7);
+        assert_eq!(second.get_question().get_qname().get_name(), "b.example");
+        pool.close().await;
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn response_with_other_question_is_ignored() {
+        let (mut pool, listener) = local_pool().await;
+        pool.set_timeout(Duration::from_millis(300));
+
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let query_bytes = read_message(&mut stream).await.unwrap();
+            let mut spoofed = query("other.example", 0);
+            spoofed.set_query_id(u16::from_be_bytes([query_bytes[0], query_bytes[1]]));
+            write_message(&mut stream, &answer(&spoofed.to_bytes(), None)).await;
+            read_message(&mut stream).await;
+        });
+
+        let response = pool.clone().send(query("a.example", 9)).await;
+
+        assert!(response.is_err());
+        pool.close().await;
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn idle_session_is_closed() {
+        let (mut pool, listener) = local_pool().await;
+        pool.set_idle_timeout(Duration::from_millis(50));
+
+        let server = tokio::spawn(async move {
+            for _ in 0..2 {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let query = read_message(&mut stream).await.unwrap();
+                write_message(&mut stream, &answer(&query, None)).await;
+                assert!(read_message(&mut stream).await.is_none());
+            }
+        });
+
+        pool.clone().send(query("a.example", 1)).await.unwrap();
+        assert!(pool.is_connected().await);
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert!(!pool.is_connected().await);
+
+        pool.clone().send(query("b.example", 2)).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        server.await.unwrap();
+    }
+
+    #[test]
+    fn next_frame_waits_for_whole_message() {
+        let mut received = vec![0];
+        assert_eq!(next_frame(&mut received), None);
+
+        received.extend_from_slice(&[3, 1, 2]);
+        assert_eq!(next_frame(&mut received), None);
+
+        received.extend_from_slice(&[3, 0, 1, 9]);
+        assert_eq!(next_frame(&mut received), Some(vec![1, 2, 3]));
+        assert_eq!(next_frame(&mut received), Some(vec![9]));
+        assert!(received.is_empty());
+    }
+
+    #[tokio::test]
+    async fn slow_response_outlasts_idle_timeout() {
+        let (mut pool, listener) = local_pool().await;
+        pool.set_idle_timeout(Duration::from_millis(50));
+
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let query = read_message(&mut stream).await.unwrap();
+            let response = answer(&query, None);
+            let length = (response.len() as u16).to_be_bytes();
+
+            // The length prefix is split around several idle timeouts
+            stream.write_all(&length[..1]).await.unwrap();
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            stream.write_all(&length[1..]).await.unwrap();
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            stream.write_all(&response).await.unwrap();
+            read_message(&mut stream).await;
+        });
+
+        let response = pool.clone().send(query("a.example", 5)).await.unwrap();
+
+        assert_eq!(DnsMessage::from_bytes(&response).unwrap().get_query_id(), 5);
+        pool.close().await;
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn keepalive_zero_closes_session() {
+        let (pool, listener) = local_pool().await;
+
+        let server = tokio::spawn(async move {
+            for _ in 0..2 {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let query = read_message(&mut stream).await.unwrap();
+                write_message(&mut stream, &answer(&query, Some(0))).await;
+                assert!(read_message(&mut stream).await.is_none());
+            }
+        });
+
+        let response = pool.clone().send(query("a.example", 1)).await.unwrap();
+        assert_eq!(tcp_keepalive_timeout(&DnsMessage::from_bytes(&response).unwrap()), Some(0));
+        assert!(!pool.is_connected().await);
+
+        // The next query opens a new connection
+        pool.clone().send(query("b.example", 2)).await.unwrap();
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn pipelined_queries_over_tls() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mut tls_config = TlsConfig::new();
+        tls_config.set_use_native_roots(false);
+        tls_config.add_root_certs_from_pem(CERT_PEM.as_bytes()).unwrap();
+        let mut pool = ClientPoolConnection::new_with_transport(IpAddr::V4(Ipv4Addr::LOCALHOST), Duration::from_secs(5), PoolTransport::TLS(tls_config));
+        pool.set_port(listener.local_addr().unwrap().port());
+
+        let acceptor = tokio_rustls::TlsAcceptor::from(Arc::new(test_server_config()));
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut stream = acceptor.accept(stream).await.unwrap();
+            while let Some(query) = read_message(&mut stream).await {
+                write_message(&mut stream, &answer(&query, None)).await;
+            }
+        });
+
+        for i in 0..3 {
+            let response = pool.clone().send(query("a.example", i)).await.unwrap();
+            assert_eq!(DnsMessage::from_bytes(&response).unwrap().get_query_id(), i);
+        }
+        pool.close().await;
+        server.await.unwrap();
+    }
+
+}
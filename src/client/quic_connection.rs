@@ -0,0 +1,522 @@
+use crate::message::DnsMessage;
+use super::client_error::ClientError;
+use super::response_match::match_response;
+use super::client_security::ClientSecurity;
+use async_trait::async_trait;
+use quinn::crypto::rustls::QuicClientConfig;
+use quinn::{ConnectionError, Endpoint, ReadError, ReadExactError, VarInt, WriteError};
+use std::convert::TryFrom;
+use std::fmt;
+use std::io::Error as IoError;
+use std::io::ErrorKind;
+use std::net::IpAddr;
+use std::net::SocketAddr;
+use tokio::time::Duration;
+use tokio::time::timeout;
+use std::sync::Arc;
+use crate::client::client_connection::local_addr_for;
+use crate::client::dns_transport::DnsTransport;
+use crate::client::tls_config::TlsConfig;
+
+/// ALPN token of DNS over QUIC.
+///
+/// [RFC 9250](https://datatracker.ietf.org/doc/html/rfc9250#section-4.1.1)
+pub const DOQ_ALPN: &[u8] = b"doq";
+
+/// Default port of DNS over QUIC, shared with DNS over TLS.
+///
+/// [RFC 9250](https://datatracker.ietf.org/doc/html/rfc9250#section-4.1.1)
+pub const DOQ_PORT: u16 = 853;
+
+/// Error codes used by DNS over QUIC to close connections and to reset or
+/// stop streams.
+///
+/// [RFC 9250](https://datatracker.ietf.org/doc/html/rfc9250#section-4.3)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DoqErrorCode {
+    /// DOQ_NO_ERROR (0x0): No error. This is used when the connection or
+    /// stream needs to be closed, but there is no error to signal.
+    NoError,
+    /// DOQ_INTERNAL_ERROR (0x1): The DoQ implementation encountered an
+    /// internal error and is incapable of pursuing the transaction or the
+    /// connection.
+    InternalError,
+    /// DOQ_PROTOCOL_ERROR (0x2): The DoQ implementation encountered a
+    /// protocol error and is forcibly aborting the connection.
+    ProtocolError,
+    /// DOQ_REQUEST_CANCELLED (0x3): A DoQ client uses this to signal that it
+    /// wants to cancel an outstanding transaction.
+    RequestCancelled,
+    /// DOQ_EXCESSIVE_LOAD (0x4): A DoQ implementation uses this to signal
+    /// when closing a connection due to excessive load.
+    ExcessiveLoad,
+    /// DOQ_UNSPECIFIED_ERROR (0x5): A DoQ implementation uses this in the
+    /// absence of a more specific error code.
+    UnspecifiedError,
+    /// Any other code, including DOQ_ERROR_RESERVED (0xd098ea5e).
+    Unknown(u64),
+}
+
+impl From<u64> for DoqErrorCode {
+    fn from(code: u64) -> DoqErrorCode {
+        match code {
+            0x0 => DoqErrorCode::NoError,
+            0x1 => DoqErrorCode::InternalError,
+            0x2 => DoqErrorCode::ProtocolError,
+            0x3 => DoqErrorCode::RequestCancelled,
+            0x4 => DoqErrorCode::ExcessiveLoad,
+            0x5 => DoqErrorCode::UnspecifiedError,
+            _ => DoqErrorCode::Unknown(code),
+        }
+    }
+}
+
+impl From<DoqErrorCode> for u64 {
+    fn from(code: DoqErrorCode) -> u64 {
+        match code {
+            DoqErrorCode::NoError => 0x0,
+            DoqErrorCode::InternalError => 0x1,
+            DoqErrorCode::ProtocolError => 0x2,
+            DoqErrorCode::RequestCancelled => 0x3,
+            DoqErrorCode::ExcessiveLoad => 0x4,
+            DoqErrorCode::UnspecifiedError => 0x5,
+            DoqErrorCode::Unknown(code) => code,
+        }
+    }
+}
+
+impl From<VarInt> for DoqErrorCode {
+    fn from(code: VarInt) -> DoqErrorCode {
+        DoqErrorCode::from(code.into_inner())
+    }
+}
+
+impl fmt::Display for DoqErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DoqErrorCode::NoError => write!(f, "DOQ_NO_ERROR"),
+            DoqErrorCode::InternalError => write!(f, "DOQ_INTERNAL_ERROR"),
+            DoqErrorCode::ProtocolError => write!(f, "DOQ_PROTOCOL_ERROR"),
+            DoqErrorCode::RequestCancelled => write!(f, "DOQ_REQUEST_CANCELLED"),
+            DoqErrorCode::ExcessiveLoad => write!(f, "DOQ_EXCESSIVE_LOAD"),
+            DoqErrorCode::UnspecifiedError => write!(f, "DOQ_UNSPECIFIED_ERROR"),
+            DoqErrorCode::Unknown(code) => write!(f, "unknown DoQ error code {:#x}", code),
+        }
+    }
+}
+
+impl From<ConnectionError> for ClientError {
+    fn from(error: ConnectionError) -> ClientError {
+        match error {
+            ConnectionError::ApplicationClosed(close) => ClientError::Doq(close.error_code.into()),
+            ConnectionError::TimedOut => ClientError::Io(IoError::new(ErrorKind::TimedOut, "QUIC connection timed out")),
+            error => ClientError::Io(IoError::other(format!("QUIC connection error: {}", error))),
+        }
+    }
+}
+
+impl From<ReadError> for ClientError {
+    fn from(error: ReadError) -> ClientError {
+        match error {
+            ReadError::Reset(code) => ClientError::Doq(code.into()),
+            ReadError::ConnectionLost(error) => ClientError::from(error),
+            error => ClientError::Io(IoError::other(format!("QUIC read error: {}", error))),
+        }
+    }
+}
+
+impl From<ReadExactError> for ClientError {
+    /// A stream finished before a whole message was read.
+    ///
+    /// [RFC 9250](https://datatracker.ietf.org/doc/html/rfc9250#section-4.3.3)
+    ///
+    /// Implementations MUST treat a STREAM FIN received before the full
+    /// 2-octet length field and DNS message as a DOQ_PROTOCOL_ERROR.
+    fn from(error: ReadExactError) -> ClientError {
+        match error {
+            ReadExactError::FinishedEarly(_) => ClientError::Doq(DoqErrorCode::ProtocolError),
+            ReadExactError::ReadError(error) => ClientError::from(error),
+        }
+    }
+}
+
+impl From<WriteError> for ClientError {
+    fn from(error: WriteError) -> ClientError {
+        match error {
+            WriteError::Stopped(code) => ClientError::Doq(code.into()),
+            WriteError::ConnectionLost(error) => ClientError::from(error),
+            error => ClientError::Io(IoError::other(format!("QUIC write error: {}", error))),
+        }
+    }
+}
+
+/// DNS over QUIC (DoQ) connection, as specified in RFC 9250.
+///
+/// Each query opens a new QUIC connection and sends the message on its own
+/// bidirectional stream.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClientQUICConnection {
+    /// Client address
+    server_addr: IpAddr,
+    /// UDP port of the DoQ server
+    port: u16,
+    /// Local address of the endpoint, chosen by the system if `None`
+    bind_addr: Option<SocketAddr>,
+    /// Read time timeout
+    timeout: tokio::time::Duration,
+    /// Whether the query name was sent with 0x20 mixed case
+    use_0x20: bool,
+    /// TLS settings: trust anchors, pins, authentication name and client certificate
+    tls_config: TlsConfig,
+}
+
+impl ClientQUICConnection {
+    pub fn new_default(server_addr:IpAddr, timeout: Duration) -> Self {
+        ClientQUICConnection {
+            server_addr,
+            port: DOQ_PORT,
+            bind_addr: None,
+            timeout,
+            use_0x20: false,
+            tls_config: TlsConfig::default(),
+        }
+    }
+
+    /// Sends the query, verifying the server with the TLS settings of the
+    /// connection and offering the `doq` ALPN.
+    ///
+    /// [RFC 9250](https://datatracker.ietf.org/doc/html/rfc9250#section-4.2.1)
+    ///
+    /// When sending queries over a QUIC connection, the DNS Message ID MUST
+    /// be set to 0. The original ID is put back in the response, so callers
+    /// can keep matching responses with their queries.
+    pub async fn send(self, dns_query: DnsMessage) -> Result<Vec<u8>, ClientError> {
+        let config = self.tls_config.to_client_config_with_alpn(&[DOQ_ALPN])?;
+        let quic_config = QuicClientConfig::try_from(config)
+            .map_err(|_| ClientError::Message("TLS configuration has no TLS 1.3 cipher suite"))?;
+        let client_config = quinn::ClientConfig::new(Arc::new(quic_config));
+        let server_name = self.tls_config.server_name(self.server_addr)?.to_str().into_owned();
+
+        let query_id = dns_query.get_query_id();
+        let mut doq_query = dns_query.clone();
+        doq_query.set_query_id(0);
+
+        let mut response = timeout(self.timeout, self.exchange(client_config, &server_name, doq_query.to_bytes()))
+            .await
+            .map_err(|_| ClientError::Io(IoError::new(ErrorKind::TimedOut, "QUIC request timed out")))??;
+
+        if response.len() < 2 {
+            return Err(ClientError::Doq(DoqErrorCode::ProtocolError));
+        }
+
+        // The stream is closed after its only response, there is nothing
+        // else to wait for.
+        if match_response(&doq_query, &response, self.use_0x20).is_none() {
+            return Err(ClientError::FormatError("The response does not answer the query."));
+        }
+        response[0..2].copy_from_slice(&query_id.to_be_bytes());
+        Ok(response)
+    }
+}
+
+#[async_trait]
+impl DnsTransport for ClientQUICConnection {
+    async fn send_query(&self, dns_query: &DnsMessage, timeout: Duration, use_0x20: bool) -> Result<Vec<u8>, ClientError> {
+        let mut conn = self.clone();
+        conn.set_timeout(timeout);
+        conn.set_use_0x20(use_0x20);
+        conn.send(dns_query.clone()).await
+    }
+
+    fn get_ip(&self) -> IpAddr {
+        self.server_addr
+    }
+}
+
+#[async_trait]
+impl ClientSecurity for ClientQUICConnection {
+
+
+}
+
+impl ClientQUICConnection {
+    /// Opens the QUIC connection and sends the query on a new stream.
+    ///
+    /// [RFC 9250](https://datatracker.ietf.org/doc/html/rfc9250#section-4.2)
+    ///
+    /// The client MUST send the DNS query over the selected stream and MUST
+    /// indicate through the STREAM FIN mechanism that no further data will
+    /// be sent on that stream. Every message is prefixed with a 2-octet
+    /// length field.
+    async fn exchange(&self, client_config: quinn::ClientConfig, server_name: &str, query: Vec<u8>) -> Result<Vec<u8>, ClientError> {
+        let server_addr = self.get_socket_addr();
+        let endpoint = Endpoint::client(local_addr_for(server_addr, self.bind_addr))?;
+        let connection = endpoint
+            .connect_with(client_config, server_addr, server_name)
+            .map_err(|e| ClientError::Io(IoError::other(format!("QUIC connect error: {}", e))))?
+            .await?;
+
+        let (mut send_stream, mut recv_stream) = connection.open_bi().await?;
+        let msg_length = query.len() as u16;
+        let full_msg = [&msg_length.to_be_bytes(), query.as_slice()].concat();
+        send_stream.write_all(&full_msg).await?;
+        send_stream
+            .finish()
+            .map_err(|e| ClientError::Io(IoError::other(format!("QUIC write error: {}", e))))?;
+
+        let mut msg_size_response: [u8; 2] = [0; 2];
+        recv_stream.read_exact(&mut msg_size_response).await?;
+        let mut response = vec![0u8; u16::from_be_bytes(msg_size_response) as usize];
+        recv_stream.read_exact(&mut response).await?;
+
+        connection.close(VarInt::from_u32(0), b"");
+        Ok(response)
+    }
+}
+
+//Getters
+impl ClientQUICConnection {
+
+    pub fn get_server_addr(&self)-> IpAddr {
+        self.server_addr
+    }
+
+    pub fn get_port(&self)-> u16 {
+        self.port
+    }
+
+    /// Returns the address and port of the DoQ server.
+    pub fn get_socket_addr(&self)-> SocketAddr {
+        SocketAddr::new(self.server_addr, self.port)
+    }
+
+    pub fn get_bind_addr(&self)-> Option<SocketAddr> {
+        self.bind_addr
+    }
+
+    pub fn get_timeout(&self)-> Duration {
+        self.timeout
+    }
+
+    pub fn get_use_0x20(&self)-> bool {
+        self.use_0x20
+    }
+
+    pub fn get_tls_config(&self)-> &TlsConfig {
+        &self.tls_config
+    }
+}
+
+//Setters
+impl ClientQUICConnection {
+
+    pub fn set_server_addr(&mut self,addr :IpAddr) {
+        self.server_addr = addr;
+    }
+
+    pub fn set_port(&mut self,port: u16) {
+        self.port = port;
+    }
+
+    /// Sets the address and port of the DoQ server.
+    pub fn set_socket_addr(&mut self, addr: SocketAddr) {
+        self.server_addr = addr.ip();
+        self.port = addr.port();
+    }
+
+    /// Sets the local address of the endpoint. With `None` the system
+    /// chooses it.
+    pub fn set_bind_addr(&mut self, bind_addr: Option<SocketAddr>) {
+        self.bind_addr = bind_addr;
+    }
+
+    pub fn set_timeout(&mut self,timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    /// Sets whether the query name was sent in 0x20 mixed case. The stream
+    /// carries a single response, which is rejected if it echoes another case.
+    pub fn set_use_0x20(&mut self, use_0x20: bool) {
+        self.use_0x20 = use_0x20;
+    }
+
+    pub fn set_tls_config(&mut self,tls_config: TlsConfig) {
+        self.tls_config = tls_config;
+    }
+}
+
+#[cfg(test)]
+mod quic_connection_test{
+    use super::*;
+    use std::net::Ipv4Addr;
+    use crate::domain_name::DomainName;
+    use crate::message::rrtype::Rrtype;
+    use crate::message::rclass::Rclass;
+    use quinn::crypto::rustls::QuicServerConfig;
+    use crate::client::tls_config::tls_config_test::{test_server_config, CERT_PEM};
+
+    fn server_endpoint() -> Endpoint {
+        let mut tls_config = test_server_config();
+        tls_config.alpn_protocols = vec![DOQ_ALPN.to_vec()];
+        let quic_config = QuicServerConfig::try_from(tls_config).unwrap();
+        let server_config = quinn::ServerConfig::with_crypto(Arc::new(quic_config));
+        Endpoint::server(server_config, "127.0.0.1:0".parse().unwrap()).unwrap()
+    }
+
+    fn client_tls_config() -> TlsConfig {
+        let mut tls_config = TlsConfig::new();
+        tls_config.set_use_native_roots(false);
+        tls_config.add_root_certs_from_pem(CERT_PEM.as_bytes()).unwrap();
+        tls_config
+    }
+
+    fn query(id: u16) -> DnsMessage {
+        let mut domain_name = DomainName::new();
+        domain_name.set_name("example.com".to_string());
+        DnsMessage::new_query_message(domain_name, Rrtype::A, Rclass::IN, 0, true, id)
+    }
+
+    fn connection_to(endpoint: &Endpoint) -> ClientQUICConnection {
+        let mut conn = ClientQUICConnection::new_default(IpAddr::V4(Ipv4Addr::LOCALHOST), Duration::from_secs(5));
+        conn.set_socket_addr(endpoint.local_addr().unwrap());
+        conn.set_bind_addr(Some(SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 0)));
+        conn.set_tls_config(client_tls_config());
+        conn
+    }
+
+    #[test]
+    fn create_quic() {
+        let ip_addr = IpAddr::V4(Ipv4Addr::new(192, 168, 0, 1));
+        let mut conn = ClientQUICConnection::new_default(ip_addr, Duration::from_secs(100));
+
+        assert_eq!(conn.get_ip(), ip_addr);
+        assert_eq!(conn.get_port(), 853);
+        assert_eq!(conn.get_timeout(), Duration::from_secs(100));
+
+        conn.set_server_addr(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)));
+        conn.set_port(8853);
+        conn.set_timeout(Duration::from_secs(200));
+
+        assert_eq!(conn.get_server_addr(), IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)));
+        assert_eq!(conn.get_port(), 8853);
+        assert_eq!(conn.get_timeout(), Duration::from_secs(200));
+    }
+
+    #[test]
+    fn doq_error_codes() {
+        assert_eq!(DoqErrorCode::from(0x4), DoqErrorCode::ExcessiveLoad);
+        assert_eq!(DoqErrorCode::from(0xd098ea5e), DoqErrorCode::Unknown(0xd098ea5e));
+        assert_eq!(u64::from(DoqErrorCode::RequestCancelled), 0x3);
+        assert_eq!(DoqErrorCode::ProtocolError.to_string(), "DOQ_PROTOCOL_ERROR");
+    }
+
+    #[tokio::test]
+    async fn send_over_quic_stream() {
+        let server = server_endpoint();
+        let conn = connection_to(&server);
+
+        let server_task = tokio::spawn(async move {
+            let connection = server.accept().await.unwrap().await.unwrap();
+            let (mut send_stream, mut recv_stream) = connection.accept_bi().await.unwrap();
+            let mut length = [0u8; 2];
+            recv_stream.read_exact(&mut length).await.unwrap();
+            let mut msg = vec![0u8; u16::from_be_bytes(length) as usize];
+            recv_stream.read_exact(&mut msg).await.unwrap();
+            // The client closes its side of the stream after the query
+            assert!(recv_stream.read_to_end(1).await.unwrap().is_empty());
+
+            let mut response = DnsMessage::from_bytes(&msg).unwrap();
+            assert_eq!(response.get_query_id(), 0);
+            let mut header = response.get_header();
+            header.set_qr(true);
+            response.set_header(header);
+            let bytes = response.to_bytes();
+            send_stream.write_all(&(bytes.len() as u16).to_be_bytes()).await.unwrap();
+            send_stream.write_all(&bytes).await.unwrap();
+            send_stream.finish().unwrap();
+            connection.closed().await;
+        });
+
+        let response = conn.send(query(4321)).await.unwrap();
+        let response = DnsMessage::from_bytes(&response).unwrap();
+
+        assert_eq!(response.get_query_id(), 4321);
+        assert!(response.get_header().get_qr());
+        assert_eq!(response.get_question().get_qname().get_name(), "example.com");
+        server_task.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn send_auth_name_as_sni() {
+        let server = server_endpoint();
+        let mut conn = connection_to(&server);
+        let mut tls_config = client_tls_config();
+        tls_config.set_auth_name(Some("localhost".to_string()));
+        conn.set_tls_config(tls_config);
+
+        let server_task = tokio::spawn(async move {
+            let connection = server.accept().await.unwrap().await.unwrap();
+            let handshake_data = connection.handshake_data().unwrap();
+            let handshake_data = handshake_data.downcast::<quinn::crypto::rustls::HandshakeData>().unwrap();
+            connection.close(VarInt::from_u32(0x0), b"");
+            server.wait_idle().await;
+            handshake_data.server_name
+        });
+
+        let error = conn.send(query(1)).await.unwrap_err();
+
+        assert!(matches!(error, ClientError::Doq(DoqErrorCode::NoError)));
+        assert_eq!(server_task.await.unwrap().as_deref(), Some("localhost"));
+    }
+
+    #[tokio::test]
+    async fn send_untrusted_certificate() {
+        let server = server_endpoint();
+        let mut conn = connection_to(&server);
+        let mut tls_config = TlsConfig::new();
+        tls_config.set_use_native_roots(false);
+        conn.set_tls_config(tls_config);
+
+        let server_task = tokio::spawn(async move {
+            assert!(server.accept().await.unwrap().await.is_err());
+        });
+
+        assert!(matches!(conn.send(query(1)).await, Err(ClientError::Io(_))));
+        server_task.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn stream_reset_maps_doq_error() {
+        let server = server_endpoint();
+        let conn = connection_to(&server);
+
+        let server_task = tokio::spawn(async move {
+            let connection = server.accept().await.unwrap().await.unwrap();
+            let (mut send_stream, _recv_stream) = connection.accept_bi().await.unwrap();
+            send_stream.reset(VarInt::from_u32(0x4)).unwrap();
+            connection.closed().await;
+        });
+
+        let error = conn.send(query(1)).await.unwrap_err();
+
+        assert!(matches!(error, ClientError::Doq(DoqErrorCode::ExcessiveLoad)));
+        server_task.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn connection_close_maps_doq_error() {
+        let server = server_endpoint();
+        let conn = connection_to(&server);
+
+        let server_task = tokio::spawn(async move {
+            let connection = server.accept().await.unwrap().await.unwrap();
+            connection.close(VarInt::from_u32(0x2), b"malformed query");
+            server.wait_idle().await;
+        });
+
+        let error = conn.send(query(1)).await.unwrap_err();
+
+        assert!(matches!(error, ClientError::Doq(DoqErrorCode::ProtocolError)));
+        server_task.await.unwrap();
+    }
+}
@@ -0,0 +1,314 @@
+use crate::message::DnsMessage;
+use crate::message::rcode::Rcode;
+use crate::message::resource_record::ResourceRecord;
+
+/// Checks that `response` is the answer to `query`.
+///
+/// [RFC 5452](https://datatracker.ietf.org/doc/html/rfc5452#section-3)
+///
+/// 3. Requirements for a Spoofing Attack
+///
+/// The resolver must accept a response only if:
+///
+///    - The question section of the reply packet is equivalent to that of a
+///      question packet currently waiting for a response.
+///
+///    - The ID field of the reply packet matches that of the question packet.
+///
+///    - The response comes from the same network address to which the
+///      question was sent.
+///
+/// The opcode must also be the one of the query, and the QR bit must be set.
+/// Servers may omit the question section when they refuse to interpret the
+/// query, so a response without question is accepted when it has an error
+/// RCODE.
+///
+/// If `use_0x20` is set, the query name was sent in mixed case
+/// ([draft-vixie-dnsext-dns0x20](https://datatracker.ietf.org/doc/html/draft-vixie-dnsext-dns0x20-00))
+/// and the server must echo it exactly, otherwise names are compared
+/// ignoring case.
+pub fn check_response(query: &DnsMessage, response: &DnsMessage, use_0x20: bool) -> Result<(), &'static str> {
+    let query_header = query.get_header();
+    let response_header = response.get_header();
+
+    if response_header.get_id() != query_header.get_id() {
+        return Err("Error expected ID from query");
+    }
+
+    if !response_header.get_qr() {
+        return Err("Message is a query. A response was expected.");
+    }
+
+    if response_header.get_op_code() != query_header.get_op_code() {
+        return Err("The opcode of the response does not match the query");
+    }
+
+    if response_header.get_qdcount() == 0 && response_header.get_rcode() != Rcode::NOERROR {
+        return Ok(());
+    }
+
+    if response_header.get_qdcount() != query_header.get_qdcount() {
+        return Err("The question of the response does not match the query");
+    }
+
+    let query_question = query.get_question();
+    let response_question = response.get_question();
+    let query_qname = query_question.get_qname();
+    let response_qname = response_question.get_qname();
+
+    let same_name = match use_0x20 {
+        true => query_qname.eq_case_sensitive(&response_qname),
+        false => query_qname == response_qname,
+    };
+
+    if !same_name
+        || query_question.get_rrtype() != response_question.get_rrtype()
+        || query_question.get_rclass() != response_question.get_rclass()
+    {
+        return Err("The question of the response does not match the query");
+    }
+
+    Ok(())
+}
+
+/// Parses `response` and returns it only if it is the answer to `query`.
+///
+/// Transports use it to discard datagrams that do not belong to the query
+/// and keep waiting for the right one. With `use_0x20` a response that does
+/// not echo the case of the query name is discarded too, so a spoofed one
+/// with the right ID does not end the wait.
+pub fn match_response(query: &DnsMessage, response: &[u8], use_0x20: bool) -> Option<DnsMessage> {
+    let response = DnsMessage::from_bytes(response).ok()?;
+
+    check_response(query, &response, use_0x20).ok()?;
+
+    Some(response)
+}
+
+/// Randomizes the case of the query name, so that an attacker also has to
+/// guess it for a spoofed response to be accepted.
+///
+/// [draft-vixie-dnsext-dns0x20](https://datatracker.ietf.org/doc/html/draft-vixie-dnsext-dns0x20-00)
+///
+/// A name with at least one letter always gets a case that differs from
+/// the one it was typed with, so a response that echoes the typed name
+/// does not match.
+pub fn apply_0x20(query: &mut DnsMessage) {
+    let mut question = query.get_question();
+    let qname = question.get_qname();
+
+    if !qname.get_labels().iter().flatten().any(u8::is_ascii_alphabetic) {
+        return;
+    }
+
+    let mut mixed_qname = qname.to_random_case();
+    while mixed_qname.eq_case_sensitive(&qname) {
+        mixed_qname = qname.to_random_case();
+    }
+
+    question.set_qname(mixed_qname);
+    query.set_question(question);
+}
+
+/// Gives the question of `response` the name of `query`, as it was before
+/// `apply_0x20`, so that the mixed case is not seen by the callers nor
+/// stored in the cache. Records owned by the query name, which servers
+/// usually write as a pointer to the question, get the same name.
+///
+/// The name is also written back into `response_bytes`. The question is
+/// the first name of the message, so it is never compressed and has the
+/// same length in any case.
+pub fn restore_qname(query: &DnsMessage, response: &mut DnsMessage, response_bytes: &mut [u8]) {
+    let qname = query.get_question().get_qname();
+    let mut question = response.get_question();
+    if response.get_header().get_qdcount() == 0 || question.get_qname() != qname {
+        return;
+    }
+
+    let qname_bytes = qname.to_bytes();
+    if let Some(wire_qname) = response_bytes.get_mut(12..12 + qname_bytes.len()) {
+        if wire_qname.eq_ignore_ascii_case(&qname_bytes) {
+            wire_qname.copy_from_slice(&qname_bytes);
+        }
+    }
+
+    let restore_owner = |rrs: Vec<ResourceRecord>| -> Vec<ResourceRecord> {
+        rrs.into_iter()
+            .map(|mut rr| {
+                if rr.get_name() == qname {
+                    rr.set_name(qname.clone());
+                }
+                rr
+            })
+            .collect()
+    };
+    response.set_answer(restore_owner(response.get_answer()));
+    response.set_authority(restore_owner(response.get_authority()));
+    response.set_additional(restore_owner(response.get_additional()));
+
+    question.set_qname(qname);
+    response.set_question(question);
+}
+
+#[cfg(test)]
+mod response_match_test {
+    use super::*;
+    use crate::domain_name::DomainName;
+    use crate::message::rclass::Rclass;
+    use crate::message::rrtype::Rrtype;
+
+    fn query(name: &str) -> DnsMessage {
+        DnsMessage::new_query_message(
+            DomainName::new_from_str(name),
+            Rrtype::A,
+            Rclass::IN,
+            0,
+            true,
+            4321,
+        )
+    }
+
+    fn response_to(query: &DnsMessage) -> DnsMessage {
+        let mut response = query.clone();
+        let mut header = response.get_header();
+        header.set_qr(true);
+        response.set_header(header);
+
+        response
+    }
+
+    #[test]
+    fn check_response_ok() {
+        let query = query("example.com");
+        let response = response_to(&query);
+
+        assert!(check_response(&query, &response, false).is_ok());
+        assert!(match_response(&query, &response.to_bytes(), false).is_some());
+    }
+
+    #[test]
+    fn check_response_wrong_id() {
+        let query = query("example.com");
+        let mut response = response_to(&query);
+        response.set_query_id(1234);
+
+        assert_eq!(check_response(&query, &response, false), Err("Error expected ID from query"));
+    }
+
+    #[test]
+    fn check_response_is_query() {
+        let query = query("example.com");
+
+        assert_eq!(
+            check_response(&query, &query, false),
+            Err("Message is a query. A response was expected.")
+        );
+    }
+
+    #[test]
+    fn check_response_wrong_opcode() {
+        let query = query("example.com");
+        let mut response = response_to(&query);
+        let mut header = response.get_header();
+        header.set_op_code(2);
+        response.set_header(header);
+
+        assert_eq!(
+            check_response(&query, &response, false),
+            Err("The opcode of the response does not match the query")
+        );
+    }
+
+    #[test]
+    fn check_response_wrong_question() {
+        let query = query("example.com");
+        let mut response = response_to(&query);
+        let mut question = response.get_question();
+        question.set_rrtype(Rrtype::AAAA);
+        response.set_question(question);
+
+        assert!(check_response(&query, &response, false).is_err());
+
+        let other = response_to(&self::query("example.org"));
+
+        assert!(check_response(&query, &other, false).is_err());
+    }
+
+    #[test]
+    fn check_response_error_without_question() {
+        let query = query("example.com");
+        let mut response = DnsMessage::new();
+        let mut header = response.get_header();
+        header.set_id(4321);
+        header.set_qr(true);
+        header.set_rcode(Rcode::FORMERR);
+        response.set_header(header);
+
+        assert!(check_response(&query, &response, false).is_ok());
+
+        let mut header = response.get_header();
+        header.set_rcode(Rcode::NOERROR);
+        response.set_header(header);
+
+        assert!(check_response(&query, &response, false).is_err());
+    }
+
+    #[test]
+    fn check_response_case() {
+        let query = query("example.com");
+        let mut response = response_to(&query);
+        let mut question = response.get_question();
+        question.set_qname(DomainName::new_from_str("EXAMPLE.com"));
+        response.set_question(question);
+
+        assert!(check_response(&query, &response, false).is_ok());
+
+        let mixed_query = self::query("ExAmPle.cOm");
+
+        assert!(check_response(&mixed_query, &response, false).is_ok());
+        assert!(check_response(&mixed_query, &response, true).is_err());
+        assert!(check_response(&mixed_query, &response_to(&mixed_query), true).is_ok());
+        assert!(match_response(&mixed_query, &response.to_bytes(), false).is_some());
+        assert!(match_response(&mixed_query, &response.to_bytes(), true).is_none());
+    }
+
+    #[test]
+    fn restore_qname_case() {
+        let original = query("www.example.com");
+        let mut mixed_query = original.clone();
+        apply_0x20(&mut mixed_query);
+        let mut response = response_to(&mixed_query);
+        let mut bytes = response.to_bytes();
+
+        restore_qname(&original, &mut response, &mut bytes);
+
+        let qname = DomainName::new_from_str("www.example.com");
+        assert!(response.get_question().get_qname().eq_case_sensitive(&qname));
+        assert_eq!(bytes, response_to(&original).to_bytes());
+    }
+
+    #[test]
+    fn apply_0x20_mixes_case() {
+        let mut query = query("www.example.com");
+        apply_0x20(&mut query);
+
+        let qname = query.get_question().get_qname();
+
+        assert!(qname.has_uppercase());
+        assert_eq!(qname, DomainName::new_from_str("www.example.com"));
+        assert!(check_response(&query, &response_to(&query), true).is_ok());
+
+        let mut upper_query = self::query("WWW.EXAMPLE.COM");
+        apply_0x20(&mut upper_query);
+
+        assert!(!upper_query.get_question().get_qname().eq_case_sensitive(&DomainName::new_from_str("WWW.EXAMPLE.COM")));
+    }
+
+    #[test]
+    fn apply_0x20_without_letters() {
+        let mut query = query("1.2.0.192");
+        apply_0x20(&mut query);
+
+        assert_eq!(query.get_question().get_qname().get_name(), "1.2.0.192");
+    }
+}
@@ -1,9 +1,11 @@
-use crate::client::ClientConnection;
+use crate::client::dns_transport::DnsTransport;
+use crate::client::client_connection::{DNS_PORT, connect_tcp};
 use crate::message::DnsMessage;
 use crate::message::rdata::Rdata;
 use crate::message::rdata::a_rdata::ARdata;
 use crate::message::resource_record::ResourceRecord;
 use super::client_error::ClientError;
+use super::response_match::match_response;
 use async_trait::async_trait;
 use std::io::Error as IoError;
 use std::io::ErrorKind;
@@ -19,40 +21,41 @@ use tokio::time::timeout;
 pub struct ClientTCPConnection {
     /// Client address
     server_addr: IpAddr,
+    /// Server port
+    port: u16,
+    /// Local address of the stream, chosen by the system if `None`
+    bind_addr: Option<SocketAddr>,
     /// Read time timeout
     timeout: tokio::time::Duration,
+    /// Whether the query name was sent with 0x20 mixed case
+    use_0x20: bool,
     /// payload size
     payload_size: usize,
 }
 
-#[async_trait]
-impl ClientConnection for ClientTCPConnection {
-
+impl ClientTCPConnection {
     /// Creates TCPConnection
-    fn new(server_addr:IpAddr, timeout: Duration, payload_size: usize) -> Self {
+    pub fn new(server_addr:IpAddr, timeout: Duration, payload_size: usize) -> Self {
         ClientTCPConnection {
             server_addr: server_addr,
+            port: DNS_PORT,
+            bind_addr: None,
             timeout: timeout,
+            use_0x20: false,
             payload_size: payload_size,
         }
     }
 
-    ///implement get_ip
-    /// returns IpAddr
-    fn get_ip(&self) -> IpAddr {
-        return self.server_addr.clone();
-    }
-
     /// creates socket tcp, sends query and receive response
-    async fn send(self, dns_query: DnsMessage) -> Result<Vec<u8>, ClientError> {
+    pub async fn send(self, dns_query: DnsMessage) -> Result<Vec<u8>, ClientError> {
     // async fn send(self, dns_query: DnsMessage) -> Result<(Vec<u8>, IpAddr), ClientError> {
         
         let conn_timeout: Duration = self.get_timeout();
         let bytes: Vec<u8> = dns_query.to_bytes();
-        let server_addr:SocketAddr = SocketAddr::new(self.get_server_addr(), 53);
+        let server_addr:SocketAddr = self.get_socket_addr();
 
         // let mut stream: TcpStream = TcpStream::connect_timeout(&server_addr,timeout)?;
-        let conn_task = TcpStream::connect(&server_addr);
+        let conn_task = connect_tcp(server_addr, self.bind_addr);
         let mut stream: TcpStream = match timeout(conn_timeout, conn_task).await {
             Ok(stream_result) => stream_result?,
             Err(_) => return Err(ClientError::Io(IoError::new(ErrorKind::TimedOut, format!("Error: timeout"))).into()),
@@ -78,13 +81,6 @@ impl ClientConnection for ClientTCPConnection {
         // stream.write(&full_msg)?;
         stream.write(&full_msg).await?;
         
-        // Read response
-        let mut msg_size_response: [u8; 2] = [0; 2];
-
-        stream.read_exact(&mut msg_size_response).await?;
-    
-        let tcp_msg_len: u16 = (msg_size_response[0] as u16) << 8 | msg_size_response[1] as u16;
-        let mut vec_msg: Vec<u8> = Vec::new();
         let ip = self.get_server_addr();
         let mut additionals = dns_query.get_additional();
         let mut ar = ARdata::new();
@@ -92,33 +88,59 @@ impl ClientConnection for ClientTCPConnection {
         let a_rdata = Rdata::A(ar);
         let rr = ResourceRecord::new(a_rdata);
         additionals.push(rr);
-        
-    
-        while vec_msg.len() < tcp_msg_len as usize {
-            let mut msg = vec![0; self.payload_size];
-            let read_task = stream.read(&mut msg);
-            let number_of_bytes_msg_result = match timeout(conn_timeout, read_task).await {
-                Ok(n) => n,
-                Err(_) => return Err(ClientError::Io(IoError::new(ErrorKind::TimedOut, format!("Error: timeout"))).into()),
-            };
-
-            let number_of_bytes_msg = match number_of_bytes_msg_result {
-                Ok(n) if n > 0 => n,
-                _ => return Err(IoError::new(ErrorKind::Other, format!("Error: no data received "))).map_err(Into::into),
-                
-            };
-
-            vec_msg.extend_from_slice(&msg[..number_of_bytes_msg]);
-        }
 
-        return Ok(vec_msg);
+        // Responses that do not answer the query are discarded, and the
+        // stream keeps being read until the timeout.
+        let read_task = async {
+            loop {
+                let mut msg_size_response: [u8; 2] = [0; 2];
+                stream.read_exact(&mut msg_size_response).await?;
+
+                let tcp_msg_len: u16 = (msg_size_response[0] as u16) << 8 | msg_size_response[1] as u16;
+                let mut vec_msg: Vec<u8> = Vec::new();
+
+                while vec_msg.len() < tcp_msg_len as usize {
+                    let remaining = tcp_msg_len as usize - vec_msg.len();
+                    let mut msg = vec![0; self.payload_size.min(remaining)];
+                    let number_of_bytes_msg = match stream.read(&mut msg).await {
+                        Ok(n) if n > 0 => n,
+                        _ => return Err(IoError::other("Error: no data received ")),
+                    };
+
+                    vec_msg.extend_from_slice(&msg[..number_of_bytes_msg]);
+                }
+
+                if match_response(&dns_query, &vec_msg, self.use_0x20).is_some() {
+                    return Ok(vec_msg);
+                }
+            }
+        };
+
+        match timeout(conn_timeout, read_task).await {
+            Ok(response) => Ok(response?),
+            Err(_) => Err(ClientError::Io(IoError::new(ErrorKind::TimedOut, "Error: timeout"))),
+        }
     }
 
-    fn new_default(server_addr: IpAddr, timeout: Duration) -> Self {
+    pub fn new_default(server_addr: IpAddr, timeout: Duration) -> Self {
         Self::new(server_addr, timeout, 512)
     }
 }
 
+#[async_trait]
+impl DnsTransport for ClientTCPConnection {
+    async fn send_query(&self, dns_query: &DnsMessage, timeout: Duration, use_0x20: bool) -> Result<Vec<u8>, ClientError> {
+        let mut conn = *self;
+        conn.set_timeout(timeout);
+        conn.set_use_0x20(use_0x20);
+        conn.send(dns_query.clone()).await
+    }
+
+    fn get_ip(&self) -> IpAddr {
+        self.server_addr
+    }
+}
+
 //Getters
 impl ClientTCPConnection {
 
@@ -126,10 +148,26 @@ impl ClientTCPConnection {
         return self.server_addr.clone();
     }
 
+    pub fn get_port(&self)-> u16 {
+        self.port
+    }
+
+    /// Returns the address and port the stream is opened to.
+    pub fn get_socket_addr(&self)-> SocketAddr {
+        SocketAddr::new(self.server_addr, self.port)
+    }
+
+    pub fn get_bind_addr(&self)-> Option<SocketAddr> {
+        self.bind_addr
+    }
+
     pub fn get_timeout(&self)-> Duration {
         return self.timeout.clone();
     }
 
+    pub fn get_use_0x20(&self)-> bool {
+        self.use_0x20
+    }
 
 }
 
@@ -140,10 +178,32 @@ impl ClientTCPConnection {
         self.server_addr = addr;
     }
 
+    pub fn set_port(&mut self, port: u16) {
+        self.port = port;
+    }
+
+    /// Sets the address and port the stream is opened to.
+    pub fn set_socket_addr(&mut self, addr: SocketAddr) {
+        self.server_addr = addr.ip();
+        self.port = addr.port();
+    }
+
+    /// Sets the local address of the stream. With `None` the system
+    /// chooses it.
+    pub fn set_bind_addr(&mut self, bind_addr: Option<SocketAddr>) {
+        self.bind_addr = bind_addr;
+    }
+
     pub fn set_timeout(&mut self,timeout: Duration) {
         self.timeout = timeout;
     }
 
+    /// Sets whether the query name was sent in 0x20 mixed case, in which
+    /// case responses that do not echo it are skipped.
+    pub fn set_use_0x20(&mut self, use_0x20: bool) {
+        self.use_0x20 = use_0x20;
+    }
+
 }
 
 #[cfg(test)]
@@ -269,7 +329,42 @@ mod tcp_connection_test{
         assert!(response.is_err());
     }
 
-
-
-
-}
\ No newline at end of file
+    #[tokio::test]
+    async fn send_to_server_on_high_port() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mut conn = ClientTCPConnection::new_default(IpAddr::V4(Ipv4Addr::LOCALHOST), Duration::from_secs(2));
+        conn.set_socket_addr(listener.local_addr().unwrap());
+        conn.set_bind_addr(Some("127.0.0.1:0".parse().unwrap()));
+
+        let server_task = tokio::spawn(async move {
+            let (mut stream, client_addr) = listener.accept().await.unwrap();
+            assert_eq!(client_addr.ip(), IpAddr::V4(Ipv4Addr::LOCALHOST));
+
+            let mut length = [0u8; 2];
+            stream.read_exact(&mut length).await.unwrap();
+            let mut msg = vec![0u8; u16::from_be_bytes(length) as usize];
+            stream.read_exact(&mut msg).await.unwrap();
+
+            // The response to another query comes first and is skipped
+            let mut response = DnsMessage::from_bytes(&msg).unwrap();
+            let mut header = response.get_header();
+            header.set_qr(true);
+            response.set_header(header);
+            for id in vec![response.get_query_id().wrapping_add(1), response.get_query_id()] {
+                response.set_query_id(id);
+                let bytes = response.to_bytes();
+                stream.write_all(&(bytes.len() as u16).to_be_bytes()).await.unwrap();
+                stream.write_all(&bytes).await.unwrap();
+            }
+        });
+
+        let domain_name = DomainName::new_from_str("example.com");
+        let dns_query = DnsMessage::new_query_message(domain_name, Rrtype::A, Rclass::IN, 0, false, 7);
+        let response = conn.send(dns_query).await.unwrap();
+        let response = DnsMessage::from_bytes(&response).unwrap();
+
+        assert_eq!(response.get_query_id(), 7);
+        assert!(response.get_header().get_qr());
+        server_task.await.unwrap();
+    }
+}
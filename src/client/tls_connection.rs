@@ -4,78 +4,82 @@ use crate::message::rdata::a_rdata::ARdata;
 use crate::message::resource_record::ResourceRecord;
 use super::client_error::ClientError;
 use super::client_security::ClientSecurity;
+use super::response_match::match_response;
 use async_trait::async_trait;
-use rustls::pki_types::ServerName;
-use std::convert::TryFrom;
 use std::io::Error as IoError;
 use std::io::ErrorKind;
 use tokio::io::AsyncWriteExt;
 use tokio::io::AsyncReadExt;
-use tokio::net::{lookup_host, TcpStream};
 use std::net::IpAddr;
 use std::net::SocketAddr;
 use tokio::time::Duration;
 use tokio::time::timeout;
-use tokio_rustls::rustls::ClientConfig;
 use tokio_rustls::TlsConnector;
-use std::sync::Arc;
 use tokio::task;
-use crate::client::client_connection::ClientConnection;
+use crate::client::client_connection::connect_tcp;
+use crate::client::dns_transport::DnsTransport;
+use crate::client::tls_config::TlsConfig;
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+/// Default port of DNS over TLS.
+///
+/// [RFC 7858](https://datatracker.ietf.org/doc/html/rfc7858#section-3.1)
+pub const DOT_PORT: u16 = 853;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct ClientTLSConnection {
     /// Client address
     server_addr: IpAddr,
+    /// TCP port of the DoT server
+    port: u16,
+    /// Local address of the stream, chosen by the system if `None`
+    bind_addr: Option<SocketAddr>,
     /// Read time timeout
     timeout: tokio::time::Duration,
+    /// Whether the query name was sent with 0x20 mixed case
+    use_0x20: bool,
+    /// TLS settings: trust anchors, pins, authentication name and client certificate
+    tls_config: TlsConfig,
     new_default: fn(IpAddr, Duration) -> Self,
 }
 
-#[async_trait]
-impl ClientConnection for ClientTLSConnection {
+impl ClientTLSConnection {
     /// Creates TLSConnection
-    fn new(server_addr:IpAddr, timeout: Duration, payload_size: usize) -> Self {
+    pub fn new(server_addr:IpAddr, timeout: Duration, _payload_size: usize) -> Self {
         ClientTLSConnection {
             server_addr: server_addr,
+            port: DOT_PORT,
+            bind_addr: None,
             timeout: timeout,
+            use_0x20: false,
+            tls_config: TlsConfig::default(),
             new_default: ClientTLSConnection::new_default,
         }
     }
 
-    fn new_default(server_addr:IpAddr, timeout: Duration) -> Self {
+    pub fn new_default(server_addr:IpAddr, timeout: Duration) -> Self {
         ClientTLSConnection {
             server_addr: server_addr,
+            port: DOT_PORT,
+            bind_addr: None,
             timeout: timeout,
+            use_0x20: false,
+            tls_config: TlsConfig::default(),
             new_default: ClientTLSConnection::new_default,
         }
     }
 
-    ///implement get_ip
-    /// returns IpAddr
-    fn get_ip(&self) -> IpAddr {
-        return self.server_addr.clone();
-    }
-
     /// creates socket tcp, sends query and receive response
-    async fn send(self, dns_query: DnsMessage) -> Result<Vec<u8>, ClientError> {
-        // Configure the root certificate store with platform-native certificates
-        let mut roots = rustls::RootCertStore::empty();
-        for cert in rustls_native_certs::load_native_certs().expect("could not load platform certs") {
-            roots.add(cert).unwrap();
-        }
-        let config = ClientConfig::builder()
-            .with_root_certificates(roots)
-            .with_no_client_auth();
-
-        let server_name_str = self.get_server_addr().to_string();
-        let server_name = ServerName::try_from(server_name_str).expect("invalid DNS name");
+    pub async fn send(self, dns_query: DnsMessage) -> Result<Vec<u8>, ClientError> {
+        // Configure trust anchors, pins and client certificate
+        let config = self.tls_config.to_client_config()?;
+        let server_name = self.tls_config.server_name(self.get_server_addr())?;
 
         // Create a TLS connector with the configured certificates
-        let connector = TlsConnector::from(Arc::new(config));
+        let connector = TlsConnector::from(config);
 
-        // Connect to the DNS server over TCP on port 853
-        let server_addr: SocketAddr = SocketAddr::new(self.get_server_addr(), 853);
-        let stream = TcpStream::connect(server_addr).await.map_err(|e| ClientError::from(e))?;
+        // Connect to the DNS server over TCP
+        let server_addr: SocketAddr = self.get_socket_addr();
+        let stream = connect_tcp(server_addr, self.bind_addr).await.map_err(ClientError::from)?;
 
         // Verify that the connected IP matches the expected IP
         let actual_ip = stream.peer_addr()?.ip();
@@ -92,7 +96,6 @@ impl ClientConnection for ClientTLSConnection {
             ClientError::Io(IoError::new(ErrorKind::Other, format!("TLS connection error: {}", e)))
         })?;
 
-
         // Prepare the DNS query message
         let bytes = dns_query.to_bytes();
         let msg_length = bytes.len() as u16;
@@ -101,17 +104,42 @@ impl ClientConnection for ClientTLSConnection {
         // Send the DNS query over the TLS connection
         tls_stream.write_all(&full_msg).await?;
 
-        // Read the size of the response
-        let mut msg_size_response: [u8; 2] = [0; 2];
-        tls_stream.read_exact(&mut msg_size_response).await?;
-        let tls_msg_len: u16 = u16::from_be_bytes(msg_size_response);
+        // Read responses until one answers the query, discarding the others
+        let read_task = async {
+            loop {
+                // Read the size of the response
+                let mut msg_size_response: [u8; 2] = [0; 2];
+                tls_stream.read_exact(&mut msg_size_response).await?;
+                let tls_msg_len: u16 = u16::from_be_bytes(msg_size_response);
+
+                // Read the full DNS response
+                let mut response = vec![0u8; tls_msg_len as usize];
+                tls_stream.read_exact(&mut response).await?;
+
+                if match_response(&dns_query, &response, self.use_0x20).is_some() {
+                    return Ok::<_, IoError>(response);
+                }
+            }
+        };
+
+        match timeout(self.get_timeout(), read_task).await {
+            Ok(response) => Ok(response?),
+            Err(_) => Err(ClientError::Io(IoError::new(ErrorKind::TimedOut, "Error: timeout"))),
+        }
+    }
+}
 
-        // Read the full DNS response
-        let mut response = vec![0u8; tls_msg_len as usize];
-        tls_stream.read_exact(&mut response).await?;
+#[async_trait]
+impl DnsTransport for ClientTLSConnection {
+    async fn send_query(&self, dns_query: &DnsMessage, timeout: Duration, use_0x20: bool) -> Result<Vec<u8>, ClientError> {
+        let mut conn = self.clone();
+        conn.set_timeout(timeout);
+        conn.set_use_0x20(use_0x20);
+        conn.send(dns_query.clone()).await
+    }
 
-        // Return the response
-        Ok(response)
+    fn get_ip(&self) -> IpAddr {
+        self.server_addr
     }
 }
 
@@ -131,6 +159,27 @@ impl ClientTLSConnection {
     pub fn get_timeout(&self)-> Duration {
         return self.timeout.clone();
     }
+
+    pub fn get_use_0x20(&self)-> bool {
+        self.use_0x20
+    }
+
+    pub fn get_port(&self)-> u16 {
+        self.port
+    }
+
+    /// Returns the address and port of the DoT server.
+    pub fn get_socket_addr(&self)-> SocketAddr {
+        SocketAddr::new(self.server_addr, self.port)
+    }
+
+    pub fn get_bind_addr(&self)-> Option<SocketAddr> {
+        self.bind_addr
+    }
+
+    pub fn get_tls_config(&self)-> &TlsConfig {
+        &self.tls_config
+    }
 }
 
 //Setters
@@ -144,6 +193,32 @@ impl ClientTLSConnection {
         self.timeout = timeout;
     }
 
+    /// Sets whether the query name was sent in 0x20 mixed case. Responses
+    /// read from the stream that do not echo that case are skipped.
+    pub fn set_use_0x20(&mut self, use_0x20: bool) {
+        self.use_0x20 = use_0x20;
+    }
+
+    pub fn set_port(&mut self,port: u16) {
+        self.port = port;
+    }
+
+    /// Sets the address and port of the DoT server.
+    pub fn set_socket_addr(&mut self, addr: SocketAddr) {
+        self.server_addr = addr.ip();
+        self.port = addr.port();
+    }
+
+    /// Sets the local address of the stream. With `None` the system
+    /// chooses it.
+    pub fn set_bind_addr(&mut self, bind_addr: Option<SocketAddr>) {
+        self.bind_addr = bind_addr;
+    }
+
+    pub fn set_tls_config(&mut self,tls_config: TlsConfig) {
+        self.tls_config = tls_config;
+    }
+
 }
 
 #[cfg(test)]
@@ -153,6 +228,9 @@ mod tls_connection_test{
     use crate::domain_name::DomainName;
     use crate::message::rrtype::Rrtype;
     use crate::message::rclass::Rclass;
+    use crate::client::tls_config::UsageProfile;
+    use crate::client::tls_config::tls_config_test::{test_server_config, CERT_PEM, CERT_PIN};
+    use std::sync::Arc;
     #[test]
     fn create_tls() {
 
@@ -241,6 +319,133 @@ mod tls_connection_test{
     }
 
 
-   
+    /// Answers one DoT query on a local port with `CERT_PEM`, echoing the question.
+    async fn local_dot_server() -> (u16, tokio::task::JoinHandle<()>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let acceptor = tokio_rustls::TlsAcceptor::from(Arc::new(test_server_config()));
+        let handle = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut tls_stream = match acceptor.accept(stream).await {
+                Ok(tls_stream) => tls_stream,
+                Err(_) => return,
+            };
+            let mut length = [0u8; 2];
+            tls_stream.read_exact(&mut length).await.unwrap();
+            let mut msg = vec![0u8; u16::from_be_bytes(length) as usize];
+            tls_stream.read_exact(&mut msg).await.unwrap();
+
+            // A response to another query comes first and must be skipped
+            let mut response = DnsMessage::from_bytes(&msg).unwrap();
+            let mut header = response.get_header();
+            header.set_qr(true);
+            response.set_header(header);
+            for id in vec![response.get_query_id().wrapping_add(1), response.get_query_id()] {
+                response.set_query_id(id);
+                let bytes = response.to_bytes();
+                tls_stream.write_all(&(bytes.len() as u16).to_be_bytes()).await.unwrap();
+                tls_stream.write_all(&bytes).await.unwrap();
+            }
+        });
+        (port, handle)
+    }
 
-}
\ No newline at end of file
+    fn local_connection(port: u16, tls_config: TlsConfig) -> ClientTLSConnection {
+        let mut conn = ClientTLSConnection::new_default(IpAddr::V4(Ipv4Addr::LOCALHOST), Duration::from_secs(5));
+        conn.set_socket_addr(SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), port));
+        conn.set_bind_addr(Some(SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 0)));
+        conn.set_tls_config(tls_config);
+        conn
+    }
+
+    fn query() -> DnsMessage {
+        let mut domain_name = DomainName::new();
+        domain_name.set_name("example.com".to_string());
+        DnsMessage::new_query_message(domain_name, Rrtype::A, Rclass::IN, 0, true, 1)
+    }
+
+    #[test]
+    fn get_and_set_port_and_tls_config() {
+        let ip_addr = IpAddr::V4(Ipv4Addr::new(192, 168, 0, 1));
+        let mut conn = ClientTLSConnection::new(ip_addr, Duration::from_secs(100), 0);
+
+        assert_eq!(conn.get_port(), 853);
+        assert_eq!(conn.get_tls_config(), &TlsConfig::default());
+
+        let mut tls_config = TlsConfig::new();
+        tls_config.set_auth_name(Some("dns.example.com".to_string()));
+        conn.set_port(8853);
+        conn.set_tls_config(tls_config.clone());
+
+        assert_eq!(conn.get_port(), 8853);
+        assert_eq!(conn.get_tls_config(), &tls_config);
+    }
+
+    #[tokio::test]
+    async fn send_with_private_ca() {
+        let (port, server) = local_dot_server().await;
+        let mut tls_config = TlsConfig::new();
+        tls_config.set_use_native_roots(false);
+        tls_config.add_root_certs_from_pem(CERT_PEM.as_bytes()).unwrap();
+
+        let response = local_connection(port, tls_config).send(query()).await.unwrap();
+        let response = DnsMessage::from_bytes(&response).unwrap();
+
+        assert_eq!(response.get_query_id(), 1);
+        assert!(response.get_header().get_qr());
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn send_with_auth_name() {
+        let (port, server) = local_dot_server().await;
+        let mut tls_config = TlsConfig::new();
+        tls_config.set_use_native_roots(false);
+        tls_config.add_root_certs_from_pem(CERT_PEM.as_bytes()).unwrap();
+        tls_config.set_auth_name(Some("localhost".to_string()));
+
+        let response = local_connection(port, tls_config).send(query()).await;
+
+        assert!(response.is_ok());
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn send_with_spki_pin() {
+        let (port, server) = local_dot_server().await;
+        let mut tls_config = TlsConfig::new();
+        tls_config.set_use_native_roots(false);
+        tls_config.add_spki_pin(CERT_PIN).unwrap();
+
+        let response = local_connection(port, tls_config).send(query()).await;
+
+        assert!(response.is_ok());
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn strict_profile_rejects_unpinned_server() {
+        let (port, server) = local_dot_server().await;
+        let mut tls_config = TlsConfig::new();
+        tls_config.set_use_native_roots(false);
+        tls_config.add_spki_pin("AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=").unwrap();
+
+        let response = local_connection(port, tls_config).send(query()).await;
+
+        assert!(response.is_err());
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn opportunistic_profile_accepts_unauthenticated_server() {
+        let (port, server) = local_dot_server().await;
+        let mut tls_config = TlsConfig::new();
+        tls_config.set_use_native_roots(false);
+        tls_config.set_profile(UsageProfile::Opportunistic);
+
+        let response = local_connection(port, tls_config).send(query()).await;
+
+        assert!(response.is_ok());
+        server.await.unwrap();
+    }
+}
@@ -1,4 +1,5 @@
-use crate::client::ClientConnection;
+use crate::client::dns_transport::DnsTransport;
+use crate::client::client_connection::{DNS_PORT, local_addr_for};
 use crate::message::DnsMessage;
 use crate::message::rdata::Rdata;
 use crate::message::rdata::a_rdata::ARdata;
@@ -12,45 +13,46 @@ use std::io::Error as IoError;
 use std::io::ErrorKind;
 use tokio::net::UdpSocket;
 use super::client_error::ClientError;
+use super::response_match::match_response;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct  ClientUDPConnection {
     /// addr to connect
     server_addr: IpAddr,
+    /// port to connect
+    port: u16,
+    /// local address of the socket, chosen by the system if `None`
+    bind_addr: Option<SocketAddr>,
     /// read timeout
     timeout: tokio::time::Duration,
+    /// whether the query name was sent with 0x20 mixed case
+    use_0x20: bool,
     /// payload size
     payload_size: usize,
 }
 
-#[async_trait]
-impl ClientConnection for ClientUDPConnection {
-
+impl ClientUDPConnection {
     /// Creates ClientUDPConnection
-    fn new(server_addr:IpAddr, timeout:Duration, payload_size: usize) -> Self {
+    pub fn new(server_addr:IpAddr, timeout:Duration, payload_size: usize) -> Self {
         ClientUDPConnection {
             server_addr: server_addr,
+            port: DNS_PORT,
+            bind_addr: None,
             timeout: timeout,
+            use_0x20: false,
             payload_size: payload_size,
         }
     }
 
-    /// implement get_ip
-    /// returns IpAddr
-    fn get_ip(&self) -> IpAddr {
-        self.server_addr.clone()
-    }
-
-    async fn send(self, dns_query:DnsMessage) -> Result<Vec<u8>, ClientError> { 
+    pub async fn send(self, dns_query:DnsMessage) -> Result<Vec<u8>, ClientError> { 
     // async fn send(self, dns_query:DnsMessage) -> Result<(Vec<u8>, IpAddr), ClientError> { 
 
         let conn_timeout:Duration = self.timeout;
-        let server_addr = SocketAddr::new(self.get_server_addr(), 53);
+        let server_addr = self.get_socket_addr();
 
         let dns_query_bytes = dns_query.to_bytes(); 
 
-        //FIXME: chage port 
-        let socket_udp = UdpSocket::bind("0.0.0.0:0").await?; //FIXME: type error
+        let socket_udp = UdpSocket::bind(local_addr_for(server_addr, self.bind_addr)).await?;
 
         // let socket_udp:UdpSocket = match UdpSocket::bind("0.0.0.0:0"){ //FIXME:
         //     Err(e) => return Err(IoError::new(ErrorKind::Other, format!("Error: could not bind socket {}", e))).map_err(Into::into),
@@ -68,28 +70,29 @@ impl ClientConnection for ClientUDPConnection {
             Ok(_) => (),
         };
         
-        let mut msg = vec![0;self.payload_size];
-        //FIXME: not always is timeout error, since it doesn't have to be wait for the timeout return
-        // and error, is just an IO error, the timeout error should come by itself from the timeout function
-        let result = match timeout(conn_timeout, socket_udp.recv_from(&mut msg)).await {
-            Ok(val) => val,
-            Err(_) => return Err(ClientError::Io(IoError::new(ErrorKind::TimedOut, format!("Error: timeout"))).into()),
+        // Datagrams that do not come from the server or do not answer the
+        // query are discarded, and the socket keeps waiting until the timeout.
+        let receive_task = async {
+            let mut msg = vec![0; self.payload_size];
+            loop {
+                let (n, src_addr) = socket_udp.recv_from(&mut msg).await
+                    .map_err(|e| IoError::other(format!("Error: could not read {}", e)))?;
+
+                if src_addr != server_addr {
+                    continue;
+                }
+
+                if match_response(&dns_query, &msg[..n], self.use_0x20).is_some() {
+                    return Ok::<_, IoError>(msg[..n].to_vec());
+                }
+            }
         };
 
-        let (_, src_addr) = match result {
-            Err(e) => return Err(IoError::new(ErrorKind::Other, format!("Error: could not read {}", e))).map_err(Into::into),
-            Ok((n, addr)) => (n, addr),
+        let response = match timeout(conn_timeout, receive_task).await {
+            Ok(val) => val?,
+            Err(_) => return Err(ClientError::Io(IoError::new(ErrorKind::TimedOut, "Error: timeout"))),
         };
 
-        // Verify that the response comes from the expected IP
-        let expected_ip = self.get_server_addr();
-        let actual_ip = src_addr.ip();
-        if actual_ip != expected_ip {
-            return Err(ClientError::Io(IoError::new(
-                ErrorKind::PermissionDenied,
-                format!("IP mismatch: expected {}, got {}", expected_ip, actual_ip),
-            )).into());
-        }
         let ip = self.get_server_addr();
         let mut additionals = dns_query.get_additional();
         let mut ar = ARdata::new();
@@ -98,17 +101,30 @@ impl ClientConnection for ClientUDPConnection {
         let rr = ResourceRecord::new(a_rdata);
         additionals.push(rr);
        
-        let (fin, _) = result?;
         drop(socket_udp);
-        return Ok(msg[0..fin].to_vec());
+        Ok(response)
     }
     // TODO create a global variable or remove new_default dependency
-    fn new_default(server_addr: IpAddr, timeout: Duration) -> Self {
+    pub fn new_default(server_addr: IpAddr, timeout: Duration) -> Self {
         const RECOMENDED_MAX_SIZE: usize = 4000;
         Self::new(server_addr, timeout, RECOMENDED_MAX_SIZE)
     }
 }
 
+#[async_trait]
+impl DnsTransport for ClientUDPConnection {
+    async fn send_query(&self, dns_query: &DnsMessage, timeout: Duration, use_0x20: bool) -> Result<Vec<u8>, ClientError> {
+        let mut conn = *self;
+        conn.set_timeout(timeout);
+        conn.set_use_0x20(use_0x20);
+        conn.send(dns_query.clone()).await
+    }
+
+    fn get_ip(&self) -> IpAddr {
+        self.server_addr
+    }
+}
+
 // Getters
 impl ClientUDPConnection {
 
@@ -116,10 +132,26 @@ impl ClientUDPConnection {
         return self.server_addr.clone();
     }
 
+    pub fn get_port(&self)-> u16 {
+        self.port
+    }
+
+    /// Returns the address and port the queries are sent to.
+    pub fn get_socket_addr(&self)-> SocketAddr {
+        SocketAddr::new(self.server_addr, self.port)
+    }
+
+    pub fn get_bind_addr(&self)-> Option<SocketAddr> {
+        self.bind_addr
+    }
+
     pub fn get_timeout(&self)-> Duration {
         return self.timeout.clone();
     }
 
+    pub fn get_use_0x20(&self)-> bool {
+        self.use_0x20
+    }
 
 }
 
@@ -130,9 +162,31 @@ impl ClientUDPConnection {
         self.server_addr = addr;
     }
 
+    pub fn set_port(&mut self, port: u16) {
+        self.port = port;
+    }
+
+    /// Sets the address and port the queries are sent to.
+    pub fn set_socket_addr(&mut self, addr: SocketAddr) {
+        self.server_addr = addr.ip();
+        self.port = addr.port();
+    }
+
+    /// Sets the local address of the socket, which selects the interface
+    /// the queries leave from. With `None` the system chooses it.
+    pub fn set_bind_addr(&mut self, bind_addr: Option<SocketAddr>) {
+        self.bind_addr = bind_addr;
+    }
+
     pub fn set_timeout(&mut self, timeout: Duration) {
         self.timeout = timeout;
     }
+
+    /// Sets whether the query name was sent in 0x20 mixed case. If so,
+    /// responses that do not echo that case are discarded.
+    pub fn set_use_0x20(&mut self, use_0x20: bool) {
+        self.use_0x20 = use_0x20;
+    }
 }
 
 #[cfg(test)]
@@ -309,4 +363,94 @@ mod udp_connection_test{
             sleep(Duration::from_secs(1)).await;
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn get_and_set_socket_and_bind_addr() {
+        let ip_addr = IpAddr::V4(Ipv4Addr::new(192, 168, 0, 1));
+        let mut conn = ClientUDPConnection::new(ip_addr, Duration::from_secs(100), DEFAULT_SIZE);
+
+        assert_eq!(conn.get_socket_addr(), SocketAddr::new(ip_addr, 53));
+        assert_eq!(conn.get_bind_addr(), None);
+
+        let server_addr: SocketAddr = "[2001:db8::1]:5353".parse().unwrap();
+        let bind_addr: SocketAddr = "[2001:db8::2]:0".parse().unwrap();
+        conn.set_socket_addr(server_addr);
+        conn.set_bind_addr(Some(bind_addr));
+
+        assert_eq!(conn.get_server_addr(), server_addr.ip());
+        assert_eq!(conn.get_port(), 5353);
+        assert_eq!(conn.get_bind_addr(), Some(bind_addr));
+    }
+
+    #[tokio::test]
+    async fn send_to_ipv6_server_on_high_port() {
+        let server = UdpSocket::bind("[::1]:0").await.unwrap();
+        let mut conn = ClientUDPConnection::new_default(IpAddr::V6(Ipv6Addr::LOCALHOST), Duration::from_secs(2));
+        conn.set_socket_addr(server.local_addr().unwrap());
+        conn.set_bind_addr(Some("[::1]:0".parse().unwrap()));
+
+        let server_task = tokio::spawn(async move {
+            let mut buf = vec![0u8; 512];
+            let (n, client_addr) = server.recv_from(&mut buf).await.unwrap();
+            assert_eq!(client_addr.ip(), IpAddr::V6(Ipv6Addr::LOCALHOST));
+
+            let mut response = DnsMessage::from_bytes(&buf[..n]).unwrap();
+            let mut header = response.get_header();
+            header.set_qr(true);
+            response.set_header(header);
+
+            // A datagram from another address and one for another query
+            // come first, and must both be discarded
+            let spoofer = UdpSocket::bind("[::1]:0").await.unwrap();
+            spoofer.send_to(&response.to_bytes(), client_addr).await.unwrap();
+            let mut other = response.clone();
+            other.set_query_id(response.get_query_id().wrapping_add(1));
+            server.send_to(&other.to_bytes(), client_addr).await.unwrap();
+            server.send_to(&response.to_bytes(), client_addr).await.unwrap();
+        });
+
+        let domain_name = DomainName::new_from_str("example.com");
+        let dns_query = DnsMessage::new_query_message(domain_name, Rrtype::A, Rclass::IN, 0, false, 7);
+        let response = conn.send(dns_query).await.unwrap();
+        let response = DnsMessage::from_bytes(&response).unwrap();
+
+        assert_eq!(response.get_query_id(), 7);
+        assert!(response.get_header().get_qr());
+        server_task.await.unwrap();
+    }
+    #[tokio::test]
+    async fn send_discards_response_with_other_case() {
+        let server = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let mut conn = ClientUDPConnection::new_default(IpAddr::V4(Ipv4Addr::LOCALHOST), Duration::from_secs(2));
+        conn.set_socket_addr(server.local_addr().unwrap());
+        conn.set_use_0x20(true);
+
+        let server_task = tokio::spawn(async move {
+            let mut buf = vec![0u8; 512];
+            let (n, client_addr) = server.recv_from(&mut buf).await.unwrap();
+
+            let mut response = DnsMessage::from_bytes(&buf[..n]).unwrap();
+            let mut header = response.get_header();
+            header.set_qr(true);
+            response.set_header(header);
+
+            // A guess with the right ID but the name in lowercase comes
+            // first, and must not end the wait
+            let mut guess = DnsMessage::new_query_message(
+                DomainName::new_from_str("example.com"), Rrtype::A, Rclass::IN, 0, false, response.get_query_id());
+            let mut header = guess.get_header();
+            header.set_qr(true);
+            guess.set_header(header);
+            server.send_to(&guess.to_bytes(), client_addr).await.unwrap();
+            server.send_to(&response.to_bytes(), client_addr).await.unwrap();
+        });
+
+        let domain_name = DomainName::new_from_str("ExAmPlE.cOm");
+        let dns_query = DnsMessage::new_query_message(domain_name, Rrtype::A, Rclass::IN, 0, false, 7);
+        let response = conn.send(dns_query).await.unwrap();
+        let response = DnsMessage::from_bytes(&response).unwrap();
+
+        assert_eq!(response.get_question().get_qname().to_string(), "ExAmPlE.cOm");
+        server_task.await.unwrap();
+    }
+}
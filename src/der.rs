@@ -0,0 +1,72 @@
+//! Minimal reader for DER encoded data, enough to walk the PKCS#8 private
+//! keys of DNSSEC and the X.509 certificates of the TLS transports.
+//!
+//! [X.690](https://www.itu.int/rec/T-REC-X.690) section 8.1: each element
+//! is its identifier octet, its length, in short form or in long form of up
+//! to four octets, and its contents.
+
+/// Reads the DER element at the start of `data`, returning its tag, its
+/// contents and the data after it.
+pub fn der_element(data: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let (&tag, rest) = data.split_first()?;
+    let (&first, rest) = rest.split_first()?;
+    let (length, rest) = match first {
+        length if length < 0x80 => (length as usize, rest),
+        octets if (0x81..=0x84).contains(&octets) => {
+            let octets = (octets & 0x7f) as usize;
+            if rest.len() < octets {
+                return None;
+            }
+            let length = rest[..octets].iter().fold(0, |length, &octet| (length << 8) | octet as usize);
+            (length, &rest[octets..])
+        }
+        _ => return None,
+    };
+    if rest.len() < length {
+        return None;
+    }
+
+    Some((tag, &rest[..length], &rest[length..]))
+}
+
+/// Reads the DER elements inside a constructed element.
+pub fn der_elements(mut data: &[u8]) -> Option<Vec<(u8, &[u8])>> {
+    let mut elements = Vec::new();
+    while !data.is_empty() {
+        let (tag, contents, rest) = der_element(data)?;
+        elements.push((tag, contents));
+        data = rest;
+    }
+    Some(elements)
+}
+
+#[cfg(test)]
+mod der_test {
+    use super::*;
+
+    #[test]
+    fn der_element_short_and_long_form() {
+        let short = [0x02, 0x01, 0x05, 0xff];
+        let mut long = vec![0x04, 0x81, 0x80];
+        long.extend(vec![0xab; 0x80]);
+
+        assert_eq!(der_element(&short), Some((0x02, &[0x05][..], &[0xff][..])));
+        assert_eq!(der_element(&long), Some((0x04, &long[3..], &[][..])));
+    }
+
+    #[test]
+    fn der_element_invalid() {
+        assert_eq!(der_element(&[]), None);
+        assert_eq!(der_element(&[0x30, 0x03, 0x01]), None);
+        assert_eq!(der_element(&[0x30, 0x80, 0x00, 0x00]), None);
+        assert_eq!(der_element(&[0x30, 0x82, 0x01]), None);
+    }
+
+    #[test]
+    fn der_elements_of_sequence() {
+        let sequence = [0x02, 0x01, 0x00, 0x04, 0x02, 0xca, 0xfe];
+
+        assert_eq!(der_elements(&sequence), Some(vec![(0x02, &[0x00][..]), (0x04, &[0xca, 0xfe][..])]));
+        assert_eq!(der_elements(&sequence[..6]), None);
+    }
+}
@@ -2,4 +2,9 @@ pub mod dnssec_message;
 pub mod dnssec_message_processing;
 pub mod dnssec_fetch;
 pub mod rrset_signature;
-mod dnssec_algorithm;
\ No newline at end of file
+pub mod trust_anchor;
+pub mod trust_anchor_store;
+pub mod denial;
+pub mod signer;
+pub mod dnssec_key;
+pub mod dnssec_algorithm;
\ No newline at end of file
@@ -0,0 +1,717 @@
+//! Authenticated denial of existence with NSEC and NSEC3 records.
+//!
+//! [RFC 4035](https://datatracker.ietf.org/doc/html/rfc4035#section-5.4)
+//! [RFC 5155](https://datatracker.ietf.org/doc/html/rfc5155#section-8)
+//!
+//! The functions of this module only check what the records prove. The
+//! signatures of the NSEC and NSEC3 RRsets must be validated beforehand.
+
+use crate::domain_name::DomainName;
+use crate::message::rdata::nsec3_rdata::Nsec3Rdata;
+use crate::message::rdata::nsec_rdata::NsecRdata;
+use crate::message::rdata::presentation::{format_base32hex, parse_base32hex};
+use crate::message::rdata::Rdata;
+use crate::message::resource_record::ResourceRecord;
+use crate::message::rrtype::Rrtype;
+use sha1::{Digest, Sha1};
+
+/// Flag of the NSEC3 RDATA set when the record may cover unsigned
+/// delegations.
+///
+/// [RFC 5155](https://datatracker.ietf.org/doc/html/rfc5155#section-3.1.2.1)
+const OPT_OUT_FLAG: u8 = 0x01;
+
+/// Largest number of extra NSEC3 hash iterations accepted.
+///
+/// [RFC 9276](https://datatracker.ietf.org/doc/html/rfc9276#section-3.2)
+///
+///    Validating resolvers MAY return an insecure response to their
+///    clients when processing NSEC3 records with iterations larger than
+///    0.  [...]  Validating resolvers MAY also return a SERVFAIL response
+///    when processing NSEC3 records with iterations larger than 0.
+///
+/// Records with more iterations are ignored, so they prove nothing.
+pub(crate) const MAX_ITERATIONS: u16 = 150;
+
+/// What a set of NSEC or NSEC3 records proves about a name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DenialVerdict {
+    /// The name does not exist and no wildcard could have matched it
+    /// (NXDOMAIN).
+    NxDomain,
+    /// The name exists but has no RRset of the queried type (NODATA).
+    NoData,
+    /// The name does not exist, and the wildcard that matches it has no
+    /// RRset of the queried type.
+    WildcardNoData,
+    /// The name does not exist, so the answer could be synthesized from a
+    /// wildcard.
+    WildcardAnswer,
+    /// The name falls in the span of an NSEC3 record with the Opt-Out flag,
+    /// so an unsigned delegation may exist there. The answer is insecure.
+    OptOut,
+    /// The name is at or below a delegation without DS RRset, which is
+    /// held. Everything below it is insecure.
+    InsecureDelegation(DomainName),
+    /// The records do not prove the absence.
+    Unproven,
+}
+
+/// Closest encloser of a name proven by NSEC3 records.
+///
+/// [RFC 5155](https://datatracker.ietf.org/doc/html/rfc5155#section-7.2.1)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClosestEncloser {
+    /// Longest existing ancestor of the name.
+    pub closest_encloser: DomainName,
+    /// Ancestor of the name one label longer than the closest encloser,
+    /// which does not exist.
+    pub next_closer: DomainName,
+    /// Whether the NSEC3 RR covering the next closer name has the Opt-Out
+    /// flag.
+    pub opt_out: bool,
+    /// Types present at the closest encloser.
+    pub types: Vec<Rrtype>,
+}
+
+/// Returns whether `name` falls strictly between the owner and the next
+/// name of an NSEC RR, in canonical order. The last NSEC of a zone points
+/// back to the apex.
+///
+/// # Example
+/// ```
+/// let name = |name: &str| DomainName::new_from_str(name);
+///
+/// assert!(nsec_covers(&name("a.example"), &name("d.example"), &name("b.example")));
+/// ```
+pub fn nsec_covers(owner: &DomainName, next: &DomainName, name: &DomainName) -> bool {
+    if owner < next {
+        owner < name && name < next
+    } else {
+        owner < name || name < next
+    }
+}
+
+/// Returns whether the owner of an NSEC RR is `name`, so its type bit map
+/// lists the types at `name`.
+pub fn nsec_matches(owner: &DomainName, name: &DomainName) -> bool {
+    owner == name
+}
+
+/// Computes the NSEC3 hash of a name.
+///
+/// [RFC 5155](https://datatracker.ietf.org/doc/html/rfc5155#section-5)
+///
+/// ```text
+/// IH(salt, x, 0) = H(x || salt), and
+/// IH(salt, x, k) = H(IH(salt, x, k-1) || salt), if k > 0
+///
+/// Then the calculated hash of an owner name is
+///
+///    IH(salt, owner name, iterations),
+///
+/// where the owner name is in the canonical form, defined as:
+///
+/// The wire format of the owner name where:
+///
+/// 1.  The owner name is fully expanded (no DNS name compression) and
+///     fully qualified;
+///
+/// 2.  All uppercase US-ASCII letters are replaced by the corresponding
+///     lowercase US-ASCII letters;
+///
+/// 3.  If the owner name is a wildcard name, the owner name is in its
+///     original unexpanded form, including the "*" label (no wildcard
+///     substitution);
+/// ```
+pub fn nsec3_hash(name: &DomainName, salt: &[u8], iterations: u16) -> Vec<u8> {
+    let mut hash = Sha1::new()
+        .chain_update(name.to_canonical().to_bytes())
+        .chain_update(salt)
+        .finalize();
+    for _ in 0..iterations {
+        hash = Sha1::new().chain_update(hash).chain_update(salt).finalize();
+    }
+
+    hash.to_vec()
+}
+
+/// Returns the owner of the NSEC3 RR of `name` in `zone`: the base32hex
+/// encoding of its hash prepended as a label to the zone.
+///
+/// # Example
+/// ```
+/// let zone = DomainName::new_from_str("example");
+/// let owner = nsec3_hashed_name(&zone, &zone, &[0xaa, 0xbb, 0xcc, 0xdd], 12);
+///
+/// assert_eq!(owner.to_string(), "0p9mhaveqvm6t7vbl5lop2u3t2rp3tom.example");
+/// ```
+pub fn nsec3_hashed_name(name: &DomainName, zone: &DomainName, salt: &[u8], iterations: u16) -> DomainName {
+    let label = format_base32hex(&nsec3_hash(name, salt, iterations));
+    zone.child(label.as_bytes())
+        .expect("a base32hex SHA-1 hash fits in a label")
+}
+
+/// Returns whether the NSEC3 RR with `owner` and `nsec3` matches `name`,
+/// that is, its owner is the hashed form of `name`.
+pub fn nsec3_matches(owner: &DomainName, nsec3: &Nsec3Rdata, name: &DomainName) -> bool {
+    match nsec3_hashes(owner, nsec3, name) {
+        Some((owner_hash, hash)) => owner_hash == hash,
+        None => false,
+    }
+}
+
+/// Returns whether the NSEC3 RR with `owner` and `nsec3` covers `name`,
+/// that is, the hash of `name` falls strictly between the owner hash and
+/// the next hashed owner name. The last NSEC3 of a zone points back to the
+/// first one.
+pub fn nsec3_covers(owner: &DomainName, nsec3: &Nsec3Rdata, name: &DomainName) -> bool {
+    let Some((owner_hash, hash)) = nsec3_hashes(owner, nsec3, name) else { return false };
+    let next = nsec3.get_next_hashed_owner_name();
+
+    if owner_hash < next {
+        owner_hash < hash && hash < next
+    } else {
+        owner_hash < hash || hash < next
+    }
+}
+
+/// Returns the hash in the owner of an NSEC3 RR and the hash of `name`
+/// with its parameters, or `None` when the record cannot be used for
+/// `name`.
+fn nsec3_hashes(owner: &DomainName, nsec3: &Nsec3Rdata, name: &DomainName) -> Option<(Vec<u8>, Vec<u8>)> {
+    if !nsec3.get_hash_algorithm().is_supported() || nsec3.get_iterations() > MAX_ITERATIONS {
+        return None;
+    }
+    let zone = owner.parent()?;
+    if !name.is_subdomain_of(&zone) {
+        return None;
+    }
+    let label = owner.get_labels().first()?.clone();
+    let owner_hash = parse_base32hex(&String::from_utf8_lossy(&label)).ok()?;
+
+    Some((owner_hash, nsec3_hash(name, &nsec3.get_salt(), nsec3.get_iterations())))
+}
+
+/// Finds the closest encloser of `name` proven by NSEC3 records.
+///
+/// [RFC 5155](https://datatracker.ietf.org/doc/html/rfc5155#section-8.3)
+///
+/// ```text
+/// 8.3.  Closest Encloser Proof
+///
+///    For each NSEC3 RR considered, the validator MUST check that the
+///    owner name of the NSEC3 RR matches the closest encloser, and that
+///    another NSEC3 RR covers the next closer name.
+/// ```
+pub fn closest_encloser(name: &DomainName, nsec3s: &[(DomainName, Nsec3Rdata)]) -> Option<ClosestEncloser> {
+    let mut next_closer = name.clone();
+    let mut candidate = name.parent()?;
+
+    loop {
+        if let Some((_, matching)) = nsec3s.iter().find(|(owner, nsec3)| nsec3_matches(owner, nsec3, &candidate)) {
+            let (_, covering) = nsec3s
+                .iter()
+                .find(|(owner, nsec3)| nsec3_covers(owner, nsec3, &next_closer))?;
+            return Some(ClosestEncloser {
+                closest_encloser: candidate,
+                next_closer,
+                opt_out: covering.get_flags() & OPT_OUT_FLAG != 0,
+                types: matching.get_type_bit_maps(),
+            });
+        }
+        next_closer = candidate.clone();
+        candidate = candidate.parent()?;
+    }
+}
+
+/// Checks the proof that `qname` has no RRset of type `qtype`.
+///
+/// The NSEC records of `proof` are used if there are any, otherwise the
+/// NSEC3 records. Other records are ignored.
+///
+/// # Example
+/// ```
+/// let verdict = verify_denial(&DomainName::new_from_str("b.example"), Rrtype::A, &authority);
+/// assert_eq!(verdict, DenialVerdict::NxDomain);
+/// ```
+pub fn verify_denial(qname: &DomainName, qtype: Rrtype, proof: &[ResourceRecord]) -> DenialVerdict {
+    let nsecs = nsec_records(proof);
+    if !nsecs.is_empty() {
+        return verify_nsec_denial(qname, qtype, &nsecs);
+    }
+
+    verify_nsec3_denial(qname, qtype, &nsec3_records(proof))
+}
+
+/// Checks that an answer synthesized from a wildcard was legitimate: no
+/// name closer to `qname` than the wildcard exists.
+///
+/// `labels` is the Labels field of the RRSIG RR of the answer, the number
+/// of labels of the wildcard owner without the `*` label. The NSEC or NSEC3
+/// records must prove that the next closer name, the ancestor of `qname`
+/// with one label more, does not exist.
+///
+/// [RFC 4035](https://datatracker.ietf.org/doc/html/rfc4035#section-5.3.4)
+///
+///    If the RRset is the result of wildcard expansion, the resolver MUST
+///    verify that the NSEC RRs prove that no closer match exists.
+///
+/// [RFC 5155](https://datatracker.ietf.org/doc/html/rfc5155#section-8.8)
+///
+///    The verifier MUST verify that there is an NSEC3 RR that covers the
+///    "next closer" name to QNAME present in the response.
+pub fn verify_wildcard_answer(qname: &DomainName, labels: u8, proof: &[ResourceRecord]) -> DenialVerdict {
+    let labels = labels as usize;
+    if labels >= qname.label_count() {
+        return DenialVerdict::Unproven;
+    }
+
+    let next_closer = ancestor(qname, labels + 1);
+    let nsecs = nsec_records(proof);
+    if !nsecs.is_empty() {
+        // An NSEC whose next name is below the next closer name proves it
+        // is an empty non-terminal, which exists
+        let covered = nsecs.iter().any(|(owner, nsec)| {
+            let next = nsec.get_next_domain_name();
+            nsec_covers(owner, &next, &next_closer) && !next.is_subdomain_of(&next_closer)
+        });
+        return if covered { DenialVerdict::WildcardAnswer } else { DenialVerdict::Unproven };
+    }
+
+    let nsec3s = nsec3_records(proof);
+    match nsec3s.iter().find(|(owner, nsec3)| nsec3_covers(owner, nsec3, &next_closer)) {
+        Some((_, nsec3)) if nsec3.get_flags() & OPT_OUT_FLAG != 0 => DenialVerdict::OptOut,
+        Some(_) => DenialVerdict::WildcardAnswer,
+        None => DenialVerdict::Unproven,
+    }
+}
+
+/// Checks a denial of existence with NSEC records.
+///
+/// [RFC 4035](https://datatracker.ietf.org/doc/html/rfc4035#section-3.1.3)
+fn verify_nsec_denial(qname: &DomainName, qtype: Rrtype, nsecs: &[(DomainName, NsecRdata)]) -> DenialVerdict {
+    // NODATA: the NSEC of the name itself does not list the type
+    if let Some((_, nsec)) = nsecs.iter().find(|(owner, _)| nsec_matches(owner, qname)) {
+        return no_data(qname, qtype, &nsec.get_type_bit_maps());
+    }
+
+    // An NSEC from above a delegation proves nothing below it, except that
+    // the delegation has no DS RRset
+    if let Some((owner, nsec)) = nsecs.iter().find(|(owner, nsec)| {
+        qname.is_subdomain_of(owner) && is_delegation(&nsec.get_type_bit_maps())
+    }) {
+        return below_delegation(owner, &nsec.get_type_bit_maps());
+    }
+
+    let Some((owner, nsec)) = nsecs
+        .iter()
+        .find(|(owner, nsec)| nsec_covers(owner, &nsec.get_next_domain_name(), qname))
+    else {
+        return DenialVerdict::Unproven;
+    };
+
+    // An empty non-terminal exists without any NSEC of its own: the next
+    // name after it is below it
+    let next = nsec.get_next_domain_name();
+    if next.is_subdomain_of(qname) {
+        return DenialVerdict::NoData;
+    }
+
+    let closest_encloser = nsec_closest_encloser(qname, owner, &next);
+    let Ok(wildcard) = closest_encloser.child(b"*") else { return DenialVerdict::Unproven };
+
+    if let Some((_, nsec)) = nsecs.iter().find(|(owner, _)| nsec_matches(owner, &wildcard)) {
+        return match no_data(&wildcard, qtype, &nsec.get_type_bit_maps()) {
+            DenialVerdict::NoData => DenialVerdict::WildcardNoData,
+            verdict => verdict,
+        };
+    }
+    if nsecs
+        .iter()
+        .any(|(owner, nsec)| nsec_covers(owner, &nsec.get_next_domain_name(), &wildcard))
+    {
+        return DenialVerdict::NxDomain;
+    }
+
+    DenialVerdict::Unproven
+}
+
+/// Returns the closest encloser of `qname` proven by the NSEC RR with
+/// `owner` and `next` that covers it: the longest ancestor shared by the
+/// name and either end of the record.
+fn nsec_closest_encloser(qname: &DomainName, owner: &DomainName, next: &DomainName) -> DomainName {
+    vec![common_ancestor(qname, owner), common_ancestor(qname, next)]
+        .into_iter()
+        .max_by_key(|name| name.label_count())
+        .unwrap_or_default()
+}
+
+/// Returns the closest encloser of `qname` when the records prove that
+/// `qname` does not exist, so that an answer can be synthesized from the
+/// wildcard below it.
+///
+/// [RFC 8198](https://datatracker.ietf.org/doc/html/rfc8198#section-5.3)
+///
+/// ```text
+///    With DNSSEC, the resolver can synthesize answers from wildcards that
+///    it has in its cache, if the NSEC or NSEC3 RRs prove that the QNAME
+///    does not exist and that the wildcard is at the closest encloser.
+/// ```
+///
+/// Names below a delegation or in an Opt-Out span have no proven closest
+/// encloser.
+pub fn proven_closest_encloser(qname: &DomainName, proof: &[ResourceRecord]) -> Option<DomainName> {
+    let nsecs = nsec_records(proof);
+    if !nsecs.is_empty() {
+        if nsecs.iter().any(|(owner, nsec)| {
+            nsec_matches(owner, qname) || qname.is_subdomain_of(owner) && is_delegation(&nsec.get_type_bit_maps())
+        }) {
+            return None;
+        }
+        let (owner, nsec) = nsecs
+            .iter()
+            .find(|(owner, nsec)| nsec_covers(owner, &nsec.get_next_domain_name(), qname))?;
+        let next = nsec.get_next_domain_name();
+        if next.is_subdomain_of(qname) {
+            return None;
+        }
+        return Some(nsec_closest_encloser(qname, owner, &next));
+    }
+
+    let proof = closest_encloser(qname, &nsec3_records(proof))?;
+    if is_delegation(&proof.types) || proof.opt_out {
+        return None;
+    }
+
+    Some(proof.closest_encloser)
+}
+
+/// Checks a denial of existence with NSEC3 records.
+///
+/// [RFC 5155](https://datatracker.ietf.org/doc/html/rfc5155#section-8.4)
+fn verify_nsec3_denial(qname: &DomainName, qtype: Rrtype, nsec3s: &[(DomainName, Nsec3Rdata)]) -> DenialVerdict {
+    // NODATA (sections 8.5 and 8.6): the NSEC3 of the name does not list the type
+    if let Some((_, nsec3)) = nsec3s.iter().find(|(owner, nsec3)| nsec3_matches(owner, nsec3, qname)) {
+        return no_data(qname, qtype, &nsec3.get_type_bit_maps());
+    }
+
+    let Some(proof) = closest_encloser(qname, nsec3s) else { return DenialVerdict::Unproven };
+    if is_delegation(&proof.types) {
+        return below_delegation(&proof.closest_encloser, &proof.types);
+    }
+    // Section 9.2: an Opt-Out span may hide an unsigned delegation
+    if proof.opt_out {
+        return DenialVerdict::OptOut;
+    }
+
+    let Ok(wildcard) = proof.closest_encloser.child(b"*") else { return DenialVerdict::Unproven };
+    // Section 8.7: wildcard NODATA
+    if let Some((_, nsec3)) = nsec3s.iter().find(|(owner, nsec3)| nsec3_matches(owner, nsec3, &wildcard)) {
+        return match no_data(&wildcard, qtype, &nsec3.get_type_bit_maps()) {
+            DenialVerdict::NoData => DenialVerdict::WildcardNoData,
+            verdict => verdict,
+        };
+    }
+    // Section 8.4: name error
+    if nsec3s.iter().any(|(owner, nsec3)| nsec3_covers(owner, nsec3, &wildcard)) {
+        return DenialVerdict::NxDomain;
+    }
+
+    DenialVerdict::Unproven
+}
+
+/// Returns what the types present at `name` prove about `qtype`.
+///
+/// A delegation point only proves the absence of a DS RRset, as the other
+/// types are held by the child zone. The existence of a CNAME RRset would
+/// have made the answer follow it.
+fn no_data(name: &DomainName, qtype: Rrtype, types: &[Rrtype]) -> DenialVerdict {
+    if types.contains(&qtype) || types.contains(&Rrtype::CNAME) {
+        return DenialVerdict::Unproven;
+    }
+    if is_delegation(types) {
+        return match qtype {
+            Rrtype::DS => DenialVerdict::InsecureDelegation(name.clone()),
+            _ => DenialVerdict::Unproven,
+        };
+    }
+
+    DenialVerdict::NoData
+}
+
+/// Returns the verdict for a name below the delegation at `cut`, given the
+/// types present at `cut`.
+fn below_delegation(cut: &DomainName, types: &[Rrtype]) -> DenialVerdict {
+    if types.contains(&Rrtype::DS) {
+        return DenialVerdict::Unproven;
+    }
+
+    DenialVerdict::InsecureDelegation(cut.clone())
+}
+
+/// Returns whether the types are those of a delegation point: NS without
+/// SOA.
+fn is_delegation(types: &[Rrtype]) -> bool {
+    types.contains(&Rrtype::NS) && !types.contains(&Rrtype::SOA)
+}
+
+/// Returns the longest name that both `name` and `other` are below.
+fn common_ancestor(name: &DomainName, other: &DomainName) -> DomainName {
+    let mut ancestor = name.clone();
+    while !other.is_subdomain_of(&ancestor) {
+        match ancestor.parent() {
+            Some(parent) => ancestor = parent,
+            None => break,
+        }
+    }
+
+    ancestor
+}
+
+/// Returns the ancestor of `name` with `labels` labels.
+fn ancestor(name: &DomainName, labels: usize) -> DomainName {
+    let mut ancestor = name.clone();
+    while ancestor.label_count() > labels {
+        ancestor = ancestor.parent().unwrap_or_default();
+    }
+
+    ancestor
+}
+
+fn nsec_records(rrs: &[ResourceRecord]) -> Vec<(DomainName, NsecRdata)> {
+    rrs.iter()
+        .filter_map(|rr| match rr.get_rdata() {
+            Rdata::NSEC(nsec) => Some((rr.get_name(), nsec)),
+            _ => None,
+        })
+        .collect()
+}
+
+fn nsec3_records(rrs: &[ResourceRecord]) -> Vec<(DomainName, Nsec3Rdata)> {
+    rrs.iter()
+        .filter_map(|rr| match rr.get_rdata() {
+            Rdata::NSEC3(nsec3) => Some((rr.get_name(), nsec3)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod denial_test {
+    use super::*;
+    use crate::dnssec::dnssec_algorithm::Nsec3HashAlgorithm;
+
+    const SALT: [u8; 4] = [0xaa, 0xbb, 0xcc, 0xdd];
+
+    fn name(name: &str) -> DomainName {
+        DomainName::new_from_str(name)
+    }
+
+    fn rr(owner: DomainName, rdata: Rdata) -> ResourceRecord {
+        let mut rr = ResourceRecord::new(rdata);
+        rr.set_name(owner);
+        rr
+    }
+
+    /// NSEC chain of a zone whose first name is the apex.
+    fn nsec_chain(names: &[(&str, Vec<Rrtype>)]) -> Vec<ResourceRecord> {
+        let mut names: Vec<(DomainName, Vec<Rrtype>)> =
+            names.iter().map(|(owner, types)| (name(owner), types.clone())).collect();
+        names.sort_by(|(owner, _), (other, _)| owner.cmp(other));
+
+        (0..names.len())
+            .map(|i| {
+                let next = names[(i + 1) % names.len()].0.clone();
+                rr(names[i].0.clone(), Rdata::NSEC(NsecRdata::new(next, names[i].1.clone())))
+            })
+            .collect()
+    }
+
+    /// NSEC3 chain of the zone `example`, with the parameters of the
+    /// examples of RFC 5155. Unlike NSEC, empty non-terminals have an NSEC3.
+    fn nsec3_chain(names: &[(&str, Vec<Rrtype>)], flags: u8) -> Vec<ResourceRecord> {
+        let zone = name("example");
+        let mut hashes: Vec<(Vec<u8>, Vec<Rrtype>)> = names
+            .iter()
+            .chain([("y.w.example", Vec::new())].iter())
+            .map(|(owner, types)| (nsec3_hash(&name(owner), &SALT, 12), types.clone()))
+            .collect();
+        hashes.sort();
+
+        (0..hashes.len())
+            .map(|i| {
+                let next = hashes[(i + 1) % hashes.len()].0.clone();
+                let owner = zone.child(format_base32hex(&hashes[i].0).as_bytes()).unwrap();
+                let nsec3 = Nsec3Rdata::new(Nsec3HashAlgorithm::SHA1, flags, 12, 4, SALT.to_vec(), 20, next, hashes[i].1.clone());
+                rr(owner, Rdata::NSEC3(nsec3))
+            })
+            .collect()
+    }
+
+    fn zone() -> Vec<(&'static str, Vec<Rrtype>)> {
+        vec![
+            ("example", vec![Rrtype::NS, Rrtype::SOA, Rrtype::RRSIG, Rrtype::DNSKEY]),
+            ("a.example", vec![Rrtype::NS, Rrtype::DS, Rrtype::RRSIG]),
+            ("b.example", vec![Rrtype::NS]),
+            ("ns1.example", vec![Rrtype::A, Rrtype::RRSIG]),
+            ("w.example", vec![Rrtype::MX, Rrtype::RRSIG]),
+            ("*.w.example", vec![Rrtype::MX, Rrtype::RRSIG]),
+            ("x.y.w.example", vec![Rrtype::MX, Rrtype::RRSIG]),
+        ]
+    }
+
+    #[test]
+    fn nsec3_hash_rfc5155_vectors() {
+        let hash = |owner: &str| format_base32hex(&nsec3_hash(&name(owner), &SALT, 12));
+
+        assert_eq!(hash("example"), "0p9mhaveqvm6t7vbl5lop2u3t2rp3tom");
+        assert_eq!(hash("a.example"), "35mthgpgcu1qg68fab165klnsnk3dpvl");
+        assert_eq!(hash("ns1.example"), "2t7b4g4vsa5smi47k61mv5bv1a22bojr");
+        assert_eq!(hash("EXAMPLE"), "0p9mhaveqvm6t7vbl5lop2u3t2rp3tom");
+        assert_eq!(
+            nsec3_hashed_name(&name("example"), &name("example"), &SALT, 12),
+            name("0p9mhaveqvm6t7vbl5lop2u3t2rp3tom.example")
+        );
+    }
+
+    #[test]
+    fn nsec_covers_name() {
+        assert!(nsec_covers(&name("a.example"), &name("d.example"), &name("b.example")));
+        assert!(!nsec_covers(&name("a.example"), &name("d.example"), &name("e.example")));
+        assert!(nsec_covers(&name("z.example"), &name("example"), &name("zz.example")));
+        assert!(!nsec_covers(&name("a.example"), &name("d.example"), &name("a.example")));
+        assert!(nsec_matches(&name("A.example"), &name("a.example")));
+    }
+
+    #[test]
+    fn nsec_name_error() {
+        let chain = nsec_chain(&zone());
+
+        assert_eq!(verify_denial(&name("c.example"), Rrtype::A, &chain), DenialVerdict::NxDomain);
+        assert_eq!(verify_denial(&name("ns1.example"), Rrtype::MX, &chain), DenialVerdict::NoData);
+        assert_eq!(verify_denial(&name("ns1.example"), Rrtype::A, &chain), DenialVerdict::Unproven);
+        assert_eq!(verify_denial(&name("y.w.example"), Rrtype::A, &chain), DenialVerdict::NoData);
+    }
+
+    #[test]
+    fn nsec_wildcard_no_data() {
+        let chain = nsec_chain(&zone());
+
+        assert_eq!(verify_denial(&name("z.w.example"), Rrtype::A, &chain), DenialVerdict::WildcardNoData);
+        assert_eq!(verify_denial(&name("z.w.example"), Rrtype::MX, &chain), DenialVerdict::Unproven);
+        assert_eq!(verify_wildcard_answer(&name("z.w.example"), 2, &chain), DenialVerdict::WildcardAnswer);
+        assert_eq!(verify_wildcard_answer(&name("x.y.w.example"), 2, &chain), DenialVerdict::Unproven);
+        // The next closer name exists, or is an empty non-terminal
+        assert_eq!(verify_wildcard_answer(&name("a.ns1.example"), 1, &chain), DenialVerdict::Unproven);
+        assert_eq!(verify_wildcard_answer(&name("a.y.w.example"), 2, &chain), DenialVerdict::Unproven);
+    }
+
+    #[test]
+    fn nsec_delegations() {
+        let chain = nsec_chain(&zone());
+
+        assert_eq!(
+            verify_denial(&name("b.example"), Rrtype::DS, &chain),
+            DenialVerdict::InsecureDelegation(name("b.example"))
+        );
+        assert_eq!(
+            verify_denial(&name("www.b.example"), Rrtype::DS, &chain),
+            DenialVerdict::InsecureDelegation(name("b.example"))
+        );
+        assert_eq!(verify_denial(&name("b.example"), Rrtype::A, &chain), DenialVerdict::Unproven);
+        assert_eq!(verify_denial(&name("a.example"), Rrtype::DS, &chain), DenialVerdict::Unproven);
+        assert_eq!(verify_denial(&name("www.a.example"), Rrtype::A, &chain), DenialVerdict::Unproven);
+    }
+
+    #[test]
+    fn nsec_partial_proof_is_unproven() {
+        let chain = nsec_chain(&zone());
+        let covering: Vec<ResourceRecord> = chain
+            .into_iter()
+            .filter(|rr| rr.get_name() == name("b.example"))
+            .collect();
+
+        // The NSEC covering the wildcard of the closest encloser is missing
+        assert_eq!(verify_denial(&name("c.example"), Rrtype::A, &covering), DenialVerdict::Unproven);
+        assert_eq!(verify_denial(&name("c.example"), Rrtype::A, &[]), DenialVerdict::Unproven);
+    }
+
+    #[test]
+    fn nsec3_closest_encloser() {
+        let chain = nsec3_chain(&zone(), 0);
+        let nsec3s = nsec3_records(&chain);
+
+        let proof = closest_encloser(&name("a.c.x.w.example"), &nsec3s).unwrap();
+        assert_eq!(proof.closest_encloser, name("w.example"));
+        assert_eq!(proof.next_closer, name("x.w.example"));
+        assert!(!proof.opt_out);
+        assert_eq!(proof.types, vec![Rrtype::MX, Rrtype::RRSIG]);
+
+        // The empty non-terminal y.w.example exists
+        let proof = closest_encloser(&name("z.y.w.example"), &nsec3s).unwrap();
+        assert_eq!(proof.closest_encloser, name("y.w.example"));
+        assert!(proof.types.is_empty());
+        assert_eq!(closest_encloser(&name("y.w.example"), &nsec3s), None);
+    }
+
+    #[test]
+    fn nsec3_name_error_and_no_data() {
+        let chain = nsec3_chain(&zone(), 0);
+
+        assert_eq!(verify_denial(&name("c.example"), Rrtype::A, &chain), DenialVerdict::NxDomain);
+        assert_eq!(verify_denial(&name("ns1.example"), Rrtype::MX, &chain), DenialVerdict::NoData);
+        assert_eq!(verify_denial(&name("ns1.example"), Rrtype::A, &chain), DenialVerdict::Unproven);
+        assert_eq!(verify_denial(&name("y.w.example"), Rrtype::A, &chain), DenialVerdict::NoData);
+        assert_eq!(verify_denial(&name("a.c.x.w.example"), Rrtype::A, &chain), DenialVerdict::WildcardNoData);
+        assert_eq!(verify_denial(&name("a.c.x.w.example"), Rrtype::MX, &chain), DenialVerdict::Unproven);
+        assert_eq!(verify_wildcard_answer(&name("a.z.w.example"), 2, &chain), DenialVerdict::WildcardAnswer);
+    }
+
+    #[test]
+    fn nsec3_delegations_and_opt_out() {
+        let chain = nsec3_chain(&zone(), 0);
+        assert_eq!(
+            verify_denial(&name("b.example"), Rrtype::DS, &chain),
+            DenialVerdict::InsecureDelegation(name("b.example"))
+        );
+        assert_eq!(
+            verify_denial(&name("www.b.example"), Rrtype::A, &chain),
+            DenialVerdict::InsecureDelegation(name("b.example"))
+        );
+        assert_eq!(verify_denial(&name("c.example"), Rrtype::DS, &chain), DenialVerdict::NxDomain);
+
+        let chain = nsec3_chain(&zone(), OPT_OUT_FLAG);
+        assert_eq!(verify_denial(&name("c.example"), Rrtype::DS, &chain), DenialVerdict::OptOut);
+        assert_eq!(verify_wildcard_answer(&name("a.z.w.example"), 2, &chain), DenialVerdict::OptOut);
+    }
+
+    #[test]
+    fn wildcard_closest_encloser() {
+        let chain = nsec_chain(&zone());
+        assert_eq!(proven_closest_encloser(&name("z.w.example"), &chain), Some(name("w.example")));
+        assert_eq!(proven_closest_encloser(&name("c.example"), &chain), Some(name("example")));
+        assert_eq!(proven_closest_encloser(&name("ns1.example"), &chain), None);
+        assert_eq!(proven_closest_encloser(&name("y.w.example"), &chain), None);
+        assert_eq!(proven_closest_encloser(&name("www.b.example"), &chain), None);
+
+        let chain = nsec3_chain(&zone(), 0);
+        assert_eq!(proven_closest_encloser(&name("a.c.x.w.example"), &chain), Some(name("w.example")));
+        assert_eq!(proven_closest_encloser(&name("www.b.example"), &chain), None);
+        let chain = nsec3_chain(&zone(), OPT_OUT_FLAG);
+        assert_eq!(proven_closest_encloser(&name("a.c.x.w.example"), &chain), None);
+    }
+
+    #[test]
+    fn nsec3_unusable_records_are_ignored() {
+        let mut chain = nsec3_chain(&zone(), 0);
+        for rr in chain.iter_mut() {
+            let Rdata::NSEC3(mut nsec3) = rr.get_rdata() else { unreachable!() };
+            nsec3.set_hash_algorithm(Nsec3HashAlgorithm::from(2));
+            rr.set_rdata(Rdata::NSEC3(nsec3));
+        }
+
+        assert_eq!(verify_denial(&name("c.example"), Rrtype::A, &chain), DenialVerdict::Unproven);
+        assert_eq!(verify_denial(&name("c.other"), Rrtype::A, &nsec3_chain(&zone(), 0)), DenialVerdict::Unproven);
+    }
+}
@@ -1,13 +1,90 @@
+//! Numbers of the algorithms used by DNSSEC, from the IANA registries
+//! "DNS Security Algorithm Numbers", "Digest Algorithms" and "DNSSEC NSEC3
+//! Hash Algorithms".
+//!
+//! Every algorithm reports whether this crate can validate and sign with
+//! it, so the supported ones can be signalled in the DAU, DHU and N3U EDNS
+//! options.
+//!
+//! [RFC 6975](https://datatracker.ietf.org/doc/html/rfc6975#section-3)
+//!
+//! ```text
+//!                   0                       8                      16
+//!                   +-----+-----+-----+-----+-----+-----+-----+-----+
+//!                   |                  OPTION-CODE                  |
+//!                   +-----+-----+-----+-----+-----+-----+-----+-----+
+//!                   |                  LIST-LENGTH                  |
+//!                   +-----+-----+-----+-----+-----+-----+-----+-----+
+//!                   |       ALG-CODE        |        ...            /
+//!                   +-----+-----+-----+-----+
+//! ```
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+use crate::edns::opt_option::option_code::OptionCode;
+use crate::edns::opt_option::option_data::OptionData;
+use crate::edns::opt_option::OptOption;
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+/// DNSKEY, RRSIG and DS algorithm numbers.
+///
+/// [RFC 8624](https://datatracker.ietf.org/doc/html/rfc8624#section-3.1)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
 pub enum DnssecAlgorithm {
+    #[default]
     RESERVED,
     RSAMD5,
     DH,
     DSA,
     ECC,
     RSASHA1,
-    UNKNOWN(u8)
+    DSANSEC3SHA1,
+    RSASHA1NSEC3SHA1,
+    RSASHA256,
+    RSASHA512,
+    ECCGOST,
+    ECDSAP256SHA256,
+    ECDSAP384SHA384,
+    ED25519,
+    ED448,
+    INDIRECT,
+    PRIVATEDNS,
+    PRIVATEOID,
+    UNKNOWN(u8),
+}
+
+impl DnssecAlgorithm {
+    /// Algorithms that can be used to sign, in order of preference.
+    pub const SIGNING: [DnssecAlgorithm; 5] = [
+        DnssecAlgorithm::ECDSAP256SHA256,
+        DnssecAlgorithm::ED25519,
+        DnssecAlgorithm::ECDSAP384SHA384,
+        DnssecAlgorithm::RSASHA256,
+        DnssecAlgorithm::RSASHA512,
+    ];
+
+    /// Returns whether signatures of the algorithm can be verified.
+    pub fn can_validate(&self) -> bool {
+        matches!(
+            self,
+            DnssecAlgorithm::RSASHA256
+                | DnssecAlgorithm::RSASHA512
+                | DnssecAlgorithm::ECDSAP256SHA256
+                | DnssecAlgorithm::ECDSAP384SHA384
+                | DnssecAlgorithm::ED25519
+        )
+    }
+
+    /// Returns whether zones can be signed with keys of the algorithm.
+    pub fn can_sign(&self) -> bool {
+        DnssecAlgorithm::SIGNING.contains(self)
+    }
+
+    /// Returns the algorithms whose signatures can be verified, as listed
+    /// in the DAU option.
+    pub fn supported() -> Vec<DnssecAlgorithm> {
+        (0..=u8::MAX).map(DnssecAlgorithm::from).filter(|algorithm| algorithm.can_validate()).collect()
+    }
 }
 
 impl From<DnssecAlgorithm> for u8 {
@@ -19,6 +96,18 @@ impl From<DnssecAlgorithm> for u8 {
             DnssecAlgorithm::DSA => 3,
             DnssecAlgorithm::ECC => 4,
             DnssecAlgorithm::RSASHA1 => 5,
+            DnssecAlgorithm::DSANSEC3SHA1 => 6,
+            DnssecAlgorithm::RSASHA1NSEC3SHA1 => 7,
+            DnssecAlgorithm::RSASHA256 => 8,
+            DnssecAlgorithm::RSASHA512 => 10,
+            DnssecAlgorithm::ECCGOST => 12,
+            DnssecAlgorithm::ECDSAP256SHA256 => 13,
+            DnssecAlgorithm::ECDSAP384SHA384 => 14,
+            DnssecAlgorithm::ED25519 => 15,
+            DnssecAlgorithm::ED448 => 16,
+            DnssecAlgorithm::INDIRECT => 252,
+            DnssecAlgorithm::PRIVATEDNS => 253,
+            DnssecAlgorithm::PRIVATEOID => 254,
             DnssecAlgorithm::UNKNOWN(other) => other
         }
     }
@@ -33,7 +122,334 @@ impl From<u8> for DnssecAlgorithm {
             3 => DnssecAlgorithm::DSA,
             4 => DnssecAlgorithm::ECC,
             5 => DnssecAlgorithm::RSASHA1,
+            6 => DnssecAlgorithm::DSANSEC3SHA1,
+            7 => DnssecAlgorithm::RSASHA1NSEC3SHA1,
+            8 => DnssecAlgorithm::RSASHA256,
+            10 => DnssecAlgorithm::RSASHA512,
+            12 => DnssecAlgorithm::ECCGOST,
+            13 => DnssecAlgorithm::ECDSAP256SHA256,
+            14 => DnssecAlgorithm::ECDSAP384SHA384,
+            15 => DnssecAlgorithm::ED25519,
+            16 => DnssecAlgorithm::ED448,
+            252 => DnssecAlgorithm::INDIRECT,
+            253 => DnssecAlgorithm::PRIVATEDNS,
+            254 => DnssecAlgorithm::PRIVATEOID,
             other => DnssecAlgorithm::UNKNOWN(other)
         }
     }
-}
\ No newline at end of file
+}
+
+impl FromStr for DnssecAlgorithm {
+    type Err = &'static str;
+
+    /// Parses an algorithm from its mnemonic, in any case, or its number.
+    ///
+    /// [RFC 4034](https://datatracker.ietf.org/doc/html/rfc4034#appendix-A.1)
+    fn from_str(mnemonic: &str) -> Result<Self, Self::Err> {
+        if !mnemonic.is_empty() && mnemonic.bytes().all(|byte| byte.is_ascii_digit()) {
+            return mnemonic.parse::<u8>().map(DnssecAlgorithm::from).map_err(|_| "Error: invalid algorithm number.");
+        }
+
+        match mnemonic.to_ascii_uppercase().as_str() {
+            "RSAMD5" => Ok(DnssecAlgorithm::RSAMD5),
+            "DH" => Ok(DnssecAlgorithm::DH),
+            "DSA" => Ok(DnssecAlgorithm::DSA),
+            "RSASHA1" => Ok(DnssecAlgorithm::RSASHA1),
+            "DSA-NSEC3-SHA1" => Ok(DnssecAlgorithm::DSANSEC3SHA1),
+            "RSASHA1-NSEC3-SHA1" => Ok(DnssecAlgorithm::RSASHA1NSEC3SHA1),
+            "RSASHA256" => Ok(DnssecAlgorithm::RSASHA256),
+            "RSASHA512" => Ok(DnssecAlgorithm::RSASHA512),
+            "ECC-GOST" => Ok(DnssecAlgorithm::ECCGOST),
+            "ECDSAP256SHA256" => Ok(DnssecAlgorithm::ECDSAP256SHA256),
+            "ECDSAP384SHA384" => Ok(DnssecAlgorithm::ECDSAP384SHA384),
+            "ED25519" => Ok(DnssecAlgorithm::ED25519),
+            "ED448" => Ok(DnssecAlgorithm::ED448),
+            "INDIRECT" => Ok(DnssecAlgorithm::INDIRECT),
+            "PRIVATEDNS" => Ok(DnssecAlgorithm::PRIVATEDNS),
+            "PRIVATEOID" => Ok(DnssecAlgorithm::PRIVATEOID),
+            _ => Err("Error: unknown algorithm."),
+        }
+    }
+}
+
+/// Algorithms are ordered by their number.
+impl Ord for DnssecAlgorithm {
+    fn cmp(&self, other: &Self) -> Ordering {
+        u8::from(*self).cmp(&u8::from(*other))
+    }
+}
+
+impl PartialOrd for DnssecAlgorithm {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for DnssecAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", match *self {
+            DnssecAlgorithm::RSAMD5 => "RSAMD5",
+            DnssecAlgorithm::DH => "DH",
+            DnssecAlgorithm::DSA => "DSA",
+            DnssecAlgorithm::RSASHA1 => "RSASHA1",
+            DnssecAlgorithm::DSANSEC3SHA1 => "DSA-NSEC3-SHA1",
+            DnssecAlgorithm::RSASHA1NSEC3SHA1 => "RSASHA1-NSEC3-SHA1",
+            DnssecAlgorithm::RSASHA256 => "RSASHA256",
+            DnssecAlgorithm::RSASHA512 => "RSASHA512",
+            DnssecAlgorithm::ECCGOST => "ECC-GOST",
+            DnssecAlgorithm::ECDSAP256SHA256 => "ECDSAP256SHA256",
+            DnssecAlgorithm::ECDSAP384SHA384 => "ECDSAP384SHA384",
+            DnssecAlgorithm::ED25519 => "ED25519",
+            DnssecAlgorithm::ED448 => "ED448",
+            DnssecAlgorithm::INDIRECT => "INDIRECT",
+            DnssecAlgorithm::PRIVATEDNS => "PRIVATEDNS",
+            DnssecAlgorithm::PRIVATEOID => "PRIVATEOID",
+            DnssecAlgorithm::RESERVED | DnssecAlgorithm::ECC | DnssecAlgorithm::UNKNOWN(_) => {
+                return write!(f, "{}", u8::from(*self));
+            }
+        })
+    }
+}
+
+/// Digest types of the DS RDATA.
+///
+/// [RFC 8624](https://datatracker.ietf.org/doc/html/rfc8624#section-3.3)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum DigestType {
+    #[default]
+    RESERVED,
+    SHA1,
+    SHA256,
+    GOST94,
+    SHA384,
+    GOST2012,
+    SM3,
+    UNKNOWN(u8),
+}
+
+impl DigestType {
+    /// Returns whether digests of the type can be computed, to create and
+    /// verify DS RRs.
+    pub fn is_supported(&self) -> bool {
+        matches!(self, DigestType::SHA1 | DigestType::SHA256 | DigestType::SHA384)
+    }
+
+    /// Returns the digest types that can be computed, as listed in the DHU
+    /// option.
+    pub fn supported() -> Vec<DigestType> {
+        (0..=u8::MAX).map(DigestType::from).filter(|digest_type| digest_type.is_supported()).collect()
+    }
+}
+
+impl From<DigestType> for u8 {
+    fn from(digest_type: DigestType) -> u8 {
+        match digest_type {
+            DigestType::RESERVED => 0,
+            DigestType::SHA1 => 1,
+            DigestType::SHA256 => 2,
+            DigestType::GOST94 => 3,
+            DigestType::SHA384 => 4,
+            DigestType::GOST2012 => 5,
+            DigestType::SM3 => 6,
+            DigestType::UNKNOWN(other) => other
+        }
+    }
+}
+
+impl From<u8> for DigestType {
+    fn from(code: u8) -> DigestType {
+        match code {
+            0 => DigestType::RESERVED,
+            1 => DigestType::SHA1,
+            2 => DigestType::SHA256,
+            3 => DigestType::GOST94,
+            4 => DigestType::SHA384,
+            5 => DigestType::GOST2012,
+            6 => DigestType::SM3,
+            other => DigestType::UNKNOWN(other)
+        }
+    }
+}
+
+impl Ord for DigestType {
+    fn cmp(&self, other: &Self) -> Ordering {
+        u8::from(*self).cmp(&u8::from(*other))
+    }
+}
+
+impl PartialOrd for DigestType {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for DigestType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", u8::from(*self))
+    }
+}
+
+/// Hash algorithms of the NSEC3 and NSEC3PARAM RDATA.
+///
+/// [RFC 5155](https://datatracker.ietf.org/doc/html/rfc5155#section-11)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Nsec3HashAlgorithm {
+    #[default]
+    RESERVED,
+    SHA1,
+    UNKNOWN(u8),
+}
+
+impl Nsec3HashAlgorithm {
+    /// Returns whether names can be hashed with the algorithm.
+    pub fn is_supported(&self) -> bool {
+        *self == Nsec3HashAlgorithm::SHA1
+    }
+
+    /// Returns the hash algorithms that can be computed, as listed in the
+    /// N3U option.
+    pub fn supported() -> Vec<Nsec3HashAlgorithm> {
+        (0..=u8::MAX).map(Nsec3HashAlgorithm::from).filter(|algorithm| algorithm.is_supported()).collect()
+    }
+}
+
+impl From<Nsec3HashAlgorithm> for u8 {
+    fn from(algorithm: Nsec3HashAlgorithm) -> u8 {
+        match algorithm {
+            Nsec3HashAlgorithm::RESERVED => 0,
+            Nsec3HashAlgorithm::SHA1 => 1,
+            Nsec3HashAlgorithm::UNKNOWN(other) => other
+        }
+    }
+}
+
+impl From<u8> for Nsec3HashAlgorithm {
+    fn from(code: u8) -> Nsec3HashAlgorithm {
+        match code {
+            0 => Nsec3HashAlgorithm::RESERVED,
+            1 => Nsec3HashAlgorithm::SHA1,
+            other => Nsec3HashAlgorithm::UNKNOWN(other)
+        }
+    }
+}
+
+impl Ord for Nsec3HashAlgorithm {
+    fn cmp(&self, other: &Self) -> Ordering {
+        u8::from(*self).cmp(&u8::from(*other))
+    }
+}
+
+impl PartialOrd for Nsec3HashAlgorithm {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Nsec3HashAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", u8::from(*self))
+    }
+}
+
+/// Returns the DAU, DHU and N3U options that signal the algorithms the
+/// crate supports.
+///
+/// [RFC 6975](https://datatracker.ietf.org/doc/html/rfc6975#section-5.1)
+///
+///    Validating stub resolvers SHOULD set the DAU, DHU, and/or N3U
+///    option(s) while performing validation.
+pub fn understood_algorithm_options() -> Vec<OptOption> {
+    vec![
+        algorithm_option(OptionCode::DAU, DnssecAlgorithm::supported().into_iter().map(u8::from).collect()),
+        algorithm_option(OptionCode::DHU, DigestType::supported().into_iter().map(u8::from).collect()),
+        algorithm_option(OptionCode::N3U, Nsec3HashAlgorithm::supported().into_iter().map(u8::from).collect()),
+    ]
+}
+
+/// Returns the algorithm codes listed in the data of a DAU, DHU or N3U
+/// option.
+pub fn algorithm_codes(option: &OptOption) -> Vec<u8> {
+    match option.get_opt_data() {
+        OptionData::Unknown(codes) => codes,
+        _ => Vec::new(),
+    }
+}
+
+/// Creates an option with a list of algorithm codes.
+fn algorithm_option(option_code: OptionCode, codes: Vec<u8>) -> OptOption {
+    let mut option = OptOption::new(option_code);
+    option.set_option_len(codes.len() as u16);
+    option.set_opt_data(OptionData::Unknown(codes));
+    option
+}
+
+#[cfg(test)]
+mod dnssec_algorithm_test {
+    use super::*;
+
+    #[test]
+    fn algorithm_numbers_round_trip() {
+        for code in 0..=u8::MAX {
+            assert_eq!(u8::from(DnssecAlgorithm::from(code)), code);
+            assert_eq!(u8::from(DigestType::from(code)), code);
+            assert_eq!(u8::from(Nsec3HashAlgorithm::from(code)), code);
+        }
+        assert_eq!(DnssecAlgorithm::from(13), DnssecAlgorithm::ECDSAP256SHA256);
+        assert_eq!(DnssecAlgorithm::from(9), DnssecAlgorithm::UNKNOWN(9));
+        assert_eq!(DnssecAlgorithm::from(253), DnssecAlgorithm::PRIVATEDNS);
+        assert_eq!(DigestType::from(4), DigestType::SHA384);
+    }
+
+    #[test]
+    fn mnemonics() {
+        assert_eq!(DnssecAlgorithm::ECDSAP256SHA256.to_string(), "ECDSAP256SHA256");
+        assert_eq!(DnssecAlgorithm::RSASHA1NSEC3SHA1.to_string(), "RSASHA1-NSEC3-SHA1");
+        assert_eq!(DnssecAlgorithm::UNKNOWN(200).to_string(), "200");
+        assert_eq!("ed25519".parse(), Ok(DnssecAlgorithm::ED25519));
+        assert_eq!("13".parse(), Ok(DnssecAlgorithm::ECDSAP256SHA256));
+        assert_eq!("200".parse(), Ok(DnssecAlgorithm::UNKNOWN(200)));
+        assert!("SHA256".parse::<DnssecAlgorithm>().is_err());
+        assert!("256".parse::<DnssecAlgorithm>().is_err());
+        assert!("".parse::<DnssecAlgorithm>().is_err());
+    }
+
+    #[test]
+    fn ordered_by_number() {
+        assert!(DnssecAlgorithm::RESERVED < DnssecAlgorithm::UNKNOWN(9));
+        assert!(DnssecAlgorithm::UNKNOWN(9) < DnssecAlgorithm::RSASHA512);
+        assert!(DnssecAlgorithm::ED448 < DnssecAlgorithm::UNKNOWN(200));
+        assert!(DnssecAlgorithm::UNKNOWN(200) < DnssecAlgorithm::PRIVATEDNS);
+        assert!(DigestType::SM3 < DigestType::UNKNOWN(7));
+    }
+
+    #[test]
+    fn capabilities() {
+        assert!(DnssecAlgorithm::ED25519.can_validate() && DnssecAlgorithm::ED25519.can_sign());
+        assert!(!DnssecAlgorithm::RSASHA1.can_validate() && !DnssecAlgorithm::RSASHA1.can_sign());
+        assert!(!DnssecAlgorithm::ED448.can_validate());
+        assert_eq!(
+            DnssecAlgorithm::supported(),
+            vec![
+                DnssecAlgorithm::RSASHA256,
+                DnssecAlgorithm::RSASHA512,
+                DnssecAlgorithm::ECDSAP256SHA256,
+                DnssecAlgorithm::ECDSAP384SHA384,
+                DnssecAlgorithm::ED25519
+            ]
+        );
+        assert_eq!(DigestType::supported(), vec![DigestType::SHA1, DigestType::SHA256, DigestType::SHA384]);
+        assert_eq!(Nsec3HashAlgorithm::supported(), vec![Nsec3HashAlgorithm::SHA1]);
+    }
+
+    #[test]
+    fn understood_options() {
+        let options = understood_algorithm_options();
+        assert_eq!(options[0].get_option_code(), OptionCode::DAU);
+        assert_eq!(algorithm_codes(&options[0]), vec![8, 10, 13, 14, 15]);
+        assert_eq!(options[0].get_option_len(), 5);
+        assert_eq!(options[1].get_option_code(), OptionCode::DHU);
+        assert_eq!(algorithm_codes(&options[1]), vec![1, 2, 4]);
+        assert_eq!(options[2].get_option_code(), OptionCode::N3U);
+        assert_eq!(algorithm_codes(&options[2]), vec![1]);
+    }
+}
@@ -0,0 +1,1067 @@
+use crate::async_resolver::AsyncResolver;
+use crate::dnssec::denial::{verify_denial, verify_wildcard_answer, DenialVerdict};
+use crate::dnssec::rrset_signature::{verify_rrsig, BogusReason, RrsigVerification};
+use crate::domain_name::DomainName;
+use crate::edns::opt_option::option_code::OptionCode;
+use crate::edns::opt_option::option_data::OptionData;
+use crate::edns::opt_option::OptOption;
+use crate::edns::options::ede::ede_code::EdeCode;
+use crate::edns::options::ede::ede_optdata::EdeOptData;
+use crate::message::rclass::Rclass;
+use crate::message::rcode::Rcode;
+use crate::message::rdata::dnskey_rdata::DnskeyRdata;
+use crate::message::rdata::ds_rdata::DsRdata;
+use crate::message::rdata::opt_rdata::OptRdata;
+use crate::message::rdata::rrsig_rdata::RRSIGRdata;
+use crate::message::rdata::Rdata;
+use crate::message::resource_record::{ResourceRecord, ToBytes};
+use crate::message::rrtype::Rrtype;
+use crate::message::DnsMessage;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+
+/// Maximum number of DS and DNSKEY queries made to validate one answer.
+const MAX_FETCHES: usize = 32;
+
+/// UDP payload of the OPT RR added to a response that did not have one.
+const DEFAULT_PAYLOAD: u16 = 512;
+
+/// Security status of an answer.
+///
+/// [RFC 4035](https://datatracker.ietf.org/doc/html/rfc4035#section-4.3)
+///
+/// 4.3.  Determining Security Status of Data
+///
+///    A security-aware resolver MUST be able to determine whether it should
+///    expect a particular RRset to be signed.  More precisely, a
+///    security-aware resolver must be able to distinguish between four
+///    cases:
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecurityStatus {
+    /// An unbroken chain of signed DNSKEY and DS RRs leads from a trust
+    /// anchor to the data.
+    Secure,
+    /// A chain from a trust anchor proves that the zone of the data is not
+    /// signed.
+    Insecure,
+    /// There should be a chain to the data but it does not validate. Holds
+    /// the Extended DNS Error code (RFC 8914) that explains the failure.
+    Bogus(EdeCode),
+    /// There is no trust anchor for the data.
+    Indeterminate,
+}
+
+impl SecurityStatus {
+    /// Returns the status of an answer made of two parts, which is the
+    /// status of the least secure one.
+    fn combine(self, other: SecurityStatus) -> SecurityStatus {
+        if other.rank() > self.rank() {
+            other
+        } else {
+            self
+        }
+    }
+
+    fn rank(&self) -> u8 {
+        match self {
+            SecurityStatus::Secure => 0,
+            SecurityStatus::Insecure => 1,
+            SecurityStatus::Indeterminate => 2,
+            SecurityStatus::Bogus(_) => 3,
+        }
+    }
+}
+
+/// Keys of a secure zone, or the status of a zone that has no trusted keys.
+type ZoneKeys = Result<Vec<DnskeyRdata>, SecurityStatus>;
+
+/// Validates answers following the chain of trust from the trust anchors.
+///
+/// [RFC 4035](https://datatracker.ietf.org/doc/html/rfc4035#section-5)
+///
+/// 5.  Authenticating DNS Responses
+///
+///    To use DNSSEC RRs for authentication, a security-aware resolver
+///    requires configured knowledge of at least one authenticated DNSKEY or
+///    DS RR.  [...]  Given an authentication chain, the resolver can
+///    authenticate the answer starting from the trust anchor, following
+///    each DS RR to the DNSKEY RRset of the child zone, until it reaches
+///    the zone that signed the answer.
+///
+/// The DNSKEY and DS RRsets of every zone in the chain are fetched with the
+/// resolver, so they are taken from its cache when possible. The keys found
+/// for each zone are kept while validating the answer.
+pub struct ChainValidator {
+    /// Resolver used to fetch the DNSKEY and DS RRsets.
+    resolver: AsyncResolver,
+    /// Trusted DS and DNSKEY records.
+    trust_anchors: Vec<ResourceRecord>,
+    /// Keys of the zones already visited.
+    zones: HashMap<DomainName, ZoneKeys>,
+    /// Number of queries made so far.
+    fetches: usize,
+}
+
+impl ChainValidator {
+    /// Creates a new `ChainValidator` that fetches records with `resolver`.
+    ///
+    /// # Example
+    /// ```
+    /// let resolver = AsyncResolver::new(ResolverConfig::default());
+    /// let validator = ChainValidator::new(resolver, root_trust_anchor());
+    /// ```
+    pub fn new(resolver: AsyncResolver, trust_anchors: Vec<ResourceRecord>) -> Self {
+        ChainValidator {
+            resolver,
+            trust_anchors,
+            zones: HashMap::new(),
+            fetches: 0,
+        }
+    }
+
+    /// Returns the security status of a response.
+    ///
+    /// Every RRset of the answer section is validated, as well as the SOA,
+    /// NSEC and NSEC3 RRsets of the authority section. The response is as
+    /// secure as its least secure RRset. A negative response without any
+    /// of those RRsets is only accepted when the zone is not signed.
+    ///
+    /// The NSEC and NSEC3 RRsets of a secure negative response must prove
+    /// that the name or the type does not exist, and those of an answer
+    /// expanded from a wildcard that the name does not exist. An answer
+    /// whose CNAME chain ends at a name without an RRset of the queried type
+    /// is a negative response for that name.
+    pub async fn validate(&mut self, response: &DnsMessage) -> SecurityStatus {
+        let answer = records(response.get_answer());
+        let authority: Vec<ResourceRecord> = records(response.get_authority())
+            .into_iter()
+            .filter(|rr| is_proof_type(rr.get_rtype()) || rr.get_rtype() == Rrtype::RRSIG)
+            .collect();
+
+        let mut status = SecurityStatus::Secure;
+        for (rrset, rrsigs) in group_rrsets(&answer).iter() {
+            let rrset_status = self.validate_rrset(rrset, rrsigs).await;
+            status = status.combine(rrset_status);
+            if let (SecurityStatus::Secure, Some(labels)) = (rrset_status, wildcard_labels(rrset, rrsigs)) {
+                let verdict = verify_wildcard_answer(&rrset[0].get_name(), labels, &authority);
+                status = status.combine(denial_status(&verdict, &[DenialVerdict::WildcardAnswer]));
+            }
+        }
+        for (rrset, rrsigs) in group_rrsets(&authority).iter() {
+            status = status.combine(self.validate_rrset(rrset, rrsigs).await);
+        }
+
+        let rcode = response.get_header().get_rcode();
+        let question = response.get_question();
+        let qtype = question.get_rrtype();
+        let qname = match qtype {
+            Rrtype::CNAME => question.get_qname(),
+            _ => cname_target(question.get_qname(), &answer),
+        };
+        let answered = answer
+            .iter()
+            .any(|rr| rr.get_name() == qname && (qtype == Rrtype::ANY || rr.get_rtype() == qtype));
+        let negative = !answered || rcode == Rcode::NXDOMAIN;
+        if negative && group_rrsets(&authority).is_empty() {
+            let unsigned = match self.zone_keys(qname).await {
+                Ok(_) => SecurityStatus::Bogus(EdeCode::NsecMissing),
+                Err(status) => status,
+            };
+            status = status.combine(unsigned);
+        } else if negative && status == SecurityStatus::Secure {
+            let verdict = verify_denial(&qname, qtype, &authority);
+            let expected: &[DenialVerdict] = match rcode {
+                Rcode::NXDOMAIN => &[DenialVerdict::NxDomain],
+                _ => &[DenialVerdict::NoData, DenialVerdict::WildcardNoData],
+            };
+            status = status.combine(denial_status(&verdict, expected));
+        }
+
+        status
+    }
+
+    /// Returns the status of an RRset given the RRSIG RRs that cover it.
+    ///
+    /// An RRset is secure when one of its signatures, made by a zone above
+    /// it, validates with a key of that zone. Signatures of zones without
+    /// trusted keys are skipped, so a forged one cannot hide a valid one.
+    /// An unsigned RRset is bogus when its zone is secure.
+    async fn validate_rrset(&mut self, rrset: &[ResourceRecord], rrsigs: &[RRSIGRdata]) -> SecurityStatus {
+        let owner = rrset[0].get_name();
+        let rrtype = rrset[0].get_rtype();
+        // The DS RRset is signed by the parent zone, never by the zone itself
+        let rrsigs: Vec<&RRSIGRdata> = rrsigs
+            .iter()
+            .filter(|rrsig| {
+                let signer = rrsig.get_signer_name();
+                owner.is_subdomain_of(&signer) && !(rrtype == Rrtype::DS && owner == signer)
+            })
+            .collect();
+
+        if rrsigs.is_empty() {
+            return match self.zone_keys(owner).await {
+                Ok(_) => SecurityStatus::Bogus(EdeCode::RrsigsMissing),
+                Err(status) => status,
+            };
+        }
+
+        // A signature that fails is bogus, which takes precedence over the
+        // status of a signer without trusted keys
+        let mut status = None;
+        for rrsig in rrsigs {
+            match self.zone_keys(rrsig.get_signer_name()).await {
+                Ok(keys) => match verify_with_keys(rrsig, &keys, rrset) {
+                    Ok(()) => return SecurityStatus::Secure,
+                    Err(code) => status = Some(SecurityStatus::Bogus(code)),
+                },
+                Err(keys_status) => {
+                    status.get_or_insert(keys_status);
+                }
+            }
+        }
+
+        status.unwrap_or(SecurityStatus::Bogus(EdeCode::DnssecBogus))
+    }
+
+    /// Returns the keys of the zone that holds `name`.
+    ///
+    /// `name` does not need to be the apex of a zone: when the parent
+    /// proves there is no zone cut at `name`, the keys of the zone that
+    /// signed the proof are returned.
+    fn zone_keys<'a>(&'a mut self, name: DomainName) -> Pin<Box<dyn Future<Output = ZoneKeys> + 'a>> {
+        Box::pin(async move {
+            if let Some(keys) = self.zones.get(&name) {
+                return keys.clone();
+            }
+            // Breaks loops between zones that sign each other's proofs
+            self.zones.insert(name.clone(), Err(SecurityStatus::Bogus(EdeCode::DnssecBogus)));
+
+            let keys = self.find_zone_keys(&name).await;
+            self.zones.insert(name, keys.clone());
+            keys
+        })
+    }
+
+    async fn find_zone_keys(&mut self, name: &DomainName) -> ZoneKeys {
+        let anchors: Vec<Rdata> = self
+            .trust_anchors
+            .iter()
+            .filter(|anchor| anchor.get_name() == *name)
+            .map(|anchor| anchor.get_rdata())
+            .collect();
+        if !anchors.is_empty() {
+            return self.anchored_zone_keys(name, &anchors).await;
+        }
+
+        let covered = self.trust_anchors.iter().any(|anchor| name.is_subdomain_of(&anchor.get_name()));
+        if !covered {
+            return Err(SecurityStatus::Indeterminate);
+        }
+
+        let response = self.fetch(name, Rrtype::DS).await.ok_or(SecurityStatus::Bogus(EdeCode::NoReachableAuth))?;
+        let answer = records(response.get_answer());
+        let ds_rrset: Vec<ResourceRecord> = answer
+            .iter()
+            .filter(|rr| rr.get_rtype() == Rrtype::DS && rr.get_name() == *name)
+            .cloned()
+            .collect();
+
+        if !ds_rrset.is_empty() {
+            let rrsigs = signatures_for(&answer, name, Rrtype::DS);
+            match self.validate_rrset(&ds_rrset, &rrsigs).await {
+                SecurityStatus::Secure => {}
+                status => return Err(status),
+            }
+            let ds_records: Vec<DsRdata> = ds_rrset
+                .iter()
+                .filter_map(|rr| match rr.get_rdata() {
+                    Rdata::DS(ds) if is_supported_ds(&ds) => Some(ds),
+                    _ => None,
+                })
+                .collect();
+            // RFC 4035 section 5.2: without a DS RR of a known algorithm
+            // the zone is treated as unsigned
+            if ds_records.is_empty() {
+                return Err(SecurityStatus::Insecure);
+            }
+
+            return self
+                .trusted_zone_keys(name, |key| ds_records.iter().any(|ds| ds_matches(name, ds, key)))
+                .await;
+        }
+
+        self.proven_ds_absence(name, &response).await
+    }
+
+    /// Returns the keys of a zone with a configured trust anchor.
+    async fn anchored_zone_keys(&mut self, zone: &DomainName, anchors: &[Rdata]) -> ZoneKeys {
+        let supported = anchors.iter().any(|anchor| match anchor {
+            Rdata::DS(ds) => is_supported_ds(ds),
+            Rdata::DNSKEY(dnskey) => dnskey.get_algorithm().can_validate(),
+            _ => false,
+        });
+        if !supported {
+            return Err(SecurityStatus::Insecure);
+        }
+
+        self.trusted_zone_keys(zone, |key| {
+            anchors.iter().any(|anchor| match anchor {
+                Rdata::DS(ds) => ds_matches(zone, ds, key),
+                Rdata::DNSKEY(dnskey) => {
+                    dnskey.get_algorithm() == key.get_algorithm()
+                        && dnskey.get_public_key() == key.get_public_key()
+                }
+                _ => false,
+            })
+        })
+        .await
+    }
+
+    /// Fetches the DNSKEY RRset of `zone` and returns its keys when it is
+    /// signed by one of the keys accepted by `trusted`.
+    async fn trusted_zone_keys<F>(&mut self, zone: &DomainName, trusted: F) -> ZoneKeys
+    where
+        F: Fn(&DnskeyRdata) -> bool,
+    {
+        let response = self.fetch(zone, Rrtype::DNSKEY).await.ok_or(SecurityStatus::Bogus(EdeCode::NoReachableAuth))?;
+        let answer = records(response.get_answer());
+        let dnskey_rrset: Vec<ResourceRecord> = answer
+            .iter()
+            .filter(|rr| rr.get_rtype() == Rrtype::DNSKEY && rr.get_name() == *zone)
+            .cloned()
+            .collect();
+        let keys: Vec<DnskeyRdata> = dnskey_rrset
+            .iter()
+            .filter_map(|rr| match rr.get_rdata() {
+                Rdata::DNSKEY(dnskey) => Some(dnskey),
+                _ => None,
+            })
+            .collect();
+
+        let trusted_keys: Vec<DnskeyRdata> = keys
+            .iter()
+            .filter(|key| !key.is_revoked() && trusted(key))
+            .cloned()
+            .collect();
+        if trusted_keys.is_empty() {
+            return Err(SecurityStatus::Bogus(EdeCode::DnskeyMissing));
+        }
+
+        let rrsigs = signatures_for(&answer, zone, Rrtype::DNSKEY);
+        if rrsigs.is_empty() {
+            return Err(SecurityStatus::Bogus(EdeCode::RrsigsMissing));
+        }
+
+        let mut code = EdeCode::DnssecBogus;
+        for rrsig in rrsigs.iter() {
+            match verify_with_keys(rrsig, &trusted_keys, &dnskey_rrset) {
+                Ok(()) => return Ok(keys),
+                Err(error) => code = error,
+            }
+        }
+
+        Err(SecurityStatus::Bogus(code))
+    }
+
+    /// Returns the keys of the zone holding `name` from a response to a DS
+    /// query without DS RRs.
+    ///
+    /// [RFC 4035](https://datatracker.ietf.org/doc/html/rfc4035#section-5.2)
+    ///
+    ///    If the validator does not support any of the algorithms listed in an
+    ///    authenticated DS RRset, then the resolver has no supported
+    ///    authentication path leading from the parent to the child.  The
+    ///    resolver should treat this case as it would the case of an
+    ///    authenticated NSEC RRset proving that no DS RRset exists, as
+    ///    described above.
+    async fn proven_ds_absence(&mut self, name: &DomainName, response: &DnsMessage) -> ZoneKeys {
+        let mut proof = records(response.get_answer());
+        proof.extend(records(response.get_authority()));
+        let proof: Vec<ResourceRecord> = proof
+            .into_iter()
+            .filter(|rr| is_proof_type(rr.get_rtype()) || matches!(rr.get_rtype(), Rrtype::CNAME | Rrtype::RRSIG))
+            .collect();
+        let rrsets = group_rrsets(&proof);
+
+        // An unsigned response is only acceptable if the zone above is not signed
+        if rrsets.iter().all(|(_, rrsigs)| rrsigs.is_empty()) {
+            let parent = name.parent().ok_or(SecurityStatus::Indeterminate)?;
+            return match self.zone_keys(parent).await {
+                Ok(_) => Err(SecurityStatus::Bogus(EdeCode::NsecMissing)),
+                Err(status) => Err(status),
+            };
+        }
+
+        let mut status = SecurityStatus::Secure;
+        for (rrset, rrsigs) in rrsets.iter() {
+            status = status.combine(self.validate_rrset(rrset, rrsigs).await);
+        }
+        if status != SecurityStatus::Secure {
+            return Err(status);
+        }
+
+        // A CNAME proves that the name is not a zone cut
+        let cname = proof.iter().any(|rr| rr.get_rtype() == Rrtype::CNAME && rr.get_name() == *name);
+        let verdict = match cname {
+            true => DenialVerdict::NoData,
+            false => verify_denial(name, Rrtype::DS, &proof),
+        };
+        match verdict {
+            DenialVerdict::InsecureDelegation(_) | DenialVerdict::OptOut => Err(SecurityStatus::Insecure),
+            // The name is not a zone cut, so the closest zone that signed
+            // the proof holds it
+            DenialVerdict::NoData | DenialVerdict::NxDomain | DenialVerdict::WildcardNoData => {
+                let signer = rrsets
+                    .iter()
+                    .flat_map(|(_, rrsigs)| rrsigs.iter().map(|rrsig| rrsig.get_signer_name()))
+                    .filter(|signer| name.is_subdomain_of(signer) && signer != name)
+                    .max_by_key(|signer| signer.label_count())
+                    .ok_or(SecurityStatus::Bogus(EdeCode::DnssecBogus))?;
+                self.zone_keys(signer).await
+            }
+            DenialVerdict::WildcardAnswer | DenialVerdict::Unproven => Err(SecurityStatus::Bogus(EdeCode::NsecMissing)),
+        }
+    }
+
+    /// Queries the resolver without validating the response. Only responses
+    /// with NOERROR or NXDOMAIN are returned.
+    async fn fetch(&mut self, name: &DomainName, rrtype: Rrtype) -> Option<DnsMessage> {
+        if self.fetches >= MAX_FETCHES {
+            return None;
+        }
+        self.fetches += 1;
+
+        let response = self
+            .resolver
+            .lookup_unvalidated(name.clone(), rrtype, Rclass::IN)
+            .await
+            .ok()?
+            .to_dns_msg();
+        match response.get_header().get_rcode() {
+            Rcode::NOERROR | Rcode::NXDOMAIN => Some(response),
+            _ => None,
+        }
+    }
+}
+
+/// Creates the response for an answer that failed validation: a SERVFAIL
+/// with the question of `response` and an Extended DNS Error option.
+///
+/// [RFC 8914](https://datatracker.ietf.org/doc/html/rfc8914#section-2)
+///
+/// The OPT RR of `response` is kept, replacing its options, so the
+/// requestor's payload size and DO bit are preserved.
+///
+/// # Example
+/// ```
+/// let servfail = bogus_response(&response, EdeCode::SigExpired);
+/// assert_eq!(servfail.get_header().get_rcode(), Rcode::SERVFAIL);
+/// ```
+pub fn bogus_response(response: &DnsMessage, code: EdeCode) -> DnsMessage {
+    let mut ede = EdeOptData::new();
+    ede.set_info_code(code);
+    let mut option = OptOption::new(OptionCode::EDE);
+    option.set_option_len(ede.to_bytes().len() as u16);
+    option.set_opt_data(OptionData::EDE(ede));
+    let mut opt_rdata = OptRdata::new();
+    opt_rdata.set_option(vec![option]);
+    let rdata = Rdata::OPT(opt_rdata);
+    let rdlength = rdata.to_bytes().len() as u16;
+
+    let mut opt_rr = match response.get_additional().into_iter().find(|rr| rr.get_rtype() == Rrtype::OPT) {
+        Some(rr) => rr,
+        None => {
+            let mut rr = ResourceRecord::new(Rdata::OPT(OptRdata::new()));
+            rr.set_name(DomainName::new_from_str("."));
+            rr.set_type_code(Rrtype::OPT);
+            rr.set_rclass(Rclass::UNKNOWN(DEFAULT_PAYLOAD));
+            rr
+        }
+    };
+    opt_rr.set_rdata(rdata);
+    opt_rr.set_rdlength(rdlength);
+
+    let mut servfail = DnsMessage::new();
+    let mut header = response.get_header();
+    header.set_qr(true);
+    header.set_ad(false);
+    header.set_rcode(Rcode::SERVFAIL);
+    servfail.set_header(header);
+    servfail.set_question(response.get_question());
+    servfail.set_additional(vec![opt_rr]);
+    servfail.update_header_counters();
+
+    servfail
+}
+
+/// Returns whether the DS RR of `owner` refers to the DNSKEY.
+///
+/// [RFC 4034](https://datatracker.ietf.org/doc/html/rfc4034#section-5.2)
+fn ds_matches(owner: &DomainName, ds: &DsRdata, dnskey: &DnskeyRdata) -> bool {
+    match dnskey.to_ds(owner, ds.get_digest_type()) {
+        Ok(expected) => expected == *ds,
+        Err(_) => false,
+    }
+}
+
+fn is_supported_ds(ds: &DsRdata) -> bool {
+    ds.get_algorithm().can_validate() && ds.get_digest_type().is_supported()
+}
+
+/// Verifies the signature with the keys that have its key tag and
+/// algorithm, returning the error code of the last failure.
+fn verify_with_keys(rrsig: &RRSIGRdata, keys: &[DnskeyRdata], rrset: &[ResourceRecord]) -> Result<(), EdeCode> {
+    let mut code = EdeCode::DnskeyMissing;
+    let candidates = keys
+        .iter()
+        .filter(|key| key.get_algorithm() == rrsig.get_algorithm() && key.key_tag() == rrsig.get_key_tag());
+
+    for key in candidates {
+        match verify_rrsig(rrsig, key, rrset) {
+            RrsigVerification::Secure => return Ok(()),
+            RrsigVerification::Bogus(reason) => code = ede_code(reason),
+            RrsigVerification::UnsupportedAlgorithm(_) => code = EdeCode::UnsupDnskeyAlg,
+        }
+    }
+
+    Err(code)
+}
+
+fn ede_code(reason: BogusReason) -> EdeCode {
+    match reason {
+        BogusReason::SignatureExpired => EdeCode::SigExpired,
+        BogusReason::SignatureNotYetValid => EdeCode::SigNotYetValid,
+        BogusReason::KeyMismatch => EdeCode::NoZoneKeyBit,
+        BogusReason::InvalidSignature | BogusReason::InvalidKey | BogusReason::RrsetMismatch => EdeCode::DnssecBogus,
+    }
+}
+
+/// Returns the status of a secure response given what its NSEC or NSEC3
+/// records prove and what they should prove.
+fn denial_status(verdict: &DenialVerdict, expected: &[DenialVerdict]) -> SecurityStatus {
+    match verdict {
+        DenialVerdict::OptOut | DenialVerdict::InsecureDelegation(_) => SecurityStatus::Insecure,
+        verdict if expected.contains(verdict) => SecurityStatus::Secure,
+        _ => SecurityStatus::Bogus(EdeCode::NsecMissing),
+    }
+}
+
+/// Returns the Labels field of the RRSIG RRs of an RRset expanded from a
+/// wildcard, or `None` when the RRset was not expanded.
+///
+/// [RFC 4035](https://datatracker.ietf.org/doc/html/rfc4035#section-5.3.4)
+fn wildcard_labels(rrset: &[ResourceRecord], rrsigs: &[RRSIGRdata]) -> Option<u8> {
+    let owner = rrset[0].get_name();
+    let is_wildcard = owner.get_labels().first().is_some_and(|label| label.as_slice() == b"*");
+    let label_count = owner.label_count() - is_wildcard as usize;
+
+    rrsigs
+        .iter()
+        .map(|rrsig| rrsig.get_labels())
+        .filter(|labels| (*labels as usize) < label_count)
+        .min()
+}
+
+/// Returns the name at the end of the CNAME chain that starts at `qname`
+/// in the answer section.
+fn cname_target(qname: DomainName, answer: &[ResourceRecord]) -> DomainName {
+    let mut name = qname;
+    for _ in 0..answer.len() {
+        let cname = answer.iter().find_map(|rr| match rr.get_rdata() {
+            Rdata::CNAME(cname) if rr.get_name() == name => Some(cname.get_cname()),
+            _ => None,
+        });
+        match cname {
+            Some(target) => name = target,
+            None => break,
+        }
+    }
+
+    name
+}
+
+fn is_proof_type(rrtype: Rrtype) -> bool {
+    matches!(rrtype, Rrtype::SOA | Rrtype::NSEC | Rrtype::NSEC3)
+}
+
+/// Returns the records of a section without the OPT and TSIG pseudo RRs.
+fn records(section: Vec<ResourceRecord>) -> Vec<ResourceRecord> {
+    section
+        .into_iter()
+        .filter(|rr| !matches!(rr.get_rtype(), Rrtype::OPT | Rrtype::TSIG))
+        .collect()
+}
+
+/// Splits records into RRsets, each with the RRSIG RDATA that cover it.
+fn group_rrsets(rrs: &[ResourceRecord]) -> Vec<(Vec<ResourceRecord>, Vec<RRSIGRdata>)> {
+    let mut rrsets: Vec<(Vec<ResourceRecord>, Vec<RRSIGRdata>)> = Vec::new();
+    for rr in rrs.iter().filter(|rr| rr.get_rtype() != Rrtype::RRSIG) {
+        let same_rrset = |other: &ResourceRecord| {
+            other.get_name() == rr.get_name()
+                && other.get_rtype() == rr.get_rtype()
+                && other.get_rclass() == rr.get_rclass()
+        };
+        match rrsets.iter_mut().find(|(rrset, _)| same_rrset(&rrset[0])) {
+            Some((rrset, _)) => rrset.push(rr.clone()),
+            None => rrsets.push((vec![rr.clone()], Vec::new())),
+        }
+    }
+
+    for (rrset, rrsigs) in rrsets.iter_mut() {
+        *rrsigs = signatures_for(rrs, &rrset[0].get_name(), rrset[0].get_rtype());
+    }
+
+    rrsets
+}
+
+/// Returns the RRSIG RDATA of `name` that cover `rrtype`.
+fn signatures_for(rrs: &[ResourceRecord], name: &DomainName, rrtype: Rrtype) -> Vec<RRSIGRdata> {
+    rrs.iter()
+        .filter(|rr| rr.get_name() == *name)
+        .filter_map(|rr| match rr.get_rdata() {
+            Rdata::RRSIG(rrsig) if rrsig.get_type_covered() == rrtype => Some(rrsig),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod dnssec_fetch_test {
+    use super::*;
+    use crate::async_resolver::config::ResolverConfig;
+    use crate::dnssec::denial::nsec3_hashed_name;
+    use crate::dnssec::rrset_signature::signature_data;
+    use crate::message::rdata::a_rdata::ARdata;
+    use crate::message::rdata::cname_rdata::CnameRdata;
+    use crate::message::rdata::nsec3_rdata::Nsec3Rdata;
+    use crate::message::rdata::nsec_rdata::NsecRdata;
+    use crate::message::rdata::soa_rdata::SoaRdata;
+    use data_encoding::{BASE64, HEXLOWER};
+    use ring::signature::{Ed25519KeyPair, KeyPair};
+    use std::net::IpAddr;
+    use std::time::{SystemTime, UNIX_EPOCH};
+    use crate::dnssec::dnssec_algorithm::{DigestType, DnssecAlgorithm, Nsec3HashAlgorithm};
+
+    const TTL: u32 = 3600;
+
+    /// Ed25519 key of a test zone.
+    struct ZoneKey {
+        zone: DomainName,
+        key_pair: Ed25519KeyPair,
+        dnskey: DnskeyRdata,
+    }
+
+    impl ZoneKey {
+        fn new(zone: &str, seed: u8) -> Self {
+            let key_pair = Ed25519KeyPair::from_seed_unchecked(&[seed; 32]).unwrap();
+            let dnskey = DnskeyRdata::new(257, 3, DnssecAlgorithm::ED25519, key_pair.public_key().as_ref().to_vec());
+            ZoneKey { zone: DomainName::new_from_str(zone), key_pair, dnskey }
+        }
+
+        fn ds(&self) -> DsRdata {
+            self.dnskey.to_ds(&self.zone, DigestType::SHA256).unwrap()
+        }
+
+        fn ds_rr(&self) -> ResourceRecord {
+            rr(&self.zone, Rdata::DS(self.ds()))
+        }
+
+        fn dnskey_rr(&self) -> ResourceRecord {
+            rr(&self.zone, Rdata::DNSKEY(self.dnskey.clone()))
+        }
+
+        fn sign_at(&self, rrset: &[ResourceRecord], inception: u32, expiration: u32) -> ResourceRecord {
+            let owner = rrset[0].get_name();
+            let mut rrsig = RRSIGRdata::new();
+            rrsig.set_type_covered(rrset[0].get_rtype());
+            rrsig.set_algorithm(DnssecAlgorithm::ED25519);
+            rrsig.set_labels(owner.label_count() as u8);
+            rrsig.set_original_ttl(TTL);
+            rrsig.set_signature_expiration(expiration);
+            rrsig.set_signature_inception(inception);
+            rrsig.set_key_tag(self.dnskey.key_tag());
+            rrsig.set_signer_name(self.zone.clone());
+            let data = signature_data(&rrsig, rrset).unwrap();
+            rrsig.set_signature(self.key_pair.sign(&data).as_ref().to_vec());
+            rr(&owner, Rdata::RRSIG(rrsig))
+        }
+
+        /// Returns the RRset followed by its signature.
+        fn signed(&self, rrset: Vec<ResourceRecord>) -> Vec<ResourceRecord> {
+            let now = now();
+            let rrsig = self.sign_at(&rrset, now - 86400, now + 86400);
+            let mut rrs = rrset;
+            rrs.push(rrsig);
+            rrs
+        }
+    }
+
+    fn now() -> u32 {
+        SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as u32
+    }
+
+    fn rr(name: &DomainName, rdata: Rdata) -> ResourceRecord {
+        let mut rr = ResourceRecord::new(rdata);
+        rr.set_name(name.clone());
+        rr.set_rclass(Rclass::IN);
+        rr.set_ttl(TTL);
+        rr
+    }
+
+    fn a_rr(name: &str, address: &str) -> ResourceRecord {
+        let address: IpAddr = address.parse().unwrap();
+        rr(&DomainName::new_from_str(name), Rdata::A(ARdata::new_from_addr(address)))
+    }
+
+    fn nsec_rr(name: &str, next: &str, types: Vec<Rrtype>) -> ResourceRecord {
+        let nsec = NsecRdata::new(DomainName::new_from_str(next), types);
+        rr(&DomainName::new_from_str(name), Rdata::NSEC(nsec))
+    }
+
+    fn soa_rr(name: &str) -> ResourceRecord {
+        let mut soa = SoaRdata::new();
+        soa.set_mname(DomainName::new_from_str("ns.example"));
+        soa.set_rname(DomainName::new_from_str("hostmaster.example"));
+        soa.set_minimum(TTL);
+        rr(&DomainName::new_from_str(name), Rdata::SOA(soa))
+    }
+
+    fn response(
+        qname: &str,
+        qtype: Rrtype,
+        answer: Vec<ResourceRecord>,
+        authority: Vec<ResourceRecord>,
+    ) -> DnsMessage {
+        let mut message = DnsMessage::new_query_message(DomainName::new_from_str(qname), qtype, Rclass::IN, 0, true, 1);
+        let mut header = message.get_header();
+        header.set_qr(true);
+        message.set_header(header);
+        message.set_answer(answer);
+        message.set_authority(authority);
+        message.update_header_counters();
+        message
+    }
+
+    /// Returns a resolver that can only answer from its cache.
+    fn offline_resolver(config: &mut ResolverConfig, responses: Vec<DnsMessage>) -> AsyncResolver {
+        config.set_name_servers(Vec::new());
+        config.set_retransmission_loop_attempts(0);
+        let resolver = AsyncResolver::new(config.clone());
+        for response in responses {
+            resolver.cache.lock().unwrap().add(response);
+        }
+        resolver
+    }
+
+    /// Signed root and `example.` zones, with the root DS as trust anchor.
+    fn signed_chain() -> (ZoneKey, ZoneKey, Vec<DnsMessage>, Vec<ResourceRecord>) {
+        let root = ZoneKey::new(".", 1);
+        let example = ZoneKey::new("example", 2);
+        let responses = vec![
+            response(".", Rrtype::DNSKEY, root.signed(vec![root.dnskey_rr()]), vec![]),
+            response("example", Rrtype::DS, root.signed(vec![example.ds_rr()]), vec![]),
+            response("example", Rrtype::DNSKEY, example.signed(vec![example.dnskey_rr()]), vec![]),
+        ];
+        let anchors = vec![root.ds_rr()];
+        (root, example, responses, anchors)
+    }
+
+    fn validator(responses: Vec<DnsMessage>, trust_anchors: Vec<ResourceRecord>) -> ChainValidator {
+        let resolver = offline_resolver(&mut ResolverConfig::default(), responses);
+        ChainValidator::new(resolver, trust_anchors)
+    }
+
+    #[tokio::test]
+    async fn secure_answer() {
+        let (_, example, responses, anchors) = signed_chain();
+        let answer = response("www.example", Rrtype::A, example.signed(vec![a_rr("www.example", "192.0.2.1")]), vec![]);
+
+        let status = validator(responses, anchors).validate(&answer).await;
+
+        assert_eq!(status, SecurityStatus::Secure);
+    }
+
+    #[tokio::test]
+    async fn trust_anchor_dnskey() {
+        let example = ZoneKey::new("example", 2);
+        let responses = vec![response("example", Rrtype::DNSKEY, example.signed(vec![example.dnskey_rr()]), vec![])];
+        let answer = response("www.example", Rrtype::A, example.signed(vec![a_rr("www.example", "192.0.2.1")]), vec![]);
+
+        let status = validator(responses, vec![example.dnskey_rr()]).validate(&answer).await;
+
+        assert_eq!(status, SecurityStatus::Secure);
+    }
+
+    #[tokio::test]
+    async fn rrsig_of_signer_without_keys_is_skipped() {
+        let example = ZoneKey::new("example", 2);
+        let other_root = ZoneKey::new(".", 9);
+        let responses = vec![response("example", Rrtype::DNSKEY, example.signed(vec![example.dnskey_rr()]), vec![])];
+        let rrset = vec![a_rr("www.example", "192.0.2.1")];
+        let mut rrs = other_root.signed(rrset.clone());
+        rrs.push(example.signed(rrset).remove(1));
+        let answer = response("www.example", Rrtype::A, rrs, vec![]);
+
+        let status = validator(responses, vec![example.dnskey_rr()]).validate(&answer).await;
+
+        assert_eq!(status, SecurityStatus::Secure);
+    }
+
+    #[tokio::test]
+    async fn tampered_answer_is_bogus() {
+        let (_, example, responses, anchors) = signed_chain();
+        let mut rrs = example.signed(vec![a_rr("www.example", "192.0.2.1")]);
+        rrs[0] = a_rr("www.example", "192.0.2.66");
+        let answer = response("www.example", Rrtype::A, rrs, vec![]);
+
+        let status = validator(responses, anchors).validate(&answer).await;
+
+        assert_eq!(status, SecurityStatus::Bogus(EdeCode::DnssecBogus));
+    }
+
+    #[tokio::test]
+    async fn expired_signature_is_bogus() {
+        let (_, example, responses, anchors) = signed_chain();
+        let rrset = vec![a_rr("www.example", "192.0.2.1")];
+        let rrsig = example.sign_at(&rrset, now() - 2 * 86400, now() - 86400);
+        let answer = response("www.example", Rrtype::A, vec![rrset[0].clone(), rrsig], vec![]);
+
+        let status = validator(responses, anchors).validate(&answer).await;
+
+        assert_eq!(status, SecurityStatus::Bogus(EdeCode::SigExpired));
+    }
+
+    #[tokio::test]
+    async fn wrong_trust_anchor_is_bogus() {
+        let (_, example, responses, _) = signed_chain();
+        let other_root = ZoneKey::new(".", 9);
+        let answer = response("www.example", Rrtype::A, example.signed(vec![a_rr("www.example", "192.0.2.1")]), vec![]);
+
+        let status = validator(responses, vec![other_root.ds_rr()]).validate(&answer).await;
+
+        assert_eq!(status, SecurityStatus::Bogus(EdeCode::DnskeyMissing));
+    }
+
+    #[tokio::test]
+    async fn unsigned_answer_in_secure_zone_is_bogus() {
+        let (_, example, mut responses, anchors) = signed_chain();
+        let nsec = nsec_rr("www.example", "zzz.example", vec![Rrtype::A, Rrtype::RRSIG, Rrtype::NSEC]);
+        responses.push(response("www.example", Rrtype::DS, vec![], example.signed(vec![nsec])));
+        let answer = response("www.example", Rrtype::A, vec![a_rr("www.example", "192.0.2.1")], vec![]);
+
+        let status = validator(responses, anchors).validate(&answer).await;
+
+        assert_eq!(status, SecurityStatus::Bogus(EdeCode::RrsigsMissing));
+    }
+
+    #[tokio::test]
+    async fn insecure_delegation() {
+        let (root, _, mut responses, anchors) = signed_chain();
+        let nsec = nsec_rr("insecure", "zzz", vec![Rrtype::NS, Rrtype::RRSIG, Rrtype::NSEC]);
+        responses.push(response("insecure", Rrtype::DS, vec![], root.signed(vec![nsec])));
+        responses.push(response("www.insecure", Rrtype::DS, vec![], vec![soa_rr("insecure")]));
+        let answer = response("www.insecure", Rrtype::A, vec![a_rr("www.insecure", "192.0.2.1")], vec![]);
+
+        let status = validator(responses, anchors).validate(&answer).await;
+
+        assert_eq!(status, SecurityStatus::Insecure);
+    }
+
+    #[tokio::test]
+    async fn delegation_with_ds_in_nsec_is_bogus() {
+        let (root, _, mut responses, anchors) = signed_chain();
+        let nsec = nsec_rr("insecure", "zzz", vec![Rrtype::NS, Rrtype::DS, Rrtype::RRSIG, Rrtype::NSEC]);
+        responses.push(response("insecure", Rrtype::DS, vec![], root.signed(vec![nsec])));
+        responses.push(response("www.insecure", Rrtype::DS, vec![], vec![soa_rr("insecure")]));
+        let answer = response("www.insecure", Rrtype::A, vec![a_rr("www.insecure", "192.0.2.1")], vec![]);
+
+        let status = validator(responses, anchors).validate(&answer).await;
+
+        assert_eq!(status, SecurityStatus::Bogus(EdeCode::NsecMissing));
+    }
+
+    #[tokio::test]
+    async fn no_trust_anchor_is_indeterminate() {
+        let example = ZoneKey::new("example", 2);
+        let answer = response("www.other", Rrtype::A, vec![a_rr("www.other", "192.0.2.1")], vec![]);
+
+        let status = validator(vec![], vec![example.ds_rr()]).validate(&answer).await;
+
+        assert_eq!(status, SecurityStatus::Indeterminate);
+    }
+
+    fn nxdomain(qname: &str, authority: Vec<ResourceRecord>) -> DnsMessage {
+        let mut message = response(qname, Rrtype::A, vec![], authority);
+        let mut header = message.get_header();
+        header.set_rcode(Rcode::NXDOMAIN);
+        message.set_header(header);
+        message
+    }
+
+    #[tokio::test]
+    async fn nxdomain_with_nsec_proof() {
+        let (_, example, responses, anchors) = signed_chain();
+        let mut authority = example.signed(vec![soa_rr("example")]);
+        authority.extend(example.signed(vec![nsec_rr("example", "www.example", vec![Rrtype::SOA, Rrtype::NSEC])]));
+        let answer = nxdomain("nothere.example", authority);
+
+        let status = validator(responses, anchors).validate(&answer).await;
+
+        assert_eq!(status, SecurityStatus::Secure);
+    }
+
+    #[tokio::test]
+    async fn nxdomain_without_wildcard_proof_is_bogus() {
+        let (_, example, responses, anchors) = signed_chain();
+        let mut authority = example.signed(vec![soa_rr("example")]);
+        authority.extend(example.signed(vec![nsec_rr("a.example", "www.example", vec![Rrtype::A, Rrtype::NSEC])]));
+        let answer = nxdomain("nothere.example", authority);
+
+        let status = validator(responses, anchors).validate(&answer).await;
+
+        assert_eq!(status, SecurityStatus::Bogus(EdeCode::NsecMissing));
+    }
+
+    #[tokio::test]
+    async fn insecure_delegation_with_nsec3() {
+        let (_, example, mut responses, anchors) = signed_chain();
+        let zone = DomainName::new_from_str("example");
+        let owner = nsec3_hashed_name(&DomainName::new_from_str("insecure.example"), &zone, &[], 0);
+        let nsec3 = Nsec3Rdata::new(Nsec3HashAlgorithm::SHA1, 0, 0, 0, vec![], 20, vec![0xff; 20], vec![Rrtype::NS]);
+        responses.push(response("insecure.example", Rrtype::DS, vec![], example.signed(vec![rr(&owner, Rdata::NSEC3(nsec3))])));
+        responses.push(response("www.insecure.example", Rrtype::DS, vec![], vec![soa_rr("insecure.example")]));
+        let answer = response("www.insecure.example", Rrtype::A, vec![a_rr("www.insecure.example", "192.0.2.1")], vec![]);
+
+        let status = validator(responses, anchors).validate(&answer).await;
+
+        assert_eq!(status, SecurityStatus::Insecure);
+    }
+
+    fn cname_rr(name: &str, target: &str) -> ResourceRecord {
+        let mut cname = CnameRdata::new();
+        cname.set_cname(DomainName::new_from_str(target));
+        rr(&DomainName::new_from_str(name), Rdata::CNAME(cname))
+    }
+
+    #[tokio::test]
+    async fn cname_to_signed_answer() {
+        let (_, example, responses, anchors) = signed_chain();
+        let mut rrs = example.signed(vec![cname_rr("www.example", "web.example")]);
+        rrs.extend(example.signed(vec![a_rr("web.example", "192.0.2.1")]));
+        let answer = response("www.example", Rrtype::A, rrs, vec![]);
+
+        let status = validator(responses, anchors).validate(&answer).await;
+
+        assert_eq!(status, SecurityStatus::Secure);
+    }
+
+    #[tokio::test]
+    async fn cname_without_target_rrset_needs_denial() {
+        let (_, example, mut responses, anchors) = signed_chain();
+        let nsec = nsec_rr("web.example", "zzz.example", vec![Rrtype::TXT, Rrtype::RRSIG, Rrtype::NSEC]);
+        responses.push(response("web.example", Rrtype::DS, vec![], example.signed(vec![nsec.clone()])));
+        let cname = example.signed(vec![cname_rr("www.example", "web.example")]);
+        let stripped = response("www.example", Rrtype::A, cname.clone(), vec![]);
+        let mut authority = example.signed(vec![soa_rr("example")]);
+        authority.extend(example.signed(vec![nsec]));
+        let no_data = response("www.example", Rrtype::A, cname, authority);
+
+        let stripped_status = validator(responses.clone(), anchors.clone()).validate(&stripped).await;
+        let no_data_status = validator(responses, anchors).validate(&no_data).await;
+
+        assert_eq!(stripped_status, SecurityStatus::Bogus(EdeCode::NsecMissing));
+        assert_eq!(no_data_status, SecurityStatus::Secure);
+    }
+
+    #[tokio::test]
+    async fn missing_keys_are_bogus() {
+        let (_, example, _, anchors) = signed_chain();
+        let answer = response("www.example", Rrtype::A, example.signed(vec![a_rr("www.example", "192.0.2.1")]), vec![]);
+
+        let status = validator(vec![], anchors).validate(&answer).await;
+
+        assert_eq!(status, SecurityStatus::Bogus(EdeCode::NoReachableAuth));
+    }
+
+    #[tokio::test]
+    async fn inner_lookup_with_validation() {
+        let (_, example, mut responses, anchors) = signed_chain();
+        responses.push(response("www.example", Rrtype::A, example.signed(vec![a_rr("www.example", "192.0.2.1")]), vec![]));
+        let mut config = ResolverConfig::default();
+        config.set_dnssec_validation(true);
+        config.set_trust_anchors(anchors);
+        let resolver = offline_resolver(&mut config, responses);
+
+        let lookup_response = resolver
+            .inner_lookup(DomainName::new_from_str("www.example"), Rrtype::A, Rclass::IN)
+            .await
+            .unwrap();
+
+        assert_eq!(lookup_response.get_security_status(), Some(SecurityStatus::Secure));
+        assert!(lookup_response.to_dns_msg().get_header().get_ad());
+        assert_eq!(lookup_response.to_dns_msg().get_header().get_rcode(), Rcode::NOERROR);
+    }
+
+    #[tokio::test]
+    async fn inner_lookup_bogus_answer() {
+        let (_, example, mut responses, anchors) = signed_chain();
+        let mut rrs = example.signed(vec![a_rr("www.example", "192.0.2.1")]);
+        rrs[0] = a_rr("www.example", "192.0.2.66");
+        responses.push(response("www.example", Rrtype::A, rrs, vec![]));
+        let mut config = ResolverConfig::default();
+        config.set_dnssec_validation(true);
+        config.set_trust_anchors(anchors);
+        let resolver = offline_resolver(&mut config, responses);
+
+        let lookup_response = resolver
+            .inner_lookup(DomainName::new_from_str("www.example"), Rrtype::A, Rclass::IN)
+            .await
+            .unwrap();
+
+        let message = lookup_response.to_dns_msg();
+        assert_eq!(lookup_response.get_security_status(), Some(SecurityStatus::Bogus(EdeCode::DnssecBogus)));
+        assert_eq!(message.get_header().get_rcode(), Rcode::SERVFAIL);
+        assert!(message.get_answer().is_empty());
+    }
+
+    #[test]
+    fn bogus_response_with_ede() {
+        let answer = response("www.example", Rrtype::A, vec![a_rr("www.example", "192.0.2.1")], vec![]);
+
+        let servfail = bogus_response(&answer, EdeCode::SigExpired);
+        let parsed = DnsMessage::from_bytes(&servfail.to_bytes()).unwrap();
+
+        assert_eq!(parsed.get_header().get_rcode(), Rcode::SERVFAIL);
+        assert!(parsed.get_header().get_qr());
+        assert_eq!(parsed.get_question().get_qname(), DomainName::new_from_str("www.example"));
+        assert!(parsed.get_answer().is_empty());
+        let opt_rr = parsed.get_additional().pop().unwrap();
+        let Rdata::OPT(opt) = opt_rr.get_rdata() else { panic!("not an OPT record") };
+        let OptionData::EDE(ede) = opt.get_option()[0].get_opt_data() else { panic!("not an EDE option") };
+        assert_eq!(ede.get_info_code(), EdeCode::SigExpired);
+    }
+
+    #[test]
+    fn ds_of_rfc8080_key() {
+        // RFC 8080 section 6.1
+        let public_key = BASE64.decode(b"l02Woi0iS8Aa25FQkUd9RMzZHJpBoRQwAQEX1SxZJA4=").unwrap();
+        let dnskey = DnskeyRdata::new(257, 3, DnssecAlgorithm::ED25519, public_key);
+        let digest = HEXLOWER.decode(b"3aa5ab37efce57f737fc1627013fee07bdf241bd10f3b1964ab55c78e79a304b").unwrap();
+        let ds = DsRdata::new(3613, DnssecAlgorithm::ED25519, DigestType::SHA256, digest);
+        let owner = DomainName::new_from_str("Example.COM");
+
+        assert!(ds_matches(&owner, &ds, &dnskey));
+        assert!(!ds_matches(&DomainName::new_from_str("example.net"), &ds, &dnskey));
+    }
+
+    #[test]
+    fn combine_keeps_least_secure() {
+        let bogus = SecurityStatus::Bogus(EdeCode::DnssecBogus);
+
+        assert_eq!(SecurityStatus::Secure.combine(SecurityStatus::Insecure), SecurityStatus::Insecure);
+        assert_eq!(SecurityStatus::Indeterminate.combine(SecurityStatus::Insecure), SecurityStatus::Indeterminate);
+        assert_eq!(SecurityStatus::Secure.combine(bogus), bogus);
+        assert_eq!(bogus.combine(SecurityStatus::Indeterminate), bogus);
+    }
+}
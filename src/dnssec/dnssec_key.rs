@@ -0,0 +1,773 @@
+//! Private keys of DNSSEC zones and their storage in the file format of BIND.
+//!
+//! BIND keeps every key in a pair of files named after the zone, the
+//! algorithm and the key tag: `K<zone>+<alg>+<tag>.key` holds the DNSKEY RR
+//! in master file format and `K<zone>+<alg>+<tag>.private` holds the private
+//! key as a list of `Field: value` lines, with binary values in base64.
+//!
+//! ```text
+//! Private-key-format: v1.3
+//! Algorithm: 13 (ECDSAP256SHA256)
+//! PrivateKey: GU6SnQ/Ou+xC5RumuIUIuJZteXT2z0O/ok1s38Et6mQ=
+//! Created: 20240101000000
+//! ```
+
+use crate::der::{der_element, der_elements};
+use crate::dnssec::dnssec_algorithm::DnssecAlgorithm;
+use crate::domain_name::DomainName;
+use crate::message::rdata::dnskey_rdata::DnskeyRdata;
+use crate::message::rdata::presentation::{format_base64, format_name, format_time, parse_base64, parse_name, parse_time};
+use aws_lc_rs::encoding::{AsDer, Pkcs8V1Der};
+use aws_lc_rs::rsa::{KeyPair as RsaGeneratedKeyPair, KeySize};
+use ring::rand::SystemRandom;
+use ring::rsa::{KeyPairComponents, PublicKeyComponents};
+use ring::signature::{
+    EcdsaKeyPair, Ed25519KeyPair, KeyPair as _, RsaEncoding, RsaKeyPair, ECDSA_P256_SHA256_FIXED_SIGNING,
+    ECDSA_P384_SHA384_FIXED_SIGNING, RSA_PKCS1_SHA256, RSA_PKCS1_SHA512,
+};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// ZONE flag of the DNSKEY RDATA: the key can verify the RRSIGs of a zone.
+///
+/// [RFC 4034](https://datatracker.ietf.org/doc/html/rfc4034#section-2.1.1)
+pub const ZONE_FLAG: u16 = 0x0100;
+
+/// REVOKE flag of the DNSKEY RDATA.
+///
+/// [RFC 5011](https://datatracker.ietf.org/doc/html/rfc5011#section-7)
+pub const REVOKE_FLAG: u16 = 0x0080;
+
+/// Secure Entry Point flag of the DNSKEY RDATA.
+///
+/// [RFC 4034](https://datatracker.ietf.org/doc/html/rfc4034#section-2.1.1)
+pub const SEP_FLAG: u16 = 0x0001;
+
+/// Flags of a zone signing key.
+pub const ZSK_FLAGS: u16 = ZONE_FLAG;
+
+/// Flags of a key signing key.
+pub const KSK_FLAGS: u16 = ZONE_FLAG | SEP_FLAG;
+
+/// Value of the protocol field of every DNSKEY RDATA.
+///
+/// [RFC 4034](https://datatracker.ietf.org/doc/html/rfc4034#section-2.1.2)
+const DNSKEY_PROTOCOL: u8 = 3;
+
+/// Version of the private key format written by BIND 9.
+const PRIVATE_KEY_FORMAT: &str = "v1.3";
+
+/// Fields of an RSA private key, in the order BIND writes them.
+const RSA_FIELDS: [&str; 8] =
+    ["Modulus", "PublicExponent", "PrivateExponent", "Prime1", "Prime2", "Exponent1", "Exponent2", "Coefficient"];
+
+/// Size of the generated RSA keys, the smallest modulus that `ring`
+/// accepts to sign.
+const RSA_KEY_SIZE: KeySize = KeySize::Rsa2048;
+
+/// Field of an ECDSA or Ed25519 private key.
+const PRIVATE_KEY_FIELD: &str = "PrivateKey";
+
+/// Error of a key that could not be generated.
+const GENERATION_FAILED: KeyError = KeyError::InvalidKey("key generation failed");
+
+/// Error of a PKCS#8 document that is not valid DER.
+const INVALID_DER: KeyError = KeyError::InvalidKey("invalid DER encoding");
+
+/// Error found while creating, reading or writing a key.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum KeyError {
+    /// The DNSKEY algorithm cannot be used to sign.
+    #[error("unsupported algorithm: {0}")]
+    UnsupportedAlgorithm(DnssecAlgorithm),
+
+    /// The private key is not valid for its algorithm.
+    #[error("invalid key: {0}")]
+    InvalidKey(&'static str),
+
+    /// The key files are not in the format of BIND.
+    #[error("invalid key file: {0}")]
+    InvalidFormat(&'static str),
+
+    /// The key files could not be read or written.
+    #[error("I/O error: {0}")]
+    Io(String),
+}
+
+/// Key pair of one of the algorithms that can sign.
+enum KeyPair {
+    Rsa(RsaKeyPair, &'static dyn RsaEncoding),
+    Ecdsa(EcdsaKeyPair),
+    Ed25519(Ed25519KeyPair),
+}
+
+/// Private key of a zone together with its DNSKEY RDATA.
+///
+/// The algorithms that can be used are RSA/SHA-256 (8), RSA/SHA-512 (10),
+/// ECDSA P-256 (13), ECDSA P-384 (14) and Ed25519 (15). Keys of every
+/// algorithm can be generated, or read from PKCS#8 documents or BIND key
+/// files.
+pub struct DnssecKey {
+    /// Public half of the key.
+    dnskey: DnskeyRdata,
+    /// Key pair used to sign.
+    key_pair: KeyPair,
+    /// Fields of the private key in the BIND format, with their values.
+    private_fields: Vec<(&'static str, Vec<u8>)>,
+    /// Time at which the key was created, in seconds since 1 January 1970.
+    created: u32,
+}
+
+impl DnssecKey {
+    /// Generates a new key of one of the `DnssecAlgorithm::SIGNING`
+    /// algorithms. RSA keys have a 2048 bit modulus.
+    ///
+    /// # Example
+    /// ```
+    /// let zsk = DnssecKey::generate(DnssecAlgorithm::ECDSAP256SHA256, ZSK_FLAGS).unwrap();
+    /// ```
+    pub fn generate(algorithm: DnssecAlgorithm, flags: u16) -> Result<Self, KeyError> {
+        let rng = SystemRandom::new();
+        let failed = |_| GENERATION_FAILED;
+        let pkcs8 = match algorithm {
+            DnssecAlgorithm::RSASHA256 | DnssecAlgorithm::RSASHA512 => {
+                let key_pair = RsaGeneratedKeyPair::generate(RSA_KEY_SIZE).map_err(|_| GENERATION_FAILED)?;
+                let der: Pkcs8V1Der = key_pair.as_der().map_err(|_| GENERATION_FAILED)?;
+                der.as_ref().to_vec()
+            }
+            DnssecAlgorithm::ECDSAP256SHA256 => {
+                EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &rng).map_err(failed)?.as_ref().to_vec()
+            }
+            DnssecAlgorithm::ECDSAP384SHA384 => {
+                EcdsaKeyPair::generate_pkcs8(&ECDSA_P384_SHA384_FIXED_SIGNING, &rng).map_err(failed)?.as_ref().to_vec()
+            }
+            DnssecAlgorithm::ED25519 => Ed25519KeyPair::generate_pkcs8(&rng).map_err(failed)?.as_ref().to_vec(),
+            _ => return Err(KeyError::UnsupportedAlgorithm(algorithm)),
+        };
+
+        DnssecKey::from_pkcs8(algorithm, flags, &pkcs8)
+    }
+
+    /// Creates a new `DnssecKey` from a private key in PKCS#8 format.
+    ///
+    /// # Example
+    /// ```
+    /// let key = DnssecKey::from_pkcs8(DnssecAlgorithm::ED25519, KSK_FLAGS, &pkcs8).unwrap();
+    /// ```
+    pub fn from_pkcs8(algorithm: DnssecAlgorithm, flags: u16, pkcs8: &[u8]) -> Result<Self, KeyError> {
+        let rng = SystemRandom::new();
+        let invalid = |_| KeyError::InvalidKey("the PKCS#8 document does not hold a key of the algorithm");
+        let key_pair = match algorithm {
+            DnssecAlgorithm::RSASHA256 => {
+                KeyPair::Rsa(RsaKeyPair::from_pkcs8(pkcs8).map_err(invalid)?, &RSA_PKCS1_SHA256)
+            }
+            DnssecAlgorithm::RSASHA512 => {
+                KeyPair::Rsa(RsaKeyPair::from_pkcs8(pkcs8).map_err(invalid)?, &RSA_PKCS1_SHA512)
+            }
+            DnssecAlgorithm::ECDSAP256SHA256 => KeyPair::Ecdsa(
+                EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, pkcs8, &rng).map_err(invalid)?,
+            ),
+            DnssecAlgorithm::ECDSAP384SHA384 => KeyPair::Ecdsa(
+                EcdsaKeyPair::from_pkcs8(&ECDSA_P384_SHA384_FIXED_SIGNING, pkcs8, &rng).map_err(invalid)?,
+            ),
+            DnssecAlgorithm::ED25519 => KeyPair::Ed25519(Ed25519KeyPair::from_pkcs8_maybe_unchecked(pkcs8).map_err(invalid)?),
+            _ => return Err(KeyError::UnsupportedAlgorithm(algorithm)),
+        };
+        let private_fields = pkcs8_private_fields(&key_pair, pkcs8)?;
+
+        Ok(DnssecKey::from_key_pair(algorithm, flags, key_pair, private_fields))
+    }
+
+    /// Creates a new `DnssecKey` from the contents of its BIND `.key` and
+    /// `.private` files, returning it with the zone it belongs to.
+    ///
+    /// # Example
+    /// ```
+    /// let (zone, key) = DnssecKey::from_bind(&key_file, &private_file).unwrap();
+    /// ```
+    pub fn from_bind(key_file: &str, private_file: &str) -> Result<(DomainName, Self), KeyError> {
+        let (zone, dnskey) = parse_key_file(key_file)?;
+        let PrivateFile { algorithm, fields, created } = parse_private_file(private_file)?;
+        if algorithm != dnskey.get_algorithm() {
+            return Err(KeyError::InvalidFormat("the algorithms of the key files are different"));
+        }
+
+        let field = |name: &str| -> Result<Vec<u8>, KeyError> {
+            fields
+                .iter()
+                .find(|(field, _)| field.eq_ignore_ascii_case(name))
+                .map(|(_, value)| value.clone())
+                .ok_or(KeyError::InvalidFormat("missing field in the private key file"))
+        };
+        let rng = SystemRandom::new();
+        let invalid = |_| KeyError::InvalidKey("the private key does not match its DNSKEY");
+        let public_key = dnskey.get_public_key();
+        let (key_pair, private_fields) = match algorithm {
+            DnssecAlgorithm::RSASHA256 | DnssecAlgorithm::RSASHA512 => {
+                let values = RSA_FIELDS.iter().map(|name| field(name)).collect::<Result<Vec<_>, _>>()?;
+                let components = KeyPairComponents {
+                    public_key: PublicKeyComponents { n: &values[0], e: &values[1] },
+                    d: &values[2],
+                    p: &values[3],
+                    q: &values[4],
+                    dP: &values[5],
+                    dQ: &values[6],
+                    qInv: &values[7],
+                };
+                let encoding: &'static dyn RsaEncoding = match algorithm {
+                    DnssecAlgorithm::RSASHA256 => &RSA_PKCS1_SHA256,
+                    _ => &RSA_PKCS1_SHA512,
+                };
+                let key_pair = RsaKeyPair::from_components(&components).map_err(invalid)?;
+                (KeyPair::Rsa(key_pair, encoding), RSA_FIELDS.iter().copied().zip(values).collect())
+            }
+            DnssecAlgorithm::ECDSAP256SHA256 | DnssecAlgorithm::ECDSAP384SHA384 => {
+                let signing = match algorithm {
+                    DnssecAlgorithm::ECDSAP256SHA256 => &ECDSA_P256_SHA256_FIXED_SIGNING,
+                    _ => &ECDSA_P384_SHA384_FIXED_SIGNING,
+                };
+                let private_key = field(PRIVATE_KEY_FIELD)?;
+                let mut point = vec![0x04];
+                point.extend(&public_key);
+                let key_pair =
+                    EcdsaKeyPair::from_private_key_and_public_key(signing, &private_key, &point, &rng).map_err(invalid)?;
+                (KeyPair::Ecdsa(key_pair), vec![(PRIVATE_KEY_FIELD, private_key)])
+            }
+            DnssecAlgorithm::ED25519 => {
+                let private_key = field(PRIVATE_KEY_FIELD)?;
+                let key_pair = Ed25519KeyPair::from_seed_and_public_key(&private_key, &public_key).map_err(invalid)?;
+                (KeyPair::Ed25519(key_pair), vec![(PRIVATE_KEY_FIELD, private_key)])
+            }
+            _ => return Err(KeyError::UnsupportedAlgorithm(algorithm)),
+        };
+
+        let mut key = DnssecKey::from_key_pair(algorithm, dnskey.get_flags(), key_pair, private_fields);
+        if key.dnskey.get_public_key() != public_key {
+            return Err(KeyError::InvalidKey("the private key does not match its DNSKEY"));
+        }
+        if let Some(created) = created {
+            key.created = created;
+        }
+
+        Ok((zone, key))
+    }
+
+    /// Reads a key from its pair of BIND files. The path can be the one of
+    /// either file, or their common prefix `K<zone>+<alg>+<tag>`.
+    ///
+    /// # Example
+    /// ```
+    /// let (zone, key) = DnssecKey::read_bind_files("keys/Kexample.com.+013+12345").unwrap();
+    /// ```
+    pub fn read_bind_files<P: AsRef<Path>>(path: P) -> Result<(DomainName, Self), KeyError> {
+        let path = path.as_ref();
+        let prefix = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("key") | Some("private") => path.with_extension(""),
+            _ => path.to_path_buf(),
+        };
+        let read = |extension: &str| {
+            let file = PathBuf::from(format!("{}.{}", prefix.display(), extension));
+            fs::read_to_string(&file).map_err(|error| KeyError::Io(format!("{}: {}", file.display(), error)))
+        };
+
+        DnssecKey::from_bind(&read("key")?, &read("private")?)
+    }
+
+    /// Writes the pair of BIND files of the key for `zone` in `directory`,
+    /// returning the common prefix of their paths. The `.private` file is
+    /// only readable by its owner.
+    ///
+    /// # Example
+    /// ```
+    /// let prefix = key.write_bind_files("keys", &DomainName::new_from_str("example.com")).unwrap();
+    /// ```
+    pub fn write_bind_files<P: AsRef<Path>>(&self, directory: P, zone: &DomainName) -> Result<PathBuf, KeyError> {
+        let prefix = directory.as_ref().join(self.bind_file_name(zone));
+        let write = |extension: &str, contents: String| {
+            let file = PathBuf::from(format!("{}.{}", prefix.display(), extension));
+            write_file(&file, &contents, extension == "private")
+                .map_err(|error| KeyError::Io(format!("{}: {}", file.display(), error)))
+        };
+        write("key", self.to_bind_key(zone))?;
+        write("private", self.to_bind_private())?;
+
+        Ok(prefix)
+    }
+
+    /// Returns the name BIND gives to the files of the key, without
+    /// extension: `K<zone>+<alg>+<tag>`.
+    ///
+    /// # Example
+    /// ```
+    /// assert_eq!(key.bind_file_name(&DomainName::new_from_str("example.com")), "Kexample.com.+013+12345");
+    /// ```
+    pub fn bind_file_name(&self, zone: &DomainName) -> String {
+        format!("K{}+{:03}+{:05}", format_name(zone).to_lowercase(), u8::from(self.get_algorithm()), self.key_tag())
+    }
+
+    /// Returns the contents of the `.key` file of the key for `zone`.
+    pub fn to_bind_key(&self, zone: &DomainName) -> String {
+        let role = match self.dnskey.get_flags() & SEP_FLAG {
+            0 => "zone-signing",
+            _ => "key-signing",
+        };
+        format!(
+            "; This is a {} key, keyid {}, for {}\n; Created: {}\n{} IN DNSKEY {}\n",
+            role,
+            self.key_tag(),
+            format_name(zone),
+            format_time(self.created),
+            format_name(zone),
+            self.dnskey.to_text()
+        )
+    }
+
+    /// Returns the contents of the `.private` file of the key.
+    pub fn to_bind_private(&self) -> String {
+        let mut private_file = format!(
+            "Private-key-format: {}\nAlgorithm: {} ({})\n",
+            PRIVATE_KEY_FORMAT,
+            u8::from(self.get_algorithm()),
+            self.get_algorithm()
+        );
+        for (field, value) in self.private_fields.iter() {
+            private_file.push_str(&format!("{}: {}\n", field, format_base64(value)));
+        }
+        private_file.push_str(&format!("Created: {}\n", format_time(self.created)));
+        private_file
+    }
+
+    /// Signs the data, returning the signature in the format of the RRSIG
+    /// RDATA.
+    pub fn sign(&self, data: &[u8]) -> Result<Vec<u8>, KeyError> {
+        let rng = SystemRandom::new();
+        let failed = |_| KeyError::InvalidKey("signing failed");
+        match &self.key_pair {
+            KeyPair::Rsa(key_pair, encoding) => {
+                let mut signature = vec![0; key_pair.public().modulus_len()];
+                key_pair.sign(*encoding, &rng, data, &mut signature).map_err(failed)?;
+                Ok(signature)
+            }
+            KeyPair::Ecdsa(key_pair) => Ok(key_pair.sign(&rng, data).map_err(failed)?.as_ref().to_vec()),
+            KeyPair::Ed25519(key_pair) => Ok(key_pair.sign(data).as_ref().to_vec()),
+        }
+    }
+
+    /// Returns the key tag of the key.
+    pub fn key_tag(&self) -> u16 {
+        self.dnskey.key_tag()
+    }
+
+    /// Builds the DNSKEY RDATA of a key pair.
+    fn from_key_pair(
+        algorithm: DnssecAlgorithm,
+        flags: u16,
+        key_pair: KeyPair,
+        private_fields: Vec<(&'static str, Vec<u8>)>,
+    ) -> Self {
+        let public_key = match &key_pair {
+            // RFC 3110 section 2: exponent length, exponent and modulus
+            KeyPair::Rsa(key_pair, _) => {
+                let components = PublicKeyComponents::<Vec<u8>>::from(key_pair.public());
+                let mut public_key = match components.e.len() {
+                    len if len <= 255 => vec![len as u8],
+                    len => vec![0, (len >> 8) as u8, len as u8],
+                };
+                public_key.extend(components.e);
+                public_key.extend(components.n);
+                public_key
+            }
+            // RFC 6605 section 4: the point without the uncompressed form prefix
+            KeyPair::Ecdsa(key_pair) => key_pair.public_key().as_ref()[1..].to_vec(),
+            KeyPair::Ed25519(key_pair) => key_pair.public_key().as_ref().to_vec(),
+        };
+        let created = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs() as u32;
+
+        DnssecKey {
+            dnskey: DnskeyRdata::new(flags, DNSKEY_PROTOCOL, algorithm, public_key),
+            key_pair,
+            private_fields,
+            created,
+        }
+    }
+}
+
+// Getters
+impl DnssecKey {
+    /// Returns the DNSKEY RDATA of the key.
+    pub fn get_dnskey(&self) -> DnskeyRdata {
+        self.dnskey.clone()
+    }
+
+    pub fn get_algorithm(&self) -> DnssecAlgorithm {
+        self.dnskey.get_algorithm()
+    }
+
+    pub fn get_flags(&self) -> u16 {
+        self.dnskey.get_flags()
+    }
+
+    pub fn get_created(&self) -> u32 {
+        self.created
+    }
+
+    /// Returns whether the key has the Secure Entry Point flag.
+    pub fn is_sep(&self) -> bool {
+        self.dnskey.is_sep()
+    }
+
+    /// Returns whether the key has the ZONE flag.
+    pub fn is_zone_key(&self) -> bool {
+        self.dnskey.get_flags() & ZONE_FLAG != 0
+    }
+
+    /// Returns whether the key has the REVOKE flag.
+    pub fn is_revoked(&self) -> bool {
+        self.dnskey.is_revoked()
+    }
+}
+
+// Setters
+impl DnssecKey {
+    pub fn set_flags(&mut self, flags: u16) {
+        self.dnskey.set_flags(flags);
+    }
+
+    pub fn set_created(&mut self, created: u32) {
+        self.created = created;
+    }
+
+    /// Sets or clears the Secure Entry Point flag. This changes the key tag.
+    pub fn set_sep(&mut self, sep: bool) {
+        self.set_flag(SEP_FLAG, sep);
+    }
+
+    /// Sets or clears the ZONE flag. This changes the key tag.
+    pub fn set_zone_key(&mut self, zone_key: bool) {
+        self.set_flag(ZONE_FLAG, zone_key);
+    }
+
+    /// Sets or clears the REVOKE flag. This changes the key tag.
+    pub fn set_revoked(&mut self, revoked: bool) {
+        self.set_flag(REVOKE_FLAG, revoked);
+    }
+
+    fn set_flag(&mut self, flag: u16, value: bool) {
+        let flags = self.dnskey.get_flags();
+        self.dnskey.set_flags(if value { flags | flag } else { flags & !flag });
+    }
+}
+
+impl fmt::Debug for DnssecKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "DnssecKey({} {} {})", self.get_flags(), self.get_algorithm(), self.key_tag())
+    }
+}
+
+/// Parses a `.key` file, returning its owner name and its DNSKEY RDATA.
+///
+/// The file holds a single DNSKEY RR in master file format, with an
+/// absolute owner name and optional TTL and class, after comment lines.
+fn parse_key_file(key_file: &str) -> Result<(DomainName, DnskeyRdata), KeyError> {
+    let line = key_file
+        .lines()
+        .map(|line| line.split(';').next().unwrap_or_default().trim())
+        .find(|line| !line.is_empty())
+        .ok_or(KeyError::InvalidFormat("the key file has no DNSKEY RR"))?;
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    let type_index = fields
+        .iter()
+        .position(|field| field.eq_ignore_ascii_case("DNSKEY"))
+        .ok_or(KeyError::InvalidFormat("the key file has no DNSKEY RR"))?;
+    if type_index == 0 {
+        return Err(KeyError::InvalidFormat("the DNSKEY RR has no owner name"));
+    }
+
+    let zone = parse_name(fields[0], &DomainName::new_from_str(".")).map_err(KeyError::InvalidFormat)?;
+    let dnskey = DnskeyRdata::from_text(&fields[type_index + 1..], &zone).map_err(KeyError::InvalidFormat)?;
+    Ok((zone, dnskey))
+}
+
+/// Contents of a `.private` file.
+struct PrivateFile {
+    algorithm: DnssecAlgorithm,
+    /// Fields of the private key with their binary values.
+    fields: Vec<(String, Vec<u8>)>,
+    created: Option<u32>,
+}
+
+/// Parses a `.private` file.
+fn parse_private_file(private_file: &str) -> Result<PrivateFile, KeyError> {
+    let mut algorithm = None;
+    let mut created = None;
+    let mut fields = Vec::new();
+    for line in private_file.lines().filter(|line| !line.trim().is_empty()) {
+        let (name, value) = line
+            .split_once(':')
+            .ok_or(KeyError::InvalidFormat("the private key file has a line without field"))?;
+        let value = value.trim();
+        match name.trim() {
+            "Private-key-format" => {
+                if !value.starts_with("v1.") {
+                    return Err(KeyError::InvalidFormat("unsupported private key format"));
+                }
+            }
+            "Algorithm" => {
+                let number = value.split_whitespace().next().unwrap_or_default();
+                let number = number.parse::<u8>().map_err(|_| KeyError::InvalidFormat("invalid algorithm"))?;
+                algorithm = Some(DnssecAlgorithm::from(number));
+            }
+            "Created" => created = Some(parse_time(value).map_err(KeyError::InvalidFormat)?),
+            // Timing metadata and other fields of newer versions
+            name if !RSA_FIELDS.contains(&name) && name != PRIVATE_KEY_FIELD => {}
+            name => fields.push((name.to_string(), parse_base64(&[value]).map_err(KeyError::InvalidFormat)?)),
+        }
+    }
+
+    let algorithm = algorithm.ok_or(KeyError::InvalidFormat("the private key file has no algorithm"))?;
+    Ok(PrivateFile { algorithm, fields, created })
+}
+
+/// Returns the fields of the BIND format of a private key given in PKCS#8.
+///
+/// [RFC 5208](https://datatracker.ietf.org/doc/html/rfc5208#section-5)
+///
+/// ```text
+/// PrivateKeyInfo ::= SEQUENCE {
+///   version                   Version,
+///   privateKeyAlgorithm       PrivateKeyAlgorithmIdentifier,
+///   privateKey                PrivateKey,
+///   attributes           [0]  IMPLICIT Attributes OPTIONAL }
+/// ```
+fn pkcs8_private_fields(key_pair: &KeyPair, pkcs8: &[u8]) -> Result<Vec<(&'static str, Vec<u8>)>, KeyError> {
+    let (_, private_key_info, _) = der_element(pkcs8).ok_or(INVALID_DER)?;
+    let private_key = der_elements(private_key_info).ok_or(INVALID_DER)?
+        .get(2)
+        .map(|(_, private_key)| *private_key)
+        .ok_or(KeyError::InvalidKey("invalid PKCS#8 document"))?;
+
+    match key_pair {
+        // RFC 8017 appendix A.1.2: RSAPrivateKey, a version and the eight fields
+        KeyPair::Rsa(..) => {
+            let (_, rsa_private_key, _) = der_element(private_key).ok_or(INVALID_DER)?;
+            let integers = der_elements(rsa_private_key).ok_or(INVALID_DER)?;
+            if integers.len() < 9 {
+                return Err(KeyError::InvalidKey("invalid RSA private key"));
+            }
+            Ok(RSA_FIELDS.iter().copied().zip(integers[1..9].iter().map(|(_, integer)| der_unsigned(integer))).collect())
+        }
+        // RFC 5915 section 3: ECPrivateKey, a version and the private key
+        KeyPair::Ecdsa(_) => {
+            let (_, ec_private_key, _) = der_element(private_key).ok_or(INVALID_DER)?;
+            match der_elements(ec_private_key).ok_or(INVALID_DER)?.get(1) {
+                Some((_, private_key)) => Ok(vec![(PRIVATE_KEY_FIELD, private_key.to_vec())]),
+                None => Err(KeyError::InvalidKey("invalid ECDSA private key")),
+            }
+        }
+        // RFC 8410 section 7: CurvePrivateKey, the seed as an OCTET STRING
+        KeyPair::Ed25519(_) => {
+            let (_, seed, _) = der_element(private_key).ok_or(INVALID_DER)?;
+            Ok(vec![(PRIVATE_KEY_FIELD, seed.to_vec())])
+        }
+    }
+}
+
+/// Returns a DER INTEGER without the leading zero that keeps it positive.
+fn der_unsigned(integer: &[u8]) -> Vec<u8> {
+    let start = integer.iter().position(|&octet| octet != 0).unwrap_or(integer.len());
+    integer[start..].to_vec()
+}
+
+/// Writes a file, making it readable only by its owner when it is private.
+fn write_file(path: &Path, contents: &str, private: bool) -> std::io::Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    if private {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    #[cfg(not(unix))]
+    let _ = private;
+
+    use std::io::Write;
+    options.open(path)?.write_all(contents.as_bytes())
+}
+
+#[cfg(test)]
+mod dnssec_key_test {
+    use super::*;
+    use crate::dnssec::rrset_signature::{signature_data, verify_rrsig, RrsigVerification};
+    use crate::message::rdata::a_rdata::ARdata;
+    use crate::message::rdata::rrsig_rdata::RRSIGRdata;
+    use crate::message::rdata::Rdata;
+    use crate::message::resource_record::ResourceRecord;
+    use crate::message::rrtype::Rrtype;
+    use std::net::IpAddr;
+
+    const RSA_PRIVATE: &str = "\
+Private-key-format: v1.3
+Algorithm: 8 (RSASHA256)
+Modulus: 0g5N5eF7Vf6TsmvxkyHLu98i8U2g8Bdh4GDfSGTPUn+PyKbgqYgTWJ3Bdtd+Ln3Hzvkt0XkoPWe2aR2HbY67cmjW8slWT+CBlltgAJHo0tZckNbXT5jvtnV2wrwrysJ3D0+nEX3sWXdJ/7zrfc071N9QNa1kL3B8aSK3ZOwmOUbKJaWBJNx9UmRSUmLbAt5lGe+YKt2qoGaWUSpThaelYsSyOcgDyCRy3zv0UW7Iosvuqm4qpaSu+qp6oZ4gahZxoIyN66GNId4yE+MfOlCvIbWv2eR2t092g1QEKWnaq6vtd33M4AFLiNOGd4tihSyBBJw4gI/PUJI+atbHBfUu6Q==
+PublicExponent: AQAB
+PrivateExponent: BYRFfYZJyewxdDlJsWRJakAyPRky6iinDOJJ+9KqoVu8A5Ew3Vy+gKlUN9P/vhEdqc61zd9uzPpGEy0oxq9MC+5Qq/u898SUCrD0KTQdlY9L2Vb3IEVKWGXfo3cI9xBMy3dl/OYzX7x57r546kdfyYl4OYIA6cdDihb/Z/t/ovCTkRucCbugyOK3CiW3qtaiO5rvg1rzczJOrarYWRoH1Nks8b+5oyZmKWc5PKE495wuTvWqxIT59UNj8p6znZ4+c6Mzj4wIHk91inOoKdCZ5Z4hFps98cti9uebynE01T/ETfYy4yZcTYef+utUIo/kuzfXcUklAK4W31B0NzgCfQ==
+Prime1: 8fgVtCaoHjNZZKUAIg/qYUISDA6QPDYXKOqUT2+Heonh0SfS/WQiU/qd4uNTNjzT4wCu1UnogIjRrxB+5U3eOMQ3GMvmPAkNIfUfpY3IACJJ0ng8/O07hMy0XyUAo2tPcq+ee1EgVvF6fKoyeRsCLQitsqCcJisrHUNHqmIagwU=
+Prime2: 3jx7tl4XtGGFzVG4Et78f6+/kW4TBB95sMXPNLwF+bEvhkZyiDEk5hGjkBQEpwTLcyxiht8Th11GHLZLsB3D2A1yazNI5Bk0tCqRGF+FSPAW75yccw/fI2BH7blawHgeE+fP/QePYtPeU8MDkl4vE7O6Iu5pVzpirA6za02IyZU=
+Exponent1: UVNroAgaG0Rw+DXI7KniwdueCzVd3w7jC9qEDIrUwyfSoSS8cnQUYZtrM5XqIuGg6bwSBxf3MDRMHYXy0IkydmvkhM4LTQRRyKcFgvQ7U2gy3a2R+gSWK5VNzmi2ZTatFkPWhWYH85Dn9TyTCEc+nPo8vzviH6YEsKorTSX5+S0=
+Exponent2: df8tPIY3FdpV/c8u6i4laPJ6x4/nXuALb9WHkBtpHMt8TVIoS7G0QsgIzV4jnj6Ie42MfsoBB3V53ZLZGxiivJtfLhYb1KiheCvFwGgvT6vSscS4uMsrkg/0AP/SdLESX0VNBK837aRKvrSjt9p0KoTaBuQJYHwqmepwTz/qqgU=
+Coefficient: x/C7V+/W7jR0jOFEnUFjpLaWWUji+fi+BsfDA8wHKKQyQcCH6Vv4QbNmPYuCfKh83kjQKl2MEJA8fpTuXCXP4CY/bbO8wS7CeL/dx7DaI2tIiv0sovOWESxKdi7V03CW1T1ip3iQjLuogIWDNl9hLHjmXL9Tu+u/bOvwnDqZNjc=
+Created: 20240101000000
+Publish: 20240101000000
+Activate: 20240101000000
+";
+
+    fn zone() -> DomainName {
+        DomainName::new_from_str("example.com")
+    }
+
+    /// Signs an A RRset with the key and verifies the signature with its
+    /// DNSKEY.
+    fn assert_signs(key: &DnssecKey) {
+        let mut rr = ResourceRecord::new(Rdata::A(ARdata::new_from_addr(IpAddr::from([192, 0, 2, 1]))));
+        rr.set_name(zone());
+        rr.set_ttl(3600);
+        let rrset = vec![rr];
+
+        let mut rrsig = RRSIGRdata::new();
+        rrsig.set_type_covered(Rrtype::A);
+        rrsig.set_algorithm(key.get_algorithm());
+        rrsig.set_labels(2);
+        rrsig.set_original_ttl(3600);
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as u32;
+        rrsig.set_signature_inception(now - 3600);
+        rrsig.set_signature_expiration(now + 3600);
+        rrsig.set_key_tag(key.key_tag());
+        rrsig.set_signer_name(zone());
+        rrsig.set_signature(key.sign(&signature_data(&rrsig, &rrset).unwrap()).unwrap());
+
+        assert_eq!(verify_rrsig(&rrsig, &key.get_dnskey(), &rrset), RrsigVerification::Secure);
+    }
+
+    #[test]
+    fn generate_and_flags() {
+        let mut key = DnssecKey::generate(DnssecAlgorithm::ECDSAP256SHA256, KSK_FLAGS).unwrap();
+        assert_eq!(key.get_dnskey().get_flags(), 257);
+        assert_eq!(key.get_dnskey().get_protocol(), 3);
+        assert_eq!(key.get_dnskey().get_public_key().len(), 64);
+        assert!(key.is_sep() && key.is_zone_key() && !key.is_revoked());
+        assert_signs(&key);
+
+        let key_tag = key.key_tag();
+        key.set_revoked(true);
+        assert_eq!(key.get_flags(), 385);
+        assert!(key.get_dnskey().is_revoked());
+        assert_ne!(key.key_tag(), key_tag);
+        key.set_sep(false);
+        key.set_revoked(false);
+        assert_eq!(key.get_flags(), ZSK_FLAGS);
+
+        assert_eq!(DnssecKey::generate(DnssecAlgorithm::ECDSAP384SHA384, ZSK_FLAGS).unwrap().get_dnskey().get_public_key().len(), 96);
+        assert_eq!(DnssecKey::generate(DnssecAlgorithm::ED25519, ZSK_FLAGS).unwrap().get_dnskey().get_public_key().len(), 32);
+        assert_eq!(DnssecKey::generate(DnssecAlgorithm::DSA, ZSK_FLAGS).unwrap_err(), KeyError::UnsupportedAlgorithm(DnssecAlgorithm::DSA));
+    }
+
+    #[test]
+    fn generate_every_signing_algorithm() {
+        for algorithm in DnssecAlgorithm::SIGNING.iter() {
+            let key = DnssecKey::generate(*algorithm, ZSK_FLAGS).unwrap();
+            assert_eq!(key.get_algorithm(), *algorithm);
+            assert_signs(&key);
+        }
+
+        // RFC 3110 public key: exponent length, exponent 65537 and a 2048 bit modulus
+        let rsa = DnssecKey::generate(DnssecAlgorithm::RSASHA512, ZSK_FLAGS).unwrap();
+        assert_eq!(rsa.get_dnskey().get_public_key()[..4], [3, 1, 0, 1]);
+        assert_eq!(rsa.get_dnskey().get_public_key().len(), 4 + 256);
+        let (_, read_key) = DnssecKey::from_bind(&rsa.to_bind_key(&zone()), &rsa.to_bind_private()).unwrap();
+        assert_eq!(read_key.get_dnskey(), rsa.get_dnskey());
+    }
+
+    #[test]
+    fn bind_round_trip() {
+        let algorithms = [DnssecAlgorithm::ECDSAP256SHA256, DnssecAlgorithm::ECDSAP384SHA384, DnssecAlgorithm::ED25519];
+        for algorithm in algorithms.iter() {
+            let mut key = DnssecKey::generate(*algorithm, KSK_FLAGS).unwrap();
+            key.set_created(1_704_067_200);
+            let key_file = key.to_bind_key(&zone());
+            let private_file = key.to_bind_private();
+            assert!(key_file.contains("; This is a key-signing key"));
+            assert!(key_file.contains("example.com. IN DNSKEY 257 3"));
+            assert!(private_file.starts_with("Private-key-format: v1.3\n"));
+            assert!(private_file.contains("Created: 20240101000000"));
+
+            let (zone, read_key) = DnssecKey::from_bind(&key_file, &private_file).unwrap();
+            assert_eq!(zone, self::zone());
+            assert_eq!(read_key.get_dnskey(), key.get_dnskey());
+            assert_eq!(read_key.get_created(), 1_704_067_200);
+            assert_eq!(read_key.to_bind_private(), private_file);
+            assert_signs(&read_key);
+        }
+    }
+
+    #[test]
+    fn read_rsa_key() {
+        let private_file = RSA_PRIVATE;
+        // Public key of RFC 3110: exponent length, exponent 65537 and modulus
+        let modulus = parse_base64(&[private_file.lines().nth(2).unwrap().split(": ").nth(1).unwrap()]).unwrap();
+        let mut public_key = vec![3, 1, 0, 1];
+        public_key.extend(modulus);
+        let key_file = format!(
+            "; This is a key-signing key\nexample.com. 3600 IN DNSKEY 257 3 8 {}\n",
+            format_base64(&public_key)
+        );
+
+        let (zone, key) = DnssecKey::from_bind(&key_file, &private_file).unwrap();
+        assert_eq!(zone, self::zone());
+        assert_eq!(key.get_dnskey().get_public_key(), public_key);
+        assert_eq!(key.get_created(), 1_704_067_200);
+        assert_signs(&key);
+        assert_eq!(key.to_bind_private().lines().count(), 11);
+    }
+
+    #[test]
+    fn mismatched_files() {
+        let key = DnssecKey::generate(DnssecAlgorithm::ECDSAP256SHA256, ZSK_FLAGS).unwrap();
+        let other = DnssecKey::generate(DnssecAlgorithm::ECDSAP256SHA256, ZSK_FLAGS).unwrap();
+        assert_eq!(
+            DnssecKey::from_bind(&key.to_bind_key(&zone()), &other.to_bind_private()).unwrap_err(),
+            KeyError::InvalidKey("the private key does not match its DNSKEY")
+        );
+
+        let ed25519 = DnssecKey::generate(DnssecAlgorithm::ED25519, ZSK_FLAGS).unwrap();
+        assert!(DnssecKey::from_bind(&key.to_bind_key(&zone()), &ed25519.to_bind_private()).is_err());
+        assert!(DnssecKey::from_bind("; no record\n", &key.to_bind_private()).is_err());
+        assert!(DnssecKey::from_bind(&key.to_bind_key(&zone()), "Algorithm: 13\n").is_err());
+    }
+
+    #[test]
+    fn bind_files() {
+        let directory = std::env::temp_dir().join(format!("dnssec_key_test_{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let key = DnssecKey::generate(DnssecAlgorithm::ED25519, KSK_FLAGS).unwrap();
+
+        let prefix = key.write_bind_files(&directory, &zone()).unwrap();
+        let file_name = format!("Kexample.com.+015+{:05}", key.key_tag());
+        assert_eq!(prefix, directory.join(&file_name));
+        assert_eq!(key.bind_file_name(&zone()), file_name);
+
+        let (zone, read_key) = DnssecKey::read_bind_files(directory.join(format!("{}.private", file_name))).unwrap();
+        assert_eq!(zone, self::zone());
+        assert_eq!(read_key.get_dnskey(), key.get_dnskey());
+        assert!(DnssecKey::read_bind_files(directory.join("Kmissing")).is_err());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let metadata = fs::metadata(directory.join(format!("{}.private", file_name))).unwrap();
+            assert_eq!(metadata.permissions().mode() & 0o777, 0o600);
+        }
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -39,6 +39,7 @@ mod dnssec_message_processing_tests {
     use crate::message::rdata::{rrsig_rdata, Rdata};
     use crate::message::resource_record::{ResourceRecord, ToBytes};
     use crate::message::rrtype::Rrtype;
+    use crate::dnssec::dnssec_algorithm::DnssecAlgorithm;
 
 
     #[test]
@@ -84,7 +85,7 @@ mod dnssec_message_processing_tests {
         // add answer signature
         let mut rrsig_rdata = rrsig_rdata::RRSIGRdata::new();
         rrsig_rdata.set_type_covered(Rrtype::A);
-        rrsig_rdata.set_algorithm(13); // ECDSA Curve P-256 with SHA-256
+        rrsig_rdata.set_algorithm(DnssecAlgorithm::ECDSAP256SHA256); // ECDSA Curve P-256 with SHA-256
         rrsig_rdata.set_labels(2);
         rrsig_rdata.set_original_ttl(3600);
         rrsig_rdata.set_signature_expiration(1731875962);
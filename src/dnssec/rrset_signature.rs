@@ -1,72 +1,542 @@
-use sha2::{Sha256, Digest};
-use sha1::Sha1;
-use data_encoding::BASE64;
+use ring::signature;
+use crate::dnssec::dnssec_algorithm::DnssecAlgorithm;
+use crate::domain_name::DomainName;
 use crate::message::rdata::Rdata;
 use crate::message::rdata::dnskey_rdata::DnskeyRdata;
 use crate::message::rdata::rrsig_rdata::RRSIGRdata;
 use crate::message::resource_record::{ResourceRecord, ToBytes};
 use crate::client::client_error::ClientError;
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
 
-pub fn verify_rrsig(rrsig: &RRSIGRdata, dnskey: &DnskeyRdata, rrset: &[ResourceRecord]) -> Result<bool, ClientError> {
-    let mut rrsig_data = Vec::new();
-    rrsig_data.extend_from_slice(&u16::from(rrsig.get_type_covered()).to_be_bytes());
-    rrsig_data.push(rrsig.get_algorithm());
-    rrsig_data.push(rrsig.get_labels());
-    rrsig_data.extend_from_slice(&rrsig.get_original_ttl().to_be_bytes());
-    rrsig_data.extend_from_slice(&rrsig.get_signature_expiration().to_be_bytes());
-    rrsig_data.extend_from_slice(&rrsig.get_signature_inception().to_be_bytes());
-    rrsig_data.extend_from_slice(&rrsig.get_key_tag().to_be_bytes());
-    rrsig_data.extend_from_slice(&rrsig.get_signer_name().to_bytes());//Try?
-
-    let mut rrset_sorted = rrset.to_vec();
-    rrset_sorted.sort_by(|a, b| a.get_name().cmp(&b.get_name()));
-
-    for rr in rrset_sorted.iter() {
-        rrsig_data.extend_from_slice(&rr.get_name().to_bytes()); //Try?
-        rrsig_data.extend_from_slice(&rr.get_ttl().to_be_bytes());
-        rrsig_data.extend_from_slice(&(rr.get_rdata().to_bytes().len() as u16).to_be_bytes());
-        rrsig_data.extend_from_slice(&rr.get_rdata().to_bytes());//Try?
-    }
-
-    let signature = rrsig.get_signature().clone();
-    let hashed = Sha256::digest(&rrsig_data);
-
-    match dnskey.algorithm {
-        3 | 5 => {
-            // (DSA/RSA)/SHA1
-            let mut sha1 = Sha1::new();
-            sha1.update(&rrsig_data);
-            let digest = sha1.finalize();
-            let hex_digest = hex::encode(digest);
-            Ok(hex_digest == BASE64.encode(&signature))
-        },
-        8 => {
-            // RSA/SHA256
-            Ok(BASE64.encode(&hashed) == BASE64.encode(&signature))
-        },
-        _ => Err(ClientError::NotImplemented("Unknown DNSKEY algorithm")),
+/// Flag of the DNSKEY RDATA that marks the key as a zone key.
+///
+/// [RFC 4034](https://datatracker.ietf.org/doc/html/rfc4034#section-2.1.1)
+const ZONE_KEY_FLAG: u16 = 0x0100;
+
+/// Value of the protocol field of every DNSKEY RDATA.
+///
+/// [RFC 4034](https://datatracker.ietf.org/doc/html/rfc4034#section-2.1.2)
+const DNSKEY_PROTOCOL: u8 = 3;
+
+/// Result of verifying the signature of an RRset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RrsigVerification {
+    /// The signature was made over the RRset with the key.
+    Secure,
+    /// The signature does not validate the RRset.
+    Bogus(BogusReason),
+    /// The algorithm of the signature is not implemented.
+    UnsupportedAlgorithm(DnssecAlgorithm),
+}
+
+/// Reason why a signature does not validate an RRset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BogusReason {
+    /// The signature expiration time is in the past.
+    SignatureExpired,
+    /// The signature inception time is in the future.
+    SignatureNotYetValid,
+    /// The signature is not the result of signing the RRset with the key.
+    InvalidSignature,
+    /// The key is not a zone key of the signature algorithm.
+    KeyMismatch,
+    /// The public key is malformed for its algorithm.
+    InvalidKey,
+    /// The RRset is empty or does not match the fields of the signature.
+    RrsetMismatch,
+}
+
+impl fmt::Display for BogusReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self {
+            BogusReason::SignatureExpired => "signature expired",
+            BogusReason::SignatureNotYetValid => "signature not yet valid",
+            BogusReason::InvalidSignature => "invalid signature",
+            BogusReason::KeyMismatch => "key does not match the signature",
+            BogusReason::InvalidKey => "invalid public key",
+            BogusReason::RrsetMismatch => "RRset does not match the signature",
+        };
+        write!(f, "{}", reason)
+    }
+}
+
+/// Verifies the signature of an RRset with a DNSKEY at the current time.
+///
+/// See `verify_rrsig_at`.
+pub fn verify_rrsig(rrsig: &RRSIGRdata, dnskey: &DnskeyRdata, rrset: &[ResourceRecord]) -> RrsigVerification {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+    verify_rrsig_at(rrsig, dnskey, rrset, now)
+}
+
+/// Verifies the signature of an RRset with a DNSKEY at the time `now`,
+/// in seconds since the epoch.
+///
+/// [RFC 4035](https://datatracker.ietf.org/doc/html/rfc4035#section-5.3)
+///
+/// The signature is checked over the canonical form of the RRset, using
+/// the original TTL of the signature, after checking that `now` is inside
+/// the validity period of the signature. The supported algorithms are
+/// RSA/SHA-256 (8), RSA/SHA-512 (10), ECDSA P-256 (13), ECDSA P-384 (14)
+/// and Ed25519 (15).
+pub fn verify_rrsig_at(
+    rrsig: &RRSIGRdata,
+    dnskey: &DnskeyRdata,
+    rrset: &[ResourceRecord],
+    now: u64,
+) -> RrsigVerification {
+    let algorithm = rrsig.get_algorithm();
+    if !algorithm.can_validate() {
+        return RrsigVerification::UnsupportedAlgorithm(algorithm);
+    }
+    if dnskey.get_algorithm() != algorithm
+        || dnskey.get_protocol() != DNSKEY_PROTOCOL
+        || dnskey.get_flags() & ZONE_KEY_FLAG == 0
+    {
+        return RrsigVerification::Bogus(BogusReason::KeyMismatch);
+    }
+
+    // Times are compared with serial number arithmetic, RFC 4034 section 3.1.5
+    let now = now as u32;
+    if serial_lt(now, rrsig.get_signature_inception()) {
+        return RrsigVerification::Bogus(BogusReason::SignatureNotYetValid);
+    }
+    if serial_lt(rrsig.get_signature_expiration(), now) {
+        return RrsigVerification::Bogus(BogusReason::SignatureExpired);
+    }
+
+    let data = match signature_data(rrsig, rrset) {
+        Ok(data) => data,
+        Err(reason) => return RrsigVerification::Bogus(reason),
+    };
+    verify_signature(algorithm, &dnskey.get_public_key(), &data, &rrsig.get_signature())
+}
+
+/// Builds the data covered by a signature.
+///
+/// [RFC 4034](https://datatracker.ietf.org/doc/html/rfc4034#section-3.1.8.1)
+///
+/// ```text
+/// signature = sign(RRSIG_RDATA | RR(1) | RR(2)... )
+/// ```
+///
+/// `RRSIG_RDATA` is the RDATA of the signature without the signature field,
+/// with the signer name in canonical form. Each `RR(i)` is in canonical
+/// form (RFC 4034 section 6.2) with the original TTL of the signature, and
+/// the RRs are sorted in canonical order without duplicates. If the signature
+/// was made for a wildcard, the owner name is rebuilt from the labels field.
+pub fn signature_data(rrsig: &RRSIGRdata, rrset: &[ResourceRecord]) -> Result<Vec<u8>, BogusReason> {
+    let first = rrset.first().ok_or(BogusReason::RrsetMismatch)?;
+    let owner = first.get_name().to_canonical();
+    let rtype = first.get_rtype();
+    let rclass = first.get_rclass();
+    let same_rrset = rrset.iter().all(|rr| {
+        rr.get_name() == owner && rr.get_rtype() == rtype && rr.get_rclass() == rclass
+    });
+    if !same_rrset || rtype != rrsig.get_type_covered() {
+        return Err(BogusReason::RrsetMismatch);
+    }
+
+    let signer_name = rrsig.get_signer_name().to_canonical();
+    if !owner.is_subdomain_of(&signer_name) {
+        return Err(BogusReason::RrsetMismatch);
+    }
+
+    let owner_labels = owner.get_labels();
+    let is_wildcard = owner_labels.first().is_some_and(|label| label.as_slice() == b"*");
+    let label_count = owner_labels.len() - is_wildcard as usize;
+    let rrsig_labels = rrsig.get_labels() as usize;
+    let owner = if rrsig_labels > label_count {
+        return Err(BogusReason::RrsetMismatch);
+    } else if rrsig_labels < label_count {
+        let mut labels = vec![b"*".to_vec()];
+        labels.extend_from_slice(&owner_labels[owner_labels.len() - rrsig_labels..]);
+        DomainName::from_labels(labels).map_err(|_| BogusReason::RrsetMismatch)?
+    } else {
+        owner
+    };
+
+    let mut data = Vec::new();
+    data.extend_from_slice(&u16::from(rrsig.get_type_covered()).to_be_bytes());
+    data.push(u8::from(rrsig.get_algorithm()));
+    data.push(rrsig.get_labels());
+    data.extend_from_slice(&rrsig.get_original_ttl().to_be_bytes());
+    data.extend_from_slice(&rrsig.get_signature_expiration().to_be_bytes());
+    data.extend_from_slice(&rrsig.get_signature_inception().to_be_bytes());
+    data.extend_from_slice(&rrsig.get_key_tag().to_be_bytes());
+    data.extend_from_slice(&signer_name.to_bytes());
+
+    let mut rdatas: Vec<Vec<u8>> = rrset.iter().map(|rr| canonical_rdata(&rr.get_rdata())).collect();
+    rdatas.sort();
+    rdatas.dedup();
+
+    let owner_bytes = owner.to_bytes();
+    for rdata in rdatas {
+        data.extend_from_slice(&owner_bytes);
+        data.extend_from_slice(&u16::from(rtype).to_be_bytes());
+        data.extend_from_slice(&u16::from(rclass).to_be_bytes());
+        data.extend_from_slice(&rrsig.get_original_ttl().to_be_bytes());
+        data.extend_from_slice(&(rdata.len() as u16).to_be_bytes());
+        data.extend_from_slice(&rdata);
+    }
+    Ok(data)
+}
+
+/// Returns the RDATA in canonical form.
+///
+/// [RFC 4034](https://datatracker.ietf.org/doc/html/rfc4034#section-6.2)
+///
+/// The domain names embedded in the RDATA of the types listed in RFC 4034
+/// are written in lowercase. NSEC is not in the list, as corrected by
+/// RFC 6840 section 5.1.
+pub fn canonical_rdata(rdata: &Rdata) -> Vec<u8> {
+    let canonical = match rdata {
+        Rdata::NS(ns) => {
+            let mut ns = ns.clone();
+            ns.set_nsdname(ns.get_nsdname().to_canonical());
+            Rdata::NS(ns)
+        }
+        Rdata::CNAME(cname) => {
+            let mut cname = cname.clone();
+            cname.set_cname(cname.get_cname().to_canonical());
+            Rdata::CNAME(cname)
+        }
+        Rdata::PTR(ptr) => {
+            let mut ptr = ptr.clone();
+            ptr.set_ptrdname(ptr.get_ptrdname().to_canonical());
+            Rdata::PTR(ptr)
+        }
+        Rdata::MX(mx) => {
+            let mut mx = mx.clone();
+            mx.set_exchange(mx.get_exchange().to_canonical());
+            Rdata::MX(mx)
+        }
+        Rdata::SOA(soa) => {
+            let mut soa = soa.clone();
+            soa.set_mname(soa.get_mname().to_canonical());
+            soa.set_rname(soa.get_rname().to_canonical());
+            Rdata::SOA(soa)
+        }
+        Rdata::SRV(srv) => {
+            let mut srv = srv.clone();
+            srv.set_target(srv.get_target().to_canonical());
+            Rdata::SRV(srv)
+        }
+        Rdata::RRSIG(rrsig) => {
+            let mut rrsig = rrsig.clone();
+            rrsig.set_signer_name(rrsig.get_signer_name().to_canonical());
+            Rdata::RRSIG(rrsig)
+        }
+        other => other.clone(),
+    };
+    canonical.to_bytes()
+}
+
+/// Checks the signature of `data` with a public key in the DNSKEY format
+/// of the algorithm.
+fn verify_signature(algorithm: DnssecAlgorithm, public_key: &[u8], data: &[u8], signature: &[u8]) -> RrsigVerification {
+    let result = match algorithm {
+        DnssecAlgorithm::RSASHA256 | DnssecAlgorithm::RSASHA512 => {
+            // RFC 3110: exponent length, exponent and modulus
+            let (e, n) = match parse_rsa_public_key(public_key) {
+                Some(components) => components,
+                None => return RrsigVerification::Bogus(BogusReason::InvalidKey),
+            };
+            let parameters = if algorithm == DnssecAlgorithm::RSASHA256 {
+                &signature::RSA_PKCS1_1024_8192_SHA256_FOR_LEGACY_USE_ONLY
+            } else {
+                &signature::RSA_PKCS1_1024_8192_SHA512_FOR_LEGACY_USE_ONLY
+            };
+            signature::RsaPublicKeyComponents { n, e }.verify(parameters, data, signature)
+        }
+        DnssecAlgorithm::ECDSAP256SHA256 | DnssecAlgorithm::ECDSAP384SHA384 => {
+            // RFC 6605: the key is the point Q as x | y
+            let (parameters, key_length) = if algorithm == DnssecAlgorithm::ECDSAP256SHA256 {
+                (&signature::ECDSA_P256_SHA256_FIXED, 64)
+            } else {
+                (&signature::ECDSA_P384_SHA384_FIXED, 96)
+            };
+            if public_key.len() != key_length {
+                return RrsigVerification::Bogus(BogusReason::InvalidKey);
+            }
+            let mut point = vec![0x04];
+            point.extend_from_slice(public_key);
+            signature::UnparsedPublicKey::new(parameters, point).verify(data, signature)
+        }
+        DnssecAlgorithm::ED25519 => {
+            // RFC 8080: the key is the 32 bytes of the public key
+            if public_key.len() != 32 {
+                return RrsigVerification::Bogus(BogusReason::InvalidKey);
+            }
+            signature::UnparsedPublicKey::new(&signature::ED25519, public_key).verify(data, signature)
+        }
+        _ => return RrsigVerification::UnsupportedAlgorithm(algorithm),
+    };
+
+    match result {
+        Ok(()) => RrsigVerification::Secure,
+        Err(_) => RrsigVerification::Bogus(BogusReason::InvalidSignature),
     }
 }
 
+/// Splits an RSA public key in the format of RFC 3110 into its exponent
+/// and modulus, without leading zeros.
+fn parse_rsa_public_key(public_key: &[u8]) -> Option<(&[u8], &[u8])> {
+    let (exponent_length, rest) = match public_key.split_first()? {
+        (0, rest) if rest.len() >= 2 => (u16::from_be_bytes([rest[0], rest[1]]) as usize, &rest[2..]),
+        (0, _) => return None,
+        (length, rest) => (*length as usize, rest),
+    };
+    if exponent_length == 0 || rest.len() <= exponent_length {
+        return None;
+    }
+    let (exponent, modulus) = rest.split_at(exponent_length);
+    Some((trim_leading_zeros(exponent), trim_leading_zeros(modulus)))
+}
+
+/// Returns the big-endian integer without its leading zero bytes.
+fn trim_leading_zeros(bytes: &[u8]) -> &[u8] {
+    let start = bytes.iter().position(|byte| *byte != 0).unwrap_or(bytes.len());
+    &bytes[start..]
+}
+
+/// Returns `true` if `a` is before `b` in serial number arithmetic.
+///
+/// [RFC 1982](https://datatracker.ietf.org/doc/html/rfc1982#section-3.2)
+fn serial_lt(a: u32, b: u32) -> bool {
+    a != b && (b.wrapping_sub(a) as i32) > 0
+}
+
+/// Returns whether the DS RR refers to the DNSKEY: the owner name, key tag,
+/// algorithm and digest of the DS RR must match those computed from the
+/// key.
+///
+/// [RFC 4034](https://datatracker.ietf.org/doc/html/rfc4034#section-5.2)
+///
+///    The DS RR and DNSKEY RR have the same owner name, [...]  The key
+///    tag, algorithm, and digest fields of the DS RR identify the DNSKEY
+///    RR.
 pub fn verify_ds(ds_record: &ResourceRecord, dnskey: &DnskeyRdata) -> Result<bool, ClientError> {
     if let Rdata::DS(ds_rdata) = &ds_record.get_rdata() {
-        let dnskey_bytes = dnskey.to_bytes();  //Try?
-        let hashed_key = match ds_rdata.algorithm {
-            1 => {
-                let mut hasher = Sha1::new();
-                hasher.update(&dnskey_bytes);
-                let result = hasher.finalize();
-                hex::encode(result)
-            },
-            2 => {
-                let hashed = Sha256::digest(&dnskey_bytes);
-                BASE64.encode(&hashed)
-            },
-            _ => return Err(ClientError::NotImplemented("Unknown DS algorithm")),
-        };
+        let expected = dnskey
+            .to_ds(&ds_record.get_name(), ds_rdata.get_digest_type())
+            .map_err(|_| ClientError::NotImplemented("Unknown DS digest type"))?;
 
-        Ok(ds_rdata.digest == hashed_key.as_bytes())
+        Ok(*ds_rdata == expected)
     } else {
         Err(ClientError::FormatError("Provided record is not a DS record"))
     }
 }
+
+#[cfg(test)]
+mod rrset_signature_test {
+    use super::*;
+    use data_encoding::BASE64;
+    use crate::message::rclass::Rclass;
+    use crate::message::rdata::a_rdata::ARdata;
+    use crate::message::rdata::ds_rdata::DsRdata;
+    use crate::message::rdata::mx_rdata::MxRdata;
+    use crate::message::rrtype::Rrtype;
+    use std::net::IpAddr;
+    use crate::dnssec::dnssec_algorithm::DigestType;
+
+    fn rr(name: &str, ttl: u32, rdata: Rdata) -> ResourceRecord {
+        let mut rr = ResourceRecord::new(rdata);
+        rr.set_name(DomainName::new_from_str(name));
+        rr.set_rclass(Rclass::IN);
+        rr.set_ttl(ttl);
+        rr
+    }
+
+    fn a_rr(name: &str, ttl: u32, address: &str) -> ResourceRecord {
+        let address: IpAddr = address.parse().unwrap();
+        rr(name, ttl, Rdata::A(ARdata::new_from_addr(address)))
+    }
+
+    fn mx_rr(name: &str, ttl: u32, preference: u16, exchange: &str) -> ResourceRecord {
+        let mut mx_rdata = MxRdata::new();
+        mx_rdata.set_preference(preference);
+        mx_rdata.set_exchange(DomainName::new_from_str(exchange));
+        rr(name, ttl, Rdata::MX(mx_rdata))
+    }
+
+    fn rrsig(type_covered: Rrtype, algorithm: DnssecAlgorithm, labels: u8, expiration: u32, inception: u32,
+             key_tag: u16, signer_name: &str, signature: &str) -> RRSIGRdata {
+        let mut rrsig = RRSIGRdata::new();
+        rrsig.set_type_covered(type_covered);
+        rrsig.set_algorithm(algorithm);
+        rrsig.set_labels(labels);
+        rrsig.set_original_ttl(3600);
+        rrsig.set_signature_expiration(expiration);
+        rrsig.set_signature_inception(inception);
+        rrsig.set_key_tag(key_tag);
+        rrsig.set_signer_name(DomainName::new_from_str(signer_name));
+        rrsig.set_signature(BASE64.decode(signature.as_bytes()).unwrap());
+        rrsig
+    }
+
+    fn dnskey(algorithm: DnssecAlgorithm, public_key: &str) -> DnskeyRdata {
+        DnskeyRdata::new(257, 3, algorithm, BASE64.decode(public_key.as_bytes()).unwrap())
+    }
+
+    // RFC 8080 section 6.1
+    fn ed25519_example() -> (RRSIGRdata, DnskeyRdata, Vec<ResourceRecord>) {
+        let rrsig = rrsig(Rrtype::MX, DnssecAlgorithm::ED25519, 2, 1440021600, 1438207200, 3613, "example.com",
+            "oL9krJun7xfBOIWcGHi7mag5/hdZrKWw15jPGrHpjQeRAvTdszaPD+QLs3fx8A4M3e23mRZ9VrbpMngwcrqNAg==");
+        let dnskey = dnskey(DnssecAlgorithm::ED25519, "l02Woi0iS8Aa25FQkUd9RMzZHJpBoRQwAQEX1SxZJA4=");
+        let rrset = vec![mx_rr("example.com", 3600, 10, "mail.example.com")];
+        (rrsig, dnskey, rrset)
+    }
+
+    #[test]
+    fn verify_ed25519() {
+        let (rrsig, dnskey, rrset) = ed25519_example();
+
+        assert_eq!(verify_rrsig_at(&rrsig, &dnskey, &rrset, 1439000000), RrsigVerification::Secure);
+    }
+
+    #[test]
+    fn verify_ecdsa_p256() {
+        // RFC 6605 section 6.1
+        let rrsig = rrsig(Rrtype::A, DnssecAlgorithm::ECDSAP256SHA256, 3, 1284026679, 1281607479, 55648, "example.net",
+            "qx6wLYqmh+l9oCKTN6qIc+bw6ya+KJ8oMz0YP107epXAyGmt+3SNruPFKG7tZoLBLlUzGGus7ZwmwWep666VCw==");
+        let dnskey = dnskey(DnssecAlgorithm::ECDSAP256SHA256, "GojIhhXUN/u4v54ZQqGSnyhWJwaubCvTmeexv7bR6edbkrSqQpF64cYbcB7wNcP+e+MAnLr+Wi9xMWyQLc8NAA==");
+        let rrset = vec![a_rr("www.example.net", 3600, "192.0.2.1")];
+
+        assert_eq!(verify_rrsig_at(&rrsig, &dnskey, &rrset, 1282000000), RrsigVerification::Secure);
+    }
+
+    #[test]
+    fn verify_ecdsa_p384() {
+        // RFC 6605 section 6.2
+        let rrsig = rrsig(Rrtype::A, DnssecAlgorithm::ECDSAP384SHA384, 3, 1284027625, 1281608425, 10771, "example.net",
+            "/L5hDKIvGDyI1fcARX3z65qrmPsVz73QD1Mr5CEqOiLP95hxQouuroGCeZOvzFaxsT8Glr74hbavRKayJNuydCuzWTSSPdz7wnqXL5bdcJzusdnI0RSMROxxwGipWcJm");
+        let dnskey = dnskey(DnssecAlgorithm::ECDSAP384SHA384, "xKYaNhWdGOfJ+nPrL8/arkwf2EY3MDJ+SErKivBVSum1w/egsXvSADtNJhyem5RCOpgQ6K8X1DRSEkrbYQ+OB+v8/uX45NBwY8rp65F6Glur8I/mlVNgF6W/qTI37m40");
+        let rrset = vec![a_rr("www.example.net", 3600, "192.0.2.1")];
+
+        assert_eq!(verify_rrsig_at(&rrsig, &dnskey, &rrset, 1282000000), RrsigVerification::Secure);
+    }
+
+    #[test]
+    fn verify_rsa_sha256_canonical_form() {
+        // Names in mixed case, TTLs decremented by a cache and records out of order
+        let rrsig = rrsig(Rrtype::MX, DnssecAlgorithm::RSASHA256, 2, 1893456000, 1577836800, 12345, "Example.COM",
+            "Ab89TC0WQf7R+buyAf7jvfw30VxPt1NVCYmHmikHNr5zGmrqzLWYu8QLnPlyOFIW2aArYhueENNPw/9zl8XmapmMAoJkYat+3ZP+CeMUUKOWmsiIZZX5Duw8+IXGieunB5h0VyZ3jleFuKpr667mAQnKHIc57cQLHSgojYJ7CGU=");
+        let dnskey = dnskey(DnssecAlgorithm::RSASHA256, "AwEAAZWL7bzmjfxNvd53WC/AU9eySmDuv7AIAB1M2k3f1NBOk1yJPpi7Mgr/ChaW1exAn+qotKlov7meV8f9BFimW42dJyiviKp+YzlH/JEj6fPXMU125PDaRQkvGvtJCfxNSK/J4r5NAyKdjJsJmtLbmrO8vHb7SOLlQe6y0LDfpWlb");
+        let rrset = vec![
+            mx_rr("Example.COM", 1200, 20, "MAIL2.example.com"),
+            mx_rr("Example.COM", 1200, 10, "mail1.Example.com"),
+        ];
+
+        assert_eq!(verify_rrsig_at(&rrsig, &dnskey, &rrset, 1700000000), RrsigVerification::Secure);
+    }
+
+    #[test]
+    fn verify_rsa_sha512_wildcard() {
+        // Signed as *.example.com, received as an answer for a.example.com
+        let rrsig = rrsig(Rrtype::A, DnssecAlgorithm::RSASHA512, 2, 1893456000, 1577836800, 12345, "example.com",
+            "F1ZYga/mBrs6S9v+jRr591iNXLBUdp3hZn9RdzidrVWIZ8c9zATMPIvYLa3esilMTMGmWXh7I6VGOLTBpBwegH02IEdLP/bwff0Opw+1JyrWJk1Gth0tBSL1XJmgaos7sIk9viqWdUqtCE/aE2gaW3k723ZeLvYtll66dxaSmFc=");
+        let dnskey = dnskey(DnssecAlgorithm::RSASHA512, "AwEAAbm+dsD1OkrxNWU0/dL9tG7ypOApQLVSa+eWb182jKG20UBiNwx3rS47jRQ6goUPd5u6i7ckr7ODxGHzXbOEKmPLV/6ZlJGBAReT+7G90Sh+lqh2YoccoxJ8nEsJqeOWhS/4tefl0tXiTyvBtY3mckY0KP5YhqU8i/j8icQ8ieKp");
+        let rrset = vec![
+            a_rr("A.Example.com", 3600, "192.0.2.2"),
+            a_rr("A.Example.com", 3600, "192.0.2.1"),
+            a_rr("A.Example.com", 3600, "192.0.2.1"),
+        ];
+
+        assert_eq!(verify_rrsig_at(&rrsig, &dnskey, &rrset, 1700000000), RrsigVerification::Secure);
+    }
+
+    #[test]
+    fn verify_modified_rrset_is_bogus() {
+        let (rrsig, dnskey, _) = ed25519_example();
+        let rrset = vec![mx_rr("example.com", 3600, 20, "mail.example.com")];
+
+        assert_eq!(
+            verify_rrsig_at(&rrsig, &dnskey, &rrset, 1439000000),
+            RrsigVerification::Bogus(BogusReason::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn verify_validity_period() {
+        let (rrsig, dnskey, rrset) = ed25519_example();
+
+        assert_eq!(
+            verify_rrsig_at(&rrsig, &dnskey, &rrset, 1438207199),
+            RrsigVerification::Bogus(BogusReason::SignatureNotYetValid)
+        );
+        assert_eq!(
+            verify_rrsig_at(&rrsig, &dnskey, &rrset, 1440021601),
+            RrsigVerification::Bogus(BogusReason::SignatureExpired)
+        );
+        assert_eq!(verify_rrsig_at(&rrsig, &dnskey, &rrset, 1440021600), RrsigVerification::Secure);
+    }
+
+    #[test]
+    fn verify_key_and_rrset_mismatch() {
+        let (rrsig, dnskey, rrset) = ed25519_example();
+
+        let not_zone_key = DnskeyRdata::new(0, 3, DnssecAlgorithm::ED25519, dnskey.get_public_key());
+        assert_eq!(
+            verify_rrsig_at(&rrsig, &not_zone_key, &rrset, 1439000000),
+            RrsigVerification::Bogus(BogusReason::KeyMismatch)
+        );
+
+        let short_key = DnskeyRdata::new(257, 3, DnssecAlgorithm::ED25519, vec![1, 2, 3]);
+        assert_eq!(
+            verify_rrsig_at(&rrsig, &short_key, &rrset, 1439000000),
+            RrsigVerification::Bogus(BogusReason::InvalidKey)
+        );
+
+        let other_owner = vec![mx_rr("example.org", 3600, 10, "mail.example.com")];
+        assert_eq!(
+            verify_rrsig_at(&rrsig, &dnskey, &other_owner, 1439000000),
+            RrsigVerification::Bogus(BogusReason::RrsetMismatch)
+        );
+        assert_eq!(
+            verify_rrsig_at(&rrsig, &dnskey, &[], 1439000000),
+            RrsigVerification::Bogus(BogusReason::RrsetMismatch)
+        );
+    }
+
+    #[test]
+    fn verify_unsupported_algorithm() {
+        let (mut rrsig, dnskey, rrset) = ed25519_example();
+        rrsig.set_algorithm(DnssecAlgorithm::RSASHA1);
+
+        assert_eq!(
+            verify_rrsig_at(&rrsig, &dnskey, &rrset, 1439000000),
+            RrsigVerification::UnsupportedAlgorithm(DnssecAlgorithm::RSASHA1)
+        );
+    }
+
+    #[test]
+    fn verify_ds_of_ecdsa_p256_key() {
+        // RFC 6605 section 6.1
+        let dnskey = dnskey(DnssecAlgorithm::ECDSAP256SHA256, "GojIhhXUN/u4v54ZQqGSnyhWJwaubCvTmeexv7bR6edbkrSqQpF64cYbcB7wNcP+e+MAnLr+Wi9xMWyQLc8NAA==");
+        let digest = hex::decode("b4c8c1fe2e7477127b27115656ad6256f424625bf5c1e2770ce6d6e37df61d17").unwrap();
+        let ds = rr("example.net", 3600, Rdata::DS(DsRdata::new(55648, DnssecAlgorithm::ECDSAP256SHA256, DigestType::SHA256, digest)));
+
+        assert_eq!(verify_ds(&ds, &dnskey).unwrap(), true);
+
+        let mut other_owner = ds.clone();
+        other_owner.set_name(DomainName::new_from_str("example.org"));
+        assert_eq!(verify_ds(&other_owner, &dnskey).unwrap(), false);
+
+        let Rdata::DS(mut unknown_digest) = ds.get_rdata() else { unreachable!() };
+        unknown_digest.set_digest_type(DigestType::from(3));
+        assert!(verify_ds(&rr("example.net", 3600, Rdata::DS(unknown_digest)), &dnskey).is_err());
+        assert!(verify_ds(&a_rr("example.net", 3600, "192.0.2.1"), &dnskey).is_err());
+    }
+
+    #[test]
+    fn serial_arithmetic() {
+        assert!(serial_lt(1, 2));
+        assert!(!serial_lt(2, 1));
+        assert!(!serial_lt(2, 2));
+        assert!(serial_lt(u32::MAX, 1));
+    }
+}
@@ -0,0 +1,696 @@
+//! Signing of zones: DNSKEY RRsets, RRSIG RRs and NSEC or NSEC3 chains.
+//!
+//! [RFC 4035](https://datatracker.ietf.org/doc/html/rfc4035#section-2)
+//!
+//! ```text
+//! 2.  Zone Signing
+//!
+//!    DNSSEC introduces the concept of signed zones.  A signed zone
+//!    includes DNS Public Key (DNSKEY), Resource Record Signature (RRSIG),
+//!    Next Secure (NSEC), and (optionally) Delegation Signer (DS) records
+//!    according to the rules specified in Sections 2.1, 2.2, 2.3, and 2.4,
+//!    respectively.
+//! ```
+
+use crate::dnssec::denial::nsec3_hash;
+use crate::dnssec::dnssec_algorithm::Nsec3HashAlgorithm;
+use crate::dnssec::dnssec_key::{DnssecKey, KeyError};
+use crate::dnssec::rrset_signature::signature_data;
+use crate::domain_name::DomainName;
+use crate::message::rdata::nsec3_rdata::Nsec3Rdata;
+use crate::message::rdata::nsec3param_rdata::Nsec3ParamRdata;
+use crate::message::rdata::nsec_rdata::NsecRdata;
+use crate::message::rdata::presentation::format_base32hex;
+use crate::message::rdata::rrsig_rdata::RRSIGRdata;
+use crate::message::rdata::Rdata;
+use crate::message::resource_record::{ResourceRecord, ToBytes};
+use crate::message::rrset::RRset;
+use crate::message::rrtype::Rrtype;
+use rand::Rng;
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Time before the signing time at which signatures start to be valid, to
+/// tolerate clocks that are behind.
+const DEFAULT_INCEPTION_OFFSET: u32 = 3600;
+
+/// Validity period of the signatures, the default of `dnssec-signzone`.
+const DEFAULT_VALIDITY: u32 = 30 * 86400;
+
+/// TTL of the DNSKEY RRset when the zone does not have one.
+const DEFAULT_DNSKEY_TTL: u32 = 3600;
+
+/// Error found while signing a zone.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum SignerError {
+    /// A key cannot be used to sign.
+    #[error(transparent)]
+    Key(#[from] KeyError),
+
+    /// The zone cannot be signed.
+    #[error("{0}")]
+    Message(&'static str),
+}
+
+/// Records used to prove that names and types do not exist.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DenialOfExistence {
+    /// A chain of NSEC RRs in canonical order.
+    ///
+    /// [RFC 4035](https://datatracker.ietf.org/doc/html/rfc4035#section-2.3)
+    Nsec,
+    /// A chain of NSEC3 RRs ordered by hashed name, with an NSEC3PARAM RR
+    /// at the apex.
+    ///
+    /// [RFC 5155](https://datatracker.ietf.org/doc/html/rfc5155#section-7.1)
+    Nsec3 {
+        /// Salt appended to the names before hashing.
+        salt: Vec<u8>,
+        /// Number of additional times the hash is applied.
+        iterations: u16,
+        /// Whether insecure delegations are left out of the chain.
+        opt_out: bool,
+    },
+}
+
+/// Signs zones with a set of keys.
+///
+/// The DNSKEY RRset is signed with the key signing keys and every other
+/// authoritative RRset with the zone signing keys. When one of the two
+/// sets is empty the other one signs everything.
+///
+/// # Example
+/// ```
+/// let mut signer = ZoneSigner::new(DomainName::new_from_str("example.com"), vec![ksk], vec![zsk]);
+/// signer.set_denial(DenialOfExistence::Nsec3 { salt: vec![], iterations: 0, opt_out: false });
+/// let signed_zone = signer.sign_zone(&rrsets).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct ZoneSigner {
+    /// Apex of the zone.
+    apex: DomainName,
+    /// Keys that sign the DNSKEY RRset.
+    ksks: Vec<DnssecKey>,
+    /// Keys that sign the other RRsets.
+    zsks: Vec<DnssecKey>,
+    /// Time at which the signatures start to be valid, in seconds since 1
+    /// January 1970.
+    inception: u32,
+    /// Time at which the signatures stop being valid, in seconds since 1
+    /// January 1970.
+    expiration: u32,
+    /// Largest number of seconds randomly taken from the expiration of
+    /// each signature, so they do not all expire at the same time.
+    jitter: u32,
+    /// Chain used to prove the absence of names and types.
+    denial: DenialOfExistence,
+    /// TTL of the DNSKEY RRset when the zone does not have one.
+    dnskey_ttl: u32,
+}
+
+impl ZoneSigner {
+    /// Creates a new `ZoneSigner` for the zone at `apex`.
+    ///
+    /// The signatures are valid from an hour ago to 30 days from now,
+    /// without jitter, and the zone gets an NSEC chain.
+    pub fn new(apex: DomainName, ksks: Vec<DnssecKey>, zsks: Vec<DnssecKey>) -> Self {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs() as u32;
+        ZoneSigner {
+            apex,
+            ksks,
+            zsks,
+            inception: now.wrapping_sub(DEFAULT_INCEPTION_OFFSET),
+            expiration: now.wrapping_add(DEFAULT_VALIDITY),
+            jitter: 0,
+            denial: DenialOfExistence::Nsec,
+            dnskey_ttl: DEFAULT_DNSKEY_TTL,
+        }
+    }
+
+    /// Signs a zone given as its RRsets.
+    ///
+    /// Returns the RRsets of the signed zone in canonical order, each
+    /// followed by its RRSIG RRset. The DNSKEY RRs of the keys are added to
+    /// the apex. RRSIG, NSEC, NSEC3 and NSEC3PARAM RRsets of the input are
+    /// replaced by new ones. The NS RRsets of delegations and the glue
+    /// below them are not authoritative, so they are kept unsigned.
+    pub fn sign_zone(&self, rrsets: &[RRset]) -> Result<Vec<RRset>, SignerError> {
+        if self.ksks.is_empty() && self.zsks.is_empty() {
+            return Err(SignerError::Message("no key to sign the zone"));
+        }
+        if rrsets.iter().any(|rrset| !rrset.get_name().is_subdomain_of(&self.apex)) {
+            return Err(SignerError::Message("RRset outside of the zone"));
+        }
+
+        let mut rrsets: Vec<RRset> = rrsets
+            .iter()
+            .filter(|rrset| {
+                !matches!(rrset.get_rrtype(), Rrtype::RRSIG | Rrtype::NSEC | Rrtype::NSEC3 | Rrtype::NSEC3PARAM)
+            })
+            .cloned()
+            .collect();
+        let denial_ttl = self.denial_ttl(&rrsets)?;
+        self.add_dnskeys(&mut rrsets);
+
+        let delegations: BTreeSet<DomainName> = rrsets
+            .iter()
+            .filter(|rrset| rrset.get_rrtype() == Rrtype::NS && rrset.get_name() != self.apex)
+            .map(|rrset| rrset.get_name())
+            .collect();
+        let occluded = |name: &DomainName| delegations.iter().any(|cut| name.is_subdomain_of(cut) && name != cut);
+
+        // Types of every authoritative name
+        let mut names: BTreeMap<DomainName, Vec<Rrtype>> = BTreeMap::new();
+        for rrset in rrsets.iter().filter(|rrset| !occluded(&rrset.get_name())) {
+            names.entry(rrset.get_name()).or_default().push(rrset.get_rrtype());
+        }
+
+        match &self.denial {
+            DenialOfExistence::Nsec => rrsets.extend(self.nsec_chain(&names, denial_ttl)),
+            DenialOfExistence::Nsec3 { salt, iterations, opt_out } => {
+                rrsets.extend(self.nsec3_chain(&names, salt, *iterations, *opt_out, denial_ttl)?);
+                let nsec3param = Nsec3ParamRdata::new(Nsec3HashAlgorithm::SHA1, 0, *iterations, salt.len() as u8, salt.clone());
+                rrsets.push(new_rrset(&self.apex, denial_ttl, vec![Rdata::NSEC3PARAM(nsec3param)]));
+            }
+        }
+
+        rrsets.sort_by(|rrset, other| {
+            (rrset.get_name(), u16::from(rrset.get_rrtype())).cmp(&(other.get_name(), u16::from(other.get_rrtype())))
+        });
+
+        let mut signed_zone = Vec::new();
+        for rrset in rrsets {
+            let name = rrset.get_name();
+            // At a delegation only the DS and NSEC RRsets belong to this zone
+            let authoritative = !occluded(&name)
+                && (!delegations.contains(&name) || matches!(rrset.get_rrtype(), Rrtype::DS | Rrtype::NSEC));
+            let rrsigs = match authoritative {
+                true => Some(self.sign_rrset(&rrset)?),
+                false => None,
+            };
+            signed_zone.push(rrset);
+            signed_zone.extend(rrsigs);
+        }
+
+        Ok(signed_zone)
+    }
+
+    /// Signs an RRset with the keys that correspond to its type, returning
+    /// the RRSIG RRset that covers it.
+    ///
+    /// [RFC 4034](https://datatracker.ietf.org/doc/html/rfc4034#section-3.1)
+    pub fn sign_rrset(&self, rrset: &RRset) -> Result<RRset, SignerError> {
+        let keys = match (rrset.get_rrtype(), self.ksks.is_empty(), self.zsks.is_empty()) {
+            (Rrtype::DNSKEY, false, _) | (_, false, true) => &self.ksks,
+            _ => &self.zsks,
+        };
+        let rrs = rrset.get_resource_records();
+        let owner = rrset.get_name();
+        let is_wildcard = owner.get_labels().first().is_some_and(|label| label.as_slice() == b"*");
+
+        let mut rrsigs = Vec::new();
+        for key in keys.iter() {
+            let mut rrsig = RRSIGRdata::new();
+            rrsig.set_type_covered(rrset.get_rrtype());
+            rrsig.set_algorithm(key.get_algorithm());
+            rrsig.set_labels((owner.label_count() - is_wildcard as usize) as u8);
+            rrsig.set_original_ttl(rrset.get_ttl());
+            rrsig.set_signature_expiration(self.expiration.wrapping_sub(self.random_jitter()));
+            rrsig.set_signature_inception(self.inception);
+            rrsig.set_key_tag(key.key_tag());
+            rrsig.set_signer_name(self.apex.clone());
+            let data = signature_data(&rrsig, &rrs).map_err(|_| SignerError::Message("invalid RRset"))?;
+            rrsig.set_signature(key.sign(&data)?);
+            rrsigs.push(Rdata::RRSIG(rrsig));
+        }
+
+        Ok(new_rrset(&owner, rrset.get_ttl(), rrsigs))
+    }
+
+    /// Returns the TTL of the NSEC and NSEC3 RRs: the minimum of the TTL of
+    /// the SOA RR and its MINIMUM field.
+    ///
+    /// [RFC 9077](https://datatracker.ietf.org/doc/html/rfc9077#section-3)
+    fn denial_ttl(&self, rrsets: &[RRset]) -> Result<u32, SignerError> {
+        let soa = rrsets
+            .iter()
+            .find(|rrset| rrset.get_rrtype() == Rrtype::SOA && rrset.get_name() == self.apex)
+            .ok_or(SignerError::Message("the zone has no SOA RR at its apex"))?;
+        match soa.get_records().first() {
+            Some((_, Rdata::SOA(soa_rdata))) => Ok(soa.get_ttl().min(soa_rdata.get_minimum())),
+            _ => Err(SignerError::Message("the zone has no SOA RR at its apex")),
+        }
+    }
+
+    /// Adds the DNSKEY RRs of the keys to the DNSKEY RRset of the apex.
+    fn add_dnskeys(&self, rrsets: &mut Vec<RRset>) {
+        let index = match rrsets
+            .iter()
+            .position(|rrset| rrset.get_rrtype() == Rrtype::DNSKEY && rrset.get_name() == self.apex)
+        {
+            Some(index) => index,
+            None => {
+                rrsets.push(new_rrset(&self.apex, self.dnskey_ttl, Vec::new()));
+                rrsets.len() - 1
+            }
+        };
+
+        let dnskey_rrset = &mut rrsets[index];
+        dnskey_rrset.set_rrtype(Rrtype::DNSKEY);
+        for key in self.ksks.iter().chain(self.zsks.iter()) {
+            let dnskey = Rdata::DNSKEY(key.get_dnskey());
+            if !dnskey_rrset.get_records().iter().any(|(_, rdata)| *rdata == dnskey) {
+                dnskey_rrset.add_record(dnskey.to_bytes().len() as u16, dnskey);
+            }
+        }
+    }
+
+    /// Builds the NSEC chain of the authoritative names.
+    ///
+    /// [RFC 4035](https://datatracker.ietf.org/doc/html/rfc4035#section-2.3)
+    ///
+    ///    The type bitmap of every NSEC resource record in a signed zone MUST
+    ///    indicate the presence of both the NSEC record type itself and its
+    ///    corresponding RRSIG record type.
+    fn nsec_chain(&self, names: &BTreeMap<DomainName, Vec<Rrtype>>, ttl: u32) -> Vec<RRset> {
+        let owners: Vec<&DomainName> = names.keys().collect();
+        names
+            .iter()
+            .enumerate()
+            .map(|(i, (name, types))| {
+                let next = owners[(i + 1) % owners.len()].clone();
+                let mut types = types.clone();
+                types.extend([Rrtype::NSEC, Rrtype::RRSIG].iter());
+                new_rrset(name, ttl, vec![Rdata::NSEC(NsecRdata::new(next, sorted_types(types)))])
+            })
+            .collect()
+    }
+
+    /// Builds the NSEC3 chain of the authoritative names and the empty
+    /// non-terminals above them.
+    ///
+    /// [RFC 5155](https://datatracker.ietf.org/doc/html/rfc5155#section-7.1)
+    ///
+    ///    o  If Opt-Out is being used, owner names of unsigned delegations MAY
+    ///       be excluded.
+    fn nsec3_chain(
+        &self,
+        names: &BTreeMap<DomainName, Vec<Rrtype>>,
+        salt: &[u8],
+        iterations: u16,
+        opt_out: bool,
+        ttl: u32,
+    ) -> Result<Vec<RRset>, SignerError> {
+        let unsigned_delegation =
+            |name: &DomainName, types: &Vec<Rrtype>| *name != self.apex && !types.contains(&Rrtype::DS);
+
+        let mut chain_names: BTreeMap<DomainName, Vec<Rrtype>> = BTreeMap::new();
+        for (name, types) in names.iter() {
+            let delegation = types.contains(&Rrtype::NS) && *name != self.apex;
+            if delegation && opt_out && unsigned_delegation(name, types) {
+                continue;
+            }
+            let mut types = types.clone();
+            if !(delegation && unsigned_delegation(name, types.as_ref())) {
+                types.push(Rrtype::RRSIG);
+            }
+            if *name == self.apex {
+                types.push(Rrtype::NSEC3PARAM);
+            }
+            chain_names.insert(name.clone(), types);
+
+            // Empty non-terminals between the name and the apex
+            let mut ancestor = name.parent();
+            while let Some(name) = ancestor.filter(|name| name.is_subdomain_of(&self.apex) && *name != self.apex) {
+                chain_names.entry(name.clone()).or_default();
+                ancestor = name.parent();
+            }
+        }
+
+        let mut hashes: BTreeMap<Vec<u8>, Vec<Rrtype>> = BTreeMap::new();
+        for (name, types) in chain_names {
+            if hashes.insert(nsec3_hash(&name, salt, iterations), types).is_some() {
+                return Err(SignerError::Message("NSEC3 hash collision"));
+            }
+        }
+
+        let next_hashes: Vec<Vec<u8>> = hashes.keys().cloned().collect();
+        let mut chain = Vec::new();
+        for (i, (hash, types)) in hashes.into_iter().enumerate() {
+            let next = next_hashes[(i + 1) % next_hashes.len()].clone();
+            let owner = self
+                .apex
+                .child(format_base32hex(&hash).as_bytes())
+                .map_err(|_| SignerError::Message("the NSEC3 owner name is too long"))?;
+            let nsec3 = Nsec3Rdata::new(
+                Nsec3HashAlgorithm::SHA1,
+                opt_out as u8,
+                iterations,
+                salt.len() as u8,
+                salt.to_vec(),
+                next.len() as u8,
+                next,
+                sorted_types(types),
+            );
+            chain.push(new_rrset(&owner, ttl, vec![Rdata::NSEC3(nsec3)]));
+        }
+
+        Ok(chain)
+    }
+
+    /// Returns a random number of seconds up to the jitter.
+    fn random_jitter(&self) -> u32 {
+        match self.jitter {
+            0 => 0,
+            jitter => rand::thread_rng().gen_range(0..=jitter),
+        }
+    }
+}
+
+// Getters
+impl ZoneSigner {
+    pub fn get_apex(&self) -> DomainName {
+        self.apex.clone()
+    }
+
+    pub fn get_inception(&self) -> u32 {
+        self.inception
+    }
+
+    pub fn get_expiration(&self) -> u32 {
+        self.expiration
+    }
+
+    pub fn get_jitter(&self) -> u32 {
+        self.jitter
+    }
+
+    pub fn get_denial(&self) -> DenialOfExistence {
+        self.denial.clone()
+    }
+
+    pub fn get_dnskey_ttl(&self) -> u32 {
+        self.dnskey_ttl
+    }
+}
+
+// Setters
+impl ZoneSigner {
+    pub fn set_inception(&mut self, inception: u32) {
+        self.inception = inception;
+    }
+
+    pub fn set_expiration(&mut self, expiration: u32) {
+        self.expiration = expiration;
+    }
+
+    pub fn set_jitter(&mut self, jitter: u32) {
+        self.jitter = jitter;
+    }
+
+    pub fn set_denial(&mut self, denial: DenialOfExistence) {
+        self.denial = denial;
+    }
+
+    pub fn set_dnskey_ttl(&mut self, dnskey_ttl: u32) {
+        self.dnskey_ttl = dnskey_ttl;
+    }
+}
+
+/// Creates an RRset of class IN from its RDATA.
+fn new_rrset(name: &DomainName, ttl: u32, rdatas: Vec<Rdata>) -> RRset {
+    let mut rrset = RRset::new();
+    rrset.set_name(name.clone());
+    rrset.set_ttl(ttl);
+    if let Some(rdata) = rdatas.first() {
+        rrset.set_rrtype(ResourceRecord::new(rdata.clone()).get_rtype());
+    }
+    for rdata in rdatas {
+        rrset.add_record(rdata.to_bytes().len() as u16, rdata);
+    }
+    rrset
+}
+
+/// Sorts types by their number, as the type bit maps list them.
+fn sorted_types(mut types: Vec<Rrtype>) -> Vec<Rrtype> {
+    types.sort_by_key(|rrtype| u16::from(*rrtype));
+    types.dedup();
+    types
+}
+
+#[cfg(test)]
+mod signer_test {
+    use super::*;
+    use crate::dnssec::dnssec_key::{KSK_FLAGS, ZSK_FLAGS};
+    use crate::message::rdata::dnskey_rdata::DnskeyRdata;
+    use crate::dnssec::denial::{verify_denial, DenialVerdict};
+    use crate::dnssec::rrset_signature::{verify_rrsig, RrsigVerification};
+    use crate::message::rclass::Rclass;
+    use crate::zone::master_file::MasterFileParser;
+    use crate::dnssec::dnssec_algorithm::DnssecAlgorithm;
+
+    const ZONE: &str = "\
+$TTL 3600
+@        IN SOA  ns1 hostmaster 1 7200 3600 1209600 300
+         IN NS   ns1
+ns1      IN A    192.0.2.1
+www      IN A    192.0.2.2
+*.wild   IN TXT  \"wildcard\"
+a.b.c    IN A    192.0.2.3
+secure   IN NS   ns.secure
+         IN DS   12345 13 2 0123456789ABCDEF0123456789ABCDEF0123456789ABCDEF0123456789ABCDEF
+ns.secure IN A   192.0.2.4
+insecure IN NS   ns.insecure
+ns.insecure IN A 192.0.2.5
+";
+
+    fn name(name: &str) -> DomainName {
+        DomainName::new_from_str(name)
+    }
+
+    fn zone() -> Vec<RRset> {
+        let rrs = MasterFileParser::new(name("example"), Rclass::IN).parse_str(ZONE).unwrap();
+        let mut rrsets: Vec<RRset> = Vec::new();
+        for rr in rrs.iter() {
+            let exists = rrsets
+                .iter()
+                .any(|rrset| rrset.get_name() == rr.get_name() && rrset.get_rrtype() == rr.get_rtype());
+            if !exists {
+                rrsets.push(RRset::get_rrset_from_rrs(rrs.clone(), rr.get_name(), rr.get_rtype(), Rclass::IN).unwrap());
+            }
+        }
+        rrsets
+    }
+
+    fn signer(denial: DenialOfExistence) -> ZoneSigner {
+        let ksk = DnssecKey::generate(DnssecAlgorithm::ED25519, KSK_FLAGS).unwrap();
+        let zsk = DnssecKey::generate(DnssecAlgorithm::ECDSAP256SHA256, ZSK_FLAGS).unwrap();
+        let mut signer = ZoneSigner::new(name("example"), vec![ksk], vec![zsk]);
+        signer.set_denial(denial);
+        signer
+    }
+
+    fn records(rrsets: &[RRset]) -> Vec<ResourceRecord> {
+        rrsets.iter().flat_map(|rrset| rrset.get_resource_records()).collect()
+    }
+
+    fn find<'a>(rrsets: &'a [RRset], owner: &str, rrtype: Rrtype) -> Option<&'a RRset> {
+        rrsets.iter().find(|rrset| rrset.get_name() == name(owner) && rrset.get_rrtype() == rrtype)
+    }
+
+    /// Returns the RRSIG RDATA that cover the RRset.
+    fn rrsigs_of(signed: &[RRset], rrset: &RRset) -> Vec<RRSIGRdata> {
+        signed
+            .iter()
+            .filter(|other| other.get_name() == rrset.get_name() && other.get_rrtype() == Rrtype::RRSIG)
+            .flat_map(|other| other.get_records())
+            .filter_map(|(_, rdata)| match rdata {
+                Rdata::RRSIG(rrsig) if rrsig.get_type_covered() == rrset.get_rrtype() => Some(rrsig),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn signatures_validate() {
+        let signer = signer(DenialOfExistence::Nsec);
+        let signed = signer.sign_zone(&zone()).unwrap();
+        let dnskeys: Vec<DnskeyRdata> = find(&signed, "example", Rrtype::DNSKEY)
+            .unwrap()
+            .get_records()
+            .into_iter()
+            .filter_map(|(_, rdata)| match rdata {
+                Rdata::DNSKEY(dnskey) => Some(dnskey),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(dnskeys.len(), 2);
+
+        for rrset in signed.iter().filter(|rrset| rrset.get_rrtype() != Rrtype::RRSIG) {
+            let rrsigs = rrsigs_of(&signed, rrset);
+            let rrs = rrset.get_resource_records();
+            for rrsig in rrsigs.iter() {
+                let dnskey = dnskeys.iter().find(|key| key.key_tag() == rrsig.get_key_tag()).unwrap();
+                assert_eq!(verify_rrsig(rrsig, dnskey, &rrs), RrsigVerification::Secure);
+            }
+            let expected_signer = match rrset.get_rrtype() {
+                Rrtype::DNSKEY => Some(DnssecAlgorithm::ED25519),
+                _ => Some(DnssecAlgorithm::ECDSAP256SHA256),
+            };
+            let authoritative = !matches!(
+                (rrset.get_name().to_string().as_str(), rrset.get_rrtype()),
+                ("secure.example", Rrtype::NS)
+                    | ("insecure.example", Rrtype::NS)
+                    | ("ns.secure.example", _)
+                    | ("ns.insecure.example", _)
+            );
+            match authoritative {
+                true => assert_eq!(rrsigs.iter().map(|rrsig| rrsig.get_algorithm()).next(), expected_signer),
+                false => assert!(rrsigs.is_empty(), "{} should not be signed", rrset.get_name()),
+            }
+        }
+    }
+
+    #[test]
+    fn wildcard_signature_labels() {
+        let signed = signer(DenialOfExistence::Nsec).sign_zone(&zone()).unwrap();
+        let wildcard = find(&signed, "*.wild.example", Rrtype::TXT).unwrap();
+
+        let rrsigs = rrsigs_of(&signed, wildcard);
+        assert_eq!(rrsigs[0].get_labels(), 2);
+        assert_eq!(rrsigs[0].get_signer_name(), name("example"));
+        assert_eq!(rrsigs[0].get_original_ttl(), 3600);
+    }
+
+    #[test]
+    fn nsec_chain() {
+        let signed = signer(DenialOfExistence::Nsec).sign_zone(&zone()).unwrap();
+        let nsecs: Vec<ResourceRecord> = records(&signed)
+            .into_iter()
+            .filter(|rr| rr.get_rtype() == Rrtype::NSEC)
+            .collect();
+        let owners: Vec<String> = nsecs.iter().map(|rr| rr.get_name().to_string()).collect();
+
+        assert_eq!(
+            owners,
+            vec!["example", "a.b.c.example", "insecure.example", "ns1.example", "secure.example", "*.wild.example", "www.example"]
+        );
+        assert_eq!(nsecs[0].get_ttl(), 300);
+        let Rdata::NSEC(apex) = nsecs[0].get_rdata() else { unreachable!() };
+        assert_eq!(apex.get_next_domain_name(), name("a.b.c.example"));
+        assert_eq!(
+            apex.get_type_bit_maps(),
+            vec![Rrtype::NS, Rrtype::SOA, Rrtype::RRSIG, Rrtype::NSEC, Rrtype::DNSKEY]
+        );
+        let Rdata::NSEC(last) = nsecs[6].get_rdata() else { unreachable!() };
+        assert_eq!(last.get_next_domain_name(), name("example"));
+
+        let proof = records(&signed);
+        assert_eq!(verify_denial(&name("nothere.example"), Rrtype::A, &proof), DenialVerdict::NxDomain);
+        assert_eq!(verify_denial(&name("b.c.example"), Rrtype::A, &proof), DenialVerdict::NoData);
+        assert_eq!(
+            verify_denial(&name("insecure.example"), Rrtype::DS, &proof),
+            DenialVerdict::InsecureDelegation(name("insecure.example"))
+        );
+        assert_eq!(verify_denial(&name("x.wild.example"), Rrtype::A, &proof), DenialVerdict::WildcardNoData);
+    }
+
+    #[test]
+    fn nsec3_chain() {
+        let denial = DenialOfExistence::Nsec3 { salt: vec![0xaa, 0xbb], iterations: 1, opt_out: false };
+        let signed = signer(denial).sign_zone(&zone()).unwrap();
+        let proof = records(&signed);
+        let nsec3s: Vec<&ResourceRecord> = proof.iter().filter(|rr| rr.get_rtype() == Rrtype::NSEC3).collect();
+
+        // Seven names and the empty non-terminals c.example, b.c.example and wild.example
+        assert_eq!(nsec3s.len(), 10);
+        let Rdata::NSEC3PARAM(param) = find(&signed, "example", Rrtype::NSEC3PARAM).unwrap().get_records()[0].1.clone()
+        else { unreachable!() };
+        assert_eq!(param.get_salt(), vec![0xaa, 0xbb]);
+        assert_eq!(param.get_iterations(), 1);
+
+        assert_eq!(verify_denial(&name("nothere.example"), Rrtype::A, &proof), DenialVerdict::NxDomain);
+        assert_eq!(verify_denial(&name("b.c.example"), Rrtype::A, &proof), DenialVerdict::NoData);
+        assert_eq!(verify_denial(&name("www.example"), Rrtype::MX, &proof), DenialVerdict::NoData);
+        assert_eq!(
+            verify_denial(&name("insecure.example"), Rrtype::DS, &proof),
+            DenialVerdict::InsecureDelegation(name("insecure.example"))
+        );
+        assert_eq!(verify_denial(&name("x.wild.example"), Rrtype::A, &proof), DenialVerdict::WildcardNoData);
+    }
+
+    #[test]
+    fn nsec3_opt_out() {
+        let denial = DenialOfExistence::Nsec3 { salt: vec![], iterations: 0, opt_out: true };
+        let signed = signer(denial).sign_zone(&zone()).unwrap();
+        let proof = records(&signed);
+        let nsec3s: Vec<Nsec3Rdata> = proof
+            .iter()
+            .filter_map(|rr| match rr.get_rdata() {
+                Rdata::NSEC3(nsec3) => Some(nsec3),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(nsec3s.len(), 9);
+        assert!(nsec3s.iter().all(|nsec3| nsec3.get_flags() == 1));
+        assert_eq!(verify_denial(&name("insecure.example"), Rrtype::DS, &proof), DenialVerdict::OptOut);
+    }
+
+    #[test]
+    fn validity_and_jitter() {
+        let mut signer = signer(DenialOfExistence::Nsec);
+        signer.set_inception(1_700_000_000);
+        signer.set_expiration(1_700_864_000);
+        signer.set_jitter(3600);
+        let signed = signer.sign_zone(&zone()).unwrap();
+
+        let rrsigs: Vec<RRSIGRdata> = records(&signed)
+            .into_iter()
+            .filter_map(|rr| match rr.get_rdata() {
+                Rdata::RRSIG(rrsig) => Some(rrsig),
+                _ => None,
+            })
+            .collect();
+        assert!(!rrsigs.is_empty());
+        for rrsig in rrsigs {
+            assert_eq!(rrsig.get_signature_inception(), 1_700_000_000);
+            let expiration = rrsig.get_signature_expiration();
+            assert!((1_700_860_400..=1_700_864_000).contains(&expiration));
+        }
+    }
+
+    #[test]
+    fn single_key_signs_everything() {
+        let csk = DnssecKey::generate(DnssecAlgorithm::ED25519, KSK_FLAGS).unwrap();
+        let key_tag = csk.key_tag();
+        let signed = ZoneSigner::new(name("example"), vec![csk], vec![]).sign_zone(&zone()).unwrap();
+
+        for rrsig in records(&signed).into_iter().filter_map(|rr| match rr.get_rdata() {
+            Rdata::RRSIG(rrsig) => Some(rrsig),
+            _ => None,
+        }) {
+            assert_eq!(rrsig.get_key_tag(), key_tag);
+        }
+    }
+
+    #[test]
+    fn resigning_replaces_records() {
+        let signer = signer(DenialOfExistence::Nsec);
+        let signed = signer.sign_zone(&zone()).unwrap();
+        let resigned = signer.sign_zone(&signed).unwrap();
+
+        assert_eq!(resigned.len(), signed.len());
+        assert_eq!(find(&resigned, "example", Rrtype::DNSKEY).unwrap().get_records().len(), 2);
+    }
+
+    #[test]
+    fn errors() {
+        let zone_without_soa: Vec<RRset> = zone().into_iter().filter(|rrset| rrset.get_rrtype() != Rrtype::SOA).collect();
+        assert!(signer(DenialOfExistence::Nsec).sign_zone(&zone_without_soa).is_err());
+        assert!(ZoneSigner::new(name("example"), vec![], vec![]).sign_zone(&zone()).is_err());
+        assert!(ZoneSigner::new(name("other"), vec![DnssecKey::generate(DnssecAlgorithm::ED25519, KSK_FLAGS).unwrap()], vec![])
+            .sign_zone(&zone())
+            .is_err());
+    }
+}
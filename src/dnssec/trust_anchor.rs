@@ -0,0 +1,113 @@
+use crate::domain_name::DomainName;
+use crate::message::rclass::Rclass;
+use crate::message::resource_record::ResourceRecord;
+use crate::message::rrtype::Rrtype;
+use crate::zone::master_file::MasterFileParser;
+use crate::zone::zone_error::ZoneError;
+use std::path::Path;
+
+/// DS records of the root zone KSKs, in master file format.
+///
+/// Taken from the `root-anchors.xml` file published by IANA
+/// (https://data.iana.org/root-anchors/root-anchors.xml). KSK-2017 has key
+/// tag 20326 and KSK-2024 has key tag 38696.
+const ROOT_TRUST_ANCHOR: &str = "\
+. 172800 IN DS 20326 8 2 E06D44B80B8F1D39A95C0B0D7C65D08458E880409BBC683457104237C7F8EC8D
+. 172800 IN DS 38696 8 2 683D2D0ACB8C9B712A1948B27F741219298D0A450D612C483AF444A4C0FB2B16
+";
+
+/// Returns the built-in trust anchor of the root zone.
+///
+/// [RFC 4035]: https://datatracker.ietf.org/doc/html/rfc4035#section-5
+///
+/// A security-aware resolver must be able to configure at least one trusted
+/// public key or DS RR, and should be able to configure the root zone's key.
+/// The validation of every answer starts from one of these records.
+///
+/// # Example
+/// ```
+/// let anchors = root_trust_anchor();
+/// assert_eq!(anchors.len(), 2);
+/// ```
+pub fn root_trust_anchor() -> Vec<ResourceRecord> {
+    MasterFileParser::new(DomainName::new_from_str("."), Rclass::IN)
+        .parse_str(ROOT_TRUST_ANCHOR)
+        .expect("the built-in root trust anchor is a valid master file")
+}
+
+/// Reads trust anchors from a file in master file format.
+///
+/// The file may hold DS and DNSKEY records, like the `root.key` files used
+/// by other resolvers. Any other record is ignored, and relative names are
+/// taken from the root.
+///
+/// # Example
+/// ```
+/// let anchors = load_trust_anchor("/etc/dns_rust/root.key").unwrap();
+/// ```
+pub fn load_trust_anchor<P: AsRef<Path>>(path: P) -> Result<Vec<ResourceRecord>, ZoneError> {
+    let rrs = MasterFileParser::new(DomainName::new_from_str("."), Rclass::IN)
+        .parse_file(path.as_ref())?;
+    let anchors: Vec<ResourceRecord> = rrs
+        .into_iter()
+        .filter(|rr| matches!(rr.get_rtype(), Rrtype::DS | Rrtype::DNSKEY))
+        .collect();
+
+    if anchors.is_empty() {
+        return Err(ZoneError::new(Some(path.as_ref().to_path_buf()), 1, 1, "no DS or DNSKEY record found"));
+    }
+
+    Ok(anchors)
+}
+
+#[cfg(test)]
+mod trust_anchor_test {
+    use super::*;
+    use crate::message::rdata::Rdata;
+    use std::fs;
+    use crate::dnssec::dnssec_algorithm::{DigestType, DnssecAlgorithm};
+
+    #[test]
+    fn root_trust_anchor_records() {
+        let anchors = root_trust_anchor();
+
+        assert_eq!(anchors.len(), 2);
+        for anchor in anchors.iter() {
+            assert!(anchor.get_name().is_root());
+            assert_eq!(anchor.get_rtype(), Rrtype::DS);
+        }
+        let Rdata::DS(ds) = anchors[0].get_rdata() else { panic!("not a DS record") };
+        assert_eq!(ds.get_key_tag(), 20326);
+        assert_eq!(ds.get_algorithm(), DnssecAlgorithm::RSASHA256);
+        assert_eq!(ds.get_digest_type(), DigestType::SHA256);
+        assert_eq!(ds.get_digest()[..4], [0xe0, 0x6d, 0x44, 0xb8]);
+    }
+
+    #[test]
+    fn load_trust_anchor_from_file() {
+        let path = std::env::temp_dir().join(format!("trust_anchor_test_{}.key", std::process::id()));
+        fs::write(
+            &path,
+            "; root KSK\n\
+             . 172800 IN DNSKEY 257 3 15 l02Woi0iS8Aa25FQkUd9RMzZHJpBoRQwAQEX1SxZJA4=\n\
+             example. 3600 IN NS ns.example.\n",
+        ).unwrap();
+
+        let anchors = load_trust_anchor(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(anchors.len(), 1);
+        assert_eq!(anchors[0].get_rtype(), Rrtype::DNSKEY);
+    }
+
+    #[test]
+    fn load_trust_anchor_without_keys() {
+        let path = std::env::temp_dir().join(format!("trust_anchor_empty_{}.key", std::process::id()));
+        fs::write(&path, "example. 3600 IN NS ns.example.\n").unwrap();
+
+        let result = load_trust_anchor(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,707 @@
+//! Automated updates of trust anchors, following the DNSKEY RRset of the
+//! trust point as its operator rolls the keys.
+//!
+//! [RFC 5011](https://datatracker.ietf.org/doc/html/rfc5011#section-2)
+//!
+//! ```text
+//! 2.  Theory of Operation
+//!
+//!    The general concept of this mechanism is that existing trust anchors
+//!    can be used to authenticate new trust anchors at the same point in
+//!    the DNS hierarchy.  When a zone operator adds a new SEP key (i.e., a
+//!    DNSKEY with the Secure Entry Point bit set) (see [RFC4034], Section
+//!    2.1.1) to a trust point DNSKEY RRSet, and when that RRSet is
+//!    validated by an existing trust anchor, then the resolver can add the
+//!    new key to its set of valid trust anchors for that trust point.
+//! ```
+
+use crate::dnssec::dnssec_key::REVOKE_FLAG;
+use crate::dnssec::rrset_signature::{verify_rrsig_at, RrsigVerification};
+use crate::domain_name::DomainName;
+use crate::message::rdata::dnskey_rdata::DnskeyRdata;
+use crate::message::rdata::ds_rdata::DsRdata;
+use crate::message::rdata::rrsig_rdata::RRSIGRdata;
+use crate::message::rdata::Rdata;
+use crate::message::resource_record::ResourceRecord;
+use crate::message::rrtype::Rrtype;
+use std::convert::TryFrom;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Time a new key must be seen before it becomes a trust anchor.
+///
+/// [RFC 5011](https://datatracker.ietf.org/doc/html/rfc5011#section-2.4.1)
+pub const ADD_HOLD_DOWN: u64 = 30 * 86400;
+
+/// Time a revoked key is remembered before it is removed.
+///
+/// [RFC 5011](https://datatracker.ietf.org/doc/html/rfc5011#section-2.4.2)
+pub const REMOVE_HOLD_DOWN: u64 = 30 * 86400;
+
+/// Error found while updating, reading or writing a trust anchor store.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum TrustAnchorError {
+    /// No trust anchor of the store validates the DNSKEY RRset.
+    #[error("the DNSKEY RRset is not signed by a trust anchor")]
+    Unverified,
+
+    /// A line of the state file is not valid.
+    #[error("invalid state file, line {0}: {1}")]
+    InvalidFormat(usize, &'static str),
+
+    /// The state file could not be read or written.
+    #[error("I/O error: {0}")]
+    Io(String),
+}
+
+/// State of a key tracked by the store.
+///
+/// [RFC 5011](https://datatracker.ietf.org/doc/html/rfc5011#section-4)
+///
+/// Keys in the `Start` state of the RFC are not tracked, so they have no
+/// variant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyState {
+    /// The key was seen in a validated DNSKEY RRset and waits for the add
+    /// hold-down time to pass.
+    AddPend,
+    /// The key is a trust anchor.
+    Valid,
+    /// The key is a trust anchor, but was not in the last DNSKEY RRset.
+    Missing,
+    /// The key revoked itself, and waits for the remove hold-down time to
+    /// pass.
+    Revoked,
+    /// The key was revoked long enough ago to be forgotten.
+    Removed,
+}
+
+impl KeyState {
+    /// Returns whether keys in this state validate the DNSKEY RRset.
+    pub fn is_trust_anchor(&self) -> bool {
+        matches!(self, KeyState::Valid | KeyState::Missing)
+    }
+}
+
+impl fmt::Display for KeyState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            KeyState::AddPend => "ADDPEND",
+            KeyState::Valid => "VALID",
+            KeyState::Missing => "MISSING",
+            KeyState::Revoked => "REVOKED",
+            KeyState::Removed => "REMOVED",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl TryFrom<&str> for KeyState {
+    type Error = &'static str;
+
+    fn try_from(state: &str) -> Result<Self, Self::Error> {
+        match state {
+            "ADDPEND" => Ok(KeyState::AddPend),
+            "VALID" => Ok(KeyState::Valid),
+            "MISSING" => Ok(KeyState::Missing),
+            "REVOKED" => Ok(KeyState::Revoked),
+            "REMOVED" => Ok(KeyState::Removed),
+            _ => Err("unknown key state"),
+        }
+    }
+}
+
+/// Key of the trust point together with its state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrackedKey {
+    /// The key as last seen, with the REVOKE flag once it is revoked.
+    dnskey: DnskeyRdata,
+    /// State of the key.
+    state: KeyState,
+    /// Time at which the key was first seen, in seconds since the epoch.
+    first_seen: u64,
+    /// Time of the last change of state, in seconds since the epoch.
+    last_change: u64,
+}
+
+impl TrackedKey {
+    fn new(dnskey: DnskeyRdata, state: KeyState, now: u64) -> Self {
+        TrackedKey { dnskey, state, first_seen: now, last_change: now }
+    }
+
+    fn set_state(&mut self, state: KeyState, now: u64) {
+        self.state = state;
+        self.last_change = now;
+    }
+
+    pub fn get_dnskey(&self) -> DnskeyRdata {
+        self.dnskey.clone()
+    }
+
+    pub fn get_state(&self) -> KeyState {
+        self.state
+    }
+
+    pub fn get_first_seen(&self) -> u64 {
+        self.first_seen
+    }
+
+    pub fn get_last_change(&self) -> u64 {
+        self.last_change
+    }
+}
+
+/// Trust anchors of a trust point, kept up to date with RFC 5011.
+///
+/// Every time the DNSKEY RRset of the trust point is fetched, it is passed
+/// to `update` with its RRSIGs. New SEP keys become trust anchors once they
+/// have been seen for the add hold-down time in RRsets signed by the current
+/// trust anchors, and keys with the REVOKE flag that sign the RRset stop
+/// being trust anchors at once. The state is saved in a text file with
+/// `save`, to be read back with `load` on the next run.
+///
+/// # Example
+/// ```
+/// let mut store = TrustAnchorStore::load("/var/lib/dns_rust/root.state")
+///     .unwrap_or_else(|_| TrustAnchorStore::new(DomainName::new_from_str("."), &root_trust_anchor(), now));
+/// if store.update(&dnskeys, &rrsigs)? {
+///     store.save("/var/lib/dns_rust/root.state")?;
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrustAnchorStore {
+    /// Owner name of the DNSKEY RRset.
+    zone: DomainName,
+    /// Keys tracked by the store.
+    keys: Vec<TrackedKey>,
+    /// DS trust anchors whose DNSKEY has not been seen yet.
+    ds_anchors: Vec<DsRdata>,
+    /// Add hold-down time, in seconds.
+    add_hold_down: u64,
+    /// Remove hold-down time, in seconds.
+    remove_hold_down: u64,
+}
+
+impl TrustAnchorStore {
+    /// Creates a store from the configured trust anchors of a zone.
+    ///
+    /// DNSKEY anchors are trust anchors from the start. DS anchors are kept
+    /// until the first update, which makes trust anchors of the keys they
+    /// point to. Records of other zones or types are ignored.
+    pub fn new(zone: DomainName, anchors: &[ResourceRecord], now: u64) -> Self {
+        let mut store = TrustAnchorStore {
+            zone,
+            keys: Vec::new(),
+            ds_anchors: Vec::new(),
+            add_hold_down: ADD_HOLD_DOWN,
+            remove_hold_down: REMOVE_HOLD_DOWN,
+        };
+
+        let zone = store.zone.clone();
+        for anchor in anchors.iter().filter(|rr| rr.get_name() == zone) {
+            match anchor.get_rdata() {
+                Rdata::DNSKEY(dnskey) => store.keys.push(TrackedKey::new(dnskey, KeyState::Valid, now)),
+                Rdata::DS(ds) => store.ds_anchors.push(ds),
+                _ => {}
+            }
+        }
+
+        store
+    }
+
+    /// Updates the store with the DNSKEY RRset of the zone at the current
+    /// time.
+    ///
+    /// See `update_at`.
+    pub fn update(&mut self, dnskeys: &[ResourceRecord], rrsigs: &[RRSIGRdata]) -> Result<bool, TrustAnchorError> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0);
+        self.update_at(dnskeys, rrsigs, now)
+    }
+
+    /// Updates the store with the DNSKEY RRset of the zone and its RRSIGs at
+    /// the time `now`, in seconds since the epoch. Returns whether the state
+    /// changed and should be saved.
+    ///
+    /// [RFC 5011](https://datatracker.ietf.org/doc/html/rfc5011#section-4.1)
+    ///
+    /// A key with the REVOKE flag that signs the RRset is revoked even when
+    /// no trust anchor signs it. Every other change needs a signature of a
+    /// trust anchor, or `TrustAnchorError::Unverified` is returned.
+    pub fn update_at(
+        &mut self,
+        dnskeys: &[ResourceRecord],
+        rrsigs: &[RRSIGRdata],
+        now: u64,
+    ) -> Result<bool, TrustAnchorError> {
+        let rrset: Vec<ResourceRecord> = dnskeys
+            .iter()
+            .filter(|rr| rr.get_name() == self.zone && rr.get_rtype() == Rrtype::DNSKEY)
+            .cloned()
+            .collect();
+        let keys: Vec<DnskeyRdata> = rrset
+            .iter()
+            .filter_map(|rr| match rr.get_rdata() {
+                Rdata::DNSKEY(dnskey) => Some(dnskey),
+                _ => None,
+            })
+            .collect();
+        let rrsigs: Vec<&RRSIGRdata> = rrsigs
+            .iter()
+            .filter(|rrsig| rrsig.get_type_covered() == Rrtype::DNSKEY && rrsig.get_signer_name() == self.zone)
+            .collect();
+        let signs = |key: &DnskeyRdata| {
+            rrsigs.iter().any(|rrsig| {
+                rrsig.get_key_tag() == key.key_tag()
+                    && verify_rrsig_at(rrsig, key, &rrset, now) == RrsigVerification::Secure
+            })
+        };
+
+        // Section 2.1: a revoked key must sign the RRset that revokes it
+        let mut changed = false;
+        for key in keys.iter().filter(|key| key.is_revoked() && signs(key)) {
+            if let Some(index) = self.keys.iter().position(|tracked| same_key(&tracked.dnskey, key)) {
+                match self.keys[index].state {
+                    KeyState::AddPend => {
+                        self.keys.remove(index);
+                    }
+                    KeyState::Valid | KeyState::Missing => {
+                        self.keys[index].dnskey = key.clone();
+                        self.keys[index].set_state(KeyState::Revoked, now);
+                    }
+                    KeyState::Revoked | KeyState::Removed => continue,
+                }
+                changed = true;
+            }
+        }
+
+        let trusted = keys
+            .iter()
+            .filter(|key| !key.is_revoked() && (self.is_trust_anchor(key) || self.matches_ds_anchor(key)))
+            .any(signs);
+        if !trusted {
+            return if changed { Ok(true) } else { Err(TrustAnchorError::Unverified) };
+        }
+
+        // The DS anchors are replaced by the keys they point to
+        for key in keys.iter().filter(|key| !key.is_revoked()) {
+            let before = self.ds_anchors.len();
+            let zone = &self.zone;
+            self.ds_anchors.retain(|ds| !ds_matches(ds, key, zone));
+            if self.ds_anchors.len() != before && !self.keys.iter().any(|tracked| same_key(&tracked.dnskey, key)) {
+                self.keys.push(TrackedKey::new(key.clone(), KeyState::Valid, now));
+                changed = true;
+            }
+        }
+
+        // Section 2.4.1: the add hold-down time is at least the original TTL
+        let ttl = rrsigs.iter().map(|rrsig| rrsig.get_original_ttl() as u64).max().unwrap_or(0);
+        let add_hold_down = self.add_hold_down.max(ttl);
+        let remove_hold_down = self.remove_hold_down;
+
+        for key in keys.iter().filter(|key| !key.is_revoked() && key.is_sep()) {
+            if !self.keys.iter().any(|tracked| same_key(&tracked.dnskey, key)) {
+                self.keys.push(TrackedKey::new(key.clone(), KeyState::AddPend, now));
+                changed = true;
+            }
+        }
+
+        let before = self.keys.len();
+        self.keys.retain(|tracked| {
+            !matches!(tracked.state, KeyState::AddPend | KeyState::Removed)
+                || keys.iter().any(|key| same_key(&tracked.dnskey, key))
+        });
+        changed |= self.keys.len() != before;
+
+        for tracked in self.keys.iter_mut() {
+            let present = keys.iter().any(|key| !key.is_revoked() && same_key(&tracked.dnskey, key));
+            let next = match tracked.state {
+                KeyState::AddPend if now.saturating_sub(tracked.first_seen) >= add_hold_down => KeyState::Valid,
+                KeyState::Valid if !present => KeyState::Missing,
+                KeyState::Missing if present => KeyState::Valid,
+                KeyState::Revoked if now.saturating_sub(tracked.last_change) >= remove_hold_down => KeyState::Removed,
+                state => state,
+            };
+            if next != tracked.state {
+                tracked.set_state(next, now);
+                changed = true;
+            }
+        }
+
+        Ok(changed)
+    }
+
+    /// Returns the trust anchors of the zone: the DNSKEY RRs of the keys
+    /// in the `Valid` and `Missing` states, and the DS RRs not yet replaced
+    /// by their keys.
+    pub fn get_trust_anchors(&self) -> Vec<ResourceRecord> {
+        let keys = self
+            .keys
+            .iter()
+            .filter(|tracked| tracked.state.is_trust_anchor())
+            .map(|tracked| Rdata::DNSKEY(tracked.dnskey.clone()));
+        let ds_anchors = self.ds_anchors.iter().map(|ds| Rdata::DS(ds.clone()));
+
+        keys.chain(ds_anchors)
+            .map(|rdata| {
+                let mut rr = ResourceRecord::new(rdata);
+                rr.set_name(self.zone.clone());
+                rr
+            })
+            .collect()
+    }
+
+    /// Reads a store from a state file written by `save`.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, TrustAnchorError> {
+        let text = fs::read_to_string(path).map_err(|error| TrustAnchorError::Io(error.to_string()))?;
+        Self::from_text(&text)
+    }
+
+    /// Writes the store to a state file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), TrustAnchorError> {
+        fs::write(path, self.to_text()).map_err(|error| TrustAnchorError::Io(error.to_string()))
+    }
+
+    /// Parses the state file format.
+    ///
+    /// Each line holds the zone and a keyword followed by its values:
+    /// `HOLDDOWN <add> <remove>`, `DS <rdata>`, or the state of a key
+    /// followed by the time it was first seen, the time of its last change
+    /// and its DNSKEY RDATA. Comments start with `;`.
+    pub fn from_text(text: &str) -> Result<Self, TrustAnchorError> {
+        let mut store: Option<TrustAnchorStore> = None;
+
+        for (number, line) in text.lines().enumerate() {
+            let line = line.split(';').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let error = |message| TrustAnchorError::InvalidFormat(number + 1, message);
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 2 {
+                return Err(error("missing fields"));
+            }
+
+            let zone = DomainName::new_from_str(fields[0]);
+            let store = store.get_or_insert_with(|| TrustAnchorStore::new(zone.clone(), &[], 0));
+            if store.zone != zone {
+                return Err(error("the lines are not of the same zone"));
+            }
+
+            match fields[1] {
+                "HOLDDOWN" if fields.len() == 4 => {
+                    store.add_hold_down = fields[2].parse().map_err(|_| error("invalid add hold-down time"))?;
+                    store.remove_hold_down = fields[3].parse().map_err(|_| error("invalid remove hold-down time"))?;
+                }
+                "HOLDDOWN" => return Err(error("missing hold-down times")),
+                "DS" => store.ds_anchors.push(DsRdata::from_text(&fields[2..], &zone).map_err(error)?),
+                state => {
+                    let state = KeyState::try_from(state).map_err(error)?;
+                    if fields.len() < 5 {
+                        return Err(error("missing fields"));
+                    }
+                    let first_seen = fields[2].parse().map_err(|_| error("invalid time"))?;
+                    let last_change = fields[3].parse().map_err(|_| error("invalid time"))?;
+                    let dnskey = DnskeyRdata::from_text(&fields[4..], &zone).map_err(error)?;
+                    store.keys.push(TrackedKey { dnskey, state, first_seen, last_change });
+                }
+            }
+        }
+
+        store.ok_or(TrustAnchorError::InvalidFormat(1, "no zone in the state file"))
+    }
+
+    /// Returns the store in the state file format.
+    pub fn to_text(&self) -> String {
+        let mut text = String::from("; RFC 5011 trust anchor state\n");
+        text.push_str(&format!("{} HOLDDOWN {} {}\n", self.zone, self.add_hold_down, self.remove_hold_down));
+        for ds in self.ds_anchors.iter() {
+            text.push_str(&format!("{} DS {}\n", self.zone, ds.to_text()));
+        }
+        for tracked in self.keys.iter() {
+            text.push_str(&format!(
+                "{} {} {} {} {} ; key tag {}\n",
+                self.zone,
+                tracked.state,
+                tracked.first_seen,
+                tracked.last_change,
+                tracked.dnskey.to_text(),
+                tracked.dnskey.key_tag()
+            ));
+        }
+        text
+    }
+
+    /// Returns whether a key is one of the trust anchors.
+    fn is_trust_anchor(&self, key: &DnskeyRdata) -> bool {
+        self.keys.iter().any(|tracked| tracked.state.is_trust_anchor() && tracked.dnskey == *key)
+    }
+
+    /// Returns whether a key is the one of a DS trust anchor.
+    fn matches_ds_anchor(&self, key: &DnskeyRdata) -> bool {
+        self.ds_anchors.iter().any(|ds| ds_matches(ds, key, &self.zone))
+    }
+
+    pub fn get_zone(&self) -> DomainName {
+        self.zone.clone()
+    }
+
+    pub fn get_keys(&self) -> Vec<TrackedKey> {
+        self.keys.clone()
+    }
+
+    pub fn get_add_hold_down(&self) -> u64 {
+        self.add_hold_down
+    }
+
+    pub fn get_remove_hold_down(&self) -> u64 {
+        self.remove_hold_down
+    }
+
+    pub fn set_add_hold_down(&mut self, add_hold_down: u64) {
+        self.add_hold_down = add_hold_down;
+    }
+
+    pub fn set_remove_hold_down(&mut self, remove_hold_down: u64) {
+        self.remove_hold_down = remove_hold_down;
+    }
+}
+
+/// Returns whether two DNSKEY RDATA are the same key, whether or not one of
+/// them has the REVOKE flag.
+fn same_key(a: &DnskeyRdata, b: &DnskeyRdata) -> bool {
+    a.get_flags() | REVOKE_FLAG == b.get_flags() | REVOKE_FLAG
+        && a.get_protocol() == b.get_protocol()
+        && a.get_algorithm() == b.get_algorithm()
+        && a.get_public_key() == b.get_public_key()
+}
+
+/// Returns whether a DS RDATA points to a key of the zone.
+fn ds_matches(ds: &DsRdata, key: &DnskeyRdata, zone: &DomainName) -> bool {
+    ds.get_key_tag() == key.key_tag()
+        && key.to_ds(zone, ds.get_digest_type()).is_ok_and(|digest| digest.get_digest() == ds.get_digest())
+}
+
+#[cfg(test)]
+mod trust_anchor_store_test {
+    use super::*;
+    use crate::dnssec::dnssec_algorithm::{DigestType, DnssecAlgorithm};
+    use crate::dnssec::dnssec_key::{DnssecKey, KSK_FLAGS, ZSK_FLAGS};
+    use crate::dnssec::rrset_signature::signature_data;
+
+    const DAY: u64 = 86400;
+
+    /// Start of the simulated clock.
+    const T0: u64 = 1_700_000_000;
+
+    fn zone() -> DomainName {
+        DomainName::new_from_str("example.")
+    }
+
+    fn dnskey_rr(key: &DnssecKey) -> ResourceRecord {
+        let mut rr = ResourceRecord::new(Rdata::DNSKEY(key.get_dnskey()));
+        rr.set_name(zone());
+        rr.set_ttl(3600);
+        rr
+    }
+
+    /// Builds the DNSKEY RRset of `keys`, signed by `signers` at `now`.
+    fn dnskey_rrset(keys: &[&DnssecKey], signers: &[&DnssecKey], now: u64) -> (Vec<ResourceRecord>, Vec<RRSIGRdata>) {
+        let rrset: Vec<ResourceRecord> = keys.iter().map(|key| dnskey_rr(key)).collect();
+        let rrsigs = signers
+            .iter()
+            .map(|key| {
+                let mut rrsig = RRSIGRdata::new();
+                rrsig.set_type_covered(Rrtype::DNSKEY);
+                rrsig.set_algorithm(key.get_algorithm());
+                rrsig.set_labels(zone().label_count() as u8);
+                rrsig.set_original_ttl(3600);
+                rrsig.set_signature_inception((now - 3600) as u32);
+                rrsig.set_signature_expiration((now + 3600) as u32);
+                rrsig.set_key_tag(key.key_tag());
+                rrsig.set_signer_name(zone());
+                rrsig.set_signature(key.sign(&signature_data(&rrsig, &rrset).unwrap()).unwrap());
+                rrsig
+            })
+            .collect();
+        (rrset, rrsigs)
+    }
+
+    fn state_of(store: &TrustAnchorStore, key: &DnssecKey) -> Option<KeyState> {
+        store
+            .get_keys()
+            .iter()
+            .find(|tracked| same_key(&tracked.get_dnskey(), &key.get_dnskey()))
+            .map(|tracked| tracked.get_state())
+    }
+
+    fn generate(flags: u16) -> DnssecKey {
+        DnssecKey::generate(DnssecAlgorithm::ECDSAP256SHA256, flags).unwrap()
+    }
+
+    #[test]
+    fn key_rollover() {
+        let mut old_ksk = generate(KSK_FLAGS);
+        let new_ksk = generate(KSK_FLAGS);
+        let zsk = generate(ZSK_FLAGS);
+        let mut store = TrustAnchorStore::new(zone(), &[dnskey_rr(&old_ksk)], T0);
+
+        let (rrset, rrsigs) = dnskey_rrset(&[&old_ksk, &zsk], &[&old_ksk], T0);
+        assert_eq!(store.update_at(&rrset, &rrsigs, T0), Ok(false));
+
+        // The new KSK is published, the ZSK is never tracked
+        let now = T0 + DAY;
+        let (rrset, rrsigs) = dnskey_rrset(&[&old_ksk, &new_ksk, &zsk], &[&old_ksk], now);
+        assert_eq!(store.update_at(&rrset, &rrsigs, now), Ok(true));
+        assert_eq!(state_of(&store, &new_ksk), Some(KeyState::AddPend));
+        assert_eq!(state_of(&store, &zsk), None);
+        assert_eq!(store.get_trust_anchors().len(), 1);
+
+        let now = T0 + 30 * DAY;
+        let (rrset, rrsigs) = dnskey_rrset(&[&old_ksk, &new_ksk, &zsk], &[&old_ksk], now);
+        assert_eq!(store.update_at(&rrset, &rrsigs, now), Ok(false));
+        assert_eq!(state_of(&store, &new_ksk), Some(KeyState::AddPend));
+
+        // The add hold-down time has passed
+        let now = T0 + 31 * DAY;
+        let (rrset, rrsigs) = dnskey_rrset(&[&old_ksk, &new_ksk, &zsk], &[&old_ksk], now);
+        assert_eq!(store.update_at(&rrset, &rrsigs, now), Ok(true));
+        assert_eq!(state_of(&store, &new_ksk), Some(KeyState::Valid));
+        assert_eq!(store.get_trust_anchors().len(), 2);
+
+        // The old KSK revokes itself
+        old_ksk.set_revoked(true);
+        let now = T0 + 40 * DAY;
+        let (rrset, rrsigs) = dnskey_rrset(&[&old_ksk, &new_ksk, &zsk], &[&old_ksk, &new_ksk], now);
+        assert_eq!(store.update_at(&rrset, &rrsigs, now), Ok(true));
+        assert_eq!(state_of(&store, &old_ksk), Some(KeyState::Revoked));
+        let anchors = store.get_trust_anchors();
+        assert_eq!(anchors.len(), 1);
+        assert_eq!(anchors[0].get_rdata(), Rdata::DNSKEY(new_ksk.get_dnskey()));
+
+        // The remove hold-down time has passed
+        let now = T0 + 70 * DAY;
+        let (rrset, rrsigs) = dnskey_rrset(&[&old_ksk, &new_ksk, &zsk], &[&old_ksk, &new_ksk], now);
+        assert_eq!(store.update_at(&rrset, &rrsigs, now), Ok(true));
+        assert_eq!(state_of(&store, &old_ksk), Some(KeyState::Removed));
+
+        // The revoked key is no longer published
+        let now = T0 + 71 * DAY;
+        let (rrset, rrsigs) = dnskey_rrset(&[&new_ksk, &zsk], &[&new_ksk], now);
+        assert_eq!(store.update_at(&rrset, &rrsigs, now), Ok(true));
+        assert_eq!(state_of(&store, &old_ksk), None);
+        assert_eq!(store.get_keys().len(), 1);
+    }
+
+    #[test]
+    fn pending_and_missing_keys() {
+        let ksk = generate(KSK_FLAGS);
+        let standby = generate(KSK_FLAGS);
+        let mut store = TrustAnchorStore::new(zone(), &[dnskey_rr(&ksk), dnskey_rr(&standby)], T0);
+        let new_ksk = generate(KSK_FLAGS);
+
+        let now = T0 + DAY;
+        let (rrset, rrsigs) = dnskey_rrset(&[&ksk, &new_ksk], &[&ksk], now);
+        assert_eq!(store.update_at(&rrset, &rrsigs, now), Ok(true));
+        assert_eq!(state_of(&store, &standby), Some(KeyState::Missing));
+        assert_eq!(state_of(&store, &new_ksk), Some(KeyState::AddPend));
+
+        // A pending key that disappears starts over
+        let now = T0 + 2 * DAY;
+        let (rrset, rrsigs) = dnskey_rrset(&[&ksk, &standby], &[&ksk], now);
+        assert_eq!(store.update_at(&rrset, &rrsigs, now), Ok(true));
+        assert_eq!(state_of(&store, &standby), Some(KeyState::Valid));
+        assert_eq!(state_of(&store, &new_ksk), None);
+
+        let now = T0 + 40 * DAY;
+        let (rrset, rrsigs) = dnskey_rrset(&[&ksk, &standby, &new_ksk], &[&ksk], now);
+        store.update_at(&rrset, &rrsigs, now).unwrap();
+        assert_eq!(state_of(&store, &new_ksk), Some(KeyState::AddPend));
+        assert_eq!(store.get_keys()[2].get_first_seen(), now);
+    }
+
+    #[test]
+    fn unverified_rrset() {
+        let ksk = generate(KSK_FLAGS);
+        let attacker = generate(KSK_FLAGS);
+        let mut store = TrustAnchorStore::new(zone(), &[dnskey_rr(&ksk)], T0);
+
+        let (rrset, rrsigs) = dnskey_rrset(&[&ksk, &attacker], &[&attacker], T0);
+        assert_eq!(store.update_at(&rrset, &rrsigs, T0), Err(TrustAnchorError::Unverified));
+
+        // The signatures have expired
+        let (rrset, rrsigs) = dnskey_rrset(&[&ksk, &attacker], &[&ksk], T0);
+        let later = T0 + DAY;
+        assert_eq!(store.update_at(&rrset, &rrsigs, later), Err(TrustAnchorError::Unverified));
+        assert_eq!(store.get_keys().len(), 1);
+
+        // A revoked key that does not sign the RRset is not revoked
+        let mut revoked = generate(KSK_FLAGS);
+        let mut store = TrustAnchorStore::new(zone(), &[dnskey_rr(&ksk), dnskey_rr(&revoked)], T0);
+        revoked.set_revoked(true);
+        let (rrset, rrsigs) = dnskey_rrset(&[&ksk, &revoked], &[&ksk], T0);
+        assert_eq!(store.update_at(&rrset, &rrsigs, T0), Ok(true));
+        assert_eq!(state_of(&store, &revoked), Some(KeyState::Missing));
+    }
+
+    #[test]
+    fn ds_trust_anchor() {
+        let ksk = generate(KSK_FLAGS);
+        let ds = ksk.get_dnskey().to_ds(&zone(), DigestType::SHA256).unwrap();
+        let mut ds_rr = ResourceRecord::new(Rdata::DS(ds));
+        ds_rr.set_name(zone());
+        let mut store = TrustAnchorStore::new(zone(), &[ds_rr.clone()], T0);
+        assert_eq!(store.get_trust_anchors(), vec![ds_rr]);
+
+        let (rrset, rrsigs) = dnskey_rrset(&[&ksk], &[&ksk], T0);
+        assert_eq!(store.update_at(&rrset, &rrsigs, T0), Ok(true));
+        assert_eq!(state_of(&store, &ksk), Some(KeyState::Valid));
+        assert_eq!(store.get_trust_anchors().len(), 1);
+        assert_eq!(store.get_trust_anchors()[0].get_rtype(), Rrtype::DNSKEY);
+    }
+
+    #[test]
+    fn save_and_load() {
+        let ksk = generate(KSK_FLAGS);
+        let new_ksk = generate(KSK_FLAGS);
+        let mut store = TrustAnchorStore::new(zone(), &[dnskey_rr(&ksk)], T0);
+        store.set_add_hold_down(10 * DAY);
+        let (rrset, rrsigs) = dnskey_rrset(&[&ksk, &new_ksk], &[&ksk], T0);
+        store.update_at(&rrset, &rrsigs, T0).unwrap();
+
+        let path = std::env::temp_dir().join(format!("trust_anchor_store_{}.state", std::process::id()));
+        store.save(&path).unwrap();
+        let mut loaded = TrustAnchorStore::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded, store);
+        assert_eq!(loaded.get_add_hold_down(), 10 * DAY);
+
+        // The hold-down time keeps running across runs
+        let now = T0 + 10 * DAY;
+        let (rrset, rrsigs) = dnskey_rrset(&[&ksk, &new_ksk], &[&ksk], now);
+        assert_eq!(loaded.update_at(&rrset, &rrsigs, now), Ok(true));
+        assert_eq!(state_of(&loaded, &new_ksk), Some(KeyState::Valid));
+    }
+
+    #[test]
+    fn invalid_state_file() {
+        assert_eq!(
+            TrustAnchorStore::from_text("; comment\n"),
+            Err(TrustAnchorError::InvalidFormat(1, "no zone in the state file"))
+        );
+        assert_eq!(
+            TrustAnchorStore::from_text("example. HOLDDOWN 1 2\nexample. PENDING 1 2 257 3 13 AAAA\n"),
+            Err(TrustAnchorError::InvalidFormat(2, "unknown key state"))
+        );
+        assert_eq!(
+            TrustAnchorStore::from_text("example. VALID 1 2 257 3 13 AAAA\nother. VALID 1 2 257 3 13 AAAA\n"),
+            Err(TrustAnchorError::InvalidFormat(2, "the lines are not of the same zone"))
+        );
+        assert!(TrustAnchorStore::load("/nonexistent/trust_anchor.state").is_err());
+    }
+}
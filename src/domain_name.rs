@@ -1,45 +1,87 @@
+use rand::{thread_rng, Rng};
+use std::cmp::Ordering;
 use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::str::FromStr;
 use std::string::String;
 
-#[derive(Clone, Default, PartialEq, Debug, Hash, PartialOrd, Ord, Eq)]
-
-// DNS domain name represented as a sequence of labels, where each label consists of
-// a length octet followed by that number of octets.
-// The domain name terminates with the zero length octet for the null label of the root.
+/// Maximum length of a label, in octets.
+const MAX_LABEL_LENGTH: usize = 63;
+
+/// Maximum length of a domain name in wire format, in octets.
+const MAX_NAME_LENGTH: usize = 255;
+
+/// Labels of the domain used for IPv4 reverse mapping.
+const IN_ADDR_ARPA: [&str; 2] = ["in-addr", "arpa"];
+
+/// Labels of the domain used for IPv6 reverse mapping.
+const IP6_ARPA: [&str; 2] = ["ip6", "arpa"];
+
+#[derive(Clone, Default, Debug)]
+/// DNS domain name represented as a sequence of labels, where each label consists of
+/// a length octet followed by that number of octets.
+/// The domain name terminates with the zero length octet for the null label of the root.
+///
+/// Labels are kept as raw octets, so any byte can be part of a label. In the text
+/// form special characters are escaped as `\.`, `\\` or `\DDD`
+/// ([RFC 1035](https://datatracker.ietf.org/doc/html/rfc1035#section-5.1)).
+///
+/// Comparison ignores ASCII case and follows the canonical ordering of
+/// [RFC 4034](https://datatracker.ietf.org/doc/html/rfc4034#section-6.1).
 pub struct DomainName {
-    name: String,
+    /// Labels from left to right, without the null label of the root.
+    labels: Vec<Vec<u8>>,
+    /// Whether the text form of the name ended with a dot.
+    fqdn: bool,
 }
 
 // Methods
 impl DomainName {
-    // Creates a new DomainName with default name
-    //
-    // # Examples
-    // ```
-    // let domain_name = DomainName::new();
-    //
-    // assert_eq!(domain_name.name, String::from(""));
-    // ```
-    //
+    /// Creates a new DomainName with default name
+    ///
+    /// # Examples
+    /// ```
+    /// let domain_name = DomainName::new();
+    ///
+    /// assert_eq!(domain_name.get_name(), String::from(""));
+    /// ```
     pub fn new() -> Self {
-        let domain_name: DomainName = DomainName {
-            name: String::from(""),
-        };
-        domain_name
+        DomainName {
+            labels: Vec::new(),
+            fqdn: false,
+        }
     }
 
     pub fn new_from_string(domain_name: String) -> Self {
-        let domain_name: DomainName = DomainName { 
-            name: domain_name.clone()
-        };
+        let mut new_domain_name = DomainName::new();
+        new_domain_name.set_name(domain_name);
 
-        domain_name
+        new_domain_name
     }
 
     pub fn new_from_str(domain_name: &str) -> Self {
         Self::new_from_string(domain_name.to_string())
     }
 
+    /// Creates a new DomainName from its labels, from left to right.
+    ///
+    /// # Examples
+    /// ```
+    /// let domain_name = DomainName::from_labels(vec![b"www".to_vec(), b"example".to_vec()]).unwrap();
+    ///
+    /// assert_eq!(domain_name.get_name(), String::from("www.example"));
+    /// ```
+    pub fn from_labels(labels: Vec<Vec<u8>>) -> Result<Self, &'static str> {
+        let domain_name = DomainName {
+            labels,
+            fqdn: false,
+        };
+        domain_name.check_lengths()?;
+
+        Ok(domain_name)
+    }
+
     // Given an array of bytes, creates a new DomainName and returns the unused bytes
     // what happens if label is longer than 9 ? check this out
     pub fn from_bytes_no_offset(bytes: &[u8]) -> String {
@@ -60,112 +102,100 @@ impl DomainName {
         name
     }
 
+    /// Given an array of bytes, creates a new DomainName and returns the unused bytes.
+    ///
+    /// Compression pointers are followed inside `full_msg`. Only pointers to
+    /// earlier positions of the message are accepted, so a malformed message
+    /// can not make the parser loop.
     pub fn from_bytes<'a>(
         bytes: &'a [u8],
         full_msg: &'a [u8],
     ) -> Result<(Self, &'a [u8]), &'static str> {
-        let mut first_byte = bytes[0].clone();
-        let mut domain_name_str = "".to_string();
-        let mut no_domain_bytes = bytes;
-       
-        while first_byte != 0 {
-            let bytes_len = no_domain_bytes.len();
-            let msg_compresion = first_byte.clone() >> 6;
-
-            if msg_compresion == 3 {
-                if bytes_len < 2 {
-                    return Err("Format Error");
-                }
-
-                let offset: usize = (((no_domain_bytes[0].clone() as u16) << 8
-                    | no_domain_bytes[1].clone() as u16)
-                    & 0b0011111111111111) as usize;
+        let mut labels: Vec<Vec<u8>> = Vec::new();
+        let mut name_len: usize = 0;
+        let mut current = bytes;
+        let mut no_domain_bytes: Option<&'a [u8]> = None;
+        // Position of `current` inside `full_msg`, known after the first pointer
+        let mut current_offset: Option<usize> = None;
+
+        loop {
+            if current.is_empty() {
+                return Err("Format Error");
+            }
 
-                let domain_name_result =
-                    DomainName::from_bytes(&full_msg[offset..], full_msg);
+            let first_byte = current[0];
 
-                match domain_name_result {
-                    Ok(_) => {}
-                    Err(e) => {
-                        return Err(e);
-                    }
+            if first_byte == 0 {
+                if no_domain_bytes.is_none() {
+                    no_domain_bytes = Some(&current[1..]);
                 }
-
-                let (domain_label, _bytes) = domain_name_result?;
-
-                let label = domain_label.get_name();
-
-                domain_name_str.push_str(&label);
-                domain_name_str.push_str(".");
-                no_domain_bytes = &no_domain_bytes[2..];
-
                 break;
-            } 
-            else {
-                if bytes_len < (first_byte + 1) as usize {
+            }
+
+            if first_byte >> 6 == 3 {
+                if current.len() < 2 {
                     return Err("Format Error");
                 }
 
-                let label_string =
-                    DomainName::from_bytes_no_offset(&no_domain_bytes[..(first_byte + 1) as usize]);
-
-                // Checks label restrictions
-                let check_label = check_label_name(label_string.clone());
+                let offset: usize =
+                    ((((current[0] as u16) << 8) | current[1] as u16) & 0b0011111111111111) as usize;
 
-                if check_label == false {
+                if let Some(position) = current_offset {
+                    if offset >= position {
+                        return Err("Format Error");
+                    }
+                }
+                if offset >= full_msg.len() {
                     return Err("Format Error");
                 }
-                //
 
-                domain_name_str.push_str(&label_string);
-                domain_name_str.push_str(".");
-                no_domain_bytes = &no_domain_bytes[(first_byte + 1) as usize..];
+                if no_domain_bytes.is_none() {
+                    no_domain_bytes = Some(&current[2..]);
+                }
 
-                first_byte = no_domain_bytes[0].clone();
+                current = &full_msg[offset..];
+                current_offset = Some(offset);
+                continue;
             }
-        }
-        if first_byte == 0 {
-            // It means that the domain name is root
-            no_domain_bytes = &no_domain_bytes[1..];
-        }
 
-        if domain_name_str.len() > 0{
-            //remove last value 0
-            domain_name_str.remove(domain_name_str.len() - 1);
-        }
+            if first_byte >> 6 != 0 {
+                // Extended label types are not supported
+                return Err("Format Error");
+            }
 
-        // Check domain name restriction, max 255 octets
-        let initial_bytes_len = bytes.len();
-        let final_bytes_len = no_domain_bytes.len();
+            let label_len = first_byte as usize;
 
-        let domain_name_len = initial_bytes_len - final_bytes_len;
+            if current.len() < label_len + 1 {
+                return Err("Format Error");
+            }
 
-        if domain_name_len > 255 {
-            return Err("Format Error");
+            name_len += label_len + 1;
+            if name_len + 1 > MAX_NAME_LENGTH {
+                return Err("Format Error");
+            }
+
+            labels.push(current[1..label_len + 1].to_vec());
+            current = &current[label_len + 1..];
+            current_offset = current_offset.map(|position| position + label_len + 1);
         }
-        //
 
-        let mut domain_name = DomainName::new();
-        domain_name.set_name(domain_name_str);
+        // The root is only written as "." when it comes alone
+        let fqdn = labels.is_empty();
+        let domain_name = DomainName {
+            labels,
+            fqdn,
+        };
 
-        Ok((domain_name, no_domain_bytes))
+        Ok((domain_name, no_domain_bytes.unwrap()))
     }
 
     // Returns an array of bytes that represents the domain name
     pub fn to_bytes(&self) -> Vec<u8> {
-        let name = self.get_name();
         let mut bytes: Vec<u8> = Vec::new();
-        for word in name.split(".") {
-            // If the name is root or empty break the loop
-            if name == "." || name == ""{
-                break;
-            }
-            let word_length = word.len();
-            bytes.push(word_length as u8);
 
-            for character in word.chars() {
-                bytes.push(character as u8);
-            }
+        for label in self.labels.iter() {
+            bytes.push(label.len() as u8);
+            bytes.extend_from_slice(label);
         }
 
         bytes.push(0u8);
@@ -189,13 +219,390 @@ impl DomainName {
             DomainName::new_from_string(full_host_name)
         }
     }
+
+    /// Parses a name as written in a master file.
+    ///
+    /// `@` stands for `origin`, names ending in a dot are absolute and any
+    /// other name is relative to `origin`.
+    ///
+    /// # Examples
+    /// ```
+    /// let origin = DomainName::new_from_str("example.com.");
+    ///
+    /// assert_eq!(DomainName::from_text("www", &origin).unwrap().to_string(), "www.example.com.");
+    /// assert_eq!(DomainName::from_text("@", &origin).unwrap(), origin);
+    /// ```
+    pub fn from_text(name: &str, origin: &DomainName) -> Result<Self, &'static str> {
+        if name == "@" {
+            return Ok(origin.clone());
+        }
+
+        let mut domain_name = DomainName::from_str(name)?;
+        if domain_name.fqdn {
+            return Ok(domain_name);
+        }
+        if domain_name.labels.is_empty() {
+            return Err("Error: empty domain name.");
+        }
+
+        domain_name.labels.extend(origin.labels.iter().cloned());
+        domain_name.fqdn = origin.fqdn;
+        domain_name.check_lengths()?;
+
+        Ok(domain_name)
+    }
+
+    /// Returns `true` if the name is the root.
+    pub fn is_root(&self) -> bool {
+        self.labels.is_empty()
+    }
+
+    /// Returns the number of labels of the name, without counting the root.
+    ///
+    /// # Examples
+    /// ```
+    /// assert_eq!(DomainName::new_from_str("www.example.com").label_count(), 3);
+    /// assert_eq!(DomainName::new_from_str(".").label_count(), 0);
+    /// ```
+    pub fn label_count(&self) -> usize {
+        self.labels.len()
+    }
+
+    /// Returns the name without its leftmost label, or `None` for the root.
+    ///
+    /// # Examples
+    /// ```
+    /// let domain_name = DomainName::new_from_str("www.example.com");
+    ///
+    /// assert_eq!(domain_name.parent().unwrap(), DomainName::new_from_str("example.com"));
+    /// ```
+    pub fn parent(&self) -> Option<DomainName> {
+        if self.is_root() {
+            return None;
+        }
+
+        Some(DomainName {
+            labels: self.labels[1..].to_vec(),
+            fqdn: self.fqdn,
+        })
+    }
+
+    /// Returns a new name with `label` added at the left of this name.
+    ///
+    /// # Examples
+    /// ```
+    /// let domain_name = DomainName::new_from_str("example.com");
+    ///
+    /// assert_eq!(domain_name.child(b"www").unwrap(), DomainName::new_from_str("www.example.com"));
+    /// ```
+    pub fn child(&self, label: &[u8]) -> Result<DomainName, &'static str> {
+        let mut labels = Vec::with_capacity(self.labels.len() + 1);
+        labels.push(label.to_vec());
+        labels.extend(self.labels.iter().cloned());
+
+        let domain_name = DomainName {
+            labels,
+            fqdn: self.fqdn,
+        };
+        domain_name.check_lengths()?;
+
+        Ok(domain_name)
+    }
+
+    /// Returns `true` if the name is equal to `other` or is below it in the tree.
+    ///
+    /// # Examples
+    /// ```
+    /// let domain_name = DomainName::new_from_str("www.Example.com");
+    ///
+    /// assert!(domain_name.is_subdomain_of(&DomainName::new_from_str("example.COM")));
+    /// assert!(!domain_name.is_subdomain_of(&DomainName::new_from_str("ample.com")));
+    /// ```
+    pub fn is_subdomain_of(&self, other: &DomainName) -> bool {
+        if other.labels.len() > self.labels.len() {
+            return false;
+        }
+
+        self.labels
+            .iter()
+            .rev()
+            .zip(other.labels.iter().rev())
+            .all(|(label, other_label)| label.eq_ignore_ascii_case(other_label))
+    }
+
+    /// Returns the canonical form of the name, with every uppercase US-ASCII
+    /// letter replaced by the corresponding lowercase letter.
+    ///
+    /// [RFC 4034](https://datatracker.ietf.org/doc/html/rfc4034#section-6.2)
+    pub fn to_canonical(&self) -> DomainName {
+        DomainName {
+            labels: self
+                .labels
+                .iter()
+                .map(|label| label.to_ascii_lowercase())
+                .collect(),
+            fqdn: self.fqdn,
+        }
+    }
+
+    /// Returns a copy of the name with the case of every US-ASCII letter
+    /// chosen at random, as described in
+    /// [draft-vixie-dnsext-dns0x20](https://datatracker.ietf.org/doc/html/draft-vixie-dnsext-dns0x20-00).
+    pub fn to_random_case(&self) -> DomainName {
+        let mut rng = thread_rng();
+
+        DomainName {
+            labels: self
+                .labels
+                .iter()
+                .map(|label| {
+                    label
+                        .iter()
+                        .map(|byte| match rng.gen::<bool>() {
+                            true => byte.to_ascii_uppercase(),
+                            false => byte.to_ascii_lowercase(),
+                        })
+                        .collect()
+                })
+                .collect(),
+            fqdn: self.fqdn,
+        }
+    }
+
+    /// Returns `true` if any label of the name has an uppercase US-ASCII letter.
+    pub fn has_uppercase(&self) -> bool {
+        self.labels
+            .iter()
+            .any(|label| label.iter().any(u8::is_ascii_uppercase))
+    }
+
+    /// Returns `true` if both names have the same labels, byte for byte.
+    ///
+    /// Unlike `==`, the case of the letters must also be the same.
+    pub fn eq_case_sensitive(&self, other: &DomainName) -> bool {
+        self.labels == other.labels
+    }
+
+    /// Returns the name used to look up the host name of an address.
+    ///
+    /// IPv4 addresses are mapped to `in-addr.arpa` with their octets in
+    /// reverse order ([RFC 1035](https://datatracker.ietf.org/doc/html/rfc1035#section-3.5)).
+    /// IPv6 addresses are mapped to `ip6.arpa` with one label for each nibble,
+    /// starting with the least significant one
+    /// ([RFC 3596](https://datatracker.ietf.org/doc/html/rfc3596#section-2.5)).
+    ///
+    /// # Examples
+    /// ```
+    /// let domain_name = DomainName::from_ip("192.0.2.1".parse().unwrap());
+    ///
+    /// assert_eq!(domain_name.get_name(), "1.2.0.192.in-addr.arpa");
+    /// ```
+    pub fn from_ip(ip_addr: IpAddr) -> Self {
+        let (mut labels, suffix): (Vec<Vec<u8>>, &[&str]) = match ip_addr {
+            IpAddr::V4(ipv4) => (
+                ipv4.octets()
+                    .iter()
+                    .rev()
+                    .map(|octet| octet.to_string().into_bytes())
+                    .collect(),
+                &IN_ADDR_ARPA,
+            ),
+            IpAddr::V6(ipv6) => (
+                ipv6.octets()
+                    .iter()
+                    .rev()
+                    .flat_map(|octet| [octet & 0x0f, octet >> 4])
+                    .map(|nibble| format!("{:x}", nibble).into_bytes())
+                    .collect(),
+                &IP6_ARPA,
+            ),
+        };
+        labels.extend(suffix.iter().map(|label| label.as_bytes().to_vec()));
+
+        DomainName {
+            labels,
+            fqdn: false,
+        }
+    }
+
+    /// Returns the address encoded in a name of `in-addr.arpa` or `ip6.arpa`.
+    ///
+    /// This is the inverse of `from_ip`. The name must have a label for every
+    /// octet or nibble of the address, so names of partial networks are rejected.
+    ///
+    /// # Examples
+    /// ```
+    /// let domain_name = DomainName::new_from_str("1.2.0.192.in-addr.arpa");
+    ///
+    /// assert_eq!(domain_name.to_ip().unwrap(), "192.0.2.1".parse::<IpAddr>().unwrap());
+    /// ```
+    pub fn to_ip(&self) -> Result<IpAddr, &'static str> {
+        let has_suffix = |suffix: &[&str]| {
+            self.labels.len() >= suffix.len()
+                && self.labels[self.labels.len() - suffix.len()..]
+                    .iter()
+                    .zip(suffix)
+                    .all(|(label, suffix_label)| label.eq_ignore_ascii_case(suffix_label.as_bytes()))
+        };
+
+        if has_suffix(&IN_ADDR_ARPA) {
+            if self.labels.len() != 4 + IN_ADDR_ARPA.len() {
+                return Err("Format Error: reverse name must have four octets");
+            }
+            let mut octets = [0u8; 4];
+            for (octet, label) in octets.iter_mut().rev().zip(&self.labels) {
+                let text = std::str::from_utf8(label).map_err(|_| "Format Error: invalid octet label")?;
+                if text.is_empty() || text.len() > 3 || !text.bytes().all(|byte| byte.is_ascii_digit()) {
+                    return Err("Format Error: invalid octet label");
+                }
+                *octet = text.parse().map_err(|_| "Format Error: invalid octet label")?;
+            }
+            return Ok(IpAddr::V4(Ipv4Addr::from(octets)));
+        }
+
+        if has_suffix(&IP6_ARPA) {
+            if self.labels.len() != 32 + IP6_ARPA.len() {
+                return Err("Format Error: reverse name must have thirty-two nibbles");
+            }
+            let mut octets = [0u8; 16];
+            for (index, label) in self.labels[..32].iter().enumerate() {
+                let nibble = match label.as_slice() {
+                    [digit] => (*digit as char).to_digit(16).ok_or("Format Error: invalid nibble label")?,
+                    _ => return Err("Format Error: invalid nibble label"),
+                } as u8;
+                let octet = &mut octets[15 - index / 2];
+                if index % 2 == 0 {
+                    *octet |= nibble;
+                } else {
+                    *octet |= nibble << 4;
+                }
+            }
+            return Ok(IpAddr::V6(Ipv6Addr::from(octets)));
+        }
+
+        Err("Format Error: name is not under in-addr.arpa or ip6.arpa")
+    }
+
+    /// Splits a name in text form into its labels, resolving the escape sequences.
+    ///
+    /// When `strict` is `false` invalid escapes are kept as they are, so any
+    /// string can be stored in a DomainName.
+    fn parse_labels(name: &str, strict: bool) -> Result<(Vec<Vec<u8>>, bool), &'static str> {
+        if name.is_empty() {
+            return Ok((Vec::new(), false));
+        }
+        if name == "." {
+            return Ok((Vec::new(), true));
+        }
+
+        let bytes = name.as_bytes();
+        let mut labels: Vec<Vec<u8>> = Vec::new();
+        let mut label: Vec<u8> = Vec::new();
+        let mut fqdn = false;
+        let mut i = 0;
+
+        while i < bytes.len() {
+            match bytes[i] {
+                b'\\' => {
+                    let digits = &bytes[i + 1..bytes.len().min(i + 4)];
+                    if digits.len() == 3 && digits.iter().all(|b| b.is_ascii_digit()) {
+                        let value = digits
+                            .iter()
+                            .fold(0u16, |acc, b| acc * 10 + (b - b'0') as u16);
+                        if value > 255 {
+                            if strict {
+                                return Err("Error: invalid escape sequence in domain name.");
+                            }
+                            label.push(b'\\');
+                            i += 1;
+                            continue;
+                        }
+                        label.push(value as u8);
+                        i += 4;
+                    } else if i + 1 < bytes.len() && !bytes[i + 1].is_ascii_digit() {
+                        label.push(bytes[i + 1]);
+                        i += 2;
+                    } else {
+                        if strict {
+                            return Err("Error: invalid escape sequence in domain name.");
+                        }
+                        label.push(b'\\');
+                        i += 1;
+                    }
+                }
+                b'.' => {
+                    if i == bytes.len() - 1 {
+                        fqdn = true;
+                    }
+                    labels.push(label);
+                    label = Vec::new();
+                    i += 1;
+                }
+                byte => {
+                    label.push(byte);
+                    i += 1;
+                }
+            }
+        }
+
+        if !fqdn {
+            labels.push(label);
+        }
+
+        Ok((labels, fqdn))
+    }
+
+    /// Checks the length restrictions of RFC 1035: labels of at most 63
+    /// octets and names of at most 255 octets in wire format.
+    fn check_lengths(&self) -> Result<(), &'static str> {
+        let mut name_len = 1;
+
+        for label in self.labels.iter() {
+            if label.is_empty() {
+                return Err("Error: Empty label is only allowed at the end of a hostname.");
+            }
+            if label.len() > MAX_LABEL_LENGTH {
+                return Err("Error: label longer than 63 octets.");
+            }
+            name_len += label.len() + 1;
+        }
+
+        if name_len > MAX_NAME_LENGTH {
+            return Err("Error: domain name longer than 255 octets.");
+        }
+
+        Ok(())
+    }
+
+    /// Writes a label in text form, escaping the characters with special meaning.
+    fn write_label(label: &[u8], formatter: &mut fmt::Formatter) -> fmt::Result {
+        for byte in label {
+            match *byte {
+                b'.' | b'\\' | b'"' | b'(' | b')' | b';' => {
+                    write!(formatter, "\\{}", *byte as char)?;
+                }
+                0x21..=0x7E => {
+                    write!(formatter, "{}", *byte as char)?;
+                }
+                _ => {
+                    write!(formatter, "\\{:03}", byte)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
 }
 
 // Setters Domain Name
 impl DomainName {
     // Sets the name attribute with a value
     pub fn set_name(&mut self, name: String) {
-        self.name = name;
+        let (labels, fqdn) = DomainName::parse_labels(&name, false)
+            .expect("non strict parsing does not fail");
+
+        self.labels = labels;
+        self.fqdn = fqdn;
     }
 }
 
@@ -203,14 +610,98 @@ impl DomainName {
 impl DomainName {
     // Gets the name attribute from a DomainName struct
     pub fn get_name(&self) -> String {
-        self.name.clone()
+        self.to_string()
+    }
+
+    /// Gets the labels of the name, from left to right.
+    pub fn get_labels(&self) -> Vec<Vec<u8>> {
+        self.labels.clone()
     }
 }
 
 impl fmt::Display for DomainName {
     fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-        let name = self.get_name();
-        formatter.write_str(&name)
+        if self.labels.is_empty() {
+            if self.fqdn {
+                formatter.write_str(".")?;
+            }
+            return Ok(());
+        }
+
+        for (i, label) in self.labels.iter().enumerate() {
+            if i > 0 {
+                formatter.write_str(".")?;
+            }
+            DomainName::write_label(label, formatter)?;
+        }
+
+        if self.fqdn {
+            formatter.write_str(".")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for DomainName {
+    type Err = &'static str;
+
+    /// Parses a domain name in text form, resolving the `\X` and `\DDD` escapes.
+    ///
+    /// # Examples
+    /// ```
+    /// let domain_name: DomainName = "a\\.b.example.com.".parse().unwrap();
+    ///
+    /// assert_eq!(domain_name.label_count(), 3);
+    /// ```
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        let (labels, fqdn) = DomainName::parse_labels(name, true)?;
+        let domain_name = DomainName { labels, fqdn };
+        domain_name.check_lengths()?;
+
+        Ok(domain_name)
+    }
+}
+
+impl PartialEq for DomainName {
+    fn eq(&self, other: &Self) -> bool {
+        self.labels.len() == other.labels.len()
+            && self
+                .labels
+                .iter()
+                .zip(other.labels.iter())
+                .all(|(label, other_label)| label.eq_ignore_ascii_case(other_label))
+    }
+}
+
+impl Eq for DomainName {}
+
+impl Hash for DomainName {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.labels.len().hash(state);
+        for label in self.labels.iter() {
+            label.to_ascii_lowercase().hash(state);
+        }
+    }
+}
+
+impl PartialOrd for DomainName {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for DomainName {
+    /// Canonical DNS name order: names are compared label by label starting
+    /// from the rightmost one, each label as a lowercase octet string.
+    ///
+    /// [RFC 4034](https://datatracker.ietf.org/doc/html/rfc4034#section-6.1)
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.labels
+            .iter()
+            .rev()
+            .map(|label| label.to_ascii_lowercase())
+            .cmp(other.labels.iter().rev().map(|label| label.to_ascii_lowercase()))
     }
 }
 
@@ -261,25 +752,28 @@ mod domain_name_test {
     use super::DomainName;
     use super::check_label_name;
     use super::domain_validity_syntax;
+    use std::net::IpAddr;
 
     #[test]
     fn constructor_test() {
         let domain_name = DomainName::new();
 
-        assert_eq!(domain_name.name, String::from(""));
+        assert_eq!(domain_name.get_name(), String::from(""));
+        assert!(domain_name.is_root());
     }
 
     #[test]
     fn new_from_str_test() {
         let domain_name = DomainName::new_from_str("example.com");
-        assert_eq!(domain_name.name, String::from("example.com"));
+        assert_eq!(domain_name.get_name(), String::from("example.com"));
+        assert_eq!(domain_name.get_labels(), vec![b"example".to_vec(), b"com".to_vec()]);
     }
 
     #[test]
     fn set_and_get_name_test() {
         let mut domain_name = DomainName::new();
 
-        assert_eq!(domain_name.name, String::from(""));
+        assert_eq!(domain_name.get_name(), String::from(""));
 
         domain_name.set_name(String::from("test.test2.com."));
 
@@ -340,6 +834,55 @@ mod domain_name_test {
         assert_eq!(domain_name.get_name(), String::from("XX.LCS.MIT.EDU."));
     }
 
+    #[test]
+    fn from_text_test() {
+        let origin = DomainName::new_from_str("example.com.");
+
+        let relative = DomainName::from_text("www", &origin).unwrap();
+        assert_eq!(relative.to_string(), "www.example.com.");
+
+        let absolute = DomainName::from_text("www.example.org.", &origin).unwrap();
+        assert_eq!(absolute.to_string(), "www.example.org.");
+
+        let at = DomainName::from_text("@", &origin).unwrap();
+        assert_eq!(at, origin);
+
+        let escaped = DomainName::from_text("a\\.b", &origin).unwrap();
+        assert_eq!(escaped.label_count(), 3);
+
+        assert!(DomainName::from_text("a..b", &origin).is_err());
+        assert!(DomainName::from_text(&"a".repeat(64), &origin).is_err());
+    }
+
+    #[test]
+    fn from_ip_test() {
+        let ipv4 = DomainName::from_ip("192.0.2.1".parse().unwrap());
+        assert_eq!(ipv4.get_name(), "1.2.0.192.in-addr.arpa");
+
+        let ipv6 = DomainName::from_ip("2001:db8::567:89ab".parse().unwrap());
+        assert_eq!(
+            ipv6.get_name(),
+            "b.a.9.8.7.6.5.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.ip6.arpa"
+        );
+    }
+
+    #[test]
+    fn to_ip_test() {
+        for address in ["192.0.2.1", "0.0.0.0", "2001:db8::567:89ab", "::1"] {
+            let ip_addr: IpAddr = address.parse().unwrap();
+            assert_eq!(DomainName::from_ip(ip_addr).to_ip().unwrap(), ip_addr);
+        }
+
+        let upper_case = DomainName::new_from_str("1.2.0.192.IN-ADDR.ARPA.");
+        assert_eq!(upper_case.to_ip().unwrap(), "192.0.2.1".parse::<IpAddr>().unwrap());
+
+        assert!(DomainName::new_from_str("2.0.192.in-addr.arpa").to_ip().is_err());
+        assert!(DomainName::new_from_str("256.2.0.192.in-addr.arpa").to_ip().is_err());
+        assert!(DomainName::new_from_str("+1.2.0.192.in-addr.arpa").to_ip().is_err());
+        assert!(DomainName::new_from_str("1.0.ip6.arpa").to_ip().is_err());
+        assert!(DomainName::new_from_str("www.example.com").to_ip().is_err());
+    }
+
     #[test]
     fn fmt_test() {
         let mut domain_name = DomainName::new();
@@ -351,7 +894,6 @@ mod domain_name_test {
     }
 
     #[test]
-    #[ignore = "the domain name should be the root"]
     fn root_domain_test(){
         let domain_name = DomainName::new_from_str(".");
         let bytes = domain_name.to_bytes();
@@ -360,6 +902,129 @@ mod domain_name_test {
         assert_eq!(new_domain_name.0.get_name(), String::from(".") );
     }
 
+    #[test]
+    fn from_bytes_pointer_loop() {
+        let bytes_test: Vec<u8> = vec![3, 70, 79, 79, 192, 0];
+
+        assert!(DomainName::from_bytes(&bytes_test[4..], &bytes_test).is_err());
+        assert!(DomainName::from_bytes(&bytes_test, &bytes_test).is_err());
+    }
+
+    #[test]
+    fn from_bytes_binary_label() {
+        let bytes_test: Vec<u8> = vec![3, 0, 46, 255, 3, 99, 111, 109, 0];
+        let (domain_name, no_domain_bytes) = DomainName::from_bytes(&bytes_test, &bytes_test).unwrap();
+
+        assert!(no_domain_bytes.is_empty());
+        assert_eq!(domain_name.get_labels(), vec![vec![0, 46, 255], b"com".to_vec()]);
+        assert_eq!(domain_name.get_name(), String::from("\\000\\.\\255.com"));
+        assert_eq!(domain_name.to_bytes(), bytes_test);
+    }
+
+    #[test]
+    fn from_str_escapes() {
+        let domain_name: DomainName = "a\\.b.\\065bc.com.".parse().unwrap();
+
+        assert_eq!(
+            domain_name.get_labels(),
+            vec![b"a.b".to_vec(), b"Abc".to_vec(), b"com".to_vec()]
+        );
+        assert_eq!(domain_name.to_string(), String::from("a\\.b.Abc.com."));
+
+        let back_slash: DomainName = "a\\\\b".parse().unwrap();
+        assert_eq!(back_slash.get_labels(), vec![b"a\\b".to_vec()]);
+        assert_eq!(back_slash.to_string(), String::from("a\\\\b"));
+    }
+
+    #[test]
+    fn from_str_errors() {
+        assert!("a..b".parse::<DomainName>().is_err());
+        assert!("a\\256b".parse::<DomainName>().is_err());
+        assert!("a\\2".parse::<DomainName>().is_err());
+
+        let long_label = "a".repeat(64);
+        assert!(long_label.parse::<DomainName>().is_err());
+
+        let long_name = vec!["a".repeat(63); 4].join(".");
+        assert!(long_name.parse::<DomainName>().is_err());
+    }
+
+    #[test]
+    fn case_insensitive_equality() {
+        let domain_name = DomainName::new_from_str("WWW.Example.COM");
+        let other_domain_name = DomainName::new_from_str("www.example.com.");
+
+        assert_eq!(domain_name, other_domain_name);
+
+        let mut set = std::collections::HashSet::new();
+        set.insert(domain_name);
+        assert!(set.contains(&other_domain_name));
+    }
+
+    #[test]
+    fn parent_and_child() {
+        let domain_name = DomainName::new_from_str("www.example.com");
+
+        let parent = domain_name.parent().unwrap();
+        assert_eq!(parent.get_name(), String::from("example.com"));
+        assert_eq!(parent.child(b"www").unwrap(), domain_name);
+
+        assert!(DomainName::new_from_str(".").parent().is_none());
+        assert!(parent.child(&[b'a'; 64]).is_err());
+    }
+
+    #[test]
+    fn is_subdomain_of_test() {
+        let domain_name = DomainName::new_from_str("a.b.example.com");
+
+        assert!(domain_name.is_subdomain_of(&DomainName::new_from_str("example.com")));
+        assert!(domain_name.is_subdomain_of(&DomainName::new_from_str("A.B.EXAMPLE.COM")));
+        assert!(domain_name.is_subdomain_of(&DomainName::new_from_str(".")));
+        assert!(!domain_name.is_subdomain_of(&DomainName::new_from_str("ample.com")));
+        assert!(!DomainName::new_from_str("com").is_subdomain_of(&domain_name));
+    }
+
+    #[test]
+    fn label_count_test() {
+        assert_eq!(DomainName::new_from_str("a.b.example.com.").label_count(), 4);
+        assert_eq!(DomainName::new_from_str("*.example.com").label_count(), 3);
+        assert_eq!(DomainName::new().label_count(), 0);
+    }
+
+    #[test]
+    fn to_canonical_test() {
+        let domain_name = DomainName::new_from_str("WWW.Example.COM");
+
+        assert_eq!(domain_name.to_canonical().get_name(), String::from("www.example.com"));
+        assert_eq!(
+            domain_name.to_canonical().to_bytes(),
+            DomainName::new_from_str("www.example.com").to_bytes()
+        );
+    }
+
+    #[test]
+    fn canonical_ordering() {
+        // Example from RFC 4034 section 6.1
+        let names = vec![
+            "example",
+            "a.example",
+            "yljkjljk.a.example",
+            "Z.a.example",
+            "zABC.a.EXAMPLE",
+            "z.example",
+            "\\001.z.example",
+            "*.z.example",
+            "\\200.z.example",
+        ];
+        let ordered: Vec<DomainName> = names.iter().map(|name| DomainName::new_from_str(name)).collect();
+
+        let mut sorted = ordered.clone();
+        sorted.reverse();
+        sorted.sort();
+
+        assert_eq!(sorted, ordered);
+    }
+
     #[test]
     fn check_label_name_empty_label() {
         let cln_empty_str = check_label_name(String::from(""));
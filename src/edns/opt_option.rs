@@ -42,6 +42,13 @@ impl OptOption {
                     opt_data: OptionData::ZoneVersion(ZoneversionOptData::new())
                 }
             },
+            OptionCode::TCPKEEPALIVE => {
+                OptOption {
+                    option_code,
+                    option_len: 0,
+                    opt_data: OptionData::TcpKeepalive(None)
+                }
+            },
             _ => {
                 OptOption {
                     option_code,
@@ -105,6 +112,17 @@ pub mod option_data {
                                      Figure 1 */
         Padding(Vec<u8>),
         ZoneVersion(ZoneversionOptData),
+        /*
+        edns-tcp-keepalive [RFC 7828]. The TIMEOUT is an idle timeout in
+        units of 100 milliseconds. It is omitted in queries and present in
+        responses.
+
+                    0                       8                      16
+                    +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+                    |                    TIMEOUT                    |
+                    +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+        */
+        TcpKeepalive(Option<u16>),
         Unknown(Vec<u8>),
     }
 
@@ -123,6 +141,9 @@ pub mod option_data {
                 OptionData::ZoneVersion(zoneversion) => {
                     zoneversion.to_bytes()
                 },
+                OptionData::TcpKeepalive(timeout) => {
+                    timeout.map(|timeout| timeout.to_be_bytes().to_vec()).unwrap_or_default()
+                },
                 OptionData::Unknown(data) => {
                     data.to_vec()
                 }
@@ -148,6 +169,13 @@ pub mod option_data {
                     let zoneversion = ZoneversionOptData::from_bytes(&bytes).map_err(|_| "Error parsing EDE")?;
                     Ok(OptionData::ZoneVersion(zoneversion))
                 },
+                OptionCode::TCPKEEPALIVE => {
+                    match bytes.len() {
+                        0 => Ok(OptionData::TcpKeepalive(None)),
+                        2 => Ok(OptionData::TcpKeepalive(Some(u16::from_be_bytes([bytes[0], bytes[1]])))),
+                        _ => Err("Error parsing TCP keepalive"),
+                    }
+                },
                 _ => Ok(OptionData::Unknown(bytes))
             }
         }
@@ -167,6 +195,8 @@ pub mod option_code {
         // added for rf8914
         EDE,
         N3U,
+        // added for rfc7828
+        TCPKEEPALIVE,
         ZONEVERSION,
         UNKNOWN(u16),
     }
@@ -178,6 +208,7 @@ pub mod option_code {
                 OptionCode::DAU => 5,
                 OptionCode::DHU => 6,
                 OptionCode::N3U => 7,
+                OptionCode::TCPKEEPALIVE => 11,
                 OptionCode::PADDING => 12,
                 OptionCode::EDE => 15,
                 OptionCode::ZONEVERSION => 19,
@@ -193,6 +224,7 @@ pub mod option_code {
                 5 => OptionCode::DAU,
                 6 => OptionCode::DHU,
                 7 => OptionCode::N3U,
+                11 => OptionCode::TCPKEEPALIVE,
                 12 => OptionCode::PADDING,
                 15 => OptionCode::EDE,
                 19 => OptionCode::ZONEVERSION,
@@ -210,6 +242,7 @@ pub mod option_code {
                 "N3U" => OptionCode::N3U,
                 "EDE" => OptionCode::EDE,
                 "PADDING" => OptionCode::PADDING,
+                "TCPKEEPALIVE" => OptionCode::TCPKEEPALIVE,
                 "ZONEVERSION" => OptionCode::ZONEVERSION,
                 _ => OptionCode::UNKNOWN(0),
             }
@@ -231,6 +264,7 @@ pub mod option_code {
                 OptionCode::N3U => "N3U",
                 OptionCode::EDE => "EDE",
                 OptionCode::PADDING => "PADDING",
+                OptionCode::TCPKEEPALIVE => "TCPKEEPALIVE",
                 OptionCode::ZONEVERSION => "ZONEVERSION",
                 OptionCode::UNKNOWN(_) => "UNKNOWN",
             })
@@ -302,6 +336,19 @@ mod option_data_tests {
 
     }
 
+    #[test]
+    fn test_option_data_tcp_keepalive() {
+        for timeout in [None, Some(1200)] {
+            let option_data = OptionData::TcpKeepalive(timeout);
+            let serialized = option_data.to_bytes();
+            let rebuilt = OptionData::from_bytes_with_opt_type(serialized.clone(), OptionCode::from(11))
+                .expect("TCP keepalive reconstruction failed");
+            assert_eq!(option_data, rebuilt);
+            assert_eq!(serialized.len(), if timeout.is_some() { 2 } else { 0 });
+        }
+        assert!(OptionData::from_bytes_with_opt_type(vec![0], OptionCode::TCPKEEPALIVE).is_err());
+    }
+
     #[test]
     fn test_option_data_unknown() {
         let unknown_bytes = vec![0xde, 0xad, 0xbe, 0xef];
@@ -2,11 +2,15 @@ pub mod client;
 pub mod resolver_cache;
 pub mod dns_cache;
 pub mod domain_name;
+pub mod der;
 pub mod message;
 pub mod async_resolver;
 pub mod truncated_dns_message;
 pub mod tsig;
 pub mod dnssec;
+pub mod zone;
+pub mod server;
+pub mod testing;
 pub mod edns{
     pub mod opt_option;
     pub mod options {
@@ -1,11 +1,12 @@
-use std::{time::Duration, net::IpAddr};
+use std::{time::Duration, net::{IpAddr, SocketAddr}};
 use std::io::Error as IoError;
 use std::io::ErrorKind;
+use std::sync::Arc;
 use dns_rust::{
     async_resolver::{
             config::ResolverConfig, AsyncResolver, server_info::ServerInfo
         }, client::{
-        client_connection::ClientConnection, client_error::ClientError, tcp_connection::ClientTCPConnection, udp_connection::ClientUDPConnection, Client}, domain_name::DomainName};
+        client_error::ClientError, tcp_connection::ClientTCPConnection, udp_connection::ClientUDPConnection, Client}, domain_name::DomainName};
 
 use clap::*;
 use rand::{thread_rng, Rng};
@@ -13,6 +14,9 @@ use dns_rust::async_resolver::lookup_response::LookupResponse;
 use dns_rust::client::client_connection::ConnectionProtocol;
 use dns_rust::client::client_security::ClientSecurity;
 use dns_rust::client::tls_connection::ClientTLSConnection;
+use dns_rust::client::https_connection::ClientHTTPSConnection;
+use dns_rust::client::quic_connection::ClientQUICConnection;
+use dns_rust::dnssec::trust_anchor::load_trust_anchor;
 use dns_rust::edns::opt_option::option_code::OptionCode;
 use dns_rust::message::DnsMessage;
 use dns_rust::message::rclass::Rclass;
@@ -74,10 +78,18 @@ struct ClientArgs {
     #[arg(long, default_value = "false")]
     noedns: bool,
 
-    /// Transport protocol, options: "UDP", "TCP", "TLS".
+    /// Transport protocol, options: "UDP", "TCP", "TLS", "HTTPS", "QUIC".
     #[arg(long, default_value_t = String::from("UDP"))]
     protocol: String,
 
+    /// Server port, by default the one of the transport protocol
+    #[arg(long)]
+    port: Option<u16>,
+
+    /// Local address the query is sent from
+    #[arg(long)]
+    bind: Option<SocketAddr>,
+
     /// TSIG arguments key, algorithm, fudge, time_signed, key_name, mac_request
     #[arg(long, value_parser = TsigArgs::from_str)]
     tsig: Option<TsigArgs>,
@@ -132,9 +144,18 @@ struct ResolverArgs {
     /// Query class
     #[arg(long, default_value_t = String::from("IN"))]
     qclass: String,
-    /// Protocol
+    /// Protocol, options: "UDP", "TCP", "HTTPS"
     #[arg(long, default_value_t = String::from("UDP"))]
     protocol: String,
+    /// Resolves the name starting from the root servers instead of asking the recursive servers
+    #[arg(long, default_value = "false")]
+    recursive: bool,
+    /// Validates the answer with DNSSEC
+    #[arg(long, default_value = "false")]
+    dnssec: bool,
+    /// File with the DS or DNSKEY records to trust instead of the built-in root anchor
+    #[arg(long)]
+    trust_anchor: Option<String>,
     /// Recursive Servers
     nameserver: Vec<IpAddr>,
 }
@@ -150,6 +171,7 @@ fn parse_edns_options(options: Vec<String>) -> Vec<OptionCode> {
                     "ede" => Some(OptionCode::EDE),
                     "padding" => Some(OptionCode::PADDING),
                     "zoneversion" => Some(OptionCode::ZONEVERSION),
+                    "keepalive" => Some(OptionCode::TCPKEEPALIVE),
                     _ => {
                         eprintln!("Unknown option: {}", opt);
                         None
@@ -218,20 +240,42 @@ pub async fn main() {
             // match tcp to set a client
             let response = match client_args.protocol.as_str() {
                 "UDP" => {
-                    let conn = ClientUDPConnection::new_default(addr, Duration::from_secs(10));
-                    let mut client = Client::new(conn);
+                    let mut conn = ClientUDPConnection::new_default(addr, Duration::from_secs(10));
+                    conn.set_port(client_args.port.unwrap_or(conn.get_port()));
+                    conn.set_bind_addr(client_args.bind);
+                    let mut client = Client::new(Arc::new(conn), Duration::from_secs(10));
                     client.set_dns_query(dns_query_message);
                     client.send_query().await
                 }
                 "TCP" => {
-                    let conn = ClientTCPConnection::new_default(addr, Duration::from_secs(10));
-                    let mut client = Client::new(conn);
+                    let mut conn = ClientTCPConnection::new_default(addr, Duration::from_secs(10));
+                    conn.set_port(client_args.port.unwrap_or(conn.get_port()));
+                    conn.set_bind_addr(client_args.bind);
+                    let mut client = Client::new(Arc::new(conn), Duration::from_secs(10));
                     client.set_dns_query(dns_query_message);
                     client.send_query().await
                 },
                 "TLS" => {
-                    let conn = ClientTLSConnection::new_default(addr, Duration::from_secs(10));
-                    let mut client = Client::new(conn);
+                    let mut conn = ClientTLSConnection::new_default(addr, Duration::from_secs(10));
+                    conn.set_port(client_args.port.unwrap_or(conn.get_port()));
+                    conn.set_bind_addr(client_args.bind);
+                    let mut client = Client::new(Arc::new(conn), Duration::from_secs(10));
+                    client.set_dns_query(dns_query_message);
+                    client.send_query().await
+                },
+                "HTTPS" => {
+                    let mut conn = ClientHTTPSConnection::new_default(addr, Duration::from_secs(10));
+                    conn.set_port(client_args.port.unwrap_or(conn.get_port()));
+                    conn.set_bind_addr(client_args.bind);
+                    let mut client = Client::new(Arc::new(conn), Duration::from_secs(10));
+                    client.set_dns_query(dns_query_message);
+                    client.send_query().await
+                },
+                "QUIC" => {
+                    let mut conn = ClientQUICConnection::new_default(addr, Duration::from_secs(10));
+                    conn.set_port(client_args.port.unwrap_or(conn.get_port()));
+                    conn.set_bind_addr(client_args.bind);
+                    let mut client = Client::new(Arc::new(conn), Duration::from_secs(10));
                     client.set_dns_query(dns_query_message);
                     client.send_query().await
                 },
@@ -255,14 +299,27 @@ pub async fn main() {
             if resolver_args.nameserver.len() > 0 {
                 let mut nameservers = Vec::new();
                 for ip_addr in resolver_args.nameserver.clone() {
-                    let udp_conn = ClientUDPConnection::new_default(ip_addr, Duration::from_secs(timeout));
-                    let tcp_conn = ClientTCPConnection::new_default(ip_addr, Duration::from_secs(timeout));
-                    let server_info = ServerInfo::new_with_ip(ip_addr, udp_conn, tcp_conn);
+                    let server_info = ServerInfo::new_from_addr_with_default_size(ip_addr, Duration::from_secs(timeout));
                     nameservers.push(server_info);
                 }
                 config.set_name_servers(nameservers);
             }
-            println!("Resolver pre loaded with nameservers: {:?}", config.get_name_servers().iter().map(|server| server.get_ip_addr()).collect::<Vec<IpAddr>>());
+            config.set_recursive_mode(resolver_args.recursive);
+            config.set_dnssec_validation(resolver_args.dnssec);
+            if let Some(path) = &resolver_args.trust_anchor {
+                match load_trust_anchor(path) {
+                    Ok(trust_anchors) => config.set_trust_anchors(trust_anchors),
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        return;
+                    }
+                }
+            }
+            if resolver_args.recursive {
+                println!("Resolver starting from root hints: {:?}", config.get_root_hints().iter().map(|(_, ip_addr)| *ip_addr).collect::<Vec<IpAddr>>());
+            } else {
+                println!("Resolver pre loaded with nameservers: {:?}", config.get_name_servers().iter().map(|server| server.get_ip_addr()).collect::<Vec<IpAddr>>());
+            }
             let mut resolver = AsyncResolver::new(config);
             let response = resolver.lookup(
                 resolver_args.domain_name.as_str(),
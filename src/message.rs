@@ -6,6 +6,7 @@ pub mod rrtype;
 pub mod rclass;
 pub mod rcode;
 pub mod rrset;
+pub mod compression;
 
 use crate::message::rclass::Rclass;
 use crate::message::rrtype::Rrtype;
@@ -14,6 +15,7 @@ use crate::domain_name::DomainName;
 use crate::message::header::Header;
 use crate::message::question::Question;
 use crate::message::resource_record::ResourceRecord;
+use crate::message::compression::CompressionTable;
 use crate::message::rdata::Rdata;
 use crate::message::rdata::opt_rdata::OptRdata;
 use crate::tsig;
@@ -590,32 +592,23 @@ impl DnsMessage {
     /// assert_eq!(arcount, 0);
     /// ```
     pub fn to_bytes(&self) -> Vec<u8> {
-        let mut header_bytes = self.get_header().to_bytes().to_vec();
-        let mut question_bytes = self.get_question().to_bytes();
-        let mut answer_bytes: Vec<u8> = Vec::new();
-        let mut authority_bytes: Vec<u8> = Vec::new();
-        let mut additional_bytes: Vec<u8> = Vec::new();
+        let mut dns_msg_bytes = self.get_header().to_bytes().to_vec();
+        let mut compression_table = CompressionTable::new();
+
+        self.get_question().to_bytes_compressed(&mut dns_msg_bytes, &mut compression_table);
 
         for answer in self.get_answer() {
-            answer_bytes.append(&mut answer.to_bytes());
+            answer.to_bytes_compressed(&mut dns_msg_bytes, &mut compression_table);
         }
 
         for authority in self.get_authority() {
-            authority_bytes.append(&mut authority.to_bytes());
+            authority.to_bytes_compressed(&mut dns_msg_bytes, &mut compression_table);
         }
 
         for additional in self.get_additional() {
-            additional_bytes.append(&mut additional.to_bytes());
+            additional.to_bytes_compressed(&mut dns_msg_bytes, &mut compression_table);
         }
 
-        let mut dns_msg_bytes = Vec::<u8>::new();
-
-        dns_msg_bytes.append(&mut header_bytes);
-        dns_msg_bytes.append(&mut question_bytes);
-        dns_msg_bytes.append(&mut answer_bytes);
-        dns_msg_bytes.append(&mut authority_bytes);
-        dns_msg_bytes.append(&mut additional_bytes);
-
         dns_msg_bytes
     }
 
@@ -981,6 +974,8 @@ mod message_test {
     use crate::message::question::Question;
     use crate::message::rdata::a_rdata::ARdata;
     use crate::message::rdata::txt_rdata::TxtRdata;
+    use crate::message::rdata::ns_rdata::NsRdata;
+    use crate::message::rdata::srv_rdata::SrvRdata;
     use crate::message::rdata::Rdata;
     use crate::message::resource_record::ResourceRecord;
     use crate::message::DnsMessage;
@@ -1245,6 +1240,76 @@ mod message_test {
         }
     }
 
+    #[test]
+    fn to_bytes_compressed_names_test() {
+        let mut dns_msg = DnsMessage::new_query_message(
+            DomainName::new_from_str("example.com"),
+            Rrtype::NS,
+            Rclass::IN,
+            0,
+            false,
+            1,
+        );
+
+        let mut ns_rdata = NsRdata::new();
+        ns_rdata.set_nsdname(DomainName::new_from_str("ns1.example.com"));
+        let mut ns_rr = ResourceRecord::new(Rdata::NS(ns_rdata));
+        ns_rr.set_name(DomainName::new_from_str("example.com"));
+
+        let mut a_rr = ResourceRecord::new(Rdata::A(ARdata::new()));
+        a_rr.set_name(DomainName::new_from_str("ns1.example.com"));
+
+        dns_msg.add_answers(vec![ns_rr]);
+        dns_msg.add_additionals(vec![a_rr]);
+
+        let msg_bytes = dns_msg.to_bytes();
+
+        let expected_bytes: Vec<u8> = vec![
+            // question: example.com NS IN
+            7, 101, 120, 97, 109, 112, 108, 101, 3, 99, 111, 109, 0, 0, 2, 0, 1,
+            // answer: pointer to example.com, NS IN, ttl 0, rdlength 6, ns1 + pointer
+            0xC0, 12, 0, 2, 0, 1, 0, 0, 0, 0, 0, 6, 3, 110, 115, 49, 0xC0, 12,
+            // additional: pointer to ns1.example.com, A IN, ttl 0, rdlength 4
+            0xC0, 41, 0, 1, 0, 1, 0, 0, 0, 0, 0, 4, 0, 0, 0, 0,
+        ];
+        assert_eq!(msg_bytes[12..].to_vec(), expected_bytes);
+
+        let parsed_msg = DnsMessage::from_bytes(&msg_bytes).unwrap();
+        assert_eq!(parsed_msg.get_answer()[0].get_name().get_name(), "example.com");
+        assert_eq!(
+            match parsed_msg.get_answer()[0].get_rdata() {
+                Rdata::NS(val) => val.get_nsdname().get_name(),
+                _ => unreachable!(),
+            },
+            "ns1.example.com"
+        );
+        assert_eq!(parsed_msg.get_additional()[0].get_name().get_name(), "ns1.example.com");
+    }
+
+    #[test]
+    fn to_bytes_does_not_compress_srv_target_test() {
+        let mut dns_msg = DnsMessage::new_query_message(
+            DomainName::new_from_str("example.com"),
+            Rrtype::SRV,
+            Rclass::IN,
+            0,
+            false,
+            1,
+        );
+
+        let mut srv_rdata = SrvRdata::new();
+        srv_rdata.set_target(DomainName::new_from_str("example.com"));
+        let mut srv_rr = ResourceRecord::new(Rdata::SRV(srv_rdata));
+        srv_rr.set_name(DomainName::new_from_str("example.com"));
+        srv_rr.set_type_code(Rrtype::SRV);
+        dns_msg.add_answers(vec![srv_rr]);
+
+        let msg_bytes = dns_msg.to_bytes();
+
+        let target_bytes = DomainName::new_from_str("example.com").to_bytes();
+        assert_eq!(msg_bytes[msg_bytes.len() - target_bytes.len()..].to_vec(), target_bytes);
+    }
+
     //ToDo: Revisar Práctica 1
     #[test]
     fn set_and_get_query_id_test() {
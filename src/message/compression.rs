@@ -0,0 +1,201 @@
+use crate::domain_name::DomainName;
+use std::collections::HashMap;
+
+/// Largest offset that fits in the 14 bits of a compression pointer.
+const MAX_POINTER_OFFSET: usize = 0x3FFF;
+
+/// Table of the names already written in a message, used to replace repeated
+/// suffixes with compression pointers.
+///
+/// [RFC 1035](https://datatracker.ietf.org/doc/html/rfc1035#section-4.1.4)
+///
+/// ```text
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// | 1  1|                OFFSET                   |
+/// +--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+/// ```
+///
+/// The same table must be used for the whole message, since the offsets are
+/// relative to the first byte of the header.
+#[derive(Clone, Default, Debug)]
+pub struct CompressionTable {
+    /// Lowercased labels of the suffixes already in the message, with the
+    /// offset where they start.
+    names: HashMap<Vec<Vec<u8>>, u16>,
+}
+
+impl CompressionTable {
+    /// Creates an empty `CompressionTable`.
+    ///
+    /// # Examples
+    /// ```
+    /// let table = CompressionTable::new();
+    /// assert!(table.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        CompressionTable {
+            names: HashMap::new(),
+        }
+    }
+
+    /// Appends `name` to `msg`, replacing the longest suffix already present in
+    /// the message with a pointer. Every suffix written in full is added to the
+    /// table so later names can point to it.
+    ///
+    /// `msg` must hold the message from its first byte, because its length is
+    /// used as the offset of the name.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut table = CompressionTable::new();
+    /// let mut msg = vec![0; 12];
+    /// table.write_name(&DomainName::new_from_str("example.com"), &mut msg);
+    /// table.write_name(&DomainName::new_from_str("www.example.com"), &mut msg);
+    /// assert_eq!(&msg[25..], &[3, 119, 119, 119, 0xC0, 12]);
+    /// ```
+    pub fn write_name(&mut self, name: &DomainName, msg: &mut Vec<u8>) {
+        let labels = name.get_labels();
+
+        for i in 0..labels.len() {
+            let suffix: Vec<Vec<u8>> = labels[i..]
+                .iter()
+                .map(|label| label.to_ascii_lowercase())
+                .collect();
+
+            if let Some(offset) = self.names.get(&suffix) {
+                msg.push(0xC0 | (offset >> 8) as u8);
+                msg.push(*offset as u8);
+                return;
+            }
+
+            let offset = msg.len();
+            if offset <= MAX_POINTER_OFFSET {
+                self.names.insert(suffix, offset as u16);
+            }
+
+            msg.push(labels[i].len() as u8);
+            msg.extend_from_slice(&labels[i]);
+        }
+
+        msg.push(0);
+    }
+
+    /// Returns `true` if no name has been added to the table.
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod compression_test {
+    use super::CompressionTable;
+    use crate::domain_name::DomainName;
+    use crate::message::rclass::Rclass;
+    use crate::message::rrtype::Rrtype;
+    use crate::message::DnsMessage;
+
+    #[test]
+    fn write_name_without_match() {
+        let mut table = CompressionTable::new();
+        let mut msg = Vec::new();
+
+        table.write_name(&DomainName::new_from_str("dcc.cl"), &mut msg);
+
+        assert_eq!(msg, vec![3, 100, 99, 99, 2, 99, 108, 0]);
+        assert!(!table.is_empty());
+    }
+
+    #[test]
+    fn write_name_repeated() {
+        let mut table = CompressionTable::new();
+        let mut msg = vec![0; 12];
+
+        table.write_name(&DomainName::new_from_str("dcc.cl"), &mut msg);
+        table.write_name(&DomainName::new_from_str("dcc.cl"), &mut msg);
+
+        assert_eq!(&msg[12..], &[3, 100, 99, 99, 2, 99, 108, 0, 0xC0, 12]);
+    }
+
+    #[test]
+    fn write_name_shared_suffix() {
+        let mut table = CompressionTable::new();
+        let mut msg = vec![0; 12];
+
+        table.write_name(&DomainName::new_from_str("www.dcc.cl"), &mut msg);
+        table.write_name(&DomainName::new_from_str("ns1.dcc.cl"), &mut msg);
+        table.write_name(&DomainName::new_from_str("uchile.cl"), &mut msg);
+
+        assert_eq!(
+            &msg[12..],
+            &[
+                3, 119, 119, 119, 3, 100, 99, 99, 2, 99, 108, 0,
+                3, 110, 115, 49, 0xC0, 16,
+                6, 117, 99, 104, 105, 108, 101, 0xC0, 20,
+            ]
+        );
+    }
+
+    #[test]
+    fn write_name_case_insensitive() {
+        let mut table = CompressionTable::new();
+        let mut msg = vec![0; 12];
+
+        table.write_name(&DomainName::new_from_str("DCC.cl"), &mut msg);
+        table.write_name(&DomainName::new_from_str("dcc.CL"), &mut msg);
+
+        assert_eq!(&msg[20..], &[0xC0, 12]);
+    }
+
+    #[test]
+    fn write_name_root() {
+        let mut table = CompressionTable::new();
+        let mut msg = Vec::new();
+
+        table.write_name(&DomainName::new_from_str("."), &mut msg);
+        table.write_name(&DomainName::new_from_str(""), &mut msg);
+
+        assert_eq!(msg, vec![0, 0]);
+        assert!(table.is_empty());
+    }
+
+    #[test]
+    fn write_name_beyond_pointer_range() {
+        let mut table = CompressionTable::new();
+        let mut msg = vec![0; 0x4000];
+
+        table.write_name(&DomainName::new_from_str("dcc.cl"), &mut msg);
+        table.write_name(&DomainName::new_from_str("dcc.cl"), &mut msg);
+
+        assert_eq!(&msg[0x4000..], &[3, 100, 99, 99, 2, 99, 108, 0, 3, 100, 99, 99, 2, 99, 108, 0]);
+    }
+
+    #[test]
+    fn write_name_escaped_label() {
+        let mut table = CompressionTable::new();
+        let mut msg = vec![0; 12];
+
+        table.write_name(&DomainName::new_from_str("a\\.b.example.com"), &mut msg);
+        table.write_name(&DomainName::new_from_str("b.example.com"), &mut msg);
+
+        assert_eq!(
+            &msg[12..],
+            &[
+                3, b'a', b'.', b'b', 7, b'e', b'x', b'a', b'm', b'p', b'l', b'e', 3, b'c', b'o', b'm', 0,
+                1, b'b', 0xC0, 16,
+            ]
+        );
+    }
+
+    #[test]
+    fn message_round_trip_escaped_label() {
+        let qname = DomainName::new_from_str("a\\.b\\\\c\\000.example.com");
+        let query = DnsMessage::new_query_message(qname.clone(), Rrtype::A, Rclass::IN, 0, false, 1);
+
+        let parsed = DnsMessage::from_bytes(&query.to_bytes()).unwrap();
+        let parsed_qname = parsed.get_question().get_qname();
+
+        assert_eq!(qname.get_labels()[0], b"a.b\\c\0".to_vec());
+        assert!(parsed_qname.eq_case_sensitive(&qname));
+        assert_eq!(parsed_qname.get_labels(), qname.get_labels());
+    }
+}
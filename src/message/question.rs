@@ -3,6 +3,7 @@ use crate::domain_name::DomainName;
 use crate::message::rclass::Rclass;
 
 use super::rrtype::Rrtype;
+use super::compression::CompressionTable;
 
 use std::fmt;
 
@@ -168,6 +169,21 @@ impl Question {
         }
         return question_bytes;
     }
+
+    /// Appends the Question to `msg`, compressing the qname with the names
+    /// already present in the message.
+    pub fn to_bytes_compressed(&self, msg: &mut Vec<u8>, compression_table: &mut CompressionTable) {
+        let qname = self.get_qname();
+
+        if qname.get_name() != "" {
+            compression_table.write_name(&qname, msg);
+
+            msg.push(self.get_first_rrtype_byte());
+            msg.push(self.get_second_rrtype_byte());
+            msg.push(self.get_first_rclass_byte());
+            msg.push(self.get_second_rclass_byte());
+        }
+    }
 }
 
 // Setters
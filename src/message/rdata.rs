@@ -17,6 +17,7 @@ pub mod nsec3_rdata;
 pub mod nsec3param_rdata;
 pub mod tsig_rdata;
 pub mod srv_rdata;
+pub mod presentation;
 
 use core::fmt;
 use crate::message::resource_record::{FromBytes, ToBytes};
@@ -39,6 +40,11 @@ use nsec3_rdata::Nsec3Rdata;
 use nsec3param_rdata::Nsec3ParamRdata;
 use tsig_rdata::TSigRdata;
 use srv_rdata::SrvRdata;
+use crate::message::rrtype::Rrtype;
+use crate::message::compression::CompressionTable;
+use crate::message::rclass::Rclass;
+use crate::domain_name::DomainName;
+use presentation::{field, format_hex, parse_hex, parse_int};
 
 #[derive(Clone, PartialEq, Debug, Eq, Hash)]
 /// Enumerates the differents types of `Rdata` struct.
@@ -62,6 +68,10 @@ pub enum Rdata {
     NSEC3(Nsec3Rdata),
     NSEC3PARAM(Nsec3ParamRdata),
     TSIG(TSigRdata),
+    /// [RFC 3597](https://www.rfc-editor.org/rfc/rfc3597#section-5)
+    /// Rdata of a type this implementation does not know how to parse,
+    /// kept as the raw bytes received so it can be forwarded unchanged.
+    Unknown { rrtype: Rrtype, data: Vec<u8> },
 }
 
 impl ToBytes for Rdata {
@@ -100,10 +110,151 @@ impl ToBytes for Rdata {
             Rdata::NSEC3(val) => val.to_bytes(),
             Rdata::NSEC3PARAM(val) => val.to_bytes(),
             Rdata::TSIG(val) => val.to_bytes(),
+            Rdata::Unknown { data, .. } => data.clone(),
         }
     }
 }
 
+impl Rdata {
+    /// Appends the `Rdata` to `msg`, compressing its domain names with the
+    /// names already present in the message.
+    ///
+    /// [RFC 3597](https://www.rfc-editor.org/rfc/rfc3597#section-4) only allows
+    /// compression in the types defined in RFC 1035, so every other type
+    /// (SRV, RRSIG signer names, NSEC next names...) is written uncompressed.
+    pub fn to_bytes_compressed(&self, msg: &mut Vec<u8>, compression_table: &mut CompressionTable) {
+        match self {
+            Rdata::NS(val) => compression_table.write_name(&val.get_nsdname(), msg),
+            Rdata::CNAME(val) => compression_table.write_name(&val.get_cname(), msg),
+            Rdata::PTR(val) => compression_table.write_name(&val.get_ptrdname(), msg),
+            Rdata::MX(val) => {
+                msg.extend_from_slice(&val.get_preference().to_be_bytes());
+                compression_table.write_name(&val.get_exchange(), msg);
+            }
+            Rdata::SOA(val) => {
+                compression_table.write_name(&val.get_mname(), msg);
+                compression_table.write_name(&val.get_rname(), msg);
+                msg.extend_from_slice(&val.get_serial().to_be_bytes());
+                msg.extend_from_slice(&val.get_refresh().to_be_bytes());
+                msg.extend_from_slice(&val.get_retry().to_be_bytes());
+                msg.extend_from_slice(&val.get_expire().to_be_bytes());
+                msg.extend_from_slice(&val.get_minimum().to_be_bytes());
+            }
+            _ => msg.extend(self.to_bytes()),
+        }
+    }
+
+    /// Creates an `Rdata` of the given type and class from the fields of its
+    /// presentation format, as written in a master file.
+    ///
+    /// Relative domain names are completed with `origin`. Every type also
+    /// accepts the generic form `\# <length> <hex data>` of
+    /// [RFC 3597](https://www.rfc-editor.org/rfc/rfc3597#section-5), which is
+    /// the only form accepted for unknown types.
+    ///
+    /// # Examples
+    /// ```
+    /// let origin = DomainName::new_from_str("example.com.");
+    /// let rdata = Rdata::from_text(Rrtype::MX, Rclass::IN, &["10", "mail"], &origin).unwrap();
+    ///
+    /// match rdata {
+    ///     Rdata::MX(val) => assert_eq!(val.get_exchange().to_string(), "mail.example.com."),
+    ///     _ => {}
+    /// }
+    /// ```
+    pub fn from_text(
+        rrtype: Rrtype,
+        rclass: Rclass,
+        fields: &[&str],
+        origin: &DomainName,
+    ) -> Result<Rdata, &'static str> {
+        if fields.first() == Some(&"\\#") {
+            return Rdata::from_generic_text(rrtype, rclass, &fields[1..]);
+        }
+
+        let rdata = match rrtype {
+            Rrtype::A if rclass == Rclass::CH => Rdata::ACH(AChRdata::from_text(fields, origin)?),
+            Rrtype::A => Rdata::A(ARdata::from_text(fields, origin)?),
+            Rrtype::NS => Rdata::NS(NsRdata::from_text(fields, origin)?),
+            Rrtype::CNAME => Rdata::CNAME(CnameRdata::from_text(fields, origin)?),
+            Rrtype::SOA => Rdata::SOA(SoaRdata::from_text(fields, origin)?),
+            Rrtype::PTR => Rdata::PTR(PtrRdata::from_text(fields, origin)?),
+            Rrtype::HINFO => Rdata::HINFO(HinfoRdata::from_text(fields, origin)?),
+            Rrtype::MX => Rdata::MX(MxRdata::from_text(fields, origin)?),
+            Rrtype::TXT => Rdata::TXT(TxtRdata::from_text(fields, origin)?),
+            Rrtype::AAAA => Rdata::AAAA(AAAARdata::from_text(fields, origin)?),
+            Rrtype::SRV => Rdata::SRV(SrvRdata::from_text(fields, origin)?),
+            Rrtype::OPT => Rdata::OPT(OptRdata::from_text(fields, origin)?),
+            Rrtype::DS => Rdata::DS(DsRdata::from_text(fields, origin)?),
+            Rrtype::RRSIG => Rdata::RRSIG(RRSIGRdata::from_text(fields, origin)?),
+            Rrtype::NSEC => Rdata::NSEC(NsecRdata::from_text(fields, origin)?),
+            Rrtype::DNSKEY => Rdata::DNSKEY(DnskeyRdata::from_text(fields, origin)?),
+            Rrtype::NSEC3 => Rdata::NSEC3(Nsec3Rdata::from_text(fields, origin)?),
+            Rrtype::NSEC3PARAM => Rdata::NSEC3PARAM(Nsec3ParamRdata::from_text(fields, origin)?),
+            Rrtype::TSIG => Rdata::TSIG(TSigRdata::from_text(fields, origin)?),
+            _ => return Err("Error: unknown type, the rdata must be in the generic form."),
+        };
+
+        Ok(rdata)
+    }
+
+    /// Returns the `Rdata` in presentation format.
+    ///
+    /// OPT and unknown types have no presentation format of their own, so
+    /// they are written in the generic form `\# <length> <hex data>`.
+    pub fn to_text(&self) -> String {
+        match self {
+            Rdata::A(val) => val.to_text(),
+            Rdata::ACH(val) => val.to_text(),
+            Rdata::MX(val) => val.to_text(),
+            Rdata::NS(val) => val.to_text(),
+            Rdata::PTR(val) => val.to_text(),
+            Rdata::SOA(val) => val.to_text(),
+            Rdata::TXT(val) => val.to_text(),
+            Rdata::CNAME(val) => val.to_text(),
+            Rdata::HINFO(val) => val.to_text(),
+            Rdata::AAAA(val) => val.to_text(),
+            Rdata::SRV(val) => val.to_text(),
+            Rdata::DS(val) => val.to_text(),
+            Rdata::RRSIG(val) => val.to_text(),
+            Rdata::NSEC(val) => val.to_text(),
+            Rdata::DNSKEY(val) => val.to_text(),
+            Rdata::NSEC3(val) => val.to_text(),
+            Rdata::NSEC3PARAM(val) => val.to_text(),
+            Rdata::TSIG(val) => val.to_text(),
+            Rdata::OPT(_) | Rdata::Unknown { .. } => {
+                let data = self.to_bytes();
+                if data.is_empty() {
+                    String::from("\\# 0")
+                } else {
+                    format!("\\# {} {}", data.len(), format_hex(&data))
+                }
+            }
+        }
+    }
+
+    /// Creates an `Rdata` from the fields after `\#` of the generic form.
+    fn from_generic_text(rrtype: Rrtype, rclass: Rclass, fields: &[&str]) -> Result<Rdata, &'static str> {
+        let length: u16 = parse_int(field(fields, 0)?)?;
+        let data = parse_hex(&fields[1..])?;
+
+        if data.len() != length as usize {
+            return Err("Error: the length of the generic rdata does not match its data.");
+        }
+
+        if let Rrtype::UNKNOWN(_) = rrtype {
+            return Ok(Rdata::Unknown { rrtype, data });
+        }
+
+        // The type and class are appended as Rdata::from_bytes expects them
+        let mut bytes = data;
+        bytes.extend_from_slice(&u16::from(rrtype).to_be_bytes());
+        bytes.extend_from_slice(&u16::from(rclass).to_be_bytes());
+
+        Rdata::from_bytes(&bytes, &bytes)
+    }
+}
+
 impl FromBytes<Result<Rdata, &'static str>> for Rdata {
     /// Given an array of bytes and a type in its code form, returns a new `Rdata`.
     fn from_bytes(bytes: &[u8], full_msg: &[u8]) -> Result<Rdata, &'static str> {
@@ -345,7 +496,10 @@ impl FromBytes<Result<Rdata, &'static str>> for Rdata {
 
                 Ok(Rdata::TSIG(rdata.unwrap()))
             }
-            _ => Err("Format Error"),
+            _ => Ok(Rdata::Unknown {
+                rrtype: Rrtype::from(type_code),
+                data: bytes[..bytes.len() - 4].to_vec(),
+            }),
         };
 
         especific_rdata
@@ -374,6 +528,7 @@ impl fmt::Display for Rdata {
             Rdata::NSEC3(val) => write!(f, "{}", val),
             Rdata::NSEC3PARAM(val) => write!(f, "{}", val),
             Rdata::TSIG(val) => write!(f, "{}", val),
+            Rdata::Unknown { .. } => write!(f, "{}", self.to_text()),
         }
     }
 }
@@ -387,6 +542,7 @@ mod resolver_query_tests {
     use crate::message::resource_record::{ToBytes, FromBytes};
     use crate::message::rdata::Rdata;
     use crate::message::rrtype::Rrtype;
+    use crate::message::rclass::Rclass;
     use super:: a_ch_rdata::AChRdata;
     use super::a_rdata::ARdata;
     use super::cname_rdata::CnameRdata;
@@ -408,6 +564,7 @@ mod resolver_query_tests {
     use super::srv_rdata::SrvRdata;
     use std::net::IpAddr;
     use std::vec;
+    use crate::dnssec::dnssec_algorithm::{DigestType, DnssecAlgorithm, Nsec3HashAlgorithm};
 
     #[test]
     fn to_bytes_rdata(){
@@ -659,10 +816,10 @@ mod resolver_query_tests {
 
     #[test]
     fn to_bytes_dnskey_rdata(){
-        let mut dnskey_rdata = DnskeyRdata::new(0, 0, 0, Vec::new());
+        let mut dnskey_rdata = DnskeyRdata::new(0, 0, DnssecAlgorithm::from(0), Vec::new());
         dnskey_rdata.set_flags(2 as u16);
         dnskey_rdata.set_protocol(3 as u8);
-        dnskey_rdata.set_algorithm(4 as u8);
+        dnskey_rdata.set_algorithm(DnssecAlgorithm::from(4));
         let public_key_simple: Vec<u8> = vec![1, 2, 3, 4, 5, 6, 7, 8];
         dnskey_rdata.set_public_key(public_key_simple);
 
@@ -680,7 +837,7 @@ mod resolver_query_tests {
     fn to_bytes_rrsig_rdata(){
         let mut rrsig_rdata = RRSIGRdata::new();
         rrsig_rdata.set_type_covered(Rrtype::A);
-        rrsig_rdata.set_algorithm(5);
+        rrsig_rdata.set_algorithm(DnssecAlgorithm::RSASHA1);
         rrsig_rdata.set_labels(2);
         rrsig_rdata.set_original_ttl(3600);
         rrsig_rdata.set_signature_expiration(1630435200);
@@ -727,10 +884,10 @@ mod resolver_query_tests {
 
     #[test]
     fn to_bytes_ds_rdata(){
-        let mut ds_rdata = DsRdata::new(0, 0, 0, vec![1, 2, 3, 4]);
+        let mut ds_rdata = DsRdata::new(0, DnssecAlgorithm::from(0), DigestType::from(0), vec![1, 2, 3, 4]);
         ds_rdata.set_key_tag(1);
-        ds_rdata.set_algorithm(2);
-        ds_rdata.set_digest_type(3);
+        ds_rdata.set_algorithm(DnssecAlgorithm::from(2));
+        ds_rdata.set_digest_type(DigestType::from(3));
 
         let bytes_to_test = [0, 1, 2, 3, 1, 2, 3, 4];
 
@@ -755,8 +912,8 @@ mod resolver_query_tests {
 
     #[test]
     fn to_bytes_nsec3_rdata(){
-        let nsec3_rdata = Nsec3Rdata::new(1, 2, 3,
-                                          4, "salt".to_string(), 22, "next_hashed_owner_name".to_string(), vec![Rrtype::A, Rrtype::MX, Rrtype::RRSIG, Rrtype::NSEC, Rrtype::UNKNOWN(1234)]);
+        let nsec3_rdata = Nsec3Rdata::new(Nsec3HashAlgorithm::SHA1, 2, 3,
+                                          4, b"salt".to_vec(), 22, b"next_hashed_owner_name".to_vec(), vec![Rrtype::A, Rrtype::MX, Rrtype::RRSIG, Rrtype::NSEC, Rrtype::UNKNOWN(1234)]);
 
         let rdata = Rdata::NSEC3(nsec3_rdata);
         let bytes = rdata.to_bytes();
@@ -777,8 +934,8 @@ mod resolver_query_tests {
 
     #[test]
     fn to_bytes_nsec3param_rdata(){
-        let nsec3param_rdata = Nsec3ParamRdata::new(1, 2, 3,
-                                                    4, "salt".to_string());
+        let nsec3param_rdata = Nsec3ParamRdata::new(Nsec3HashAlgorithm::SHA1, 2, 3,
+                                                    4, b"salt".to_vec());
 
         let rdata = Rdata::NSEC3PARAM(nsec3param_rdata);
         let bytes = rdata.to_bytes();
@@ -1035,7 +1192,7 @@ mod resolver_query_tests {
             Rdata::DNSKEY(val) => {
                 assert_eq!(val.get_flags(), 2);
                 assert_eq!(val.get_protocol(), 3);
-                assert_eq!(val.get_algorithm(), 4);
+                assert_eq!(val.get_algorithm(), DnssecAlgorithm::from(4));
                 assert_eq!(val.get_public_key(), vec![1, 2, 3, 4, 5, 6, 7, 8]);
             }
             _ => {}
@@ -1054,7 +1211,7 @@ mod resolver_query_tests {
         match rdata {
             Rdata::RRSIG(val) => {
                 assert_eq!(val.get_type_covered(), Rrtype::A);
-                assert_eq!(val.get_algorithm(), 5);
+                assert_eq!(val.get_algorithm(), DnssecAlgorithm::RSASHA1);
                 assert_eq!(val.get_labels(), 2);
                 assert_eq!(val.get_original_ttl(), 3600);
                 assert_eq!(val.get_signature_expiration(), 1630435200);
@@ -1100,8 +1257,8 @@ mod resolver_query_tests {
         match rdata {
             Rdata::DS(val) => {
                 assert_eq!(val.get_key_tag(), 1);
-                assert_eq!(val.get_algorithm(), 2);
-                assert_eq!(val.get_digest_type(), 3);
+                assert_eq!(val.get_algorithm(), DnssecAlgorithm::from(2));
+                assert_eq!(val.get_digest_type(), DigestType::from(3));
                 assert_eq!(val.get_digest(), vec![1, 2, 3, 4]);
             }
             _ => {}
@@ -1138,13 +1295,13 @@ mod resolver_query_tests {
 
         match rdata {
             Rdata::NSEC3(val) => {
-                assert_eq!(val.get_hash_algorithm(), 1);
+                assert_eq!(val.get_hash_algorithm(), Nsec3HashAlgorithm::SHA1);
                 assert_eq!(val.get_flags(), 2);
                 assert_eq!(val.get_iterations(), 3);
                 assert_eq!(val.get_salt_length(), 4);
-                assert_eq!(val.get_salt(), "salt");
+                assert_eq!(val.get_salt(), b"salt");
                 assert_eq!(val.get_hash_length(), 22);
-                assert_eq!(val.get_next_hashed_owner_name(), "next_hashed_owner_name");
+                assert_eq!(val.get_next_hashed_owner_name(), b"next_hashed_owner_name");
                 assert_eq!(val.get_type_bit_maps(), vec![Rrtype::A, Rrtype::MX, Rrtype::RRSIG, Rrtype::NSEC, Rrtype::UNKNOWN(1234)]);
             }
             _ => {}
@@ -1163,11 +1320,11 @@ mod resolver_query_tests {
 
         match rdata {
             Rdata::NSEC3(val) => {
-                assert_eq!(val.get_hash_algorithm(), 1);
+                assert_eq!(val.get_hash_algorithm(), Nsec3HashAlgorithm::SHA1);
                 assert_eq!(val.get_flags(), 2);
                 assert_eq!(val.get_iterations(), 3);
                 assert_eq!(val.get_salt_length(), 4);
-                assert_eq!(val.get_salt(), "salt");
+                assert_eq!(val.get_salt(), b"salt");
             }
             _ => {}
         }
@@ -1188,10 +1345,82 @@ mod resolver_query_tests {
         }
     }
 
+    #[test]
+    fn from_bytes_unknown_rdata(){
+        // CAA record: flags 0, tag "issue", value "ca.test"
+        let data_bytes = [0, 5, 105, 115, 115, 117, 101, 99, 97, 46, 116, 101, 115, 116, 1, 1, 0, 1];
+        let rdata = Rdata::from_bytes(&data_bytes, &data_bytes).unwrap();
+        match rdata {
+            Rdata::Unknown { rrtype, data } => {
+                assert_eq!(rrtype, Rrtype::UNKNOWN(257));
+                assert_eq!(data, data_bytes[..data_bytes.len() - 4].to_vec());
+            }
+            _ => panic!("expected Rdata::Unknown"),
+        }
+    }
+
+    #[test]
+    fn to_bytes_unknown_rdata(){
+        let data_bytes = [1, 0, 0, 3, 104, 50, 0, 0, 65, 0, 1];
+        let rdata = Rdata::from_bytes(&data_bytes, &data_bytes).unwrap();
+        assert_eq!(rdata.to_bytes(), data_bytes[..data_bytes.len() - 4].to_vec());
+    }
+
+    #[test]
+    fn display_unknown_rdata(){
+        let rdata = Rdata::Unknown {
+            rrtype: Rrtype::UNKNOWN(65280),
+            data: vec![0x0a, 0x00, 0x00, 0x01],
+        };
+        assert_eq!(rdata.to_string(), "\\# 4 0A000001");
+        assert_eq!(rdata.to_string(), rdata.to_text());
+
+        let empty_rdata = Rdata::Unknown {
+            rrtype: Rrtype::UNKNOWN(65280),
+            data: Vec::new(),
+        };
+        assert_eq!(empty_rdata.to_string(), "\\# 0");
+    }
+
     #[test]
     #[should_panic]
     fn from_bytes_format_error(){
         let data_bytes = [];
         let _rdata = Rdata::from_bytes(&data_bytes, &data_bytes).unwrap();
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn from_text_mx_rdata() {
+        let origin = DomainName::new_from_str("example.com.");
+        let rdata = Rdata::from_text(Rrtype::MX, Rclass::IN, &["10", "mail"], &origin).unwrap();
+
+        let mut expected = MxRdata::new();
+        expected.set_preference(10);
+        expected.set_exchange(DomainName::new_from_str("mail.example.com."));
+        assert_eq!(rdata, Rdata::MX(expected));
+    }
+
+    #[test]
+    fn from_text_errors() {
+        let origin = DomainName::new_from_str("example.com.");
+
+        assert!(Rdata::from_text(Rrtype::MX, Rclass::IN, &["10"], &origin).is_err());
+        assert!(Rdata::from_text(Rrtype::MX, Rclass::IN, &["10", "a", "b"], &origin).is_err());
+        assert!(Rdata::from_text(Rrtype::A, Rclass::IN, &["::1"], &origin).is_err());
+        assert!(Rdata::from_text(Rrtype::OPT, Rclass::IN, &[], &origin).is_err());
+        assert!(Rdata::from_text(Rrtype::UNKNOWN(65280), Rclass::IN, &["01"], &origin).is_err());
+    }
+
+    #[test]
+    fn from_text_generic_rdata() {
+        let origin = DomainName::new_from_str("example.com.");
+
+        let a = Rdata::from_text(Rrtype::A, Rclass::IN, &["\\#", "4", "7f000001"], &origin).unwrap();
+        assert_eq!(a, Rdata::A(ARdata::new_from_addr(IpAddr::from([127, 0, 0, 1]))));
+
+        let unknown = Rdata::from_text(Rrtype::UNKNOWN(65280), Rclass::IN, &["\\#", "2", "ab", "cd"], &origin).unwrap();
+        assert_eq!(unknown, Rdata::Unknown { rrtype: Rrtype::UNKNOWN(65280), data: vec![0xab, 0xcd] });
+
+        assert!(Rdata::from_text(Rrtype::UNKNOWN(65280), Rclass::IN, &["\\#", "3", "abcd"], &origin).is_err());
+    }
+}
@@ -6,6 +6,7 @@ use crate::message::resource_record::{FromBytes, ResourceRecord, ToBytes};
 
 use std::str::SplitWhitespace;
 use std::fmt;
+use crate::message::rdata::presentation::{expect_fields, field, format_name, parse_name};
 
 #[derive(Clone, PartialEq, Debug, Eq, Hash)]
 /// An struct that represents the RDATA for A TYPE in CH class.
@@ -160,6 +161,26 @@ impl AChRdata {
 
         resource_record
     }
+
+    /// Creates a `AChRdata` from the fields of its presentation format.
+    ///
+    /// The address is written in octal, as in
+    /// [RFC 1035](https://datatracker.ietf.org/doc/html/rfc1035#section-3.4.1).
+    pub fn from_text(fields: &[&str], origin: &DomainName) -> Result<Self, &'static str> {
+        expect_fields(fields, 2)?;
+        let mut a_ch_rdata = AChRdata::new();
+        a_ch_rdata.set_domain_name(parse_name(field(fields, 0)?, origin)?);
+        let ch_address = u16::from_str_radix(field(fields, 1)?, 8)
+            .map_err(|_| "Error: invalid Chaos address.")?;
+        a_ch_rdata.set_ch_address(ch_address);
+
+        Ok(a_ch_rdata)
+    }
+
+    /// Returns the `AChRdata` in presentation format.
+    pub fn to_text(&self) -> String {
+        format!("{} {:o}", format_name(&self.domain_name), self.ch_address)
+    }
 }
 
 // Getters
@@ -7,6 +7,8 @@ use crate::message::resource_record::{FromBytes, ResourceRecord, ToBytes};
 
 use std::str::SplitWhitespace;
 use std::fmt;
+use crate::message::rdata::presentation::{expect_fields, field};
+use std::net::Ipv4Addr;
 
 
 pub trait SetAddress {
@@ -186,6 +188,27 @@ impl ARdata {
 
         ip_address
     }
+
+    /// Creates a `ARdata` from the fields of its presentation format.
+    ///
+    /// # Examples
+    /// ```
+    /// let a_rdata = ARdata::from_text(&["192.0.2.1"], &DomainName::new()).unwrap();
+    /// assert_eq!(a_rdata.get_address(), IpAddr::from([192, 0, 2, 1]));
+    /// ```
+    pub fn from_text(fields: &[&str], _origin: &DomainName) -> Result<Self, &'static str> {
+        expect_fields(fields, 1)?;
+        let address: Ipv4Addr = field(fields, 0)?
+            .parse()
+            .map_err(|_| "Error: invalid IPv4 address.")?;
+
+        Ok(ARdata::new_from_addr(IpAddr::V4(address)))
+    }
+
+    /// Returns the `ARdata` in presentation format.
+    pub fn to_text(&self) -> String {
+        self.address.to_string()
+    }
 }
 
 // Getters
@@ -1,6 +1,9 @@
 use crate::message::resource_record::{FromBytes, ToBytes};
 use std::fmt;
 use std::net::IpAddr;
+use crate::message::rdata::presentation::{expect_fields, field};
+use crate::domain_name::DomainName;
+use std::net::Ipv6Addr;
 
 // Define a trait that abstracts setting the address
 pub trait SetAddress {
@@ -90,6 +93,20 @@ impl AAAARdata{
         }
     }
 
+    /// Creates a `AAAARdata` from the fields of its presentation format.
+    pub fn from_text(fields: &[&str], _origin: &DomainName) -> Result<Self, &'static str> {
+        expect_fields(fields, 1)?;
+        let address: Ipv6Addr = field(fields, 0)?
+            .parse()
+            .map_err(|_| "Error: invalid IPv6 address.")?;
+
+        Ok(AAAARdata::new_from_addr(IpAddr::V6(address)))
+    }
+
+    /// Returns the `AAAARdata` in presentation format.
+    pub fn to_text(&self) -> String {
+        self.address.to_string()
+    }
 }
 /// Getter for the struct AAAARdata
 impl AAAARdata{
@@ -5,6 +5,7 @@ use crate::message::rrtype::Rrtype;
 use crate::message::resource_record::{FromBytes, ResourceRecord, ToBytes};
 use std::str::SplitWhitespace;
 use std::fmt;
+use crate::message::rdata::presentation::{expect_fields, field, format_name, parse_name};
 
 #[derive(Clone, PartialEq, Debug, Eq, Hash)]
 /// [RFC 1035]: https://datatracker.ietf.org/doc/html/rfc1035#section-3.3.1
@@ -107,6 +108,20 @@ impl CnameRdata {
 
         resource_record
     }
+
+    /// Creates a `CnameRdata` from the fields of its presentation format.
+    pub fn from_text(fields: &[&str], origin: &DomainName) -> Result<Self, &'static str> {
+        expect_fields(fields, 1)?;
+        let mut cname_rdata = CnameRdata::new();
+        cname_rdata.set_cname(parse_name(field(fields, 0)?, origin)?);
+
+        Ok(cname_rdata)
+    }
+
+    /// Returns the `CnameRdata` in presentation format.
+    pub fn to_text(&self) -> String {
+        format_name(&self.cname)
+    }
 }
 
 /// Getter
@@ -1,6 +1,13 @@
 use crate::message::resource_record::{FromBytes, ToBytes};
 
 use std::fmt;
+use crate::message::rdata::presentation::{field, format_base64, parse_algorithm, parse_base64, parse_int};
+use crate::message::rdata::ds_rdata::DsRdata;
+use crate::domain_name::DomainName;
+use crate::dnssec::dnssec_algorithm::{DigestType, DnssecAlgorithm};
+use crate::dnssec::dnssec_key::{REVOKE_FLAG, SEP_FLAG};
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha384};
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 /// Struct for DNSKEY Rdata
@@ -19,7 +26,7 @@ use std::fmt;
 pub struct DnskeyRdata {
     pub flags: u16,
     pub protocol: u8,
-    pub algorithm: u8,
+    pub algorithm: DnssecAlgorithm,
     pub public_key: Vec<u8>,
 }
 
@@ -30,7 +37,7 @@ impl ToBytes for DnskeyRdata {
 
         bytes.extend_from_slice(&self.flags.to_be_bytes());
         bytes.push(self.protocol);
-        bytes.push(self.algorithm);
+        bytes.push(u8::from(self.algorithm));
         bytes.extend_from_slice(&self.public_key);
 
         bytes
@@ -49,7 +56,7 @@ impl FromBytes<Result<Self, &'static str>> for DnskeyRdata {
             return Err("Public key not assigned");
         }
 
-        let mut dnskey_rdata = DnskeyRdata::new(0, 0, 0, Vec::new());
+        let mut dnskey_rdata = DnskeyRdata::new(0, 0, DnssecAlgorithm::RESERVED, Vec::new());
 
         let array_bytes = [bytes[0], bytes[1]];
         let flags = u16::from_be_bytes(array_bytes);
@@ -58,7 +65,7 @@ impl FromBytes<Result<Self, &'static str>> for DnskeyRdata {
         let protocol = bytes[2];
         dnskey_rdata.set_protocol(protocol);
 
-        let algorithm = bytes[3];
+        let algorithm = DnssecAlgorithm::from(bytes[3]);
         dnskey_rdata.set_algorithm(algorithm);
 
         let mut public_key: Vec<u8> = Vec::new();
@@ -79,9 +86,9 @@ impl DnskeyRdata {
     ///
     /// ```
     ///
-    /// let dnskey_rdata = DnskeyRdata::new(0, 0, 0, Vec::new());
+    /// let dnskey_rdata = DnskeyRdata::new(0, 0, DnssecAlgorithm::from(0), Vec::new());
     /// ```
-    pub fn new(flags: u16, protocol: u8, algorithm: u8, public_key: Vec<u8>) -> DnskeyRdata {
+    pub fn new(flags: u16, protocol: u8, algorithm: DnssecAlgorithm, public_key: Vec<u8>) -> DnskeyRdata {
         DnskeyRdata {
             flags,
             protocol,
@@ -95,7 +102,7 @@ impl DnskeyRdata {
     /// # Examples
     /// 
     /// ```
-    /// let mut dnskey_rdata = DnskeyRdata::new(0, 0, 0, Vec::new());
+    /// let mut dnskey_rdata = DnskeyRdata::new(0, 0, DnssecAlgorithm::from(0), Vec::new());
     /// assert_eq!(dnskey_rdata.get_flags(), 0);
     /// ```
     pub fn get_flags(&self) -> u16 {
@@ -107,7 +114,7 @@ impl DnskeyRdata {
     /// # Examples
     /// 
     /// ```
-    /// let mut dnskey_rdata = DnskeyRdata::new(0, 0, 0, Vec::new());
+    /// let mut dnskey_rdata = DnskeyRdata::new(0, 0, DnssecAlgorithm::from(0), Vec::new());
     /// assert_eq!(dnskey_rdata.get_protocol(), 0);
     /// ```
     pub fn get_protocol(&self) -> u8 {
@@ -119,11 +126,11 @@ impl DnskeyRdata {
     /// # Examples
     /// 
     /// ```
-    /// let mut dnskey_rdata = DnskeyRdata::new(0, 0, 0, Vec::new());
-    /// assert_eq!(dnskey_rdata.get_algorithm(), 0);
+    /// let mut dnskey_rdata = DnskeyRdata::new(0, 0, DnssecAlgorithm::from(0), Vec::new());
+    /// assert_eq!(dnskey_rdata.get_algorithm(), DnssecAlgorithm::from(0));
     /// ```
-    pub fn get_algorithm(&self) -> u8 {
-        self.algorithm.clone()
+    pub fn get_algorithm(&self) -> DnssecAlgorithm {
+        self.algorithm
     }
 
     /// Get the public key of the DNSKEY RDATA.
@@ -131,15 +138,115 @@ impl DnskeyRdata {
     /// # Examples
     /// 
     /// ```
-    /// let mut dnskey_rdata = DnskeyRdata::new(0, 0, 0, Vec::new());
+    /// let mut dnskey_rdata = DnskeyRdata::new(0, 0, DnssecAlgorithm::from(0), Vec::new());
     /// assert_eq!(dnskey_rdata.get_public_key(), Vec::new());
     /// ```
     pub fn get_public_key(&self) -> Vec<u8> {
         self.public_key.clone()
     }
 
+    /// Returns whether the REVOKE flag (bit 8, RFC 5011) is set.
     pub fn is_revoked(&self) -> bool {
-        self.flags & 0x0100 != 0
+        self.flags & REVOKE_FLAG != 0
+    }
+
+    /// Returns whether the Secure Entry Point flag (bit 15, RFC 4034) is set.
+    pub fn is_sep(&self) -> bool {
+        self.flags & SEP_FLAG != 0
+    }
+
+    /// Creates a `DnskeyRdata` from the fields of its presentation format.
+    pub fn from_text(fields: &[&str], _origin: &DomainName) -> Result<Self, &'static str> {
+        let flags = parse_int(field(fields, 0)?)?;
+        let protocol = parse_int(field(fields, 1)?)?;
+        let algorithm = parse_algorithm(field(fields, 2)?)?;
+        field(fields, 3)?;
+        let public_key = parse_base64(&fields[3..])?;
+
+        Ok(DnskeyRdata::new(flags, protocol, algorithm, public_key))
+    }
+
+    /// Returns the `DnskeyRdata` in presentation format.
+    pub fn to_text(&self) -> String {
+        format!(
+            "{} {} {} {}",
+            self.flags,
+            self.protocol,
+            u8::from(self.algorithm),
+            format_base64(&self.public_key)
+        )
+    }
+
+    /// Returns the key tag of the DNSKEY RDATA.
+    ///
+    /// [RFC 4034](https://datatracker.ietf.org/doc/html/rfc4034#appendix-B)
+    ///
+    /// ```text
+    /// The key tag is the same for all DNSKEY algorithm types except
+    /// algorithm 1 (please see Appendix B.1 for the definition of the key
+    /// tag for algorithm 1).  The key tag algorithm is the sum of the wire
+    /// format of the DNSKEY RDATA broken into 2 octet groups.  First, the
+    /// RDATA (in wire format) is treated as a series of 2 octet groups.
+    /// These groups are then added together, ignoring any carry bits.
+    /// ```
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let dnskey_rdata = DnskeyRdata::new(257, 3, DnssecAlgorithm::ED25519, public_key);
+    /// assert_eq!(dnskey_rdata.key_tag(), 3613);
+    /// ```
+    pub fn key_tag(&self) -> u16 {
+        // Appendix B.1: the most significant 16 bits of the least
+        // significant 24 bits of the RSA/MD5 modulus
+        if self.algorithm == DnssecAlgorithm::RSAMD5 {
+            let key = &self.public_key;
+            return match key.len() {
+                len if len >= 3 => u16::from_be_bytes([key[len - 3], key[len - 2]]),
+                _ => 0,
+            };
+        }
+
+        let mut accumulator: u32 = 0;
+        for (i, byte) in self.to_bytes().iter().enumerate() {
+            accumulator += if i & 1 == 0 { (*byte as u32) << 8 } else { *byte as u32 };
+        }
+        accumulator += (accumulator >> 16) & 0xffff;
+
+        (accumulator & 0xffff) as u16
+    }
+
+    /// Returns the DS RDATA that refers to this key, owned by `owner`.
+    ///
+    /// [RFC 4034](https://datatracker.ietf.org/doc/html/rfc4034#section-5.1.4)
+    ///
+    /// ```text
+    /// digest = digest_algorithm( DNSKEY owner name | DNSKEY RDATA);
+    ///
+    ///    "|" denotes concatenation
+    ///
+    ///    DNSKEY RDATA = Flags | Protocol | Algorithm | Public Key.
+    /// ```
+    ///
+    /// The digest types are SHA-1 (1), SHA-256 (2) and SHA-384 (4).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let ds_rdata = dnskey_rdata.to_ds(&DomainName::new_from_str("example.com"), DigestType::SHA256).unwrap();
+    /// assert_eq!(ds_rdata.get_key_tag(), dnskey_rdata.key_tag());
+    /// ```
+    pub fn to_ds(&self, owner: &DomainName, digest_type: DigestType) -> Result<DsRdata, &'static str> {
+        let mut data = owner.to_canonical().to_bytes();
+        data.extend(self.to_bytes());
+        let digest = match digest_type {
+            DigestType::SHA1 => Sha1::digest(&data).to_vec(),
+            DigestType::SHA256 => Sha256::digest(&data).to_vec(),
+            DigestType::SHA384 => Sha384::digest(&data).to_vec(),
+            _ => return Err("Unsupported DS digest type"),
+        };
+
+        Ok(DsRdata::new(self.key_tag(), self.algorithm, digest_type, digest))
     }
 }
 
@@ -150,7 +257,7 @@ impl DnskeyRdata {
     /// # Examples
     /// 
     /// ```
-    /// let mut dnskey_rdata = DnskeyRdata::new(0, 0, 0, Vec::new());
+    /// let mut dnskey_rdata = DnskeyRdata::new(0, 0, DnssecAlgorithm::from(0), Vec::new());
     /// dnskey_rdata.set_flags(1);
     /// assert_eq!(dnskey_rdata.get_flags(), 1);
     /// ```
@@ -158,8 +265,9 @@ impl DnskeyRdata {
         self.flags = flags;
     }
 
+    /// Sets or clears the REVOKE flag (bit 8, RFC 5011) of the DNSKEY RDATA.
     pub fn set_revoke_flag(&mut self, revoke: bool) {
-        if revoke { self.flags = 0x0100 } else { self.flags = 0 }
+        if revoke { self.flags |= REVOKE_FLAG } else { self.flags &= !REVOKE_FLAG }
     }
 
     /// Set the protocol of the DNSKEY RDATA.
@@ -167,7 +275,7 @@ impl DnskeyRdata {
     /// # Examples
     /// 
     /// ```
-    /// let mut dnskey_rdata = DnskeyRdata::new(0, 0, 0, Vec::new());
+    /// let mut dnskey_rdata = DnskeyRdata::new(0, 0, DnssecAlgorithm::from(0), Vec::new());
     /// dnskey_rdata.set_protocol(1);
     /// assert_eq!(dnskey_rdata.get_protocol(), 1);
     /// ```
@@ -180,11 +288,11 @@ impl DnskeyRdata {
     /// # Examples
     /// 
     /// ```
-    /// let mut dnskey_rdata = DnskeyRdata::new(0, 0, 0, Vec::new());
-    /// dnskey_rdata.set_algorithm(1);
-    /// assert_eq!(dnskey_rdata.get_algorithm(), 1);
+    /// let mut dnskey_rdata = DnskeyRdata::new(0, 0, DnssecAlgorithm::from(0), Vec::new());
+    /// dnskey_rdata.set_algorithm(DnssecAlgorithm::RSAMD5);
+    /// assert_eq!(dnskey_rdata.get_algorithm(), DnssecAlgorithm::RSAMD5);
     /// ```
-    pub fn set_algorithm(&mut self, algorithm: u8) {
+    pub fn set_algorithm(&mut self, algorithm: DnssecAlgorithm) {
         self.algorithm = algorithm;
     }
 
@@ -193,7 +301,7 @@ impl DnskeyRdata {
     /// # Examples
     /// 
     /// ```
-    /// let mut dnskey_rdata = DnskeyRdata::new(0, 0, 0, Vec::new());
+    /// let mut dnskey_rdata = DnskeyRdata::new(0, 0, DnssecAlgorithm::from(0), Vec::new());
     /// dnskey_rdata.set_public_key(vec![0x01, 0x02]);
     /// assert_eq!(dnskey_rdata.get_public_key(), vec![0x01, 0x02]);
     /// ```
@@ -205,7 +313,7 @@ impl DnskeyRdata {
 impl fmt::Display for DnskeyRdata {
     /// Formats the Rdata as a string.
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{} {} {}", self.flags, self.protocol, self.algorithm)
+        write!(f, "{} {} {}", self.flags, self.protocol, u8::from(self.algorithm))
     }
 }
 
@@ -216,21 +324,91 @@ mod dnskey_rdata_test{
 
     #[test]
     fn setters_and_getters_test(){
-        let mut dnskey_rdata = DnskeyRdata::new(0, 0, 0, Vec::new());
+        let mut dnskey_rdata = DnskeyRdata::new(0, 0, DnssecAlgorithm::from(0), Vec::new());
         dnskey_rdata.set_flags(1);
         dnskey_rdata.set_protocol(2);
-        dnskey_rdata.set_algorithm(3);
+        dnskey_rdata.set_algorithm(DnssecAlgorithm::from(3));
         dnskey_rdata.set_public_key(vec![1, 2]);
 
         assert_eq!(dnskey_rdata.get_flags(), 1);
         assert_eq!(dnskey_rdata.get_protocol(), 2);
-        assert_eq!(dnskey_rdata.get_algorithm(), 3);
+        assert_eq!(dnskey_rdata.get_algorithm(), DnssecAlgorithm::from(3));
         assert_eq!(dnskey_rdata.get_public_key(), vec![1, 2]);
     }
 
+    #[test]
+    fn revoke_flag_test(){
+        let mut dnskey_rdata = DnskeyRdata::new(257, 3, DnssecAlgorithm::RSASHA256, Vec::new());
+        assert!(!dnskey_rdata.is_revoked());
+
+        dnskey_rdata.set_revoke_flag(true);
+        assert!(dnskey_rdata.is_revoked());
+        assert_eq!(dnskey_rdata.get_flags(), 385);
+
+        dnskey_rdata.set_revoke_flag(false);
+        assert_eq!(dnskey_rdata.get_flags(), 257);
+    }
+
+    #[test]
+    fn sep_flag_test(){
+        assert!(DnskeyRdata::new(257, 3, DnssecAlgorithm::RSASHA256, Vec::new()).is_sep());
+        assert!(!DnskeyRdata::new(256, 3, DnssecAlgorithm::RSASHA256, Vec::new()).is_sep());
+    }
+
+    #[test]
+    fn key_tag_and_ds_rfc4034(){
+        // RFC 4034 section 5.4
+        let public_key = parse_base64(&[
+            "AQOeiiR0GOMYkDshWoSKz9XzfwJr1AYtsmx3TGkJaNXVbfi/2pHm822aJ5iI9BMzNXxeYCmZDRD99WYwYqUSdjMmmAphXdvxegXd/M5+X7OrzKBaMbCVdFLUUh6DhweJBjEVv5f2wwjM9XzcnOf+EPbtG9DMBmADjFDc2w/rljwvFw==",
+        ]).unwrap();
+        let dnskey_rdata = DnskeyRdata::new(256, 3, DnssecAlgorithm::RSASHA1, public_key);
+        let ds_rdata = dnskey_rdata.to_ds(&DomainName::new_from_str("dskey.example.com"), DigestType::SHA1).unwrap();
+
+        assert_eq!(dnskey_rdata.key_tag(), 60485);
+        assert_eq!(ds_rdata.get_key_tag(), 60485);
+        assert_eq!(ds_rdata.get_algorithm(), DnssecAlgorithm::RSASHA1);
+        assert_eq!(ds_rdata.get_digest_type(), DigestType::SHA1);
+        assert_eq!(hex::encode_upper(ds_rdata.get_digest()), "2BB183AF5F22588179A53B0A98631FAD1A292118");
+    }
+
+    #[test]
+    fn key_tag_and_ds_rfc8080(){
+        // RFC 8080 section 6.1
+        let public_key = parse_base64(&["l02Woi0iS8Aa25FQkUd9RMzZHJpBoRQwAQEX1SxZJA4="]).unwrap();
+        let dnskey_rdata = DnskeyRdata::new(257, 3, DnssecAlgorithm::ED25519, public_key);
+        let ds_rdata = dnskey_rdata.to_ds(&DomainName::new_from_str("Example.COM"), DigestType::SHA256).unwrap();
+
+        assert_eq!(dnskey_rdata.key_tag(), 3613);
+        assert_eq!(
+            hex::encode(ds_rdata.get_digest()),
+            "3aa5ab37efce57f737fc1627013fee07bdf241bd10f3b1964ab55c78e79a304b"
+        );
+    }
+
+    #[test]
+    fn to_ds_digest_types(){
+        let dnskey_rdata = DnskeyRdata::new(257, 3, DnssecAlgorithm::ECDSAP256SHA256, vec![1; 64]);
+        let owner = DomainName::new_from_str("example.net");
+
+        assert_eq!(dnskey_rdata.to_ds(&owner, DigestType::SHA1).unwrap().get_digest().len(), 20);
+        assert_eq!(dnskey_rdata.to_ds(&owner, DigestType::SHA256).unwrap().get_digest().len(), 32);
+        assert_eq!(dnskey_rdata.to_ds(&owner, DigestType::SHA384).unwrap().get_digest().len(), 48);
+        assert_ne!(
+            dnskey_rdata.to_ds(&owner, DigestType::SHA256).unwrap(),
+            dnskey_rdata.to_ds(&DomainName::new_from_str("example.org"), DigestType::SHA256).unwrap()
+        );
+        assert!(dnskey_rdata.to_ds(&owner, DigestType::from(3)).is_err());
+    }
+
+    #[test]
+    fn key_tag_rsa_md5(){
+        let dnskey_rdata = DnskeyRdata::new(256, 3, DnssecAlgorithm::RSAMD5, vec![1, 2, 3, 0x12, 0x34, 0x56]);
+        assert_eq!(dnskey_rdata.key_tag(), 0x1234);
+    }
+
     #[test]
     fn to_bytes(){
-        let dnskey_rdata = DnskeyRdata::new(1, 2, 3, vec![1,2]);
+        let dnskey_rdata = DnskeyRdata::new(1, 2, DnssecAlgorithm::from(3), vec![1,2]);
 
         let bytes_test: Vec<u8> = vec![0, 1, 2, 3, 1, 2];
 
@@ -239,7 +417,7 @@ mod dnskey_rdata_test{
 
     #[test]
     fn from_bytes(){
-        let dnskey_rdata = DnskeyRdata::new(1, 2, 3, vec![1,2]);
+        let dnskey_rdata = DnskeyRdata::new(1, 2, DnssecAlgorithm::from(3), vec![1,2]);
 
         let bytes_test: Vec<u8> = vec![0, 1, 2, 3, 1, 2];
 
@@ -259,11 +437,11 @@ mod dnskey_rdata_test{
 
     #[test]
     fn max_values_from_bytes(){
-        let mut dnskey_rdata = DnskeyRdata::new(0, 0, 0, Vec::new());
+        let mut dnskey_rdata = DnskeyRdata::new(0, 0, DnssecAlgorithm::from(0), Vec::new());
         //Max value of 2 bytes is 65535 (16 ones in the 2 bytes)
         dnskey_rdata.set_flags(65535);
         dnskey_rdata.set_protocol(255);
-        dnskey_rdata.set_algorithm(255);
+        dnskey_rdata.set_algorithm(DnssecAlgorithm::from(255));
         dnskey_rdata.set_public_key(vec![255, 255]);
 
         let bytes_test: Vec<u8> = vec![255, 255, 255, 255, 255, 255];
@@ -278,11 +456,11 @@ mod dnskey_rdata_test{
 
     #[test]
     fn max_values_to_bytes(){
-        let mut dnskey_rdata = DnskeyRdata::new(0, 0, 0, Vec::new());
+        let mut dnskey_rdata = DnskeyRdata::new(0, 0, DnssecAlgorithm::from(0), Vec::new());
         //Max value of 2 bytes is 65535 (16 ones in the 2 bytes)
         dnskey_rdata.set_flags(65535);
         dnskey_rdata.set_protocol(255);
-        dnskey_rdata.set_algorithm(255);
+        dnskey_rdata.set_algorithm(DnssecAlgorithm::from(255));
         dnskey_rdata.set_public_key(vec![255, 255]);
 
         let bytes_test: Vec<u8> = vec![255, 255, 255, 255, 255, 255];
@@ -292,10 +470,10 @@ mod dnskey_rdata_test{
 
     #[test]
     fn min_values_from_bytes(){
-        let mut dnskey_rdata = DnskeyRdata::new(0, 0, 0, Vec::new());
+        let mut dnskey_rdata = DnskeyRdata::new(0, 0, DnssecAlgorithm::from(0), Vec::new());
         dnskey_rdata.set_flags(0);
         dnskey_rdata.set_protocol(0);
-        dnskey_rdata.set_algorithm(0);
+        dnskey_rdata.set_algorithm(DnssecAlgorithm::from(0));
         dnskey_rdata.set_public_key(vec![0, 0]);
 
         let bytes_test: Vec<u8> = vec![0, 0, 0, 0, 0, 0];
@@ -309,10 +487,10 @@ mod dnskey_rdata_test{
     }
     #[test]
     fn min_values_to_bytes(){
-        let mut dnskey_rdata = DnskeyRdata::new(0, 0, 0, Vec::new());
+        let mut dnskey_rdata = DnskeyRdata::new(0, 0, DnssecAlgorithm::from(0), Vec::new());
         dnskey_rdata.set_flags(0);
         dnskey_rdata.set_protocol(0);
-        dnskey_rdata.set_algorithm(0);
+        dnskey_rdata.set_algorithm(DnssecAlgorithm::from(0));
         dnskey_rdata.set_public_key(vec![0, 0]);
 
         let bytes_test: Vec<u8> = vec![0, 0, 0, 0, 0, 0];
@@ -1,6 +1,9 @@
 use core::fmt;
 
 use crate::message::resource_record::{FromBytes, ToBytes};
+use crate::message::rdata::presentation::{field, format_hex, parse_algorithm, parse_hex, parse_int};
+use crate::domain_name::DomainName;
+use crate::dnssec::dnssec_algorithm::{DigestType, DnssecAlgorithm};
 
 #[derive(Clone, PartialEq, Debug, Eq, Hash)]
 /// Struct for the DS Rdata
@@ -18,8 +21,8 @@ use crate::message::resource_record::{FromBytes, ToBytes};
 
 pub struct DsRdata {
     pub key_tag: u16, //the key tag of the DNSKEY RR referred
-    pub algorithm: u8, //the algorithm number of the DNSKEY RR referred to by the DS record.
-    pub digest_type: u8, //the algorithm to construct the digest
+    pub algorithm: DnssecAlgorithm, //the algorithm number of the DNSKEY RR referred to by the DS record.
+    pub digest_type: DigestType, //the algorithm to construct the digest
     pub digest: Vec<u8>, //digest = digest_algorithm( DNSKEY owner name | DNSKEY RDATA);
 }
 
@@ -33,14 +36,14 @@ impl ToBytes for DsRdata{
     /// If the digest is longer than 255 bytes
     /// # Examples
     /// ```
-    /// let ds_rdata = DsRdata::new(0, 0, 0, vec![0]);
+    /// let ds_rdata = DsRdata::new(0, DnssecAlgorithm::from(0), DigestType::from(0), vec![0]);
     /// let ds_rdata_bytes = ds_rdata.to_bytes();
     /// ```
     fn to_bytes(&self) -> Vec<u8> {
         let mut bytes: Vec<u8> = Vec::new();
         bytes.extend_from_slice(&self.key_tag.to_be_bytes());
-        bytes.push(self.algorithm);
-        bytes.push(self.digest_type);
+        bytes.push(u8::from(self.algorithm));
+        bytes.push(u8::from(self.digest_type));
         if self.digest.len() > 255 {
             panic!("Digest is longer than 255 bytes");
         }
@@ -58,7 +61,7 @@ impl FromBytes<Result<Self, &'static str>> for DsRdata{
     /// * `Result<DsRdata, &'static str>` - The result with the DsRdata (or error)
     /// # Examples
     /// ```
-    /// let ds_rdata = DsRdata::new(0, 0, 0, vec![0]);
+    /// let ds_rdata = DsRdata::new(0, DnssecAlgorithm::from(0), DigestType::from(0), vec![0]);
     /// let ds_rdata_bytes = ds_rdata.to_bytes();
     /// let ds_rdata = DsRdata::from_bytes(&ds_rdata_bytes, &ds_rdata_bytes).unwrap();
     /// ```
@@ -67,8 +70,8 @@ impl FromBytes<Result<Self, &'static str>> for DsRdata{
             return Err("Format error");
         }
         let key_tag = u16::from_be_bytes([bytes[0], bytes[1]]);
-        let algorithm = bytes[2];
-        let digest_type = bytes[3];
+        let algorithm = DnssecAlgorithm::from(bytes[2]);
+        let digest_type = DigestType::from(bytes[3]);
         let digest = bytes[4..].to_vec();
         let digest_len = digest.len();
         if digest_len > 255 {
@@ -87,16 +90,16 @@ impl DsRdata {
     /// Constructor
     /// # Arguments
     /// * `key_tag` - u16
-    /// * `algorithm` - u8
-    /// * `digest_type` - u8
+    /// * `algorithm` - DnssecAlgorithm
+    /// * `digest_type` - DigestType
     /// * `digest` - Vec<u8>
     /// # Return
     /// * `DsRdata` - DsRdata
     /// # Examples
     /// ```
-    /// let ds_rdata = DsRdata::new(0, 0, 0, vec![0]);
+    /// let ds_rdata = DsRdata::new(0, DnssecAlgorithm::from(0), DigestType::from(0), vec![0]);
     /// ```
-    pub fn new(key_tag: u16, algorithm: u8, digest_type: u8, digest: Vec<u8>) -> DsRdata {
+    pub fn new(key_tag: u16, algorithm: DnssecAlgorithm, digest_type: DigestType, digest: Vec<u8>) -> DsRdata {
         DsRdata {
             key_tag,
             algorithm,
@@ -111,19 +114,41 @@ impl DsRdata {
     }
 
     /// Getter for the algorithm field
-    pub fn get_algorithm(&self) -> u8 {
-        self.algorithm.clone()
+    pub fn get_algorithm(&self) -> DnssecAlgorithm {
+        self.algorithm
     }
 
     /// Getter for the digest_type field
-    pub fn get_digest_type(&self) -> u8 {
-        self.digest_type.clone()
+    pub fn get_digest_type(&self) -> DigestType {
+        self.digest_type
     }
 
     /// Getter for the digest field
     pub fn get_digest(&self) -> Vec<u8> {
         self.digest.clone()
     }
+
+    /// Creates a `DsRdata` from the fields of its presentation format.
+    pub fn from_text(fields: &[&str], _origin: &DomainName) -> Result<Self, &'static str> {
+        let key_tag = parse_int(field(fields, 0)?)?;
+        let algorithm = parse_algorithm(field(fields, 1)?)?;
+        let digest_type = DigestType::from(parse_int::<u8>(field(fields, 2)?)?);
+        field(fields, 3)?;
+        let digest = parse_hex(&fields[3..])?;
+
+        Ok(DsRdata::new(key_tag, algorithm, digest_type, digest))
+    }
+
+    /// Returns the `DsRdata` in presentation format.
+    pub fn to_text(&self) -> String {
+        format!(
+            "{} {} {} {}",
+            self.key_tag,
+            u8::from(self.algorithm),
+            self.digest_type,
+            format_hex(&self.digest)
+        )
+    }
 }
 
 impl DsRdata{
@@ -133,12 +158,12 @@ impl DsRdata{
     }
 
     /// Setter for the algorithm field
-    pub fn set_algorithm(&mut self, algorithm: u8) {
+    pub fn set_algorithm(&mut self, algorithm: DnssecAlgorithm) {
         self.algorithm = algorithm;
     }
 
     /// Setter for the digest_type field
-    pub fn set_digest_type(&mut self, digest_type: u8) {
+    pub fn set_digest_type(&mut self, digest_type: DigestType) {
         self.digest_type = digest_type;
     }
 
@@ -156,11 +181,11 @@ impl fmt::Display for DsRdata {
     /// * `String` - The formatted DsRdata
     /// # Examples
     /// ```
-    /// let ds_rdata = DsRdata::new(0, 0, 0, vec![0]);
+    /// let ds_rdata = DsRdata::new(0, DnssecAlgorithm::from(0), DigestType::from(0), vec![0]);
     /// println!("{}", ds_rdata);
     /// ```
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{} {} {} {}", self.key_tag, self.algorithm, self.digest_type, self.digest.iter().map(|b| format!("{:02x}", b)).collect::<String>())
+        write!(f, "{} {} {} {}", self.key_tag, u8::from(self.algorithm), self.digest_type, self.digest.iter().map(|b| format!("{:02x}", b)).collect::<String>())
     }
 }
 
@@ -170,28 +195,28 @@ mod ds_rdata_test{
 
     #[test]
     fn get_and_set_key_tag(){
-        let mut ds_rdata = DsRdata::new(0, 0, 0, vec![0]);
+        let mut ds_rdata = DsRdata::new(0, DnssecAlgorithm::from(0), DigestType::from(0), vec![0]);
         ds_rdata.set_key_tag(1);
         assert_eq!(ds_rdata.get_key_tag(), 1);
     }
 
     #[test]
     fn get_and_set_algorithm(){
-        let mut ds_rdata = DsRdata::new(0, 0, 0, vec![0]);
-        ds_rdata.set_algorithm(1);
-        assert_eq!(ds_rdata.get_algorithm(), 1);
+        let mut ds_rdata = DsRdata::new(0, DnssecAlgorithm::from(0), DigestType::from(0), vec![0]);
+        ds_rdata.set_algorithm(DnssecAlgorithm::RSAMD5);
+        assert_eq!(ds_rdata.get_algorithm(), DnssecAlgorithm::RSAMD5);
     }
 
     #[test]
     fn get_and_set_digest_type(){
-        let mut ds_rdata = DsRdata::new(0, 0, 0, vec![0]);
-        ds_rdata.set_digest_type(1);
-        assert_eq!(ds_rdata.get_digest_type(), 1);
+        let mut ds_rdata = DsRdata::new(0, DnssecAlgorithm::from(0), DigestType::from(0), vec![0]);
+        ds_rdata.set_digest_type(DigestType::SHA1);
+        assert_eq!(ds_rdata.get_digest_type(), DigestType::SHA1);
     }
 
     #[test]
     fn get_and_set_digest(){
-        let mut ds_rdata = DsRdata::new(0, 0, 0, vec![0]);
+        let mut ds_rdata = DsRdata::new(0, DnssecAlgorithm::from(0), DigestType::from(0), vec![0]);
         ds_rdata.set_digest(vec![1, 2, 3]);
         assert_eq!(ds_rdata.get_digest(), vec![1, 2, 3]);
     }
@@ -199,7 +224,7 @@ mod ds_rdata_test{
 
     #[test]
     fn to_bytes_test(){
-        let ds_rdata = DsRdata::new(0, 0, 0, vec![1, 2, 3]);
+        let ds_rdata = DsRdata::new(0, DnssecAlgorithm::from(0), DigestType::from(0), vec![1, 2, 3]);
         let ds_rdata_bytes = ds_rdata.to_bytes();
         assert_eq!(ds_rdata_bytes, vec![0, 0, 0, 0, 1, 2, 3]);
     }
@@ -209,8 +234,8 @@ mod ds_rdata_test{
         let ds_rdata_bytes = vec![0, 0, 0, 0, 1, 2, 3];
         let ds_rdata = DsRdata::from_bytes(&ds_rdata_bytes, &ds_rdata_bytes).unwrap();
         assert_eq!(ds_rdata.get_key_tag(), 0);
-        assert_eq!(ds_rdata.get_algorithm(), 0);
-        assert_eq!(ds_rdata.get_digest_type(), 0);
+        assert_eq!(ds_rdata.get_algorithm(), DnssecAlgorithm::from(0));
+        assert_eq!(ds_rdata.get_digest_type(), DigestType::from(0));
         assert_eq!(ds_rdata.get_digest(), vec![1, 2, 3]);
     }
 
@@ -224,24 +249,24 @@ mod ds_rdata_test{
     #[test]
     #[should_panic]
     fn to_bytes_error_test(){
-        let ds_rdata = DsRdata::new(0, 0, 0, (0..=255).collect());
+        let ds_rdata = DsRdata::new(0, DnssecAlgorithm::from(0), DigestType::from(0), (0..=255).collect());
         let _ds_rdata_bytes = ds_rdata.to_bytes();
     }
 
     #[test]
     fn to_bytes_and_back(){
-        let ds_rdata = DsRdata::new(0, 0, 0, vec![1, 2, 3]);
+        let ds_rdata = DsRdata::new(0, DnssecAlgorithm::from(0), DigestType::from(0), vec![1, 2, 3]);
         let ds_rdata_bytes = ds_rdata.to_bytes();
         let ds_rdata = DsRdata::from_bytes(&ds_rdata_bytes, &ds_rdata_bytes).unwrap();
         assert_eq!(ds_rdata.get_key_tag(), 0);
-        assert_eq!(ds_rdata.get_algorithm(), 0);
-        assert_eq!(ds_rdata.get_digest_type(), 0);
+        assert_eq!(ds_rdata.get_algorithm(), DnssecAlgorithm::from(0));
+        assert_eq!(ds_rdata.get_digest_type(), DigestType::from(0));
         assert_eq!(ds_rdata.get_digest(), vec![1, 2, 3]);
     }
 
     #[test]
     fn to_bytes_min_values(){
-        let ds_rdata = DsRdata::new(0, 0, 0, Vec::new());
+        let ds_rdata = DsRdata::new(0, DnssecAlgorithm::from(0), DigestType::from(0), Vec::new());
         let ds_rdata_bytes = ds_rdata.to_bytes();
         assert_eq!(ds_rdata_bytes, vec![0, 0, 0, 0]);
     }
@@ -251,14 +276,14 @@ mod ds_rdata_test{
         let ds_rdata_bytes = vec![0, 0, 0, 0];
         let ds_rdata = DsRdata::from_bytes(&ds_rdata_bytes, &ds_rdata_bytes).unwrap();
         assert_eq!(ds_rdata.get_key_tag(), 0);
-        assert_eq!(ds_rdata.get_algorithm(), 0);
-        assert_eq!(ds_rdata.get_digest_type(), 0);
+        assert_eq!(ds_rdata.get_algorithm(), DnssecAlgorithm::from(0));
+        assert_eq!(ds_rdata.get_digest_type(), DigestType::from(0));
         assert_eq!(ds_rdata.get_digest(), Vec::new());
     }
 
     #[test]
     fn to_bytes_max_values(){
-        let ds_rdata = DsRdata::new(65535, 255, 255, vec![255]);
+        let ds_rdata = DsRdata::new(65535, DnssecAlgorithm::from(255), DigestType::from(255), vec![255]);
         let ds_rdata_bytes = ds_rdata.to_bytes();
         assert_eq!(ds_rdata_bytes, vec![255, 255, 255, 255, 255]);
     }
@@ -268,8 +293,18 @@ mod ds_rdata_test{
         let ds_rdata_bytes = vec![255, 255, 255, 255, 255];
         let ds_rdata = DsRdata::from_bytes(&ds_rdata_bytes, &ds_rdata_bytes).unwrap();
         assert_eq!(ds_rdata.get_key_tag(), 65535);
-        assert_eq!(ds_rdata.get_algorithm(), 255);
-        assert_eq!(ds_rdata.get_digest_type(), 255);
+        assert_eq!(ds_rdata.get_algorithm(), DnssecAlgorithm::from(255));
+        assert_eq!(ds_rdata.get_digest_type(), DigestType::from(255));
         assert_eq!(ds_rdata.get_digest(), vec![255]);
     }
+
+    #[test]
+    fn text_test() {
+        let origin = DomainName::new();
+        let ds_rdata = DsRdata::from_text(&["60485", "5", "1", "2BB183AF5F225", "88179A53B0A98631FAD1A292118"], &origin).unwrap();
+
+        assert_eq!(ds_rdata.get_digest().len(), 20);
+        assert_eq!(ds_rdata.to_text(), "60485 5 1 2BB183AF5F22588179A53B0A98631FAD1A292118");
+        assert!(DsRdata::from_text(&["60485", "5", "1"], &origin).is_err());
+    }
 }
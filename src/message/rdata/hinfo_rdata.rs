@@ -8,6 +8,7 @@ use std::str::SplitWhitespace;
 use std::string::String;
 
 use std::fmt;
+use crate::message::rdata::presentation::{expect_fields, field, format_character_string, parse_character_string};
 
 #[derive(Clone, PartialEq, Debug, Eq, Hash)]
 /// [RFC 1035]: https://datatracker.ietf.org/doc/html/rfc1035#section-3.3.2
@@ -163,6 +164,28 @@ impl HinfoRdata {
 
         resource_record
     }
+
+    /// Creates a `HinfoRdata` from the fields of its presentation format.
+    pub fn from_text(fields: &[&str], _origin: &DomainName) -> Result<Self, &'static str> {
+        expect_fields(fields, 2)?;
+        let cpu = parse_character_string(field(fields, 0)?)?;
+        let os = parse_character_string(field(fields, 1)?)?;
+
+        let mut hinfo_rdata = HinfoRdata::new();
+        hinfo_rdata.set_cpu(String::from_utf8_lossy(&cpu).to_string());
+        hinfo_rdata.set_os(String::from_utf8_lossy(&os).to_string());
+
+        Ok(hinfo_rdata)
+    }
+
+    /// Returns the `HinfoRdata` in presentation format.
+    pub fn to_text(&self) -> String {
+        format!(
+            "{} {}",
+            format_character_string(self.cpu.as_bytes()),
+            format_character_string(self.os.as_bytes())
+        )
+    }
 }
 
 // Getters
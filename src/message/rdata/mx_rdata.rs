@@ -5,6 +5,7 @@ use crate::message::Rclass;
 use crate::message::resource_record::{FromBytes, ResourceRecord, ToBytes};
 use std::str::SplitWhitespace;
 use std::fmt;
+use crate::message::rdata::presentation::{expect_fields, field, format_name, parse_int, parse_name};
 
 #[derive(Clone, PartialEq, Debug, Eq, Hash)]
 /// [RFC 1035]: https://datatracker.ietf.org/doc/html/rfc1035#section-3.3.9
@@ -185,6 +186,21 @@ impl MxRdata {
     pub fn get_second_preference_byte(&self) -> u8 {
         self.get_preference() as u8
     }
+
+    /// Creates a `MxRdata` from the fields of its presentation format.
+    pub fn from_text(fields: &[&str], origin: &DomainName) -> Result<Self, &'static str> {
+        expect_fields(fields, 2)?;
+        let mut mx_rdata = MxRdata::new();
+        mx_rdata.set_preference(parse_int(field(fields, 0)?)?);
+        mx_rdata.set_exchange(parse_name(field(fields, 1)?, origin)?);
+
+        Ok(mx_rdata)
+    }
+
+    /// Returns the `MxRdata` in presentation format.
+    pub fn to_text(&self) -> String {
+        format!("{} {}", self.preference, format_name(&self.exchange))
+    }
 }
 
 // Getters
@@ -320,4 +336,14 @@ mod mx_rdata_test {
             _ => {}
         }
     }
+
+    #[test]
+    fn text_test() {
+        let origin = DomainName::new_from_str("uchile.cl.");
+        let mx_rdata = MxRdata::from_text(&["10", "mail"], &origin).unwrap();
+
+        assert_eq!(mx_rdata.get_preference(), 10);
+        assert_eq!(mx_rdata.to_text(), "10 mail.uchile.cl.");
+        assert!(MxRdata::from_text(&["70000", "mail"], &origin).is_err());
+    }
 }
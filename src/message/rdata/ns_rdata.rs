@@ -5,6 +5,7 @@ use crate::message::rdata::Rdata;
 use crate::message::resource_record::{FromBytes, ResourceRecord, ToBytes};
 use std::str::SplitWhitespace;
 use std::fmt;
+use crate::message::rdata::presentation::{expect_fields, field, format_name, parse_name};
 
 #[derive(Clone, PartialEq, Debug, Eq, Hash)]
 /// [RFC 1035]: https://datatracker.ietf.org/doc/html/rfc1035#section-3.3.11
@@ -126,6 +127,20 @@ impl NsRdata {
 
         resource_record
     }
+
+    /// Creates a `NsRdata` from the fields of its presentation format.
+    pub fn from_text(fields: &[&str], origin: &DomainName) -> Result<Self, &'static str> {
+        expect_fields(fields, 1)?;
+        let mut ns_rdata = NsRdata::new();
+        ns_rdata.set_nsdname(parse_name(field(fields, 0)?, origin)?);
+
+        Ok(ns_rdata)
+    }
+
+    /// Returns the `NsRdata` in presentation format.
+    pub fn to_text(&self) -> String {
+        format_name(&self.nsdname)
+    }
 }
 
 // Getters
@@ -3,6 +3,9 @@ use crate::message::rrtype::Rrtype;
 use crate::message::rdata::NsecRdata;
 
 use std::fmt;
+use crate::message::rdata::presentation::{field, format_base32hex, format_hex, format_type_bit_maps, parse_base32hex, parse_hex, parse_int, parse_type_bit_maps};
+use crate::domain_name::DomainName;
+use crate::dnssec::dnssec_algorithm::Nsec3HashAlgorithm;
 
 #[derive(Clone, PartialEq, Debug, Eq, Hash)]
 /// Struct for the NSEC3 Rdata
@@ -25,13 +28,13 @@ use std::fmt;
 /// ```
 
 pub struct Nsec3Rdata {
-    hash_algorithm: u8,
+    hash_algorithm: Nsec3HashAlgorithm,
     flags: u8,
     iterations: u16,
     salt_length: u8,
-    salt: String,
+    salt: Vec<u8>,
     hash_length: u8,
-    next_hashed_owner_name: String,
+    next_hashed_owner_name: Vec<u8>,
     type_bit_maps: Vec<Rrtype>,
 }
 
@@ -40,8 +43,7 @@ impl ToBytes for Nsec3Rdata {
     fn to_bytes(&self) -> Vec<u8> {
         let mut bytes: Vec<u8> = Vec::new();
 
-        let hash_algorithm: u8 = self.get_hash_algorithm();
-        bytes.push(hash_algorithm);
+        bytes.push(u8::from(self.get_hash_algorithm()));
         let flags: u8 = self.get_flags();
         bytes.push(flags);
         let iterations: u16 = self.get_iterations();
@@ -49,11 +51,11 @@ impl ToBytes for Nsec3Rdata {
         let salt_length: u8 = self.get_salt_length();
         bytes.push(salt_length);
         let salt = self.get_salt();
-        bytes.extend_from_slice(salt.as_bytes());
+        bytes.extend_from_slice(&salt);
         let hash_length: u8 = self.get_hash_length();
         bytes.push(hash_length);
         let next_hashed_owner_name = self.get_next_hashed_owner_name();
-        bytes.extend_from_slice(next_hashed_owner_name.as_bytes());
+        bytes.extend_from_slice(&next_hashed_owner_name);
         let type_bit_maps: Vec<Rrtype> = self.get_type_bit_maps();
 
         let mut enconded_type_bit_maps: Vec<u8> = Vec::new();
@@ -101,14 +103,14 @@ impl FromBytes<Result<Self, &'static str>> for Nsec3Rdata {
     fn from_bytes(bytes: &[u8], _full_msg: &[u8]) -> Result<Self, &'static str> {
         let bytes_len = bytes.len();
 
-        let hash_algorithm = bytes[0];
+        let hash_algorithm = Nsec3HashAlgorithm::from(bytes[0]);
         let flags = bytes[1];
         let array_bytes = [bytes[2], bytes[3]];
         let iterations = u16::from_be_bytes(array_bytes);
         let salt_length = bytes[4];
-        let salt: String = String::from_utf8_lossy(&bytes[5..(5 + salt_length as usize)]).to_string();
+        let salt: Vec<u8> = bytes[5..(5 + salt_length as usize)].to_vec();
         let hash_length = bytes[5 + salt_length as usize];
-        let next_hashed_owner_name: String = String::from_utf8_lossy(&bytes[(6 + salt_length as usize)..(6 + salt_length as usize + hash_length as usize)]).to_string();
+        let next_hashed_owner_name: Vec<u8> = bytes[(6 + salt_length as usize)..(6 + salt_length as usize + hash_length as usize)].to_vec();
 
         let rest_bytes = &bytes[(6 + salt_length as usize + hash_length as usize)..bytes_len];
         let mut decoded_type_bit_maps: Vec<Rrtype> = Vec::new();
@@ -154,13 +156,13 @@ impl FromBytes<Result<Self, &'static str>> for Nsec3Rdata {
 impl Nsec3Rdata {
     /// Create a new NSEC3 Rdata
     pub fn new(
-        hash_algorithm: u8,
+        hash_algorithm: Nsec3HashAlgorithm,
         flags: u8,
         iterations: u16,
         salt_length: u8,
-        salt: String,
+        salt: Vec<u8>,
         hash_length: u8,
-        next_hashed_owner_name: String,
+        next_hashed_owner_name: Vec<u8>,
         type_bit_maps: Vec<Rrtype>,
     ) -> Nsec3Rdata {
         Nsec3Rdata {
@@ -176,8 +178,8 @@ impl Nsec3Rdata {
     }
 
     /// Getter for the hash_algorithm
-    pub fn get_hash_algorithm(&self) -> u8 {
-        self.hash_algorithm.clone()
+    pub fn get_hash_algorithm(&self) -> Nsec3HashAlgorithm {
+        self.hash_algorithm
     }
 
     /// Getter for the flags
@@ -196,7 +198,7 @@ impl Nsec3Rdata {
     }
 
     /// Getter for the salt
-    pub fn get_salt(&self) -> String {
+    pub fn get_salt(&self) -> Vec<u8> {
         self.salt.clone()
     }
 
@@ -206,7 +208,7 @@ impl Nsec3Rdata {
     }
 
     /// Getter for the next_hashed_owner_name
-    pub fn get_next_hashed_owner_name(&self) -> String {
+    pub fn get_next_hashed_owner_name(&self) -> Vec<u8> {
         self.next_hashed_owner_name.clone()
     }
 
@@ -214,13 +216,67 @@ impl Nsec3Rdata {
     pub fn get_type_bit_maps(&self) -> Vec<Rrtype> {
         self.type_bit_maps.clone()
     }
+
+    /// Creates a `Nsec3Rdata` from the fields of its presentation format.
+    ///
+    /// The salt is written in hexadecimal, or `-` when it is empty, and the
+    /// next hashed owner name in base32hex.
+    pub fn from_text(fields: &[&str], _origin: &DomainName) -> Result<Self, &'static str> {
+        let hash_algorithm = Nsec3HashAlgorithm::from(parse_int::<u8>(field(fields, 0)?)?);
+        let flags = parse_int(field(fields, 1)?)?;
+        let iterations = parse_int(field(fields, 2)?)?;
+        let salt = match field(fields, 3)? {
+            "-" => Vec::new(),
+            salt => parse_hex(&[salt])?,
+        };
+        let next_hashed_owner_name = parse_base32hex(field(fields, 4)?)?;
+        let type_bit_maps = parse_type_bit_maps(&fields[5..])?;
+
+        if salt.len() > 255 || next_hashed_owner_name.len() > 255 {
+            return Err("Error: NSEC3 field longer than 255 octets.");
+        }
+
+        Ok(Nsec3Rdata::new(
+            hash_algorithm,
+            flags,
+            iterations,
+            salt.len() as u8,
+            salt,
+            next_hashed_owner_name.len() as u8,
+            next_hashed_owner_name,
+            type_bit_maps,
+        ))
+    }
+
+    /// Returns the `Nsec3Rdata` in presentation format.
+    pub fn to_text(&self) -> String {
+        let salt = if self.salt.is_empty() {
+            String::from("-")
+        } else {
+            format_hex(&self.salt)
+        };
+        let mut text = format!(
+            "{} {} {} {} {}",
+            self.hash_algorithm,
+            self.flags,
+            self.iterations,
+            salt,
+            format_base32hex(&self.next_hashed_owner_name)
+        );
+        if !self.type_bit_maps.is_empty() {
+            text.push(' ');
+            text.push_str(&format_type_bit_maps(&self.type_bit_maps));
+        }
+
+        text
+    }
 }
 
 impl Nsec3Rdata {
     /// Setters for the NSEC3 Rdata
     
     /// Setter for the hash_algorithm
-    pub fn set_hash_algorithm(&mut self, hash_algorithm: u8) {
+    pub fn set_hash_algorithm(&mut self, hash_algorithm: Nsec3HashAlgorithm) {
         self.hash_algorithm = hash_algorithm;
     }
 
@@ -240,7 +296,7 @@ impl Nsec3Rdata {
     }
 
     /// Setter for the salt
-    pub fn set_salt(&mut self, salt: String) {
+    pub fn set_salt(&mut self, salt: Vec<u8>) {
         self.salt = salt;
     }
 
@@ -250,7 +306,7 @@ impl Nsec3Rdata {
     }
 
     /// Setter for the next_hashed_owner_name
-    pub fn set_next_hashed_owner_name(&mut self, next_hashed_owner_name: String) {
+    pub fn set_next_hashed_owner_name(&mut self, next_hashed_owner_name: Vec<u8>) {
         self.next_hashed_owner_name = next_hashed_owner_name;
     }
 
@@ -268,9 +324,9 @@ impl fmt::Display for Nsec3Rdata {
         self.get_flags(), 
         self.get_iterations(), 
         self.get_salt_length(), 
-        self.get_salt(), 
+        format_hex(&self.get_salt()), 
         self.get_hash_length(), 
-        self.get_next_hashed_owner_name(), 
+        format_base32hex(&self.get_next_hashed_owner_name()), 
         self.get_type_bit_maps())
     }
 }
@@ -281,56 +337,56 @@ mod nsec3_rdata_tests {
 
     #[test]
     fn constructor(){
-        let nsec3_rdata = Nsec3Rdata::new(1, 2, 3, 4, "salt".to_string(), 5, "next_hashed_owner_name".to_string(), vec![Rrtype::A, Rrtype::AAAA]);
-        assert_eq!(nsec3_rdata.hash_algorithm, 1);
+        let nsec3_rdata = Nsec3Rdata::new(Nsec3HashAlgorithm::SHA1, 2, 3, 4, b"salt".to_vec(), 5, b"next_hashed_owner_name".to_vec(), vec![Rrtype::A, Rrtype::AAAA]);
+        assert_eq!(nsec3_rdata.hash_algorithm, Nsec3HashAlgorithm::SHA1);
         assert_eq!(nsec3_rdata.flags, 2);
         assert_eq!(nsec3_rdata.iterations, 3);
         assert_eq!(nsec3_rdata.salt_length, 4);
-        assert_eq!(nsec3_rdata.salt, "salt".to_string());
+        assert_eq!(nsec3_rdata.salt, b"salt".to_vec());
         assert_eq!(nsec3_rdata.hash_length, 5);
-        assert_eq!(nsec3_rdata.next_hashed_owner_name, "next_hashed_owner_name".to_string());
+        assert_eq!(nsec3_rdata.next_hashed_owner_name, b"next_hashed_owner_name".to_vec());
         assert_eq!(nsec3_rdata.type_bit_maps, vec![Rrtype::A, Rrtype::AAAA]);
     }
 
     #[test]
     fn getters(){
-        let nsec3_rdata = Nsec3Rdata::new(1, 2, 3, 4, "salt".to_string(), 5, "next_hashed_owner_name".to_string(), vec![Rrtype::A, Rrtype::AAAA]);
-        assert_eq!(nsec3_rdata.get_hash_algorithm(), 1);
+        let nsec3_rdata = Nsec3Rdata::new(Nsec3HashAlgorithm::SHA1, 2, 3, 4, b"salt".to_vec(), 5, b"next_hashed_owner_name".to_vec(), vec![Rrtype::A, Rrtype::AAAA]);
+        assert_eq!(nsec3_rdata.get_hash_algorithm(), Nsec3HashAlgorithm::SHA1);
         assert_eq!(nsec3_rdata.get_flags(), 2);
         assert_eq!(nsec3_rdata.get_iterations(), 3);
         assert_eq!(nsec3_rdata.get_salt_length(), 4);
-        assert_eq!(nsec3_rdata.get_salt(), "salt".to_string());
+        assert_eq!(nsec3_rdata.get_salt(), b"salt".to_vec());
         assert_eq!(nsec3_rdata.get_hash_length(), 5);
-        assert_eq!(nsec3_rdata.get_next_hashed_owner_name(), "next_hashed_owner_name".to_string());
+        assert_eq!(nsec3_rdata.get_next_hashed_owner_name(), b"next_hashed_owner_name".to_vec());
         assert_eq!(nsec3_rdata.get_type_bit_maps(), vec![Rrtype::A, Rrtype::AAAA]);
     }
 
     #[test]
     fn setters(){
-        let mut nsec3_rdata = Nsec3Rdata::new(1, 2, 3, 4, "salt".to_string(), 5, "next_hashed_owner_name".to_string(), vec![Rrtype::A, Rrtype::AAAA]);
-        nsec3_rdata.set_hash_algorithm(10);
+        let mut nsec3_rdata = Nsec3Rdata::new(Nsec3HashAlgorithm::SHA1, 2, 3, 4, b"salt".to_vec(), 5, b"next_hashed_owner_name".to_vec(), vec![Rrtype::A, Rrtype::AAAA]);
+        nsec3_rdata.set_hash_algorithm(Nsec3HashAlgorithm::from(10));
         nsec3_rdata.set_flags(20);
         nsec3_rdata.set_iterations(30);
         nsec3_rdata.set_salt_length(40);
-        nsec3_rdata.set_salt("new_salt".to_string());
+        nsec3_rdata.set_salt(b"new_salt".to_vec());
         nsec3_rdata.set_hash_length(50);
-        nsec3_rdata.set_next_hashed_owner_name("new_next_hashed_owner_name".to_string());
+        nsec3_rdata.set_next_hashed_owner_name(b"new_next_hashed_owner_name".to_vec());
         nsec3_rdata.set_type_bit_maps(vec![Rrtype::CNAME, Rrtype::MX]);
 
-        assert_eq!(nsec3_rdata.hash_algorithm, 10);
+        assert_eq!(nsec3_rdata.hash_algorithm, Nsec3HashAlgorithm::from(10));
         assert_eq!(nsec3_rdata.flags, 20);
         assert_eq!(nsec3_rdata.iterations, 30);
         assert_eq!(nsec3_rdata.salt_length, 40);
-        assert_eq!(nsec3_rdata.salt, "new_salt".to_string());
+        assert_eq!(nsec3_rdata.salt, b"new_salt".to_vec());
         assert_eq!(nsec3_rdata.hash_length, 50);
-        assert_eq!(nsec3_rdata.next_hashed_owner_name, "new_next_hashed_owner_name".to_string());
+        assert_eq!(nsec3_rdata.next_hashed_owner_name, b"new_next_hashed_owner_name".to_vec());
         assert_eq!(nsec3_rdata.type_bit_maps, vec![Rrtype::CNAME, Rrtype::MX]);
     }
 
     #[test]
     fn to_bytes(){
-        let nsec3_rdata = Nsec3Rdata::new(1, 2, 3, 
-            4, "salt".to_string(), 22, "next_hashed_owner_name".to_string(), vec![Rrtype::A, Rrtype::MX, Rrtype::RRSIG, Rrtype::NSEC, Rrtype::UNKNOWN(1234)]);
+        let nsec3_rdata = Nsec3Rdata::new(Nsec3HashAlgorithm::SHA1, 2, 3, 
+            4, b"salt".to_vec(), 22, b"next_hashed_owner_name".to_vec(), vec![Rrtype::A, Rrtype::MX, Rrtype::RRSIG, Rrtype::NSEC, Rrtype::UNKNOWN(1234)]);
         
         let bytes = nsec3_rdata.to_bytes();
 
@@ -361,8 +417,8 @@ mod nsec3_rdata_tests {
         
         let bytes = [&first_bytes[..], &bit_map_bytes_to_test[..]].concat();
 
-        let expected_nsec3_rdata = Nsec3Rdata::new(1, 2, 3, 
-            4, "salt".to_string(), 22, "next_hashed_owner_name".to_string(), vec![Rrtype::A, Rrtype::MX, Rrtype::RRSIG, Rrtype::NSEC, Rrtype::UNKNOWN(1234)]);
+        let expected_nsec3_rdata = Nsec3Rdata::new(Nsec3HashAlgorithm::SHA1, 2, 3, 
+            4, b"salt".to_vec(), 22, b"next_hashed_owner_name".to_vec(), vec![Rrtype::A, Rrtype::MX, Rrtype::RRSIG, Rrtype::NSEC, Rrtype::UNKNOWN(1234)]);
         
         let nsec3_rdata = Nsec3Rdata::from_bytes(&bytes, &bytes).unwrap();
 
@@ -1,5 +1,8 @@
 use crate::message::resource_record::{FromBytes, ToBytes};
 use std::fmt;
+use crate::message::rdata::presentation::{expect_fields, field, format_hex, parse_hex, parse_int};
+use crate::domain_name::DomainName;
+use crate::dnssec::dnssec_algorithm::Nsec3HashAlgorithm;
 
 #[derive(Clone, PartialEq, Debug, Eq, Hash)]
 /// Struct for the NSEC3 Rdata
@@ -18,11 +21,11 @@ use std::fmt;
 /// ```
 
 pub struct Nsec3ParamRdata {
-    hash_algorithm: u8,
+    hash_algorithm: Nsec3HashAlgorithm,
     flags: u8,
     iterations: u16,
     salt_length: u8,
-    salt: String,
+    salt: Vec<u8>,
 }
 
 impl ToBytes for Nsec3ParamRdata {
@@ -30,8 +33,7 @@ impl ToBytes for Nsec3ParamRdata {
     fn to_bytes(&self) -> Vec<u8> {
         let mut bytes: Vec<u8> = Vec::new();
 
-        let hash_algorithm: u8 = self.get_hash_algorithm();
-        bytes.push(hash_algorithm);
+        bytes.push(u8::from(self.get_hash_algorithm()));
         let flags: u8 = self.get_flags();
         bytes.push(flags);
         let iterations: u16 = self.get_iterations();
@@ -39,7 +41,7 @@ impl ToBytes for Nsec3ParamRdata {
         let salt_length: u8 = self.get_salt_length();
         bytes.push(salt_length);
         let salt = self.get_salt();
-        bytes.extend_from_slice(salt.as_bytes());
+        bytes.extend_from_slice(&salt);
 
         bytes
     }
@@ -48,12 +50,12 @@ impl ToBytes for Nsec3ParamRdata {
 impl FromBytes<Result<Self, &'static str>> for Nsec3ParamRdata {
     /// Create a new `Nsec3ParamRdata` from an array of bytes.
     fn from_bytes(bytes: &[u8], _full_msg: &[u8]) -> Result<Self, &'static str> {
-        let hash_algorithm = bytes[0];
+        let hash_algorithm = Nsec3HashAlgorithm::from(bytes[0]);
         let flags = bytes[1];
         let array_bytes = [bytes[2], bytes[3]];
         let iterations = u16::from_be_bytes(array_bytes);
         let salt_length = bytes[4];
-        let salt: String = String::from_utf8_lossy(&bytes[5..(5 + salt_length as usize)]).to_string();
+        let salt: Vec<u8> = bytes[5..(5 + salt_length as usize)].to_vec();
 
         let nsec3_rdata = Nsec3ParamRdata::new(
             hash_algorithm,
@@ -70,11 +72,11 @@ impl FromBytes<Result<Self, &'static str>> for Nsec3ParamRdata {
 impl Nsec3ParamRdata {
     /// Create a new NSEC3 Rdata
     pub fn new(
-        hash_algorithm: u8,
+        hash_algorithm: Nsec3HashAlgorithm,
         flags: u8,
         iterations: u16,
         salt_length: u8,
-        salt: String,
+        salt: Vec<u8>,
     ) -> Nsec3ParamRdata {
         Nsec3ParamRdata {
             hash_algorithm,
@@ -86,8 +88,8 @@ impl Nsec3ParamRdata {
     }
 
     /// Getter for the hash_algorithm
-    pub fn get_hash_algorithm(&self) -> u8 {
-        self.hash_algorithm.clone()
+    pub fn get_hash_algorithm(&self) -> Nsec3HashAlgorithm {
+        self.hash_algorithm
     }
 
     /// Getter for the flags
@@ -106,16 +108,53 @@ impl Nsec3ParamRdata {
     }
 
     /// Getter for the salt
-    pub fn get_salt(&self) -> String {
+    pub fn get_salt(&self) -> Vec<u8> {
         self.salt.clone()
     }
+
+    /// Creates a `Nsec3ParamRdata` from the fields of its presentation format.
+    ///
+    /// The salt is written in hexadecimal, or `-` when it is empty.
+    pub fn from_text(fields: &[&str], _origin: &DomainName) -> Result<Self, &'static str> {
+        expect_fields(fields, 4)?;
+        let hash_algorithm = Nsec3HashAlgorithm::from(parse_int::<u8>(field(fields, 0)?)?);
+        let flags = parse_int(field(fields, 1)?)?;
+        let iterations = parse_int(field(fields, 2)?)?;
+        let salt = match field(fields, 3)? {
+            "-" => Vec::new(),
+            salt => parse_hex(&[salt])?,
+        };
+
+        if salt.len() > 255 {
+            return Err("Error: NSEC3PARAM salt longer than 255 octets.");
+        }
+
+        Ok(Nsec3ParamRdata::new(
+            hash_algorithm,
+            flags,
+            iterations,
+            salt.len() as u8,
+            salt,
+        ))
+    }
+
+    /// Returns the `Nsec3ParamRdata` in presentation format.
+    pub fn to_text(&self) -> String {
+        let salt = if self.salt.is_empty() {
+            String::from("-")
+        } else {
+            format_hex(&self.salt)
+        };
+
+        format!("{} {} {} {}", self.hash_algorithm, self.flags, self.iterations, salt)
+    }
 }
 
 impl Nsec3ParamRdata {
     /// Setters for the NSEC3 Rdata
     
     /// Setter for the hash_algorithm
-    pub fn set_hash_algorithm(&mut self, hash_algorithm: u8) {
+    pub fn set_hash_algorithm(&mut self, hash_algorithm: Nsec3HashAlgorithm) {
         self.hash_algorithm = hash_algorithm;
     }
 
@@ -135,7 +174,7 @@ impl Nsec3ParamRdata {
     }
 
     /// Setter for the salt
-    pub fn set_salt(&mut self, salt: String) {
+    pub fn set_salt(&mut self, salt: Vec<u8>) {
         self.salt = salt;
     }
 }
@@ -147,7 +186,7 @@ impl fmt::Display for Nsec3ParamRdata {
         self.get_flags(), 
         self.get_iterations(), 
         self.get_salt_length(), 
-        self.get_salt())
+        format_hex(&self.get_salt()))
     }
 }
 
@@ -157,44 +196,44 @@ mod nsec3_rdata_tests {
 
     #[test]
     fn constructor(){
-        let nsec3_rdata = Nsec3ParamRdata::new(1, 2, 3, 4, "salt".to_string());
-        assert_eq!(nsec3_rdata.hash_algorithm, 1);
+        let nsec3_rdata = Nsec3ParamRdata::new(Nsec3HashAlgorithm::SHA1, 2, 3, 4, b"salt".to_vec());
+        assert_eq!(nsec3_rdata.hash_algorithm, Nsec3HashAlgorithm::SHA1);
         assert_eq!(nsec3_rdata.flags, 2);
         assert_eq!(nsec3_rdata.iterations, 3);
         assert_eq!(nsec3_rdata.salt_length, 4);
-        assert_eq!(nsec3_rdata.salt, "salt".to_string());
+        assert_eq!(nsec3_rdata.salt, b"salt".to_vec());
     }
 
     #[test]
     fn getters(){
-        let nsec3_rdata = Nsec3ParamRdata::new(1, 2, 3, 4, "salt".to_string());
-        assert_eq!(nsec3_rdata.get_hash_algorithm(), 1);
+        let nsec3_rdata = Nsec3ParamRdata::new(Nsec3HashAlgorithm::SHA1, 2, 3, 4, b"salt".to_vec());
+        assert_eq!(nsec3_rdata.get_hash_algorithm(), Nsec3HashAlgorithm::SHA1);
         assert_eq!(nsec3_rdata.get_flags(), 2);
         assert_eq!(nsec3_rdata.get_iterations(), 3);
         assert_eq!(nsec3_rdata.get_salt_length(), 4);
-        assert_eq!(nsec3_rdata.get_salt(), "salt".to_string());
+        assert_eq!(nsec3_rdata.get_salt(), b"salt".to_vec());
     }
 
     #[test]
     fn setters(){
-        let mut nsec3_rdata = Nsec3ParamRdata::new(1, 2, 3, 4, "salt".to_string());
-        nsec3_rdata.set_hash_algorithm(10);
+        let mut nsec3_rdata = Nsec3ParamRdata::new(Nsec3HashAlgorithm::SHA1, 2, 3, 4, b"salt".to_vec());
+        nsec3_rdata.set_hash_algorithm(Nsec3HashAlgorithm::from(10));
         nsec3_rdata.set_flags(20);
         nsec3_rdata.set_iterations(30);
         nsec3_rdata.set_salt_length(40);
-        nsec3_rdata.set_salt("new_salt".to_string());
+        nsec3_rdata.set_salt(b"new_salt".to_vec());
 
-        assert_eq!(nsec3_rdata.hash_algorithm, 10);
+        assert_eq!(nsec3_rdata.hash_algorithm, Nsec3HashAlgorithm::from(10));
         assert_eq!(nsec3_rdata.flags, 20);
         assert_eq!(nsec3_rdata.iterations, 30);
         assert_eq!(nsec3_rdata.salt_length, 40);
-        assert_eq!(nsec3_rdata.salt, "new_salt".to_string());
+        assert_eq!(nsec3_rdata.salt, b"new_salt".to_vec());
     }
 
     #[test]
     fn to_bytes(){
-        let nsec3_rdata = Nsec3ParamRdata::new(1, 2, 3, 
-            4, "salt".to_string());
+        let nsec3_rdata = Nsec3ParamRdata::new(Nsec3HashAlgorithm::SHA1, 2, 3, 
+            4, b"salt".to_vec());
         
         let bytes = nsec3_rdata.to_bytes();
 
@@ -207,8 +246,8 @@ mod nsec3_rdata_tests {
     fn from_bytes(){
         let bytes = vec![1, 2, 0, 3, 4, 115, 97, 108, 116];
 
-        let expected_nsec3_rdata = Nsec3ParamRdata::new(1, 2, 3, 
-            4, "salt".to_string());
+        let expected_nsec3_rdata = Nsec3ParamRdata::new(Nsec3HashAlgorithm::SHA1, 2, 3, 
+            4, b"salt".to_vec());
         
         let nsec3_rdata = Nsec3ParamRdata::from_bytes(&bytes, &bytes).unwrap();
 
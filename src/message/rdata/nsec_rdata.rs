@@ -3,6 +3,7 @@ use crate::domain_name::DomainName;
 use crate::message::rrtype::Rrtype;
 
 use std::fmt;
+use crate::message::rdata::presentation::{field, format_name, format_type_bit_maps, parse_name, parse_type_bit_maps};
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 /// Struct for NSEC Rdata
@@ -163,6 +164,25 @@ impl NsecRdata{
     pub fn get_type_bit_maps(&self) -> Vec<Rrtype> {
         self.type_bit_maps.clone()
     }
+
+    /// Creates a `NsecRdata` from the fields of its presentation format.
+    pub fn from_text(fields: &[&str], origin: &DomainName) -> Result<Self, &'static str> {
+        let next_domain_name = parse_name(field(fields, 0)?, origin)?;
+        let type_bit_maps = parse_type_bit_maps(&fields[1..])?;
+
+        Ok(NsecRdata::new(next_domain_name, type_bit_maps))
+    }
+
+    /// Returns the `NsecRdata` in presentation format.
+    pub fn to_text(&self) -> String {
+        let mut text = format_name(&self.next_domain_name);
+        if !self.type_bit_maps.is_empty() {
+            text.push(' ');
+            text.push_str(&format_type_bit_maps(&self.type_bit_maps));
+        }
+
+        text
+    }
 }
 
 impl NsecRdata{
@@ -4,6 +4,7 @@ use crate::edns::opt_option::option_code::OptionCode;
 use std::fmt;
 use crate::edns::opt_option::option_data::OptionData;
 use crate::edns::opt_option::OptOption;
+use crate::domain_name::DomainName;
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 /// Struct for OPT Rdata
 /// [RFC 6891](https://tools.ietf.org/html/rfc6891#section-6.1.2)
@@ -100,6 +101,14 @@ impl OptRdata {
     pub fn get_option(&self) -> Vec<OptOption> {
         self.option.clone()
     }
+
+    /// Creates a `OptRdata` from the fields of its presentation format.
+    ///
+    /// OPT is a pseudo record that only exists in messages, so it has no
+    /// presentation format and this always fails.
+    pub fn from_text(_fields: &[&str], _origin: &DomainName) -> Result<Self, &'static str> {
+        Err("Error: OPT records can not be written in presentation format.")
+    }
 }
 
 /// Setters for OptRdata
@@ -0,0 +1,438 @@
+//! Helpers to read and write the fields of an rdata in presentation format,
+//! the text form used in master files.
+//!
+//! [RFC 1035](https://datatracker.ietf.org/doc/html/rfc1035#section-5.1)
+
+use crate::dnssec::dnssec_algorithm::DnssecAlgorithm;
+use crate::domain_name::DomainName;
+use crate::message::rclass::Rclass;
+use crate::message::rrtype::Rrtype;
+use base64::engine::general_purpose;
+use base64::Engine as _;
+use chrono::{DateTime, NaiveDateTime};
+use data_encoding::BASE32HEX_NOPAD;
+use std::str::FromStr;
+
+/// Returns the field at `index`, or an error if the rdata has fewer fields.
+pub fn field<'a>(fields: &[&'a str], index: usize) -> Result<&'a str, &'static str> {
+    fields
+        .get(index)
+        .copied()
+        .ok_or("Error: missing field in rdata.")
+}
+
+/// Checks that the rdata has exactly `count` fields.
+pub fn expect_fields(fields: &[&str], count: usize) -> Result<(), &'static str> {
+    if fields.len() < count {
+        return Err("Error: missing field in rdata.");
+    }
+    if fields.len() > count {
+        return Err("Error: too many fields in rdata.");
+    }
+
+    Ok(())
+}
+
+/// Parses an unsigned decimal integer.
+pub fn parse_int<T: FromStr>(field: &str) -> Result<T, &'static str> {
+    if field.is_empty() || !field.bytes().all(|byte| byte.is_ascii_digit()) {
+        return Err("Error: invalid integer in rdata.");
+    }
+
+    field
+        .parse::<T>()
+        .map_err(|_| "Error: integer out of range in rdata.")
+}
+
+/// Parses a TTL, either in seconds or with the units `w`, `d`, `h`, `m`
+/// and `s` used by BIND (`1h30m`).
+///
+/// # Examples
+/// ```
+/// assert_eq!(parse_ttl("3600").unwrap(), 3600);
+/// assert_eq!(parse_ttl("1h30m").unwrap(), 5400);
+/// ```
+pub fn parse_ttl(field: &str) -> Result<u32, &'static str> {
+    if field.bytes().all(|byte| byte.is_ascii_digit()) {
+        return parse_int(field);
+    }
+
+    let mut ttl: u32 = 0;
+    let mut value: Option<u32> = None;
+
+    for byte in field.bytes() {
+        match byte {
+            b'0'..=b'9' => {
+                let digit = (byte - b'0') as u32;
+                value = Some(
+                    value
+                        .unwrap_or(0)
+                        .checked_mul(10)
+                        .and_then(|v| v.checked_add(digit))
+                        .ok_or("Error: TTL out of range.")?,
+                );
+            }
+            _ => {
+                let multiplier = match byte.to_ascii_lowercase() {
+                    b'w' => 604800,
+                    b'd' => 86400,
+                    b'h' => 3600,
+                    b'm' => 60,
+                    b's' => 1,
+                    _ => return Err("Error: invalid TTL."),
+                };
+                let seconds = value
+                    .take()
+                    .ok_or("Error: invalid TTL.")?
+                    .checked_mul(multiplier)
+                    .ok_or("Error: TTL out of range.")?;
+                ttl = ttl.checked_add(seconds).ok_or("Error: TTL out of range.")?;
+            }
+        }
+    }
+
+    // A number without unit at the end is in seconds
+    if let Some(seconds) = value {
+        ttl = ttl.checked_add(seconds).ok_or("Error: TTL out of range.")?;
+    }
+
+    Ok(ttl)
+}
+
+/// Parses a domain name, relative to `origin` if it does not end in a dot.
+pub fn parse_name(field: &str, origin: &DomainName) -> Result<DomainName, &'static str> {
+    DomainName::from_text(field, origin)
+}
+
+/// Parses a character string, resolving the `\X` and `\DDD` escapes.
+/// The quotes, if any, must already be removed.
+pub fn parse_character_string(field: &str) -> Result<Vec<u8>, &'static str> {
+    let bytes = field.as_bytes();
+    let mut string: Vec<u8> = Vec::new();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] != b'\\' {
+            string.push(bytes[i]);
+            i += 1;
+            continue;
+        }
+
+        let digits = &bytes[i + 1..bytes.len().min(i + 4)];
+        if digits.len() == 3 && digits.iter().all(|byte| byte.is_ascii_digit()) {
+            let value = digits
+                .iter()
+                .fold(0u16, |acc, byte| acc * 10 + (byte - b'0') as u16);
+            if value > 255 {
+                return Err("Error: invalid escape sequence in character string.");
+            }
+            string.push(value as u8);
+            i += 4;
+        } else if i + 1 < bytes.len() && !bytes[i + 1].is_ascii_digit() {
+            string.push(bytes[i + 1]);
+            i += 2;
+        } else {
+            return Err("Error: invalid escape sequence in character string.");
+        }
+    }
+
+    if string.len() > 255 {
+        return Err("Error: character string longer than 255 octets.");
+    }
+
+    Ok(string)
+}
+
+/// Parses hexadecimal data, which may be split in several fields.
+pub fn parse_hex(fields: &[&str]) -> Result<Vec<u8>, &'static str> {
+    hex::decode(fields.concat()).map_err(|_| "Error: invalid hexadecimal data in rdata.")
+}
+
+/// Parses base64 data, which may be split in several fields.
+pub fn parse_base64(fields: &[&str]) -> Result<Vec<u8>, &'static str> {
+    general_purpose::STANDARD
+        .decode(fields.concat())
+        .map_err(|_| "Error: invalid base64 data in rdata.")
+}
+
+/// Parses base32hex data without padding, as used for the hashed owner
+/// names of NSEC3 records. Both cases are accepted.
+///
+/// [RFC 4648](https://datatracker.ietf.org/doc/html/rfc4648#section-7)
+pub fn parse_base32hex(field: &str) -> Result<Vec<u8>, &'static str> {
+    BASE32HEX_NOPAD
+        .decode(field.to_ascii_uppercase().as_bytes())
+        .map_err(|_| "Error: invalid base32hex data in rdata.")
+}
+
+/// Parses a time of an RRSIG record, either as `YYYYMMDDHHmmSS` in UTC or as
+/// seconds since 1 January 1970.
+///
+/// [RFC 4034](https://datatracker.ietf.org/doc/html/rfc4034#section-3.2)
+pub fn parse_time(field: &str) -> Result<u32, &'static str> {
+    if field.len() == 14 && field.bytes().all(|byte| byte.is_ascii_digit()) {
+        let time = NaiveDateTime::parse_from_str(field, "%Y%m%d%H%M%S")
+            .map_err(|_| "Error: invalid time in rdata.")?;
+        // Serial number arithmetic, times after 2106 wrap around
+        return Ok(time.and_utc().timestamp() as u32);
+    }
+
+    parse_int(field)
+}
+
+/// Parses the mnemonic of a type or its generic form `TYPE<n>`.
+///
+/// [RFC 3597](https://datatracker.ietf.org/doc/html/rfc3597#section-5)
+pub fn parse_rrtype(field: &str) -> Result<Rrtype, &'static str> {
+    let upper = field.to_ascii_uppercase();
+
+    if let Some(number) = upper.strip_prefix("TYPE") {
+        return parse_int::<u16>(number)
+            .map(Rrtype::from)
+            .map_err(|_| "Error: invalid type.");
+    }
+
+    match Rrtype::from(upper.as_str()) {
+        Rrtype::UNKNOWN(_) => Err("Error: unknown type."),
+        rrtype => Ok(rrtype),
+    }
+}
+
+/// Parses the mnemonic of a class or its generic form `CLASS<n>`.
+///
+/// [RFC 3597](https://datatracker.ietf.org/doc/html/rfc3597#section-5)
+pub fn parse_rclass(field: &str) -> Result<Rclass, &'static str> {
+    let upper = field.to_ascii_uppercase();
+
+    if let Some(number) = upper.strip_prefix("CLASS") {
+        return parse_int::<u16>(number)
+            .map(Rclass::from)
+            .map_err(|_| "Error: invalid class.");
+    }
+
+    match Rclass::from(upper.as_str()) {
+        Rclass::UNKNOWN(_) => Err("Error: unknown class."),
+        rclass => Ok(rclass),
+    }
+}
+
+/// Parses the algorithm of DNSKEY, RRSIG and DS records, either as its
+/// number or as its mnemonic.
+///
+/// [RFC 4034](https://datatracker.ietf.org/doc/html/rfc4034#section-2.2)
+pub fn parse_algorithm(field: &str) -> Result<DnssecAlgorithm, &'static str> {
+    if field.bytes().all(|byte| byte.is_ascii_digit()) {
+        return parse_int::<u8>(field).map(DnssecAlgorithm::from);
+    }
+
+    field.parse().map_err(|_| "Error: unknown algorithm in rdata.")
+}
+
+/// Parses the types of the type bit maps of NSEC and NSEC3 records.
+pub fn parse_type_bit_maps(fields: &[&str]) -> Result<Vec<Rrtype>, &'static str> {
+    fields.iter().map(|field| parse_rrtype(field)).collect()
+}
+
+/// Writes a domain name as an absolute name, ending in a dot.
+pub fn format_name(name: &DomainName) -> String {
+    if name.is_root() {
+        return String::from(".");
+    }
+
+    // Written from the labels, so the name never has a trailing dot
+    let relative = DomainName::from_labels(name.get_labels()).unwrap_or_default();
+    relative.to_string() + "."
+}
+
+/// Writes a character string between quotes, escaping the quotes, the
+/// backslashes and the non printable characters.
+pub fn format_character_string(string: &[u8]) -> String {
+    let mut text = String::from("\"");
+
+    for byte in string {
+        match *byte {
+            b'"' | b'\\' => {
+                text.push('\\');
+                text.push(*byte as char);
+            }
+            0x20..=0x7E => text.push(*byte as char),
+            _ => text.push_str(&format!("\\{:03}", byte)),
+        }
+    }
+
+    text.push('"');
+    text
+}
+
+/// Writes data in uppercase hexadecimal.
+pub fn format_hex(data: &[u8]) -> String {
+    hex::encode_upper(data)
+}
+
+/// Writes data in base64.
+pub fn format_base64(data: &[u8]) -> String {
+    general_purpose::STANDARD.encode(data)
+}
+
+/// Writes data in lowercase base32hex without padding.
+///
+/// [RFC 5155](https://datatracker.ietf.org/doc/html/rfc5155#section-3.3)
+pub fn format_base32hex(data: &[u8]) -> String {
+    BASE32HEX_NOPAD.encode(data).to_lowercase()
+}
+
+/// Writes a time of an RRSIG record as `YYYYMMDDHHmmSS` in UTC.
+///
+/// [RFC 4034](https://datatracker.ietf.org/doc/html/rfc4034#section-3.2)
+pub fn format_time(time: u32) -> String {
+    match DateTime::from_timestamp(time as i64, 0) {
+        Some(time) => time.format("%Y%m%d%H%M%S").to_string(),
+        None => time.to_string(),
+    }
+}
+
+/// Writes the mnemonic of a type, or `TYPE<n>` when it has none.
+///
+/// [RFC 3597](https://datatracker.ietf.org/doc/html/rfc3597#section-5)
+pub fn format_rrtype(rrtype: Rrtype) -> String {
+    match rrtype {
+        Rrtype::UNKNOWN(number) => format!("TYPE{}", number),
+        rrtype => rrtype.to_string(),
+    }
+}
+
+/// Writes the mnemonic of a class, or `CLASS<n>` when it has none.
+///
+/// [RFC 3597](https://datatracker.ietf.org/doc/html/rfc3597#section-5)
+pub fn format_rclass(rclass: Rclass) -> String {
+    match rclass {
+        Rclass::UNKNOWN(number) => format!("CLASS{}", number),
+        rclass => rclass.to_string(),
+    }
+}
+
+/// Writes the types of the type bit maps of NSEC and NSEC3 records, in
+/// increasing order of their numbers.
+pub fn format_type_bit_maps(type_bit_maps: &[Rrtype]) -> String {
+    let mut rrtypes = type_bit_maps.to_vec();
+    rrtypes.sort_by_key(|rrtype| u16::from(*rrtype));
+    rrtypes.dedup();
+
+    rrtypes
+        .into_iter()
+        .map(format_rrtype)
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod presentation_test {
+    use super::*;
+
+    #[test]
+    fn parse_int_test() {
+        assert_eq!(parse_int::<u16>("65535").unwrap(), 65535);
+        assert!(parse_int::<u16>("65536").is_err());
+        assert!(parse_int::<u16>("-1").is_err());
+        assert!(parse_int::<u16>("").is_err());
+    }
+
+    #[test]
+    fn parse_ttl_test() {
+        assert_eq!(parse_ttl("86400").unwrap(), 86400);
+        assert_eq!(parse_ttl("1d").unwrap(), 86400);
+        assert_eq!(parse_ttl("1W2d3H4m5s").unwrap(), 788645);
+        assert_eq!(parse_ttl("1h30").unwrap(), 3630);
+        assert!(parse_ttl("h").is_err());
+        assert!(parse_ttl("1x").is_err());
+        assert!(parse_ttl("99999999999").is_err());
+    }
+
+    #[test]
+    fn parse_character_string_test() {
+        assert_eq!(parse_character_string("a b").unwrap(), b"a b".to_vec());
+        assert_eq!(parse_character_string("\\\"quoted\\\"").unwrap(), b"\"quoted\"".to_vec());
+        assert_eq!(parse_character_string("\\065\\;").unwrap(), b"A;".to_vec());
+        assert!(parse_character_string("\\256").is_err());
+        assert!(parse_character_string(&"a".repeat(256)).is_err());
+    }
+
+    #[test]
+    fn parse_time_test() {
+        assert_eq!(parse_time("20240101000000").unwrap(), 1704067200);
+        assert_eq!(parse_time("1704067200").unwrap(), 1704067200);
+        assert!(parse_time("20241301000000").is_err());
+    }
+
+    #[test]
+    fn parse_rrtype_test() {
+        assert_eq!(parse_rrtype("mx").unwrap(), Rrtype::MX);
+        assert_eq!(parse_rrtype("TYPE15").unwrap(), Rrtype::MX);
+        assert_eq!(parse_rrtype("TYPE65280").unwrap(), Rrtype::UNKNOWN(65280));
+        assert!(parse_rrtype("FOO").is_err());
+    }
+
+    #[test]
+    fn parse_algorithm_test() {
+        assert_eq!(parse_algorithm("13").unwrap(), DnssecAlgorithm::ECDSAP256SHA256);
+        assert_eq!(parse_algorithm("ed25519").unwrap(), DnssecAlgorithm::ED25519);
+        assert_eq!(parse_algorithm("200").unwrap(), DnssecAlgorithm::UNKNOWN(200));
+        assert!(parse_algorithm("256").is_err());
+        assert!(parse_algorithm("FOO").is_err());
+    }
+
+    #[test]
+    fn parse_rclass_test() {
+        assert_eq!(parse_rclass("in").unwrap(), Rclass::IN);
+        assert_eq!(parse_rclass("CLASS3").unwrap(), Rclass::CH);
+        assert!(parse_rclass("XX").is_err());
+    }
+
+    #[test]
+    fn parse_encoded_data_test() {
+        assert_eq!(parse_hex(&["0a0B", "ff"]).unwrap(), vec![10, 11, 255]);
+        assert!(parse_hex(&["0g"]).is_err());
+        assert_eq!(parse_base64(&["AQID", "BA=="]).unwrap(), vec![1, 2, 3, 4]);
+        assert!(parse_base64(&["A"]).is_err());
+        assert_eq!(parse_base32hex("0p1g").unwrap(), vec![0x06, 0x43]);
+        assert_eq!(parse_base32hex("0P1G").unwrap(), vec![0x06, 0x43]);
+        assert!(parse_base32hex("0z").is_err());
+    }
+
+    #[test]
+    fn format_base32hex_test() {
+        assert_eq!(format_base32hex(&[0x06, 0x43]), "0p1g");
+        assert_eq!(format_base32hex(&[]), "");
+    }
+
+    #[test]
+    fn format_name_test() {
+        assert_eq!(format_name(&DomainName::new_from_str("example.com")), "example.com.");
+        assert_eq!(format_name(&DomainName::new_from_str("example.com.")), "example.com.");
+        assert_eq!(format_name(&DomainName::new_from_str("a\\.b")), "a\\.b.");
+        assert_eq!(format_name(&DomainName::new_from_str(".")), ".");
+        assert_eq!(format_name(&DomainName::new()), ".");
+    }
+
+    #[test]
+    fn format_character_string_test() {
+        assert_eq!(format_character_string(b"a b"), "\"a b\"");
+        assert_eq!(format_character_string(b"say \"hi\" \\"), "\"say \\\"hi\\\" \\\\\"");
+        assert_eq!(format_character_string(&[0, 255]), "\"\\000\\255\"");
+        assert_eq!(parse_character_string("say \\\"hi\\\"").unwrap(), b"say \"hi\"".to_vec());
+    }
+
+    #[test]
+    fn format_time_test() {
+        assert_eq!(format_time(1704067200), "20240101000000");
+        assert_eq!(parse_time(&format_time(4294967295)).unwrap(), 4294967295);
+    }
+
+    #[test]
+    fn format_type_bit_maps_test() {
+        let type_bit_maps = vec![Rrtype::NSEC, Rrtype::A, Rrtype::UNKNOWN(1234), Rrtype::A];
+
+        assert_eq!(format_type_bit_maps(&type_bit_maps), "A NSEC TYPE1234");
+        assert_eq!(format_rclass(Rclass::UNKNOWN(10)), "CLASS10");
+    }
+}
@@ -5,6 +5,7 @@ use crate::message::rdata::Rdata;
 use crate::message::resource_record::{FromBytes, ResourceRecord, ToBytes};
 use std::str::SplitWhitespace;
 use std::fmt;
+use crate::message::rdata::presentation::{expect_fields, field, format_name, parse_name};
 
 #[derive(Clone, PartialEq, Debug, Eq, Hash)]
 /// Struct that represents the `Rdata` for PTR TYPE.
@@ -128,6 +129,20 @@ impl PtrRdata {
 
         resource_record
     }
+
+    /// Creates a `PtrRdata` from the fields of its presentation format.
+    pub fn from_text(fields: &[&str], origin: &DomainName) -> Result<Self, &'static str> {
+        expect_fields(fields, 1)?;
+        let mut ptr_rdata = PtrRdata::new();
+        ptr_rdata.set_ptrdname(parse_name(field(fields, 0)?, origin)?);
+
+        Ok(ptr_rdata)
+    }
+
+    /// Returns the `PtrRdata` in presentation format.
+    pub fn to_text(&self) -> String {
+        format_name(&self.ptrdname)
+    }
 }
 
 // Getters
@@ -1,9 +1,11 @@
 use crate::message::resource_record::{FromBytes, ToBytes};
 use crate::domain_name::DomainName;
 use crate::message::rrtype::Rrtype;
+use crate::dnssec::dnssec_algorithm::DnssecAlgorithm;
 use base64::engine::general_purpose;
 use base64::Engine as _;
 use std::fmt;
+use crate::message::rdata::presentation::{field, format_base64, format_name, format_rrtype, format_time, parse_algorithm, parse_base64, parse_int, parse_name, parse_rrtype, parse_time};
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 /// Struct for RRSIG Rdata
@@ -30,7 +32,7 @@ use std::fmt;
 
 pub struct RRSIGRdata {
     type_covered: Rrtype, // RR type mnemonic
-    algorithm: u8, // Unsigned decimal integer
+    algorithm: DnssecAlgorithm, // Algorithm number or mnemonic
     labels: u8, // Unsigned decimal integer, represents the number of layers in the siger name
     original_ttl: u32, // Unsigned decimal integer
     signature_expiration: u32, // Unsigned decimal integer 
@@ -48,7 +50,7 @@ impl ToBytes for RRSIGRdata {
         let type_covered = u16::from(self.type_covered.clone());
         bytes.extend_from_slice(&type_covered.to_be_bytes());
 
-        bytes.push(self.algorithm);
+        bytes.push(u8::from(self.algorithm));
         bytes.push(self.labels);
         bytes.extend_from_slice(&self.original_ttl.to_be_bytes());
         bytes.extend_from_slice(&self.signature_expiration.to_be_bytes());
@@ -81,7 +83,7 @@ impl FromBytes<Result<Self, &'static str>> for RRSIGRdata {
         let type_covered = Rrtype::from(type_covered_int);
         rrsig_rdata.set_type_covered(type_covered);
 
-        let algorithm = bytes[2];
+        let algorithm = DnssecAlgorithm::from(bytes[2]);
         rrsig_rdata.set_algorithm(algorithm);
 
         let labels = bytes[3];
@@ -162,7 +164,7 @@ impl RRSIGRdata{
     pub fn new() -> RRSIGRdata{
         RRSIGRdata{
             type_covered: Rrtype::A,
-            algorithm: 0,
+            algorithm: DnssecAlgorithm::RESERVED,
             labels: 0,
             original_ttl: 0,
             signature_expiration: 0,
@@ -192,8 +194,8 @@ impl RRSIGRdata{
     /// let rrsig_rdata = RRSIGRdata::new();
     /// let algorithm = rrsig_rdata.get_algorithm();
     /// ```
-    pub fn get_algorithm(&self) -> u8{
-        self.algorithm.clone()
+    pub fn get_algorithm(&self) -> DnssecAlgorithm {
+        self.algorithm
     }
 
     /// Getter for labels
@@ -279,6 +281,42 @@ impl RRSIGRdata{
     pub fn get_signature(&self) -> Vec<u8>{
         self.signature.clone()
     }
+
+    /// Creates a `RRSIGRdata` from the fields of its presentation format.
+    ///
+    /// The expiration and inception can be written as `YYYYMMDDHHmmSS` or as
+    /// seconds since 1970.
+    pub fn from_text(fields: &[&str], origin: &DomainName) -> Result<Self, &'static str> {
+        let mut rrsig_rdata = RRSIGRdata::new();
+        rrsig_rdata.set_type_covered(parse_rrtype(field(fields, 0)?)?);
+        rrsig_rdata.set_algorithm(parse_algorithm(field(fields, 1)?)?);
+        rrsig_rdata.set_labels(parse_int(field(fields, 2)?)?);
+        rrsig_rdata.set_original_ttl(parse_int(field(fields, 3)?)?);
+        rrsig_rdata.set_signature_expiration(parse_time(field(fields, 4)?)?);
+        rrsig_rdata.set_signature_inception(parse_time(field(fields, 5)?)?);
+        rrsig_rdata.set_key_tag(parse_int(field(fields, 6)?)?);
+        rrsig_rdata.set_signer_name(parse_name(field(fields, 7)?, origin)?);
+        field(fields, 8)?;
+        rrsig_rdata.set_signature(parse_base64(&fields[8..])?);
+
+        Ok(rrsig_rdata)
+    }
+
+    /// Returns the `RRSIGRdata` in presentation format.
+    pub fn to_text(&self) -> String {
+        format!(
+            "{} {} {} {} {} {} {} {} {}",
+            format_rrtype(self.type_covered),
+            u8::from(self.algorithm),
+            self.labels,
+            self.original_ttl,
+            format_time(self.signature_expiration),
+            format_time(self.signature_inception),
+            self.key_tag,
+            format_name(&self.signer_name),
+            format_base64(&self.signature)
+        )
+    }
 }
 
 // Settters for RRSIGRdata
@@ -301,9 +339,9 @@ impl RRSIGRdata{
     /// 
     /// ```
     /// let mut rrsig_rdata = RRSIGRdata::new();
-    /// rrsig_rdata.set_algorithm(5);
+    /// rrsig_rdata.set_algorithm(DnssecAlgorithm::RSASHA1);
     /// ```
-    pub fn set_algorithm(&mut self, algorithm: u8) {
+    pub fn set_algorithm(&mut self, algorithm: DnssecAlgorithm) {
         self.algorithm = algorithm;
     }
 
@@ -397,7 +435,7 @@ impl fmt::Display for RRSIGRdata {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{} {} {} {} {} {} {} {} {}", 
         u16::from(self.get_type_covered()), 
-        self.get_algorithm(), 
+        u8::from(self.get_algorithm()), 
         self.get_labels(), 
         self.get_original_ttl(), 
         self.get_signature_expiration(), 
@@ -417,7 +455,7 @@ mod rrsig_rdata_test{
         let rrsig_rdata = RRSIGRdata::new();
 
         assert_eq!(rrsig_rdata.type_covered, Rrtype::A);
-        assert_eq!(rrsig_rdata.algorithm, 0);
+        assert_eq!(rrsig_rdata.algorithm, DnssecAlgorithm::from(0));
         assert_eq!(rrsig_rdata.labels, 0);
         assert_eq!(rrsig_rdata.original_ttl, 0);
         assert_eq!(rrsig_rdata.signature_expiration, 0);
@@ -432,7 +470,7 @@ mod rrsig_rdata_test{
         let mut rrsig_rdata = RRSIGRdata::new();
 
         assert_eq!(rrsig_rdata.get_type_covered(), Rrtype::A);
-        assert_eq!(rrsig_rdata.get_algorithm(), 0);
+        assert_eq!(rrsig_rdata.get_algorithm(), DnssecAlgorithm::from(0));
         assert_eq!(rrsig_rdata.get_labels(), 0);
         assert_eq!(rrsig_rdata.get_original_ttl(), 0);
         assert_eq!(rrsig_rdata.get_signature_expiration(), 0);
@@ -442,7 +480,7 @@ mod rrsig_rdata_test{
         assert_eq!(rrsig_rdata.get_signature(), vec![]);
 
         rrsig_rdata.set_type_covered(Rrtype::CNAME);
-        rrsig_rdata.set_algorithm(5);
+        rrsig_rdata.set_algorithm(DnssecAlgorithm::RSASHA1);
         rrsig_rdata.set_labels(2);
         rrsig_rdata.set_original_ttl(3600);
         rrsig_rdata.set_signature_expiration(1630435200);
@@ -452,7 +490,7 @@ mod rrsig_rdata_test{
         rrsig_rdata.set_signature(b"abcdefg".to_vec());
 
         assert_eq!(rrsig_rdata.get_type_covered(), Rrtype::CNAME);
-        assert_eq!(rrsig_rdata.get_algorithm(), 5);
+        assert_eq!(rrsig_rdata.get_algorithm(), DnssecAlgorithm::RSASHA1);
         assert_eq!(rrsig_rdata.get_labels(), 2);
         assert_eq!(rrsig_rdata.get_original_ttl(), 3600);
         assert_eq!(rrsig_rdata.get_signature_expiration(), 1630435200);
@@ -466,7 +504,7 @@ mod rrsig_rdata_test{
     fn to_bytes(){
         let mut rrsig_rdata = RRSIGRdata::new();
         rrsig_rdata.set_type_covered(Rrtype::CNAME);
-        rrsig_rdata.set_algorithm(5);
+        rrsig_rdata.set_algorithm(DnssecAlgorithm::RSASHA1);
         rrsig_rdata.set_labels(2);
         rrsig_rdata.set_original_ttl(3600);
         rrsig_rdata.set_signature_expiration(1630435200);
@@ -498,7 +536,7 @@ mod rrsig_rdata_test{
 
         let mut rrsig_rdata = RRSIGRdata::new();
         rrsig_rdata.set_type_covered(Rrtype::CNAME);
-        rrsig_rdata.set_algorithm(5);
+        rrsig_rdata.set_algorithm(DnssecAlgorithm::RSASHA1);
         rrsig_rdata.set_labels(2);
         rrsig_rdata.set_original_ttl(3600);
         rrsig_rdata.set_signature_expiration(1630435200);
@@ -537,7 +575,7 @@ mod rrsig_rdata_test{
 
        let mut rrsig_rdata = RRSIGRdata::new();
        rrsig_rdata.set_type_covered(Rrtype::UNKNOWN(65535));
-       rrsig_rdata.set_algorithm(255);
+       rrsig_rdata.set_algorithm(DnssecAlgorithm::from(255));
        rrsig_rdata.set_labels(2);
        rrsig_rdata.set_original_ttl(4294967295);
        rrsig_rdata.set_signature_expiration(4294967295);
@@ -570,7 +608,7 @@ mod rrsig_rdata_test{
 
        let mut rrsig_rdata = RRSIGRdata::new();
        rrsig_rdata.set_type_covered(Rrtype::UNKNOWN(65535));
-       rrsig_rdata.set_algorithm(255);
+       rrsig_rdata.set_algorithm(DnssecAlgorithm::from(255));
        rrsig_rdata.set_labels(2);
        rrsig_rdata.set_original_ttl(4294967295);
        rrsig_rdata.set_signature_expiration(4294967295);
@@ -598,7 +636,7 @@ mod rrsig_rdata_test{
 
        let mut rrsig_rdata = RRSIGRdata::new();
        rrsig_rdata.set_type_covered(Rrtype::UNKNOWN(0));
-       rrsig_rdata.set_algorithm(0);
+       rrsig_rdata.set_algorithm(DnssecAlgorithm::from(0));
        rrsig_rdata.set_labels(0);
        rrsig_rdata.set_original_ttl(0);
        rrsig_rdata.set_signature_expiration(0);
@@ -630,7 +668,7 @@ mod rrsig_rdata_test{
 
        let mut rrsig_rdata = RRSIGRdata::new();
        rrsig_rdata.set_type_covered(Rrtype::UNKNOWN(0));
-       rrsig_rdata.set_algorithm(0);
+       rrsig_rdata.set_algorithm(DnssecAlgorithm::from(0));
        rrsig_rdata.set_labels(0);
        rrsig_rdata.set_original_ttl(0);
        rrsig_rdata.set_signature_expiration(0);
@@ -702,7 +740,7 @@ mod rrsig_rdata_test{
 
         let mut rrsig_rdata = RRSIGRdata::new();
         rrsig_rdata.set_type_covered(Rrtype::CNAME);
-        rrsig_rdata.set_algorithm(5);
+        rrsig_rdata.set_algorithm(DnssecAlgorithm::RSASHA1);
         rrsig_rdata.set_labels(8);
         rrsig_rdata.set_original_ttl(3600);
         rrsig_rdata.set_signature_expiration(1630435200);
@@ -756,7 +794,7 @@ mod rrsig_rdata_test{
 
         let mut rrsig_rdata = RRSIGRdata::new();
         rrsig_rdata.set_type_covered(Rrtype::CNAME);
-        rrsig_rdata.set_algorithm(5);
+        rrsig_rdata.set_algorithm(DnssecAlgorithm::RSASHA1);
         rrsig_rdata.set_labels(0);
         rrsig_rdata.set_original_ttl(3600);
         rrsig_rdata.set_signature_expiration(1630435200);
@@ -773,5 +811,14 @@ mod rrsig_rdata_test{
         }
     }
 
+    #[test]
+    fn text_test() {
+        let origin = DomainName::new_from_str("example.com.");
+        let fields = ["A", "8", "2", "3600", "20240201000000", "20240101000000", "12345", "example.com.", "AQID"];
+        let rrsig_rdata = RRSIGRdata::from_text(&fields, &origin).unwrap();
 
-} 
+        assert_eq!(rrsig_rdata.get_signature_expiration(), 1706745600);
+        assert_eq!(rrsig_rdata.to_text(), fields.join(" "));
+        assert!(RRSIGRdata::from_text(&fields[..8], &origin).is_err());
+    }
+}
@@ -5,6 +5,7 @@ use crate::message::rdata::Rdata;
 use crate::message::resource_record::{FromBytes, ResourceRecord, ToBytes};
 use std::str::SplitWhitespace;
 use std::fmt;
+use crate::message::rdata::presentation::{expect_fields, field, format_name, parse_int, parse_name, parse_ttl};
 
 #[derive(Clone, PartialEq, Debug, Eq, Hash)]
 /// An struct that represents the `Rdata` for SOA TYPE.
@@ -479,6 +480,37 @@ impl SoaRdata {
 
         self.set_minimum(first_byte | second_byte | third_byte | fourth_byte);
     }
+
+    /// Creates a `SoaRdata` from the fields of its presentation format.
+    ///
+    /// The timers can be written with the units of a TTL, as in `1h`.
+    pub fn from_text(fields: &[&str], origin: &DomainName) -> Result<Self, &'static str> {
+        expect_fields(fields, 7)?;
+        let mut soa_rdata = SoaRdata::new();
+        soa_rdata.set_mname(parse_name(field(fields, 0)?, origin)?);
+        soa_rdata.set_rname(parse_name(field(fields, 1)?, origin)?);
+        soa_rdata.set_serial(parse_int(field(fields, 2)?)?);
+        soa_rdata.set_refresh(parse_ttl(field(fields, 3)?)?);
+        soa_rdata.set_retry(parse_ttl(field(fields, 4)?)?);
+        soa_rdata.set_expire(parse_ttl(field(fields, 5)?)?);
+        soa_rdata.set_minimum(parse_ttl(field(fields, 6)?)?);
+
+        Ok(soa_rdata)
+    }
+
+    /// Returns the `SoaRdata` in presentation format.
+    pub fn to_text(&self) -> String {
+        format!(
+            "{} {} {} {} {} {} {}",
+            format_name(&self.mname),
+            format_name(&self.rname),
+            self.serial,
+            self.refresh,
+            self.retry,
+            self.expire,
+            self.minimum
+        )
+    }
 }
 
 /// Getters
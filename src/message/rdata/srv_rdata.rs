@@ -2,6 +2,7 @@ use core::fmt;
 
 use crate::domain_name::DomainName;
 use crate::message::resource_record::{FromBytes, ToBytes};
+use crate::message::rdata::presentation::{expect_fields, field, format_name, parse_int, parse_name};
 
 
 /// RFC 2782: https://datatracker.ietf.org/doc/html/rfc2782
@@ -99,6 +100,29 @@ impl SrvRdata {
             target,
         }
     }
+
+    /// Creates a `SrvRdata` from the fields of its presentation format.
+    pub fn from_text(fields: &[&str], origin: &DomainName) -> Result<Self, &'static str> {
+        expect_fields(fields, 4)?;
+
+        Ok(SrvRdata::new_with_values(
+            parse_int(field(fields, 0)?)?,
+            parse_int(field(fields, 1)?)?,
+            parse_int(field(fields, 2)?)?,
+            parse_name(field(fields, 3)?, origin)?,
+        ))
+    }
+
+    /// Returns the `SrvRdata` in presentation format.
+    pub fn to_text(&self) -> String {
+        format!(
+            "{} {} {} {}",
+            self.priority,
+            self.weight,
+            self.port,
+            format_name(&self.target)
+        )
+    }
 }
 
 impl SrvRdata {
@@ -5,6 +5,7 @@ use crate::message::Rclass;
 use crate::message::resource_record::{FromBytes, ResourceRecord, ToBytes};
 use std::str::SplitWhitespace;
 use std::fmt;
+use crate::message::rdata::presentation::{field, format_base64, format_name, parse_base64, parse_int, parse_name};
 
 #[derive(Clone, PartialEq, Debug, Eq, Hash)]
 /// Struct for the TSIG RData
@@ -300,6 +301,72 @@ impl TSigRdata {
 
         self.set_other_len(other_len);
     }
+
+    /// Creates a `TSigRdata` from the fields of its presentation format.
+    ///
+    /// The fields are written as BIND does: algorithm, time signed, fudge,
+    /// MAC size, MAC in base64 (absent when the size is zero), original ID,
+    /// error, other length and other data in base64 (absent when empty).
+    pub fn from_text(fields: &[&str], origin: &DomainName) -> Result<Self, &'static str> {
+        let mut tsig_rdata = TSigRdata::new();
+        tsig_rdata.set_algorithm_name(parse_name(field(fields, 0)?, origin)?);
+        tsig_rdata.set_time_signed(parse_int(field(fields, 1)?)?);
+        tsig_rdata.set_fudge(parse_int(field(fields, 2)?)?);
+
+        let mac_size: u16 = parse_int(field(fields, 3)?)?;
+        let mut index = 4;
+        if mac_size > 0 {
+            let mac = parse_base64(&[field(fields, index)?])?;
+            if mac.len() != mac_size as usize {
+                return Err("Error: the MAC size does not match the MAC.");
+            }
+            tsig_rdata.set_mac(mac);
+            index += 1;
+        }
+        tsig_rdata.set_mac_size(mac_size);
+
+        tsig_rdata.set_original_id(parse_int(field(fields, index)?)?);
+        tsig_rdata.set_error(parse_int(field(fields, index + 1)?)?);
+
+        let other_len: u16 = parse_int(field(fields, index + 2)?)?;
+        index += 3;
+        if other_len > 0 {
+            let other_data = parse_base64(&[field(fields, index)?])?;
+            if other_data.len() != other_len as usize {
+                return Err("Error: the other length does not match the other data.");
+            }
+            tsig_rdata.set_other_data(other_data);
+            index += 1;
+        }
+        tsig_rdata.set_other_len(other_len);
+
+        if fields.len() > index {
+            return Err("Error: too many fields in rdata.");
+        }
+
+        Ok(tsig_rdata)
+    }
+
+    /// Returns the `TSigRdata` in presentation format.
+    pub fn to_text(&self) -> String {
+        let mut fields = vec![
+            format_name(&self.algorithm_name),
+            self.time_signed.to_string(),
+            self.fudge.to_string(),
+            self.mac_size.to_string(),
+        ];
+        if self.mac_size > 0 {
+            fields.push(format_base64(&self.mac));
+        }
+        fields.push(self.original_id.to_string());
+        fields.push(self.error.to_string());
+        fields.push(self.other_len.to_string());
+        if self.other_len > 0 {
+            fields.push(format_base64(&self.other_data));
+        }
+
+        fields.join(" ")
+    }
 }
 
 /// Getters
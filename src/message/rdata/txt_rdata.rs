@@ -7,6 +7,7 @@ use crate::message::resource_record::{FromBytes, ResourceRecord, ToBytes};
 use std::str::SplitWhitespace;
 use std::string::String;
 use std::fmt;
+use crate::message::rdata::presentation::{format_character_string, parse_character_string};
 
 #[derive(Clone, PartialEq, Debug, Eq, Hash)]
 /// [RFC 1035]: https://datatracker.ietf.org/doc/html/rfc1035#section-3.3.14
@@ -133,6 +134,30 @@ impl TxtRdata {
 
         resource_record
     }
+
+    /// Creates a `TxtRdata` from the fields of its presentation format.
+    pub fn from_text(fields: &[&str], _origin: &DomainName) -> Result<Self, &'static str> {
+        if fields.is_empty() {
+            return Err("Error: missing field in rdata.");
+        }
+
+        let mut text: Vec<String> = Vec::new();
+        for field in fields {
+            let string = parse_character_string(field)?;
+            text.push(String::from_utf8_lossy(&string).to_string());
+        }
+
+        Ok(TxtRdata::new(text))
+    }
+
+    /// Returns the `TxtRdata` in presentation format.
+    pub fn to_text(&self) -> String {
+        self.text
+            .iter()
+            .map(|string| format_character_string(string.as_bytes()))
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
 }
 
 /// Getters
@@ -231,4 +256,14 @@ mod txt_rdata_test {
             _ => {}
         }
     }
+
+    #[test]
+    fn text_test() {
+        let origin = crate::domain_name::DomainName::new();
+        let txt_rdata = TxtRdata::from_text(&["dcc uchile", "say \\\"hi\\\""], &origin).unwrap();
+
+        assert_eq!(txt_rdata.get_text(), vec!["dcc uchile", "say \"hi\""]);
+        assert_eq!(txt_rdata.to_text(), "\"dcc uchile\" \"say \\\"hi\\\"\"");
+        assert!(TxtRdata::from_text(&[], &origin).is_err());
+    }
 }
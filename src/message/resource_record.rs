@@ -1,13 +1,14 @@
 use crate::message::rdata::Rdata;
 use crate::message::Rclass;
-use crate::domain_name;
 use crate::domain_name::DomainName;
 use std::fmt;
 use std::vec::Vec;
 
 use super::rrtype::Rrtype;
+use super::compression::CompressionTable;
+use crate::message::rdata::presentation::{format_name, format_rclass, format_rrtype};
 
-#[derive(Clone, PartialEq, Debug, Hash)]
+#[derive(Clone, PartialEq, Eq, Debug, Hash)]
 /// [RFC 1035]: https://datatracker.ietf.org/doc/html/rfc1035#section-3.2.1
 /// An struct that represents the Resource Record secction from a dns message.
 /// 
@@ -217,6 +218,14 @@ impl ResourceRecord {
                 rdlength: val.to_bytes().len() as u16,
                 rdata: Rdata::TSIG(val),
             },
+            Rdata::Unknown { rrtype, data } => ResourceRecord {
+                name: DomainName::new(),
+                rtype: rrtype,
+                rclass: Rclass::IN,
+                ttl: 0,
+                rdlength: data.len() as u16,
+                rdata: Rdata::Unknown { rrtype, data },
+            },
             _ => ResourceRecord {
                 name: DomainName::new(),
                 rtype: Rrtype::UNKNOWN(0),
@@ -253,16 +262,9 @@ impl ResourceRecord {
         bytes: &'a [u8],
         full_msg: &'a [u8],
     ) -> Result<(ResourceRecord, &'a [u8]), &'static str> {
-        let domain_name_result = DomainName::from_bytes(bytes, full_msg);
-
-        match domain_name_result.clone() {
-            Ok((domain_name,_)) => {
-                domain_name::domain_validity_syntax(domain_name)?;
-            }
-            Err(e) => return Err(e),
-        }
-
-        let (name, bytes_without_name) = domain_name_result.unwrap();
+        // Any binary label is valid on the wire (RFC 2181 section 11), so
+        // names are not checked against the host name syntax here
+        let (name, bytes_without_name) = DomainName::from_bytes(bytes, full_msg)?;
 
         if bytes_without_name.len() < 10 {
             return Err("Format Error");
@@ -462,11 +464,60 @@ impl ResourceRecord {
         rr_bytes
     }
 
+    /// Appends the resource record to `msg`, compressing the owner name and the
+    /// names inside the rdata that can be compressed with the names already
+    /// present in the message.
+    pub fn to_bytes_compressed(&self, msg: &mut Vec<u8>, compression_table: &mut CompressionTable) {
+        compression_table.write_name(&self.get_name(), msg);
+
+        msg.push(self.get_first_type_code_byte());
+        msg.push(self.get_second_type_code_byte());
+        msg.push(self.get_first_class_byte());
+        msg.push(self.get_second_class_byte());
+        msg.push(self.get_first_ttl_byte());
+        msg.push(self.get_second_ttl_byte());
+        msg.push(self.get_third_ttl_byte());
+        msg.push(self.get_fourth_ttl_byte());
+
+        // RDLENGTH is known only after writing the rdata
+        let rdlength_index = msg.len();
+        msg.push(0);
+        msg.push(0);
+
+        self.get_rdata().to_bytes_compressed(msg, compression_table);
+
+        let rd_length = (msg.len() - rdlength_index - 2) as u16;
+        msg[rdlength_index] = (rd_length >> 8) as u8;
+        msg[rdlength_index + 1] = rd_length as u8;
+    }
+
     pub fn get_string_type(&self) -> String {
         let qtype = self.get_rtype().to_string();
         qtype
     }
 
+    /// Returns the resource record in presentation format, as a line of a
+    /// master file with an absolute owner name.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut rr = ResourceRecord::new(Rdata::A(ARdata::new_from_addr(IpAddr::from([192, 0, 2, 1]))));
+    /// rr.set_name(DomainName::new_from_str("www.example.com"));
+    /// rr.set_ttl(3600);
+    ///
+    /// assert_eq!(rr.to_text(), "www.example.com.\t3600\tIN\tA\t192.0.2.1");
+    /// ```
+    pub fn to_text(&self) -> String {
+        format!(
+            "{}\t{}\t{}\t{}\t{}",
+            format_name(&self.name),
+            self.ttl,
+            format_rclass(self.rclass),
+            format_rrtype(self.rtype),
+            self.rdata.to_text()
+        )
+    }
+
 }
 
 /// Setters
@@ -1010,6 +1061,25 @@ mod resource_record_test {
         }
     }
 
+    #[test]
+    fn from_bytes_unknown_type_test() {
+        let bytes_msg = [
+            3, 100, 99, 99, 2, 99, 108, 0, 0, 65, 0, 1, 0, 0, 0b00010110, 0b00001010, 0, 3, 0, 1,
+            0,
+        ];
+
+        let (resource_record_test, _other_rr_bytes) =
+            ResourceRecord::from_bytes(&bytes_msg, &bytes_msg).unwrap();
+
+        assert_eq!(u16::from(resource_record_test.get_rtype()), 65);
+        assert_eq!(resource_record_test.get_rdlength(), 3);
+        assert_eq!(
+            resource_record_test.get_rdata(),
+            Rdata::Unknown { rrtype: Rrtype::UNKNOWN(65), data: vec![0, 1, 0] }
+        );
+        assert_eq!(resource_record_test.to_bytes(), bytes_msg.to_vec());
+    }
+
     #[test]
     fn from_bytes_test() {
         let mut bytes_msg = [
@@ -1064,6 +1134,23 @@ mod resource_record_test {
         );
     }
 
+    #[test]
+    fn from_bytes_root_and_numeric_owner() {
+        // Root DNSKEY owner, then an owner whose label starts with a digit
+        let bytes_msg = [0, 0, 48, 0, 1, 0, 0, 0, 60, 0, 5, 1, 1, 3, 15, 7];
+
+        let (resource_record_test, _) = ResourceRecord::from_bytes(&bytes_msg, &bytes_msg).unwrap();
+
+        assert!(resource_record_test.get_name().is_root());
+        assert_eq!(resource_record_test.get_rtype(), Rrtype::DNSKEY);
+
+        let bytes_msg = [1, 52, 0, 0, 1, 0, 1, 0, 0, 0, 60, 0, 4, 192, 0, 2, 1];
+
+        let (resource_record_test, _) = ResourceRecord::from_bytes(&bytes_msg, &bytes_msg).unwrap();
+
+        assert_eq!(resource_record_test.get_name().get_name(), String::from("4"));
+    }
+
     #[test]
     fn get_string_type_test() {
         let a_rdata = Rdata::A(ARdata::new());
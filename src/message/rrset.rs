@@ -138,6 +138,22 @@ impl RRset {
         rrset.set_ttl(minttl);
         Some(rrset)
     }
+
+    /// Returns the records of the RRset as resource records, all with the
+    /// name, class and TTL of the RRset.
+    pub fn get_resource_records(&self) -> Vec<ResourceRecord> {
+        self.records
+            .iter()
+            .map(|(rdlen, rdata)| {
+                let mut rr = ResourceRecord::new(rdata.clone());
+                rr.set_name(self.name.clone());
+                rr.set_rclass(self.rclass);
+                rr.set_ttl(self.ttl);
+                rr.set_rdlength(*rdlen);
+                rr
+            })
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -145,6 +161,7 @@ mod rrset_test {
     use std::net::IpAddr;
     use crate::message::{self, rdata::{a_rdata::ARdata, rrsig_rdata}, resource_record::ResourceRecord};
     use super::*;
+    use crate::dnssec::dnssec_algorithm::DnssecAlgorithm;
     #[test]
     fn constructor_test() {
         let rrset = RRset::new();
@@ -220,7 +237,7 @@ mod rrset_test {
         // add answer signature
         let mut rrsig_rdata = rrsig_rdata::RRSIGRdata::new();
         rrsig_rdata.set_type_covered(Rrtype::A);
-        rrsig_rdata.set_algorithm(13); // ECDSA Curve P-256 with SHA-256
+        rrsig_rdata.set_algorithm(DnssecAlgorithm::ECDSAP256SHA256); // ECDSA Curve P-256 with SHA-256
         rrsig_rdata.set_labels(2);
         rrsig_rdata.set_original_ttl(3600);
         rrsig_rdata.set_signature_expiration(1731875962);
@@ -249,4 +266,23 @@ mod rrset_test {
                                         (4, Rdata::A(ARdata::new_from_addr(IpAddr::from([93,184,215,15]))))]);
         assert_eq!(expected_rrset, rrset)
     }
-}
\ No newline at end of file
+    #[test]
+    fn get_resource_records_test() {
+        let mut rrset = RRset::new();
+        rrset.set_name(DomainName::new_from_str("example.com"));
+        rrset.set_ttl(300);
+        rrset.add_record(4, Rdata::A(ARdata::new_from_addr(IpAddr::from([192,0,2,1]))));
+        rrset.add_record(4, Rdata::A(ARdata::new_from_addr(IpAddr::from([192,0,2,2]))));
+
+        let rrs = rrset.get_resource_records();
+
+        assert_eq!(rrs.len(), 2);
+        for rr in rrs.iter() {
+            assert_eq!(rr.get_name(), DomainName::new_from_str("example.com"));
+            assert_eq!(rr.get_rtype(), Rrtype::A);
+            assert_eq!(rr.get_ttl(), 300);
+        }
+        let rebuilt = RRset::get_rrset_from_rrs(rrs, rrset.get_name(), Rrtype::A, Rclass::IN).unwrap();
+        assert_eq!(rebuilt, rrset);
+    }
+}
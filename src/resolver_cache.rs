@@ -1,4 +1,7 @@
+pub mod aggressive_cache;
+
 use crate::dns_cache::{CacheKey, DnsCache};
+use crate::resolver_cache::aggressive_cache::AggressiveCache;
 use crate::domain_name::DomainName;
 use crate::message::rclass::Rclass;
 use crate::message::rcode::Rcode;
@@ -21,6 +24,7 @@ pub struct ResolverCache {
     cache_answer: DnsCache,
     cache_authority: DnsCache,
     cache_additional: DnsCache,
+    cache_aggressive: AggressiveCache,
 }
 
 impl ResolverCache {
@@ -31,6 +35,7 @@ impl ResolverCache {
             cache_answer: DnsCache::new(Some(size)),
             cache_authority: DnsCache::new(Some(size)),
             cache_additional: DnsCache::new(Some(size)),
+            cache_aggressive: AggressiveCache::new(Some(size)),
         }
     }
 
@@ -44,6 +49,7 @@ impl ResolverCache {
             cache_answer: DnsCache::new(size_answer),
             cache_authority: DnsCache::new(size_authority),
             cache_additional: DnsCache::new(size_additional),
+            cache_aggressive: AggressiveCache::new(size_authority),
         }
     }
 
@@ -52,6 +58,7 @@ impl ResolverCache {
         self.cache_answer.set_max_size(size);
         self.cache_authority.set_max_size(size);
         self.cache_additional.set_max_size(size);
+        self.cache_aggressive.set_max_size(size);
     }
 
     /// See if the cache is empty.
@@ -59,6 +66,7 @@ impl ResolverCache {
         self.cache_answer.is_empty()
             && self.cache_authority.is_empty()
             && self.cache_additional.is_empty()
+            && self.cache_aggressive.is_empty()
     }
 
     /// See if an element is in the cache.
@@ -225,6 +233,15 @@ impl ResolverCache {
          */
     }
 
+    /// Adds a response validated as secure to the cache of NSEC and NSEC3
+    /// records and wildcards, used to synthesize answers for names that are
+    /// not cached. The response must have the AD bit.
+    ///
+    /// [RFC 8198](https://datatracker.ietf.org/doc/html/rfc8198#section-4)
+    pub fn add_validated(&mut self, message: &DnsMessage) {
+        self.cache_aggressive.add(message);
+    }
+
     /// Gets elements from the answer cache
     pub fn get_answer(
         &mut self,
@@ -341,7 +358,9 @@ impl ResolverCache {
             && message.get_authority().is_empty()
             && message.get_additional().is_empty()
         {
-            None
+            // Without an exact match, the answer may be synthesized from
+            // validated NSEC and NSEC3 records and wildcards
+            self.cache_aggressive.get(&query)
         } else {
             Some(message)
         }
@@ -404,6 +423,7 @@ impl ResolverCache {
         self.timeout_answer();
         self.timeout_authority();
         self.timeout_additional();
+        self.cache_aggressive.timeout();
     }
 }
 
@@ -422,6 +442,11 @@ impl ResolverCache {
     pub fn get_cache_additional(&self) -> &DnsCache {
         &self.cache_additional
     }
+
+    /// Get the cache of validated NSEC and NSEC3 records and wildcards.
+    pub fn get_cache_aggressive(&self) -> &AggressiveCache {
+        &self.cache_aggressive
+    }
 }
 
 impl ResolverCache {
@@ -439,6 +464,11 @@ impl ResolverCache {
     pub fn set_cache_additional(&mut self, cache: DnsCache) {
         self.cache_additional = cache;
     }
+
+    /// Set the cache of validated NSEC and NSEC3 records and wildcards.
+    pub fn set_cache_aggressive(&mut self, cache: AggressiveCache) {
+        self.cache_aggressive = cache;
+    }
 }
 
 #[cfg(test)]
@@ -1387,4 +1417,55 @@ mod resolver_cache_test {
         assert!(rr_authority.is_none());
         assert!(rr_additional.is_none());
     }
+
+    #[test]
+    fn get_synthesized_from_validated_denial() {
+        use crate::message::rdata::nsec_rdata::NsecRdata;
+        use crate::message::rdata::rrsig_rdata::RRSIGRdata;
+        use crate::message::rdata::soa_rdata::SoaRdata;
+
+        let zone = DomainName::new_from_str("example.com");
+        let signed = |owner: &str, rdata: Rdata| {
+            let mut rr = ResourceRecord::new(rdata);
+            rr.set_name(DomainName::new_from_str(owner));
+            rr.set_ttl(3600);
+            let mut rrsig = RRSIGRdata::new();
+            rrsig.set_type_covered(rr.get_rtype());
+            rrsig.set_signer_name(zone.clone());
+            let mut rrsig_rr = ResourceRecord::new(Rdata::RRSIG(rrsig));
+            rrsig_rr.set_name(rr.get_name());
+            rrsig_rr.set_ttl(3600);
+            vec![rr, rrsig_rr]
+        };
+        let nsec = |owner: &str, next: &str| {
+            signed(owner, Rdata::NSEC(NsecRdata::new(DomainName::new_from_str(next), vec![Rrtype::A])))
+        };
+
+        let mut response = DnsMessage::new_query_message(
+            DomainName::new_from_str("b.example.com"), Rrtype::A, Rclass::IN, 0, true, 1);
+        let mut header = response.get_header();
+        header.set_rcode(Rcode::NXDOMAIN);
+        header.set_ad(true);
+        response.set_header(header);
+        response.set_authority([
+            signed("example.com", Rdata::SOA(SoaRdata::new())),
+            nsec("example.com", "a.example.com"),
+            nsec("a.example.com", "www.example.com"),
+        ].concat());
+
+        let mut resolver_cache = ResolverCache::new(None);
+        resolver_cache.add_validated(&response);
+        assert!(!resolver_cache.is_empty());
+        assert_eq!(resolver_cache.get_cache_aggressive().len(), 3);
+
+        let query = DnsMessage::new_query_message(
+            DomainName::new_from_str("random.example.com"), Rrtype::A, Rclass::IN, 0, true, 2);
+        let message = resolver_cache.get(query).unwrap();
+        assert_eq!(message.get_header().get_rcode(), Rcode::NXDOMAIN);
+        assert_eq!(message.get_query_id(), 2);
+
+        let query = DnsMessage::new_query_message(
+            DomainName::new_from_str("www.example.com"), Rrtype::A, Rclass::IN, 0, true, 3);
+        assert_eq!(resolver_cache.get(query), None);
+    }
 }
@@ -0,0 +1,581 @@
+//! Aggressive use of the DNSSEC-validated cache.
+//!
+//! [RFC 8198](https://datatracker.ietf.org/doc/html/rfc8198#section-4)
+//!
+//! A validating resolver can answer queries for names it has never asked
+//! for, with the NSEC and NSEC3 RRs of earlier answers: a validated record
+//! that covers a name proves that it does not exist, for every name in its
+//! span. Together with the wildcards of the zone, also taken from earlier
+//! answers, the resolver can synthesize NXDOMAIN, NODATA and wildcard
+//! answers without asking the authoritative servers. This blunts floods of
+//! queries for random subdomains, which are never in the cache by name.
+
+use crate::dnssec::denial::{nsec3_hashed_name, proven_closest_encloser, verify_denial, DenialVerdict, MAX_ITERATIONS};
+use crate::domain_name::DomainName;
+use crate::message::rclass::Rclass;
+use crate::message::rcode::Rcode;
+use crate::message::rdata::Rdata;
+use crate::message::resource_record::ResourceRecord;
+use crate::message::rrtype::Rrtype;
+use crate::message::DnsMessage;
+use chrono::{DateTime, Duration, Utc};
+use std::collections::{BTreeMap, HashMap};
+use std::num::NonZeroUsize;
+
+/// RRset cached together with its RRSIGs until the smallest of their TTLs
+/// expires.
+#[derive(Clone, Debug)]
+struct CachedRecords {
+    records: Vec<ResourceRecord>,
+    expiration: DateTime<Utc>,
+}
+
+impl CachedRecords {
+    /// Returns `None` when the records must not be cached, because one of
+    /// them has a TTL of zero.
+    fn new(records: Vec<ResourceRecord>, now: DateTime<Utc>) -> Option<Self> {
+        let ttl = records.iter().map(|rr| rr.get_ttl()).min()?;
+        if ttl == 0 {
+            return None;
+        }
+
+        Some(CachedRecords { records, expiration: now + Duration::seconds(ttl as i64) })
+    }
+
+    fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expiration <= now
+    }
+
+    /// Returns the records with the TTL left at `now`.
+    fn get_records(&self, now: DateTime<Utc>) -> Vec<ResourceRecord> {
+        let ttl = (self.expiration - now).num_seconds().max(0) as u32;
+        self.records
+            .iter()
+            .map(|rr| {
+                let mut rr = rr.clone();
+                rr.set_ttl(ttl);
+                rr
+            })
+            .collect()
+    }
+}
+
+/// Validated records of a zone, with the NSEC and NSEC3 RRs sorted in
+/// canonical order of their owners.
+#[derive(Clone, Debug, Default)]
+struct CachedZone {
+    soa: Option<CachedRecords>,
+    nsecs: BTreeMap<DomainName, CachedRecords>,
+    nsec3s: BTreeMap<DomainName, CachedRecords>,
+}
+
+impl CachedZone {
+    fn len(&self) -> usize {
+        self.soa.is_some() as usize + self.nsecs.len() + self.nsec3s.len()
+    }
+
+    /// Returns the NSEC or NSEC3 RR that may match or cover `name`: the one
+    /// whose owner is the last before the name, or its hash, in canonical
+    /// order. A name before every owner is covered by the last record,
+    /// which points back to the first one.
+    fn proof_for(&self, zone: &DomainName, name: &DomainName) -> Option<&CachedRecords> {
+        let (chain, key) = if !self.nsecs.is_empty() {
+            (&self.nsecs, name.clone())
+        } else {
+            let Rdata::NSEC3(nsec3) = self.nsec3s.values().next()?.records[0].get_rdata() else { return None };
+            (&self.nsec3s, nsec3_hashed_name(name, zone, &nsec3.get_salt(), nsec3.get_iterations()))
+        };
+
+        chain
+            .range(..=key)
+            .next_back()
+            .or_else(|| chain.iter().next_back())
+            .map(|(_, records)| records)
+    }
+
+    fn timeout(&mut self, now: DateTime<Utc>) {
+        if self.soa.as_ref().is_some_and(|soa| soa.is_expired(now)) {
+            self.soa = None;
+        }
+        self.nsecs.retain(|_, records| !records.is_expired(now));
+        self.nsec3s.retain(|_, records| !records.is_expired(now));
+    }
+}
+
+/// Entry of the cache, used to find the one to evict.
+enum Slot {
+    Soa(DomainName, Rclass),
+    Nsec(DomainName, Rclass, DomainName),
+    Nsec3(DomainName, Rclass, DomainName),
+    Wildcard(DomainName, Rrtype, Rclass),
+}
+
+/// Cache of validated NSEC and NSEC3 RRs and wildcards, used to synthesize
+/// answers.
+///
+/// Only messages with the AD bit are cached, so the resolver must add them
+/// once it has validated them as secure.
+///
+/// # Example
+/// ```
+/// let mut cache = AggressiveCache::new(None);
+/// cache.add(&validated_nxdomain_response);
+/// let response = cache.get(&query_for_another_name).unwrap();
+/// assert_eq!(response.get_header().get_rcode(), Rcode::NXDOMAIN);
+/// ```
+#[derive(Clone, Debug)]
+pub struct AggressiveCache {
+    /// Records of each zone, by apex and class.
+    zones: HashMap<(DomainName, Rclass), CachedZone>,
+    /// RRsets of wildcards, by wildcard owner, type and class.
+    wildcards: HashMap<(DomainName, Rrtype, Rclass), CachedRecords>,
+    max_size: NonZeroUsize,
+}
+
+impl AggressiveCache {
+    /// Creates an empty cache holding at most `max_size` RRsets.
+    pub fn new(max_size: Option<NonZeroUsize>) -> Self {
+        AggressiveCache {
+            zones: HashMap::new(),
+            wildcards: HashMap::new(),
+            max_size: max_size.unwrap_or(NonZeroUsize::new(1667).unwrap()),
+        }
+    }
+
+    /// Adds the signed NSEC, NSEC3 and SOA RRsets of the authority section
+    /// of a validated message, and the wildcards its answer was expanded
+    /// from.
+    pub fn add(&mut self, message: &DnsMessage) {
+        self.add_at(message, Utc::now());
+    }
+
+    fn add_at(&mut self, message: &DnsMessage, now: DateTime<Utc>) {
+        if !message.get_header().get_ad() {
+            return;
+        }
+        let qclass = message.get_question().get_rclass();
+
+        let authority = message.get_authority();
+        for rr in authority.iter() {
+            let owner = rr.get_name();
+            let rrsigs = signatures(&authority, &owner, rr.get_rtype());
+            let Some(zone) = rrsigs.iter().find_map(|rrsig| match rrsig.get_rdata() {
+                Rdata::RRSIG(rrsig) => Some(rrsig.get_signer_name()),
+                _ => None,
+            }) else {
+                continue;
+            };
+            if !owner.is_subdomain_of(&zone) {
+                continue;
+            }
+
+            let rdata = rr.get_rdata();
+            let usable = match &rdata {
+                Rdata::SOA(_) => owner == zone,
+                Rdata::NSEC(_) => true,
+                Rdata::NSEC3(nsec3) => {
+                    owner.parent().as_ref() == Some(&zone)
+                        && nsec3.get_hash_algorithm().is_supported()
+                        && nsec3.get_iterations() <= MAX_ITERATIONS
+                }
+                _ => false,
+            };
+            if !usable {
+                continue;
+            }
+
+            let records = std::iter::once(rr.clone()).chain(rrsigs).collect();
+            let Some(cached) = CachedRecords::new(records, now) else { continue };
+            let cached_zone = self.zones.entry((zone, qclass)).or_default();
+            match rdata {
+                Rdata::SOA(_) => cached_zone.soa = Some(cached),
+                Rdata::NSEC(_) => {
+                    cached_zone.nsecs.insert(owner, cached);
+                }
+                _ => {
+                    cached_zone.nsec3s.insert(owner, cached);
+                }
+            }
+        }
+
+        // RFC 4035 section 5.3.4: the Labels field of the RRSIG of an
+        // expanded answer is smaller than the label count of its owner
+        let answer = message.get_answer();
+        for rr in answer.iter() {
+            let Rdata::RRSIG(rrsig) = rr.get_rdata() else { continue };
+            let owner = rr.get_name();
+            let is_wildcard = owner.get_labels().first().is_some_and(|label| label.as_slice() == b"*");
+            let labels = rrsig.get_labels() as usize;
+            if labels + is_wildcard as usize >= owner.label_count() {
+                continue;
+            }
+
+            let mut source = owner.clone();
+            while source.label_count() > labels {
+                source = source.parent().unwrap_or_default();
+            }
+            let Ok(wildcard) = source.child(b"*") else { continue };
+            let rtype = rrsig.get_type_covered();
+            let records = answer
+                .iter()
+                .filter(|other| other.get_name() == owner && other.get_rtype() == rtype)
+                .cloned()
+                .chain(signatures(&answer, &owner, rtype))
+                .map(|mut record| {
+                    record.set_name(wildcard.clone());
+                    record
+                })
+                .collect();
+            if let Some(cached) = CachedRecords::new(records, now) {
+                self.wildcards.insert((wildcard, rtype, qclass), cached);
+            }
+        }
+
+        self.evict(now);
+    }
+
+    /// Synthesizes the response to a query from the cached records, or
+    /// returns `None` if they do not prove the answer.
+    ///
+    /// [RFC 8198](https://datatracker.ietf.org/doc/html/rfc8198#section-5)
+    ///
+    /// NXDOMAIN and NODATA responses hold the SOA RR of the zone and the
+    /// NSEC or NSEC3 RRs of the proof, with their RRSIGs. Answers from a
+    /// wildcard are expanded to the query name, with the records that prove
+    /// the name does not exist.
+    pub fn get(&self, query: &DnsMessage) -> Option<DnsMessage> {
+        self.get_at(query, Utc::now())
+    }
+
+    fn get_at(&self, query: &DnsMessage, now: DateTime<Utc>) -> Option<DnsMessage> {
+        let question = query.get_question();
+        let qname = question.get_qname();
+        let qtype = question.get_rrtype();
+        let qclass = question.get_rclass();
+
+        // The records of the deepest zone the name is in
+        let ((zone, _), cached_zone) = self
+            .zones
+            .iter()
+            .filter(|((zone, rclass), _)| *rclass == qclass && qname.is_subdomain_of(zone))
+            .max_by_key(|((zone, _), _)| zone.label_count())?;
+
+        // A proof only involves the name, its ancestors in the zone and
+        // their wildcards
+        let mut names = Vec::new();
+        let mut name = qname.clone();
+        loop {
+            if let Ok(wildcard) = name.child(b"*") {
+                names.push(wildcard);
+            }
+            names.push(name.clone());
+            if name.label_count() <= zone.label_count() {
+                break;
+            }
+            name = name.parent()?;
+        }
+        let mut used: Vec<&CachedRecords> = Vec::new();
+        for cached in names.iter().filter_map(|name| cached_zone.proof_for(zone, name)) {
+            if !cached.is_expired(now) && !used.iter().any(|other| std::ptr::eq(*other, cached)) {
+                used.push(cached);
+            }
+        }
+        let proof: Vec<ResourceRecord> = used.iter().flat_map(|cached| cached.get_records(now)).collect();
+        let soa = cached_zone.soa.as_ref().filter(|soa| !soa.is_expired(now));
+
+        let (rcode, answer, authority) = match verify_denial(&qname, qtype, &proof) {
+            DenialVerdict::NxDomain => (Rcode::NXDOMAIN, Vec::new(), [soa?.get_records(now), proof].concat()),
+            DenialVerdict::NoData | DenialVerdict::WildcardNoData => {
+                (Rcode::NOERROR, Vec::new(), [soa?.get_records(now), proof].concat())
+            }
+            DenialVerdict::Unproven => {
+                let wildcard = proven_closest_encloser(&qname, &proof)?.child(b"*").ok()?;
+                let cached = [qtype, Rrtype::CNAME]
+                    .iter()
+                    .find_map(|rtype| self.wildcards.get(&(wildcard.clone(), *rtype, qclass)))
+                    .filter(|cached| !cached.is_expired(now))?;
+                let answer = cached
+                    .get_records(now)
+                    .into_iter()
+                    .map(|mut rr| {
+                        rr.set_name(qname.clone());
+                        rr
+                    })
+                    .collect();
+                (Rcode::NOERROR, answer, proof)
+            }
+            _ => return None,
+        };
+
+        let mut message = DnsMessage::new();
+        let mut header = query.get_header();
+        header.set_rcode(rcode);
+        message.set_header(header);
+        message.set_question(question);
+        message.set_answer(answer);
+        message.set_authority(authority);
+        message.update_header_counters();
+        Some(message)
+    }
+
+    /// Removes the records whose TTL has expired.
+    pub fn timeout(&mut self) {
+        self.timeout_at(Utc::now());
+    }
+
+    fn timeout_at(&mut self, now: DateTime<Utc>) {
+        for cached_zone in self.zones.values_mut() {
+            cached_zone.timeout(now);
+        }
+        self.zones.retain(|_, cached_zone| cached_zone.len() > 0);
+        self.wildcards.retain(|_, cached| !cached.is_expired(now));
+    }
+
+    /// Removes expired records, then the ones closest to expire, until the
+    /// cache is not over its maximum size.
+    fn evict(&mut self, now: DateTime<Utc>) {
+        if self.len() <= self.max_size.get() {
+            return;
+        }
+        self.timeout_at(now);
+
+        while self.len() > self.max_size.get() {
+            let Some(slot) = self.slot_closest_to_expire() else { return };
+            match slot {
+                Slot::Soa(zone, rclass) => {
+                    self.zones.entry((zone, rclass)).or_default().soa = None;
+                }
+                Slot::Nsec(zone, rclass, owner) => {
+                    self.zones.entry((zone, rclass)).or_default().nsecs.remove(&owner);
+                }
+                Slot::Nsec3(zone, rclass, owner) => {
+                    self.zones.entry((zone, rclass)).or_default().nsec3s.remove(&owner);
+                }
+                Slot::Wildcard(owner, rtype, rclass) => {
+                    self.wildcards.remove(&(owner, rtype, rclass));
+                }
+            }
+        }
+        self.zones.retain(|_, cached_zone| cached_zone.len() > 0);
+    }
+
+    fn slot_closest_to_expire(&self) -> Option<Slot> {
+        let mut slots: Vec<(DateTime<Utc>, Slot)> = Vec::new();
+        for ((zone, rclass), cached_zone) in self.zones.iter() {
+            if let Some(soa) = &cached_zone.soa {
+                slots.push((soa.expiration, Slot::Soa(zone.clone(), *rclass)));
+            }
+            for (owner, cached) in cached_zone.nsecs.iter() {
+                slots.push((cached.expiration, Slot::Nsec(zone.clone(), *rclass, owner.clone())));
+            }
+            for (owner, cached) in cached_zone.nsec3s.iter() {
+                slots.push((cached.expiration, Slot::Nsec3(zone.clone(), *rclass, owner.clone())));
+            }
+        }
+        for ((owner, rtype, rclass), cached) in self.wildcards.iter() {
+            slots.push((cached.expiration, Slot::Wildcard(owner.clone(), *rtype, *rclass)));
+        }
+
+        slots.into_iter().min_by_key(|(expiration, _)| *expiration).map(|(_, slot)| slot)
+    }
+
+    /// Returns the number of RRsets in the cache.
+    pub fn len(&self) -> usize {
+        self.zones.values().map(|cached_zone| cached_zone.len()).sum::<usize>() + self.wildcards.len()
+    }
+
+    /// See if the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get the maximum size of the cache.
+    pub fn get_max_size(&self) -> NonZeroUsize {
+        self.max_size
+    }
+
+    /// Set the maximum size of the cache.
+    pub fn set_max_size(&mut self, max_size: NonZeroUsize) {
+        self.max_size = max_size;
+    }
+}
+
+/// Returns the RRSIG RRs of a section that cover the RRset of `owner` and
+/// `rtype`.
+fn signatures(section: &[ResourceRecord], owner: &DomainName, rtype: Rrtype) -> Vec<ResourceRecord> {
+    section
+        .iter()
+        .filter(|rr| {
+            rr.get_name() == *owner && matches!(rr.get_rdata(), Rdata::RRSIG(rrsig) if rrsig.get_type_covered() == rtype)
+        })
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod aggressive_cache_test {
+    use super::*;
+    use crate::message::rdata::mx_rdata::MxRdata;
+    use crate::message::rdata::nsec_rdata::NsecRdata;
+    use crate::message::rdata::rrsig_rdata::RRSIGRdata;
+    use crate::message::rdata::soa_rdata::SoaRdata;
+
+    fn name(name: &str) -> DomainName {
+        DomainName::new_from_str(name)
+    }
+
+    fn rr(owner: &str, ttl: u32, rdata: Rdata) -> ResourceRecord {
+        let mut rr = ResourceRecord::new(rdata);
+        rr.set_name(name(owner));
+        rr.set_ttl(ttl);
+        rr
+    }
+
+    /// The RR followed by an RRSIG of the zone `example` that covers it.
+    fn signed(rr: ResourceRecord, labels: u8) -> Vec<ResourceRecord> {
+        let mut rrsig = RRSIGRdata::new();
+        rrsig.set_type_covered(rr.get_rtype());
+        rrsig.set_labels(labels);
+        rrsig.set_original_ttl(rr.get_ttl());
+        rrsig.set_signer_name(name("example"));
+        rrsig.set_signature(vec![1, 2, 3, 4]);
+        let mut rrsig_rr = ResourceRecord::new(Rdata::RRSIG(rrsig));
+        rrsig_rr.set_name(rr.get_name());
+        rrsig_rr.set_ttl(rr.get_ttl());
+        vec![rr, rrsig_rr]
+    }
+
+    fn nsec(owner: &str, next: &str, types: Vec<Rrtype>) -> Vec<ResourceRecord> {
+        let labels = name(owner).label_count() as u8;
+        signed(rr(owner, 3600, Rdata::NSEC(NsecRdata::new(name(next), types))), labels)
+    }
+
+    fn soa() -> Vec<ResourceRecord> {
+        signed(rr("example", 3600, Rdata::SOA(SoaRdata::new())), 1)
+    }
+
+    fn query(qname: &str, qtype: Rrtype) -> DnsMessage {
+        DnsMessage::new_query_message(name(qname), qtype, Rclass::IN, 0, false, 1)
+    }
+
+    fn response(qname: &str, qtype: Rrtype, answer: Vec<ResourceRecord>, authority: Vec<ResourceRecord>) -> DnsMessage {
+        let mut message = query(qname, qtype);
+        let mut header = message.get_header();
+        header.set_qr(true);
+        header.set_ad(true);
+        message.set_header(header);
+        message.set_answer(answer);
+        message.set_authority(authority);
+        message
+    }
+
+    /// NXDOMAIN response for c.example: b.example covers the name and
+    /// example covers the wildcard *.example.
+    fn nxdomain() -> DnsMessage {
+        let authority = [
+            soa(),
+            nsec("b.example", "ns1.example", vec![Rrtype::NS]),
+            nsec("example", "a.example", vec![Rrtype::NS, Rrtype::SOA, Rrtype::RRSIG, Rrtype::NSEC]),
+        ]
+        .concat();
+        let mut message = response("c.example", Rrtype::A, Vec::new(), authority);
+        let mut header = message.get_header();
+        header.set_rcode(Rcode::NXDOMAIN);
+        message.set_header(header);
+        message
+    }
+
+    #[test]
+    fn synthesized_nxdomain() {
+        let mut cache = AggressiveCache::new(None);
+        cache.add(&nxdomain());
+        assert_eq!(cache.len(), 3);
+
+        let response = cache.get(&query("mail.example", Rrtype::A)).unwrap();
+        assert_eq!(response.get_header().get_rcode(), Rcode::NXDOMAIN);
+        assert_eq!(response.get_question().get_qname(), name("mail.example"));
+        assert!(response.get_answer().is_empty());
+        assert_eq!(response.get_authority().len(), 6);
+        assert_eq!(response.get_header().get_nscount(), 6);
+
+        // Names outside the span of the cached NSEC RRs
+        assert!(cache.get(&query("x.example", Rrtype::A)).is_none());
+        assert!(cache.get(&query("a.example", Rrtype::A)).is_none());
+        assert!(cache.get(&query("c.other", Rrtype::A)).is_none());
+    }
+
+    #[test]
+    fn synthesized_no_data() {
+        let mut cache = AggressiveCache::new(None);
+        let authority = [soa(), nsec("ns1.example", "w.example", vec![Rrtype::A, Rrtype::RRSIG, Rrtype::NSEC])].concat();
+        cache.add(&response("ns1.example", Rrtype::AAAA, Vec::new(), authority));
+
+        let response = cache.get(&query("ns1.example", Rrtype::MX)).unwrap();
+        assert_eq!(response.get_header().get_rcode(), Rcode::NOERROR);
+        assert!(response.get_answer().is_empty());
+        assert_eq!(response.get_authority().len(), 4);
+        assert!(cache.get(&query("ns1.example", Rrtype::A)).is_none());
+    }
+
+    #[test]
+    fn synthesized_wildcard_answer() {
+        let mut cache = AggressiveCache::new(None);
+        let answer = signed(rr("b.w.example", 3600, Rdata::MX(MxRdata::new())), 2);
+        let authority = nsec("*.w.example", "x.y.w.example", vec![Rrtype::MX, Rrtype::RRSIG, Rrtype::NSEC]);
+        cache.add(&response("b.w.example", Rrtype::MX, answer, authority));
+
+        let response = cache.get(&query("c.w.example", Rrtype::MX)).unwrap();
+        assert_eq!(response.get_header().get_rcode(), Rcode::NOERROR);
+        let answer = response.get_answer();
+        assert_eq!(answer.len(), 2);
+        assert!(answer.iter().all(|rr| rr.get_name() == name("c.w.example")));
+        assert_eq!(answer[0].get_rtype(), Rrtype::MX);
+        assert_eq!(response.get_authority().len(), 2);
+
+        // The wildcard has no A RRset, but there is no SOA to build a
+        // NODATA response with
+        assert!(cache.get(&query("c.w.example", Rrtype::A)).is_none());
+        // x.y.w.example is not covered
+        assert!(cache.get(&query("z.w.example", Rrtype::MX)).is_none());
+    }
+
+    #[test]
+    fn unvalidated_messages_are_ignored() {
+        let mut cache = AggressiveCache::new(None);
+        let mut message = nxdomain();
+        let mut header = message.get_header();
+        header.set_ad(false);
+        message.set_header(header);
+        cache.add(&message);
+        assert!(cache.is_empty());
+
+        // Unsigned NSEC RRs are ignored as well
+        let authority = vec![rr("b.example", 3600, Rdata::NSEC(NsecRdata::new(name("ns1.example"), vec![Rrtype::NS])))];
+        cache.add(&response("c.example", Rrtype::A, Vec::new(), authority));
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn records_expire() {
+        let now = Utc::now();
+        let mut cache = AggressiveCache::new(None);
+        cache.add_at(&nxdomain(), now);
+
+        let response = cache.get_at(&query("d.example", Rrtype::A), now + Duration::seconds(600)).unwrap();
+        assert!(response.get_authority().iter().all(|rr| rr.get_ttl() == 3000));
+
+        let later = now + Duration::seconds(3600);
+        assert!(cache.get_at(&query("d.example", Rrtype::A), later).is_none());
+        cache.timeout_at(later);
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn eviction_of_records_closest_to_expire() {
+        let mut cache = AggressiveCache::new(NonZeroUsize::new(2));
+        cache.add(&nxdomain());
+
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get_max_size(), NonZeroUsize::new(2).unwrap());
+    }
+}
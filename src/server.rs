@@ -0,0 +1,883 @@
+pub mod server_error;
+
+use crate::domain_name::DomainName;
+use crate::message::header::Header;
+use crate::message::rclass::Rclass;
+use crate::message::rcode::Rcode;
+use crate::message::rdata::Rdata;
+use crate::message::resource_record::ResourceRecord;
+use crate::message::rrtype::Rrtype;
+use crate::message::DnsMessage;
+use crate::server::server_error::ServerError;
+use crate::zone::Zone;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream, UdpSocket};
+use tokio::time::{timeout, Duration};
+
+/// Maximum size of a response sent over UDP to a client without EDNS.
+const MAX_UDP_PAYLOAD: usize = 512;
+
+/// Maximum UDP payload advertised by the server when EDNS is used.
+const MAX_EDNS_PAYLOAD: u16 = 1232;
+
+/// Maximum number of CNAME records followed while answering a query.
+const MAX_CNAME_CHAIN: usize = 16;
+
+/// Time a TCP connection is kept open waiting for a new query.
+const TCP_IDLE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Authoritative name server.
+///
+/// The server answers the queries from the zones it has loaded following
+/// the algorithm of [RFC 1034](https://datatracker.ietf.org/doc/html/rfc1034#section-4.3.2).
+/// It does not offer recursion, so names outside its zones are refused.
+///
+/// The same `Server` can listen on UDP and TCP at the same time with
+/// `run`, or each listener can be driven separately with `serve_udp` and
+/// `serve_tcp`.
+#[derive(Clone, Debug, Default)]
+pub struct Server {
+    /// Zones the server is authoritative for.
+    zones: Arc<Vec<Zone>>,
+}
+
+/// Result of looking up a name inside a single zone.
+enum ZoneLookup {
+    /// Records that answer the question.
+    Answer(Vec<ResourceRecord>),
+    /// The name is an alias, the CNAME record must be followed.
+    Cname(ResourceRecord),
+    /// The name is below a delegation point, the NS records of the child zone.
+    Referral(Vec<ResourceRecord>),
+    /// The name exists but has no records of the requested type.
+    NoData,
+    /// The name does not exist.
+    NxDomain,
+}
+
+impl Server {
+    /// Creates a new `Server` authoritative for the given zones.
+    ///
+    /// # Examples
+    /// ```
+    /// let zone = Zone::new(DomainName::new_from_str("example.com"), Rclass::IN);
+    /// let server = Server::new(vec![zone]);
+    /// ```
+    pub fn new(zones: Vec<Zone>) -> Self {
+        Server {
+            zones: Arc::new(zones),
+        }
+    }
+
+    /// Adds a zone to the server.
+    pub fn add_zone(&mut self, zone: Zone) {
+        Arc::make_mut(&mut self.zones).push(zone);
+    }
+
+    /// Binds UDP and TCP listeners to `addr` and answers queries until
+    /// one of them fails.
+    pub async fn run(&self, addr: SocketAddr) -> Result<(), ServerError> {
+        let udp_socket = UdpSocket::bind(addr).await?;
+        let tcp_listener = TcpListener::bind(addr).await?;
+
+        tokio::try_join!(self.serve_udp(udp_socket), self.serve_tcp(tcp_listener))?;
+
+        Ok(())
+    }
+
+    /// Answers the queries received in a bound UDP socket.
+    pub async fn serve_udp(&self, socket: UdpSocket) -> Result<(), ServerError> {
+        let mut buffer = vec![0; u16::MAX as usize];
+
+        loop {
+            let (n, src_addr) = socket.recv_from(&mut buffer).await?;
+
+            if let Some(response) = self.handle_query(&buffer[..n], true) {
+                // A client that can not be reached must not stop the server
+                let _ = socket.send_to(&response, src_addr).await;
+            }
+        }
+    }
+
+    /// Accepts TCP connections and answers the queries received on them.
+    /// Each connection is served in its own task.
+    pub async fn serve_tcp(&self, listener: TcpListener) -> Result<(), ServerError> {
+        loop {
+            let (stream, _) = listener.accept().await?;
+            let server = self.clone();
+
+            tokio::spawn(async move {
+                let _ = server.handle_tcp_connection(stream).await;
+            });
+        }
+    }
+
+    /// Answers the queries of a TCP connection, each one preceded by its
+    /// length in two bytes, until the client closes it or stays idle.
+    async fn handle_tcp_connection(&self, mut stream: TcpStream) -> Result<(), ServerError> {
+        loop {
+            let mut msg_size: [u8; 2] = [0; 2];
+
+            match timeout(TCP_IDLE_TIMEOUT, stream.read_exact(&mut msg_size)).await {
+                Ok(Ok(_)) => {}
+                // The client closed the connection or stayed idle
+                _ => return Ok(()),
+            }
+
+            let mut query = vec![0; u16::from_be_bytes(msg_size) as usize];
+            stream.read_exact(&mut query).await?;
+
+            if let Some(response) = self.handle_query(&query, false) {
+                let response_size = (response.len() as u16).to_be_bytes();
+                stream.write_all(&[&response_size, response.as_slice()].concat()).await?;
+            }
+        }
+    }
+
+    /// Given the bytes of a query, returns the bytes of the response.
+    ///
+    /// Returns `None` when the message must not be answered, which happens
+    /// when it is a response or it is too short to contain a header.
+    /// Responses sent over UDP are truncated to the payload size of the
+    /// client.
+    pub fn handle_query(&self, bytes: &[u8], udp: bool) -> Option<Vec<u8>> {
+        let query = match DnsMessage::from_bytes(bytes) {
+            Ok(query) => query,
+            Err(_) => {
+                if bytes.len() < 12 {
+                    return None;
+                }
+
+                let header = Header::from_bytes(&bytes[0..12]);
+                if header.get_qr() {
+                    return None;
+                }
+
+                let mut response = DnsMessage::format_error_msg();
+                let mut response_header = response.get_header();
+                response_header.set_id(header.get_id());
+                response_header.set_op_code(header.get_op_code());
+                response_header.set_rd(header.get_rd());
+                response.set_header(response_header);
+
+                return Some(response.to_bytes());
+            }
+        };
+
+        if query.get_header().get_qr() {
+            return None;
+        }
+
+        let response = self.answer(&query);
+
+        let max_size = if udp {
+            max_udp_payload(&query)
+        } else {
+            u16::MAX as usize
+        };
+
+        Some(truncate(response, max_size))
+    }
+
+    /// Answers a query from the zones of the server.
+    ///
+    /// [RFC 1034](https://datatracker.ietf.org/doc/html/rfc1034#section-4.3.2)
+    pub fn answer(&self, query: &DnsMessage) -> DnsMessage {
+        let header = query.get_header();
+
+        if header.get_op_code() != 0 {
+            return error_response(query, DnsMessage::not_implemented_msg());
+        }
+        if header.get_qdcount() != 1 {
+            return error_response(query, DnsMessage::format_error_msg());
+        }
+
+        let question = query.get_question();
+        let qtype = question.get_rrtype();
+        let qclass = question.get_rclass();
+
+        // Zone transfers are not supported
+        if qtype == Rrtype::AXFR || qtype == Rrtype::UNKNOWN(251) {
+            return error_response(query, DnsMessage::not_implemented_msg());
+        }
+
+        let mut response = DnsMessage::new_response_message(
+            question.get_qname().get_name(),
+            &qtype.to_string(),
+            &qclass.to_string(),
+            header.get_op_code(),
+            header.get_rd(),
+            header.get_id(),
+        );
+        response.set_question(question.clone());
+
+        let mut qname = question.get_qname();
+        let mut zone = match self.find_zone(&qname, qclass) {
+            Some(zone) => zone,
+            None => {
+                let mut response_header = response.get_header();
+                response_header.set_rcode(Rcode::REFUSED);
+                response.set_header(response_header);
+                return response;
+            }
+        };
+
+        let mut aa = true;
+        let mut rcode = Rcode::NOERROR;
+        let mut answer: Vec<ResourceRecord> = Vec::new();
+        let mut authority: Vec<ResourceRecord> = Vec::new();
+
+        loop {
+            match lookup_in_zone(zone, &qname, qtype) {
+                ZoneLookup::Answer(rrs) => {
+                    answer.extend(rrs);
+                    break;
+                }
+                ZoneLookup::Cname(cname_rr) => {
+                    let canonical_name = match cname_rr.get_rdata() {
+                        Rdata::CNAME(cname) => cname.get_cname(),
+                        _ => unreachable!(),
+                    };
+                    answer.push(cname_rr);
+
+                    // Stops at loops, long chains and names out of our zones
+                    let looped = answer.iter().any(|rr| rr.get_name() == canonical_name);
+                    if looped || answer.len() >= MAX_CNAME_CHAIN {
+                        break;
+                    }
+
+                    zone = match self.find_zone(&canonical_name, qclass) {
+                        Some(zone) => zone,
+                        None => break,
+                    };
+                    qname = canonical_name;
+                }
+                ZoneLookup::Referral(ns_rrs) => {
+                    if answer.is_empty() {
+                        aa = false;
+                    }
+                    authority.extend(ns_rrs);
+                    break;
+                }
+                ZoneLookup::NoData => {
+                    authority.extend(negative_soa(zone));
+                    break;
+                }
+                ZoneLookup::NxDomain => {
+                    rcode = Rcode::NXDOMAIN;
+                    authority.extend(negative_soa(zone));
+                    break;
+                }
+            }
+        }
+
+        let additional = self.additional_records(&answer, &authority);
+
+        let mut response_header = response.get_header();
+        response_header.set_aa(aa);
+        response_header.set_ra(false);
+        response_header.set_rcode(rcode);
+        response.set_header(response_header);
+
+        response.set_answer(answer);
+        response.set_authority(authority);
+        response.set_additional(additional);
+
+        if has_opt(query) {
+            response.add_edns0(Some(MAX_EDNS_PAYLOAD), Rcode::NOERROR, 0, false, None);
+        }
+
+        response.update_header_counters();
+
+        response
+    }
+
+    /// Returns the zone with the longest origin containing `name`.
+    fn find_zone(&self, name: &DomainName, rclass: Rclass) -> Option<&Zone> {
+        self.zones
+            .iter()
+            .filter(|zone| rclass == Rclass::ANY || zone.get_rclass() == rclass)
+            .filter(|zone| name.is_subdomain_of(&zone.get_origin()))
+            .max_by_key(|zone| zone.get_origin().label_count())
+    }
+
+    /// Returns the address records of the names in NS, MX and SRV records
+    /// of the answer and authority sections.
+    fn additional_records(
+        &self,
+        answer: &[ResourceRecord],
+        authority: &[ResourceRecord],
+    ) -> Vec<ResourceRecord> {
+        let mut additional: Vec<ResourceRecord> = Vec::new();
+
+        for rr in answer.iter().chain(authority.iter()) {
+            let target = match rr.get_rdata() {
+                Rdata::NS(ns) => ns.get_nsdname(),
+                Rdata::MX(mx) => mx.get_exchange(),
+                Rdata::SRV(srv) => srv.get_target(),
+                _ => continue,
+            };
+
+            let zone = match self.find_zone(&target, rr.get_rclass()) {
+                Some(zone) => zone,
+                None => continue,
+            };
+
+            for rrtype in [Rrtype::A, Rrtype::AAAA] {
+                for address_rr in zone.get_rrs(&target, rrtype) {
+                    if !additional.contains(&address_rr) && !answer.contains(&address_rr) {
+                        additional.push(address_rr);
+                    }
+                }
+            }
+        }
+
+        additional
+    }
+}
+
+/// Looks up `qname` inside `zone`, walking down the tree from the origin
+/// of the zone as in step 3 of RFC 1034 section 4.3.2.
+fn lookup_in_zone(zone: &Zone, qname: &DomainName, qtype: Rrtype) -> ZoneLookup {
+    let origin_label_count = zone.get_origin().label_count();
+
+    // Names between the origin (excluded) and the qname
+    let mut nodes: Vec<DomainName> = Vec::new();
+    let mut node = qname.clone();
+    while node.label_count() > origin_label_count {
+        let parent = node.parent().unwrap();
+        nodes.push(node);
+        node = parent;
+    }
+    nodes.reverse();
+
+    for node in nodes.iter() {
+        if !zone.contains_name(node) {
+            let closest_encloser = node.parent().unwrap();
+            let wildcard = match closest_encloser.child(b"*") {
+                Ok(wildcard) => wildcard,
+                Err(_) => return ZoneLookup::NxDomain,
+            };
+
+            if zone.contains_name(&wildcard) {
+                return lookup_in_node(zone, &wildcard, qname, qtype);
+            }
+
+            return ZoneLookup::NxDomain;
+        }
+
+        // The DS records of a delegation belong to the parent zone
+        let ds_at_cut = node == qname && qtype == Rrtype::DS;
+        if zone.is_delegation(node) && !ds_at_cut {
+            return ZoneLookup::Referral(zone.get_rrs(node, Rrtype::NS));
+        }
+    }
+
+    lookup_in_node(zone, qname, qname, qtype)
+}
+
+/// Looks up the records of `node`, writing `qname` as their owner name.
+/// Both names are different when `node` is a wildcard.
+fn lookup_in_node(zone: &Zone, node: &DomainName, qname: &DomainName, qtype: Rrtype) -> ZoneLookup {
+    let with_owner = |mut rr: ResourceRecord| {
+        rr.set_name(qname.clone());
+        rr
+    };
+
+    let rrs = zone.get_rrs(node, qtype);
+    if !rrs.is_empty() {
+        return ZoneLookup::Answer(rrs.into_iter().map(with_owner).collect());
+    }
+
+    if qtype != Rrtype::CNAME {
+        if let Some(cname_rr) = zone.get_rrs(node, Rrtype::CNAME).into_iter().next() {
+            return ZoneLookup::Cname(with_owner(cname_rr));
+        }
+    }
+
+    ZoneLookup::NoData
+}
+
+/// Returns the SOA record of the zone to add in the authority section of a
+/// negative answer, with the TTL set as in RFC 2308 section 3.
+fn negative_soa(zone: &Zone) -> Option<ResourceRecord> {
+    let mut soa_rr = zone.get_soa()?;
+
+    if let Some(minimum) = zone.get_minimum_ttl() {
+        soa_rr.set_ttl(soa_rr.get_ttl().min(minimum));
+    }
+
+    Some(soa_rr)
+}
+
+/// Builds an error response to `query` from one of the error messages of
+/// `DnsMessage`.
+fn error_response(query: &DnsMessage, mut response: DnsMessage) -> DnsMessage {
+    let query_header = query.get_header();
+    let mut header = response.get_header();
+
+    header.set_id(query_header.get_id());
+    header.set_op_code(query_header.get_op_code());
+    header.set_rd(query_header.get_rd());
+    header.set_qdcount(query_header.get_qdcount().min(1));
+    response.set_header(header);
+    response.set_question(query.get_question());
+
+    response
+}
+
+/// Returns `true` if the message has an OPT record.
+fn has_opt(msg: &DnsMessage) -> bool {
+    msg.get_additional()
+        .iter()
+        .any(|rr| rr.get_rtype() == Rrtype::OPT)
+}
+
+/// Returns the size of the largest response the client accepts over UDP.
+fn max_udp_payload(query: &DnsMessage) -> usize {
+    let requested = query
+        .get_additional()
+        .iter()
+        .find(|rr| rr.get_rtype() == Rrtype::OPT)
+        .map(|opt_rr| u16::from(opt_rr.get_rclass()) as usize);
+
+    match requested {
+        Some(size) => size.clamp(MAX_UDP_PAYLOAD, MAX_EDNS_PAYLOAD as usize),
+        None => MAX_UDP_PAYLOAD,
+    }
+}
+
+/// Returns the bytes of the response, removing records until it fits in
+/// `max_size`.
+///
+/// The additional section is dropped first, since it is not needed to
+/// answer the question. If the response still does not fit, the answer and
+/// authority sections are removed. The TC bit is set whenever the removed
+/// records were not optional, so the client can retry over TCP
+/// ([RFC 2181](https://datatracker.ietf.org/doc/html/rfc2181#section-9)).
+///
+/// Glue of in-domain name servers is not optional in a referral
+/// ([RFC 9471](https://datatracker.ietf.org/doc/html/rfc9471#section-3)):
+///
+/// If message size constraints prevent the inclusion of all glue records
+/// for in-domain name servers, the server MUST set the TC (Truncated) flag
+/// to inform the client that the response is incomplete and that the client
+/// SHOULD use another transport to retrieve the full response.
+fn truncate(mut response: DnsMessage, max_size: usize) -> Vec<u8> {
+    let bytes = response.to_bytes();
+    if bytes.len() <= max_size {
+        return bytes;
+    }
+
+    let required_glue_dropped = has_in_domain_glue(&response);
+    let opt_rrs: Vec<ResourceRecord> = response
+        .get_additional()
+        .into_iter()
+        .filter(|rr| rr.get_rtype() == Rrtype::OPT)
+        .collect();
+    response.set_additional(opt_rrs);
+    response.update_header_counters();
+
+    if required_glue_dropped {
+        let mut header = response.get_header();
+        header.set_tc(true);
+        response.set_header(header);
+    }
+
+    let bytes = response.to_bytes();
+    if bytes.len() <= max_size {
+        return bytes;
+    }
+
+    let mut header = response.get_header();
+    header.set_tc(true);
+    response.set_header(header);
+    response.set_answer(Vec::new());
+    response.set_authority(Vec::new());
+    response.update_header_counters();
+
+    response.to_bytes()
+}
+
+/// Returns whether the response is a referral with address records, in the
+/// additional section, of name servers inside the delegated zone.
+fn has_in_domain_glue(response: &DnsMessage) -> bool {
+    if !response.get_answer().is_empty() {
+        return false;
+    }
+
+    let in_domain_servers: Vec<DomainName> = response
+        .get_authority()
+        .into_iter()
+        .filter_map(|rr| match rr.get_rdata() {
+            Rdata::NS(ns_rdata) if ns_rdata.get_nsdname().is_subdomain_of(&rr.get_name()) => {
+                Some(ns_rdata.get_nsdname())
+            }
+            _ => None,
+        })
+        .collect();
+
+    response.get_additional().iter().any(|rr| {
+        matches!(rr.get_rtype(), Rrtype::A | Rrtype::AAAA) && in_domain_servers.contains(&rr.get_name())
+    })
+}
+
+#[cfg(test)]
+mod server_test {
+    use super::{truncate, Server};
+    use crate::domain_name::DomainName;
+    use crate::message::header::Header;
+    use crate::message::rclass::Rclass;
+    use crate::message::rcode::Rcode;
+    use crate::message::rdata::a_rdata::ARdata;
+    use crate::message::rdata::cname_rdata::CnameRdata;
+    use crate::message::rdata::mx_rdata::MxRdata;
+    use crate::message::rdata::ns_rdata::NsRdata;
+    use crate::message::rdata::soa_rdata::SoaRdata;
+    use crate::message::rdata::txt_rdata::TxtRdata;
+    use crate::message::rdata::Rdata;
+    use crate::message::resource_record::ResourceRecord;
+    use crate::message::rrtype::Rrtype;
+    use crate::message::DnsMessage;
+    use crate::zone::Zone;
+    use std::net::IpAddr;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream, UdpSocket};
+
+    fn rr(name: &str, rdata: Rdata, rrtype: Rrtype) -> ResourceRecord {
+        let mut rr = ResourceRecord::new(rdata);
+        rr.set_name(DomainName::new_from_str(name));
+        rr.set_type_code(rrtype);
+        rr.set_ttl(3600);
+        rr
+    }
+
+    fn a_rr(name: &str, address: [u8; 4]) -> ResourceRecord {
+        rr(name, Rdata::A(ARdata::new_from_addr(IpAddr::from(address))), Rrtype::A)
+    }
+
+    fn ns_rr(name: &str, nsdname: &str) -> ResourceRecord {
+        let mut ns_rdata = NsRdata::new();
+        ns_rdata.set_nsdname(DomainName::new_from_str(nsdname));
+        rr(name, Rdata::NS(ns_rdata), Rrtype::NS)
+    }
+
+    fn cname_rr(name: &str, cname: &str) -> ResourceRecord {
+        let mut cname_rdata = CnameRdata::new();
+        cname_rdata.set_cname(DomainName::new_from_str(cname));
+        rr(name, Rdata::CNAME(cname_rdata), Rrtype::CNAME)
+    }
+
+    fn example_zone() -> Zone {
+        let mut soa_rdata = SoaRdata::new();
+        soa_rdata.set_mname(DomainName::new_from_str("ns1.example.com"));
+        soa_rdata.set_rname(DomainName::new_from_str("admin.example.com"));
+        soa_rdata.set_serial(1);
+        soa_rdata.set_minimum(300);
+
+        let mut mx_rdata = MxRdata::new();
+        mx_rdata.set_preference(10);
+        mx_rdata.set_exchange(DomainName::new_from_str("mail.example.com"));
+
+        Zone::from_rrs(
+            DomainName::new_from_str("example.com"),
+            Rclass::IN,
+            vec![
+                rr("example.com", Rdata::SOA(soa_rdata), Rrtype::SOA),
+                ns_rr("example.com", "ns1.example.com"),
+                rr("example.com", Rdata::MX(mx_rdata), Rrtype::MX),
+                a_rr("ns1.example.com", [192, 0, 2, 1]),
+                a_rr("mail.example.com", [192, 0, 2, 2]),
+                a_rr("www.example.com", [192, 0, 2, 3]),
+                cname_rr("alias.example.com", "www.example.com"),
+                cname_rr("outside.example.com", "www.example.org"),
+                a_rr("*.wild.example.com", [192, 0, 2, 4]),
+                a_rr("host.deep.example.com", [192, 0, 2, 5]),
+                ns_rr("sub.example.com", "ns.sub.example.com"),
+                a_rr("ns.sub.example.com", [192, 0, 2, 6]),
+            ],
+        )
+        .unwrap()
+    }
+
+    fn query(name: &str, rrtype: Rrtype) -> DnsMessage {
+        DnsMessage::new_query_message(DomainName::new_from_str(name), rrtype, Rclass::IN, 0, false, 1234)
+    }
+
+    #[test]
+    fn answer_authoritative_data() {
+        let server = Server::new(vec![example_zone()]);
+
+        let response = server.answer(&query("www.example.com", Rrtype::A));
+        let header = response.get_header();
+
+        assert_eq!(header.get_id(), 1234);
+        assert!(header.get_qr());
+        assert!(header.get_aa());
+        assert!(!header.get_ra());
+        assert_eq!(header.get_rcode(), Rcode::NOERROR);
+        assert_eq!(response.get_answer(), vec![a_rr("www.example.com", [192, 0, 2, 3])]);
+    }
+
+    #[test]
+    fn answer_adds_additional_records() {
+        let server = Server::new(vec![example_zone()]);
+
+        let response = server.answer(&query("example.com", Rrtype::MX));
+
+        assert_eq!(response.get_answer().len(), 1);
+        assert_eq!(response.get_additional(), vec![a_rr("mail.example.com", [192, 0, 2, 2])]);
+    }
+
+    #[test]
+    fn answer_follows_cname() {
+        let server = Server::new(vec![example_zone()]);
+
+        let response = server.answer(&query("alias.example.com", Rrtype::A));
+
+        assert_eq!(
+            response.get_answer(),
+            vec![
+                cname_rr("alias.example.com", "www.example.com"),
+                a_rr("www.example.com", [192, 0, 2, 3]),
+            ]
+        );
+        assert!(response.get_header().get_aa());
+    }
+
+    #[test]
+    fn answer_cname_out_of_zone() {
+        let server = Server::new(vec![example_zone()]);
+
+        let response = server.answer(&query("outside.example.com", Rrtype::A));
+
+        assert_eq!(response.get_answer(), vec![cname_rr("outside.example.com", "www.example.org")]);
+        assert_eq!(response.get_header().get_rcode(), Rcode::NOERROR);
+    }
+
+    #[test]
+    fn answer_cname_query() {
+        let server = Server::new(vec![example_zone()]);
+
+        let response = server.answer(&query("alias.example.com", Rrtype::CNAME));
+
+        assert_eq!(response.get_answer(), vec![cname_rr("alias.example.com", "www.example.com")]);
+    }
+
+    #[test]
+    fn answer_wildcard() {
+        let server = Server::new(vec![example_zone()]);
+
+        let response = server.answer(&query("anything.wild.example.com", Rrtype::A));
+
+        assert_eq!(
+            response.get_answer(),
+            vec![a_rr("anything.wild.example.com", [192, 0, 2, 4])]
+        );
+        assert_eq!(response.get_header().get_rcode(), Rcode::NOERROR);
+    }
+
+    #[test]
+    fn answer_nxdomain() {
+        let server = Server::new(vec![example_zone()]);
+
+        let response = server.answer(&query("nothing.example.com", Rrtype::A));
+        let header = response.get_header();
+
+        assert_eq!(header.get_rcode(), Rcode::NXDOMAIN);
+        assert!(header.get_aa());
+        assert!(response.get_answer().is_empty());
+        assert_eq!(response.get_authority().len(), 1);
+        assert_eq!(response.get_authority()[0].get_rtype(), Rrtype::SOA);
+        assert_eq!(response.get_authority()[0].get_ttl(), 300);
+    }
+
+    #[test]
+    fn answer_no_data() {
+        let server = Server::new(vec![example_zone()]);
+
+        let empty_non_terminal = server.answer(&query("deep.example.com", Rrtype::A));
+        assert_eq!(empty_non_terminal.get_header().get_rcode(), Rcode::NOERROR);
+        assert!(empty_non_terminal.get_answer().is_empty());
+        assert_eq!(empty_non_terminal.get_authority()[0].get_rtype(), Rrtype::SOA);
+
+        let other_type = server.answer(&query("www.example.com", Rrtype::MX));
+        assert_eq!(other_type.get_header().get_rcode(), Rcode::NOERROR);
+        assert!(other_type.get_answer().is_empty());
+        assert_eq!(other_type.get_authority()[0].get_rtype(), Rrtype::SOA);
+    }
+
+    #[test]
+    fn answer_referral() {
+        let server = Server::new(vec![example_zone()]);
+
+        let response = server.answer(&query("www.sub.example.com", Rrtype::A));
+        let header = response.get_header();
+
+        assert!(!header.get_aa());
+        assert_eq!(header.get_rcode(), Rcode::NOERROR);
+        assert!(response.get_answer().is_empty());
+        assert_eq!(response.get_authority(), vec![ns_rr("sub.example.com", "ns.sub.example.com")]);
+        assert_eq!(response.get_additional(), vec![a_rr("ns.sub.example.com", [192, 0, 2, 6])]);
+    }
+
+    #[test]
+    fn answer_refused_out_of_zones() {
+        let server = Server::new(vec![example_zone()]);
+
+        let response = server.answer(&query("www.example.org", Rrtype::A));
+
+        assert_eq!(response.get_header().get_rcode(), Rcode::REFUSED);
+        assert!(response.get_answer().is_empty());
+    }
+
+    #[test]
+    fn answer_not_implemented_opcode() {
+        let server = Server::new(vec![example_zone()]);
+
+        let mut status_query = query("www.example.com", Rrtype::A);
+        let mut header = status_query.get_header();
+        header.set_op_code(2);
+        status_query.set_header(header);
+
+        let response = server.answer(&status_query);
+
+        assert_eq!(response.get_header().get_rcode(), Rcode::NOTIMP);
+        assert_eq!(response.get_header().get_id(), 1234);
+    }
+
+    #[test]
+    fn handle_query_format_error() {
+        let server = Server::new(vec![example_zone()]);
+
+        let bytes = [0, 7, 1, 0, 0, 1, 0, 0, 0, 0, 0, 0, 200, 1];
+        let response = server.handle_query(&bytes, true).unwrap();
+        let header = Header::from_bytes(&response[0..12]);
+
+        assert_eq!(header.get_rcode(), Rcode::FORMERR);
+        assert_eq!(header.get_id(), 7);
+        assert!(header.get_qr());
+        assert!(server.handle_query(&[0, 7, 1], true).is_none());
+    }
+
+    #[test]
+    fn handle_query_ignores_responses() {
+        let server = Server::new(vec![example_zone()]);
+
+        let mut response = query("www.example.com", Rrtype::A);
+        let mut header = response.get_header();
+        header.set_qr(true);
+        response.set_header(header);
+
+        assert!(server.handle_query(&response.to_bytes(), true).is_none());
+    }
+
+    #[test]
+    fn handle_query_truncates_udp() {
+        let mut zone = example_zone();
+        for i in 0..40 {
+            let text = format!("record number {} with some text to fill the response", i);
+            zone.add_rr(rr("txt.example.com", Rdata::TXT(TxtRdata::new(vec![text])), Rrtype::TXT))
+                .unwrap();
+        }
+        let server = Server::new(vec![zone]);
+        let query_bytes = query("txt.example.com", Rrtype::TXT).to_bytes();
+
+        let udp_bytes = server.handle_query(&query_bytes, true).unwrap();
+        let udp_response = DnsMessage::from_bytes(&udp_bytes).unwrap();
+        assert!(udp_bytes.len() <= 512);
+        assert!(udp_response.get_header().get_tc());
+        assert!(udp_response.get_answer().is_empty());
+
+        let tcp_response = DnsMessage::from_bytes(&server.handle_query(&query_bytes, false).unwrap()).unwrap();
+        assert!(!tcp_response.get_header().get_tc());
+        assert_eq!(tcp_response.get_answer().len(), 40);
+    }
+
+    #[test]
+    fn truncate_answer_over_limit() {
+        let mut response = query("txt.example.com", Rrtype::TXT);
+        let answer: Vec<ResourceRecord> = (0..40)
+            .map(|i| {
+                let text = format!("record number {} with some text to fill the response", i);
+                rr("txt.example.com", Rdata::TXT(TxtRdata::new(vec![text])), Rrtype::TXT)
+            })
+            .collect();
+        response.set_answer(answer);
+        response.update_header_counters();
+
+        let truncated = DnsMessage::from_bytes(&truncate(response, 512)).unwrap();
+
+        assert!(truncated.get_header().get_tc());
+        assert!(truncated.get_answer().is_empty());
+        assert_eq!(truncated.get_header().get_ancount(), 0);
+    }
+
+    #[test]
+    fn truncate_optional_additional() {
+        let mut response = query("www.example.com", Rrtype::A);
+        response.set_answer(vec![a_rr("www.example.com", [192, 0, 2, 3])]);
+        response.set_additional((0..40).map(|i| a_rr("mail.example.com", [192, 0, 2, i])).collect());
+        response.update_header_counters();
+
+        let truncated = DnsMessage::from_bytes(&truncate(response, 512)).unwrap();
+
+        assert!(!truncated.get_header().get_tc());
+        assert_eq!(truncated.get_answer().len(), 1);
+        assert!(truncated.get_additional().is_empty());
+    }
+
+    #[test]
+    fn truncate_in_domain_glue() {
+        let mut response = query("www.sub.example.com", Rrtype::A);
+        response.set_authority(vec![ns_rr("sub.example.com", "ns1.sub.example.com")]);
+        response.set_additional((0..40).map(|i| a_rr("ns1.sub.example.com", [192, 0, 2, i])).collect());
+        response.update_header_counters();
+
+        let truncated = DnsMessage::from_bytes(&truncate(response, 512)).unwrap();
+
+        assert!(truncated.get_header().get_tc());
+        assert_eq!(truncated.get_authority().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn serve_udp_and_tcp() {
+        let server = Server::new(vec![example_zone()]);
+
+        let udp_socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let udp_addr = udp_socket.local_addr().unwrap();
+        let tcp_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let tcp_addr = tcp_listener.local_addr().unwrap();
+
+        let udp_server = server.clone();
+        tokio::spawn(async move { udp_server.serve_udp(udp_socket).await });
+        tokio::spawn(async move { server.serve_tcp(tcp_listener).await });
+
+        let query_bytes = query("www.example.com", Rrtype::A).to_bytes();
+
+        // UDP
+        let client_socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        client_socket.send_to(&query_bytes, udp_addr).await.unwrap();
+        let mut buffer = vec![0; 512];
+        let (n, _) = client_socket.recv_from(&mut buffer).await.unwrap();
+        let udp_response = DnsMessage::from_bytes(&buffer[..n]).unwrap();
+        assert_eq!(udp_response.get_answer(), vec![a_rr("www.example.com", [192, 0, 2, 3])]);
+
+        // TCP, two queries on the same connection
+        let mut stream = TcpStream::connect(tcp_addr).await.unwrap();
+        for _ in 0..2 {
+            let length = (query_bytes.len() as u16).to_be_bytes();
+            stream.write_all(&[&length, query_bytes.as_slice()].concat()).await.unwrap();
+
+            let mut length = [0; 2];
+            stream.read_exact(&mut length).await.unwrap();
+            let mut response_bytes = vec![0; u16::from_be_bytes(length) as usize];
+            stream.read_exact(&mut response_bytes).await.unwrap();
+
+            let tcp_response = DnsMessage::from_bytes(&response_bytes).unwrap();
+            assert_eq!(tcp_response.get_answer(), vec![a_rr("www.example.com", [192, 0, 2, 3])]);
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use std::fmt;
+use std::fmt::Debug;
+
+#[derive(thiserror::Error)]
+#[non_exhaustive]
+/// Common error types to handle errors from the Server.
+///
+/// Errors answering a query are not returned as a `ServerError`, they are
+/// sent to the client as a response with the corresponding RCODE. These
+/// errors come from the listeners of the server.
+pub enum ServerError {
+    /// An error io connection.
+    ///
+    /// This error occurs when a socket can not be bound or when reading
+    /// from or writing to a socket fails.
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// An error with a message to display.
+    #[error("{0}")]
+    Message(&'static str),
+}
+
+impl Debug for ServerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use self::ServerError::*;
+        match self {
+            Io(err) => write!(f, "io error: {}", err),
+            Message(err) => write!(f, "{}", err),
+        }
+    }
+}
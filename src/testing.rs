@@ -0,0 +1,262 @@
+pub mod mock_connection;
+pub mod stub_server;
+
+use crate::domain_name::DomainName;
+use crate::message::rcode::Rcode;
+use crate::message::resource_record::ResourceRecord;
+use crate::message::rrtype::Rrtype;
+use crate::message::DnsMessage;
+use std::collections::HashMap;
+use tokio::time::Duration;
+
+/// What a scripted upstream does when it receives a query.
+///
+/// Used by `MockConnection` and `StubServer` to reproduce the behaviour of
+/// real name servers, including the failures a resolver has to survive,
+/// without network access.
+#[derive(Clone, Debug, PartialEq)]
+pub enum MockResponse {
+    /// Answers with the message, after copying into it the ID, opcode, RD
+    /// bit and question of the query.
+    Message(DnsMessage),
+    /// Answers like `Message` but with the TC bit set and without records,
+    /// as a server does when the response does not fit in a datagram.
+    Truncated(DnsMessage),
+    /// Answers with these bytes, that do not need to be a DNS message.
+    Malformed(Vec<u8>),
+    /// Does not answer.
+    Drop,
+    /// Waits before doing what the inner response says.
+    Delay(Duration, Box<MockResponse>),
+}
+
+impl MockResponse {
+    /// Creates a response with the given records in the answer section.
+    pub fn answer(answer: Vec<ResourceRecord>) -> Self {
+        let mut message = DnsMessage::new();
+        message.set_answer(answer);
+        message.update_header_counters();
+
+        MockResponse::Message(message)
+    }
+
+    /// Creates a response without records and with the given RCODE.
+    pub fn rcode(rcode: Rcode) -> Self {
+        let mut message = DnsMessage::new();
+        let mut header = message.get_header();
+        header.set_rcode(rcode);
+        message.set_header(header);
+
+        MockResponse::Message(message)
+    }
+
+    /// Creates a response that waits `delay` before doing what `self` says.
+    pub fn delayed(self, delay: Duration) -> Self {
+        MockResponse::Delay(delay, Box::new(self))
+    }
+
+    /// Waits the delays of the response and returns the bytes to send back
+    /// for `query`, or `None` if the query is dropped.
+    pub async fn reply_to(&self, query: &DnsMessage) -> Option<Vec<u8>> {
+        let mut response = self;
+
+        loop {
+            match response {
+                MockResponse::Message(message) => {
+                    return Some(response_for(query, message).to_bytes());
+                }
+                MockResponse::Truncated(message) => {
+                    let mut truncated = response_for(query, message);
+                    truncated.set_answer(Vec::new());
+                    truncated.set_authority(Vec::new());
+                    truncated.set_additional(Vec::new());
+                    truncated.update_header_counters();
+                    let mut header = truncated.get_header();
+                    header.set_tc(true);
+                    truncated.set_header(header);
+
+                    return Some(truncated.to_bytes());
+                }
+                MockResponse::Malformed(bytes) => return Some(bytes.clone()),
+                MockResponse::Drop => return None,
+                MockResponse::Delay(delay, inner) => {
+                    tokio::time::sleep(*delay).await;
+                    response = inner;
+                }
+            }
+        }
+    }
+}
+
+/// Returns `message` turned into the response to `query`.
+fn response_for(query: &DnsMessage, message: &DnsMessage) -> DnsMessage {
+    let query_header = query.get_header();
+    let mut response = message.clone();
+    response.set_question(query.get_question());
+
+    let mut header = response.get_header();
+    header.set_id(query_header.get_id());
+    header.set_qr(true);
+    header.set_op_code(query_header.get_op_code());
+    header.set_rd(query_header.get_rd());
+    header.set_qdcount(query_header.get_qdcount());
+    response.set_header(header);
+
+    response
+}
+
+/// Scripted responses of an upstream, by question.
+///
+/// Each question has a list of responses that are used in order by its
+/// successive queries, the last one being repeated once the others are
+/// used. This way a test can make the first query time out and the retry
+/// succeed. Questions without responses get the default response, which
+/// is REFUSED unless it is changed.
+#[derive(Clone, Debug)]
+pub struct ResponseTable {
+    /// Responses left for each question, identified by its name and type.
+    responses: HashMap<(DomainName, Rrtype), Vec<MockResponse>>,
+    /// Response to the questions that are not in the table.
+    default_response: MockResponse,
+}
+
+impl ResponseTable {
+    /// Creates an empty table that refuses every query.
+    pub fn new() -> Self {
+        ResponseTable {
+            responses: HashMap::new(),
+            default_response: MockResponse::rcode(Rcode::REFUSED),
+        }
+    }
+
+    /// Adds `response` after the ones already scripted for the question.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut table = ResponseTable::new();
+    /// let qname = DomainName::new_from_str("example.com");
+    /// table.add_response(qname.clone(), Rrtype::A, MockResponse::Drop);
+    /// table.add_response(qname, Rrtype::A, MockResponse::answer(vec![]));
+    /// ```
+    pub fn add_response(&mut self, qname: DomainName, qtype: Rrtype, response: MockResponse) {
+        self.responses.entry((qname, qtype)).or_default().push(response);
+    }
+
+    /// Takes the response for the next query with the question of `query`.
+    pub fn next_response(&mut self, query: &DnsMessage) -> MockResponse {
+        let question = query.get_question();
+        let key = (question.get_qname(), question.get_rrtype());
+
+        match self.responses.get_mut(&key) {
+            Some(responses) if responses.len() > 1 => responses.remove(0),
+            Some(responses) if !responses.is_empty() => responses[0].clone(),
+            _ => self.default_response.clone(),
+        }
+    }
+
+    /// Gets the response to the questions that are not in the table.
+    pub fn get_default_response(&self) -> MockResponse {
+        self.default_response.clone()
+    }
+
+    /// Sets the response to the questions that are not in the table.
+    pub fn set_default_response(&mut self, default_response: MockResponse) {
+        self.default_response = default_response;
+    }
+}
+
+impl Default for ResponseTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod testing_test {
+    use super::*;
+    use crate::message::rclass::Rclass;
+    use crate::message::rdata::a_rdata::ARdata;
+    use crate::message::rdata::Rdata;
+    use std::net::{IpAddr, Ipv4Addr};
+
+    fn query(name: &str, id: u16) -> DnsMessage {
+        DnsMessage::new_query_message(DomainName::new_from_str(name), Rrtype::A, Rclass::IN, 0, true, id)
+    }
+
+    fn a_rr(name: &str) -> ResourceRecord {
+        let mut rr = ResourceRecord::new(Rdata::A(ARdata::new_from_addr(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1)))));
+        rr.set_name(DomainName::new_from_str(name));
+        rr.set_type_code(Rrtype::A);
+        rr
+    }
+
+    #[tokio::test]
+    async fn reply_to_message() {
+        let query = query("example.com", 7);
+        let bytes = MockResponse::answer(vec![a_rr("example.com")]).reply_to(&query).await.unwrap();
+        let response = DnsMessage::from_bytes(&bytes).unwrap();
+
+        assert_eq!(response.get_query_id(), 7);
+        assert!(response.get_header().get_qr());
+        assert!(response.get_header().get_rd());
+        assert_eq!(response.get_question().get_qname(), DomainName::new_from_str("example.com"));
+        assert_eq!(response.get_answer().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn reply_to_truncated() {
+        let query = query("example.com", 7);
+        let mut message = DnsMessage::new();
+        message.set_answer(vec![a_rr("example.com")]);
+        message.update_header_counters();
+        let bytes = MockResponse::Truncated(message).reply_to(&query).await.unwrap();
+        let response = DnsMessage::from_bytes(&bytes).unwrap();
+
+        assert!(response.get_header().get_tc());
+        assert_eq!(response.get_header().get_ancount(), 0);
+        assert!(response.get_answer().is_empty());
+    }
+
+    #[tokio::test]
+    async fn reply_to_drop_and_malformed() {
+        let query = query("example.com", 7);
+
+        assert_eq!(MockResponse::Drop.reply_to(&query).await, None);
+        assert_eq!(MockResponse::Malformed(vec![1, 2, 3]).reply_to(&query).await, Some(vec![1, 2, 3]));
+    }
+
+    #[tokio::test]
+    async fn reply_to_delay() {
+        let query = query("example.com", 7);
+        let response = MockResponse::rcode(Rcode::NXDOMAIN).delayed(Duration::from_millis(50));
+        let start = tokio::time::Instant::now();
+        let bytes = response.reply_to(&query).await.unwrap();
+
+        assert!(start.elapsed() >= Duration::from_millis(50));
+        assert_eq!(DnsMessage::from_bytes(&bytes).unwrap().get_header().get_rcode(), Rcode::NXDOMAIN);
+    }
+
+    #[test]
+    fn next_response_in_order() {
+        let mut table = ResponseTable::new();
+        let qname = DomainName::new_from_str("example.com");
+        table.add_response(qname.clone(), Rrtype::A, MockResponse::Drop);
+        table.add_response(qname, Rrtype::A, MockResponse::rcode(Rcode::SERVFAIL));
+
+        assert_eq!(table.next_response(&query("example.com", 1)), MockResponse::Drop);
+        assert_eq!(table.next_response(&query("EXAMPLE.com", 2)), MockResponse::rcode(Rcode::SERVFAIL));
+        assert_eq!(table.next_response(&query("example.com", 3)), MockResponse::rcode(Rcode::SERVFAIL));
+    }
+
+    #[test]
+    fn next_response_default() {
+        let mut table = ResponseTable::new();
+
+        assert_eq!(table.next_response(&query("example.org", 1)), MockResponse::rcode(Rcode::REFUSED));
+
+        table.set_default_response(MockResponse::Drop);
+
+        assert_eq!(table.get_default_response(), MockResponse::Drop);
+        assert_eq!(table.next_response(&query("example.org", 1)), MockResponse::Drop);
+    }
+}
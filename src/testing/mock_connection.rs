@@ -0,0 +1,244 @@
+use crate::client::dns_transport::DnsTransport;
+use crate::client::client_error::ClientError;
+use crate::domain_name::DomainName;
+use crate::message::rrtype::Rrtype;
+use crate::message::DnsMessage;
+use crate::testing::{MockResponse, ResponseTable};
+use async_trait::async_trait;
+use std::io::{Error as IoError, ErrorKind};
+use std::net::{IpAddr, Ipv4Addr};
+use std::sync::{Arc, Mutex};
+use tokio::time::Duration;
+
+/// Connection that answers queries from a `ResponseTable` in memory.
+///
+/// It is a `DnsTransport`, so it can replace the connections of a
+/// `ServerInfo` with `set_transport` to test the resolver without network.
+/// The clones of a `MockConnection` share the table and the list of
+/// queries received, so a test can keep one to add responses and check how
+/// many queries the resolver sent.
+///
+/// A dropped query, or one delayed past the timeout, fails with a
+/// `TimedOut` error after the timeout, like a UDP connection. Malformed
+/// bytes are returned as they are, so that the parsing of the resolver
+/// sees them.
+#[derive(Clone, Debug)]
+pub struct MockConnection {
+    /// Address of the server this connection pretends to reach.
+    server_addr: IpAddr,
+    /// Time to wait for a response.
+    timeout: Duration,
+    /// Responses to send, shared between clones.
+    table: Arc<Mutex<ResponseTable>>,
+    /// Queries received, shared between clones.
+    queries: Arc<Mutex<Vec<DnsMessage>>>,
+}
+
+#[async_trait]
+impl DnsTransport for MockConnection {
+    /// Records the query and answers it with the next scripted response.
+    /// The scripted responses are returned as they are, whatever the case
+    /// of their question.
+    async fn send_query(&self, dns_query: &DnsMessage, timeout: Duration, _use_0x20: bool) -> Result<Vec<u8>, ClientError> {
+        let response = {
+            self.queries.lock().unwrap().push(dns_query.clone());
+            self.table.lock().unwrap().next_response(dns_query)
+        };
+
+        match tokio::time::timeout(timeout, response.reply_to(dns_query)).await {
+            Ok(Some(bytes)) => Ok(bytes),
+            Ok(None) => {
+                tokio::time::sleep(timeout).await;
+                Err(ClientError::Io(IoError::new(ErrorKind::TimedOut, "Error: timeout")))
+            }
+            Err(_) => Err(ClientError::Io(IoError::new(ErrorKind::TimedOut, "Error: timeout"))),
+        }
+    }
+
+    fn get_ip(&self) -> IpAddr {
+        self.server_addr
+    }
+}
+
+impl MockConnection {
+    /// Creates a new `MockConnection` with an empty table.
+    pub fn new_default(server_addr: IpAddr, timeout: Duration) -> Self {
+        MockConnection::new_with_table(server_addr, timeout, ResponseTable::new())
+    }
+
+    /// Sends the query with the timeout of the connection.
+    pub async fn send(self, dns_query: DnsMessage) -> Result<Vec<u8>, ClientError> {
+        self.send_query(&dns_query, self.timeout, false).await
+    }
+
+    /// Creates a new `MockConnection` that answers from `table`.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut table = ResponseTable::new();
+    /// table.add_response(DomainName::new_from_str("example.com"), Rrtype::A, MockResponse::Drop);
+    /// let conn = MockConnection::new_with_table(IpAddr::V4(Ipv4Addr::LOCALHOST), Duration::from_secs(1), table);
+    /// ```
+    pub fn new_with_table(server_addr: IpAddr, timeout: Duration, table: ResponseTable) -> Self {
+        MockConnection {
+            server_addr,
+            timeout,
+            table: Arc::new(Mutex::new(table)),
+            queries: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Adds `response` after the ones already scripted for the question.
+    pub fn add_response(&self, qname: DomainName, qtype: Rrtype, response: MockResponse) {
+        self.table.lock().unwrap().add_response(qname, qtype, response);
+    }
+
+    /// Returns the queries received so far, in order.
+    pub fn get_queries(&self) -> Vec<DnsMessage> {
+        self.queries.lock().unwrap().clone()
+    }
+
+    /// Returns the number of queries received so far.
+    pub fn get_query_count(&self) -> usize {
+        self.queries.lock().unwrap().len()
+    }
+
+    pub fn get_timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+}
+
+impl Default for MockConnection {
+    fn default() -> Self {
+        MockConnection::new_default(IpAddr::V4(Ipv4Addr::LOCALHOST), Duration::from_secs(1))
+    }
+}
+
+#[cfg(test)]
+mod mock_connection_test {
+    use super::*;
+    use crate::async_resolver::config::ResolverConfig;
+    use crate::async_resolver::lookup::LookupStrategy;
+    use crate::async_resolver::AsyncResolver;
+    use crate::client::client_connection::ConnectionProtocol;
+    use crate::message::rclass::Rclass;
+    use crate::message::rcode::Rcode;
+    use crate::message::rdata::a_rdata::ARdata;
+    use crate::message::rdata::Rdata;
+    use crate::message::resource_record::ResourceRecord;
+    use crate::message;
+    use std::net::SocketAddr;
+
+    fn a_rr(name: &str) -> ResourceRecord {
+        let mut rr = ResourceRecord::new(Rdata::A(ARdata::new_from_addr(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1)))));
+        rr.set_name(DomainName::new_from_str(name));
+        rr.set_type_code(Rrtype::A);
+        rr.set_ttl(300);
+        rr
+    }
+
+    fn config_with(udp: &MockConnection, tcp: &MockConnection) -> ResolverConfig {
+        let mut config = ResolverConfig::default();
+        config.remove_servers();
+        config.add_server_with_transport(SocketAddr::new(udp.get_ip(), 53), ConnectionProtocol::UDP, Arc::new(udp.clone()));
+        let mut server_info = config.get_name_servers().remove(0);
+        server_info.set_transport(ConnectionProtocol::TCP, Arc::new(tcp.clone()));
+        config.set_name_servers(vec![server_info]);
+        config.set_protocol(ConnectionProtocol::UDP);
+        config.set_use_0x20(false);
+        config
+    }
+
+    #[tokio::test]
+    async fn send_scripted_responses() {
+        let conn = MockConnection::new_default(IpAddr::V4(Ipv4Addr::LOCALHOST), Duration::from_millis(100));
+        let qname = DomainName::new_from_str("example.com");
+        conn.add_response(qname.clone(), Rrtype::A, MockResponse::Drop);
+        conn.add_response(qname.clone(), Rrtype::A, MockResponse::answer(vec![a_rr("example.com")]));
+        let query = DnsMessage::new_query_message(qname, Rrtype::A, Rclass::IN, 0, true, 12);
+
+        let dropped = conn.clone().send(query.clone()).await;
+        let answered = conn.clone().send(query).await.unwrap();
+
+        assert!(matches!(dropped, Err(ClientError::Io(ref e)) if e.kind() == ErrorKind::TimedOut));
+        assert_eq!(DnsMessage::from_bytes(&answered).unwrap().get_answer().len(), 1);
+        assert_eq!(conn.get_query_count(), 2);
+        assert_eq!(conn.get_queries()[1].get_query_id(), 12);
+    }
+
+    #[tokio::test]
+    async fn send_delay_past_timeout() {
+        let conn = MockConnection::new_default(IpAddr::V4(Ipv4Addr::LOCALHOST), Duration::from_millis(50));
+        conn.add_response(
+            DomainName::new_from_str("example.com"),
+            Rrtype::A,
+            MockResponse::rcode(Rcode::NOERROR).delayed(Duration::from_secs(5)),
+        );
+        let query = DnsMessage::new_query_message(DomainName::new_from_str("example.com"), Rrtype::A, Rclass::IN, 0, true, 1);
+
+        assert!(conn.send(query).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn lookup_strategy_falls_back_to_tcp() {
+        let udp = MockConnection::new_default(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 53)), Duration::from_millis(100));
+        let tcp = MockConnection::new_default(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 53)), Duration::from_millis(100));
+        let qname = DomainName::new_from_str("example.com");
+        udp.add_response(qname.clone(), Rrtype::A, MockResponse::rcode(Rcode::SERVFAIL));
+        tcp.add_response(qname.clone(), Rrtype::A, MockResponse::answer(vec![a_rr("example.com")]));
+
+        let mut lookup_strategy = LookupStrategy::new(
+            message::create_recursive_query(qname, Rrtype::A, Rclass::IN),
+            config_with(&udp, &tcp),
+        );
+        let response = lookup_strategy.run().await.unwrap();
+
+        assert_eq!(response.to_dns_msg().get_answer().len(), 1);
+        assert_eq!(udp.get_query_count(), 1);
+        assert_eq!(tcp.get_query_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn lookup_strategy_retries_malformed() {
+        let udp = MockConnection::new_default(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 53)), Duration::from_millis(100));
+        let tcp = MockConnection::new_default(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 53)), Duration::from_millis(100));
+        let qname = DomainName::new_from_str("example.com");
+        udp.add_response(qname.clone(), Rrtype::A, MockResponse::Malformed(vec![0xde, 0xad]));
+        udp.add_response(qname.clone(), Rrtype::A, MockResponse::answer(vec![a_rr("example.com")]));
+        tcp.add_response(qname.clone(), Rrtype::A, MockResponse::Drop);
+
+        let mut config = config_with(&udp, &tcp);
+        config.set_retransmission_loop_attempts(2);
+        config.set_max_retry_interval_seconds(1);
+        let mut lookup_strategy = LookupStrategy::new(
+            message::create_recursive_query(qname, Rrtype::A, Rclass::IN),
+            config,
+        );
+        let response = lookup_strategy.run().await.unwrap();
+
+        assert_eq!(response.to_dns_msg().get_answer().len(), 1);
+        assert_eq!(udp.get_query_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn resolver_answers_from_cache() {
+        let udp = MockConnection::new_default(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 53)), Duration::from_millis(100));
+        let tcp = MockConnection::new_default(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 53)), Duration::from_millis(100));
+        udp.add_response(DomainName::new_from_str("example.com"), Rrtype::A, MockResponse::answer(vec![a_rr("example.com")]));
+
+        let mut config = config_with(&udp, &tcp);
+        config.set_cache_enabled(true);
+        let mut resolver = AsyncResolver::new(config);
+
+        let first = resolver.lookup("example.com", "UDP", "A", "IN").await.unwrap();
+        let second = resolver.lookup("example.com", "UDP", "A", "IN").await.unwrap();
+
+        assert_eq!(first.to_dns_msg().get_answer().len(), 1);
+        assert_eq!(second.to_dns_msg().get_answer().len(), 1);
+        assert_eq!(udp.get_query_count(), 1);
+    }
+}
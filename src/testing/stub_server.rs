@@ -0,0 +1,283 @@
+use crate::message::DnsMessage;
+use crate::server::Server;
+use crate::testing::ResponseTable;
+use crate::zone::Zone;
+use std::io::{ErrorKind, Result as IoResult};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream, UdpSocket};
+use tokio::task::JoinHandle;
+
+/// Number of times a free port for both UDP and TCP is looked for.
+const BIND_ATTEMPTS: usize = 16;
+
+/// How a `StubServer` builds its responses.
+#[derive(Clone, Debug)]
+enum Responder {
+    /// Answers from zones, like an authoritative server.
+    Zones(Server),
+    /// Answers with the scripted responses of a table.
+    Table(Arc<Mutex<ResponseTable>>),
+}
+
+/// Name server listening on a local port, for tests that need real UDP
+/// and TCP sockets.
+///
+/// It listens on UDP and TCP on the same port of `127.0.0.1`, chosen by
+/// the system, and answers either from zones, with the same logic as
+/// `Server`, or from a `ResponseTable`. In the latter case the UDP and TCP
+/// queries for the same question take their responses from the same list.
+///
+/// The server stops when it is dropped.
+#[derive(Debug)]
+pub struct StubServer {
+    /// Address the server listens on, for UDP and TCP.
+    addr: SocketAddr,
+    /// Queries received, over UDP and TCP.
+    queries: Arc<Mutex<Vec<DnsMessage>>>,
+    /// Tasks of the UDP and TCP listeners.
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl StubServer {
+    /// Starts a server that answers from the given zones.
+    ///
+    /// Responses over UDP are truncated to the payload size of the client,
+    /// so large answers make the client retry over TCP.
+    ///
+    /// # Examples
+    /// ```
+    /// let zone = Zone::new(DomainName::new_from_str("example.com"), Rclass::IN);
+    /// let server = StubServer::from_zones(vec![zone]).await.unwrap();
+    /// let mut conn = ClientUDPConnection::new_default(server.get_addr().ip(), timeout);
+    /// conn.set_socket_addr(server.get_addr());
+    /// ```
+    pub async fn from_zones(zones: Vec<Zone>) -> IoResult<Self> {
+        StubServer::start(Responder::Zones(Server::new(zones))).await
+    }
+
+    /// Starts a server that answers with the responses of `table`.
+    pub async fn from_table(table: ResponseTable) -> IoResult<Self> {
+        StubServer::start(Responder::Table(Arc::new(Mutex::new(table)))).await
+    }
+
+    /// Binds the sockets and spawns the tasks that serve them.
+    async fn start(responder: Responder) -> IoResult<Self> {
+        let (udp_socket, tcp_listener) = bind_local().await?;
+        let addr = udp_socket.local_addr()?;
+        let queries = Arc::new(Mutex::new(Vec::new()));
+
+        let udp_task = tokio::spawn(serve_udp(udp_socket, responder.clone(), queries.clone()));
+        let tcp_task = tokio::spawn(serve_tcp(tcp_listener, responder, queries.clone()));
+
+        Ok(StubServer {
+            addr,
+            queries,
+            tasks: vec![udp_task, tcp_task],
+        })
+    }
+
+    /// Returns the address the server listens on.
+    pub fn get_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Returns the queries received so far that could be parsed, in order.
+    pub fn get_queries(&self) -> Vec<DnsMessage> {
+        self.queries.lock().unwrap().clone()
+    }
+
+    /// Returns the number of queries received so far that could be parsed.
+    pub fn get_query_count(&self) -> usize {
+        self.queries.lock().unwrap().len()
+    }
+}
+
+impl Drop for StubServer {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+impl Responder {
+    /// Returns the bytes to send back for the query in `bytes`, or `None`
+    /// if it is not answered.
+    async fn respond(&self, bytes: &[u8], udp: bool, queries: &Mutex<Vec<DnsMessage>>) -> Option<Vec<u8>> {
+        let query = DnsMessage::from_bytes(bytes).ok();
+        if let Some(query) = &query {
+            queries.lock().unwrap().push(query.clone());
+        }
+
+        match self {
+            Responder::Zones(server) => server.handle_query(bytes, udp),
+            Responder::Table(table) => {
+                let query = query?;
+                let response = table.lock().unwrap().next_response(&query);
+                response.reply_to(&query).await
+            }
+        }
+    }
+}
+
+/// Binds a UDP socket and a TCP listener to the same free port of
+/// `127.0.0.1`.
+async fn bind_local() -> IoResult<(UdpSocket, TcpListener)> {
+    let mut last_error = None;
+
+    for _ in 0..BIND_ATTEMPTS {
+        let udp_socket = UdpSocket::bind(SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 0)).await?;
+        match TcpListener::bind(udp_socket.local_addr()?).await {
+            Ok(tcp_listener) => return Ok((udp_socket, tcp_listener)),
+            Err(e) if e.kind() == ErrorKind::AddrInUse => last_error = Some(e),
+            Err(e) => return Err(e),
+        }
+    }
+
+    Err(last_error.unwrap_or_else(|| ErrorKind::AddrInUse.into()))
+}
+
+/// Answers the UDP queries, each one in its own task so that a delayed
+/// response does not hold back the others.
+async fn serve_udp(socket: UdpSocket, responder: Responder, queries: Arc<Mutex<Vec<DnsMessage>>>) {
+    let socket = Arc::new(socket);
+    let mut buffer = vec![0; u16::MAX as usize];
+
+    while let Ok((n, src_addr)) = socket.recv_from(&mut buffer).await {
+        let bytes = buffer[..n].to_vec();
+        let socket = socket.clone();
+        let responder = responder.clone();
+        let queries = queries.clone();
+
+        tokio::spawn(async move {
+            if let Some(response) = responder.respond(&bytes, true, &queries).await {
+                let _ = socket.send_to(&response, src_addr).await;
+            }
+        });
+    }
+}
+
+/// Accepts TCP connections and answers their queries.
+async fn serve_tcp(listener: TcpListener, responder: Responder, queries: Arc<Mutex<Vec<DnsMessage>>>) {
+    while let Ok((stream, _)) = listener.accept().await {
+        let responder = responder.clone();
+        let queries = queries.clone();
+
+        tokio::spawn(async move {
+            let _ = handle_tcp_connection(stream, responder, queries).await;
+        });
+    }
+}
+
+/// Answers the queries of a TCP connection, each one preceded by its
+/// length in two bytes, until the client closes it.
+async fn handle_tcp_connection(mut stream: TcpStream, responder: Responder, queries: Arc<Mutex<Vec<DnsMessage>>>) -> IoResult<()> {
+    loop {
+        let mut msg_size: [u8; 2] = [0; 2];
+        stream.read_exact(&mut msg_size).await?;
+
+        let mut query = vec![0; u16::from_be_bytes(msg_size) as usize];
+        stream.read_exact(&mut query).await?;
+
+        if let Some(response) = responder.respond(&query, false, &queries).await {
+            let response_size = (response.len() as u16).to_be_bytes();
+            stream.write_all(&[&response_size, response.as_slice()].concat()).await?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod stub_server_test {
+    use super::*;
+    use crate::async_resolver::config::ResolverConfig;
+    use crate::async_resolver::lookup::LookupStrategy;
+    use crate::async_resolver::server_info::ServerInfo;
+    use crate::client::client_connection::ConnectionProtocol;
+    use crate::client::tcp_connection::ClientTCPConnection;
+    use crate::client::udp_connection::ClientUDPConnection;
+    use crate::domain_name::DomainName;
+    use crate::message;
+    use crate::message::rclass::Rclass;
+    use crate::message::rcode::Rcode;
+    use crate::message::rdata::a_rdata::ARdata;
+    use crate::message::rdata::Rdata;
+    use crate::message::resource_record::ResourceRecord;
+    use crate::message::rrtype::Rrtype;
+    use crate::testing::MockResponse;
+    use tokio::time::Duration;
+
+    fn a_rr(name: &str, address: [u8; 4]) -> ResourceRecord {
+        let mut rr = ResourceRecord::new(Rdata::A(ARdata::new_from_addr(IpAddr::from(address))));
+        rr.set_name(DomainName::new_from_str(name));
+        rr.set_type_code(Rrtype::A);
+        rr.set_ttl(3600);
+        rr
+    }
+
+    fn query(name: &str) -> DnsMessage {
+        DnsMessage::new_query_message(DomainName::new_from_str(name), Rrtype::A, Rclass::IN, 0, false, 99)
+    }
+
+    #[tokio::test]
+    async fn from_zones_udp_and_tcp() {
+        let zone = Zone::from_rrs(
+            DomainName::new_from_str("example.com"),
+            Rclass::IN,
+            vec![a_rr("www.example.com", [192, 0, 2, 10])],
+        ).unwrap();
+        let server = StubServer::from_zones(vec![zone]).await.unwrap();
+        let timeout = Duration::from_secs(2);
+
+        let mut udp = ClientUDPConnection::new_default(server.get_addr().ip(), timeout);
+        udp.set_socket_addr(server.get_addr());
+        let mut tcp = ClientTCPConnection::new_default(server.get_addr().ip(), timeout);
+        tcp.set_socket_addr(server.get_addr());
+
+        let udp_response = DnsMessage::from_bytes(&udp.send(query("www.example.com")).await.unwrap()).unwrap();
+        let tcp_response = DnsMessage::from_bytes(&tcp.send(query("www.example.com")).await.unwrap()).unwrap();
+
+        assert_eq!(udp_response.get_answer(), vec![a_rr("www.example.com", [192, 0, 2, 10])]);
+        assert_eq!(tcp_response.get_answer(), udp_response.get_answer());
+        assert_eq!(server.get_query_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn from_table_truncated() {
+        let mut table = ResponseTable::new();
+        let mut message = DnsMessage::new();
+        message.set_answer(vec![a_rr("example.com", [192, 0, 2, 1])]);
+        message.update_header_counters();
+        table.add_response(DomainName::new_from_str("example.com"), Rrtype::A, MockResponse::Truncated(message));
+        let server = StubServer::from_table(table).await.unwrap();
+
+        let mut udp = ClientUDPConnection::new_default(server.get_addr().ip(), Duration::from_secs(2));
+        udp.set_socket_addr(server.get_addr());
+        let response = DnsMessage::from_bytes(&udp.send(query("example.com")).await.unwrap()).unwrap();
+
+        assert!(response.get_header().get_tc());
+        assert!(response.get_answer().is_empty());
+    }
+
+    #[tokio::test]
+    async fn lookup_strategy_against_stub_server() {
+        let mut table = ResponseTable::new();
+        let qname = DomainName::new_from_str("example.com");
+        table.add_response(qname.clone(), Rrtype::A, MockResponse::rcode(Rcode::SERVFAIL));
+        table.add_response(qname.clone(), Rrtype::A, MockResponse::answer(vec![a_rr("example.com", [192, 0, 2, 1])]));
+        let server = StubServer::from_table(table).await.unwrap();
+
+        let mut config = ResolverConfig::default();
+        config.set_name_servers(vec![ServerInfo::new_from_socket_addr(server.get_addr(), Duration::from_secs(2))]);
+        config.set_protocol(ConnectionProtocol::UDP);
+        let mut lookup_strategy = LookupStrategy::new(
+            message::create_recursive_query(qname, Rrtype::A, Rclass::IN),
+            config,
+        );
+        let response = lookup_strategy.run().await.unwrap();
+
+        assert_eq!(response.to_dns_msg().get_answer().len(), 1);
+        assert_eq!(server.get_query_count(), 2);
+    }
+}
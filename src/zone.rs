@@ -0,0 +1,287 @@
+pub mod master_file;
+pub mod zone_error;
+
+use crate::domain_name::DomainName;
+use crate::message::rclass::Rclass;
+use crate::message::rdata::Rdata;
+use crate::message::resource_record::ResourceRecord;
+use crate::message::rrtype::Rrtype;
+use crate::zone::master_file::{write_master_file, MasterFileParser};
+use crate::zone::zone_error::ZoneError;
+use std::collections::HashMap;
+use std::path::Path;
+
+#[derive(Clone, PartialEq, Debug)]
+/// Struct that represents the authoritative data of a zone.
+///
+/// [RFC 1034](https://datatracker.ietf.org/doc/html/rfc1034#section-4.2)
+///
+/// A zone holds every resource record whose owner name is at or below the
+/// `origin`, down to the delegation points where the data of a child zone
+/// starts. The records are grouped by owner name to answer queries without
+/// going through the whole zone.
+pub struct Zone {
+    /// Domain name of the top node of the zone.
+    origin: DomainName,
+    /// Class of the data of the zone.
+    rclass: Rclass,
+    /// Resource records of the zone grouped by owner name.
+    records: HashMap<DomainName, Vec<ResourceRecord>>,
+}
+
+impl Zone {
+    /// Creates a new empty `Zone`.
+    ///
+    /// # Examples
+    /// ```
+    /// let zone = Zone::new(DomainName::new_from_str("example.com"), Rclass::IN);
+    ///
+    /// assert_eq!(zone.get_origin(), DomainName::new_from_str("example.com"));
+    /// assert!(zone.get_soa().is_none());
+    /// ```
+    pub fn new(origin: DomainName, rclass: Rclass) -> Self {
+        Zone {
+            origin,
+            rclass,
+            records: HashMap::new(),
+        }
+    }
+
+    /// Creates a new `Zone` with the given resource records.
+    pub fn from_rrs(origin: DomainName, rclass: Rclass, rrs: Vec<ResourceRecord>) -> Result<Self, &'static str> {
+        let mut zone = Zone::new(origin, rclass);
+
+        for rr in rrs {
+            zone.add_rr(rr)?;
+        }
+
+        Ok(zone)
+    }
+
+    /// Creates a new `Zone` from a master file.
+    ///
+    /// [RFC 1035](https://datatracker.ietf.org/doc/html/rfc1035#section-5)
+    ///
+    /// Relative names in the file are completed with `origin`. Records out
+    /// of the zone are reported as errors at their position in the file.
+    ///
+    /// # Examples
+    /// ```
+    /// let zone = Zone::from_master_file(
+    ///     "1034-scenario-6.1-edu.txt",
+    ///     DomainName::new_from_str("EDU."),
+    ///     Rclass::IN,
+    /// ).unwrap();
+    ///
+    /// assert!(zone.get_soa().is_some());
+    /// ```
+    pub fn from_master_file<P: AsRef<Path>>(
+        path: P,
+        origin: DomainName,
+        rclass: Rclass,
+    ) -> Result<Self, ZoneError> {
+        let mut zone = Zone::new(origin.clone(), rclass);
+        MasterFileParser::new(origin, rclass).parse_file_with(path.as_ref(), &mut |rr| zone.add_rr(rr))?;
+
+        Ok(zone)
+    }
+
+    /// Adds a resource record to the zone.
+    ///
+    /// The owner name of the record must be at or below the origin of the
+    /// zone, and the record must have the class of the zone.
+    pub fn add_rr(&mut self, rr: ResourceRecord) -> Result<(), &'static str> {
+        if !rr.get_name().is_subdomain_of(&self.origin) {
+            return Err("The resource record is not in the zone");
+        }
+        if rr.get_rclass() != self.rclass {
+            return Err("The class of the resource record is not the class of the zone");
+        }
+
+        let rrs = self.records.entry(rr.get_name()).or_default();
+
+        // RFC 2181: duplicated records are suppressed
+        if !rrs.iter().any(|other| {
+            other.get_rtype() == rr.get_rtype() && other.get_rdata() == rr.get_rdata()
+        }) {
+            rrs.push(rr);
+        }
+
+        Ok(())
+    }
+
+    /// Returns the records of the given type owned by `name`. With
+    /// `Rrtype::ANY` every record of the node is returned.
+    pub fn get_rrs(&self, name: &DomainName, rrtype: Rrtype) -> Vec<ResourceRecord> {
+        match self.records.get(name) {
+            Some(rrs) => rrs
+                .iter()
+                .filter(|rr| rrtype == Rrtype::ANY || rr.get_rtype() == rrtype)
+                .cloned()
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Returns `true` if `name` is a node of the zone.
+    ///
+    /// A name also exists when it has no records but some name below it
+    /// does (an empty non-terminal).
+    pub fn contains_name(&self, name: &DomainName) -> bool {
+        if self.records.contains_key(name) {
+            return true;
+        }
+
+        self.records.keys().any(|owner| owner.is_subdomain_of(name))
+            && name.is_subdomain_of(&self.origin)
+    }
+
+    /// Returns `true` if `name` is a delegation point of the zone, that is
+    /// a node below the origin with NS records.
+    pub fn is_delegation(&self, name: &DomainName) -> bool {
+        *name != self.origin && !self.get_rrs(name, Rrtype::NS).is_empty()
+    }
+
+    /// Returns the SOA record of the zone.
+    pub fn get_soa(&self) -> Option<ResourceRecord> {
+        self.get_rrs(&self.origin, Rrtype::SOA).into_iter().next()
+    }
+
+    /// Returns the minimum TTL of the zone, taken from the MINIMUM field of
+    /// the SOA record.
+    pub fn get_minimum_ttl(&self) -> Option<u32> {
+        match self.get_soa()?.get_rdata() {
+            Rdata::SOA(soa) => Some(soa.get_minimum()),
+            _ => None,
+        }
+    }
+
+    /// Returns every resource record of the zone.
+    pub fn get_all_rrs(&self) -> Vec<ResourceRecord> {
+        self.records.values().flatten().cloned().collect()
+    }
+
+    /// Returns the zone as a master file, with names relative to the origin
+    /// and the records sorted in canonical order.
+    pub fn to_master_file(&self) -> String {
+        write_master_file(&self.origin, &self.get_all_rrs())
+    }
+}
+
+// Getters
+impl Zone {
+    pub fn get_origin(&self) -> DomainName {
+        self.origin.clone()
+    }
+
+    pub fn get_rclass(&self) -> Rclass {
+        self.rclass
+    }
+}
+
+#[cfg(test)]
+mod zone_test {
+    use super::Zone;
+    use crate::domain_name::DomainName;
+    use crate::message::rclass::Rclass;
+    use crate::message::rdata::a_rdata::ARdata;
+    use crate::message::rdata::ns_rdata::NsRdata;
+    use crate::message::rdata::soa_rdata::SoaRdata;
+    use crate::message::rdata::Rdata;
+    use crate::message::resource_record::ResourceRecord;
+    use crate::message::rrtype::Rrtype;
+    use std::net::IpAddr;
+
+    fn a_rr(name: &str, address: [u8; 4]) -> ResourceRecord {
+        let mut rr = ResourceRecord::new(Rdata::A(ARdata::new_from_addr(IpAddr::from(address))));
+        rr.set_name(DomainName::new_from_str(name));
+        rr
+    }
+
+    #[test]
+    fn add_rr_out_of_zone() {
+        let mut zone = Zone::new(DomainName::new_from_str("example.com"), Rclass::IN);
+
+        assert!(zone.add_rr(a_rr("www.example.com", [10, 0, 0, 1])).is_ok());
+        assert!(zone.add_rr(a_rr("www.example.org", [10, 0, 0, 1])).is_err());
+    }
+
+    #[test]
+    fn add_rr_duplicated() {
+        let mut zone = Zone::new(DomainName::new_from_str("example.com"), Rclass::IN);
+
+        zone.add_rr(a_rr("www.example.com", [10, 0, 0, 1])).unwrap();
+        zone.add_rr(a_rr("WWW.example.com", [10, 0, 0, 1])).unwrap();
+        zone.add_rr(a_rr("www.example.com", [10, 0, 0, 2])).unwrap();
+
+        assert_eq!(zone.get_rrs(&DomainName::new_from_str("www.example.com"), Rrtype::A).len(), 2);
+    }
+
+    #[test]
+    fn contains_empty_non_terminal() {
+        let zone = Zone::from_rrs(
+            DomainName::new_from_str("example.com"),
+            Rclass::IN,
+            vec![a_rr("a.b.example.com", [10, 0, 0, 1])],
+        )
+        .unwrap();
+
+        assert!(zone.contains_name(&DomainName::new_from_str("b.example.com")));
+        assert!(zone.contains_name(&DomainName::new_from_str("a.b.example.com")));
+        assert!(!zone.contains_name(&DomainName::new_from_str("c.example.com")));
+        assert!(!zone.contains_name(&DomainName::new_from_str("com")));
+    }
+
+    #[test]
+    fn from_master_file() {
+        let zone = Zone::from_master_file(
+            "1034-scenario-6.1-edu.txt",
+            DomainName::new_from_str("EDU."),
+            Rclass::IN,
+        )
+        .unwrap();
+
+        assert_eq!(zone.get_minimum_ttl(), Some(86400));
+        assert!(zone.is_delegation(&DomainName::new_from_str("ISI.EDU.")));
+        assert_eq!(zone.get_rrs(&DomainName::new_from_str("VAXA.ISI.EDU"), Rrtype::A).len(), 2);
+
+        let written = zone.to_master_file();
+        assert!(written.starts_with("$ORIGIN EDU.\n@\t86400\tIN\tSOA\tSRI-NIC.ARPA. HOSTMASTER.SRI-NIC.ARPA. "));
+        assert!(written.contains("\nVAXA.ISI\t172800\tIN\tA\t10.2.0.27\n"));
+
+        let error = Zone::from_master_file(
+            "1034-scenario-6.1-root.txt",
+            DomainName::new_from_str("EDU."),
+            Rclass::IN,
+        )
+        .unwrap_err();
+        assert_eq!((error.get_line(), error.get_column()), (1, 1));
+    }
+
+    #[test]
+    fn delegation_and_soa() {
+        let origin = DomainName::new_from_str("example.com");
+        let mut zone = Zone::new(origin.clone(), Rclass::IN);
+
+        let mut soa_rdata = SoaRdata::new();
+        soa_rdata.set_minimum(300);
+        let mut soa_rr = ResourceRecord::new(Rdata::SOA(soa_rdata));
+        soa_rr.set_name(origin.clone());
+        zone.add_rr(soa_rr).unwrap();
+
+        let mut ns_rdata = NsRdata::new();
+        ns_rdata.set_nsdname(DomainName::new_from_str("ns.sub.example.com"));
+        let mut ns_rr = ResourceRecord::new(Rdata::NS(ns_rdata.clone()));
+        ns_rr.set_name(DomainName::new_from_str("sub.example.com"));
+        zone.add_rr(ns_rr).unwrap();
+
+        let mut origin_ns_rr = ResourceRecord::new(Rdata::NS(ns_rdata));
+        origin_ns_rr.set_name(origin.clone());
+        zone.add_rr(origin_ns_rr).unwrap();
+
+        assert!(zone.is_delegation(&DomainName::new_from_str("sub.example.com")));
+        assert!(!zone.is_delegation(&origin));
+        assert!(zone.get_soa().is_some());
+        assert_eq!(zone.get_minimum_ttl(), Some(300));
+    }
+}
@@ -0,0 +1,807 @@
+use crate::domain_name::DomainName;
+use crate::message::rclass::Rclass;
+use crate::message::rdata::presentation::{
+    format_name, format_rclass, format_rrtype, parse_rclass, parse_rrtype, parse_ttl,
+};
+use crate::message::rdata::Rdata;
+use crate::message::resource_record::{ResourceRecord, ToBytes};
+use crate::message::rrtype::Rrtype;
+use crate::zone::zone_error::ZoneError;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Maximum number of nested `$INCLUDE` directives, to stop include loops.
+const MAX_INCLUDE_DEPTH: usize = 16;
+
+/// Word of a master file.
+#[derive(Clone, Debug)]
+struct Token {
+    /// Text of the word, without the quotes. Escape sequences are kept.
+    text: String,
+    /// `true` if the word was written between quotes.
+    quoted: bool,
+    line: usize,
+    column: usize,
+}
+
+/// Logical line of a master file: a directive or a resource record, which
+/// may span several lines inside parentheses.
+#[derive(Clone, Debug)]
+struct Entry {
+    tokens: Vec<Token>,
+    /// `true` if the line starts with a blank, so the owner is the last one.
+    blank_owner: bool,
+}
+
+/// Parser of master files.
+///
+/// [RFC 1035](https://datatracker.ietf.org/doc/html/rfc1035#section-5)
+///
+/// ```text
+/// $ORIGIN <domain-name> [<comment>]
+/// $INCLUDE <file-name> [<domain-name>] [<comment>]
+/// $TTL <ttl> [<comment>]
+/// <domain-name> <rr> [<comment>]
+/// <blank> <rr> [<comment>]
+///
+/// <rr> = [<TTL>] [<class>] <type> <RDATA>
+///      | [<class>] [<TTL>] <type> <RDATA>
+/// ```
+///
+/// Records without owner, TTL or class take them from the previous record.
+/// When there is no `$TTL` nor a previous TTL, the SOA record takes its
+/// MINIMUM field as TTL.
+#[derive(Clone, Debug)]
+pub struct MasterFileParser {
+    /// Name appended to relative names.
+    origin: DomainName,
+    /// Class of the records that do not set one.
+    rclass: Rclass,
+    /// TTL set by the last `$TTL` directive.
+    default_ttl: Option<u32>,
+    last_owner: Option<DomainName>,
+    last_ttl: Option<u32>,
+    last_class: Option<Rclass>,
+    /// File being parsed, used to find included files and in the errors.
+    file: Option<PathBuf>,
+    include_depth: usize,
+}
+
+impl MasterFileParser {
+    /// Creates a new `MasterFileParser`.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut parser = MasterFileParser::new(DomainName::new_from_str("example.com."), Rclass::IN);
+    /// let rrs = parser.parse_str("www 3600 A 192.0.2.1").unwrap();
+    ///
+    /// assert_eq!(rrs[0].get_name().to_string(), "www.example.com.");
+    /// ```
+    pub fn new(origin: DomainName, rclass: Rclass) -> Self {
+        MasterFileParser {
+            origin,
+            rclass,
+            default_ttl: None,
+            last_owner: None,
+            last_ttl: None,
+            last_class: None,
+            file: None,
+            include_depth: 0,
+        }
+    }
+
+    /// Parses the records of a master file given as a string. Relative
+    /// paths in `$INCLUDE` are taken from the current directory.
+    pub fn parse_str(&mut self, text: &str) -> Result<Vec<ResourceRecord>, ZoneError> {
+        let mut rrs = Vec::new();
+        self.parse_text(text, &mut |rr| {
+            rrs.push(rr);
+            Ok(())
+        })?;
+
+        Ok(rrs)
+    }
+
+    /// Parses the records of a master file. Relative paths in `$INCLUDE`
+    /// are taken from the directory of the file.
+    pub fn parse_file<P: AsRef<Path>>(&mut self, path: P) -> Result<Vec<ResourceRecord>, ZoneError> {
+        let mut rrs = Vec::new();
+        self.parse_file_with(path.as_ref(), &mut |rr| {
+            rrs.push(rr);
+            Ok(())
+        })?;
+
+        Ok(rrs)
+    }
+
+    /// Parses a master file, giving each record to `add_rr`. An error of
+    /// `add_rr` is returned with the position of the record.
+    pub(crate) fn parse_file_with(
+        &mut self,
+        path: &Path,
+        add_rr: &mut dyn FnMut(ResourceRecord) -> Result<(), &'static str>,
+    ) -> Result<(), ZoneError> {
+        let text = fs::read_to_string(path).map_err(|err| {
+            ZoneError::new(Some(path.to_path_buf()), 0, 0, &format!("can not read file: {}", err))
+        })?;
+        self.file = Some(path.to_path_buf());
+
+        self.parse_text(&text, add_rr)
+    }
+
+    /// Parses a master file given as a string, giving each record to
+    /// `add_rr`.
+    pub(crate) fn parse_text(
+        &mut self,
+        text: &str,
+        add_rr: &mut dyn FnMut(ResourceRecord) -> Result<(), &'static str>,
+    ) -> Result<(), ZoneError> {
+        let entries = self.split_entries(text)?;
+
+        for entry in entries {
+            let first = &entry.tokens[0];
+
+            if !entry.blank_owner && !first.quoted && first.text.starts_with('$') {
+                self.parse_directive(&entry, add_rr)?;
+                continue;
+            }
+
+            let rr = self.parse_rr(&entry)?;
+            add_rr(rr).map_err(|err| self.error(first, err))?;
+        }
+
+        Ok(())
+    }
+
+    /// Splits the text in entries, removing comments and joining the lines
+    /// inside parentheses.
+    fn split_entries(&self, text: &str) -> Result<Vec<Entry>, ZoneError> {
+        let mut entries: Vec<Entry> = Vec::new();
+        let mut current = Entry {
+            tokens: Vec::new(),
+            blank_owner: false,
+        };
+        // Position of the open parenthesis, if any
+        let mut open_paren: Option<(usize, usize)> = None;
+
+        for (line_index, line) in text.lines().enumerate() {
+            let line_number = line_index + 1;
+            let chars: Vec<char> = line.chars().collect();
+
+            if open_paren.is_none() {
+                if !current.tokens.is_empty() {
+                    entries.push(current);
+                }
+                current = Entry {
+                    tokens: Vec::new(),
+                    blank_owner: matches!(chars.first(), Some(' ') | Some('\t')),
+                };
+            }
+
+            let mut i = 0;
+            while i < chars.len() {
+                let column = i + 1;
+
+                match chars[i] {
+                    ' ' | '\t' | '\r' => i += 1,
+                    ';' => break,
+                    '(' => {
+                        if open_paren.is_some() {
+                            return Err(self.error_at(line_number, column, "Error: nested parentheses."));
+                        }
+                        open_paren = Some((line_number, column));
+                        i += 1;
+                    }
+                    ')' => {
+                        if open_paren.is_none() {
+                            return Err(self.error_at(line_number, column, "Error: unbalanced parenthesis."));
+                        }
+                        open_paren = None;
+                        i += 1;
+                    }
+                    '"' => {
+                        let mut text = String::new();
+                        i += 1;
+                        loop {
+                            match chars.get(i) {
+                                None => {
+                                    return Err(self.error_at(
+                                        line_number,
+                                        column,
+                                        "Error: unterminated quoted string.",
+                                    ));
+                                }
+                                Some('"') => {
+                                    i += 1;
+                                    break;
+                                }
+                                Some('\\') if i + 1 < chars.len() => {
+                                    text.push('\\');
+                                    text.push(chars[i + 1]);
+                                    i += 2;
+                                }
+                                Some(c) => {
+                                    text.push(*c);
+                                    i += 1;
+                                }
+                            }
+                        }
+                        current.tokens.push(Token {
+                            text,
+                            quoted: true,
+                            line: line_number,
+                            column,
+                        });
+                    }
+                    _ => {
+                        let mut text = String::new();
+                        while i < chars.len() {
+                            match chars[i] {
+                                ' ' | '\t' | '\r' | ';' | '(' | ')' | '"' => break,
+                                '\\' if i + 1 < chars.len() => {
+                                    text.push('\\');
+                                    text.push(chars[i + 1]);
+                                    i += 2;
+                                }
+                                c => {
+                                    text.push(c);
+                                    i += 1;
+                                }
+                            }
+                        }
+                        current.tokens.push(Token {
+                            text,
+                            quoted: false,
+                            line: line_number,
+                            column,
+                        });
+                    }
+                }
+            }
+        }
+
+        if let Some((line, column)) = open_paren {
+            return Err(self.error_at(line, column, "Error: unbalanced parenthesis."));
+        }
+        if !current.tokens.is_empty() {
+            entries.push(current);
+        }
+
+        Ok(entries)
+    }
+
+    /// Applies a `$ORIGIN`, `$TTL` or `$INCLUDE` directive.
+    fn parse_directive(
+        &mut self,
+        entry: &Entry,
+        add_rr: &mut dyn FnMut(ResourceRecord) -> Result<(), &'static str>,
+    ) -> Result<(), ZoneError> {
+        let directive = &entry.tokens[0];
+        let argument = entry
+            .tokens
+            .get(1)
+            .ok_or_else(|| self.error(directive, "Error: missing argument of the directive."))?;
+
+        match directive.text.to_ascii_uppercase().as_str() {
+            "$ORIGIN" => {
+                self.check_arguments(entry, 2)?;
+                self.origin = DomainName::from_text(&argument.text, &self.origin)
+                    .map_err(|err| self.error(argument, err))?;
+            }
+            "$TTL" => {
+                self.check_arguments(entry, 2)?;
+                let ttl = parse_ttl(&argument.text).map_err(|err| self.error(argument, err))?;
+                self.default_ttl = Some(ttl);
+            }
+            "$INCLUDE" => {
+                self.check_arguments(entry, 3)?;
+                if self.include_depth >= MAX_INCLUDE_DEPTH {
+                    return Err(self.error(directive, "Error: too many nested $INCLUDE directives."));
+                }
+
+                let mut path = PathBuf::from(&argument.text);
+                if path.is_relative() {
+                    if let Some(dir) = self.file.as_ref().and_then(|file| file.parent()) {
+                        path = dir.join(path);
+                    }
+                }
+
+                // The included file does not change the origin of this one
+                let mut include_parser = self.clone();
+                include_parser.include_depth += 1;
+                if let Some(origin) = entry.tokens.get(2) {
+                    include_parser.origin = DomainName::from_text(&origin.text, &self.origin)
+                        .map_err(|err| self.error(origin, err))?;
+                }
+
+                if !path.is_file() {
+                    return Err(self.error(argument, "Error: the included file does not exist."));
+                }
+                include_parser.parse_file_with(&path, add_rr)?;
+            }
+            _ => return Err(self.error(directive, "Error: unknown directive.")),
+        }
+
+        Ok(())
+    }
+
+    /// Parses a resource record, taking the missing owner, TTL and class
+    /// from the previous records.
+    fn parse_rr(&mut self, entry: &Entry) -> Result<ResourceRecord, ZoneError> {
+        let tokens = &entry.tokens;
+        let mut index = 0;
+
+        let owner = if entry.blank_owner {
+            self.last_owner
+                .clone()
+                .ok_or_else(|| self.error(&tokens[0], "Error: the first record has no owner name."))?
+        } else {
+            index += 1;
+            DomainName::from_text(&tokens[0].text, &self.origin).map_err(|err| self.error(&tokens[0], err))?
+        };
+
+        // The TTL and the class can be written in any order
+        let mut ttl: Option<u32> = None;
+        let mut rclass: Option<Rclass> = None;
+        while let Some(token) = tokens.get(index) {
+            if token.quoted {
+                break;
+            }
+            if ttl.is_none() && token.text.starts_with(|c: char| c.is_ascii_digit()) {
+                ttl = Some(parse_ttl(&token.text).map_err(|err| self.error(token, err))?);
+            } else if rclass.is_none() && parse_rclass(&token.text).is_ok() {
+                rclass = parse_rclass(&token.text).ok();
+            } else {
+                break;
+            }
+            index += 1;
+        }
+
+        let type_token = tokens
+            .get(index)
+            .ok_or_else(|| self.error(&tokens[tokens.len() - 1], "Error: missing type of the record."))?;
+        let rrtype = parse_rrtype(&type_token.text).map_err(|err| self.error(type_token, err))?;
+
+        let rclass = rclass.or(self.last_class).unwrap_or(self.rclass);
+
+        let fields: Vec<&str> = tokens[index + 1..].iter().map(|token| token.text.as_str()).collect();
+        let rdata_token = tokens.get(index + 1).unwrap_or(type_token);
+        let rdata =
+            Rdata::from_text(rrtype, rclass, &fields, &self.origin).map_err(|err| self.error(rdata_token, err))?;
+
+        let ttl = match ttl.or(self.default_ttl).or(self.last_ttl) {
+            Some(ttl) => ttl,
+            None => match &rdata {
+                Rdata::SOA(soa) => soa.get_minimum(),
+                _ => return Err(self.error(type_token, "Error: the record has no TTL and there is no $TTL.")),
+            },
+        };
+
+        let mut rr = ResourceRecord::new(rdata);
+        rr.set_name(owner.clone());
+        rr.set_type_code(rrtype);
+        rr.set_rclass(rclass);
+        rr.set_ttl(ttl);
+        rr.set_rdlength(rr.get_rdata().to_bytes().len() as u16);
+
+        self.last_owner = Some(owner);
+        self.last_ttl = Some(ttl);
+        self.last_class = Some(rclass);
+
+        Ok(rr)
+    }
+
+    /// Checks that a directive has at most `max` words.
+    fn check_arguments(&self, entry: &Entry, max: usize) -> Result<(), ZoneError> {
+        match entry.tokens.get(max) {
+            Some(token) => Err(self.error(token, "Error: too many arguments in the directive.")),
+            None => Ok(()),
+        }
+    }
+
+    fn error(&self, token: &Token, message: &str) -> ZoneError {
+        self.error_at(token.line, token.column, message)
+    }
+
+    fn error_at(&self, line: usize, column: usize, message: &str) -> ZoneError {
+        ZoneError::new(self.file.clone(), line, column, message)
+    }
+}
+
+/// Writes resource records as a master file.
+///
+/// The file starts with a `$ORIGIN` directive and the owner names below
+/// `origin` are written relative to it. The SOA record of the origin goes
+/// first and the rest of the records are sorted by owner name in canonical
+/// order, then by type, so the records of an RRset are written together.
+///
+/// # Examples
+/// ```
+/// let origin = DomainName::new_from_str("example.com.");
+/// let rrs = MasterFileParser::new(origin.clone(), Rclass::IN)
+///     .parse_str("www 3600 A 192.0.2.1")
+///     .unwrap();
+///
+/// assert_eq!(
+///     write_master_file(&origin, &rrs),
+///     "$ORIGIN example.com.\nwww\t3600\tIN\tA\t192.0.2.1\n"
+/// );
+/// ```
+pub fn write_master_file(origin: &DomainName, rrs: &[ResourceRecord]) -> String {
+    let mut sorted_rrs: Vec<&ResourceRecord> = rrs.iter().collect();
+    sorted_rrs.sort_by(|a, b| {
+        let a_is_soa = a.get_rtype() == Rrtype::SOA && a.get_name() == *origin;
+        let b_is_soa = b.get_rtype() == Rrtype::SOA && b.get_name() == *origin;
+
+        b_is_soa
+            .cmp(&a_is_soa)
+            .then_with(|| a.get_name().cmp(&b.get_name()))
+            .then_with(|| u16::from(a.get_rtype()).cmp(&u16::from(b.get_rtype())))
+            .then_with(|| a.get_rdata().to_bytes().cmp(&b.get_rdata().to_bytes()))
+    });
+
+    let mut text = format!("$ORIGIN {}\n", format_name(origin));
+    for rr in sorted_rrs {
+        text.push_str(&format!(
+            "{}\t{}\t{}\t{}\t{}\n",
+            relative_name(&rr.get_name(), origin),
+            rr.get_ttl(),
+            format_rclass(rr.get_rclass()),
+            format_rrtype(rr.get_rtype()),
+            rr.get_rdata().to_text()
+        ));
+    }
+
+    text
+}
+
+/// Writes `name` relative to `origin` when it is below it, or as an
+/// absolute name otherwise.
+fn relative_name(name: &DomainName, origin: &DomainName) -> String {
+    if name == origin {
+        return String::from("@");
+    }
+    if !name.is_subdomain_of(origin) {
+        return format_name(name);
+    }
+
+    let labels = name.get_labels();
+    let relative_labels = labels[..labels.len() - origin.label_count()].to_vec();
+    let relative = DomainName::from_labels(relative_labels)
+        .unwrap_or_default()
+        .to_string();
+
+    // A relative name must not be read as `@` or as a directive
+    if relative == "@" || relative.starts_with('$') {
+        return format!("\\{}", relative);
+    }
+
+    relative
+}
+
+#[cfg(test)]
+mod master_file_test {
+    use super::{write_master_file, MasterFileParser};
+    use crate::domain_name::DomainName;
+    use crate::message::rclass::Rclass;
+    use crate::message::rdata::Rdata;
+    use crate::message::rrtype::Rrtype;
+    use std::fs;
+    use std::net::IpAddr;
+
+    fn parser(origin: &str) -> MasterFileParser {
+        MasterFileParser::new(DomainName::new_from_str(origin), Rclass::IN)
+    }
+
+    #[test]
+    fn parse_relative_and_absolute_names() {
+        let rrs = parser("example.com.")
+            .parse_str("$TTL 1h\nwww A 192.0.2.1\nwww.example.org. A 192.0.2.2\n@ NS ns1\n")
+            .unwrap();
+
+        assert_eq!(rrs.len(), 3);
+        assert_eq!(rrs[0].get_name().to_string(), "www.example.com.");
+        assert_eq!(rrs[0].get_ttl(), 3600);
+        assert_eq!(rrs[1].get_name().to_string(), "www.example.org.");
+        assert_eq!(rrs[2].get_name().to_string(), "example.com.");
+        match rrs[2].get_rdata() {
+            Rdata::NS(ns) => assert_eq!(ns.get_nsdname().to_string(), "ns1.example.com."),
+            _ => panic!("expected an NS record"),
+        }
+    }
+
+    #[test]
+    fn parse_inherits_owner_ttl_and_class() {
+        let text = "host 300 IN A 192.0.2.1\n    A 192.0.2.2\n    IN 600 TXT \"text\"\nother A 192.0.2.3\n";
+        let rrs = parser("example.com.").parse_str(text).unwrap();
+
+        assert_eq!(rrs[1].get_name().to_string(), "host.example.com.");
+        assert_eq!(rrs[1].get_ttl(), 300);
+        assert_eq!(rrs[1].get_rclass(), Rclass::IN);
+        assert_eq!(rrs[2].get_name().to_string(), "host.example.com.");
+        assert_eq!(rrs[2].get_ttl(), 600);
+        assert_eq!(rrs[3].get_ttl(), 600);
+    }
+
+    #[test]
+    fn parse_multiline_soa_with_comments() {
+        let text = "@ IN SOA ns1 admin\\.name ( ; comment\n  2024010101 ; serial\n  1h 15m 1w\n  300 )\n";
+        let rrs = parser("example.com.").parse_str(text).unwrap();
+
+        assert_eq!(rrs.len(), 1);
+        // Without $TTL the SOA takes its minimum
+        assert_eq!(rrs[0].get_ttl(), 300);
+        match rrs[0].get_rdata() {
+            Rdata::SOA(soa) => {
+                assert_eq!(soa.get_mname().to_string(), "ns1.example.com.");
+                assert_eq!(soa.get_rname().label_count(), 3);
+                assert_eq!(soa.get_serial(), 2024010101);
+                assert_eq!(soa.get_refresh(), 3600);
+                assert_eq!(soa.get_retry(), 900);
+                assert_eq!(soa.get_expire(), 604800);
+            }
+            _ => panic!("expected a SOA record"),
+        }
+    }
+
+    #[test]
+    fn parse_quoted_strings() {
+        let text = "txt 60 TXT \"hello world\" \"semi;colon\" \"say \\\"hi\\\"\" plain\n";
+        let rrs = parser("example.com.").parse_str(text).unwrap();
+
+        match rrs[0].get_rdata() {
+            Rdata::TXT(txt) => assert_eq!(
+                txt.get_text(),
+                vec!["hello world", "semi;colon", "say \"hi\"", "plain"]
+            ),
+            _ => panic!("expected a TXT record"),
+        }
+    }
+
+    #[test]
+    fn parse_origin_directive() {
+        let text = "$TTL 60\n$ORIGIN sub\nwww A 192.0.2.1\n$ORIGIN example.org.\nwww A 192.0.2.2\n";
+        let rrs = parser("example.com.").parse_str(text).unwrap();
+
+        assert_eq!(rrs[0].get_name().to_string(), "www.sub.example.com.");
+        assert_eq!(rrs[1].get_name().to_string(), "www.example.org.");
+    }
+
+    #[test]
+    fn parse_generic_rdata() {
+        let text = "$TTL 60\na TYPE1 \\# 4 C0000201\nb TYPE65280 \\# 3 ( 0102\n 03 )\nc CLASS1 TYPE65281 \\# 0\n";
+        let rrs = parser("example.com.").parse_str(text).unwrap();
+
+        match rrs[0].get_rdata() {
+            Rdata::A(a) => assert_eq!(a.get_address(), IpAddr::from([192, 0, 2, 1])),
+            _ => panic!("expected an A record"),
+        }
+        assert_eq!(rrs[1].get_rtype(), Rrtype::UNKNOWN(65280));
+        assert_eq!(
+            rrs[1].get_rdata(),
+            Rdata::Unknown {
+                rrtype: Rrtype::UNKNOWN(65280),
+                data: vec![1, 2, 3]
+            }
+        );
+        assert_eq!(rrs[2].get_rdlength(), 0);
+    }
+
+    #[test]
+    fn parse_every_rdata_type() {
+        let text = "$TTL 3600
+@          SOA     ns1 admin 1 7200 3600 1209600 300
+@          NS      ns1
+@          MX      10 mail
+ns1        A       192.0.2.1
+ns1        AAAA    2001:db8::1
+alias      CNAME   ns1
+1.2        PTR     ns1
+host       HINFO   \"PDP-11/70\" UNIX
+txt        TXT     \"v=spf1 -all\"
+_sip._tcp  SRV     10 60 5060 sip
+@          DNSKEY  257 3 8 AwEAAQ==
+@          DS      12345 8 2 ( 49FD46E6C4B45C55D4AC
+                                69CBD3CD34AC1AFE51DE )
+@          RRSIG   A 8 2 3600 20240201000000 20240101000000 12345 @ AQID
+@          NSEC    ns1 A NS SOA RRSIG NSEC DNSKEY
+@          NSEC3   1 0 10 AABBCCDD 2T7B4G4VSA5SMI47K61MV5BV1A22BOJR A RRSIG
+@          NSEC3PARAM 1 0 10 -
+key        TSIG    hmac-sha256. 1700000000 300 2 AQI= 4660 0 0
+";
+        let rrs = parser("example.com.").parse_str(text).unwrap();
+        let rrtypes: Vec<Rrtype> = rrs.iter().map(|rr| rr.get_rtype()).collect();
+
+        assert_eq!(
+            rrtypes,
+            vec![
+                Rrtype::SOA,
+                Rrtype::NS,
+                Rrtype::MX,
+                Rrtype::A,
+                Rrtype::AAAA,
+                Rrtype::CNAME,
+                Rrtype::PTR,
+                Rrtype::HINFO,
+                Rrtype::TXT,
+                Rrtype::SRV,
+                Rrtype::DNSKEY,
+                Rrtype::DS,
+                Rrtype::RRSIG,
+                Rrtype::NSEC,
+                Rrtype::NSEC3,
+                Rrtype::NSEC3PARAM,
+                Rrtype::TSIG,
+            ]
+        );
+
+        match rrs[9].get_rdata() {
+            Rdata::SRV(srv) => {
+                assert_eq!(srv.get_port(), 5060);
+                assert_eq!(srv.get_target().to_string(), "sip.example.com.");
+            }
+            _ => panic!("expected a SRV record"),
+        }
+        match rrs[11].get_rdata() {
+            Rdata::DS(ds) => assert_eq!(ds.get_digest().len(), 20),
+            _ => panic!("expected a DS record"),
+        }
+        match rrs[12].get_rdata() {
+            Rdata::RRSIG(rrsig) => {
+                assert_eq!(rrsig.get_type_covered(), Rrtype::A);
+                assert_eq!(rrsig.get_signature_inception(), 1704067200);
+                assert_eq!(rrsig.get_signer_name().to_string(), "example.com.");
+                assert_eq!(rrsig.get_signature(), vec![1, 2, 3]);
+            }
+            _ => panic!("expected a RRSIG record"),
+        }
+        match rrs[14].get_rdata() {
+            Rdata::NSEC3(nsec3) => {
+                assert_eq!(nsec3.get_salt_length(), 4);
+                assert_eq!(nsec3.get_hash_length(), 20);
+            }
+            _ => panic!("expected a NSEC3 record"),
+        }
+    }
+
+    #[test]
+    fn parse_chaos_address() {
+        let rrs = MasterFileParser::new(DomainName::new_from_str("mit.edu."), Rclass::CH)
+            .parse_str("host 60 CH A server 2420\n")
+            .unwrap();
+
+        match rrs[0].get_rdata() {
+            Rdata::ACH(ach) => assert_eq!(ach.get_ch_address(), 0o2420),
+            _ => panic!("expected a Chaos A record"),
+        }
+    }
+
+    #[test]
+    fn parse_errors_have_position() {
+        let cases = [
+            ("$TTL 60\nwww A 192.0.2.300\n", 2, 7),
+            ("$TTL 60\nwww FOO 1\n", 2, 5),
+            ("www 60 A 192.0.2.1\n  MX ( 10\n", 2, 6),
+            ("www 60 A 192.0.2.1 )\n", 1, 20),
+            ("www 60 TXT \"open\n", 1, 12),
+            ("  A 192.0.2.1\n", 1, 3),
+            ("www A 192.0.2.1\n", 1, 5),
+            ("$FOO bar\n", 1, 1),
+            ("$TTL 60\n\n\n  ; comment\nwww MX 10\n", 5, 8),
+        ];
+
+        for (text, line, column) in cases.iter() {
+            let error = parser("example.com.").parse_str(text).unwrap_err();
+            assert_eq!((error.get_line(), error.get_column()), (*line, *column), "{}", text);
+        }
+    }
+
+    #[test]
+    fn parse_include() {
+        let dir = std::env::temp_dir().join(format!("dns_rust_include_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("hosts.txt"), "www A 192.0.2.1\n").unwrap();
+        fs::write(
+            dir.join("zone.txt"),
+            "$TTL 60\n@ NS ns1\n$INCLUDE hosts.txt sub\nmail A 192.0.2.2\n$INCLUDE missing.txt\n",
+        )
+        .unwrap();
+
+        let error = parser("example.com.").parse_file(dir.join("zone.txt")).unwrap_err();
+        assert_eq!((error.get_line(), error.get_column()), (5, 10));
+        assert_eq!(error.get_file(), Some(dir.join("zone.txt")));
+
+        fs::write(dir.join("zone.txt"), "$TTL 60\n@ NS ns1\n$INCLUDE hosts.txt sub\nmail A 192.0.2.2\n").unwrap();
+        let rrs = parser("example.com.").parse_file(dir.join("zone.txt")).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        let names: Vec<String> = rrs.iter().map(|rr| rr.get_name().to_string()).collect();
+        assert_eq!(names, vec!["example.com.", "www.sub.example.com.", "mail.example.com."]);
+    }
+
+    #[test]
+    fn parse_rfc_1034_scenario_files() {
+        let edu = parser("EDU.").parse_file("1034-scenario-6.1-edu.txt").unwrap();
+        assert_eq!(edu.len(), 25);
+        assert_eq!(edu[0].get_rtype(), Rrtype::SOA);
+        assert_eq!(edu[0].get_ttl(), 86400);
+        assert_eq!(edu[1].get_name().to_string(), "EDU.");
+        assert_eq!(edu[3].get_name().to_string(), "UCI.EDU.");
+        assert_eq!(edu[3].get_ttl(), 172800);
+
+        let root = parser(".").parse_file("1034-scenario-6.1-root.txt").unwrap();
+        assert_eq!(root.len(), 23);
+        assert_eq!(root[root.len() - 1].get_name().to_string(), "C.ISI.EDU.");
+
+        let uchile = parser(".").parse_file("test.txt").unwrap();
+        assert_eq!(uchile[0].get_name().to_string(), "uchile.cl.");
+    }
+
+    #[test]
+    fn write_master_file_sorted_and_relative() {
+        let origin = DomainName::new_from_str("example.com.");
+        let text = "$TTL 60
+www A 192.0.2.2
+www A 192.0.2.1
+b NS ns.example.org.
+@ MX 10 mail
+mail.example.org. A 192.0.2.3
+@ SOA ns1 admin 1 2 3 4 5
+\\@ TXT at
+";
+        let rrs = parser("example.com.").parse_str(text).unwrap();
+
+        assert_eq!(
+            write_master_file(&origin, &rrs),
+            "$ORIGIN example.com.
+@\t60\tIN\tSOA\tns1.example.com. admin.example.com. 1 2 3 4 5
+@\t60\tIN\tMX\t10 mail.example.com.
+\\@\t60\tIN\tTXT\t\"at\"
+b\t60\tIN\tNS\tns.example.org.
+www\t60\tIN\tA\t192.0.2.1
+www\t60\tIN\tA\t192.0.2.2
+mail.example.org.\t60\tIN\tA\t192.0.2.3
+"
+        );
+    }
+
+    #[test]
+    fn write_and_parse_every_rdata_type() {
+        let origin = DomainName::new_from_str("example.com.");
+        let text = "$TTL 3600
+@          SOA     ns1 admin 1 7200 3600 1209600 300
+@          NS      ns1
+@          MX      10 mail
+ns1        A       192.0.2.1
+ns1        AAAA    2001:db8::1
+alias      CNAME   ns1
+1.2        PTR     ns1
+host       HINFO   \"PDP-11/70\" UNIX
+txt        TXT     \"v=spf1 -all\" \"quote \\\" and \\\\\"
+_sip._tcp  SRV     10 60 5060 sip
+@          DNSKEY  257 3 8 AwEAAQ==
+@          DS      12345 8 2 49FD46E6C4B45C55D4AC69CBD3CD34AC1AFE51DE
+@          RRSIG   A 8 2 3600 20240201000000 20240101000000 12345 @ AQID
+@          NSEC    ns1 A NS SOA RRSIG NSEC DNSKEY TYPE1234
+@          NSEC3   1 0 10 - C5H66P35CPJMGQBADDM6QRJFE1ON4SRK A RRSIG
+@          NSEC3PARAM 1 0 10 -
+key        TSIG    hmac-sha256. 1700000000 300 2 AQI= 4660 0 0
+generic    TYPE65280 \\# 2 ABCD
+";
+        let rrs = parser("example.com.").parse_str(text).unwrap();
+
+        let written = write_master_file(&origin, &rrs);
+        let reparsed = parser(".").parse_str(&written).unwrap();
+
+        assert_eq!(reparsed.len(), rrs.len());
+        for rr in rrs.iter() {
+            assert!(reparsed.iter().any(|other| other.get_name() == rr.get_name()
+                && other.get_rtype() == rr.get_rtype()
+                && other.get_ttl() == rr.get_ttl()
+                && other.get_rdata() == rr.get_rdata()), "{}", rr.to_text());
+        }
+        assert_eq!(write_master_file(&origin, &reparsed), written);
+        assert!(written.contains("\tRRSIG\tA 8 2 3600 20240201000000 20240101000000 12345 example.com. AQID\n"));
+        assert!(written.contains("\tNSEC\tns1.example.com. A NS SOA RRSIG NSEC DNSKEY TYPE1234\n"));
+    }
+}
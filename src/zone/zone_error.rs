@@ -0,0 +1,67 @@
+use std::fmt;
+use std::fmt::Debug;
+use std::path::PathBuf;
+
+/// Error found while reading a master file.
+///
+/// It holds the position of the error, so it can be shown to the user in
+/// the same way compilers do: `file:line:column: message`.
+#[derive(Clone, PartialEq)]
+pub struct ZoneError {
+    /// File where the error was found, `None` when parsing a string.
+    file: Option<PathBuf>,
+    /// Line of the error, starting at 1.
+    line: usize,
+    /// Column of the error, starting at 1.
+    column: usize,
+    /// Description of the error.
+    message: String,
+}
+
+impl ZoneError {
+    /// Creates a new `ZoneError` at the given position.
+    pub fn new(file: Option<PathBuf>, line: usize, column: usize, message: &str) -> Self {
+        ZoneError {
+            file,
+            line,
+            column,
+            message: message.to_string(),
+        }
+    }
+}
+
+// Getters
+impl ZoneError {
+    pub fn get_file(&self) -> Option<PathBuf> {
+        self.file.clone()
+    }
+
+    pub fn get_line(&self) -> usize {
+        self.line
+    }
+
+    pub fn get_column(&self) -> usize {
+        self.column
+    }
+
+    pub fn get_message(&self) -> String {
+        self.message.clone()
+    }
+}
+
+impl fmt::Display for ZoneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{}:", file.display())?;
+        }
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl Debug for ZoneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+impl std::error::Error for ZoneError {}
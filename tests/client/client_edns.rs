@@ -1,9 +1,9 @@
 #[cfg(test)]
 mod client_edns {
     use std::net::{IpAddr, Ipv4Addr};
+    use std::sync::Arc;
     use std::time::Duration;
     use dns_rust::client::Client;
-    use dns_rust::client::client_connection::ClientConnection;
     use dns_rust::client::udp_connection::ClientUDPConnection;
     use dns_rust::domain_name::DomainName;
     use dns_rust::edns::opt_option::option_code::OptionCode;
@@ -22,7 +22,7 @@ mod client_edns {
         // client
         let addr = IpAddr::V4(Ipv4Addr::new(8, 8, 8, 8));
         let conn = ClientUDPConnection::new_default(addr, Duration::from_secs(10));
-        let mut client = Client::new(conn);
+        let mut client = Client::new(Arc::new(conn), Duration::from_secs(10));
 
         // message
         let mut dns_query_message =
@@ -83,7 +83,7 @@ mod client_edns {
         // client
         let addr = IpAddr::V4(Ipv4Addr::new(8, 8, 8, 8));
         let conn = ClientUDPConnection::new_default(addr, Duration::from_secs(10));
-        let mut client = Client::new(conn);
+        let mut client = Client::new(Arc::new(conn), Duration::from_secs(10));
 
         // message
         let mut dns_query_message =
@@ -148,7 +148,7 @@ mod client_edns {
         // client
         let addr = IpAddr::V4(Ipv4Addr::new(8, 8, 8, 8));
         let conn = ClientUDPConnection::new_default(addr, Duration::from_secs(10));
-        let mut client = Client::new(conn);
+        let mut client = Client::new(Arc::new(conn), Duration::from_secs(10));
 
         // message
         let mut dns_query_message =
@@ -206,7 +206,7 @@ mod client_edns {
         // client
         let addr = IpAddr::V4(Ipv4Addr::new(74, 82, 42, 42)); // Hurricane Electric
         let conn = ClientUDPConnection::new_default(addr, Duration::from_secs(10));
-        let mut client = Client::new(conn);
+        let mut client = Client::new(Arc::new(conn), Duration::from_secs(10));
 
         // message
         let mut dns_query_message =
@@ -270,7 +270,7 @@ mod client_edns {
         // client
         let addr = IpAddr::V4(Ipv4Addr::new(8, 8, 8, 8));
         let conn = ClientUDPConnection::new_default(addr, Duration::from_secs(10));
-        let mut client = Client::new(conn);
+        let mut client = Client::new(Arc::new(conn), Duration::from_secs(10));
         // message
         let mut dns_query_message =
             DnsMessage::new_query_message(
@@ -1,9 +1,9 @@
 #[cfg(test)]
 mod client_test {
-    use std::{net::{IpAddr, Ipv4Addr, Ipv6Addr}, time::Duration};
+    use std::{net::{IpAddr, Ipv4Addr, Ipv6Addr}, sync::Arc, time::Duration};
     use dns_rust::{
         client::{
-            client_connection::ClientConnection,  udp_connection::ClientUDPConnection, Client}, domain_name::DomainName,
+            udp_connection::ClientUDPConnection, Client}, domain_name::DomainName,
             message::{rdata::{a_rdata::ARdata, Rdata, mx_rdata::MxRdata, ns_rdata::NsRdata}}};
 
 
@@ -15,7 +15,7 @@ mod client_test {
     async fn QTYPE_A_TEST_AUTH_ANSWER() {
         let addr = IpAddr::V4(Ipv4Addr::new(199, 43, 135, 53)); // a.iana-servers.net ip
         let conn = ClientUDPConnection::new_default(addr, Duration::from_secs(10));
-        let mut client = Client::new(conn);
+        let mut client = Client::new(Arc::new(conn), Duration::from_secs(10));
 
         let response = client.query(
             DomainName::new_from_string("example.com".to_string()), 
@@ -63,7 +63,7 @@ mod client_test {
     async fn QTYPE_A_TEST() {
         let addr = IpAddr::V4(Ipv4Addr::new(1, 1, 1, 1));
         let conn = ClientUDPConnection::new_default(addr, Duration::from_secs(10));
-    let mut client = Client::new(conn);
+    let mut client = Client::new(Arc::new(conn), Duration::from_secs(10));
 
         let response = client.query(
             DomainName::new_from_string("example.com".to_string()), 
@@ -114,7 +114,7 @@ mod client_test {
     async fn QTYPE_ANY_AUTH_ANSWER() {
         let addr = IpAddr::V4(Ipv4Addr::new(199, 43, 135, 53)); // a.iana-servers.net ip
         let conn = ClientUDPConnection::new_default(addr, Duration::from_secs(10));
-        let mut client = Client::new(conn);
+        let mut client = Client::new(Arc::new(conn), Duration::from_secs(10));
 
         let response = client.query(
             DomainName::new_from_string("example.com".to_string()), 
@@ -163,7 +163,7 @@ mod client_test {
     async fn QTYPE_ANY() {
         let addr = IpAddr::V4(Ipv4Addr::new(1, 1, 1, 1)); 
         let conn = ClientUDPConnection::new_default(addr, Duration::from_secs(10));
-        let mut client = Client::new(conn);
+        let mut client = Client::new(Arc::new(conn), Duration::from_secs(10));
 
         let response = client.query(
             DomainName::new_from_string("example.com".to_string()), 
@@ -199,7 +199,7 @@ mod client_test {
     async fn QTYPE_MX() {
         let addr = IpAddr::V4(Ipv4Addr::new(199, 43, 135, 53)); // a.iana-servers.net ip
         let conn = ClientUDPConnection::new_default(addr, Duration::from_secs(10));
-        let mut client = Client::new(conn);
+        let mut client = Client::new(Arc::new(conn), Duration::from_secs(10));
 
         let response = client.query(
             DomainName::new_from_string("example.com".to_string()), 
@@ -241,7 +241,7 @@ mod client_test {
     async fn QTYPE_NS() {
         let addr = IpAddr::V4(Ipv4Addr::new(199, 43, 135, 53)); // a.iana-servers.net ip
         let conn = ClientUDPConnection::new_default(addr, Duration::from_secs(10));
-        let mut client = Client::new(conn);
+        let mut client = Client::new(Arc::new(conn), Duration::from_secs(10));
 
         let response = client.query(
             DomainName::new_from_string("example.com".to_string()), 
@@ -303,7 +303,7 @@ mod client_test {
     async fn QTYPE_A_MISTYPED() {
         let addr = IpAddr::V4(Ipv4Addr::new(199, 43, 135, 53)); // a.iana-servers.net ip
         let conn = ClientUDPConnection::new_default(addr, Duration::from_secs(10));
-        let mut client = Client::new(conn);
+        let mut client = Client::new(Arc::new(conn), Duration::from_secs(10));
 
         let response = client.query(
             DomainName::new_from_string("notexists.example.com".to_string()), // this domain doesnt exists
@@ -353,7 +353,7 @@ mod client_test {
     async fn QTYPE_A_REFERRAL() {
         let addr = IpAddr::V4(Ipv4Addr::new(198, 41, 0, 4)); // a.root-servers.net ip
         let conn = ClientUDPConnection::new_default(addr, Duration::from_secs(10));
-        let mut client = Client::new(conn);
+        let mut client = Client::new(Arc::new(conn), Duration::from_secs(10));
 
         let response = client.query(
             DomainName::new_from_string("example.com".to_string()), 
@@ -513,7 +513,7 @@ mod client_test {
     async fn QTYPE_CNAME(){
         let addr = IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)); // localhost
         let conn = ClientUDPConnection::new_default(addr, Duration::from_secs(10));
-        let mut client = Client::new(conn);
+        let mut client = Client::new(Arc::new(conn), Duration::from_secs(10));
 
         let response = client.query(
             DomainName::new_from_string("test.uchile.cl".to_string()),
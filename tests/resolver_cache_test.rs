@@ -6,7 +6,7 @@ mod resolver_cache_test {
             lookup_response::LookupResponse
         },
         client::{
-            client_connection::ClientConnection, client_error::ClientError,
+            client_error::ClientError,
             tcp_connection::ClientTCPConnection, udp_connection::ClientUDPConnection
         },
         dns_cache::CacheKey,